@@ -0,0 +1,71 @@
+//! BAM and SAM reading and BAM writing.
+//!
+//! This is a minimal implementation for converting reads to and from FASTQ. Records are written as
+//! unaligned BAM (uBAM), i.e., BAM files with no reference sequences, and only the fields of
+//! unaligned reads are read.
+
+pub mod bgzf;
+pub mod flags;
+mod read_group;
+mod reader;
+mod record;
+mod sam_reader;
+mod writer;
+
+pub use self::{
+    read_group::{ParseReadGroupError, ReadGroup},
+    reader::Reader,
+    record::Record,
+    sam_reader::SamReader,
+    writer::Writer,
+};
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use flate2::bufread::MultiGzDecoder;
+
+/// Creates a BAM file.
+///
+/// The output is always BGZF compressed, regardless of its extension.
+pub fn create<P>(dst: P) -> io::Result<Writer<Box<dyn Write>>>
+where
+    P: AsRef<Path>,
+{
+    let file = File::create(dst)?;
+    Ok(Writer::new(Box::new(BufWriter::new(file))))
+}
+
+/// Opens a BAM file.
+pub fn open<P>(src: P) -> io::Result<Reader<Box<dyn Read>>>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(src)?;
+    let decoder = MultiGzDecoder::new(BufReader::new(file));
+    Ok(Reader::new(Box::new(BufReader::new(decoder))))
+}
+
+/// Opens a SAM file.
+///
+/// The input is decompressed if it ends in `.gz`.
+pub fn open_sam<P>(src: P) -> io::Result<SamReader<Box<dyn BufRead>>>
+where
+    P: AsRef<Path>,
+{
+    let path = src.as_ref();
+    let extension = path.extension();
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+
+    match extension.and_then(|ext| ext.to_str()) {
+        Some("gz") => {
+            let decoder = MultiGzDecoder::new(reader);
+            Ok(SamReader::new(Box::new(BufReader::new(decoder))))
+        }
+        _ => Ok(SamReader::new(Box::new(reader))),
+    }
+}
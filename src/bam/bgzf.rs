@@ -0,0 +1,571 @@
+//! BGZF, the blocked gzip format that BAM files are compressed with.
+
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression, Crc};
+
+// The maximum number of uncompressed bytes in a block. This leaves room for the compression
+// overhead of incompressible data in the 64 KiB limit of a block.
+const MAX_BLOCK_DATA_LEN: usize = 0xff00;
+
+const HEADER: [u8; 16] = [
+    0x1f, 0x8b, // ID1, ID2
+    0x08, // CM = DEFLATE
+    0x04, // FLG = FEXTRA
+    0x00, 0x00, 0x00, 0x00, // MTIME
+    0x00, // XFL
+    0xff, // OS = unknown
+    0x06, 0x00, // XLEN
+    b'B', b'C', // SI1, SI2
+    0x02, 0x00, // SLEN
+];
+
+const HEADER_LEN: usize = HEADER.len() + 2;
+const TRAILER_LEN: usize = 8;
+
+/// The empty block that marks the end of a BGZF file.
+pub const EOF_BLOCK: [u8; 28] = [
+    0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43, 0x02, 0x00,
+    0x1b, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+/// Returns whether the start of a file is a BGZF block header.
+///
+/// # Examples
+///
+/// ```
+/// use fq::bam::bgzf;
+///
+/// assert!(bgzf::is_bgzf(&bgzf::EOF_BLOCK));
+/// assert!(!bgzf::is_bgzf(b"@r0\nACGT\n+\nIIII\n"));
+/// ```
+pub fn is_bgzf(buf: &[u8]) -> bool {
+    buf.starts_with(&HEADER)
+}
+
+/// A BGZF reader.
+///
+/// Positions are virtual offsets, i.e., the compressed offset of a block in the upper 48 bits
+/// and the uncompressed offset in the block in the lower 16 bits.
+pub struct Reader<R> {
+    inner: R,
+    // The compressed offsets of the current and next blocks
+    block_position: u64,
+    next_block_position: u64,
+    buf: Vec<u8>,
+    pos: usize,
+    cdata: Vec<u8>,
+}
+
+impl<R> Reader<R>
+where
+    R: Read,
+{
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            block_position: 0,
+            next_block_position: 0,
+            buf: Vec::new(),
+            pos: 0,
+            cdata: Vec::new(),
+        }
+    }
+
+    /// Returns the virtual position of the next byte to be read.
+    pub fn virtual_position(&self) -> u64 {
+        (self.block_position << 16) | (self.pos as u64)
+    }
+
+    // Reads and decompresses the next block. This returns false at EOF.
+    fn read_block(&mut self) -> io::Result<bool> {
+        let mut header = [0; HEADER_LEN];
+
+        match self.inner.read(&mut header[..1])? {
+            0 => return Ok(false),
+            _ => self.inner.read_exact(&mut header[1..])?,
+        }
+
+        if !is_bgzf(&header) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid BGZF block header",
+            ));
+        }
+
+        let bsize = usize::from(u16::from_le_bytes([header[16], header[17]])) + 1;
+
+        let cdata_len = bsize
+            .checked_sub(HEADER_LEN + TRAILER_LEN)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid BGZF block size"))?;
+
+        self.cdata.resize(cdata_len, 0);
+        self.inner.read_exact(&mut self.cdata)?;
+
+        let mut trailer = [0; TRAILER_LEN];
+        self.inner.read_exact(&mut trailer)?;
+
+        self.buf.clear();
+        DeflateDecoder::new(&self.cdata[..]).read_to_end(&mut self.buf)?;
+
+        let mut crc = Crc::new();
+        crc.update(&self.buf);
+
+        let expected_crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+        let expected_len = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+
+        if crc.sum() != expected_crc || self.buf.len() as u32 != expected_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "BGZF block checksum mismatch",
+            ));
+        }
+
+        self.block_position = self.next_block_position;
+        self.next_block_position += bsize as u64;
+        self.pos = 0;
+
+        Ok(true)
+    }
+}
+
+impl<R> Reader<R>
+where
+    R: Read + Seek,
+{
+    /// Seeks to a virtual position.
+    pub fn seek(&mut self, virtual_position: u64) -> io::Result<()> {
+        let compressed_position = virtual_position >> 16;
+        let uncompressed_position = (virtual_position & 0xffff) as usize;
+
+        self.inner.seek(SeekFrom::Start(compressed_position))?;
+
+        self.buf.clear();
+        self.pos = 0;
+        self.block_position = compressed_position;
+        self.next_block_position = compressed_position;
+
+        self.read_block()?;
+
+        if uncompressed_position > self.buf.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid BGZF virtual position",
+            ));
+        }
+
+        self.pos = uncompressed_position;
+
+        Ok(())
+    }
+}
+
+impl<R> Read for Reader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let src = self.fill_buf()?;
+        let n = src.len().min(buf.len());
+        buf[..n].copy_from_slice(&src[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R> BufRead for Reader<R>
+where
+    R: Read,
+{
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        // Empty blocks, e.g., the EOF marker, are skipped.
+        while self.pos >= self.buf.len() {
+            if !self.read_block()? {
+                break;
+            }
+        }
+
+        Ok(&self.buf[self.pos.min(self.buf.len())..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.buf.len());
+    }
+}
+
+/// A map of uncompressed positions to virtual positions in a BGZF file.
+///
+/// Only block headers and trailers are read, without decompressing the blocks. Blocks are read
+/// as needed from the last mapped block, so mapping increasing positions reads each block once.
+pub struct VirtualPositions<R> {
+    inner: R,
+    // The compressed position and the uncompressed position of the start of the current block
+    block_position: u64,
+    data_position: u64,
+    // The compressed and uncompressed sizes of the current block, if read
+    block_sizes: Option<(u64, u64)>,
+}
+
+impl<R> VirtualPositions<R>
+where
+    R: Read + Seek,
+{
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            block_position: 0,
+            data_position: 0,
+            block_sizes: None,
+        }
+    }
+
+    /// Returns the virtual position of a position in the uncompressed data.
+    ///
+    /// This returns `None` if the position is at or past the end of the data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::{self, Cursor, Write};
+    /// use fq::bam::bgzf;
+    ///
+    /// let mut writer = bgzf::Writer::new(Vec::new());
+    /// writer.write_all(b"@r0\nACGT\n+\nIIII\n")?;
+    /// writer.finish()?;
+    ///
+    /// let mut positions = bgzf::VirtualPositions::new(Cursor::new(writer.get_ref()));
+    /// assert_eq!(positions.get(4)?, Some(4));
+    /// assert_eq!(positions.get(18)?, None);
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    pub fn get(&mut self, position: u64) -> io::Result<Option<u64>> {
+        if position < self.data_position {
+            self.block_position = 0;
+            self.data_position = 0;
+            self.block_sizes = None;
+        }
+
+        loop {
+            let (bsize, isize) = match self.block_sizes {
+                Some(sizes) => sizes,
+                None => match self.read_block_sizes()? {
+                    Some(sizes) => {
+                        self.block_sizes = Some(sizes);
+                        sizes
+                    }
+                    None => return Ok(None),
+                },
+            };
+
+            if position < self.data_position + isize {
+                let offset = position - self.data_position;
+                return Ok(Some((self.block_position << 16) | offset));
+            }
+
+            self.block_position += bsize;
+            self.data_position += isize;
+            self.block_sizes = None;
+        }
+    }
+
+    // Reads the compressed and uncompressed sizes of the block at the current block position.
+    // This returns `None` at EOF.
+    fn read_block_sizes(&mut self) -> io::Result<Option<(u64, u64)>> {
+        self.inner.seek(SeekFrom::Start(self.block_position))?;
+
+        let mut header = [0; HEADER_LEN];
+
+        match self.inner.read(&mut header[..1])? {
+            0 => return Ok(None),
+            _ => self.inner.read_exact(&mut header[1..])?,
+        }
+
+        if !is_bgzf(&header) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid BGZF block header",
+            ));
+        }
+
+        let bsize = u64::from(u16::from_le_bytes([header[16], header[17]])) + 1;
+
+        // ISIZE is the last field of the trailer.
+        let isize_position = (self.block_position + bsize)
+            .checked_sub(4)
+            .filter(|&n| n >= self.block_position + HEADER_LEN as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid BGZF block size"))?;
+
+        self.inner.seek(SeekFrom::Start(isize_position))?;
+
+        let mut buf = [0; 4];
+        self.inner.read_exact(&mut buf)?;
+
+        Ok(Some((bsize, u64::from(u32::from_le_bytes(buf)))))
+    }
+}
+
+/// A BGZF writer.
+///
+/// Data is buffered and compressed into independent gzip members of at most 64 KiB each. The
+/// writer must be finished to write the end-of-file marker. This is also attempted on drop,
+/// ignoring errors.
+pub struct Writer<W>
+where
+    W: Write,
+{
+    inner: W,
+    buf: Vec<u8>,
+    is_finished: bool,
+}
+
+impl<W> Writer<W>
+where
+    W: Write,
+{
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            buf: Vec::with_capacity(MAX_BLOCK_DATA_LEN),
+            is_finished: false,
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Flushes the buffered data and writes the end-of-file marker.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Write};
+    ///
+    /// use flate2::read::MultiGzDecoder;
+    /// use fq::bam::bgzf;
+    ///
+    /// let mut writer = bgzf::Writer::new(Vec::new());
+    /// writer.write_all(b"fq")?;
+    /// writer.finish()?;
+    ///
+    /// let data = writer.get_ref();
+    /// assert!(data.ends_with(&bgzf::EOF_BLOCK));
+    ///
+    /// let mut buf = Vec::new();
+    /// MultiGzDecoder::new(&data[..]).read_to_end(&mut buf)?;
+    /// assert_eq!(buf, b"fq");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.is_finished {
+            return Ok(());
+        }
+
+        self.flush_block()?;
+        self.inner.write_all(&EOF_BLOCK)?;
+        self.inner.flush()?;
+
+        self.is_finished = true;
+
+        Ok(())
+    }
+
+    fn flush_block(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+
+        let block = compress_block(&self.buf)?;
+        self.buf.clear();
+
+        self.inner.write_all(&block)
+    }
+}
+
+impl<W> Write for Writer<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.buf.len() >= MAX_BLOCK_DATA_LEN {
+            self.flush_block()?;
+        }
+
+        let n = buf.len().min(MAX_BLOCK_DATA_LEN - self.buf.len());
+        self.buf.extend_from_slice(&buf[..n]);
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flush_block()?;
+        self.inner.flush()
+    }
+}
+
+impl<W> Drop for Writer<W>
+where
+    W: Write,
+{
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+fn compress_block(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut block = Vec::with_capacity(HEADER_LEN + data.len() + TRAILER_LEN);
+    block.extend_from_slice(&HEADER);
+    // BSIZE is set after compression.
+    block.extend_from_slice(&[0, 0]);
+
+    let mut encoder = DeflateEncoder::new(block, Compression::default());
+    encoder.write_all(data)?;
+    let mut block = encoder.finish()?;
+
+    let mut crc = Crc::new();
+    crc.update(data);
+
+    block.extend_from_slice(&crc.sum().to_le_bytes());
+    block.extend_from_slice(&(data.len() as u32).to_le_bytes());
+
+    let bsize = u16::try_from(block.len() - 1)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "BGZF block is too large"))?;
+    block[HEADER.len()..HEADER_LEN].copy_from_slice(&bsize.to_le_bytes());
+
+    Ok(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::MultiGzDecoder;
+
+    use super::*;
+
+    #[test]
+    fn test_write() -> io::Result<()> {
+        let data: Vec<u8> = (0..MAX_BLOCK_DATA_LEN * 2 + 8)
+            .map(|i| (i % 251) as u8)
+            .collect();
+
+        let mut writer = Writer::new(Vec::new());
+        writer.write_all(&data)?;
+        writer.finish()?;
+
+        let dst = writer.get_ref();
+
+        // Each block is an independent gzip member with its size in the extra field.
+        let mut blocks = 0;
+        let mut i = 0;
+
+        while i < dst.len() {
+            assert_eq!(&dst[i..i + HEADER.len()], &HEADER);
+            let bsize = u16::from_le_bytes([dst[i + 16], dst[i + 17]]);
+            i += usize::from(bsize) + 1;
+            blocks += 1;
+        }
+
+        assert_eq!(i, dst.len());
+        // 3 data blocks and the EOF marker
+        assert_eq!(blocks, 4);
+
+        let mut buf = Vec::new();
+        MultiGzDecoder::new(&dst[..]).read_to_end(&mut buf)?;
+        assert_eq!(buf, data);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read() -> io::Result<()> {
+        let data: Vec<u8> = (0..MAX_BLOCK_DATA_LEN + 8)
+            .map(|i| (i % 251) as u8)
+            .collect();
+
+        let mut writer = Writer::new(Vec::new());
+        writer.write_all(&data)?;
+        writer.finish()?;
+
+        let mut reader = Reader::new(&writer.get_ref()[..]);
+        assert_eq!(reader.virtual_position(), 0);
+
+        let mut buf = vec![0; 8];
+        reader.read_exact(&mut buf)?;
+        assert_eq!(buf, &data[..8]);
+        assert_eq!(reader.virtual_position(), 8);
+
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+        assert_eq!(buf, &data[8..]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_virtual_positions() -> io::Result<()> {
+        use std::io::Cursor;
+
+        let data: Vec<u8> = (0..MAX_BLOCK_DATA_LEN + 8)
+            .map(|i| (i % 251) as u8)
+            .collect();
+
+        let mut writer = Writer::new(Vec::new());
+        writer.write_all(&data)?;
+        writer.finish()?;
+
+        let mut reader = Reader::new(&writer.get_ref()[..]);
+        let mut buf = vec![0; MAX_BLOCK_DATA_LEN + 2];
+        reader.read_exact(&mut buf)?;
+
+        let mut positions = VirtualPositions::new(Cursor::new(writer.get_ref()));
+
+        let position = (MAX_BLOCK_DATA_LEN + 2) as u64;
+        assert_eq!(positions.get(position)?, Some(reader.virtual_position()));
+        assert_eq!(positions.get(8)?, Some(8));
+        assert_eq!(positions.get(data.len() as u64)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_seek() -> io::Result<()> {
+        use std::io::Cursor;
+
+        let data: Vec<u8> = (0..MAX_BLOCK_DATA_LEN + 8)
+            .map(|i| (i % 251) as u8)
+            .collect();
+
+        let mut writer = Writer::new(Vec::new());
+        writer.write_all(&data)?;
+        writer.finish()?;
+
+        let mut reader = Reader::new(Cursor::new(writer.get_ref()));
+
+        // Read through the first block to find the start of the second.
+        let mut buf = vec![0; MAX_BLOCK_DATA_LEN + 2];
+        reader.read_exact(&mut buf)?;
+        let virtual_position = reader.virtual_position();
+        assert_eq!(virtual_position & 0xffff, 2);
+
+        reader.seek(0)?;
+        let mut buf = vec![0; 4];
+        reader.read_exact(&mut buf)?;
+        assert_eq!(buf, &data[..4]);
+
+        reader.seek(virtual_position)?;
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+        assert_eq!(buf, &data[MAX_BLOCK_DATA_LEN + 2..]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_finish_with_no_data() -> io::Result<()> {
+        let mut writer = Writer::new(Vec::new());
+        writer.finish()?;
+        assert_eq!(writer.get_ref(), &EOF_BLOCK);
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+//! BAM record flags.
+
+/// The read is one of multiple segments, e.g., paired.
+pub const SEGMENTED: u16 = 0x01;
+/// The read is unmapped.
+pub const UNMAPPED: u16 = 0x04;
+/// The mate of the read is unmapped.
+pub const MATE_UNMAPPED: u16 = 0x08;
+/// The sequence is reverse complemented, i.e., the read is mapped to the reverse strand.
+pub const REVERSE_COMPLEMENTED: u16 = 0x10;
+/// The read is the first segment, e.g., read 1.
+pub const FIRST_SEGMENT: u16 = 0x40;
+/// The read is the last segment, e.g., read 2.
+pub const LAST_SEGMENT: u16 = 0x80;
+/// The record is a secondary alignment.
+pub const SECONDARY: u16 = 0x100;
+/// The record is a supplementary alignment.
+pub const SUPPLEMENTARY: u16 = 0x800;
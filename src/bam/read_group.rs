@@ -0,0 +1,161 @@
+use std::{error, fmt, str::FromStr};
+
+const PREFIX: &str = "@RG";
+const ID_TAG: &str = "ID";
+
+/// A read group header record.
+///
+/// The fields are kept in order, with the ID first.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReadGroup {
+    id: String,
+    fields: Vec<(String, String)>,
+}
+
+impl ReadGroup {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the fields other than the ID.
+    pub fn fields(&self) -> &[(String, String)] {
+        &self.fields
+    }
+}
+
+impl fmt::Display for ReadGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\t{}:{}", PREFIX, ID_TAG, self.id)?;
+
+        for (tag, value) in &self.fields {
+            write!(f, "\t{}:{}", tag, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseReadGroupError {
+    InvalidField(String),
+    MissingId,
+    DuplicateId,
+}
+
+impl error::Error for ParseReadGroupError {}
+
+impl fmt::Display for ParseReadGroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidField(s) => write!(
+                f,
+                "invalid read group field: expected TAG:VALUE, got '{}'",
+                s
+            ),
+            Self::MissingId => f.write_str("missing read group ID"),
+            Self::DuplicateId => f.write_str("duplicate read group ID"),
+        }
+    }
+}
+
+impl FromStr for ReadGroup {
+    type Err = ParseReadGroupError;
+
+    /// Parses a read group from a SAM header line.
+    ///
+    /// Fields are separated by tabs or escaped tabs (`\t`), and the leading `@RG` is optional.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::bam::ReadGroup;
+    ///
+    /// let read_group: ReadGroup = r"@RG\tID:rg0\tSM:sample0".parse()?;
+    /// assert_eq!(read_group.id(), "rg0");
+    /// assert_eq!(read_group.to_string(), "@RG\tID:rg0\tSM:sample0");
+    ///
+    /// let read_group: ReadGroup = "ID:rg0\tPL:ILLUMINA".parse()?;
+    /// assert_eq!(read_group.to_string(), "@RG\tID:rg0\tPL:ILLUMINA");
+    /// # Ok::<(), fq::bam::ParseReadGroupError>(())
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.replace("\\t", "\t");
+
+        let mut id = None;
+        let mut fields = Vec::new();
+
+        for (i, raw_field) in s.split('\t').enumerate() {
+            if i == 0 && raw_field == PREFIX {
+                continue;
+            }
+
+            let (tag, value) = raw_field
+                .split_once(':')
+                .filter(|(tag, value)| is_valid_tag(tag) && !value.is_empty())
+                .ok_or_else(|| ParseReadGroupError::InvalidField(raw_field.into()))?;
+
+            if tag == ID_TAG {
+                if id.is_some() {
+                    return Err(ParseReadGroupError::DuplicateId);
+                }
+
+                id = Some(value.into());
+            } else {
+                fields.push((tag.into(), value.into()));
+            }
+        }
+
+        let id = id.ok_or(ParseReadGroupError::MissingId)?;
+
+        Ok(Self { id, fields })
+    }
+}
+
+fn is_valid_tag(tag: &str) -> bool {
+    let b = tag.as_bytes();
+    b.len() == 2 && b[0].is_ascii_alphabetic() && b[1].is_ascii_alphanumeric()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            "@RG\tSM:sample0\tID:rg0".parse(),
+            Ok(ReadGroup {
+                id: String::from("rg0"),
+                fields: vec![(String::from("SM"), String::from("sample0"))],
+            })
+        );
+
+        assert_eq!(
+            "ID:rg0\tDS:a:b"
+                .parse::<ReadGroup>()
+                .map(|rg| rg.to_string()),
+            Ok(String::from("@RG\tID:rg0\tDS:a:b"))
+        );
+
+        assert_eq!(
+            "".parse::<ReadGroup>(),
+            Err(ParseReadGroupError::InvalidField(String::new()))
+        );
+        assert_eq!(
+            "@RG\tSM:sample0".parse::<ReadGroup>(),
+            Err(ParseReadGroupError::MissingId)
+        );
+        assert_eq!(
+            "ID:rg0\tID:rg1".parse::<ReadGroup>(),
+            Err(ParseReadGroupError::DuplicateId)
+        );
+        assert_eq!(
+            "ID:rg0\tsample0".parse::<ReadGroup>(),
+            Err(ParseReadGroupError::InvalidField(String::from("sample0")))
+        );
+        assert_eq!(
+            "ID:".parse::<ReadGroup>(),
+            Err(ParseReadGroupError::InvalidField(String::from("ID:")))
+        );
+    }
+}
@@ -0,0 +1,390 @@
+use std::io::{self, Read};
+
+use super::{writer::MAGIC_NUMBER, Record};
+use crate::{metrics::Metrics, quality::Encoding};
+
+const QUALITY_OFFSET: u8 = Encoding::Phred33.offset();
+const MAX_QUALITY_SCORE: u8 = b'~' - QUALITY_OFFSET;
+const MISSING_QUALITY_SCORE: u8 = 0xff;
+
+// The length of the fixed fields of a record, excluding the block size.
+const FIXED_FIELDS_LEN: usize = 32;
+
+const BASES: &[u8] = b"=ACMGRSVTWYHKDBN";
+
+/// A BAM reader.
+///
+/// The source must already be decompressed, e.g., by a `MultiGzDecoder`, which can read BGZF.
+/// Only the fields of unaligned reads and the read group (`RG`) are decoded. Alignment fields and
+/// other auxiliary data are skipped.
+pub struct Reader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    metrics: Option<Metrics>,
+}
+
+impl<R> Reader<R>
+where
+    R: Read,
+{
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buf: Vec::new(),
+            metrics: None,
+        }
+    }
+
+    /// Sets the metrics to update on each record read.
+    pub fn set_metrics(&mut self, metrics: Metrics) {
+        self.metrics = Some(metrics);
+    }
+
+    /// Reads the BAM header and returns its SAM header text.
+    ///
+    /// This must be called before reading records. Reference sequences are skipped.
+    pub fn read_header(&mut self) -> io::Result<String> {
+        let mut magic_number = [0; 4];
+        self.inner.read_exact(&mut magic_number)?;
+
+        if magic_number != MAGIC_NUMBER {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid BAM header: missing magic number",
+            ));
+        }
+
+        let l_text = read_len(&mut self.inner)?;
+        let mut text = vec![0; l_text];
+        self.inner.read_exact(&mut text)?;
+
+        // The text may be NUL-padded.
+        if let Some(i) = text.iter().position(|&b| b == 0) {
+            text.truncate(i);
+        }
+
+        let text = String::from_utf8(text).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid BAM header: invalid text",
+            )
+        })?;
+
+        let n_ref = read_len(&mut self.inner)?;
+
+        for _ in 0..n_ref {
+            let l_name = read_len(&mut self.inner)?;
+            // name and l_ref
+            skip(&mut self.inner, l_name + 4)?;
+        }
+
+        Ok(text)
+    }
+
+    /// Reads a single record.
+    ///
+    /// This returns the number of bytes read, or 0 at EOF.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use flate2::read::MultiGzDecoder;
+    /// use fq::bam::{self, flags, Record};
+    ///
+    /// let mut writer = bam::Writer::new(Vec::new());
+    /// writer.write_header("@HD\tVN:1.6\n")?;
+    /// writer.write_record(&Record::new("r0", flags::UNMAPPED, "ACGT", "IIII"))?;
+    /// writer.finish()?;
+    ///
+    /// let data = writer.get_ref();
+    /// let mut reader = bam::Reader::new(MultiGzDecoder::new(&data[..]));
+    /// assert_eq!(reader.read_header()?, "@HD\tVN:1.6\n");
+    ///
+    /// let mut record = Record::default();
+    /// reader.read_record(&mut record)?;
+    /// assert_eq!(record, Record::new("r0", flags::UNMAPPED, "ACGT", "IIII"));
+    ///
+    /// assert_eq!(reader.read_record(&mut record)?, 0);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn read_record(&mut self, record: &mut Record) -> io::Result<usize> {
+        let block_size = match read_block_size(&mut self.inner)? {
+            Some(n) => n,
+            None => return Ok(0),
+        };
+
+        self.buf.resize(block_size, 0);
+        self.inner.read_exact(&mut self.buf)?;
+
+        decode_record(&self.buf, record)?;
+
+        let len = block_size + 4;
+
+        if let Some(metrics) = &self.metrics {
+            metrics.add_records_read(1);
+            metrics.add_bytes_read(len as u64);
+        }
+
+        Ok(len)
+    }
+}
+
+// Reads a block size, or `None` at EOF.
+fn read_block_size<R>(reader: &mut R) -> io::Result<Option<usize>>
+where
+    R: Read,
+{
+    let mut buf = [0; 4];
+    let mut n = 0;
+
+    while n < buf.len() {
+        match reader.read(&mut buf[n..]) {
+            Ok(0) if n == 0 => return Ok(None),
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            Ok(m) => n += m,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    to_len(i32::from_le_bytes(buf)).map(Some)
+}
+
+fn read_len<R>(reader: &mut R) -> io::Result<usize>
+where
+    R: Read,
+{
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    to_len(i32::from_le_bytes(buf))
+}
+
+fn to_len(n: i32) -> io::Result<usize> {
+    usize::try_from(n).map_err(|_| invalid_data("invalid length"))
+}
+
+fn skip<R>(reader: &mut R, len: usize) -> io::Result<()>
+where
+    R: Read,
+{
+    let n = io::copy(&mut reader.take(len as u64), &mut io::sink())?;
+
+    if n < len as u64 {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+    }
+
+    Ok(())
+}
+
+fn decode_record(buf: &[u8], record: &mut Record) -> io::Result<()> {
+    record.clear();
+
+    if buf.len() < FIXED_FIELDS_LEN {
+        return Err(invalid_data(
+            "invalid record: unexpected end of fixed fields",
+        ));
+    }
+
+    let l_read_name = usize::from(buf[8]);
+    let n_cigar_op = usize::from(u16::from_le_bytes([buf[12], buf[13]]));
+    let flags = u16::from_le_bytes([buf[14], buf[15]]);
+    let l_seq = u32::from_le_bytes([buf[16], buf[17], buf[18], buf[19]]) as usize;
+
+    let mut rest = &buf[FIXED_FIELDS_LEN..];
+
+    let name = take(&mut rest, l_read_name)?;
+    let name = name.strip_suffix(&[0]).unwrap_or(name);
+    record.name_mut().extend_from_slice(name);
+
+    *record.flags_mut() = flags;
+
+    take(&mut rest, n_cigar_op * 4)?;
+
+    let sequence = take(&mut rest, l_seq / 2 + l_seq % 2)?;
+
+    for i in 0..l_seq {
+        let code = (sequence[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+        record.sequence_mut().push(BASES[usize::from(code)]);
+    }
+
+    let quality_scores = take(&mut rest, l_seq)?;
+
+    if quality_scores.first() != Some(&MISSING_QUALITY_SCORE) {
+        for &score in quality_scores {
+            if score > MAX_QUALITY_SCORE {
+                return Err(invalid_data("invalid record: invalid quality score"));
+            }
+
+            record.quality_scores_mut().push(score + QUALITY_OFFSET);
+        }
+    }
+
+    decode_data(rest, record)
+}
+
+fn decode_data(mut buf: &[u8], record: &mut Record) -> io::Result<()> {
+    while !buf.is_empty() {
+        let field = take(&mut buf, 3)?;
+        let (tag, ty) = ([field[0], field[1]], field[2]);
+
+        match ty {
+            b'A' | b'c' | b'C' => {
+                take(&mut buf, 1)?;
+            }
+            b's' | b'S' => {
+                take(&mut buf, 2)?;
+            }
+            b'i' | b'I' | b'f' => {
+                take(&mut buf, 4)?;
+            }
+            b'Z' | b'H' => {
+                let len = buf
+                    .iter()
+                    .position(|&b| b == 0)
+                    .ok_or_else(|| invalid_data("invalid record: unterminated string"))?;
+
+                let value = take(&mut buf, len + 1)?;
+
+                if &tag == b"RG" && ty == b'Z' {
+                    *record.read_group_mut() = Some(value[..len].to_vec());
+                }
+            }
+            b'B' => {
+                let header = take(&mut buf, 5)?;
+                let n = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+
+                let size = match header[0] {
+                    b'c' | b'C' => 1,
+                    b's' | b'S' => 2,
+                    b'i' | b'I' | b'f' => 4,
+                    _ => return Err(invalid_data("invalid record: invalid array subtype")),
+                };
+
+                take(&mut buf, n * size)?;
+            }
+            _ => return Err(invalid_data("invalid record: invalid data type")),
+        }
+    }
+
+    Ok(())
+}
+
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if buf.len() < len {
+        return Err(invalid_data("invalid record: unexpected end of data"));
+    }
+
+    let (head, tail) = buf.split_at(len);
+    *buf = tail;
+
+    Ok(head)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bam::flags;
+
+    #[test]
+    fn test_read_header() -> io::Result<()> {
+        let data = [
+            &b"BAM\x01"[..],
+            &12i32.to_le_bytes(),
+            b"@HD\tVN:1.6\n\x00",
+            // n_ref
+            &1i32.to_le_bytes(),
+            &4i32.to_le_bytes(),
+            b"sq0\x00",
+            &8u32.to_le_bytes(),
+        ]
+        .concat();
+
+        let mut reader = Reader::new(&data[..]);
+        assert_eq!(reader.read_header()?, "@HD\tVN:1.6\n");
+
+        let mut record = Record::default();
+        assert_eq!(reader.read_record(&mut record)?, 0);
+
+        let mut reader = Reader::new(&b"BAM\x02"[..]);
+        assert!(reader.read_header().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_record() -> io::Result<()> {
+        let data = [
+            &0i32.to_le_bytes()[..],
+            &8i32.to_le_bytes(),
+            &[3, 60],
+            &4681u16.to_le_bytes(),
+            // n_cigar_op
+            &1u16.to_le_bytes(),
+            &(flags::SEGMENTED | flags::REVERSE_COMPLEMENTED | flags::LAST_SEGMENT).to_le_bytes(),
+            &5u32.to_le_bytes(),
+            &0i32.to_le_bytes(),
+            &100i32.to_le_bytes(),
+            &(-95i32).to_le_bytes(),
+            b"r0\x00",
+            // 5M
+            &0x50u32.to_le_bytes(),
+            &[0x12, 0x48, 0xf0],
+            &[0, 10, 20, 30, 40],
+            b"NMi",
+            &0i32.to_le_bytes(),
+            b"RGZrg0\x00",
+        ]
+        .concat();
+
+        let mut record = Record::default();
+        decode_record(&data, &mut record)?;
+
+        let mut expected = Record::new(
+            "r0",
+            flags::SEGMENTED | flags::REVERSE_COMPLEMENTED | flags::LAST_SEGMENT,
+            "ACGTN",
+            "!+5?I",
+        );
+        *expected.read_group_mut() = Some(b"rg0".to_vec());
+
+        assert_eq!(record, expected);
+
+        assert!(decode_record(&data[..40], &mut record).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_record_with_missing_quality_scores() -> io::Result<()> {
+        let data = [
+            &(-1i32).to_le_bytes()[..],
+            &(-1i32).to_le_bytes(),
+            &[3, 0],
+            &4680u16.to_le_bytes(),
+            &0u16.to_le_bytes(),
+            &flags::UNMAPPED.to_le_bytes(),
+            &2u32.to_le_bytes(),
+            &(-1i32).to_le_bytes(),
+            &(-1i32).to_le_bytes(),
+            &0i32.to_le_bytes(),
+            b"r0\x00",
+            &[0x12],
+            &[0xff, 0xff],
+        ]
+        .concat();
+
+        let mut record = Record::default();
+        decode_record(&data, &mut record)?;
+
+        assert_eq!(record.sequence(), b"AC");
+        assert!(record.quality_scores().is_empty());
+
+        Ok(())
+    }
+}
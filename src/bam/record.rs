@@ -0,0 +1,104 @@
+use super::flags;
+
+/// A BAM record.
+///
+/// Only the fields of unaligned reads are kept. Quality scores are Phred+33 characters, as in
+/// FASTQ, and are converted to and from raw scores when written and read.
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+pub struct Record {
+    name: Vec<u8>,
+    flags: u16,
+    sequence: Vec<u8>,
+    quality_scores: Vec<u8>,
+    read_group: Option<Vec<u8>>,
+}
+
+impl Record {
+    pub fn new<S, T, U>(name: S, flags: u16, sequence: T, quality_scores: U) -> Self
+    where
+        S: Into<Vec<u8>>,
+        T: Into<Vec<u8>>,
+        U: Into<Vec<u8>>,
+    {
+        Self {
+            name: name.into(),
+            flags,
+            sequence: sequence.into(),
+            quality_scores: quality_scores.into(),
+            read_group: None,
+        }
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    pub fn name_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.name
+    }
+
+    pub fn flags(&self) -> u16 {
+        self.flags
+    }
+
+    pub fn flags_mut(&mut self) -> &mut u16 {
+        &mut self.flags
+    }
+
+    /// Returns whether the record is read 1 of a pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::bam::{flags, Record};
+    ///
+    /// let record = Record::new("r0", flags::SEGMENTED | flags::FIRST_SEGMENT, "ACGT", "IIII");
+    /// assert!(record.is_read_1());
+    /// assert!(!record.is_read_2());
+    /// ```
+    pub fn is_read_1(&self) -> bool {
+        self.is_segmented() && self.flags & flags::FIRST_SEGMENT != 0
+    }
+
+    /// Returns whether the record is read 2 of a pair.
+    pub fn is_read_2(&self) -> bool {
+        self.is_segmented() && self.flags & flags::LAST_SEGMENT != 0
+    }
+
+    fn is_segmented(&self) -> bool {
+        self.flags & flags::SEGMENTED != 0
+    }
+
+    pub fn sequence(&self) -> &[u8] {
+        &self.sequence
+    }
+
+    pub fn sequence_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.sequence
+    }
+
+    pub fn quality_scores(&self) -> &[u8] {
+        &self.quality_scores
+    }
+
+    pub fn quality_scores_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.quality_scores
+    }
+
+    /// Returns the read group ID (`RG` tag).
+    pub fn read_group(&self) -> Option<&[u8]> {
+        self.read_group.as_deref()
+    }
+
+    pub fn read_group_mut(&mut self) -> &mut Option<Vec<u8>> {
+        &mut self.read_group
+    }
+
+    pub fn clear(&mut self) {
+        self.name.clear();
+        self.flags = 0;
+        self.sequence.clear();
+        self.quality_scores.clear();
+        self.read_group = None;
+    }
+}
@@ -0,0 +1,161 @@
+use std::io::{self, BufRead};
+
+use super::Record;
+use crate::metrics::Metrics;
+
+const HEADER_PREFIX: u8 = b'@';
+const LINE_FEED: u8 = b'\n';
+const CARRIAGE_RETURN: u8 = b'\r';
+const MISSING: &[u8] = b"*";
+
+// QNAME, FLAG, RNAME, POS, MAPQ, CIGAR, RNEXT, PNEXT, TLEN, SEQ, QUAL
+const MANDATORY_FIELD_COUNT: usize = 11;
+
+/// A SAM reader.
+///
+/// As with the BAM reader, only the fields of unaligned reads are kept.
+pub struct SamReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    metrics: Option<Metrics>,
+}
+
+impl<R> SamReader<R>
+where
+    R: BufRead,
+{
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buf: Vec::new(),
+            metrics: None,
+        }
+    }
+
+    /// Sets the metrics to update on each record read.
+    pub fn set_metrics(&mut self, metrics: Metrics) {
+        self.metrics = Some(metrics);
+    }
+
+    /// Reads the header lines, i.e., the leading lines that start with `@`.
+    ///
+    /// This must be called before reading records.
+    pub fn read_header(&mut self) -> io::Result<String> {
+        let mut text = Vec::new();
+
+        while self.inner.fill_buf()?.first() == Some(&HEADER_PREFIX) {
+            self.inner.read_until(LINE_FEED, &mut text)?;
+        }
+
+        String::from_utf8(text)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid SAM header"))
+    }
+
+    /// Reads a single record.
+    ///
+    /// This returns the number of bytes read, or 0 at EOF.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::bam::{flags, Record, SamReader};
+    ///
+    /// let data = b"@HD\tVN:1.6\nr0\t4\t*\t0\t0\t*\t*\t0\t0\tACGT\tIIII\n";
+    /// let mut reader = SamReader::new(&data[..]);
+    /// reader.read_header()?;
+    ///
+    /// let mut record = Record::default();
+    /// reader.read_record(&mut record)?;
+    /// assert_eq!(record, Record::new("r0", flags::UNMAPPED, "ACGT", "IIII"));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn read_record(&mut self, record: &mut Record) -> io::Result<usize> {
+        record.clear();
+
+        self.buf.clear();
+        let len = self.inner.read_until(LINE_FEED, &mut self.buf)?;
+
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let line = self.buf.strip_suffix(&[LINE_FEED]).unwrap_or(&self.buf);
+        let line = line.strip_suffix(&[CARRIAGE_RETURN]).unwrap_or(line);
+
+        parse_record(line, record)?;
+
+        if let Some(metrics) = &self.metrics {
+            metrics.add_records_read(1);
+            metrics.add_bytes_read(len as u64);
+        }
+
+        Ok(len)
+    }
+}
+
+fn parse_record(line: &[u8], record: &mut Record) -> io::Result<()> {
+    let fields: Vec<_> = line.split(|&b| b == b'\t').collect();
+
+    if fields.len() < MANDATORY_FIELD_COUNT {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "invalid SAM record: expected at least {} fields, got {}",
+                MANDATORY_FIELD_COUNT,
+                fields.len()
+            ),
+        ));
+    }
+
+    let flags = std::str::from_utf8(fields[1])
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid SAM record flags"))?;
+
+    record.name_mut().extend_from_slice(fields[0]);
+    *record.flags_mut() = flags;
+
+    if fields[9] != MISSING {
+        record.sequence_mut().extend_from_slice(fields[9]);
+    }
+
+    if fields[10] != MISSING {
+        record.quality_scores_mut().extend_from_slice(fields[10]);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bam::flags;
+
+    #[test]
+    fn test_read_header() -> io::Result<()> {
+        let data = b"@HD\tVN:1.6\n@RG\tID:rg0\nr0\t4\t*\t0\t0\t*\t*\t0\t0\t*\t*\n";
+        let mut reader = SamReader::new(&data[..]);
+
+        assert_eq!(reader.read_header()?, "@HD\tVN:1.6\n@RG\tID:rg0\n");
+
+        let mut record = Record::default();
+        reader.read_record(&mut record)?;
+        assert_eq!(record, Record::new("r0", flags::UNMAPPED, "", ""));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_record() -> io::Result<()> {
+        let data = b"r0\t83\tsq0\t8\t60\t4M\t=\t1\t-11\tACGT\tIII5\tRG:Z:rg0\r\nr1\n";
+        let mut reader = SamReader::new(&data[..]);
+
+        let mut record = Record::default();
+        reader.read_record(&mut record)?;
+        assert_eq!(record, Record::new("r0", 83, "ACGT", "III5"));
+
+        assert!(reader.read_record(&mut record).is_err());
+
+        Ok(())
+    }
+}
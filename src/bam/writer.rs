@@ -0,0 +1,302 @@
+use std::io::{self, Write};
+
+use super::{bgzf, Record};
+use crate::{metrics::Metrics, quality::Encoding};
+
+pub(super) const MAGIC_NUMBER: &[u8] = b"BAM\x01";
+
+const QUALITY_OFFSET: u8 = Encoding::Phred33.offset();
+const MISSING_QUALITY_SCORE: u8 = 0xff;
+
+// Unaligned records have no reference sequence or position.
+const UNMAPPED_REFERENCE_SEQUENCE_ID: i32 = -1;
+const UNMAPPED_POSITION: i32 = -1;
+const UNMAPPED_MAPPING_QUALITY: u8 = 0;
+// reg2bin(-1, 0)
+const UNMAPPED_BIN: u16 = 4680;
+
+const MAX_NAME_LEN: usize = 254;
+
+const BASES: &[u8] = b"=ACMGRSVTWYHKDBN";
+
+/// A BAM writer.
+///
+/// The header has no reference sequences, so records are written unaligned.
+pub struct Writer<W>
+where
+    W: Write,
+{
+    inner: bgzf::Writer<W>,
+    buf: Vec<u8>,
+    metrics: Option<Metrics>,
+}
+
+impl<W> Writer<W>
+where
+    W: Write,
+{
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner: bgzf::Writer::new(inner),
+            buf: Vec::new(),
+            metrics: None,
+        }
+    }
+
+    /// Sets the metrics to update on each record written.
+    pub fn set_metrics(&mut self, metrics: Metrics) {
+        self.metrics = Some(metrics);
+    }
+
+    pub fn get_ref(&self) -> &W {
+        self.inner.get_ref()
+    }
+
+    /// Writes the BAM header with the given SAM header text.
+    pub fn write_header(&mut self, text: &str) -> io::Result<()> {
+        let l_text = i32::try_from(text.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "header is too large"))?;
+
+        self.inner.write_all(MAGIC_NUMBER)?;
+        self.inner.write_all(&l_text.to_le_bytes())?;
+        self.inner.write_all(text.as_bytes())?;
+
+        // n_ref
+        self.inner.write_all(&0i32.to_le_bytes())?;
+
+        Ok(())
+    }
+
+    /// Writes an unaligned record.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::bam::{flags, Record, Writer};
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.write_header("@HD\tVN:1.6\n")?;
+    ///
+    /// let record = Record::new("r0", flags::UNMAPPED, "ACGT", "IIII");
+    /// writer.write_record(&record)?;
+    ///
+    /// writer.finish()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_record(&mut self, record: &Record) -> io::Result<()> {
+        encode_record(&mut self.buf, record)?;
+
+        let block_size = i32::try_from(self.buf.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "record is too large"))?;
+
+        self.inner.write_all(&block_size.to_le_bytes())?;
+        self.inner.write_all(&self.buf)?;
+
+        if let Some(metrics) = &self.metrics {
+            metrics.add_records_written(1);
+            metrics.add_bytes_written((self.buf.len() + 4) as u64);
+        }
+
+        Ok(())
+    }
+
+    /// Flushes the buffered records and writes the end-of-file marker.
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.inner.finish()
+    }
+}
+
+// Encodes a record without its block size.
+fn encode_record(buf: &mut Vec<u8>, record: &Record) -> io::Result<()> {
+    let name = record.name();
+
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "invalid read name length: expected 1..={}, got {}",
+                MAX_NAME_LEN,
+                name.len()
+            ),
+        ));
+    }
+
+    let sequence = record.sequence();
+    let quality_scores = record.quality_scores();
+
+    if !quality_scores.is_empty() && quality_scores.len() != sequence.len() {
+        return Err(invalid_record(
+            name,
+            "sequence and quality scores lengths mismatch",
+        ));
+    }
+
+    let l_seq =
+        u32::try_from(sequence.len()).map_err(|_| invalid_record(name, "sequence is too long"))?;
+
+    buf.clear();
+
+    buf.extend_from_slice(&UNMAPPED_REFERENCE_SEQUENCE_ID.to_le_bytes());
+    buf.extend_from_slice(&UNMAPPED_POSITION.to_le_bytes());
+    buf.push((name.len() + 1) as u8);
+    buf.push(UNMAPPED_MAPPING_QUALITY);
+    buf.extend_from_slice(&UNMAPPED_BIN.to_le_bytes());
+    // n_cigar_op
+    buf.extend_from_slice(&0u16.to_le_bytes());
+    buf.extend_from_slice(&record.flags().to_le_bytes());
+    buf.extend_from_slice(&l_seq.to_le_bytes());
+    // next_refID, next_pos, tlen
+    buf.extend_from_slice(&UNMAPPED_REFERENCE_SEQUENCE_ID.to_le_bytes());
+    buf.extend_from_slice(&UNMAPPED_POSITION.to_le_bytes());
+    buf.extend_from_slice(&0i32.to_le_bytes());
+
+    buf.extend_from_slice(name);
+    buf.push(0);
+
+    for pair in sequence.chunks(2) {
+        let hi = encode_base(pair[0]).ok_or_else(|| invalid_base(name, pair[0]))?;
+
+        let lo = match pair.get(1) {
+            Some(&b) => encode_base(b).ok_or_else(|| invalid_base(name, b))?,
+            None => 0,
+        };
+
+        buf.push((hi << 4) | lo);
+    }
+
+    if quality_scores.is_empty() {
+        buf.resize(buf.len() + sequence.len(), MISSING_QUALITY_SCORE);
+    } else {
+        for &score in quality_scores {
+            let score = score
+                .checked_sub(QUALITY_OFFSET)
+                .ok_or_else(|| invalid_record(name, "invalid quality score"))?;
+
+            buf.push(score);
+        }
+    }
+
+    if let Some(read_group) = record.read_group() {
+        buf.extend_from_slice(b"RGZ");
+        buf.extend_from_slice(read_group);
+        buf.push(0);
+    }
+
+    Ok(())
+}
+
+fn encode_base(base: u8) -> Option<u8> {
+    let base = base.to_ascii_uppercase();
+    BASES.iter().position(|&b| b == base).map(|i| i as u8)
+}
+
+fn invalid_record(name: &[u8], message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{}: {}", String::from_utf8_lossy(name), message),
+    )
+}
+
+fn invalid_base(name: &[u8], base: u8) -> io::Error {
+    invalid_record(
+        name,
+        &format!("invalid base: '{}'", char::from(base).escape_default()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::MultiGzDecoder;
+
+    use super::*;
+    use crate::bam::flags;
+
+    #[test]
+    fn test_encode_record() -> io::Result<()> {
+        let mut record = Record::new(
+            "r0",
+            flags::SEGMENTED | flags::UNMAPPED | flags::MATE_UNMAPPED | flags::FIRST_SEGMENT,
+            "ACGTN",
+            "!+5?I",
+        );
+        *record.read_group_mut() = Some(b"rg0".to_vec());
+
+        let mut buf = Vec::new();
+        encode_record(&mut buf, &record)?;
+
+        let expected = [
+            &(-1i32).to_le_bytes()[..],
+            &(-1i32).to_le_bytes(),
+            &[3, 0],
+            &4680u16.to_le_bytes(),
+            &0u16.to_le_bytes(),
+            &77u16.to_le_bytes(),
+            &5u32.to_le_bytes(),
+            &(-1i32).to_le_bytes(),
+            &(-1i32).to_le_bytes(),
+            &0i32.to_le_bytes(),
+            b"r0\x00",
+            &[0x12, 0x48, 0xf0],
+            &[0, 10, 20, 30, 40],
+            b"RGZrg0\x00",
+        ]
+        .concat();
+
+        assert_eq!(buf, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_record_with_missing_quality_scores() -> io::Result<()> {
+        let record = Record::new("r0", flags::UNMAPPED, "ac", "");
+
+        let mut buf = Vec::new();
+        encode_record(&mut buf, &record)?;
+
+        assert!(buf.ends_with(b"r0\x00\x12\xff\xff"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_record_with_invalid_record() {
+        let mut buf = Vec::new();
+
+        let record = Record::new("", flags::UNMAPPED, "ACGT", "IIII");
+        assert!(encode_record(&mut buf, &record).is_err());
+
+        let record = Record::new("r0", flags::UNMAPPED, "ACGT", "III");
+        assert!(encode_record(&mut buf, &record).is_err());
+
+        let record = Record::new("r0", flags::UNMAPPED, "AC.T", "IIII");
+        assert!(encode_record(&mut buf, &record).is_err());
+
+        let record = Record::new("r0", flags::UNMAPPED, "ACGT", "II I");
+        assert!(encode_record(&mut buf, &record).is_err());
+    }
+
+    #[test]
+    fn test_write_header() -> io::Result<()> {
+        let mut writer = Writer::new(Vec::new());
+        writer.write_header("@HD\tVN:1.6\n")?;
+        writer.finish()?;
+
+        let mut buf = Vec::new();
+        MultiGzDecoder::new(&writer.get_ref()[..]).read_to_end(&mut buf)?;
+
+        let expected = [
+            &b"BAM\x01"[..],
+            &11i32.to_le_bytes(),
+            b"@HD\tVN:1.6\n",
+            &0i32.to_le_bytes(),
+        ]
+        .concat();
+
+        assert_eq!(buf, expected);
+
+        Ok(())
+    }
+}
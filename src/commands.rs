@@ -1,6 +1,39 @@
+mod cat;
+mod clip;
+mod compare;
+mod convert;
+mod count;
+mod dedup;
+mod demux;
+mod describe;
+mod extract;
 pub mod filter;
+mod fix;
 pub mod generate;
+mod grep;
+mod head;
+mod index;
 pub mod lint;
+mod mask;
+mod merge_pairs;
+mod rename;
+mod repair;
+mod revcomp;
+mod screen;
+mod sort;
+mod split;
+mod stats;
 mod subsample;
+mod trim;
+mod trim_primers;
+mod umi;
+mod view;
 
-pub use self::{filter::filter, generate::generate, lint::lint, subsample::subsample};
+pub use self::{
+    cat::cat, clip::clip, compare::compare, convert::convert, count::count, dedup::dedup,
+    demux::demux, describe::describe, extract::extract, filter::filter, fix::fix,
+    generate::generate, grep::grep, head::head, index::index, lint::lint, mask::mask,
+    merge_pairs::merge_pairs, rename::rename, repair::repair, revcomp::revcomp, screen::screen,
+    sort::sort, split::split, stats::stats, subsample::subsample, trim::trim,
+    trim_primers::trim_primers, umi::umi, view::view,
+};
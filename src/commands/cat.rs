@@ -0,0 +1,249 @@
+use std::io::{self, BufRead, BufWriter, Write};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fastq::{self, Record, ValidatingWriter},
+    metrics::Metrics,
+    validators,
+};
+
+const NAME_PREFIX: u8 = b'@';
+const FIELD_DELIMITER: u8 = b':';
+
+// The number of fields in an Illumina 1.8+ read name, e.g.,
+// `@<instrument>:<run number>:<flowcell ID>:<lane>:<tile>:<x-pos>:<y-pos>`.
+const ILLUMINA_FIELD_COUNT: usize = 7;
+
+// The number of leading fields identifying the run, i.e., the instrument, run number, and
+// flowcell ID.
+const RUN_FIELD_COUNT: usize = 3;
+
+pub fn cat(matches: &ArgMatches) -> anyhow::Result<()> {
+    let srcs: Vec<_> = matches.values_of("srcs").unwrap().collect();
+    let dst = matches.value_of("dst");
+
+    let validation_level = matches
+        .value_of_t("single-read-validation-level")
+        .unwrap_or_else(|e| e.exit());
+
+    let mut disabled_validators = Vec::new();
+
+    for s in matches.values_of("disable-validator").unwrap_or_default() {
+        let description = validators::find_description(s)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid validator = {}", s))?;
+
+        disabled_validators.push(description.code.to_string());
+    }
+
+    let mut run_checker = if matches.is_present("check-run") {
+        Some(RunChecker::default())
+    } else {
+        None
+    };
+
+    info!("fq-cat start");
+
+    let metrics = Metrics::new();
+
+    let mut writer = match dst {
+        Some(dst) => {
+            fastq::create(dst).with_context(|| format!("Could not create file: {}", dst))?
+        }
+        None => fastq::Writer::new(Box::new(BufWriter::new(io::stdout())) as Box<dyn Write>),
+    };
+
+    writer.set_metrics(metrics.clone());
+
+    let mut writer = ValidatingWriter::new(writer, validation_level, &disabled_validators);
+
+    let stage = metrics.stage("concatenating records");
+
+    for src in &srcs {
+        info!("concatenating {}", src);
+
+        let mut reader =
+            fastq::open(src).with_context(|| format!("Could not open file: {}", src))?;
+        reader.set_metrics(metrics.clone());
+
+        let n = cat_records(&mut reader, &mut writer, run_checker.as_mut())
+            .with_context(|| format!("Could not concatenate file: {}", src))?;
+
+        info!("concatenated {} records from {}", n, src);
+    }
+
+    writer.finish().context("Invalid output")?;
+
+    drop(stage);
+
+    info!(
+        "concatenated {} records from {} files",
+        metrics.records_written(),
+        srcs.len()
+    );
+
+    metrics.log();
+
+    info!("fq-cat end");
+
+    Ok(())
+}
+
+// Copies all records from a reader, validating each and, if given, checking that it is from the
+// same run as all previous records. This returns the number of records copied.
+fn cat_records<R, W>(
+    reader: &mut fastq::Reader<R>,
+    writer: &mut ValidatingWriter<W>,
+    mut run_checker: Option<&mut RunChecker>,
+) -> anyhow::Result<u64>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut record = Record::default();
+    let mut n = 0;
+
+    while reader.read_record(&mut record)? != 0 {
+        if let Some(checker) = run_checker.as_deref_mut() {
+            checker
+                .check(&record)
+                .with_context(|| format!("Invalid record at position {}", n + 1))?;
+        }
+
+        writer
+            .write_record(&record)
+            .with_context(|| format!("Invalid record at position {}", n + 1))?;
+
+        n += 1;
+    }
+
+    Ok(n)
+}
+
+/// Checks that all records are from the same sequencing run.
+///
+/// The run is identified by the instrument, run number, and flowcell ID of an Illumina read name.
+#[derive(Debug, Default)]
+struct RunChecker {
+    expected: Option<Vec<u8>>,
+}
+
+impl RunChecker {
+    fn check(&mut self, record: &Record) -> io::Result<()> {
+        let run_id = run_id(record.name()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "invalid Illumina read name: {}",
+                    String::from_utf8_lossy(record.name())
+                ),
+            )
+        })?;
+
+        match self.expected.as_deref() {
+            Some(expected) if expected != run_id => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "run mismatch: expected '{}', got '{}'",
+                    String::from_utf8_lossy(expected),
+                    String::from_utf8_lossy(run_id)
+                ),
+            )),
+            Some(_) => Ok(()),
+            None => {
+                self.expected = Some(run_id.to_vec());
+                Ok(())
+            }
+        }
+    }
+}
+
+// Returns the `<instrument>:<run number>:<flowcell ID>` prefix of an Illumina read name.
+fn run_id(name: &[u8]) -> Option<&[u8]> {
+    let name = name.strip_prefix(&[NAME_PREFIX])?;
+
+    let end = name
+        .iter()
+        .position(|b| b.is_ascii_whitespace())
+        .unwrap_or(name.len());
+
+    let id = &name[..end];
+
+    let delimiters: Vec<_> = id
+        .iter()
+        .enumerate()
+        .filter(|(_, &b)| b == FIELD_DELIMITER)
+        .map(|(i, _)| i)
+        .collect();
+
+    if delimiters.len() + 1 != ILLUMINA_FIELD_COUNT {
+        return None;
+    }
+
+    Some(&id[..delimiters[RUN_FIELD_COUNT - 1]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validators::ValidationLevel;
+
+    #[test]
+    fn test_cat_records() -> anyhow::Result<()> {
+        let mut writer =
+            ValidatingWriter::new(fastq::Writer::new(Vec::new()), ValidationLevel::High, &[]);
+
+        let data = b"@r0\nAC\n+\nFQ\n";
+        let mut reader = fastq::Reader::new(&data[..]);
+        assert_eq!(cat_records(&mut reader, &mut writer, None)?, 1);
+
+        let data = b"@r1\nGT\n+\nLI\n";
+        let mut reader = fastq::Reader::new(&data[..]);
+        assert_eq!(cat_records(&mut reader, &mut writer, None)?, 1);
+
+        assert_eq!(
+            writer.get_ref().get_ref(),
+            b"@r0\nAC\n+\nFQ\n@r1\nGT\n+\nLI\n"
+        );
+
+        let data = b"@r2\nAC\n+\nF\n";
+        let mut reader = fastq::Reader::new(&data[..]);
+        assert!(cat_records(&mut reader, &mut writer, None).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cat_records_with_run_checker() -> anyhow::Result<()> {
+        let mut writer =
+            ValidatingWriter::new(fastq::Writer::new(Vec::new()), ValidationLevel::High, &[]);
+        let mut checker = RunChecker::default();
+
+        let data = b"@M1:7:FC1:1:1:1:1 1:N:0\nAC\n+\nFQ\n@M1:7:FC1:2:1:1:2\nGT\n+\nLI\n";
+        let mut reader = fastq::Reader::new(&data[..]);
+        assert_eq!(
+            cat_records(&mut reader, &mut writer, Some(&mut checker))?,
+            2
+        );
+
+        let data = b"@M1:7:FC2:1:1:1:1\nAC\n+\nFQ\n";
+        let mut reader = fastq::Reader::new(&data[..]);
+        assert!(cat_records(&mut reader, &mut writer, Some(&mut checker)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_id() {
+        assert_eq!(
+            run_id(b"@M1:7:FC1:1:1101:1:1 1:N:0:ACGT"),
+            Some(&b"M1:7:FC1"[..])
+        );
+        assert_eq!(run_id(b"@M1:7:FC1:1:1101:1:1"), Some(&b"M1:7:FC1"[..]));
+        assert!(run_id(b"@fqlib:1").is_none());
+        assert!(run_id(b"M1:7:FC1:1:1101:1:1").is_none());
+    }
+}
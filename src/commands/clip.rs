@@ -0,0 +1,221 @@
+use std::io::{self, BufRead, Write};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fastq::{self, Record},
+    metrics::Metrics,
+    trim::{trim_end, trim_start},
+};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct ClipLengths {
+    front: usize,
+    tail: usize,
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Counts {
+    records: u64,
+    bases_clipped: u64,
+}
+
+pub fn clip(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r1_dst = matches.value_of("r1-dst").unwrap();
+
+    let r2_src = matches.value_of("r2-src");
+    let r2_dst = matches.value_of("r2-dst");
+
+    let r1_lengths = ClipLengths {
+        front: matches.value_of_t("front").unwrap_or_else(|e| e.exit()),
+        tail: matches.value_of_t("tail").unwrap_or_else(|e| e.exit()),
+    };
+
+    // Read 2 uses the read 1 lengths unless they are overridden.
+    let r2_lengths = ClipLengths {
+        front: if matches.is_present("r2-front") {
+            matches.value_of_t("r2-front").unwrap_or_else(|e| e.exit())
+        } else {
+            r1_lengths.front
+        },
+        tail: if matches.is_present("r2-tail") {
+            matches.value_of_t("r2-tail").unwrap_or_else(|e| e.exit())
+        } else {
+            r1_lengths.tail
+        },
+    };
+
+    info!("fq-clip start");
+
+    let metrics = Metrics::new();
+
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    let mut w1 =
+        fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+    r1.set_metrics(metrics.clone());
+    w1.set_metrics(metrics.clone());
+
+    let _stage = metrics.stage("clipping");
+
+    let counts = match (r2_src, r2_dst) {
+        (Some(r2_src), Some(r2_dst)) => {
+            info!("clipping paired end reads");
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            let mut w2 = fastq::create(r2_dst)
+                .with_context(|| format!("Could not create file: {}", r2_dst))?;
+            r2.set_metrics(metrics.clone());
+            w2.set_metrics(metrics.clone());
+
+            clip_paired(
+                (&mut r1, &mut w1, r1_lengths),
+                (&mut r2, &mut w2, r2_lengths),
+            )?
+        }
+        (Some(r2_src), None) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-dst for {}", r2_src));
+        }
+        (None, Some(r2_dst)) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-src for {}", r2_dst));
+        }
+        (None, None) => {
+            info!("clipping single end reads");
+            clip_single(&mut r1, &mut w1, r1_lengths)?
+        }
+    };
+
+    info!(
+        "clipped {} bases from {} records",
+        counts.bases_clipped, counts.records
+    );
+
+    metrics.log();
+
+    info!("fq-clip end");
+
+    Ok(())
+}
+
+fn clip_record(record: &mut Record, lengths: ClipLengths, counts: &mut Counts) {
+    let len = record.sequence().len();
+
+    trim_start(record, lengths.front);
+    trim_end(record, lengths.tail);
+
+    counts.records += 1;
+    counts.bases_clipped += (len - record.sequence().len()) as u64;
+}
+
+fn clip_single<R, W>(
+    reader: &mut fastq::Reader<R>,
+    writer: &mut fastq::Writer<W>,
+    lengths: ClipLengths,
+) -> anyhow::Result<Counts>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut record = Record::default();
+    let mut counts = Counts::default();
+
+    while reader.read_record(&mut record)? != 0 {
+        clip_record(&mut record, lengths, &mut counts);
+        writer.write_record(&record)?;
+    }
+
+    Ok(counts)
+}
+
+fn clip_paired<R, S, W, X>(
+    (r1, w1, r1_lengths): (&mut fastq::Reader<R>, &mut fastq::Writer<W>, ClipLengths),
+    (r2, w2, r2_lengths): (&mut fastq::Reader<S>, &mut fastq::Writer<X>, ClipLengths),
+) -> anyhow::Result<Counts>
+where
+    R: BufRead,
+    S: BufRead,
+    W: Write,
+    X: Write,
+{
+    let mut s1 = Record::default();
+    let mut s2 = Record::default();
+
+    let mut counts = Counts::default();
+
+    loop {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, _) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r1-src unexpectedly ended before r2-src");
+            }
+            (_, 0) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r2-src unexpectedly ended before r1-src");
+            }
+            (_, _) => {
+                clip_record(&mut s1, r1_lengths, &mut counts);
+                clip_record(&mut s2, r2_lengths, &mut counts);
+
+                w1.write_record(&s1)?;
+                w2.write_record(&s2)?;
+            }
+        }
+    }
+
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clip_single() -> anyhow::Result<()> {
+        let data = b"@r0\nACGTACGT\n+\nFQLIBFQL\n@r1\nACG\n+\nIII\n";
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+        let lengths = ClipLengths { front: 2, tail: 1 };
+
+        let counts = clip_single(&mut reader, &mut writer, lengths)?;
+
+        assert_eq!(
+            counts,
+            Counts {
+                records: 2,
+                bases_clipped: 6,
+            }
+        );
+        assert_eq!(writer.get_ref(), b"@r0\nGTACG\n+\nLIBFQ\n@r1\n\n+\n\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_clip_paired() -> anyhow::Result<()> {
+        let r1_data = b"@r0/1\nACGTACGT\n+\nFQLIBFQL\n";
+        let r2_data = b"@r0/2\nTTGGCCAA\n+\nIIII####\n";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut w2 = fastq::Writer::new(Vec::new());
+
+        let counts = clip_paired(
+            (&mut r1, &mut w1, ClipLengths { front: 1, tail: 0 }),
+            (&mut r2, &mut w2, ClipLengths { front: 0, tail: 4 }),
+        )?;
+
+        assert_eq!(counts.bases_clipped, 5);
+        assert_eq!(w1.get_ref(), b"@r0/1\nCGTACGT\n+\nQLIBFQL\n");
+        assert_eq!(w2.get_ref(), b"@r0/2\nTTGG\n+\nIIII\n");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,267 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fmt,
+    hash::{Hash, Hasher},
+    io::{self, BufRead},
+};
+
+use anyhow::{anyhow, Context};
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fastq::{self, name_id, Record},
+    metrics::Metrics,
+};
+
+/// A difference between two FASTQ sources, `a` and `b`.
+#[derive(Debug, Eq, PartialEq)]
+enum Difference {
+    /// A field of the records at the same (1-based) position differ.
+    Record { position: u64, field: &'static str },
+    /// A source ended before the other, after the given number of records.
+    Truncated {
+        src: &'static str,
+        record_count: u64,
+    },
+    /// The number of records found in only one of the sources.
+    Content { a_only: u64, b_only: u64 },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Record { position, field } => {
+                write!(
+                    f,
+                    "records at position {} have different {}",
+                    position, field
+                )
+            }
+            Self::Truncated { src, record_count } => {
+                write!(f, "{} ended after {} records", src, record_count)
+            }
+            Self::Content { a_only, b_only } => write!(
+                f,
+                "{} records are only in a, and {} records are only in b",
+                a_only, b_only
+            ),
+        }
+    }
+}
+
+pub fn compare(matches: &ArgMatches) -> anyhow::Result<()> {
+    let a_src = matches.value_of("a-src").unwrap();
+    let b_src = matches.value_of("b-src").unwrap();
+
+    let mode = matches.value_of("mode").unwrap();
+
+    info!("fq-compare start");
+
+    let metrics = Metrics::new();
+
+    let mut a = fastq::open(a_src).with_context(|| format!("Could not open file: {}", a_src))?;
+    a.set_metrics(metrics.clone());
+
+    let mut b = fastq::open(b_src).with_context(|| format!("Could not open file: {}", b_src))?;
+    b.set_metrics(metrics.clone());
+
+    let stage = metrics.stage("comparing records");
+
+    let difference = match mode {
+        "ordered" => {
+            info!("comparing records in order");
+            compare_ordered(&mut a, &mut b)
+        }
+        "unordered" => {
+            info!("comparing record contents regardless of order");
+            compare_unordered(&mut a, &mut b)
+        }
+        _ => unreachable!(),
+    }
+    .with_context(|| format!("Could not compare files: {}, {}", a_src, b_src))?;
+
+    drop(stage);
+
+    metrics.log();
+
+    if let Some(difference) = difference {
+        return Err(anyhow!("{}", difference))
+            .with_context(|| format!("Files differ: {}, {}", a_src, b_src));
+    }
+
+    info!("files are identical");
+
+    info!("fq-compare end");
+
+    Ok(())
+}
+
+// Compares records field by field in the order they are read.
+fn compare_ordered<R, S>(
+    a: &mut fastq::Reader<R>,
+    b: &mut fastq::Reader<S>,
+) -> io::Result<Option<Difference>>
+where
+    R: BufRead,
+    S: BufRead,
+{
+    let mut s = Record::default();
+    let mut t = Record::default();
+
+    let mut n = 0;
+
+    loop {
+        match (a.read_record(&mut s)?, b.read_record(&mut t)?) {
+            (0, 0) => return Ok(None),
+            (0, _) => {
+                return Ok(Some(Difference::Truncated {
+                    src: "a",
+                    record_count: n,
+                }))
+            }
+            (_, 0) => {
+                return Ok(Some(Difference::Truncated {
+                    src: "b",
+                    record_count: n,
+                }))
+            }
+            (_, _) => {
+                n += 1;
+
+                let field = if s.name() != t.name() {
+                    Some("names")
+                } else if s.sequence() != t.sequence() {
+                    Some("sequences")
+                } else if s.plus_line() != t.plus_line() {
+                    Some("plus lines")
+                } else if s.quality_scores() != t.quality_scores() {
+                    Some("quality scores")
+                } else {
+                    None
+                };
+
+                if let Some(field) = field {
+                    return Ok(Some(Difference::Record { position: n, field }));
+                }
+            }
+        }
+    }
+}
+
+// Compares the multisets of records, ignoring order, name comments, pair suffixes, and plus
+// lines.
+//
+// Only a hash of each record is held in memory.
+fn compare_unordered<R, S>(
+    a: &mut fastq::Reader<R>,
+    b: &mut fastq::Reader<S>,
+) -> io::Result<Option<Difference>>
+where
+    R: BufRead,
+    S: BufRead,
+{
+    let mut counts: HashMap<u64, i64> = HashMap::new();
+    let mut record = Record::default();
+
+    while a.read_record(&mut record)? != 0 {
+        *counts.entry(hash_record(&record)).or_default() += 1;
+    }
+
+    while b.read_record(&mut record)? != 0 {
+        *counts.entry(hash_record(&record)).or_default() -= 1;
+    }
+
+    let mut a_only = 0;
+    let mut b_only = 0;
+
+    for &count in counts.values() {
+        if count > 0 {
+            a_only += count as u64;
+        } else {
+            b_only += count.unsigned_abs();
+        }
+    }
+
+    if a_only == 0 && b_only == 0 {
+        Ok(None)
+    } else {
+        Ok(Some(Difference::Content { a_only, b_only }))
+    }
+}
+
+fn hash_record(record: &Record) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    name_id(record.name()).hash(&mut hasher);
+    record.sequence().hash(&mut hasher);
+    record.quality_scores().hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compare_ordered_data(a: &[u8], b: &[u8]) -> io::Result<Option<Difference>> {
+        compare_ordered(&mut fastq::Reader::new(a), &mut fastq::Reader::new(b))
+    }
+
+    fn compare_unordered_data(a: &[u8], b: &[u8]) -> io::Result<Option<Difference>> {
+        compare_unordered(&mut fastq::Reader::new(a), &mut fastq::Reader::new(b))
+    }
+
+    #[test]
+    fn test_compare_ordered() -> io::Result<()> {
+        let a = b"@r0\nAC\n+\nFQ\n@r1\nGT\n+\nLI\n";
+
+        assert_eq!(compare_ordered_data(a, a)?, None);
+
+        let b = b"@r0\nAC\n+\nFQ\n@r1\nGT\n+\nLL\n";
+        assert_eq!(
+            compare_ordered_data(a, b)?,
+            Some(Difference::Record {
+                position: 2,
+                field: "quality scores"
+            })
+        );
+
+        let b = b"@r1\nGT\n+\nLI\n@r0\nAC\n+\nFQ\n";
+        assert_eq!(
+            compare_ordered_data(a, b)?,
+            Some(Difference::Record {
+                position: 1,
+                field: "names"
+            })
+        );
+
+        let b = b"@r0\nAC\n+\nFQ\n";
+        assert_eq!(
+            compare_ordered_data(a, b)?,
+            Some(Difference::Truncated {
+                src: "b",
+                record_count: 1
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compare_unordered() -> io::Result<()> {
+        let a = b"@r0 1:N:0\nAC\n+\nFQ\n@r1\nGT\n+\nLI\n";
+
+        let b = b"@r1\nGT\n+r1\nLI\n@r0 2:N:0\nAC\n+\nFQ\n";
+        assert_eq!(compare_unordered_data(a, b)?, None);
+
+        let b = b"@r1\nGT\n+\nLI\n@r1\nGT\n+\nLI\n@r2\nAA\n+\nBF\n";
+        assert_eq!(
+            compare_unordered_data(a, b)?,
+            Some(Difference::Content {
+                a_only: 1,
+                b_only: 2
+            })
+        );
+
+        Ok(())
+    }
+}
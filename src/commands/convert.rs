@@ -0,0 +1,1231 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufWriter, Write},
+};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    bam::{self, flags, ReadGroup},
+    fasta,
+    fastq::{self, name_id},
+    metrics::Metrics,
+    quality::{self, Detector, Encoding},
+    tab,
+};
+
+const NAME_PREFIX: u8 = b'@';
+
+// The number of records read to detect the quality score encoding of the source.
+const DETECTION_RECORD_COUNT: usize = 10000;
+
+// The quality score of bases without one in BAM or SAM input, i.e., Q0.
+const MISSING_QUALITY_SCORE: u8 = b'!';
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Counts {
+    single: u64,
+    pairs: u64,
+    singletons: u64,
+    skipped: u64,
+}
+
+pub fn convert(matches: &ArgMatches) -> anyhow::Result<()> {
+    let src = matches.value_of("src").unwrap();
+    let r2_src = matches.value_of("r2-src");
+    let dst = matches.value_of("dst");
+
+    let from = matches.value_of("from").unwrap();
+    let to = matches.value_of("to");
+
+    info!("fq-convert start");
+
+    let metrics = Metrics::new();
+
+    let quality_from = matches.value_of("quality-from");
+    let quality_to = matches.value_of("quality-to");
+    let recode = quality_from.is_some() || quality_to.is_some();
+
+    if r2_src.is_some() && !matches!(to, Some("bam" | "tab")) {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .context("A read 2 source is only supported for BAM and tabular output");
+    }
+
+    if matches.is_present("read-group") && to != Some("bam") {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .context("--read-group is only supported for BAM output");
+    }
+
+    if matches.is_present("r2-dst") && !matches!(from, "bam" | "sam" | "tab") {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .context("--r2-dst is only supported for BAM, SAM, and tabular input");
+    }
+
+    if matches.is_present("singletons") && !matches!(from, "bam" | "sam") {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .context("--singletons is only supported for BAM and SAM input");
+    }
+
+    match (from, to) {
+        ("fastq", None | Some("fastq")) if recode => {
+            let src_encoding = match quality_from {
+                None | Some("auto") => detect_encoding(src)?,
+                Some(s) => s.parse()?,
+            };
+
+            let dst_encoding = quality_to
+                .map(|s| s.parse())
+                .transpose()?
+                .unwrap_or(Encoding::Phred33);
+
+            convert_fastq_quality(&metrics, src, dst, src_encoding, dst_encoding)?;
+        }
+        (_, _) if recode => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .context("Quality encoding conversion is only supported from FASTQ to FASTQ");
+        }
+        ("fastq", Some("bam")) => {
+            let read_group = matches
+                .value_of("read-group")
+                .map(|s| s.parse::<ReadGroup>())
+                .transpose()
+                .context("Invalid --read-group")?;
+
+            convert_fastq_to_bam(&metrics, src, r2_src, dst, read_group.as_ref())?;
+        }
+        ("bam" | "sam", None | Some("fastq")) => {
+            let r2_dst = matches.value_of("r2-dst");
+            let singletons_dst = matches.value_of("singletons");
+
+            convert_alignments_to_fastq(&metrics, from, src, dst, r2_dst, singletons_dst)?;
+        }
+        ("fastq", Some("tab")) => {
+            convert_fastq_to_tab(&metrics, src, r2_src, dst)?;
+        }
+        ("tab", None | Some("fastq")) => {
+            let r2_dst = matches.value_of("r2-dst");
+            convert_tab_to_fastq(&metrics, src, dst, r2_dst)?;
+        }
+        ("fastq", None | Some("fasta")) => {
+            let line_width = matches
+                .value_of_t("line-width")
+                .unwrap_or_else(|e| e.exit());
+
+            convert_fastq_to_fasta(&metrics, src, dst, line_width)?;
+        }
+        ("fasta", None | Some("fastq")) => {
+            let qual_src = matches.value_of("qual").ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Missing --qual for FASTA input",
+                )
+            })?;
+
+            convert_fasta_to_fastq(&metrics, src, qual_src, dst)?;
+        }
+        (from, to) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput)).with_context(|| {
+                format!("Unsupported conversion: {} to {}", from, to.unwrap_or(from))
+            });
+        }
+    }
+
+    metrics.log();
+
+    info!("fq-convert end");
+
+    Ok(())
+}
+
+fn stdout() -> Box<dyn Write> {
+    Box::new(BufWriter::new(io::stdout()))
+}
+
+fn convert_fastq_to_fasta(
+    metrics: &Metrics,
+    src: &str,
+    dst: Option<&str>,
+    line_width: usize,
+) -> anyhow::Result<()> {
+    let mut reader = fastq::open(src).with_context(|| format!("Could not open file: {}", src))?;
+    reader.set_metrics(metrics.clone());
+
+    let mut writer = match dst {
+        Some(dst) => {
+            fasta::create(dst).with_context(|| format!("Could not create file: {}", dst))?
+        }
+        None => fasta::Writer::new(stdout()),
+    };
+
+    writer.set_line_width(line_width);
+    writer.set_metrics(metrics.clone());
+
+    info!("converting FASTQ to FASTA");
+
+    let _stage = metrics.stage("converting records");
+    let n = fastq_to_fasta(&mut reader, &mut writer)
+        .with_context(|| format!("Could not convert file: {}", src))?;
+
+    info!("converted {} records", n);
+
+    Ok(())
+}
+
+fn convert_fasta_to_fastq(
+    metrics: &Metrics,
+    src: &str,
+    qual_src: &str,
+    dst: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut reader = fasta::open(src).with_context(|| format!("Could not open file: {}", src))?;
+
+    let mut qual_reader =
+        fasta::open_qual(qual_src).with_context(|| format!("Could not open file: {}", qual_src))?;
+
+    let mut writer = match dst {
+        Some(dst) => {
+            fastq::create(dst).with_context(|| format!("Could not create file: {}", dst))?
+        }
+        None => fastq::Writer::new(stdout()),
+    };
+
+    writer.set_metrics(metrics.clone());
+
+    info!("converting FASTA and QUAL to FASTQ");
+
+    let _stage = metrics.stage("converting records");
+    let n = fasta_to_fastq(&mut reader, &mut qual_reader, &mut writer)
+        .with_context(|| format!("Could not convert files: {}, {}", src, qual_src))?;
+
+    info!("converted {} records", n);
+
+    Ok(())
+}
+
+fn convert_fastq_to_tab(
+    metrics: &Metrics,
+    r1_src: &str,
+    r2_src: Option<&str>,
+    dst: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    r1.set_metrics(metrics.clone());
+
+    let mut writer = match dst {
+        Some(dst) => tab::create(dst).with_context(|| format!("Could not create file: {}", dst))?,
+        None => tab::Writer::new(stdout()),
+    };
+
+    writer.set_metrics(metrics.clone());
+
+    let _stage = metrics.stage("converting records");
+
+    let n = if let Some(r2_src) = r2_src {
+        info!("converting paired end FASTQ to tabular");
+
+        let mut r2 =
+            fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+        r2.set_metrics(metrics.clone());
+
+        fastq_to_tab_paired(&mut r1, &mut r2, &mut writer)
+            .with_context(|| format!("Could not convert files: {}, {}", r1_src, r2_src))?
+    } else {
+        info!("converting FASTQ to tabular");
+
+        fastq_to_tab_single(&mut r1, &mut writer)
+            .with_context(|| format!("Could not convert file: {}", r1_src))?
+    };
+
+    info!("converted {} lines", n);
+
+    Ok(())
+}
+
+fn fastq_to_tab_single<R, W>(
+    reader: &mut fastq::Reader<R>,
+    writer: &mut tab::Writer<W>,
+) -> io::Result<u64>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut record = fastq::Record::default();
+    let mut n = 0;
+
+    while reader.read_record(&mut record)? != 0 {
+        writer.write_record(&record)?;
+        n += 1;
+    }
+
+    Ok(n)
+}
+
+fn fastq_to_tab_paired<R, S, W>(
+    r1: &mut fastq::Reader<R>,
+    r2: &mut fastq::Reader<S>,
+    writer: &mut tab::Writer<W>,
+) -> io::Result<u64>
+where
+    R: BufRead,
+    S: BufRead,
+    W: Write,
+{
+    let mut s1 = fastq::Record::default();
+    let mut s2 = fastq::Record::default();
+    let mut n = 0;
+
+    loop {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, _) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "r1-src unexpectedly ended before r2-src",
+                ));
+            }
+            (_, 0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "r2-src unexpectedly ended before r1-src",
+                ));
+            }
+            (_, _) => {
+                writer.write_pair(&s1, &s2)?;
+                n += 1;
+            }
+        }
+    }
+
+    Ok(n)
+}
+
+fn convert_tab_to_fastq(
+    metrics: &Metrics,
+    src: &str,
+    dst: Option<&str>,
+    r2_dst: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut reader = tab::open(src).with_context(|| format!("Could not open file: {}", src))?;
+    reader.set_metrics(metrics.clone());
+
+    let mut w1 = match dst {
+        Some(dst) => {
+            fastq::create(dst).with_context(|| format!("Could not create file: {}", dst))?
+        }
+        None => fastq::Writer::new(stdout()),
+    };
+
+    w1.set_metrics(metrics.clone());
+
+    let mut w2 = r2_dst
+        .map(|dst| fastq::create(dst).with_context(|| format!("Could not create file: {}", dst)))
+        .transpose()?;
+
+    if let Some(writer) = w2.as_mut() {
+        writer.set_metrics(metrics.clone());
+    }
+
+    info!("converting tabular to FASTQ");
+
+    let _stage = metrics.stage("converting records");
+
+    let n = tab_to_fastq(&mut reader, &mut w1, w2.as_mut())
+        .with_context(|| format!("Could not convert file: {}", src))?;
+
+    info!("converted {} records", n);
+
+    Ok(())
+}
+
+// Read 1 of a paired line is written to `w1`, and read 2, to `w2` or, if not set, after read 1 in
+// `w1`.
+fn tab_to_fastq<R, W, X>(
+    reader: &mut tab::Reader<R>,
+    w1: &mut fastq::Writer<W>,
+    mut w2: Option<&mut fastq::Writer<X>>,
+) -> io::Result<u64>
+where
+    R: BufRead,
+    W: Write,
+    X: Write,
+{
+    let mut s1 = fastq::Record::default();
+    let mut s2 = fastq::Record::default();
+    let mut n = 0;
+
+    loop {
+        match reader.read_records(&mut s1, &mut s2)? {
+            0 => break,
+            1 => w1.write_record(&s1)?,
+            _ => {
+                w1.write_record(&s1)?;
+
+                match w2.as_mut() {
+                    Some(w2) => w2.write_record(&s2)?,
+                    None => w1.write_record(&s2)?,
+                }
+            }
+        }
+
+        n += 1;
+    }
+
+    Ok(n)
+}
+
+fn convert_fastq_to_bam(
+    metrics: &Metrics,
+    r1_src: &str,
+    r2_src: Option<&str>,
+    dst: Option<&str>,
+    read_group: Option<&ReadGroup>,
+) -> anyhow::Result<()> {
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    r1.set_metrics(metrics.clone());
+
+    let mut writer = match dst {
+        Some(dst) => bam::create(dst).with_context(|| format!("Could not create file: {}", dst))?,
+        None => bam::Writer::new(stdout()),
+    };
+
+    writer.set_metrics(metrics.clone());
+
+    writer
+        .write_header(&build_bam_header(read_group))
+        .context("Could not write BAM header")?;
+
+    let read_group_id = read_group.map(|rg| rg.id().as_bytes());
+
+    let _stage = metrics.stage("converting records");
+
+    let n = match r2_src {
+        Some(r2_src) => {
+            info!("converting paired end FASTQ to BAM");
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            r2.set_metrics(metrics.clone());
+
+            fastq_to_bam_paired(&mut r1, &mut r2, &mut writer, read_group_id)
+                .with_context(|| format!("Could not convert files: {}, {}", r1_src, r2_src))?
+        }
+        None => {
+            info!("converting single end FASTQ to BAM");
+
+            fastq_to_bam_single(&mut r1, &mut writer, read_group_id)
+                .with_context(|| format!("Could not convert file: {}", r1_src))?
+        }
+    };
+
+    writer.finish().context("Could not finish BAM output")?;
+
+    info!("converted {} records (or pairs)", n);
+
+    Ok(())
+}
+
+// Builds a SAM header for unaligned records. Mates are written next to each other, so records are
+// grouped by name.
+fn build_bam_header(read_group: Option<&ReadGroup>) -> String {
+    let mut text = String::from("@HD\tVN:1.6\tSO:unsorted\tGO:query\n");
+
+    if let Some(read_group) = read_group {
+        text.push_str(&read_group.to_string());
+        text.push('\n');
+    }
+
+    text.push_str(&format!(
+        "@PG\tID:fq\tPN:fq\tVN:{}\n",
+        env!("CARGO_PKG_VERSION")
+    ));
+
+    text
+}
+
+fn fastq_to_bam_single<R, W>(
+    reader: &mut fastq::Reader<R>,
+    writer: &mut bam::Writer<W>,
+    read_group_id: Option<&[u8]>,
+) -> io::Result<u64>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut src = fastq::Record::default();
+    let mut dst = bam::Record::default();
+
+    let mut n = 0;
+
+    while reader.read_record(&mut src)? != 0 {
+        copy_bam_record(&src, flags::UNMAPPED, read_group_id, &mut dst);
+        writer.write_record(&dst)?;
+        n += 1;
+    }
+
+    Ok(n)
+}
+
+fn fastq_to_bam_paired<R, S, W>(
+    r1: &mut fastq::Reader<R>,
+    r2: &mut fastq::Reader<S>,
+    writer: &mut bam::Writer<W>,
+    read_group_id: Option<&[u8]>,
+) -> io::Result<u64>
+where
+    R: BufRead,
+    S: BufRead,
+    W: Write,
+{
+    const PAIRED_FLAGS: u16 = flags::SEGMENTED | flags::UNMAPPED | flags::MATE_UNMAPPED;
+
+    let mut s1 = fastq::Record::default();
+    let mut s2 = fastq::Record::default();
+    let mut d1 = bam::Record::default();
+    let mut d2 = bam::Record::default();
+
+    let mut n = 0;
+
+    loop {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, _) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "r1-src unexpectedly ended before r2-src",
+                ));
+            }
+            (_, 0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "r2-src unexpectedly ended before r1-src",
+                ));
+            }
+            (_, _) => {
+                if name_id(s1.name()) != name_id(s2.name()) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Mate names mismatch: '{}', '{}'",
+                            String::from_utf8_lossy(s1.name()),
+                            String::from_utf8_lossy(s2.name())
+                        ),
+                    ));
+                }
+
+                copy_bam_record(
+                    &s1,
+                    PAIRED_FLAGS | flags::FIRST_SEGMENT,
+                    read_group_id,
+                    &mut d1,
+                );
+                copy_bam_record(
+                    &s2,
+                    PAIRED_FLAGS | flags::LAST_SEGMENT,
+                    read_group_id,
+                    &mut d2,
+                );
+
+                writer.write_record(&d1)?;
+                writer.write_record(&d2)?;
+
+                n += 1;
+            }
+        }
+    }
+
+    Ok(n)
+}
+
+// Copies a FASTQ record to an unaligned BAM record. The name is the record ID, i.e., without the
+// pair suffix and comment.
+fn copy_bam_record(
+    src: &fastq::Record,
+    flags: u16,
+    read_group_id: Option<&[u8]>,
+    dst: &mut bam::Record,
+) {
+    dst.clear();
+
+    dst.name_mut().extend_from_slice(name_id(src.name()));
+    *dst.flags_mut() = flags;
+    dst.sequence_mut().extend_from_slice(src.sequence());
+    dst.quality_scores_mut()
+        .extend_from_slice(src.quality_scores());
+    *dst.read_group_mut() = read_group_id.map(|id| id.to_vec());
+}
+
+fn convert_alignments_to_fastq(
+    metrics: &Metrics,
+    format: &str,
+    src: &str,
+    dst: Option<&str>,
+    r2_dst: Option<&str>,
+    singletons_dst: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut w1 = match dst {
+        Some(dst) => {
+            fastq::create(dst).with_context(|| format!("Could not create file: {}", dst))?
+        }
+        None => fastq::Writer::new(stdout()),
+    };
+
+    w1.set_metrics(metrics.clone());
+
+    let mut w2 = r2_dst
+        .map(|dst| fastq::create(dst).with_context(|| format!("Could not create file: {}", dst)))
+        .transpose()?;
+
+    let mut singletons_writer = singletons_dst
+        .map(|dst| fastq::create(dst).with_context(|| format!("Could not create file: {}", dst)))
+        .transpose()?;
+
+    for writer in w2.iter_mut().chain(singletons_writer.iter_mut()) {
+        writer.set_metrics(metrics.clone());
+    }
+
+    info!("converting {} to FASTQ", format.to_uppercase());
+
+    let _stage = metrics.stage("converting records");
+
+    let counts = if format == "bam" {
+        let mut reader = bam::open(src).with_context(|| format!("Could not open file: {}", src))?;
+        reader.set_metrics(metrics.clone());
+        reader
+            .read_header()
+            .with_context(|| format!("Could not read header: {}", src))?;
+
+        alignments_to_fastq(
+            |record| reader.read_record(record),
+            &mut w1,
+            w2.as_mut(),
+            singletons_writer.as_mut(),
+        )
+    } else {
+        let mut reader =
+            bam::open_sam(src).with_context(|| format!("Could not open file: {}", src))?;
+        reader.set_metrics(metrics.clone());
+        reader
+            .read_header()
+            .with_context(|| format!("Could not read header: {}", src))?;
+
+        alignments_to_fastq(
+            |record| reader.read_record(record),
+            &mut w1,
+            w2.as_mut(),
+            singletons_writer.as_mut(),
+        )
+    };
+
+    let counts = counts.with_context(|| format!("Could not convert file: {}", src))?;
+
+    info!(
+        "converted {} single end reads and {} pairs ({} singletons, {} secondary or supplementary records skipped)",
+        counts.single, counts.pairs, counts.singletons, counts.skipped
+    );
+
+    Ok(())
+}
+
+// Mates are matched by name, regardless of order. Read 1 is written to `w1`, and read 2, to `w2`
+// or, if not set, after read 1 in `w1`. Records that are not paired are also written to `w1`.
+// Mates without a pair are written to the singletons writer, if set, in input order.
+fn alignments_to_fastq<F, W, X, Y>(
+    mut read_record: F,
+    w1: &mut fastq::Writer<W>,
+    mut w2: Option<&mut fastq::Writer<X>>,
+    singletons_writer: Option<&mut fastq::Writer<Y>>,
+) -> io::Result<Counts>
+where
+    F: FnMut(&mut bam::Record) -> io::Result<usize>,
+    W: Write,
+    X: Write,
+    Y: Write,
+{
+    const SKIPPED_FLAGS: u16 = flags::SECONDARY | flags::SUPPLEMENTARY;
+
+    let mut src = bam::Record::default();
+    let mut dst = fastq::Record::default();
+
+    // name => (index, is read 1, record)
+    let mut unpaired: HashMap<Vec<u8>, (u64, bool, fastq::Record)> = HashMap::new();
+    let mut counts = Counts::default();
+    let mut i = 0;
+
+    while read_record(&mut src)? != 0 {
+        if src.flags() & SKIPPED_FLAGS != 0 {
+            counts.skipped += 1;
+            continue;
+        }
+
+        copy_fastq_record(&src, &mut dst);
+
+        if !src.is_read_1() && !src.is_read_2() {
+            w1.write_record(&dst)?;
+            counts.single += 1;
+            continue;
+        }
+
+        match unpaired.remove(src.name()) {
+            Some((_, mate_is_read_1, mate)) => {
+                let (s1, s2) = match (src.is_read_1(), mate_is_read_1) {
+                    (false, true) => (&mate, &dst),
+                    (true, false) => (&dst, &mate),
+                    _ => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "{}: mates have the same segment flags",
+                                String::from_utf8_lossy(src.name())
+                            ),
+                        ));
+                    }
+                };
+
+                w1.write_record(s1)?;
+
+                match w2.as_mut() {
+                    Some(w2) => w2.write_record(s2)?,
+                    None => w1.write_record(s2)?,
+                }
+
+                counts.pairs += 1;
+            }
+            None => {
+                unpaired.insert(src.name().to_vec(), (i, src.is_read_1(), dst.clone()));
+            }
+        }
+
+        i += 1;
+    }
+
+    let mut singletons: Vec<_> = unpaired.into_values().collect();
+    counts.singletons = singletons.len() as u64;
+
+    if let Some(writer) = singletons_writer {
+        singletons.sort_unstable_by_key(|(i, _, _)| *i);
+
+        for (_, _, record) in &singletons {
+            writer.write_record(record)?;
+        }
+    }
+
+    Ok(counts)
+}
+
+// Copies a BAM record to a FASTQ record. Mates get a pair suffix (`/1` or `/2`), and reads mapped
+// to the reverse strand are reverse complemented back to their original orientation.
+fn copy_fastq_record(src: &bam::Record, dst: &mut fastq::Record) {
+    dst.clear();
+
+    dst.name_mut().push(NAME_PREFIX);
+    dst.name_mut().extend_from_slice(src.name());
+
+    if src.is_read_1() {
+        dst.name_mut().extend_from_slice(b"/1");
+    } else if src.is_read_2() {
+        dst.name_mut().extend_from_slice(b"/2");
+    }
+
+    dst.sequence_mut().extend_from_slice(src.sequence());
+    dst.plus_line_mut().push(b'+');
+
+    if src.quality_scores().is_empty() {
+        dst.quality_scores_mut()
+            .resize(src.sequence().len(), MISSING_QUALITY_SCORE);
+    } else {
+        dst.quality_scores_mut()
+            .extend_from_slice(src.quality_scores());
+    }
+
+    if src.flags() & flags::REVERSE_COMPLEMENTED != 0 {
+        dst.reverse_complement();
+    }
+}
+
+// Reads the first records of the source to detect its quality score encoding.
+fn detect_encoding(src: &str) -> anyhow::Result<Encoding> {
+    let mut reader = fastq::open(src).with_context(|| format!("Could not open file: {}", src))?;
+
+    let mut record = fastq::Record::default();
+    let mut detector = Detector::default();
+
+    for _ in 0..DETECTION_RECORD_COUNT {
+        if reader.read_record(&mut record)? == 0 {
+            break;
+        }
+
+        detector.add(record.quality_scores());
+    }
+
+    let encoding = detector.encoding().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Could not detect the quality score encoding of {}. Set --quality-from.",
+                src
+            ),
+        )
+    })?;
+
+    info!("detected quality score encoding: {}", encoding);
+
+    Ok(encoding)
+}
+
+fn convert_fastq_quality(
+    metrics: &Metrics,
+    src: &str,
+    dst: Option<&str>,
+    src_encoding: Encoding,
+    dst_encoding: Encoding,
+) -> anyhow::Result<()> {
+    let mut reader = fastq::open(src).with_context(|| format!("Could not open file: {}", src))?;
+    reader.set_metrics(metrics.clone());
+
+    let mut writer = match dst {
+        Some(dst) => {
+            fastq::create(dst).with_context(|| format!("Could not create file: {}", dst))?
+        }
+        None => fastq::Writer::new(stdout()),
+    };
+
+    writer.set_metrics(metrics.clone());
+
+    info!(
+        "converting quality scores from {} to {}",
+        src_encoding, dst_encoding
+    );
+
+    let _stage = metrics.stage("converting records");
+    let n = recode_fastq(&mut reader, &mut writer, src_encoding, dst_encoding)
+        .with_context(|| format!("Could not convert file: {}", src))?;
+
+    info!("converted {} records", n);
+
+    Ok(())
+}
+
+fn recode_fastq<R, W>(
+    reader: &mut fastq::Reader<R>,
+    writer: &mut fastq::Writer<W>,
+    src_encoding: Encoding,
+    dst_encoding: Encoding,
+) -> io::Result<u64>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut record = fastq::Record::default();
+    let mut n = 0;
+
+    while reader.read_record(&mut record)? != 0 {
+        quality::recode(record.quality_scores_mut(), src_encoding, dst_encoding).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", String::from_utf8_lossy(record.name()), e),
+            )
+        })?;
+
+        writer.write_record(&record)?;
+        n += 1;
+    }
+
+    Ok(n)
+}
+
+fn fastq_to_fasta<R, W>(
+    reader: &mut fastq::Reader<R>,
+    writer: &mut fasta::Writer<W>,
+) -> io::Result<u64>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut src = fastq::Record::default();
+    let mut dst = fasta::Record::default();
+
+    let mut n = 0;
+
+    while reader.read_record(&mut src)? != 0 {
+        copy_record(&src, &mut dst);
+        writer.write_record(&dst)?;
+        n += 1;
+    }
+
+    Ok(n)
+}
+
+fn fasta_to_fastq<R, S, W>(
+    reader: &mut fasta::Reader<R>,
+    qual_reader: &mut fasta::QualReader<S>,
+    writer: &mut fastq::Writer<W>,
+) -> io::Result<u64>
+where
+    R: BufRead,
+    S: BufRead,
+    W: Write,
+{
+    let mut sequence = fasta::Record::default();
+    let mut quality_scores = fasta::Record::default();
+    let mut dst = fastq::Record::default();
+
+    let mut n = 0;
+
+    loop {
+        match (
+            reader.read_record(&mut sequence)?,
+            qual_reader.read_record(&mut quality_scores)?,
+        ) {
+            (0, 0) => break,
+            (0, _) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "FASTA unexpectedly ended before QUAL",
+                ));
+            }
+            (_, 0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "QUAL unexpectedly ended before FASTA",
+                ));
+            }
+            (_, _) => {
+                zip_record(&sequence, &quality_scores, &mut dst)?;
+                writer.write_record(&dst)?;
+                n += 1;
+            }
+        }
+    }
+
+    Ok(n)
+}
+
+// Joins a FASTA record and its QUAL record into a FASTQ record.
+fn zip_record(
+    sequence: &fasta::Record,
+    quality_scores: &fasta::Record,
+    dst: &mut fastq::Record,
+) -> io::Result<()> {
+    if sequence.id() != quality_scores.id() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "FASTA and QUAL names mismatch: expected '{}', got '{}'",
+                String::from_utf8_lossy(sequence.id()),
+                String::from_utf8_lossy(quality_scores.id())
+            ),
+        ));
+    }
+
+    if sequence.sequence().len() != quality_scores.sequence().len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "FASTA and QUAL lengths mismatch for '{}': {} bases, {} quality scores",
+                String::from_utf8_lossy(sequence.id()),
+                sequence.sequence().len(),
+                quality_scores.sequence().len()
+            ),
+        ));
+    }
+
+    dst.clear();
+
+    dst.name_mut().push(NAME_PREFIX);
+    dst.name_mut().extend_from_slice(sequence.name());
+    dst.sequence_mut().extend_from_slice(sequence.sequence());
+    dst.plus_line_mut().push(b'+');
+    dst.quality_scores_mut()
+        .extend_from_slice(quality_scores.sequence());
+
+    Ok(())
+}
+
+// Copies the name, without the leading `@`, and sequence of a FASTQ record to a FASTA record.
+fn copy_record(src: &fastq::Record, dst: &mut fasta::Record) {
+    dst.clear();
+
+    let name = src.name();
+    let name = name.strip_prefix(&[NAME_PREFIX]).unwrap_or(name);
+
+    dst.name_mut().extend_from_slice(name);
+    dst.sequence_mut().extend_from_slice(src.sequence());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fastq_to_fasta() -> io::Result<()> {
+        let data = b"@r0 1:N:0\nACGTAC\n+\nIIIIII\n@r1\nTT\n+\nII\n";
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fasta::Writer::new(Vec::new());
+        writer.set_line_width(4);
+
+        let n = fastq_to_fasta(&mut reader, &mut writer)?;
+
+        assert_eq!(n, 2);
+        assert_eq!(writer.get_ref(), b">r0 1:N:0\nACGT\nAC\n>r1\nTT\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fastq_to_tab_paired() -> io::Result<()> {
+        let r1_data = b"@r0/1\nACGT\n+\nIIII\n";
+        let r2_data = b"@r0/2\nTTGG\n+\nABCD\n";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut writer = tab::Writer::new(Vec::new());
+
+        assert_eq!(fastq_to_tab_paired(&mut r1, &mut r2, &mut writer)?, 1);
+        assert_eq!(writer.get_ref(), b"r0/1\tACGT\tIIII\tr0/2\tTTGG\tABCD\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tab_to_fastq() -> io::Result<()> {
+        let data = b"r0/1\tACGT\tIIII\tr0/2\tTTGG\tABCD\nr1\tAC\tII\n";
+
+        let mut reader = tab::Reader::new(&data[..]);
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut w2 = fastq::Writer::new(Vec::new());
+
+        assert_eq!(tab_to_fastq(&mut reader, &mut w1, Some(&mut w2))?, 2);
+        assert_eq!(w1.get_ref(), b"@r0/1\nACGT\n+\nIIII\n@r1\nAC\n+\nII\n");
+        assert_eq!(w2.get_ref(), b"@r0/2\nTTGG\n+\nABCD\n");
+
+        let mut reader = tab::Reader::new(&data[..]);
+        let mut w1 = fastq::Writer::new(Vec::new());
+
+        tab_to_fastq(&mut reader, &mut w1, None::<&mut fastq::Writer<Vec<u8>>>)?;
+        assert_eq!(
+            w1.get_ref(),
+            b"@r0/1\nACGT\n+\nIIII\n@r0/2\nTTGG\n+\nABCD\n@r1\nAC\n+\nII\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_bam_header() {
+        let read_group: ReadGroup = "ID:rg0\tSM:sample0".parse().unwrap();
+        let text = build_bam_header(Some(&read_group));
+
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some("@HD\tVN:1.6\tSO:unsorted\tGO:query"));
+        assert_eq!(lines.next(), Some("@RG\tID:rg0\tSM:sample0"));
+        assert!(lines.next().unwrap().starts_with("@PG\tID:fq\t"));
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn test_fastq_to_bam_paired() -> io::Result<()> {
+        use std::io::Read;
+
+        use flate2::read::MultiGzDecoder;
+
+        let r1_data = b"@r0/1 1:N:0\nACGT\n+\nIIII\n";
+        let r2_data = b"@r0/2 2:N:0\nTT\n+\n!!\n";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut writer = bam::Writer::new(Vec::new());
+
+        let n = fastq_to_bam_paired(&mut r1, &mut r2, &mut writer, Some(b"rg0"))?;
+        writer.finish()?;
+
+        assert_eq!(n, 1);
+
+        let mut buf = Vec::new();
+        MultiGzDecoder::new(&writer.get_ref()[..]).read_to_end(&mut buf)?;
+
+        // block_size (4) + fixed fields (32) + name (3) + sequence + quality scores + RG (7)
+        assert_eq!(
+            buf.len(),
+            (4 + 32 + 3 + 2 + 4 + 7) + (4 + 32 + 3 + 1 + 2 + 7)
+        );
+
+        // flags
+        assert_eq!(&buf[18..20], &77u16.to_le_bytes());
+        assert_eq!(&buf[52 + 18..52 + 20], &141u16.to_le_bytes());
+
+        let r1_data = b"@r0/1\nACGT\n+\nIIII\n";
+        let r2_data = b"@r1/2\nACGT\n+\nIIII\n";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut writer = bam::Writer::new(Vec::new());
+
+        assert!(fastq_to_bam_paired(&mut r1, &mut r2, &mut writer, None).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_bam_record() {
+        let src = fastq::Record::new("@r0/1 1:N:0", "ACGT", "+", "IIII");
+        let mut dst = bam::Record::default();
+
+        copy_bam_record(&src, flags::UNMAPPED, Some(b"rg0"), &mut dst);
+
+        let mut expected = bam::Record::new("r0", flags::UNMAPPED, "ACGT", "IIII");
+        *expected.read_group_mut() = Some(b"rg0".to_vec());
+
+        assert_eq!(dst, expected);
+    }
+
+    #[test]
+    fn test_alignments_to_fastq() -> io::Result<()> {
+        let data = [
+            "@HD\tVN:1.6\tSO:coordinate",
+            "r0\t99\tsq0\t1\t60\t4M\t=\t5\t8\tACGT\tIII5",
+            "r1\t0\tsq0\t2\t60\t2M\t*\t0\t0\tTT\tII",
+            "r2\t73\tsq0\t3\t60\t2M\t=\t3\t0\tGG\tII",
+            "r0\t2195\tsq0\t5\t60\t2H2M\t=\t1\t-8\tAC\tII",
+            "r0\t147\tsq0\t5\t60\t4M\t=\t1\t-8\tAACC\t#5II",
+            "r3\t133\tsq0\t9\t0\t*\t=\t9\t0\tNN\t*",
+            "",
+        ]
+        .join("\n");
+
+        let mut reader = bam::SamReader::new(data.as_bytes());
+        reader.read_header()?;
+
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut w2 = fastq::Writer::new(Vec::new());
+        let mut singletons_writer = fastq::Writer::new(Vec::new());
+
+        let counts = alignments_to_fastq(
+            |record| reader.read_record(record),
+            &mut w1,
+            Some(&mut w2),
+            Some(&mut singletons_writer),
+        )?;
+
+        assert_eq!(
+            counts,
+            Counts {
+                single: 1,
+                pairs: 1,
+                singletons: 2,
+                skipped: 1,
+            }
+        );
+
+        assert_eq!(w1.get_ref(), b"@r1\nTT\n+\nII\n@r0/1\nACGT\n+\nIII5\n");
+        assert_eq!(w2.get_ref(), b"@r0/2\nGGTT\n+\nII5#\n");
+        assert_eq!(
+            singletons_writer.get_ref(),
+            b"@r2/1\nGG\n+\nII\n@r3/2\nNN\n+\n!!\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_alignments_to_fastq_with_interleaved_output() -> io::Result<()> {
+        let data = b"r0\t141\t*\t0\t0\t*\t*\t0\t0\tTT\tII\nr0\t77\t*\t0\t0\t*\t*\t0\t0\tAC\tII\n";
+        let mut reader = bam::SamReader::new(&data[..]);
+
+        let mut writer = fastq::Writer::new(Vec::new());
+
+        let counts = alignments_to_fastq(
+            |record| reader.read_record(record),
+            &mut writer,
+            None::<&mut fastq::Writer<Vec<u8>>>,
+            None::<&mut fastq::Writer<Vec<u8>>>,
+        )?;
+
+        assert_eq!(counts.pairs, 1);
+        assert_eq!(writer.get_ref(), b"@r0/1\nAC\n+\nII\n@r0/2\nTT\n+\nII\n");
+
+        let data = b"r0\t77\t*\t0\t0\t*\t*\t0\t0\tTT\tII\nr0\t77\t*\t0\t0\t*\t*\t0\t0\tAC\tII\n";
+        let mut reader = bam::SamReader::new(&data[..]);
+
+        assert!(alignments_to_fastq(
+            |record| reader.read_record(record),
+            &mut writer,
+            None::<&mut fastq::Writer<Vec<u8>>>,
+            None::<&mut fastq::Writer<Vec<u8>>>,
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_recode_fastq() -> io::Result<()> {
+        let data = b"@r0\nACGT\n+\nBFhh\n";
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+        let n = recode_fastq(
+            &mut reader,
+            &mut writer,
+            Encoding::Phred64,
+            Encoding::Phred33,
+        )?;
+
+        assert_eq!(n, 1);
+        assert_eq!(writer.get_ref(), b"@r0\nACGT\n+\n#'II\n");
+
+        let data = b"@r0\nACGT\n+\nBF#h\n";
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+        assert!(recode_fastq(
+            &mut reader,
+            &mut writer,
+            Encoding::Phred64,
+            Encoding::Phred33
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fasta_to_fastq() -> io::Result<()> {
+        let fasta_data = b">r0 LN:4\nACGT\n>r1\nTT\nG\n";
+        let qual_data = b">r0\n40 40 30 20\n>r1\n0 10\n20\n";
+
+        let mut reader = fasta::Reader::new(&fasta_data[..]);
+        let mut qual_reader = fasta::QualReader::new(&qual_data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+
+        let n = fasta_to_fastq(&mut reader, &mut qual_reader, &mut writer)?;
+
+        assert_eq!(n, 2);
+        assert_eq!(
+            writer.get_ref(),
+            b"@r0 LN:4\nACGT\n+\nII?5\n@r1\nTTG\n+\n!+5\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fasta_to_fastq_with_mismatches() {
+        let fasta_data = b">r0\nACGT\n";
+
+        let mut writer = fastq::Writer::new(Vec::new());
+
+        let mut reader = fasta::Reader::new(&fasta_data[..]);
+        let mut qual_reader = fasta::QualReader::new(&b">r1\n40 40 40 40\n"[..]);
+        assert!(fasta_to_fastq(&mut reader, &mut qual_reader, &mut writer).is_err());
+
+        let mut reader = fasta::Reader::new(&fasta_data[..]);
+        let mut qual_reader = fasta::QualReader::new(&b">r0\n40 40\n"[..]);
+        assert!(fasta_to_fastq(&mut reader, &mut qual_reader, &mut writer).is_err());
+
+        let mut reader = fasta::Reader::new(&fasta_data[..]);
+        let mut qual_reader = fasta::QualReader::new(&b""[..]);
+        assert!(fasta_to_fastq(&mut reader, &mut qual_reader, &mut writer).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+use std::io::{self, BufRead, BufWriter, Write};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{fastq, metrics::Metrics};
+
+const LINE_FEED: u8 = b'\n';
+const CARRIAGE_RETURN: u8 = b'\r';
+
+const LINES_PER_RECORD: u64 = 4;
+const SEQUENCE_LINE: u64 = 1;
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Counts {
+    records: u64,
+    bases: u64,
+    bytes: u64,
+}
+
+pub fn count(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r2_src = matches.value_of("r2-src");
+
+    let with_bases = matches.is_present("bases");
+
+    info!("fq-count start");
+
+    let metrics = Metrics::new();
+    let stage = metrics.stage("counting records");
+
+    let mut counts = count_src(&metrics, r1_src, with_bases)?;
+
+    if let Some(r2_src) = r2_src {
+        let r2_counts = count_src(&metrics, r2_src, with_bases)?;
+
+        if r2_counts.records != counts.records {
+            return Err(io::Error::from(io::ErrorKind::InvalidData)).with_context(|| {
+                format!(
+                    "r1-src and r2-src have different numbers of records: {} != {}",
+                    counts.records, r2_counts.records
+                )
+            });
+        }
+
+        counts.bases += r2_counts.bases;
+    }
+
+    drop(stage);
+
+    let stdout = io::stdout();
+    let mut writer = BufWriter::new(stdout.lock());
+
+    if with_bases {
+        writeln!(writer, "{}\t{}", counts.records, counts.bases)
+    } else {
+        writeln!(writer, "{}", counts.records)
+    }
+    .context("Could not write counts to stdout")?;
+
+    metrics.log();
+
+    info!("fq-count end");
+
+    Ok(())
+}
+
+fn count_src(metrics: &Metrics, src: &str, with_bases: bool) -> anyhow::Result<Counts> {
+    let reader = fastq::open(src)
+        .map(fastq::Reader::into_inner)
+        .with_context(|| format!("Could not open file: {}", src))?;
+
+    let counts = if with_bases {
+        count_records_and_bases(reader)
+    } else {
+        count_records(reader)
+    }
+    .with_context(|| format!("Could not read file: {}", src))?;
+
+    info!("{}: {} records", src, counts.records);
+
+    metrics.add_records_read(counts.records);
+    metrics.add_bytes_read(counts.bytes);
+
+    Ok(counts)
+}
+
+// Records are counted by their line feeds, without parsing them.
+fn count_records<R>(mut reader: R) -> io::Result<Counts>
+where
+    R: BufRead,
+{
+    let mut line_count = 0;
+    let mut bytes = 0;
+    let mut last_byte = LINE_FEED;
+
+    loop {
+        let len = {
+            let buf = reader.fill_buf()?;
+
+            if buf.is_empty() {
+                break;
+            }
+
+            line_count += bytecount::count(buf, LINE_FEED) as u64;
+            last_byte = buf[buf.len() - 1];
+
+            buf.len()
+        };
+
+        reader.consume(len);
+        bytes += len as u64;
+    }
+
+    // The last line may not have a line feed.
+    if last_byte != LINE_FEED {
+        line_count += 1;
+    }
+
+    build_counts(line_count, 0, bytes)
+}
+
+// This is the same as `count_records`, but it also sums the length of each sequence line.
+fn count_records_and_bases<R>(mut reader: R) -> io::Result<Counts>
+where
+    R: BufRead,
+{
+    let mut line_count = 0;
+    let mut bases = 0;
+    let mut bytes = 0;
+
+    // The length and last byte of the current line, which can span multiple buffers.
+    let mut line_len = 0;
+    let mut last_byte = LINE_FEED;
+
+    loop {
+        let len = {
+            let buf = reader.fill_buf()?;
+
+            if buf.is_empty() {
+                break;
+            }
+
+            let mut rest = buf;
+
+            while let Some(i) = rest.iter().position(|&b| b == LINE_FEED) {
+                if i > 0 {
+                    last_byte = rest[i - 1];
+                }
+
+                line_len += i as u64;
+                end_line(&mut line_count, &mut bases, line_len, last_byte);
+                line_len = 0;
+                last_byte = LINE_FEED;
+
+                rest = &rest[i + 1..];
+            }
+
+            if let Some(&b) = rest.last() {
+                line_len += rest.len() as u64;
+                last_byte = b;
+            }
+
+            buf.len()
+        };
+
+        reader.consume(len);
+        bytes += len as u64;
+    }
+
+    if line_len > 0 {
+        end_line(&mut line_count, &mut bases, line_len, last_byte);
+    }
+
+    build_counts(line_count, bases, bytes)
+}
+
+fn end_line(line_count: &mut u64, bases: &mut u64, line_len: u64, last_byte: u8) {
+    if *line_count % LINES_PER_RECORD == SEQUENCE_LINE {
+        *bases += line_len - u64::from(last_byte == CARRIAGE_RETURN);
+    }
+
+    *line_count += 1;
+}
+
+fn build_counts(line_count: u64, bases: u64, bytes: u64) -> io::Result<Counts> {
+    if !line_count.is_multiple_of(LINES_PER_RECORD) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "incomplete record: line count ({}) is not a multiple of {}",
+                line_count, LINES_PER_RECORD
+            ),
+        ));
+    }
+
+    Ok(Counts {
+        records: line_count / LINES_PER_RECORD,
+        bases,
+        bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_records() -> io::Result<()> {
+        let data = b"@r0\nACGT\n+\nIIII\n@r1\nAC\n+\nII\n";
+        assert_eq!(count_records(&data[..])?.records, 2);
+
+        let data = b"@r0\nACGT\n+\nIIII\n@r1\nAC\n+\nII";
+        assert_eq!(count_records(&data[..])?.records, 2);
+
+        assert_eq!(count_records(&b""[..])?, Counts::default());
+
+        let data = b"@r0\nACGT\n+\nIIII\n@r1\nAC\n";
+        assert!(count_records(&data[..]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_records_and_bases() -> io::Result<()> {
+        let data = b"@r0\nACGT\n+\nIIII\n@r1\r\nAC\r\n+\r\nII\r\n@r2\n\n+\n\n";
+
+        assert_eq!(
+            count_records_and_bases(&data[..])?,
+            Counts {
+                records: 3,
+                bases: 6,
+                bytes: data.len() as u64,
+            }
+        );
+
+        // Lines spanning buffers
+        let reader = io::BufReader::with_capacity(3, &data[..]);
+        assert_eq!(count_records_and_bases(reader)?.bases, 6);
+
+        let data = b"@r0\nACGT\n+\nIIII\n@r1\nAC\n+\nII";
+        assert_eq!(count_records_and_bases(&data[..])?.bases, 6);
+
+        let data = b"@r0\nACGT\n+\n";
+        assert!(count_records_and_bases(&data[..]).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,524 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    dedup::{build_key, cluster_umis, DuplicateFilter, Keep, UmiSource},
+    fastq::{self, Record},
+    metrics::Metrics,
+};
+
+/// UMI deduplication options.
+#[derive(Clone, Copy, Debug)]
+struct UmiOptions {
+    source: UmiSource,
+    max_distance: usize,
+    prefix_length: usize,
+    keep: Keep,
+}
+
+pub fn dedup(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r1_dst = matches.value_of("r1-dst").unwrap();
+
+    let r2_src = matches.value_of("r2-src");
+    let r2_dst = matches.value_of("r2-dst");
+
+    match (r2_src, r2_dst) {
+        (Some(r2_src), None) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-dst for {}", r2_src));
+        }
+        (None, Some(r2_dst)) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-src for {}", r2_dst));
+        }
+        _ => {}
+    }
+
+    info!("fq-dedup start");
+
+    let metrics = Metrics::new();
+
+    if matches.is_present("umi-from") {
+        let options = UmiOptions {
+            source: matches.value_of_t("umi-from").unwrap_or_else(|e| e.exit()),
+            max_distance: matches
+                .value_of_t("umi-distance")
+                .unwrap_or_else(|e| e.exit()),
+            prefix_length: matches
+                .value_of_t("prefix-length")
+                .unwrap_or_else(|e| e.exit()),
+            keep: matches.value_of_t("keep").unwrap_or_else(|e| e.exit()),
+        };
+
+        dedup_by_umi(&metrics, r1_src, r1_dst, r2_src, r2_dst, options)?;
+    } else {
+        let with_names = matches.is_present("with-names");
+
+        let filter = if matches.is_present("approximate") {
+            info!("using approximate duplicate filter");
+            DuplicateFilter::approximate()
+        } else {
+            DuplicateFilter::exact()
+        };
+
+        dedup_by_sequence(&metrics, r1_src, r1_dst, r2_src, r2_dst, filter, with_names)?;
+    }
+
+    metrics.log();
+
+    info!("fq-dedup end");
+
+    Ok(())
+}
+
+fn dedup_by_umi(
+    metrics: &Metrics,
+    r1_src: &str,
+    r1_dst: &str,
+    r2_src: Option<&str>,
+    r2_dst: Option<&str>,
+    options: UmiOptions,
+) -> anyhow::Result<()> {
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    r1.set_metrics(metrics.clone());
+
+    let templates = if let Some(r2_src) = r2_src {
+        info!("reading paired end reads");
+
+        let mut r2 =
+            fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+        r2.set_metrics(metrics.clone());
+
+        let _stage = metrics.stage("reading records");
+        read_paired(&mut r1, &mut r2)?
+    } else {
+        info!("reading single end reads");
+
+        let _stage = metrics.stage("reading records");
+        read_single(&mut r1).with_context(|| format!("Could not read file: {}", r1_src))?
+    };
+
+    let stage = metrics.stage("deduplicating");
+    let kept = dedup_umis(&templates, options)?;
+    drop(stage);
+
+    info!(
+        "kept {} of {} records ({} duplicates removed)",
+        kept.len(),
+        templates.len(),
+        templates.len() - kept.len()
+    );
+
+    let _stage = metrics.stage("writing records");
+
+    let mut w1 =
+        fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+    w1.set_metrics(metrics.clone());
+
+    write_templates(&mut w1, &kept, 0)
+        .with_context(|| format!("Could not write file: {}", r1_dst))?;
+
+    if let Some(r2_dst) = r2_dst {
+        let mut w2 =
+            fastq::create(r2_dst).with_context(|| format!("Could not create file: {}", r2_dst))?;
+        w2.set_metrics(metrics.clone());
+
+        write_templates(&mut w2, &kept, 1)
+            .with_context(|| format!("Could not write file: {}", r2_dst))?;
+    }
+
+    Ok(())
+}
+
+// Records are streamed, keeping the first occurrence of each sequence (or pair of sequences), so
+// only the filter is held in memory.
+fn dedup_by_sequence(
+    metrics: &Metrics,
+    r1_src: &str,
+    r1_dst: &str,
+    r2_src: Option<&str>,
+    r2_dst: Option<&str>,
+    mut filter: DuplicateFilter,
+    with_names: bool,
+) -> anyhow::Result<()> {
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    r1.set_metrics(metrics.clone());
+
+    let mut w1 =
+        fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+    w1.set_metrics(metrics.clone());
+
+    let _stage = metrics.stage("deduplicating");
+
+    let (kept, total) = match (r2_src, r2_dst) {
+        (Some(r2_src), Some(r2_dst)) => {
+            info!("deduplicating paired end reads");
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            r2.set_metrics(metrics.clone());
+
+            let mut w2 = fastq::create(r2_dst)
+                .with_context(|| format!("Could not create file: {}", r2_dst))?;
+            w2.set_metrics(metrics.clone());
+
+            dedup_paired(&mut r1, &mut r2, &mut w1, &mut w2, &mut filter, with_names)?
+        }
+        _ => {
+            info!("deduplicating single end reads");
+            dedup_single(&mut r1, &mut w1, &mut filter, with_names)
+                .with_context(|| format!("Could not deduplicate file: {}", r1_src))?
+        }
+    };
+
+    info!(
+        "kept {} of {} records ({} duplicates removed)",
+        kept,
+        total,
+        total - kept
+    );
+
+    Ok(())
+}
+
+fn dedup_single<R, W>(
+    reader: &mut fastq::Reader<R>,
+    writer: &mut fastq::Writer<W>,
+    filter: &mut DuplicateFilter,
+    with_names: bool,
+) -> io::Result<(u64, u64)>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut record = Record::default();
+    let mut key = Vec::new();
+
+    let mut kept = 0;
+    let mut total = 0;
+
+    while reader.read_record(&mut record)? != 0 {
+        total += 1;
+
+        build_key(&[&record], with_names, &mut key);
+
+        if filter.insert(&key) {
+            writer.write_record(&record)?;
+            kept += 1;
+        }
+    }
+
+    Ok((kept, total))
+}
+
+fn dedup_paired<R, S, W, X>(
+    r1: &mut fastq::Reader<R>,
+    r2: &mut fastq::Reader<S>,
+    w1: &mut fastq::Writer<W>,
+    w2: &mut fastq::Writer<X>,
+    filter: &mut DuplicateFilter,
+    with_names: bool,
+) -> anyhow::Result<(u64, u64)>
+where
+    R: BufRead,
+    S: BufRead,
+    W: Write,
+    X: Write,
+{
+    let mut s1 = Record::default();
+    let mut s2 = Record::default();
+    let mut key = Vec::new();
+
+    let mut kept = 0;
+    let mut total = 0;
+
+    loop {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, len) if len > 0 => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r1-src unexpectedly ended before r2-src");
+            }
+            (len, 0) if len > 0 => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r2-src unexpectedly ended before r1-src");
+            }
+            (_, _) => {
+                total += 1;
+
+                build_key(&[&s1, &s2], with_names, &mut key);
+
+                if filter.insert(&key) {
+                    w1.write_record(&s1)?;
+                    w2.write_record(&s2)?;
+                    kept += 1;
+                }
+            }
+        }
+    }
+
+    Ok((kept, total))
+}
+
+// A template is the list of records from the same molecule, i.e., one record for single end reads
+// or both mates for paired end reads.
+type Template = Vec<Record>;
+
+fn read_single<R>(reader: &mut fastq::Reader<R>) -> io::Result<Vec<Template>>
+where
+    R: BufRead,
+{
+    let mut templates = Vec::new();
+    let mut record = Record::default();
+
+    while reader.read_record(&mut record)? != 0 {
+        templates.push(vec![record.clone()]);
+    }
+
+    Ok(templates)
+}
+
+fn read_paired<R, S>(
+    r1: &mut fastq::Reader<R>,
+    r2: &mut fastq::Reader<S>,
+) -> anyhow::Result<Vec<Template>>
+where
+    R: BufRead,
+    S: BufRead,
+{
+    let mut templates = Vec::new();
+
+    let mut s1 = Record::default();
+    let mut s2 = Record::default();
+
+    loop {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, len) if len > 0 => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r1-src unexpectedly ended before r2-src");
+            }
+            (len, 0) if len > 0 => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r2-src unexpectedly ended before r1-src");
+            }
+            (_, _) => templates.push(vec![s1.clone(), s2.clone()]),
+        }
+    }
+
+    Ok(templates)
+}
+
+// Groups templates by UMI and sequence prefix, clusters similar UMIs within each group, and returns
+// a representative of each cluster in order of first occurrence.
+//
+// The UMI is always read from the first record of a template. The sequence prefix is taken from
+// each record, after an inline UMI.
+fn dedup_umis(templates: &[Template], options: UmiOptions) -> anyhow::Result<Vec<Template>> {
+    let mut groups: HashMap<Vec<u8>, HashMap<&[u8], Vec<usize>>> = HashMap::new();
+
+    for (i, template) in templates.iter().enumerate() {
+        let first = &template[0];
+
+        let (umi, offset) = options.source.extract(first).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Missing UMI in record: {}",
+                    String::from_utf8_lossy(first.name())
+                ),
+            )
+        })?;
+
+        let mut key = Vec::new();
+
+        for (j, record) in template.iter().enumerate() {
+            let start = if j == 0 { offset } else { 0 };
+            let sequence = &record.sequence()[start..];
+            let end = options.prefix_length.min(sequence.len());
+
+            key.extend_from_slice(&sequence[..end]);
+            key.push(b'\t');
+        }
+
+        groups
+            .entry(key)
+            .or_default()
+            .entry(umi)
+            .or_default()
+            .push(i);
+    }
+
+    let mut representatives = Vec::new();
+
+    for umis in groups.values() {
+        let umis: Vec<_> = umis.iter().collect();
+        let counts: Vec<_> = umis.iter().map(|(umi, ids)| (**umi, ids.len())).collect();
+
+        for cluster in cluster_umis(&counts, options.max_distance) {
+            let mut ids: Vec<usize> = cluster
+                .iter()
+                .flat_map(|&k| umis[k].1.iter().copied())
+                .collect();
+
+            ids.sort_unstable();
+
+            let template = build_representative(templates, &ids, options.keep);
+            representatives.push((ids[0], template));
+        }
+    }
+
+    representatives.sort_unstable_by_key(|(i, _)| *i);
+
+    Ok(representatives.into_iter().map(|(_, t)| t).collect())
+}
+
+// Builds a representative template from each record position, e.g., read 1 from all read 1s.
+fn build_representative(templates: &[Template], ids: &[usize], keep: Keep) -> Template {
+    let record_count = templates[ids[0]].len();
+
+    (0..record_count)
+        .map(|j| {
+            let records: Vec<_> = ids.iter().map(|&i| &templates[i][j]).collect();
+            keep.build(&records)
+        })
+        .collect()
+}
+
+// Writes the record at position `j` of each template, e.g., read 2 for `j = 1`.
+fn write_templates<W>(
+    writer: &mut fastq::Writer<W>,
+    templates: &[Template],
+    j: usize,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    for template in templates {
+        writer.write_record(&template[j])?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_options(source: UmiSource) -> UmiOptions {
+        UmiOptions {
+            source,
+            max_distance: 1,
+            prefix_length: 4,
+            keep: Keep::BestQuality,
+        }
+    }
+
+    #[test]
+    fn test_dedup_umis_with_name() -> anyhow::Result<()> {
+        let data = b"@r0:AAAA\nACGTACGT\n+\n!!!!!!!!
+@r1:AAAT\nACGTACGT\n+\nIIIIIIII
+@r2:GGGG\nACGTACGT\n+\nIIIIIIII
+@r3:AAAA\nTTTTACGT\n+\nIIIIIIII
+";
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let templates = read_single(&mut reader)?;
+
+        let kept = dedup_umis(&templates, build_options(UmiSource::Name))?;
+        let names: Vec<_> = kept.iter().map(|t| t[0].name()).collect();
+        assert_eq!(names, [&b"@r1:AAAT"[..], b"@r2:GGGG", b"@r3:AAAA"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedup_umis_with_sequence() -> anyhow::Result<()> {
+        let r1_data = b"@r0\nAAACCCCG\n+\nIIIIIIII
+@r1\nAAACCCCT\n+\nIIIIIIII
+@r2\nAAACGGGG\n+\nIIIIIIII
+";
+        let r2_data = b"@r0\nTTTT\n+\nIIII
+@r1\nTTTT\n+\nIIII
+@r2\nTTTT\n+\nIIII
+";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let templates = read_paired(&mut r1, &mut r2)?;
+
+        let kept = dedup_umis(&templates, build_options(UmiSource::Sequence(3)))?;
+        let names: Vec<_> = kept.iter().map(|t| t[1].name()).collect();
+        assert_eq!(names, [&b"@r0"[..], b"@r2"]);
+
+        let templates = vec![vec![Record::new("@r0", "AC", "+", "II")]];
+        assert!(dedup_umis(&templates, build_options(UmiSource::Sequence(3))).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedup_single() -> io::Result<()> {
+        let data = b"@r0\nACGT\n+\nIIII
+@r1\nACGT\n+\nIIII
+@r0\nACGT\n+\nIIII
+@r2\nTTTT\n+\nIIII
+";
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+        let mut filter = DuplicateFilter::exact();
+        let counts = dedup_single(&mut reader, &mut writer, &mut filter, false)?;
+        assert_eq!(counts, (2, 4));
+        assert_eq!(
+            writer.get_ref(),
+            b"@r0\nACGT\n+\nIIII\n@r2\nTTTT\n+\nIIII\n"
+        );
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+        let mut filter = DuplicateFilter::exact();
+        let counts = dedup_single(&mut reader, &mut writer, &mut filter, true)?;
+        assert_eq!(counts, (3, 4));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedup_paired() -> anyhow::Result<()> {
+        let r1_data = b"@r0\nACGT\n+\nIIII
+@r1\nACGT\n+\nIIII
+@r2\nACGT\n+\nIIII
+";
+        let r2_data = b"@r0\nTTTT\n+\nIIII
+@r1\nTTTT\n+\nIIII
+@r2\nGGGG\n+\nIIII
+";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut w2 = fastq::Writer::new(Vec::new());
+        let mut filter = DuplicateFilter::exact();
+
+        let counts = dedup_paired(&mut r1, &mut r2, &mut w1, &mut w2, &mut filter, false)?;
+        assert_eq!(counts, (2, 3));
+        assert_eq!(w2.get_ref(), b"@r0\nTTTT\n+\nIIII\n@r2\nGGGG\n+\nIIII\n");
+
+        let r2_data = b"@r0\nTTTT\n+\nIIII\n";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut filter = DuplicateFilter::exact();
+        assert!(dedup_paired(&mut r1, &mut r2, &mut w1, &mut w2, &mut filter, false).is_err());
+
+        Ok(())
+    }
+}
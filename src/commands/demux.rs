@@ -0,0 +1,293 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    demux::{read_samples, Demultiplexer, Sample},
+    fastq::{self, Record},
+    metrics::Metrics,
+};
+
+const UNDETERMINED_NAME: &str = "Undetermined";
+const DST_SUFFIX: &str = ".fastq.gz";
+
+pub fn demux(matches: &ArgMatches) -> anyhow::Result<()> {
+    let barcodes_src = matches.value_of("barcodes").unwrap();
+    let output_dir = matches.value_of("output-dir").unwrap();
+
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r2_src = matches.value_of("r2-src");
+
+    let i1_src = matches.value_of("i1");
+    let i2_src = matches.value_of("i2");
+
+    let max_mismatches = matches
+        .value_of_t("max-mismatches")
+        .unwrap_or_else(|e| e.exit());
+    let n_mode = matches.value_of_t("n-mode").unwrap_or_else(|e| e.exit());
+
+    info!("fq-demux start");
+
+    let samples = File::open(barcodes_src)
+        .map(BufReader::new)
+        .and_then(read_samples)
+        .with_context(|| format!("Could not read file: {}", barcodes_src))?;
+
+    if samples.is_empty() {
+        return Err(io::Error::from(io::ErrorKind::InvalidData))
+            .with_context(|| format!("No samples in {}", barcodes_src));
+    }
+
+    if samples.iter().any(|s| s.name() == UNDETERMINED_NAME) {
+        return Err(io::Error::from(io::ErrorKind::InvalidData)).with_context(|| {
+            format!(
+                "Sample name is reserved for undetermined reads: {}",
+                UNDETERMINED_NAME
+            )
+        });
+    }
+
+    info!("read {} samples", samples.len());
+
+    let demultiplexer = Demultiplexer::new(&samples, max_mismatches, n_mode)
+        .with_context(|| format!("Invalid barcodes in {}", barcodes_src))?;
+
+    let index_srcs: Vec<&str> = match (demultiplexer.barcode_count(), i1_src, i2_src) {
+        (1, None, None) => {
+            info!("reading inline barcodes from read 1");
+            Vec::new()
+        }
+        (1, Some(i1_src), None) => {
+            info!("reading barcodes from index read 1");
+            vec![i1_src]
+        }
+        (2, Some(i1_src), Some(i2_src)) => {
+            info!("reading barcodes from index reads 1 and 2");
+            vec![i1_src, i2_src]
+        }
+        (n, _, _) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput)).with_context(|| {
+                format!(
+                    "Samples have {} barcode(s), but {} index read source(s) were given",
+                    n,
+                    [i1_src, i2_src].iter().flatten().count()
+                )
+            });
+        }
+    };
+
+    let metrics = Metrics::new();
+
+    let read_srcs: Vec<&str> = [Some(r1_src), r2_src].iter().flatten().copied().collect();
+
+    let mut reads = open_all(&metrics, &read_srcs)?;
+    let mut indexes = open_all(&metrics, &index_srcs)?;
+
+    let names: Vec<&str> = samples
+        .iter()
+        .map(Sample::name)
+        .chain([UNDETERMINED_NAME])
+        .collect();
+
+    let mut writers = Vec::with_capacity(names.len());
+
+    for name in &names {
+        let mut sample_writers = Vec::with_capacity(reads.len());
+
+        for i in 0..reads.len() {
+            let dst = Path::new(output_dir).join(format!("{}_R{}{}", name, i + 1, DST_SUFFIX));
+
+            let mut writer = fastq::create(&dst)
+                .with_context(|| format!("Could not create file: {}", dst.display()))?;
+            writer.set_metrics(metrics.clone());
+
+            sample_writers.push(writer);
+        }
+
+        writers.push(sample_writers);
+    }
+
+    let stage = metrics.stage("demultiplexing records");
+    let counts = demux_records(&mut reads, &mut indexes, &demultiplexer, &mut writers)?;
+    drop(stage);
+
+    for (name, count) in names.iter().zip(&counts) {
+        info!("{}: {} records", name, count);
+    }
+
+    metrics.log();
+
+    info!("fq-demux end");
+
+    Ok(())
+}
+
+fn open_all(
+    metrics: &Metrics,
+    srcs: &[&str],
+) -> anyhow::Result<Vec<fastq::Reader<Box<dyn BufRead>>>> {
+    srcs.iter()
+        .map(|src| {
+            let mut reader =
+                fastq::open(src).with_context(|| format!("Could not open file: {}", src))?;
+            reader.set_metrics(metrics.clone());
+            Ok(reader)
+        })
+        .collect()
+}
+
+// Routes each template to the writers of its sample, or the last set of writers for undetermined
+// reads. Barcodes are read from the index reads or, if there are none, the start of read 1.
+//
+// This returns the number of templates written for each set of writers.
+fn demux_records<R, W>(
+    reads: &mut [fastq::Reader<R>],
+    indexes: &mut [fastq::Reader<R>],
+    demultiplexer: &Demultiplexer,
+    writers: &mut [Vec<fastq::Writer<W>>],
+) -> anyhow::Result<Vec<u64>>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut read_records = vec![Record::default(); reads.len()];
+    let mut index_records = vec![Record::default(); indexes.len()];
+
+    let mut counts = vec![0; writers.len()];
+    let undetermined_id = writers.len() - 1;
+
+    loop {
+        let has_reads = read_next(reads, &mut read_records)?;
+
+        if !indexes.is_empty() && read_next(indexes, &mut index_records)? != has_reads {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                .with_context(|| "Read and index sources have different numbers of records");
+        }
+
+        if !has_reads {
+            break;
+        }
+
+        let id = if index_records.is_empty() {
+            demultiplexer.find(&[read_records[0].sequence()])
+        } else {
+            let barcodes: Vec<_> = index_records.iter().map(|r| r.sequence()).collect();
+            demultiplexer.find(&barcodes)
+        };
+
+        let id = id.unwrap_or(undetermined_id);
+
+        for (writer, record) in writers[id].iter_mut().zip(&read_records) {
+            writer.write_record(record)?;
+        }
+
+        counts[id] += 1;
+    }
+
+    Ok(counts)
+}
+
+// Reads the next record of each reader, returning false when all are at EOF.
+fn read_next<R>(readers: &mut [fastq::Reader<R>], records: &mut [Record]) -> anyhow::Result<bool>
+where
+    R: BufRead,
+{
+    let mut eof_count = 0;
+
+    for (reader, record) in readers.iter_mut().zip(records.iter_mut()) {
+        if reader.read_record(record)? == 0 {
+            eof_count += 1;
+        }
+    }
+
+    if eof_count == 0 {
+        Ok(true)
+    } else if eof_count == readers.len() {
+        Ok(false)
+    } else {
+        Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+            .with_context(|| "Sources have different numbers of records")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demux::NMode;
+
+    fn build_writers(sample_count: usize, mate_count: usize) -> Vec<Vec<fastq::Writer<Vec<u8>>>> {
+        (0..sample_count)
+            .map(|_| {
+                (0..mate_count)
+                    .map(|_| fastq::Writer::new(Vec::new()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_demux_records_with_inline_barcodes() -> anyhow::Result<()> {
+        let samples = [
+            Sample::new("s1", vec![b"AAAA".to_vec()]),
+            Sample::new("s2", vec![b"CCCC".to_vec()]),
+        ];
+        let demultiplexer = Demultiplexer::new(&samples, 1, NMode::Mismatch)?;
+
+        let r1_data = b"@r0\nAAATGT\n+\nIIIIII\n@r1\nCCCCGT\n+\nIIIIII\n@r2\nGGGGGT\n+\nIIIIII\n";
+        let r2_data = b"@r0\nTT\n+\nII\n@r1\nTC\n+\nII\n@r2\nTG\n+\nII\n";
+
+        let mut reads = vec![
+            fastq::Reader::new(&r1_data[..]),
+            fastq::Reader::new(&r2_data[..]),
+        ];
+        let mut writers = build_writers(3, 2);
+
+        let counts = demux_records(&mut reads, &mut [], &demultiplexer, &mut writers)?;
+
+        assert_eq!(counts, [1, 1, 1]);
+        assert_eq!(writers[0][0].get_ref(), b"@r0\nAAATGT\n+\nIIIIII\n");
+        assert_eq!(writers[1][1].get_ref(), b"@r1\nTC\n+\nII\n");
+        assert_eq!(writers[2][0].get_ref(), b"@r2\nGGGGGT\n+\nIIIIII\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_demux_records_with_index_reads() -> anyhow::Result<()> {
+        let samples = [
+            Sample::new("s1", vec![b"AAAA".to_vec(), b"GGGG".to_vec()]),
+            Sample::new("s2", vec![b"AAAA".to_vec(), b"TTTT".to_vec()]),
+        ];
+        let demultiplexer = Demultiplexer::new(&samples, 0, NMode::Mismatch)?;
+
+        let r1_data = b"@r0\nACGT\n+\nIIII\n@r1\nACGT\n+\nIIII\n";
+        let i1_data = b"@r0\nAAAA\n+\nIIII\n@r1\nAAAA\n+\nIIII\n";
+        let i2_data = b"@r0\nTTTT\n+\nIIII\n@r1\nTTTA\n+\nIIII\n";
+
+        let mut reads = vec![fastq::Reader::new(&r1_data[..])];
+        let mut indexes = vec![
+            fastq::Reader::new(&i1_data[..]),
+            fastq::Reader::new(&i2_data[..]),
+        ];
+        let mut writers = build_writers(3, 1);
+
+        let counts = demux_records(&mut reads, &mut indexes, &demultiplexer, &mut writers)?;
+        assert_eq!(counts, [0, 1, 1]);
+
+        let mut reads = vec![fastq::Reader::new(&r1_data[..])];
+        let mut indexes = vec![
+            fastq::Reader::new(&i1_data[..16]),
+            fastq::Reader::new(&i2_data[..16]),
+        ];
+        let mut writers = build_writers(3, 1);
+        assert!(demux_records(&mut reads, &mut indexes, &demultiplexer, &mut writers).is_err());
+
+        Ok(())
+    }
+}
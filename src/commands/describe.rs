@@ -0,0 +1,229 @@
+use std::{
+    error, fmt,
+    io::{self, BufRead, BufWriter, Write},
+    str::FromStr,
+};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::{
+    fastq::{self, Record},
+    metrics::Metrics,
+    stats::Summary,
+};
+
+/// The output format of a summary.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Format {
+    Json,
+    Tsv,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct ParseFormatError(String);
+
+impl error::Error for ParseFormatError {}
+
+impl fmt::Display for ParseFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid format: expected json or tsv, got '{}'", self.0)
+    }
+}
+
+impl FromStr for Format {
+    type Err = ParseFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "tsv" => Ok(Self::Tsv),
+            _ => Err(ParseFormatError(s.into())),
+        }
+    }
+}
+
+pub fn describe(matches: &ArgMatches) -> anyhow::Result<()> {
+    let src = matches.value_of("src").unwrap();
+    let format = matches.value_of_t("format").unwrap_or_else(|e| e.exit());
+
+    info!("fq-describe start");
+
+    let metrics = Metrics::new();
+
+    let mut reader = fastq::open(src).with_context(|| format!("Could not open file: {}", src))?;
+    reader.set_metrics(metrics.clone());
+
+    let stage = metrics.stage("reading records");
+    let summary =
+        read_summary(&mut reader).with_context(|| format!("Could not read file: {}", src))?;
+    drop(stage);
+
+    info!("read {} records", summary.record_count());
+
+    let stdout = io::stdout();
+    let mut writer = BufWriter::new(stdout.lock());
+
+    match format {
+        Format::Json => write_json(&mut writer, &summary),
+        Format::Tsv => write_tsv(&mut writer, &summary),
+    }
+    .context("Could not write summary to stdout")?;
+
+    metrics.log();
+
+    info!("fq-describe end");
+
+    Ok(())
+}
+
+fn read_summary<R>(reader: &mut fastq::Reader<R>) -> io::Result<Summary>
+where
+    R: BufRead,
+{
+    let mut record = Record::default();
+    let mut summary = Summary::default();
+
+    while reader.read_record(&mut record)? != 0 {
+        summary.add(&record);
+    }
+
+    Ok(summary)
+}
+
+fn write_json<W>(writer: &mut W, summary: &Summary) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(writer, "{}", build_json(summary))
+}
+
+/// Builds the JSON object of a summary.
+pub fn build_json(summary: &Summary) -> Value {
+    json!({
+        "recordCount": summary.record_count(),
+        "baseCount": summary.base_count(),
+        "minLength": summary.min_length(),
+        "maxLength": summary.max_length(),
+        "meanLength": summary.mean_length(),
+        "cycleMeanQualityScores": summary.cycle_mean_quality_scores(),
+        "gcContent": summary.gc_content(),
+        "nContent": summary.n_content(),
+    })
+}
+
+// Missing values are written as empty fields, and per-cycle mean qualities as a comma-separated
+// list.
+fn write_tsv<W>(writer: &mut W, summary: &Summary) -> io::Result<()>
+where
+    W: Write,
+{
+    fn optional<T>(value: Option<T>) -> String
+    where
+        T: ToString,
+    {
+        value.map(|v| v.to_string()).unwrap_or_default()
+    }
+
+    let cycle_mean_quality_scores: Vec<_> = summary
+        .cycle_mean_quality_scores()
+        .iter()
+        .map(|score| format!("{:.4}", score))
+        .collect();
+
+    writeln!(writer, "record_count\t{}", summary.record_count())?;
+    writeln!(writer, "base_count\t{}", summary.base_count())?;
+    writeln!(writer, "min_length\t{}", optional(summary.min_length()))?;
+    writeln!(writer, "max_length\t{}", optional(summary.max_length()))?;
+    writeln!(
+        writer,
+        "mean_length\t{}",
+        optional(summary.mean_length().map(|n| format!("{:.4}", n)))
+    )?;
+    writeln!(
+        writer,
+        "cycle_mean_quality_scores\t{}",
+        cycle_mean_quality_scores.join(",")
+    )?;
+    writeln!(
+        writer,
+        "gc_content\t{}",
+        optional(summary.gc_content().map(|n| format!("{:.4}", n)))
+    )?;
+    writeln!(
+        writer,
+        "n_content\t{}",
+        optional(summary.n_content().map(|n| format!("{:.4}", n)))
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_summary() -> io::Result<Summary> {
+        let data = b"@r0\nACGN\n+\n+5?I\n@r1\nGC\n+\n5?\n";
+        let mut reader = fastq::Reader::new(&data[..]);
+        read_summary(&mut reader)
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("json".parse(), Ok(Format::Json));
+        assert_eq!("tsv".parse(), Ok(Format::Tsv));
+        assert_eq!(
+            "xml".parse::<Format>(),
+            Err(ParseFormatError(String::from("xml")))
+        );
+    }
+
+    #[test]
+    fn test_write_json() -> io::Result<()> {
+        let summary = build_summary()?;
+
+        let mut buf = Vec::new();
+        write_json(&mut buf, &summary)?;
+
+        let expected = r#"{"recordCount":2,"baseCount":6,"minLength":2,"maxLength":4,"meanLength":3.0,"cycleMeanQualityScores":[15.0,25.0,30.0,40.0],"gcContent":0.8,"nContent":0.16666666666666666}
+"#;
+
+        assert_eq!(String::from_utf8(buf).unwrap(), expected);
+
+        let mut buf = Vec::new();
+        write_json(&mut buf, &Summary::default())?;
+
+        let expected = r#"{"recordCount":0,"baseCount":0,"minLength":null,"maxLength":null,"meanLength":null,"cycleMeanQualityScores":[],"gcContent":null,"nContent":null}
+"#;
+
+        assert_eq!(String::from_utf8(buf).unwrap(), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_tsv() -> io::Result<()> {
+        let summary = build_summary()?;
+
+        let mut buf = Vec::new();
+        write_tsv(&mut buf, &summary)?;
+
+        let expected = "\
+record_count\t2
+base_count\t6
+min_length\t2
+max_length\t4
+mean_length\t3.0000
+cycle_mean_quality_scores\t15.0000,25.0000,30.0000,40.0000
+gc_content\t0.8000
+n_content\t0.1667
+";
+
+        assert_eq!(String::from_utf8(buf).unwrap(), expected);
+
+        Ok(())
+    }
+}
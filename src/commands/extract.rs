@@ -0,0 +1,187 @@
+use std::io::{self, BufRead, BufWriter, Seek, Write};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fastq::{self, Record},
+    index::{self, IndexedReader},
+    mask::PositionRange,
+    metrics::Metrics,
+};
+
+pub fn extract(matches: &ArgMatches) -> anyhow::Result<()> {
+    let src = matches.value_of("src").unwrap();
+    let dst = matches.value_of("dst");
+
+    let ranges: Vec<PositionRange> = if matches.is_present("index") {
+        matches.values_of_t("index").unwrap_or_else(|e| e.exit())
+    } else {
+        Vec::new()
+    };
+
+    let names: Vec<&str> = matches
+        .values_of("name")
+        .map(|values| values.collect())
+        .unwrap_or_default();
+
+    info!("fq-extract start");
+
+    let metrics = Metrics::new();
+
+    let mut reader = index::open(src).with_context(|| {
+        format!(
+            "Could not open indexed file: {}. Create its index with `fq index`.",
+            src
+        )
+    })?;
+
+    let mut writer = match dst {
+        Some(dst) => {
+            fastq::create(dst).with_context(|| format!("Could not create file: {}", dst))?
+        }
+        None => fastq::Writer::new(Box::new(BufWriter::new(io::stdout())) as Box<dyn Write>),
+    };
+
+    writer.set_metrics(metrics.clone());
+
+    let _stage = metrics.stage("extracting records");
+
+    let mut n = 0;
+
+    for range in ranges {
+        n += extract_range(&mut reader, &mut writer, range)
+            .with_context(|| format!("Could not extract records: {}", src))?;
+    }
+
+    for name in names {
+        extract_name(&mut reader, &mut writer, name)
+            .with_context(|| format!("Could not extract record: {}", src))?;
+        n += 1;
+    }
+
+    info!("extracted {} records", n);
+
+    metrics.log();
+
+    info!("fq-extract end");
+
+    Ok(())
+}
+
+// Copies the records in an inclusive range of 1-based record numbers. The end of the range is
+// clamped to the last record.
+fn extract_range<R, W>(
+    reader: &mut IndexedReader<R>,
+    writer: &mut fastq::Writer<W>,
+    range: PositionRange,
+) -> io::Result<u64>
+where
+    R: BufRead + Seek,
+    W: Write,
+{
+    let record_count = reader.index().entries().len();
+    let start = range.start() - 1;
+    let end = range.end().min(record_count);
+
+    if start >= record_count {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "record {} is out of range: the index has {} records",
+                range.start(),
+                record_count
+            ),
+        ));
+    }
+
+    reader.seek(start)?;
+
+    let mut record = Record::default();
+    let mut n = 0;
+
+    for _ in start..end {
+        if reader.read_record(&mut record)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "source unexpectedly ended before the last indexed record",
+            ));
+        }
+
+        writer.write_record(&record)?;
+        n += 1;
+    }
+
+    Ok(n)
+}
+
+fn extract_name<R, W>(
+    reader: &mut IndexedReader<R>,
+    writer: &mut fastq::Writer<W>,
+    name: &str,
+) -> io::Result<()>
+where
+    R: BufRead + Seek,
+    W: Write,
+{
+    let i = reader.index().find(name.as_bytes()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("record not found: {}", name),
+        )
+    })?;
+
+    reader.seek(i)?;
+
+    let mut record = Record::default();
+    reader.read_record(&mut record)?;
+    writer.write_record(&record)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    static DATA: &[u8] = b"@r0\nA\n+\nI\n@r1\nC\n+\nI\n@r2\nG\n+\nI\n@r3\nT\n+\nI\n";
+
+    #[test]
+    fn test_extract_range() -> io::Result<()> {
+        let index = index::build(DATA)?;
+        let mut reader = IndexedReader::new(Cursor::new(DATA), index);
+        let mut writer = fastq::Writer::new(Vec::new());
+
+        let range = PositionRange::new(2, 3).unwrap();
+        assert_eq!(extract_range(&mut reader, &mut writer, range)?, 2);
+
+        let range = PositionRange::new(4, 8).unwrap();
+        assert_eq!(extract_range(&mut reader, &mut writer, range)?, 1);
+
+        assert_eq!(
+            writer.get_ref(),
+            b"@r1\nC\n+\nI\n@r2\nG\n+\nI\n@r3\nT\n+\nI\n"
+        );
+
+        let range = PositionRange::new(5, 8).unwrap();
+        assert!(extract_range(&mut reader, &mut writer, range).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_name() -> io::Result<()> {
+        let index = index::build(DATA)?;
+        let mut reader = IndexedReader::new(Cursor::new(DATA), index);
+        let mut writer = fastq::Writer::new(Vec::new());
+
+        extract_name(&mut reader, &mut writer, "r2")?;
+        extract_name(&mut reader, &mut writer, "r0")?;
+        assert_eq!(writer.get_ref(), b"@r2\nG\n+\nI\n@r0\nA\n+\nI\n");
+
+        assert!(extract_name(&mut reader, &mut writer, "r4").is_err());
+
+        Ok(())
+    }
+}
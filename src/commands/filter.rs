@@ -8,19 +8,47 @@ use anyhow::Context;
 use clap::ArgMatches;
 use tracing::info;
 
-use crate::fastq;
+use crate::{complexity::entropy, fastq, metrics::Metrics};
+
+#[derive(Debug, Default)]
+struct Filters {
+    names: Option<HashSet<Vec<u8>>>,
+    min_complexity: Option<f64>,
+}
+
+impl Filters {
+    // A record is kept if it passes all filters.
+    fn is_match(&self, record: &fastq::Record) -> bool {
+        if let Some(names) = &self.names {
+            if !names.contains(name_id(record.name())) {
+                return false;
+            }
+        }
+
+        if let Some(min_complexity) = self.min_complexity {
+            if entropy(record.sequence()) < min_complexity {
+                return false;
+            }
+        }
+
+        true
+    }
+}
 
 fn copy_filtered<R, W>(
     mut reader: fastq::Reader<R>,
-    names: &HashSet<Vec<u8>>,
+    filters: &Filters,
     mut writer: fastq::Writer<W>,
-) -> io::Result<()>
+) -> io::Result<(u64, u64)>
 where
     R: BufRead,
     W: Write,
 {
     let mut record = fastq::Record::default();
 
+    let mut n = 0;
+    let mut total = 0;
+
     loop {
         let bytes_read = reader.read_record(&mut record)?;
 
@@ -28,14 +56,15 @@ where
             break;
         }
 
-        let id = name_id(record.name());
-
-        if names.contains(id) {
+        if filters.is_match(&record) {
             writer.write_record(&record)?;
+            n += 1;
         }
+
+        total += 1;
     }
 
-    Ok(())
+    Ok((n, total))
 }
 
 fn read_names<R>(reader: R) -> io::Result<HashSet<Vec<u8>>>
@@ -65,35 +94,63 @@ fn name_id(name: &[u8]) -> &[u8] {
 
 pub fn filter(matches: &ArgMatches) -> anyhow::Result<()> {
     let src = matches.value_of("src").unwrap();
-    let names_src = matches.value_of("names").unwrap();
 
     info!("fq-filter start");
 
-    info!("reading names");
+    let metrics = Metrics::new();
+
+    let mut filters = Filters::default();
+
+    if let Some(names_src) = matches.value_of("names") {
+        info!("reading names");
 
-    let file =
-        File::open(names_src).with_context(|| format!("Could not open file: {}", names_src))?;
+        let file =
+            File::open(names_src).with_context(|| format!("Could not open file: {}", names_src))?;
 
-    let reader = BufReader::new(file);
+        let reader = BufReader::new(file);
 
-    let names =
-        read_names(reader).with_context(|| format!("Could not read file: {}", names_src))?;
+        let names =
+            read_names(reader).with_context(|| format!("Could not read file: {}", names_src))?;
 
-    info!("read {} names", names.len());
+        info!("read {} names", names.len());
+
+        filters.names = Some(names);
+    }
+
+    if matches.is_present("min-complexity") {
+        let min_complexity: f64 = matches
+            .value_of_t("min-complexity")
+            .unwrap_or_else(|e| e.exit());
+
+        if !(0.0..=1.0).contains(&min_complexity) {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("invalid min complexity = {}", min_complexity));
+        }
+
+        info!("min complexity = {}", min_complexity);
+
+        filters.min_complexity = Some(min_complexity);
+    }
 
     let stdout = io::stdout();
     let handle = stdout.lock();
     let buf = BufWriter::new(handle);
-    let writer = fastq::Writer::new(buf);
+    let mut writer = fastq::Writer::new(buf);
+    writer.set_metrics(metrics.clone());
 
     info!("filtering fastq");
 
-    let reader =
+    let mut reader =
         crate::fastq::open(src).with_context(|| format!("Could not open file: {}", src))?;
+    reader.set_metrics(metrics.clone());
 
-    copy_filtered(reader, &names, writer)
+    let (n, total) = copy_filtered(reader, &filters, writer)
         .with_context(|| format!("Could not copy record from {} to stdout", src))?;
 
+    info!("kept {}/{} records", n, total);
+
+    metrics.log();
+
     info!("fq-filter end");
 
     Ok(())
@@ -105,7 +162,10 @@ mod tests {
 
     #[test]
     fn test_copy_filtered() {
-        let names = [b"fqlib:2".to_vec()].iter().cloned().collect();
+        let filters = Filters {
+            names: Some([b"fqlib:2".to_vec()].iter().cloned().collect()),
+            ..Default::default()
+        };
 
         let data = "\
 @fqlib:1/1\nAGCT\n+\nabcd
@@ -118,12 +178,36 @@ mod tests {
         let mut buf = Vec::new();
         let writer = fastq::Writer::new(&mut buf);
 
-        copy_filtered(reader, &names, writer).unwrap();
+        copy_filtered(reader, &filters, writer).unwrap();
 
         let expected = b"@fqlib:2/1\nTCGA\n+\ndcba\n";
         assert_eq!(buf, expected);
     }
 
+    #[test]
+    fn test_copy_filtered_with_min_complexity() {
+        let filters = Filters {
+            min_complexity: Some(0.5),
+            ..Default::default()
+        };
+
+        let data = "\
+@fqlib:1/1\nAAAAAAAAAA\n+\nabcdefghij
+@fqlib:2/1\nACGTTGCAAT\n+\nabcdefghij
+@fqlib:3/1\nATATATATAT\n+\nabcdefghij
+";
+
+        let reader = fastq::Reader::new(data.as_bytes());
+
+        let mut buf = Vec::new();
+        let writer = fastq::Writer::new(&mut buf);
+
+        assert_eq!(copy_filtered(reader, &filters, writer).unwrap(), (1, 3));
+
+        let expected = b"@fqlib:2/1\nACGTTGCAAT\n+\nabcdefghij\n";
+        assert_eq!(buf, expected);
+    }
+
     #[test]
     fn test_read_names() {
         let data = "@fqlib:1/1\n@fqlib:2/1\n@fqlib:3/1\n";
@@ -0,0 +1,150 @@
+use std::{
+    io::{BufRead, Write},
+    mem,
+};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::{info, warn};
+
+use crate::{
+    fastq::{self, Record},
+    fix::{Fixer, Fixes, QualityLengthPolicy},
+    metrics::Metrics,
+};
+
+pub fn fix(matches: &ArgMatches) -> anyhow::Result<()> {
+    let src = matches.value_of("src").unwrap();
+    let dst = matches.value_of("dst").unwrap();
+
+    let quality_length_policy: Option<QualityLengthPolicy> =
+        match matches.value_of("quality-length") {
+            Some(s) => Some(s.parse()?),
+            None => None,
+        };
+
+    let fixer = Fixer::new(quality_length_policy);
+
+    info!("fq-fix start");
+
+    let metrics = Metrics::new();
+
+    let mut reader = fastq::open(src).with_context(|| format!("Could not open file: {}", src))?;
+    let mut writer =
+        fastq::create(dst).with_context(|| format!("Could not create file: {}", dst))?;
+    reader.set_metrics(metrics.clone());
+    writer.set_metrics(metrics.clone());
+
+    let _stage = metrics.stage("fixing records");
+
+    let (n, fixes) = fix_records(&fixer, src, &mut reader, &mut writer)?;
+
+    info!("wrote {} records", n);
+
+    for (code, count) in fixes.codes() {
+        if count > 0 {
+            info!("[{}] fixed {} records", code, count);
+        }
+    }
+
+    if fixes.partial_records > 0 {
+        warn!("removed partial record at the end of {}", src);
+    }
+
+    metrics.log();
+
+    info!("fq-fix end");
+
+    Ok(())
+}
+
+// Records are read one ahead so that the last record can be checked for truncation.
+fn fix_records<R, W>(
+    fixer: &Fixer,
+    src: &str,
+    reader: &mut fastq::Reader<R>,
+    writer: &mut fastq::Writer<W>,
+) -> anyhow::Result<(u64, Fixes)>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut record = Record::default();
+    let mut next_record = Record::default();
+
+    let mut fixes = Fixes::default();
+    let mut n = 0;
+
+    if reader.read_record(&mut record)? == 0 {
+        return Ok((n, fixes));
+    }
+
+    loop {
+        let is_last = reader.read_record(&mut next_record)? == 0;
+
+        if is_last && fixer.is_partial(&record) {
+            fixes.partial_records += 1;
+            break;
+        }
+
+        fixer.fix(&mut record, &mut fixes).with_context(|| {
+            let line_no = n * 4 + 1;
+            format!("{}:{}: could not fix record", src, line_no)
+        })?;
+
+        writer.write_record(&record)?;
+        n += 1;
+
+        if is_last {
+            break;
+        }
+
+        mem::swap(&mut record, &mut next_record);
+    }
+
+    Ok((n, fixes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_records() -> anyhow::Result<()> {
+        let data = b"@r0\r\nACGT\r\n+r0\r\nIIII\r\n@r1\nACGT\n+\nIII\n@r2\nACGT\n+\nII";
+
+        let fixer = Fixer::new(Some(QualityLengthPolicy::Pad));
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+
+        let (n, fixes) = fix_records(&fixer, "in.fq", &mut reader, &mut writer)?;
+
+        assert_eq!(n, 2);
+        assert_eq!(
+            fixes,
+            Fixes {
+                plus_lines: 1,
+                partial_records: 1,
+                quality_lengths: 1,
+            }
+        );
+        assert_eq!(
+            writer.get_ref(),
+            b"@r0\nACGT\n+\nIIII\n@r1\nACGT\n+\nIII!\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fix_records_with_unfixable_record() {
+        let data = b"@r0\nACGT\n+\nIIII\n@r1\nACGT\n+\nIII\n@r2\nACGT\n+\nIIII\n";
+
+        let fixer = Fixer::default();
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+
+        let e = fix_records(&fixer, "in.fq", &mut reader, &mut writer).unwrap_err();
+        assert_eq!(e.to_string(), "in.fq:5: could not fix record");
+    }
+}
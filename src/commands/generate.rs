@@ -1,13 +1,77 @@
-use anyhow::Context;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    ops::Range,
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Context};
 use clap::ArgMatches;
-use rand::{rngs::SmallRng, SeedableRng};
+use flate2::Compression;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
 use tracing::info;
 
-use crate::{generator::Builder, Generator, PairWriter};
+use crate::{
+    bam::{self, ParseReadGroupError, ReadGroup},
+    demux::{read_samples, Sample, MAX_BARCODE_COUNT},
+    fasta,
+    fastq::{self, Record},
+    generator::{
+        BaseComposition, Builder, ErrorModel, Model, NameFormat, NameTemplate, QualityProfile,
+        ReadLengthDistribution, Reference, UmiLocation, MAX_DUPLICATE_RATE,
+    },
+    metrics::Metrics,
+    Generator, PairWriter,
+};
+
+const STDOUT: &str = "-";
+const DEFAULT_READ_GROUP: &str = "ID:A\tSM:sample";
+const MAX_COMPRESSION_LEVEL: u32 = 9;
+
+/// A part `index` of `count` of a generated dataset, e.g., `2/4`. The index is 1-based.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Shard {
+    index: u64,
+    count: u64,
+}
+
+impl Shard {
+    // Returns the indices of the records in this shard. Shards differ in size by at most one.
+    fn record_indices(&self, record_count: u64) -> Range<u64> {
+        let boundary =
+            |i: u64| (u128::from(record_count) * u128::from(i) / u128::from(self.count)) as u64;
+        boundary(self.index - 1)..boundary(self.index)
+    }
+}
+
+impl Default for Shard {
+    fn default() -> Self {
+        Self { index: 1, count: 1 }
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid_shard = || format!("invalid shard: expected i/N with 1 <= i <= N, got '{}'", s);
+
+        let (index, count) = s.split_once('/').ok_or_else(invalid_shard)?;
+        let index = index.parse().map_err(|_| invalid_shard())?;
+        let count = count.parse().map_err(|_| invalid_shard())?;
+
+        if index == 0 || index > count {
+            return Err(invalid_shard());
+        }
+
+        Ok(Self { index, count })
+    }
+}
 
 pub fn generate(matches: &ArgMatches) -> anyhow::Result<()> {
-    let r1_dst = matches.value_of("r1-dst").unwrap();
-    let r2_dst = matches.value_of("r2-dst").unwrap();
+    let interleaved_dst = matches.value_of("interleaved");
+    let r1_dst = matches.value_of("r1-dst");
+    let r2_dst = matches.value_of("r2-dst");
 
     let record_count = matches
         .value_of_t("record-count")
@@ -15,33 +79,673 @@ pub fn generate(matches: &ArgMatches) -> anyhow::Result<()> {
     let read_length = matches
         .value_of_t("read-length")
         .unwrap_or_else(|e| e.exit());
+    let name_format: NameFormat = matches
+        .value_of_t("name-format")
+        .unwrap_or_else(|e| e.exit());
+    let read_length_distribution: Option<ReadLengthDistribution> =
+        if matches.is_present("read-length-distribution") {
+            Some(
+                matches
+                    .value_of_t("read-length-distribution")
+                    .unwrap_or_else(|e| e.exit()),
+            )
+        } else {
+            None
+        };
+    let insert_size = matches
+        .value_of_t("insert-size")
+        .unwrap_or_else(|e| e.exit());
+    let insert_size_std_dev: f64 = matches
+        .value_of_t("insert-size-std-dev")
+        .unwrap_or_else(|e| e.exit());
+    let orientation = matches
+        .value_of_t("orientation")
+        .unwrap_or_else(|e| e.exit());
+    let quality_profile: QualityProfile = matches
+        .value_of_t("quality-model")
+        .unwrap_or_else(|e| e.exit());
+
+    let shard = if matches.is_present("shard") {
+        matches.value_of_t("shard").unwrap_or_else(|e| e.exit())
+    } else {
+        Shard::default()
+    };
+
+    let stdout_dst_count = [interleaved_dst, r1_dst, r2_dst]
+        .iter()
+        .chain(&[matches.value_of("i1-dst"), matches.value_of("i2-dst")])
+        .filter(|dst| **dst == Some(STDOUT))
+        .count();
+
+    if stdout_dst_count > 1 {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .context("Only one destination can be stdout (`-`)");
+    }
+
+    let is_bam = matches.value_of("format") == Some("bam");
+
+    if is_bam && r2_dst.is_some() {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .context("BAM output writes both mates to one destination (`--interleaved`)");
+    }
+
+    if matches.is_present("read-group") && !is_bam {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .context("--read-group is only supported for BAM output");
+    }
+
+    if matches.is_present("read-group") && matches.is_present("barcodes") {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput)).context(
+            "--read-group cannot be used with --barcodes, which sets one read group per sample",
+        );
+    }
+
+    // Barcodes are only used by index reads, BAM read groups, and `{barcode}` in names.
+    let has_barcode_placeholder = matches
+        .value_of("name-template")
+        .map(|template| template.contains("{barcode}"))
+        .unwrap_or(false);
+
+    if matches.is_present("barcodes")
+        && !is_bam
+        && !matches.is_present("i1-dst")
+        && !has_barcode_placeholder
+    {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput)).context(
+            "--barcodes requires index reads (`--i1-dst`), BAM output, or `{barcode}` in `--name-template`",
+        );
+    }
 
     info!("fq-generate start");
 
-    let builder = if matches.is_present("seed") {
-        let seed = matches.value_of_t("seed").unwrap_or_else(|e| e.exit());
+    if !insert_size_std_dev.is_finite() || insert_size_std_dev < 0.0 {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid insert size std. dev. = {}", insert_size_std_dev));
+    }
+
+    let duplicate_rate: f64 = matches
+        .value_of_t("duplicate-rate")
+        .unwrap_or_else(|e| e.exit());
+
+    if !(0.0..=MAX_DUPLICATE_RATE).contains(&duplicate_rate) {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid duplicate rate = {}", duplicate_rate));
+    }
+
+    let base_composition = read_base_composition(matches)?;
+    let error_model = read_error_model(matches)?;
+
+    let threads: usize = matches.value_of_t("threads").unwrap_or_else(|e| e.exit());
+
+    if threads == 0 {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid threads = {}", threads));
+    }
+
+    // Records are only generated in parallel when they are seeded, so that each thread can
+    // generate its part of the dataset independently.
+    let seed = if matches.is_present("seed") {
+        Some(matches.value_of_t("seed").unwrap_or_else(|e| e.exit()))
+    } else if threads > 1 {
+        Some(rand::random())
+    } else {
+        None
+    };
+
+    let builder = if let Some(seed) = seed {
         let rng = SmallRng::seed_from_u64(seed);
         Builder::from_rng(rng)
     } else {
         Generator::builder()
     };
 
-    let generator = builder.set_read_length(read_length).build();
+    let mut builder = builder
+        .set_name_format(name_format)
+        .set_read_length(read_length)
+        .set_fragment_length(insert_size)
+        .set_fragment_length_std_dev(insert_size_std_dev)
+        .set_duplicate_rate(duplicate_rate)
+        .set_orientation(orientation)
+        .set_quality_model(quality_profile.build());
+
+    builder = set_name_fields(builder, matches)?;
+
+    if let Some(read_length_distribution) = read_length_distribution {
+        builder = builder.set_read_length_distribution(read_length_distribution);
+    }
+
+    if let Some((r1_adapter, r2_adapter)) = read_adapters(matches)? {
+        builder = builder.set_adapters(r1_adapter, r2_adapter);
+    }
+
+    let samples = read_sample_sheet(matches)?;
+
+    if let Some(samples) = &samples {
+        builder = set_samples(builder, matches, samples.clone())?;
+    }
+
+    if let Some(src) = matches.value_of("reference") {
+        builder = builder.set_reference(read_reference(src)?);
+    }
+
+    if let Some(base_composition) = base_composition {
+        builder = builder.set_base_composition(base_composition);
+    }
+
+    if let Some(error_model) = error_model {
+        builder = builder.set_error_model(error_model);
+    }
+
+    if let Some(model) = read_model(matches)? {
+        builder = builder
+            .set_model(&model)
+            .ok_or_else(|| anyhow!("Generator model has no records"))?;
+    }
+
+    let generator = builder.build();
+
+    let (compression_level, compression_threads) = read_compression(matches)?;
+
+    // Records are tagged with the given read group or, with a sample sheet, the read group of the
+    // sample of their pair.
+    let (read_groups, read_group_id) = if let Some(samples) = &samples {
+        let read_groups = samples
+            .iter()
+            .map(sample_read_group)
+            .collect::<Result<Vec<_>, _>>()
+            .context("Invalid sample name")?;
+
+        (read_groups, None)
+    } else {
+        let read_group: ReadGroup = matches
+            .value_of("read-group")
+            .unwrap_or(DEFAULT_READ_GROUP)
+            .parse()
+            .context("Invalid --read-group")?;
+
+        let read_group_id = read_group.id().as_bytes().to_vec();
+        (vec![read_group], Some(read_group_id))
+    };
+
+    let metrics = Metrics::new();
+
+    let create = |dst: &str| {
+        let mut writer = if dst == STDOUT {
+            fastq::Writer::new(Box::new(BufWriter::new(io::stdout())) as Box<dyn Write>)
+        } else {
+            fastq::create_with_compression(dst, compression_level, compression_threads)
+                .with_context(|| format!("Could not create file: {}", dst))?
+        };
+
+        writer.set_metrics(metrics.clone());
+
+        Ok::<_, anyhow::Error>(writer)
+    };
+
+    let create_bam = |dst: &str| {
+        let mut writer = if dst == STDOUT {
+            bam::Writer::new(Box::new(BufWriter::new(io::stdout())) as Box<dyn Write>)
+        } else {
+            bam::create(dst).with_context(|| format!("Could not create file: {}", dst))?
+        };
+
+        writer.set_metrics(metrics.clone());
+
+        writer
+            .write_header(&build_bam_header(&read_groups))
+            .context("Could not write BAM header")?;
+
+        Ok::<_, anyhow::Error>(writer)
+    };
+
+    // Without a read 2 destination, only read 1 of each pair is written.
+    let mut writer = match (interleaved_dst, r1_dst, r2_dst) {
+        (Some(dst), _, _) if is_bam => {
+            info!("generating interleaved paired end reads (BAM)");
+            PairWriter::bam(create_bam(dst)?, read_group_id)
+        }
+        (None, Some(r1_dst), None) if is_bam => {
+            info!("generating single end reads (BAM)");
+            PairWriter::bam_single_end(create_bam(r1_dst)?, read_group_id)
+        }
+        (Some(dst), _, _) => {
+            info!("generating interleaved paired end reads");
+            PairWriter::interleaved(create(dst)?)
+        }
+        (None, Some(r1_dst), Some(r2_dst)) => PairWriter::new(create(r1_dst)?, create(r2_dst)?),
+        (None, Some(r1_dst), None) => {
+            info!("generating single end reads");
+            PairWriter::single_end(create(r1_dst)?)
+        }
+        (None, None, _) => unreachable!("r1-dst is required without interleaved"),
+    };
+
+    let index_writers = ["i1-dst", "i2-dst"]
+        .iter()
+        .filter_map(|&name| matches.value_of(name))
+        .map(create)
+        .collect::<anyhow::Result<Vec<_>>>()?;
 
-    let w1 = crate::fastq::create(r1_dst)
-        .with_context(|| format!("Could not create file: {}", r1_dst))?;
+    if !index_writers.is_empty() {
+        info!("generating {} index reads", index_writers.len());
+        writer.set_index_writers(index_writers);
+    }
 
-    let w2 = crate::fastq::create(r2_dst)
-        .with_context(|| format!("Could not create file: {}", r2_dst))?;
+    // Seeded records are generated independently, so a shard is the same regardless of how the
+    // dataset is split.
+    let generated_count = if let Some(seed) = seed {
+        let record_indices = shard.record_indices(record_count);
 
-    let mut writer = PairWriter::new(w1, w2);
+        info!(
+            "generating shard {}/{} (records {}..{})",
+            shard.index, shard.count, record_indices.start, record_indices.end
+        );
 
-    writer
-        .write(generator, record_count)
-        .context("Could not write generated records")?;
+        let generated_count = record_indices.end - record_indices.start;
+
+        writer
+            .write_range_parallel(generator, seed, record_indices, threads)
+            .context("Could not write generated records")?;
+
+        generated_count
+    } else {
+        writer
+            .write(generator, record_count)
+            .context("Could not write generated records")?;
 
-    info!("generated {} records", record_count);
+        record_count
+    };
+
+    writer.finish().context("Could not finish output")?;
+
+    info!("generated {} records", generated_count);
+    metrics.log();
     info!("fq-generate end");
 
     Ok(())
 }
+
+// Sets the instrument, run number, flow cell ID, and template of read names and the UMIs of
+// pairs, if given.
+fn set_name_fields<R>(mut builder: Builder<R>, matches: &ArgMatches) -> anyhow::Result<Builder<R>>
+where
+    R: Rng,
+{
+    // Fields are separated by colons, and the name ends at the first whitespace.
+    let read_field = |name: &str| -> anyhow::Result<Option<&str>> {
+        match matches.value_of(name) {
+            Some(s) if s.is_empty() || s.contains(|c: char| c == ':' || c.is_whitespace()) => {
+                Err(io::Error::from(io::ErrorKind::InvalidInput))
+                    .with_context(|| format!("invalid {} = '{}'", name.replace('-', " "), s))
+            }
+            value => Ok(value),
+        }
+    };
+
+    if let Some(instrument) = read_field("instrument")? {
+        builder = builder.set_instrument(instrument);
+    }
+
+    if matches.is_present("run-number") {
+        let run_number = matches
+            .value_of_t("run-number")
+            .unwrap_or_else(|e| e.exit());
+        builder = builder.set_run_number(run_number);
+    }
+
+    if let Some(flow_cell_id) = read_field("flow-cell-id")? {
+        builder = builder.set_flow_cell_id(flow_cell_id);
+    }
+
+    if matches.is_present("name-template") {
+        let name_template: NameTemplate = matches
+            .value_of_t("name-template")
+            .unwrap_or_else(|e| e.exit());
+        builder = builder.set_name_template(name_template);
+    }
+
+    if matches.is_present("umi-location") {
+        let umi_location: UmiLocation = matches
+            .value_of_t("umi-location")
+            .unwrap_or_else(|e| e.exit());
+        builder = builder.set_umi_location(umi_location);
+    }
+
+    if matches.is_present("umi-length") {
+        let umi_length = matches
+            .value_of_t("umi-length")
+            .unwrap_or_else(|e| e.exit());
+        builder = builder.set_umi_length(umi_length);
+
+        // A UMI length alone adds UMIs to the sequence, unless they are only used in names.
+        let has_umi_placeholder = matches
+            .value_of("name-template")
+            .map(|template| template.contains("{umi}"))
+            .unwrap_or(false);
+
+        if !matches.is_present("umi-location") && !has_umi_placeholder {
+            builder = builder.set_umi_location(UmiLocation::Sequence);
+        }
+    }
+
+    Ok(builder)
+}
+
+// Returns the samples of the sample sheet, if given.
+fn read_sample_sheet(matches: &ArgMatches) -> anyhow::Result<Option<Vec<Sample>>> {
+    let src = match matches.value_of("barcodes") {
+        Some(src) => src,
+        None => return Ok(None),
+    };
+
+    let samples = File::open(src)
+        .map(BufReader::new)
+        .and_then(read_samples)
+        .with_context(|| format!("Could not read file: {}", src))?;
+
+    if samples.is_empty() {
+        return Err(io::Error::from(io::ErrorKind::InvalidData))
+            .with_context(|| format!("No samples in {}", src));
+    }
+
+    let is_valid_barcode = |barcode: &[u8]| barcode.iter().all(|b| b"ACGTN".contains(b));
+
+    if let Some(sample) = samples
+        .iter()
+        .find(|sample| !sample.barcodes().iter().all(|b| is_valid_barcode(b)))
+    {
+        return Err(io::Error::from(io::ErrorKind::InvalidData))
+            .with_context(|| format!("invalid barcodes of sample '{}'", sample.name()));
+    }
+
+    if matches.is_present("i2-dst") && samples[0].barcodes().len() < MAX_BARCODE_COUNT {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("Index read 2 requires two barcodes per sample in {}", src));
+    }
+
+    info!("read {} samples", samples.len());
+
+    Ok(Some(samples))
+}
+
+// Sets the samples of pairs and the error rate of their index reads.
+fn set_samples<R>(
+    builder: Builder<R>,
+    matches: &ArgMatches,
+    samples: Vec<Sample>,
+) -> anyhow::Result<Builder<R>>
+where
+    R: Rng,
+{
+    let index_error_rate: f64 = matches
+        .value_of_t("index-error-rate")
+        .unwrap_or_else(|e| e.exit());
+
+    if !(0.0..=1.0).contains(&index_error_rate) {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid index error rate = {}", index_error_rate));
+    }
+
+    Ok(builder
+        .set_samples(samples)
+        .set_index_error_rate(index_error_rate))
+}
+
+// Returns the read group of a sample. Its ID and sample (`SM`) are the sample name, and its
+// barcodes (`BC`) are joined by `-`, e.g., `ACGTACGT-TTTTGGGG` for dual indexes.
+fn sample_read_group(sample: &Sample) -> Result<ReadGroup, ParseReadGroupError> {
+    let barcodes: Vec<_> = sample
+        .barcodes()
+        .iter()
+        .map(|barcode| String::from_utf8_lossy(barcode))
+        .collect();
+
+    format!(
+        "ID:{}\tSM:{}\tBC:{}",
+        sample.name(),
+        sample.name(),
+        barcodes.join("-")
+    )
+    .parse()
+}
+
+// Builds a SAM header for unaligned records. Mates are written next to each other, so records are
+// grouped by name.
+fn build_bam_header(read_groups: &[ReadGroup]) -> String {
+    let mut text = String::from("@HD\tVN:1.6\tSO:unsorted\tGO:query\n");
+
+    for read_group in read_groups {
+        text.push_str(&read_group.to_string());
+        text.push('\n');
+    }
+
+    text.push_str(&format!(
+        "@PG\tID:fq\tPN:fq\tVN:{}\n",
+        env!("CARGO_PKG_VERSION")
+    ));
+
+    text
+}
+
+// Returns the gzip compression level and number of compression threads of outputs.
+fn read_compression(matches: &ArgMatches) -> anyhow::Result<(Compression, usize)> {
+    let level: u32 = matches
+        .value_of_t("compression-level")
+        .unwrap_or_else(|e| e.exit());
+
+    if level > MAX_COMPRESSION_LEVEL {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid compression level = {}", level));
+    }
+
+    let threads: usize = matches
+        .value_of_t("compression-threads")
+        .unwrap_or_else(|e| e.exit());
+
+    if threads == 0 {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid compression threads = {}", threads));
+    }
+
+    Ok((Compression::new(level), threads))
+}
+
+// Returns the adapters of read 1 and read 2, if given.
+fn read_adapters(matches: &ArgMatches) -> anyhow::Result<Option<(Vec<u8>, Vec<u8>)>> {
+    let read_adapter = |name: &str| -> anyhow::Result<Option<Vec<u8>>> {
+        match matches.value_of(name) {
+            Some(s) => {
+                let adapter = s.to_ascii_uppercase().into_bytes();
+
+                if adapter.is_empty() || !adapter.iter().all(|b| b"ACGTN".contains(b)) {
+                    return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                        .with_context(|| format!("invalid {} = '{}'", name, s));
+                }
+
+                Ok(Some(adapter))
+            }
+            None => Ok(None),
+        }
+    };
+
+    let r1_adapter = match read_adapter("adapter")? {
+        Some(adapter) => adapter,
+        None => return Ok(None),
+    };
+
+    let r2_adapter = read_adapter("adapter-2")?.unwrap_or_else(|| r1_adapter.clone());
+
+    Ok(Some((r1_adapter, r2_adapter)))
+}
+
+// Returns the base composition from either a GC content or base weights, if either is set.
+fn read_base_composition(matches: &ArgMatches) -> anyhow::Result<Option<BaseComposition>> {
+    let base_composition = if matches.is_present("gc-content") {
+        let gc_content: f64 = matches
+            .value_of_t("gc-content")
+            .unwrap_or_else(|e| e.exit());
+
+        BaseComposition::from_gc_content(gc_content)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid gc content = {}", gc_content))?
+    } else if matches.is_present("base-weights") {
+        matches
+            .value_of_t("base-weights")
+            .unwrap_or_else(|e| e.exit())
+    } else {
+        return Ok(None);
+    };
+
+    info!("gc content = {}", base_composition.gc_content());
+
+    Ok(Some(base_composition))
+}
+
+// Returns the model of sequencing errors, if any error rate is set.
+fn read_error_model(matches: &ArgMatches) -> anyhow::Result<Option<ErrorModel>> {
+    let read_rate = |name: &str| -> anyhow::Result<f64> {
+        let rate: f64 = matches.value_of_t(name).unwrap_or_else(|e| e.exit());
+
+        if !(0.0..=1.0).contains(&rate) {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("invalid {} = {}", name.replace('-', " "), rate));
+        }
+
+        Ok(rate)
+    };
+
+    let substitution_rate = read_rate("error-rate")?;
+    let insertion_rate = read_rate("insertion-rate")?;
+    let deletion_rate = read_rate("deletion-rate")?;
+
+    if substitution_rate == 0.0 && insertion_rate == 0.0 && deletion_rate == 0.0 {
+        return Ok(None);
+    }
+
+    info!(
+        "error rates: substitution = {}, insertion = {}, deletion = {}",
+        substitution_rate, insertion_rate, deletion_rate
+    );
+
+    Ok(Some(ErrorModel::new(
+        substitution_rate,
+        insertion_rate,
+        deletion_rate,
+    )))
+}
+
+fn read_reference(src: &str) -> anyhow::Result<Reference> {
+    info!("reading reference");
+
+    let mut reader = fasta::open(src).with_context(|| format!("Could not open file: {}", src))?;
+    let reference =
+        Reference::read(&mut reader).with_context(|| format!("Could not read file: {}", src))?;
+
+    info!("read reference with {} bases", reference.len());
+
+    Ok(reference)
+}
+
+fn read_model(matches: &ArgMatches) -> anyhow::Result<Option<Model>> {
+    if let Some(src) = matches.value_of("model-from") {
+        info!("learning model");
+
+        let mut reader =
+            fastq::open(src).with_context(|| format!("Could not open file: {}", src))?;
+        let model =
+            learn_model(&mut reader).with_context(|| format!("Could not read file: {}", src))?;
+
+        info!("learned model from {} records", model.record_count());
+
+        if let Some(dst) = matches.value_of("model-dst") {
+            let file =
+                File::create(dst).with_context(|| format!("Could not create file: {}", dst))?;
+
+            model
+                .write(BufWriter::new(file))
+                .with_context(|| format!("Could not write file: {}", dst))?;
+        }
+
+        Ok(Some(model))
+    } else if let Some(src) = matches.value_of("model") {
+        info!("reading model");
+
+        let file = File::open(src).with_context(|| format!("Could not open file: {}", src))?;
+
+        Model::read(BufReader::new(file))
+            .map(Some)
+            .with_context(|| format!("Could not read file: {}", src))
+    } else {
+        Ok(None)
+    }
+}
+
+fn learn_model<R>(reader: &mut fastq::Reader<R>) -> io::Result<Model>
+where
+    R: BufRead,
+{
+    let mut record = Record::default();
+    let mut model = Model::default();
+
+    while reader.read_record(&mut record)? != 0 {
+        model.add(&record);
+    }
+
+    Ok(model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_record_indices() {
+        let shard = Shard { index: 1, count: 1 };
+        assert_eq!(shard.record_indices(10), 0..10);
+
+        let shards: Vec<_> = (1..=3)
+            .map(|index| Shard { index, count: 3 }.record_indices(10))
+            .collect();
+
+        assert_eq!(shards, [0..3, 3..6, 6..10]);
+
+        let shard = Shard { index: 4, count: 4 };
+        assert_eq!(shard.record_indices(2), 1..2);
+    }
+
+    #[test]
+    fn test_shard_from_str() {
+        assert_eq!("2/4".parse(), Ok(Shard { index: 2, count: 4 }));
+        assert_eq!("1/1".parse(), Ok(Shard::default()));
+
+        assert!("0/4".parse::<Shard>().is_err());
+        assert!("5/4".parse::<Shard>().is_err());
+        assert!("2".parse::<Shard>().is_err());
+        assert!("a/b".parse::<Shard>().is_err());
+    }
+
+    #[test]
+    fn test_sample_read_group() {
+        let sample = Sample::new("s1", vec![b"ACGTACGT".to_vec(), b"TTTTGGGG".to_vec()]);
+        let read_group = sample_read_group(&sample).unwrap();
+        assert_eq!(read_group.id(), "s1");
+        assert_eq!(
+            read_group.to_string(),
+            "@RG\tID:s1\tSM:s1\tBC:ACGTACGT-TTTTGGGG"
+        );
+    }
+
+    #[test]
+    fn test_learn_model() -> io::Result<()> {
+        let data = b"@r0\nACGT\n+\nFQLB\n@r1\nNNGCGC\n+\nFQLIBF\n";
+        let mut reader = fastq::Reader::new(&data[..]);
+
+        let model = learn_model(&mut reader)?;
+
+        assert_eq!(model.record_count(), 2);
+        assert_eq!(model.gc_content(), 0.75);
+        assert_eq!(model.n_rate(), 0.2);
+
+        Ok(())
+    }
+}
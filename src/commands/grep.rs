@@ -0,0 +1,275 @@
+use std::io::{self, BufRead, Write};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use regex::bytes::Regex;
+use tracing::info;
+
+use crate::{
+    fastq::{self, Record},
+    grep::{motif_regex, RecordMatcher},
+    metrics::Metrics,
+};
+
+pub fn grep(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r2_src = matches.value_of("r2-src");
+
+    let r1_dst = matches.value_of("r1-dst");
+    let r2_dst = matches.value_of("r2-dst");
+
+    let name_regex = matches
+        .value_of("name-regex")
+        .map(Regex::new)
+        .transpose()
+        .context("Invalid name regex")?;
+
+    let motif_regex = matches
+        .value_of("motif")
+        .map(motif_regex)
+        .transpose()
+        .context("Invalid motif")?;
+
+    let matcher = RecordMatcher::new(name_regex, motif_regex);
+
+    let invert = matches.is_present("invert");
+    let count_only = matches.is_present("count");
+
+    info!("fq-grep start");
+
+    let metrics = Metrics::new();
+
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    r1.set_metrics(metrics.clone());
+
+    let _stage = metrics.stage("searching records");
+
+    let n = match (r2_src, r1_dst, r2_dst) {
+        (Some(r2_src), _, _) if count_only => {
+            info!("counting matching paired end reads");
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            r2.set_metrics(metrics.clone());
+
+            grep_paired(&mut r1, &mut r2, &matcher, invert, |_, _| Ok(()))?
+        }
+        (None, _, _) if count_only => {
+            info!("counting matching single end reads");
+            grep_single(&mut r1, &matcher, invert, |_| Ok(()))?
+        }
+        (Some(r2_src), Some(r1_dst), Some(r2_dst)) => {
+            info!("searching paired end reads");
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            r2.set_metrics(metrics.clone());
+
+            let mut w1 = fastq::create(r1_dst)
+                .with_context(|| format!("Could not create file: {}", r1_dst))?;
+            let mut w2 = fastq::create(r2_dst)
+                .with_context(|| format!("Could not create file: {}", r2_dst))?;
+            w1.set_metrics(metrics.clone());
+            w2.set_metrics(metrics.clone());
+
+            grep_paired(&mut r1, &mut r2, &matcher, invert, |s1, s2| {
+                w1.write_record(s1)?;
+                w2.write_record(s2)
+            })?
+        }
+        (Some(r2_src), None, None) => {
+            info!("searching paired end reads, writing to stdout (interleaved)");
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            r2.set_metrics(metrics.clone());
+
+            let mut writer = fastq::create_stdout_with_metrics(metrics.clone());
+
+            grep_paired(&mut r1, &mut r2, &matcher, invert, |s1, s2| {
+                writer.write_record(s1)?;
+                writer.write_record(s2)
+            })?
+        }
+        (Some(r2_src), _, None) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-dst for {}", r2_src));
+        }
+        (None, _, Some(r2_dst)) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-src for {}", r2_dst));
+        }
+        (_, None, Some(r2_dst)) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r1-dst for {}", r2_dst));
+        }
+        (None, Some(r1_dst), None) => {
+            info!("searching single end reads");
+
+            let mut writer = fastq::create(r1_dst)
+                .with_context(|| format!("Could not create file: {}", r1_dst))?;
+            writer.set_metrics(metrics.clone());
+
+            grep_single(&mut r1, &matcher, invert, |record| {
+                writer.write_record(record)
+            })?
+        }
+        (None, None, None) => {
+            info!("searching single end reads, writing to stdout");
+            let mut writer = fastq::create_stdout_with_metrics(metrics.clone());
+            grep_single(&mut r1, &matcher, invert, |record| {
+                writer.write_record(record)
+            })?
+        }
+    };
+
+    info!("matched {} records (or pairs)", n);
+
+    if count_only {
+        let stdout = io::stdout();
+        let mut writer = stdout.lock();
+        writeln!(writer, "{}", n).context("Could not write count to stdout")?;
+    }
+
+    metrics.log();
+
+    info!("fq-grep end");
+
+    Ok(())
+}
+
+// Calls `write_record` for each record that matches, or, if inverted, does not match.
+//
+// This returns the number of records written.
+fn grep_single<R, F>(
+    reader: &mut fastq::Reader<R>,
+    matcher: &RecordMatcher,
+    invert: bool,
+    mut write_record: F,
+) -> anyhow::Result<u64>
+where
+    R: BufRead,
+    F: FnMut(&Record) -> io::Result<()>,
+{
+    let mut record = Record::default();
+    let mut n = 0;
+
+    while reader.read_record(&mut record)? != 0 {
+        if matcher.is_match(&record) != invert {
+            write_record(&record)?;
+            n += 1;
+        }
+    }
+
+    Ok(n)
+}
+
+// A pair matches if either mate matches. When inverted, a pair is kept only if neither mate
+// matches.
+fn grep_paired<R, S, F>(
+    r1: &mut fastq::Reader<R>,
+    r2: &mut fastq::Reader<S>,
+    matcher: &RecordMatcher,
+    invert: bool,
+    mut write_pair: F,
+) -> anyhow::Result<u64>
+where
+    R: BufRead,
+    S: BufRead,
+    F: FnMut(&Record, &Record) -> io::Result<()>,
+{
+    let mut s1 = Record::default();
+    let mut s2 = Record::default();
+
+    let mut n = 0;
+
+    loop {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, _) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r1-src unexpectedly ended before r2-src");
+            }
+            (_, 0) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r2-src unexpectedly ended before r1-src");
+            }
+            (_, _) => {
+                let is_match = matcher.is_match(&s1) || matcher.is_match(&s2);
+
+                if is_match != invert {
+                    write_pair(&s1, &s2)?;
+                    n += 1;
+                }
+            }
+        }
+    }
+
+    Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static R1_DATA: &[u8] = b"@r0/1\nAACC\n+\nIIII\n@r1/1\nGGTT\n+\nIIII\n@r2/1\nACGT\n+\nIIII\n";
+    static R2_DATA: &[u8] = b"@r0/2\nTTTT\n+\nIIII\n@r1/2\nGGAA\n+\nIIII\n@r2/2\nCCCC\n+\nIIII\n";
+
+    #[test]
+    fn test_grep_single() -> anyhow::Result<()> {
+        let matcher = RecordMatcher::new(None, Some(motif_regex("GK")?));
+
+        let mut reader = fastq::Reader::new(R1_DATA);
+        let mut writer = fastq::Writer::new(Vec::new());
+        let n = grep_single(&mut reader, &matcher, false, |record| {
+            writer.write_record(record)
+        })?;
+
+        assert_eq!(n, 2);
+        assert_eq!(
+            writer.get_ref(),
+            b"@r1/1\nGGTT\n+\nIIII\n@r2/1\nACGT\n+\nIIII\n"
+        );
+
+        let mut reader = fastq::Reader::new(R1_DATA);
+        let mut writer = fastq::Writer::new(Vec::new());
+        let n = grep_single(&mut reader, &matcher, true, |record| {
+            writer.write_record(record)
+        })?;
+
+        assert_eq!(n, 1);
+        assert_eq!(writer.get_ref(), b"@r0/1\nAACC\n+\nIIII\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_grep_paired() -> anyhow::Result<()> {
+        let matcher = RecordMatcher::new(None, Some(motif_regex("GGA")?));
+
+        let mut r1 = fastq::Reader::new(R1_DATA);
+        let mut r2 = fastq::Reader::new(R2_DATA);
+        let mut names = Vec::new();
+
+        let n = grep_paired(&mut r1, &mut r2, &matcher, false, |s1, s2| {
+            names.push((s1.name().to_vec(), s2.name().to_vec()));
+            Ok(())
+        })?;
+
+        assert_eq!(n, 1);
+        assert_eq!(names, [(b"@r1/1".to_vec(), b"@r1/2".to_vec())]);
+
+        let matcher = RecordMatcher::new(Some(Regex::new("^r[01]/")?), None);
+
+        let mut r1 = fastq::Reader::new(R1_DATA);
+        let mut r2 = fastq::Reader::new(R2_DATA);
+        let n = grep_paired(&mut r1, &mut r2, &matcher, true, |_, _| Ok(()))?;
+        assert_eq!(n, 1);
+
+        let mut r1 = fastq::Reader::new(R1_DATA);
+        let mut r2 = fastq::Reader::new(&R2_DATA[..36]);
+        assert!(grep_paired(&mut r1, &mut r2, &matcher, false, |_, _| Ok(())).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+use std::io::{self, BufRead, Write};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fastq::{self, Record},
+    metrics::Metrics,
+};
+
+pub fn head(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r2_src = matches.value_of("r2-src");
+
+    let r1_dst = matches.value_of("r1-dst");
+    let r2_dst = matches.value_of("r2-dst");
+
+    let record_count = matches
+        .value_of_t("record-count")
+        .unwrap_or_else(|e| e.exit());
+
+    info!("fq-head start");
+
+    let metrics = Metrics::new();
+
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    r1.set_metrics(metrics.clone());
+
+    let _stage = metrics.stage("copying records");
+
+    let n = match (r2_src, r1_dst, r2_dst) {
+        (Some(r2_src), Some(r1_dst), Some(r2_dst)) => {
+            info!("copying paired end reads");
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            r2.set_metrics(metrics.clone());
+
+            let mut w1 = fastq::create(r1_dst)
+                .with_context(|| format!("Could not create file: {}", r1_dst))?;
+            let mut w2 = fastq::create(r2_dst)
+                .with_context(|| format!("Could not create file: {}", r2_dst))?;
+            w1.set_metrics(metrics.clone());
+            w2.set_metrics(metrics.clone());
+
+            head_paired(&mut r1, &mut r2, record_count, |s1, s2| {
+                w1.write_record(s1)?;
+                w2.write_record(s2)
+            })?
+        }
+        (Some(r2_src), None, None) => {
+            info!("copying paired end reads to stdout (interleaved)");
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            r2.set_metrics(metrics.clone());
+
+            let mut writer = fastq::create_stdout_with_metrics(metrics.clone());
+
+            head_paired(&mut r1, &mut r2, record_count, |s1, s2| {
+                writer.write_record(s1)?;
+                writer.write_record(s2)
+            })?
+        }
+        (Some(r2_src), _, None) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-dst for {}", r2_src));
+        }
+        (None, _, Some(r2_dst)) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-src for {}", r2_dst));
+        }
+        (_, None, Some(r2_dst)) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r1-dst for {}", r2_dst));
+        }
+        (None, Some(r1_dst), None) => {
+            info!("copying single end reads");
+
+            let mut writer = fastq::create(r1_dst)
+                .with_context(|| format!("Could not create file: {}", r1_dst))?;
+            writer.set_metrics(metrics.clone());
+
+            head_single(&mut r1, &mut writer, record_count)?
+        }
+        (None, None, None) => {
+            info!("copying single end reads to stdout");
+            let mut writer = fastq::create_stdout_with_metrics(metrics.clone());
+            head_single(&mut r1, &mut writer, record_count)?
+        }
+    };
+
+    info!("copied {} records", n);
+
+    metrics.log();
+
+    info!("fq-head end");
+
+    Ok(())
+}
+
+// Reading stops after `record_count` records, so only the start of each source is decompressed.
+fn head_single<R, W>(
+    reader: &mut fastq::Reader<R>,
+    writer: &mut fastq::Writer<W>,
+    record_count: u64,
+) -> anyhow::Result<u64>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut record = Record::default();
+    let mut n = 0;
+
+    while n < record_count && reader.read_record(&mut record)? != 0 {
+        writer.write_record(&record)?;
+        n += 1;
+    }
+
+    Ok(n)
+}
+
+fn head_paired<R, S, F>(
+    r1: &mut fastq::Reader<R>,
+    r2: &mut fastq::Reader<S>,
+    record_count: u64,
+    mut write_pair: F,
+) -> anyhow::Result<u64>
+where
+    R: BufRead,
+    S: BufRead,
+    F: FnMut(&Record, &Record) -> io::Result<()>,
+{
+    let mut s1 = Record::default();
+    let mut s2 = Record::default();
+
+    let mut n = 0;
+
+    while n < record_count {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, _) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r1-src unexpectedly ended before r2-src");
+            }
+            (_, 0) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r2-src unexpectedly ended before r1-src");
+            }
+            (_, _) => {
+                write_pair(&s1, &s2)?;
+                n += 1;
+            }
+        }
+    }
+
+    Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_head_single() -> anyhow::Result<()> {
+        let data = b"@r0\nAC\n+\nFQ\n@r1\nGT\n+\nLI\n@r2\nAA\n+\nBF\n";
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+        let n = head_single(&mut reader, &mut writer, 2)?;
+
+        assert_eq!(n, 2);
+        assert_eq!(writer.get_ref(), b"@r0\nAC\n+\nFQ\n@r1\nGT\n+\nLI\n");
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+        assert_eq!(head_single(&mut reader, &mut writer, 8)?, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_head_paired() -> anyhow::Result<()> {
+        let r1_data = b"@r0/1\nAC\n+\nFQ\n@r1/1\nGT\n+\nLI\n";
+        let r2_data = b"@r0/2\nTT\n+\nFQ\n@r1/2\nCC\n+\nLI\n";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+
+        let n = head_paired(&mut r1, &mut r2, 1, |s1, s2| {
+            writer.write_record(s1)?;
+            writer.write_record(s2)
+        })?;
+
+        assert_eq!(n, 1);
+        assert_eq!(writer.get_ref(), b"@r0/1\nAC\n+\nFQ\n@r0/2\nTT\n+\nFQ\n");
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..14]);
+        assert!(head_paired(&mut r1, &mut r2, 2, |_, _| Ok(())).is_err());
+
+        Ok(())
+    }
+}
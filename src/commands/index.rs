@@ -0,0 +1,24 @@
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::index;
+
+pub fn index(matches: &ArgMatches) -> anyhow::Result<()> {
+    let src = matches.value_of("src").unwrap();
+
+    info!("fq-index start");
+
+    let index = index::build_file(src).with_context(|| format!("Could not index file: {}", src))?;
+
+    info!(
+        "indexed {} records ({}) to {}",
+        index.entries().len(),
+        index.format(),
+        index::index_path(src).display()
+    );
+
+    info!("fq-index end");
+
+    Ok(())
+}
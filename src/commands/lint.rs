@@ -1,287 +1,2553 @@
+mod fix;
+mod index;
+mod offsets;
+mod output;
+mod progress;
+mod report;
+mod sample;
+mod summary;
+
 use std::{
-    io::{self, BufRead},
-    process,
+    fmt,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, IsTerminal, Write},
+    path::Path,
+    process, thread,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context};
+use clap::ArgMatches;
+use serde_json::{json, Map, Value};
+use tracing::{error, info, warn};
+
+use self::{
+    offsets::RecordOffsets,
+    output::{Diagnostic, Severity},
+    progress::Progress,
+    report::Report,
+    sample::Sampling,
+    summary::Summary,
 };
+use super::describe;
+use crate::{
+    fastq::{self, Record},
+    metrics::Metrics,
+    quality::Detector,
+    stats::{self, Adapter},
+    validators::{
+        self,
+        single::{DuplicateNameValidator, DuplicateSequenceValidator},
+        IdenticalMatesValidator, LineType, LintMode, MalformedRecordValidator, NContentValidator,
+        NameConsistencyValidator, NameFormat, NameFormatValidator, PairedReadValidator,
+        PlusLineStyle, PlusLineStyleValidator, QualityEncodingValidator, ReadLengthValidator,
+        RuleValidator, SequenceLengthValidator, SingleReadValidator, SingleReadValidatorMut,
+        TruncatedFileValidator, ValidationLevel, WhitespaceValidator,
+    },
+};
+
+// The source name for stdin.
+const STDIN: &str = "-";
+
+// A lint report and where to write it.
+struct ReportDestination {
+    report: Report,
+    format: report::Format,
+    dst: String,
+}
+
+// Read statistics of each source and where to write them.
+struct StatsDestination {
+    stats: Vec<(String, stats::Summary)>,
+    dst: String,
+}
+
+// Handles validation errors by exiting or logging, depending on the lint mode. Errors are
+// written in the output format, summarized, and, if requested, added to a report.
+//
+// Errors of validators in `warn_validators` are downgraded to warnings, which are written and
+// summarized but never stop validation or fail it.
+struct ErrorHandler {
+    lint_mode: LintMode,
+    max_errors: Option<u64>,
+    warn_validators: Vec<String>,
+    output_format: output::Format,
+    // The output file, if any, and its path. Without it, output is written to stdout, and text
+    // errors are logged.
+    output: Option<(BufWriter<File>, String)>,
+    summary: Summary,
+    metrics: Metrics,
+    report: Option<ReportDestination>,
+    // The path of the summary document, if any.
+    summary_dst: Option<String>,
+    // Read statistics collected from the validated records, if requested.
+    stats: Option<StatsDestination>,
+    // The codes and names of the enabled validators.
+    validators: Vec<(String, String)>,
+    // Errors held for SARIF output, which is written as a single document.
+    diagnostics: Vec<Diagnostic>,
+    // The difference between the line numbers of the records of the current source and their
+    // positions assuming four lines per record, which changes when malformed records are skipped.
+    line_offset: isize,
+    // The offsets of the records of gzipped sources in the current batch.
+    record_offsets: Vec<RecordOffsets>,
+}
+
+impl ErrorHandler {
+    fn add_validator(&mut self, code: &str, name: &str) {
+        if !self.validators.iter().any(|(c, _)| c == code) {
+            self.validators.push((code.into(), name.into()));
+        }
+
+        if let Some(destination) = self.report.as_mut() {
+            destination.report.add_validator(code, name);
+        }
+    }
+
+    // Adds a validated record of a source to the read statistics, if any.
+    fn add_stats(&mut self, src: &str, record: &Record) {
+        if let Some(destination) = self.stats.as_mut() {
+            if let Some((_, stats)) = destination.stats.iter_mut().find(|(s, _)| s == src) {
+                stats.add(record);
+            }
+        }
+    }
+
+    fn handle(&mut self, mut diagnostic: Diagnostic) {
+        if self.warn_validators.contains(&diagnostic.code) {
+            diagnostic.severity = Severity::Warning;
+        }
+
+        self.summary.add(&diagnostic);
+
+        let severity = diagnostic.severity;
+        let message = diagnostic.to_string();
+
+        if severity == Severity::Error {
+            self.metrics.add_errors(1);
+
+            if let Some(destination) = self.report.as_mut() {
+                destination
+                    .report
+                    .add_error(&diagnostic.code, &diagnostic.name, message.clone());
+            }
+        }
+
+        match (self.output_format, self.lint_mode, severity) {
+            (output::Format::Text, _, _) if self.output.is_some() => self.write_output(&message),
+            (output::Format::Text, _, Severity::Warning) => warn!("{}", message),
+            (output::Format::Text, LintMode::Panic, Severity::Error) => eprintln!("{}", message),
+            (output::Format::Text, LintMode::Log, Severity::Error) => error!("{}", message),
+            (output::Format::Json, _, _) => self.write_output(&diagnostic.to_json()),
+            (output::Format::Sarif, _, _) => self.diagnostics.push(diagnostic),
+            (output::Format::Delimited(delimiter), _, _) => {
+                self.write_output(&diagnostic.to_row(delimiter))
+            }
+        }
+
+        if severity == Severity::Warning {
+            return;
+        }
+
+        if self.lint_mode == LintMode::Panic {
+            self.abort();
+        }
+
+        if let Some(max_errors) = self.max_errors {
+            if self.summary.error_count() >= max_errors {
+                error!(
+                    "stopping validation after reaching the maximum number of errors ({})",
+                    max_errors
+                );
+
+                self.abort();
+            }
+        }
+    }
+
+    // Writes a line to the output file, if any, or stdout.
+    fn write_output(&mut self, line: &dyn fmt::Display) {
+        if let Some((writer, dst)) = self.output.as_mut() {
+            if let Err(e) = writeln!(writer, "{}", line) {
+                error!("Could not write file: {}: {}", dst, e);
+                process::exit(1);
+            }
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    // Writes the header row of delimited output, if any.
+    fn start(&mut self) {
+        if let output::Format::Delimited(delimiter) = self.output_format {
+            self.write_output(&output::build_delimited_header(delimiter));
+        }
+    }
+
+    fn abort(&mut self) -> ! {
+        if let Err(e) = self.finish() {
+            error!("{:#}", e);
+        }
+
+        process::exit(1);
+    }
+
+    fn handle_validation_error(
+        &mut self,
+        error: validators::Error,
+        pathname: &str,
+        record_counter: usize,
+    ) {
+        let mut diagnostic = Diagnostic::from_record_error(error, pathname, record_counter);
+
+        if let Some(line_no) = diagnostic.line_no.as_mut() {
+            *line_no = line_no.saturating_add_signed(self.line_offset);
+        }
+
+        if let Some(offsets) = self.record_offsets.iter_mut().find(|o| o.src() == pathname) {
+            (diagnostic.offset, diagnostic.virtual_offset) = offsets.get(record_counter);
+        }
+
+        self.handle(diagnostic);
+    }
+
+    // Tracks the record offsets of a source, if it is gzipped, to report them with its errors.
+    fn add_record_offsets(&mut self, src: &str) -> anyhow::Result<()> {
+        if self
+            .record_offsets
+            .iter()
+            .any(|offsets| offsets.src() == src)
+        {
+            return Ok(());
+        }
+
+        if let Some(offsets) =
+            RecordOffsets::open(src).with_context(|| format!("Could not open file: {}", src))?
+        {
+            self.record_offsets.push(offsets);
+        }
+
+        Ok(())
+    }
+
+    // Removes the record offsets of the last batch.
+    fn clear_record_offsets(&mut self) {
+        for offsets in &mut self.record_offsets {
+            offsets.clear();
+        }
+    }
+
+    // Adds the uncompressed offset of a record of a gzipped source.
+    fn add_record_offset(&mut self, src: &str, record_index: usize, offset: u64) {
+        if let Some(offsets) = self.record_offsets.iter_mut().find(|o| o.src() == src) {
+            offsets.push(record_index, offset);
+        }
+    }
+
+    fn handle_file_validation_error(&mut self, error: validators::Error, pathname: &str) {
+        self.handle(Diagnostic::from_file_error(error, pathname));
+    }
+
+    // Logs the summary and writes the JSON summary or SARIF log, if any, the report, if any, the
+    // summary document, if any, and the read statistics, if any.
+    fn finish(&mut self) -> anyhow::Result<()> {
+        self.summary.log();
+
+        match self.output_format {
+            output::Format::Text | output::Format::Delimited(_) => {}
+            output::Format::Json => {
+                let sources: Vec<_> = self.summary.files().map(|(src, _)| src).collect();
+
+                let summary = output::build_json_summary(
+                    &sources,
+                    self.summary.error_count(),
+                    self.summary.warning_count(),
+                );
+
+                self.write_output(&summary);
+            }
+            output::Format::Sarif => {
+                let sarif = output::build_sarif(&self.validators, &self.diagnostics);
+                self.write_output(&sarif);
+            }
+        }
+
+        if let Some((writer, dst)) = self.output.as_mut() {
+            writer
+                .flush()
+                .with_context(|| format!("Could not write file: {}", dst))?;
+        }
+
+        self.write_report()?;
+        self.write_summary()?;
+        self.write_stats()
+    }
+
+    fn write_report(&self) -> anyhow::Result<()> {
+        if let Some(destination) = &self.report {
+            let dst = &destination.dst;
+
+            let file =
+                File::create(dst).with_context(|| format!("Could not create file: {}", dst))?;
+            let mut writer = BufWriter::new(file);
+
+            destination
+                .report
+                .write(&mut writer, destination.format)
+                .with_context(|| format!("Could not write file: {}", dst))?;
+        }
+
+        Ok(())
+    }
+
+    fn write_summary(&self) -> anyhow::Result<()> {
+        if let Some(dst) = &self.summary_dst {
+            let summary = self
+                .summary
+                .to_json(&self.validators, self.metrics.elapsed());
+
+            let file =
+                File::create(dst).with_context(|| format!("Could not create file: {}", dst))?;
+            let mut writer = BufWriter::new(file);
+
+            writeln!(writer, "{}", summary)
+                .and_then(|_| writer.flush())
+                .with_context(|| format!("Could not write file: {}", dst))?;
+        }
+
+        Ok(())
+    }
+
+    fn write_stats(&self) -> anyhow::Result<()> {
+        if let Some(destination) = &self.stats {
+            let dst = &destination.dst;
+
+            let files = destination
+                .stats
+                .iter()
+                .map(|(src, stats)| {
+                    let mut fields = Map::new();
+                    fields.insert(String::from("file"), Value::from(src.as_str()));
+
+                    if let Value::Object(summary) = describe::build_json(stats) {
+                        fields.extend(summary);
+                    }
+
+                    let length_distribution: Vec<_> = stats
+                        .length_distribution()
+                        .map(|(len, n)| json!({ "length": len, "count": n }))
+                        .collect();
+
+                    fields.insert(
+                        String::from("lengthDistribution"),
+                        Value::Array(length_distribution),
+                    );
+
+                    Value::Object(fields)
+                })
+                .collect::<Vec<_>>();
+
+            let value = json!({
+                "partial": self.summary.is_partial(),
+                "files": files,
+            });
+
+            let file =
+                File::create(dst).with_context(|| format!("Could not create file: {}", dst))?;
+            let mut writer = BufWriter::new(file);
+
+            writeln!(writer, "{}", value)
+                .and_then(|_| writer.flush())
+                .with_context(|| format!("Could not write file: {}", dst))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn finish_validators(
+    validators: &mut [Box<dyn SingleReadValidatorMut>],
+    handler: &mut ErrorHandler,
+    pathname: &str,
+) {
+    for validator in validators {
+        validator
+            .finish()
+            .unwrap_or_else(|e| handler.handle_file_validation_error(e, pathname));
+    }
+}
+
+// Returns the truncated file validator if it is within the validation level and not disabled.
+fn filter_truncated_file_validator(
+    validation_level: ValidationLevel,
+    disabled_validators: &[String],
+) -> Option<TruncatedFileValidator> {
+    let validator = TruncatedFileValidator;
+
+    if validator.level() <= validation_level
+        && !disabled_validators.contains(&validator.code().to_string())
+    {
+        Some(validator)
+    } else {
+        None
+    }
+}
+
+// Validators that check records as read, i.e., before carriage returns are stripped and records
+// are reset, so they are not run with the other single read validators.
+struct RawRecordValidators {
+    whitespace: Option<WhitespaceValidator>,
+    plus_line_style: Option<PlusLineStyleValidator>,
+}
+
+impl RawRecordValidators {
+    // Creates the validators that are within the validation level and not disabled.
+    fn new(
+        validation_level: ValidationLevel,
+        disabled_validators: &[String],
+        plus_line_style: PlusLineStyle,
+    ) -> Self {
+        let is_enabled = |code: &str, level: ValidationLevel| {
+            level <= validation_level && !disabled_validators.iter().any(|c| c == code)
+        };
+
+        let whitespace = Some(WhitespaceValidator).filter(|v| is_enabled(v.code(), v.level()));
+
+        let plus_line_style = Some(PlusLineStyleValidator::new(plus_line_style))
+            .filter(|v| is_enabled(v.code(), v.level()));
+
+        Self {
+            whitespace,
+            plus_line_style,
+        }
+    }
+
+    fn add_to(&self, handler: &mut ErrorHandler) {
+        if let Some(validator) = &self.whitespace {
+            handler.add_validator(validator.code(), validator.name());
+        }
+
+        if let Some(validator) = &self.plus_line_style {
+            handler.add_validator(validator.code(), validator.name());
+        }
+    }
+
+    // Returns whether the reader must keep the carriage returns of CRLF line endings.
+    fn keeps_carriage_returns(&self) -> bool {
+        self.whitespace.is_some()
+    }
+
+    // Validates a record and then strips carriage returns, if kept. Errors are added to `errors`.
+    fn validate(&mut self, record: &mut Record, errors: &mut Vec<validators::Error>) {
+        if let Some(validator) = &self.whitespace {
+            if let Err(e) = validator.validate(record) {
+                errors.push(e);
+            }
+
+            strip_carriage_returns(record);
+        }
+
+        if let Some(validator) = &mut self.plus_line_style {
+            if let Err(e) = validator.validate(record) {
+                errors.push(e);
+            }
+        }
+    }
+}
+
+// Removes the carriage return of a CRLF line ending from each line, as the reader does by default.
+fn strip_carriage_returns(record: &mut Record) {
+    fn strip(buf: &mut Vec<u8>) {
+        if buf.ends_with(b"\r") {
+            buf.pop();
+        }
+    }
+
+    strip(record.name_mut());
+    strip(record.sequence_mut());
+    strip(record.plus_line_mut());
+    strip(record.quality_scores_mut());
+}
+
+// Reads a record and returns its length, which is 0 at the end of the source.
+//
+// When a truncated file validator is given, an input that ends mid-record is handled as a
+// validation error, and `None` is returned. Otherwise, it is a read error.
+fn read_record<R>(
+    reader: &mut fastq::Reader<R>,
+    record: &mut Record,
+    truncated_file_validator: Option<&TruncatedFileValidator>,
+    pathname: &str,
+) -> anyhow::Result<Result<usize, validators::Error>>
+where
+    R: BufRead,
+{
+    match reader.read_complete_record(record) {
+        Ok(len) => Ok(Ok(len)),
+        Err(e) => match truncated_file_validator.map(|v| v.validate(&e)) {
+            Some(Err(error)) => Ok(Err(error)),
+            _ => Err(e).with_context(|| format!("Could not read record from file: {}", pathname)),
+        },
+    }
+}
+
+// Runs `f` on each item and returns the output of each item, in order.
+//
+// With more than one thread, items are split into contiguous chunks that are validated in
+// parallel, so the order of the outputs does not depend on scheduling.
+fn validate_batch<T, E, F>(items: &[T], threads: usize, f: F) -> Vec<E>
+where
+    T: Sync,
+    E: Default + Send,
+    F: Fn(&T, &mut E) + Sync,
+{
+    let mut outputs: Vec<E> = items.iter().map(|_| E::default()).collect();
+
+    if threads <= 1 || items.len() <= 1 {
+        for (item, output) in items.iter().zip(&mut outputs) {
+            f(item, output);
+        }
+
+        return outputs;
+    }
+
+    let chunk_size = items.len().div_ceil(threads);
+    let f = &f;
+
+    thread::scope(|scope| {
+        for (items, outputs) in items.chunks(chunk_size).zip(outputs.chunks_mut(chunk_size)) {
+            scope.spawn(move || {
+                for (item, output) in items.iter().zip(outputs) {
+                    f(item, output);
+                }
+            });
+        }
+    });
+
+    outputs
+}
+
+// Returns an error for a source that ended before its mate.
+fn unexpected_eof(message: String) -> anyhow::Error {
+    anyhow::Error::new(io::Error::from(io::ErrorKind::UnexpectedEof)).context(message)
+}
+
+// Returns whether both sources are the same file or are byte-identical.
+//
+// Sources of different sizes are not compared, and sources of the same size are compared until
+// the first difference.
+fn is_identical_source<P, Q>(r1_src: P, r2_src: Q) -> io::Result<bool>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let r1_src = r1_src.as_ref();
+    let r2_src = r2_src.as_ref();
+
+    if fs::canonicalize(r1_src)? == fs::canonicalize(r2_src)? {
+        return Ok(true);
+    }
+
+    if fs::metadata(r1_src)?.len() != fs::metadata(r2_src)?.len() {
+        return Ok(false);
+    }
+
+    let mut r1_reader = BufReader::new(File::open(r1_src)?);
+    let mut r2_reader = BufReader::new(File::open(r2_src)?);
+
+    loop {
+        let r1_buf = r1_reader.fill_buf()?;
+        let r2_buf = r2_reader.fill_buf()?;
+
+        let len = r1_buf.len().min(r2_buf.len());
+
+        if len == 0 {
+            return Ok(r1_buf.is_empty() && r2_buf.is_empty());
+        } else if r1_buf[..len] != r2_buf[..len] {
+            return Ok(false);
+        }
+
+        r1_reader.consume(len);
+        r2_reader.consume(len);
+    }
+}
+
+fn check_identical_sources(
+    handler: &mut ErrorHandler,
+    r1_src: &str,
+    r2_src: &str,
+) -> anyhow::Result<()> {
+    let validator = IdenticalMatesValidator;
+    handler.add_validator(validator.code(), validator.name());
+
+    let is_identical = is_identical_source(r1_src, r2_src)
+        .with_context(|| format!("Could not compare files: {}, {}", r1_src, r2_src))?;
+
+    if is_identical {
+        let error = validators::Error::new(
+            validator.code(),
+            validator.name(),
+            format!("{} and {} are identical", r1_src, r2_src),
+            LineType::Name,
+            None,
+        );
+
+        handler.handle_file_validation_error(error, r1_src);
+    }
+
+    Ok(())
+}
+
+// Creates the destinations of fixed records and a fixer that writes to them.
+//
+// Fixing writes a single input, and paired reads need a read 2 destination unless they are
+// interleaved.
+fn create_fixer(
+    matches: &ArgMatches,
+    inputs: &[Input],
+    plus_line_style: PlusLineStyle,
+    metrics: &Metrics,
+) -> anyhow::Result<fix::Fixer> {
+    let mut policies = Vec::new();
+
+    for s in matches.values_of("fix-policy").unwrap_or_default() {
+        policies.push(fix::parse_policy(s)?);
+    }
+
+    let r1_dst = matches.value_of("r1-dst").unwrap();
+    let r2_dst = matches.value_of("r2-dst");
+
+    match (&inputs[0], r2_dst) {
+        (Input::Pair(_, r2_src), None) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-dst for {}", r2_src));
+        }
+        (Input::Single(_), Some(r2_dst)) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-src for {}", r2_dst));
+        }
+        _ => {}
+    }
+
+    let mut w1 =
+        fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+    w1.set_metrics(metrics.clone());
+
+    let w2 = match r2_dst {
+        Some(r2_dst) => {
+            let mut w2 = fastq::create(r2_dst)
+                .with_context(|| format!("Could not create file: {}", r2_dst))?;
+            w2.set_metrics(metrics.clone());
+            Some(w2)
+        }
+        None => None,
+    };
+
+    fix::Fixer::new(policies, plus_line_style, w1, w2)
+}
+
+// Validators that are only used when requested, e.g., with an expected value.
+#[derive(Clone, Debug, Default)]
+struct OptionalValidators {
+    max_n_fraction: Option<f64>,
+    name_format: Option<NameFormat>,
+    sequence_length: Option<SequenceLengthValidator>,
+    read_length: Option<ReadLengthValidator>,
+    name_consistency: Option<NameConsistencyValidator>,
+    quality_encoding: Option<QualityEncodingValidator>,
+    max_duplicate_rate: Option<f64>,
+    // The maximum memory, in bytes, of the duplicate name filter.
+    max_duplicate_name_memory: Option<usize>,
+    // User-defined validators.
+    rules: Vec<RuleValidator>,
+    // Whether malformed records are reported and skipped.
+    resync: bool,
+}
+
+impl OptionalValidators {
+    // Returns a new duplicate name validator, which is bounded by the requested memory, if any,
+    // if it is within the validation level and not disabled.
+    fn duplicate_name_validator(
+        &self,
+        validation_level: ValidationLevel,
+        disabled_validators: &[String],
+    ) -> Option<DuplicateNameValidator> {
+        let validator = match self.max_duplicate_name_memory {
+            Some(max_memory) => DuplicateNameValidator::with_max_memory(max_memory),
+            None => DuplicateNameValidator::new(),
+        };
+
+        Some(validator)
+            .filter(|v| v.level() <= validation_level)
+            .filter(|v| !disabled_validators.contains(&v.code().to_string()))
+    }
+
+    // Adds the requested stateless validators and the rules that are within the validation level
+    // and not disabled.
+    fn push_to(
+        &self,
+        validators: &mut Vec<Box<dyn SingleReadValidator>>,
+        validation_level: ValidationLevel,
+        disabled_validators: &[String],
+    ) {
+        let requested_validators: [Option<Box<dyn SingleReadValidator>>; 3] = [
+            self.max_n_fraction
+                .map(|max_fraction| Box::new(NContentValidator::new(max_fraction)) as _),
+            self.name_format
+                .map(|format| Box::new(NameFormatValidator::new(format)) as _),
+            self.sequence_length.map(|v| Box::new(v) as _),
+        ];
+
+        validators.extend(
+            requested_validators
+                .into_iter()
+                .flatten()
+                .chain(self.rules.iter().map(|v| Box::new(v.clone()) as _))
+                .filter(|v| v.level() <= validation_level)
+                .filter(|v| !disabled_validators.contains(&v.code().to_string())),
+        );
+    }
+
+    // Returns a new duplicate sequence validator, if requested, within the validation level, and
+    // not disabled.
+    //
+    // Unlike the other stateful validators, this checks templates, so paired reads share one
+    // instance.
+    fn duplicate_sequence_validator(
+        &self,
+        validation_level: ValidationLevel,
+        disabled_validators: &[String],
+    ) -> Option<DuplicateSequenceValidator> {
+        self.max_duplicate_rate
+            .map(DuplicateSequenceValidator::new)
+            .filter(|v| v.level() <= validation_level)
+            .filter(|v| !disabled_validators.contains(&v.code().to_string()))
+    }
+
+    // Returns the malformed record validator, if resyncing is requested and the validator is
+    // within the validation level and not disabled.
+    fn malformed_record_validator(
+        &self,
+        validation_level: ValidationLevel,
+        disabled_validators: &[String],
+    ) -> Option<MalformedRecordValidator> {
+        Some(MalformedRecordValidator)
+            .filter(|_| self.resync)
+            .filter(|v| v.level() <= validation_level)
+            .filter(|v| !disabled_validators.contains(&v.code().to_string()))
+    }
+
+    // Adds new instances of the requested stateful validators that are within the validation
+    // level and not disabled.
+    fn push_mut_to(
+        &self,
+        validators: &mut Vec<Box<dyn SingleReadValidatorMut>>,
+        validation_level: ValidationLevel,
+        disabled_validators: &[String],
+    ) {
+        let requested_validators: [Option<Box<dyn SingleReadValidatorMut>>; 3] = [
+            self.read_length.clone().map(|v| Box::new(v) as _),
+            self.name_consistency.clone().map(|v| Box::new(v) as _),
+            self.quality_encoding.clone().map(|v| Box::new(v) as _),
+        ];
+
+        validators.extend(
+            requested_validators
+                .into_iter()
+                .flatten()
+                .filter(|v| v.level() <= validation_level)
+                .filter(|v| !disabled_validators.contains(&v.code().to_string())),
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn validate_single(
+    mut reader: fastq::Reader<impl BufRead>,
+    single_read_validation_level: ValidationLevel,
+    disabled_validators: &[String],
+    validator_config: &validators::Config,
+    optional_validators: &OptionalValidators,
+    plus_line_style: PlusLineStyle,
+    threads: usize,
+    sampling: Sampling,
+    mut fixer: Option<&mut fix::Fixer>,
+    handler: &mut ErrorHandler,
+    r1_src: &str,
+) -> anyhow::Result<()> {
+    let (mut single_read_validators, mut single_read_validators_mut, _) =
+        validators::filter_validators(
+            single_read_validation_level,
+            None,
+            disabled_validators,
+            validator_config,
+        );
+
+    optional_validators.push_to(
+        &mut single_read_validators,
+        single_read_validation_level,
+        disabled_validators,
+    );
+
+    optional_validators.push_mut_to(
+        &mut single_read_validators_mut,
+        single_read_validation_level,
+        disabled_validators,
+    );
+
+    if let Some(validator) = optional_validators
+        .duplicate_sequence_validator(single_read_validation_level, disabled_validators)
+    {
+        single_read_validators_mut.push(Box::new(validator));
+    }
+
+    for validator in &single_read_validators {
+        handler.add_validator(validator.code(), validator.name());
+    }
+
+    for validator in &single_read_validators_mut {
+        handler.add_validator(validator.code(), validator.name());
+    }
+
+    let truncated_file_validator =
+        filter_truncated_file_validator(single_read_validation_level, disabled_validators);
+
+    if let Some(validator) = &truncated_file_validator {
+        handler.add_validator(validator.code(), validator.name());
+    }
+
+    let malformed_record_validator = optional_validators
+        .malformed_record_validator(single_read_validation_level, disabled_validators);
+
+    if let Some(validator) = &malformed_record_validator {
+        handler.add_validator(validator.code(), validator.name());
+    }
+
+    let mut raw_record_validators = RawRecordValidators::new(
+        single_read_validation_level,
+        disabled_validators,
+        plus_line_style,
+    );
+
+    raw_record_validators.add_to(handler);
+    reader.set_strip_carriage_returns(!raw_record_validators.keeps_carriage_returns());
+
+    reader.set_metrics(handler.metrics.clone());
+    handler.line_offset = 0;
+    handler.add_record_offsets(r1_src)?;
+
+    info!("starting validation");
+    let _stage = handler.metrics.stage("validation");
+
+    let batch_size = batch_size(threads);
+
+    let mut records = vec![Record::default(); batch_size];
+    let mut raw_errors: Vec<Vec<_>> = (0..batch_size).map(|_| Vec::new()).collect();
+    let mut record_indices = vec![0; batch_size];
+    let mut record_counter = 0;
+    let mut detector = Detector::default();
+
+    // The names of the records before they are reset and the codes of their errors, for fixing.
+    let mut names = vec![Vec::new(); batch_size];
+    let mut codes = vec![Vec::new(); batch_size];
+
+    loop {
+        let mut len = 0;
+        let mut truncated_file_error = None;
+        let mut malformed_record_error = None;
+        let mut is_eof = false;
+        let mut is_done = false;
+        let batch_start = record_counter;
+
+        handler.clear_record_offsets();
+
+        while len < batch_size {
+            if sampling.is_done(record_counter) {
+                // Validation is only partial if another record remains.
+                if reader
+                    .is_eof()
+                    .with_context(|| format!("Could not read file: {}", r1_src))?
+                {
+                    is_eof = true;
+                } else {
+                    is_done = true;
+                }
+
+                break;
+            }
+
+            handler.add_record_offset(r1_src, record_counter, reader.position());
+
+            match read_record(
+                &mut reader,
+                &mut records[len],
+                truncated_file_validator.as_ref(),
+                r1_src,
+            )? {
+                Ok(0) => {
+                    is_eof = true;
+                    break;
+                }
+                Ok(_) => {
+                    if let Some(validator) = &malformed_record_validator {
+                        if let Err(e) = validator.validate(&records[len]) {
+                            let skipped_lines = reader
+                                .resync(&records[len])
+                                .with_context(|| format!("Could not read file: {}", r1_src))?;
+
+                            malformed_record_error = Some((e, skipped_lines));
+                            break;
+                        }
+                    }
+
+                    // Records that are not sampled are read into the next slot but not
+                    // validated.
+                    if sampling.is_sampled(record_counter) {
+                        record_indices[len] = record_counter;
+                        len += 1;
+                    }
+
+                    record_counter += 1;
+                }
+                Err(e) => {
+                    truncated_file_error = Some(e);
+                    break;
+                }
+            }
+        }
+
+        handler
+            .summary
+            .add_records(r1_src, (record_counter - batch_start) as u64);
+
+        for ((record, errors), name) in records[..len]
+            .iter_mut()
+            .zip(&mut raw_errors)
+            .zip(&mut names)
+        {
+            raw_record_validators.validate(record, errors);
+
+            if fixer.is_some() {
+                name.clone_from(record.name_mut());
+            }
+
+            record.reset();
+            detector.add(record.quality_scores());
+            handler.add_stats(r1_src, record);
+        }
+
+        let batch = &records[..len];
+
+        let errors = validate_batch(batch, threads, |record, errors: &mut Vec<_>| {
+            for validator in &single_read_validators {
+                if let Err(e) = validator.validate(record) {
+                    errors.push(e);
+                }
+            }
+        });
+
+        for ((((record, raw_errors), errors), codes), &record_index) in batch
+            .iter()
+            .zip(&mut raw_errors)
+            .zip(errors)
+            .zip(&mut codes)
+            .zip(&record_indices)
+        {
+            codes.clear();
+
+            for e in raw_errors.drain(..).chain(errors) {
+                codes.push(e.code.clone());
+                handler.handle_validation_error(e, r1_src, record_index);
+            }
+
+            for validator in &mut single_read_validators_mut {
+                if let Err(e) = validator.validate(record) {
+                    codes.push(e.code.clone());
+                    handler.handle_validation_error(e, r1_src, record_index);
+                }
+            }
+        }
+
+        if let Some(fixer) = fixer.as_mut() {
+            for ((record, name), codes) in records[..len].iter_mut().zip(&names).zip(&codes) {
+                record.name_mut().clone_from(name);
+                fixer.write_record(record, codes)?;
+            }
+        }
+
+        if let Some((e, skipped_lines)) = malformed_record_error {
+            if let Some(fixer) = fixer.as_mut() {
+                fixer.add_dropped(&e.code);
+            }
+
+            handler.summary.add_records(r1_src, 1);
+            handler.handle_validation_error(e, r1_src, record_counter);
+
+            // The malformed record spans its name line and the skipped lines instead of four
+            // lines.
+            handler.line_offset += skipped_lines as isize - 3;
+            record_counter += 1;
+
+            continue;
+        }
+
+        if let Some(e) = truncated_file_error {
+            if let Some(fixer) = fixer.as_mut() {
+                fixer.add_dropped(&e.code);
+            }
+
+            handler.handle_validation_error(e, r1_src, record_counter);
+            break;
+        }
+
+        if is_done {
+            handler.summary.set_stopped();
+            break;
+        }
+
+        if is_eof {
+            break;
+        }
+    }
+
+    handler.summary.set_encoding(r1_src, detector.encoding());
+    finish_validators(&mut single_read_validators_mut, handler, r1_src);
+
+    info!("read {} records", record_counter);
+
+    Ok(())
+}
+
+// The number of records, or pairs, read and validated at a time with more than one thread.
+const BATCH_SIZE: usize = 4096;
+
+// Returns the number of records, or pairs, to read at a time.
+//
+// A single thread validates each record as it is read, e.g., so followed sources are reported
+// as they grow.
+fn batch_size(threads: usize) -> usize {
+    if threads > 1 {
+        BATCH_SIZE
+    } else {
+        1
+    }
+}
+
+// A read of a pair.
+#[derive(Clone, Copy)]
+enum Mate {
+    R1,
+    R2,
+}
+
+// Errors of a pair from validators that are run on a batch, in validation order.
+#[derive(Default)]
+struct PairErrors {
+    single: Vec<(Mate, validators::Error)>,
+    paired: Vec<validators::Error>,
+}
+
+// Validates paired reads.
+//
+// Without `reader_2`, the source is interleaved, i.e., the mates of each pair are consecutive
+// records in `reader_1`, and `r2_src` is the same as `r1_src`.
+#[allow(clippy::too_many_arguments)]
+fn validate_pair(
+    mut reader_1: fastq::Reader<impl BufRead>,
+    mut reader_2: Option<fastq::Reader<impl BufRead>>,
+    single_read_validation_level: ValidationLevel,
+    paired_read_validation_level: ValidationLevel,
+    disabled_validators: &[String],
+    validator_config: &validators::Config,
+    optional_validators: &OptionalValidators,
+    plus_line_style: PlusLineStyle,
+    threads: usize,
+    sampling: Sampling,
+    mut fixer: Option<&mut fix::Fixer>,
+    mut index_reads: Option<&mut index::IndexReads>,
+    handler: &mut ErrorHandler,
+    r1_src: &str,
+    r2_src: &str,
+) -> anyhow::Result<()> {
+    let (mut single_read_validators, mut r1_single_read_validators_mut, paired_read_validators) =
+        validators::filter_validators(
+            single_read_validation_level,
+            Some(paired_read_validation_level),
+            disabled_validators,
+            validator_config,
+        );
+
+    let mut r2_single_read_validators_mut = validators::filter_single_read_validators_mut(
+        single_read_validation_level,
+        disabled_validators,
+        validator_config,
+    );
+
+    optional_validators.push_to(
+        &mut single_read_validators,
+        single_read_validation_level,
+        disabled_validators,
+    );
+
+    for validators in [
+        &mut r1_single_read_validators_mut,
+        &mut r2_single_read_validators_mut,
+    ] {
+        optional_validators.push_mut_to(
+            validators,
+            single_read_validation_level,
+            disabled_validators,
+        );
+    }
+
+    let mut duplicate_sequence_validator = optional_validators
+        .duplicate_sequence_validator(single_read_validation_level, disabled_validators);
+
+    let mut duplicate_name_validator = optional_validators
+        .duplicate_name_validator(single_read_validation_level, disabled_validators);
+
+    // Duplicate names are confirmed by reading read 1 again, which is not possible for stdin.
+    if r1_src == STDIN {
+        if let Some(validator) = duplicate_name_validator.take() {
+            warn!(
+                "[{}] {} is skipped when reading from stdin",
+                validator.code(),
+                validator.name()
+            );
+        }
+    }
+
+    let validators = duplicate_name_validator
+        .as_ref()
+        .map(|v| format!(r#""[{}] {}""#, v.code(), v.name()))
+        .unwrap_or_default();
+
+    info!("enabled special validators: [{}]", validators);
+
+    for validator in &single_read_validators {
+        handler.add_validator(validator.code(), validator.name());
+    }
+
+    for validator in &r1_single_read_validators_mut {
+        handler.add_validator(validator.code(), validator.name());
+    }
+
+    if let Some(validator) = &duplicate_name_validator {
+        handler.add_validator(validator.code(), validator.name());
+    }
+
+    let truncated_file_validator =
+        filter_truncated_file_validator(single_read_validation_level, disabled_validators);
+
+    if let Some(validator) = &truncated_file_validator {
+        handler.add_validator(validator.code(), validator.name());
+    }
+
+    if let Some(validator) = &duplicate_sequence_validator {
+        handler.add_validator(validator.code(), validator.name());
+    }
+
+    let mut r1_raw_record_validators = RawRecordValidators::new(
+        single_read_validation_level,
+        disabled_validators,
+        plus_line_style,
+    );
+
+    let mut r2_raw_record_validators = RawRecordValidators::new(
+        single_read_validation_level,
+        disabled_validators,
+        plus_line_style,
+    );
+
+    r1_raw_record_validators.add_to(handler);
+
+    let strip_carriage_returns = !r1_raw_record_validators.keeps_carriage_returns();
+    reader_1.set_strip_carriage_returns(strip_carriage_returns);
+
+    if let Some(reader) = reader_2.as_mut() {
+        reader.set_strip_carriage_returns(strip_carriage_returns);
+    }
+
+    for validator in &paired_read_validators {
+        handler.add_validator(validator.code(), validator.name());
+    }
+
+    let metrics = handler.metrics.clone();
+
+    reader_1.set_metrics(metrics.clone());
+
+    if let Some(reader) = reader_2.as_mut() {
+        reader.set_metrics(metrics.clone());
+    }
+
+    handler.add_record_offsets(r1_src)?;
+    handler.add_record_offsets(r2_src)?;
+
+    let is_interleaved = reader_2.is_none();
+
+    info!("starting validation (pass 1)");
+    let stage = metrics.stage("validation (pass 1)");
+
+    // The indices of the records of a pair in their sources.
+    let indices = |n: usize| {
+        if is_interleaved {
+            (n * 2, n * 2 + 1)
+        } else {
+            (n, n)
+        }
+    };
+
+    let batch_size = batch_size(threads);
+
+    let mut r1_records = vec![Record::default(); batch_size];
+    let mut r2_records = vec![Record::default(); batch_size];
+    let mut r1_raw_errors: Vec<Vec<_>> = (0..batch_size).map(|_| Vec::new()).collect();
+    let mut r2_raw_errors: Vec<Vec<_>> = (0..batch_size).map(|_| Vec::new()).collect();
+
+    // The names of the records before they are reset and the codes of the errors of each mate
+    // and pair, for fixing.
+    let mut r1_names = vec![Vec::new(); batch_size];
+    let mut r2_names = vec![Vec::new(); batch_size];
+    let mut r1_codes = vec![Vec::new(); batch_size];
+    let mut r2_codes = vec![Vec::new(); batch_size];
+    let mut pair_codes = vec![Vec::new(); batch_size];
+    let mut pair_indices = vec![0; batch_size];
+    let mut record_counter = 0;
+    let mut r1_detector = Detector::default();
+    let mut r2_detector = Detector::default();
+
+    let mut is_truncated = false;
+
+    loop {
+        let mut len = 0;
+        let mut truncated_file_errors = Vec::new();
+        let mut is_eof = false;
+
+        // A read error that stops validation after the records before it are reported.
+        let mut pending_error = None;
+
+        let mut is_done = false;
+        let batch_start = record_counter;
+
+        handler.clear_record_offsets();
+
+        while len < batch_size {
+            if sampling.is_done(record_counter) {
+                // Validation is only partial if another record, of either mate, remains.
+                let result = reader_1
+                    .is_eof()
+                    .with_context(|| format!("Could not read file: {}", r1_src))
+                    .and_then(|is_r1_eof| match reader_2.as_mut() {
+                        Some(reader) => reader
+                            .is_eof()
+                            .map(|is_r2_eof| is_r1_eof && is_r2_eof)
+                            .with_context(|| format!("Could not read file: {}", r2_src)),
+                        None => Ok(is_r1_eof),
+                    });
+
+                match result {
+                    Ok(true) => is_eof = true,
+                    Ok(false) => is_done = true,
+                    Err(e) => pending_error = Some(e),
+                }
+
+                break;
+            }
+
+            let (r1_index, r2_index) = indices(record_counter);
+
+            handler.add_record_offset(r1_src, r1_index, reader_1.position());
+
+            let r1_result = read_record(
+                &mut reader_1,
+                &mut r1_records[len],
+                truncated_file_validator.as_ref(),
+                r1_src,
+            );
+
+            let r1_result = match r1_result {
+                Ok(result) => result,
+                Err(e) => {
+                    pending_error = Some(e);
+                    break;
+                }
+            };
+
+            let r2_position = match reader_2.as_ref() {
+                Some(reader) => reader.position(),
+                None => reader_1.position(),
+            };
+
+            handler.add_record_offset(r2_src, r2_index, r2_position);
+
+            let r2_result = match reader_2.as_mut() {
+                Some(reader) => read_record(
+                    reader,
+                    &mut r2_records[len],
+                    truncated_file_validator.as_ref(),
+                    r2_src,
+                ),
+                None => read_record(
+                    &mut reader_1,
+                    &mut r2_records[len],
+                    truncated_file_validator.as_ref(),
+                    r2_src,
+                ),
+            };
+
+            let r2_result = match r2_result {
+                Ok(result) => result,
+                Err(e) => {
+                    pending_error = Some(e);
+                    break;
+                }
+            };
+
+            let (r1_len, r2_len) = match (r1_result, r2_result) {
+                (Ok(r1_len), Ok(r2_len)) => (r1_len, r2_len),
+                (r1_result, r2_result) => {
+                    if let Err(e) = r1_result {
+                        truncated_file_errors.push((e, r1_src, r1_index));
+                    }
+
+                    if let Err(e) = r2_result {
+                        truncated_file_errors.push((e, r2_src, r2_index));
+                    }
+
+                    break;
+                }
+            };
+
+            if is_interleaved && r1_len > 0 && r2_len == 0 {
+                pending_error = Some(unexpected_eof(format!(
+                    "{} has an unpaired last record",
+                    r1_src
+                )));
+                break;
+            } else if r1_len == 0 && r2_len > 0 {
+                pending_error = Some(unexpected_eof(format!(
+                    "{} unexpectedly ended before {}",
+                    r1_src, r2_src
+                )));
+                break;
+            } else if r2_len == 0 && r1_len > 0 {
+                pending_error = Some(unexpected_eof(format!(
+                    "{} unexpectedly ended before {}",
+                    r2_src, r1_src
+                )));
+                break;
+            } else if r1_len == 0 && r2_len == 0 {
+                is_eof = true;
+                break;
+            }
+
+            // Pairs that are not sampled are read into the next slot but not validated.
+            if sampling.is_sampled(record_counter) {
+                pair_indices[len] = record_counter;
+                len += 1;
+            }
+
+            record_counter += 1;
+        }
+
+        if let Some(index_reads) = index_reads.as_mut() {
+            let result =
+                index_reads.read_batch(&pair_indices[..len], record_counter, r1_src, handler);
+
+            if let Err(e) = result {
+                pending_error.get_or_insert(e);
+            }
+        }
+
+        // An interleaved source counts both mates, since `r2_src` is `r1_src`.
+        let n = (record_counter - batch_start) as u64;
+        handler.summary.add_records(r1_src, n);
+        handler.summary.add_records(r2_src, n);
+
+        for i in 0..len {
+            let (b, d) = (&mut r1_records[i], &mut r2_records[i]);
+
+            // An interleaved source is one file, so both mates share the validators and
+            // detector of read 1.
+            r1_raw_record_validators.validate(b, &mut r1_raw_errors[i]);
+
+            if is_interleaved {
+                r1_raw_record_validators.validate(d, &mut r2_raw_errors[i]);
+            } else {
+                r2_raw_record_validators.validate(d, &mut r2_raw_errors[i]);
+            }
+
+            if fixer.is_some() {
+                r1_names[i].clone_from(b.name_mut());
+                r2_names[i].clone_from(d.name_mut());
+            }
+
+            b.reset();
+            d.reset();
+
+            r1_detector.add(b.quality_scores());
+            handler.add_stats(r1_src, b);
+            handler.add_stats(r2_src, d);
+
+            if is_interleaved {
+                r1_detector.add(d.quality_scores());
+            } else {
+                r2_detector.add(d.quality_scores());
+            }
+
+            if let Some(validator) = &mut duplicate_name_validator {
+                validator.insert(b);
+            }
+        }
+
+        let pairs: Vec<_> = r1_records[..len].iter().zip(&r2_records[..len]).collect();
+
+        let errors = validate_batch(&pairs, threads, |(b, d), errors: &mut PairErrors| {
+            for validator in &single_read_validators {
+                if let Err(e) = validator.validate(b) {
+                    errors.single.push((Mate::R1, e));
+                }
+
+                if let Err(e) = validator.validate(d) {
+                    errors.single.push((Mate::R2, e));
+                }
+            }
+
+            for validator in &paired_read_validators {
+                if let Err(e) = validator.validate(b, d) {
+                    errors.paired.push(e);
+                }
+            }
+        });
+
+        for (i, ((b, d), errors)) in pairs.iter().zip(errors).enumerate() {
+            let (r1_index, r2_index) = indices(pair_indices[i]);
+
+            let r1_codes = &mut r1_codes[i];
+            let r2_codes = &mut r2_codes[i];
+            let pair_codes = &mut pair_codes[i];
+
+            r1_codes.clear();
+            r2_codes.clear();
+            pair_codes.clear();
+
+            for e in r1_raw_errors[i].drain(..) {
+                r1_codes.push(e.code.clone());
+                handler.handle_validation_error(e, r1_src, r1_index);
+            }
+
+            for e in r2_raw_errors[i].drain(..) {
+                r2_codes.push(e.code.clone());
+                handler.handle_validation_error(e, r2_src, r2_index);
+            }
+
+            for (mate, e) in errors.single {
+                match mate {
+                    Mate::R1 => {
+                        r1_codes.push(e.code.clone());
+                        handler.handle_validation_error(e, r1_src, r1_index);
+                    }
+                    Mate::R2 => {
+                        r2_codes.push(e.code.clone());
+                        handler.handle_validation_error(e, r2_src, r2_index);
+                    }
+                }
+            }
+
+            for validator in &mut r1_single_read_validators_mut {
+                if let Err(e) = validator.validate(b) {
+                    r1_codes.push(e.code.clone());
+                    handler.handle_validation_error(e, r1_src, r1_index);
+                }
+            }
+
+            let r2_single_read_validators_mut = if is_interleaved {
+                &mut r1_single_read_validators_mut
+            } else {
+                &mut r2_single_read_validators_mut
+            };
+
+            for validator in r2_single_read_validators_mut {
+                if let Err(e) = validator.validate(d) {
+                    r2_codes.push(e.code.clone());
+                    handler.handle_validation_error(e, r2_src, r2_index);
+                }
+            }
+
+            for e in errors.paired {
+                pair_codes.push(e.code.clone());
+                handler.handle_validation_error(e, r1_src, r1_index);
+            }
+
+            if let Some(index_reads) = index_reads.as_mut() {
+                index_reads.validate(i, b, r1_index, handler);
+            }
+
+            if let Some(validator) = &mut duplicate_sequence_validator {
+                validator.insert(&[b, d]);
+            }
+        }
+
+        if let Some(fixer) = fixer.as_mut() {
+            for i in 0..len {
+                let (b, d) = (&mut r1_records[i], &mut r2_records[i]);
+
+                b.name_mut().clone_from(&r1_names[i]);
+                d.name_mut().clone_from(&r2_names[i]);
+
+                fixer.write_pair((b, &r1_codes[i]), (d, &r2_codes[i]), &pair_codes[i])?;
+            }
+        }
+
+        let is_index_truncated = index_reads
+            .as_mut()
+            .map(|index_reads| index_reads.handle_truncated_file_errors(handler))
+            .unwrap_or(false);
+
+        if let Some((e, _, _)) = truncated_file_errors.first() {
+            // The pair is dropped once, even if both mates are truncated.
+            if let Some(fixer) = fixer.as_mut() {
+                fixer.add_dropped(&e.code);
+            }
+
+            for (e, src, index) in truncated_file_errors {
+                handler.handle_validation_error(e, src, index);
+            }
+
+            is_truncated = true;
+            break;
+        }
+
+        if is_index_truncated {
+            is_truncated = true;
+            break;
+        }
+
+        if let Some(e) = pending_error {
+            return Err(e);
+        }
+
+        if is_done {
+            handler.summary.set_stopped();
+            break;
+        }
+
+        if is_eof {
+            if let Some(index_reads) = index_reads.as_mut() {
+                index_reads.check_eof(r1_src)?;
+            }
+
+            break;
+        }
+    }
+
+    if let Some(index_reads) = index_reads {
+        index_reads.finish(handler);
+    }
+
+    handler.summary.set_encoding(r1_src, r1_detector.encoding());
+    finish_validators(&mut r1_single_read_validators_mut, handler, r1_src);
+
+    if !is_interleaved {
+        handler.summary.set_encoding(r2_src, r2_detector.encoding());
+        finish_validators(&mut r2_single_read_validators_mut, handler, r2_src);
+    }
+
+    if let Some(validator) = &mut duplicate_sequence_validator {
+        validator
+            .finish()
+            .unwrap_or_else(|e| handler.handle_file_validation_error(e, r1_src));
+    }
+
+    drop(stage);
+
+    // Duplicates among the possible duplicates that were not kept are not reported, so
+    // validation is partial.
+    if let Some(validator) = duplicate_name_validator.as_ref().filter(|v| v.is_full()) {
+        warn!(
+            "[{}] {}: possible duplicates exceed the memory limit (--max-memory), so some duplicates may not be reported",
+            validator.code(),
+            validator.name()
+        );
+
+        handler
+            .summary
+            .add_incomplete_validator(validator.code(), validator.name());
+    }
+
+    info!("read {} * 2 records", record_counter);
+    info!("starting validation (pass 2)");
+
+    // A truncated source was already reported, so it is not read again.
+    let mut duplicate_name_validator = match duplicate_name_validator {
+        Some(validator) if !is_truncated => validator,
+        _ => return Ok(()),
+    };
+
+    let _stage = metrics.stage("validation (pass 2)");
+
+    let mut reader =
+        crate::fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    reader.set_metrics(metrics.clone());
+
+    let pair_count = record_counter;
+
+    let mut record = Record::default();
+    let mut record_counter = 0;
+
+    loop {
+        let pair_index = if is_interleaved {
+            record_counter / 2
+        } else {
+            record_counter
+        };
+
+        // Only the pairs read in pass 1 are checked.
+        if pair_index >= pair_count {
+            break;
+        }
+
+        let position = reader.position();
+
+        let bytes_read = reader
+            .read_record(&mut record)
+            .with_context(|| format!("Could not read record from file: {}", r1_src))?;
+
+        if bytes_read == 0 {
+            break;
+        }
+
+        // Only read 1 names of sampled pairs are checked, i.e., every other record when
+        // interleaved.
+        if sampling.is_sampled(pair_index) && (!is_interleaved || record_counter % 2 == 0) {
+            handler.clear_record_offsets();
+            handler.add_record_offset(r1_src, record_counter, position);
+
+            record.reset();
+
+            duplicate_name_validator
+                .validate(&record)
+                .unwrap_or_else(|e| handler.handle_validation_error(e, r1_src, record_counter));
+        }
+
+        record_counter += 1;
+    }
+
+    duplicate_name_validator
+        .finish()
+        .unwrap_or_else(|e| handler.handle_file_validation_error(e, r1_src));
+
+    info!("read {} records", record_counter);
+
+    Ok(())
+}
+
+// A single end, paired end, or interleaved paired end input.
+#[derive(Debug, Eq, PartialEq)]
+enum Input {
+    Single(String),
+    Pair(String, String),
+    Interleaved(String),
+}
+
+impl Input {
+    fn sources(&self) -> Vec<String> {
+        match self {
+            Self::Single(src) | Self::Interleaved(src) => vec![src.clone()],
+            Self::Pair(r1_src, r2_src) => vec![r1_src.clone(), r2_src.clone()],
+        }
+    }
+}
+
+// Returns the codes of the validators or rules given by code or name for an argument.
+fn parse_validator_codes(
+    matches: &ArgMatches,
+    name: &str,
+    rules: &[RuleValidator],
+) -> anyhow::Result<Vec<String>> {
+    let mut codes = Vec::new();
+
+    for s in matches.values_of(name).unwrap_or_default() {
+        let code = validators::find_description(s)
+            .map(|d| d.code)
+            .or_else(|| {
+                rules
+                    .iter()
+                    .find(|r| r.code() == s || r.name() == s)
+                    .map(|r| r.code())
+            })
+            .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid validator = {}", s))?;
+
+        codes.push(code.to_string());
+    }
+
+    Ok(codes)
+}
+
+// Reads the user-defined validators from the rules file, if any.
+fn read_rules(matches: &ArgMatches) -> anyhow::Result<Vec<RuleValidator>> {
+    let src = match matches.value_of("rules") {
+        Some(src) => src,
+        None => return Ok(Vec::new()),
+    };
+
+    let s = fs::read_to_string(src).with_context(|| format!("Could not open file: {}", src))?;
+    let rules =
+        validators::parse_rules(&s).with_context(|| format!("Could not parse rules: {}", src))?;
+
+    info!("loaded {} rule(s) from {}", rules.len(), src);
+
+    Ok(rules)
+}
+
+// Parses a pair of sources, e.g., `r1.fastq,r2.fastq`.
+fn parse_pair(s: &str) -> anyhow::Result<Input> {
+    match s.split_once(',') {
+        Some((r1_src, r2_src)) if !r1_src.is_empty() && !r2_src.is_empty() => {
+            Ok(Input::Pair(r1_src.into(), r2_src.into()))
+        }
+        _ => Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid pair = {}: expected r1,r2", s)),
+    }
+}
 
-use anyhow::Context;
-use clap::ArgMatches;
-use tracing::{error, info};
+fn is_fastq_file_name(name: &str) -> bool {
+    const EXTENSIONS: [&str; 4] = [".fastq", ".fq", ".fastq.gz", ".fq.gz"];
+    EXTENSIONS.iter().any(|ext| name.ends_with(ext))
+}
 
-use crate::{
-    fastq::{self, Record},
-    validators::{
-        self, single::DuplicateNameValidator, LintMode, SingleReadValidatorMut, ValidationLevel,
-    },
-};
+// Groups sources into inputs by file name.
+//
+// A source with `_R1` in its file name is paired with the source that has the last `_R1`
+// replaced with `_R2`, e.g., `sample_R1_001.fastq.gz` and `sample_R2_001.fastq.gz`. All other
+// sources are single end inputs. Inputs are ordered by their first source.
+fn group_sources(mut srcs: Vec<String>) -> Vec<Input> {
+    fn r2_src(r1_src: &str) -> Option<String> {
+        let start = r1_src.rfind('/').map(|i| i + 1).unwrap_or(0);
+        let i = start + r1_src[start..].rfind("_R1")?;
+        Some(format!("{}_R2{}", &r1_src[..i], &r1_src[i + 3..]))
+    }
 
-fn build_error_message(error: validators::Error, pathname: &str, record_counter: usize) -> String {
-    let mut message = String::new();
+    srcs.sort();
 
-    let line_offset = error.line_type as usize;
-    let line_no = record_counter * 4 + line_offset + 1;
-    message.push_str(&format!("{}:{}:", pathname, line_no));
+    let mates: Vec<_> = srcs
+        .iter()
+        .filter_map(|src| r2_src(src).filter(|r2_src| srcs.contains(r2_src)))
+        .collect();
+
+    srcs.into_iter()
+        .filter(|src| !mates.contains(src))
+        .map(
+            |src| match r2_src(&src).filter(|r2_src| mates.contains(r2_src)) {
+                Some(r2_src) => Input::Pair(src, r2_src),
+                None => Input::Single(src),
+            },
+        )
+        .collect()
+}
+
+// Finds the FASTQ files in a directory and groups them into inputs.
+fn read_dir_inputs(dir: &str) -> anyhow::Result<Vec<Input>> {
+    let mut srcs = Vec::new();
 
-    if let Some(col_no) = error.col_no {
-        message.push_str(&format!("{}:", col_no));
+    let entries =
+        fs::read_dir(dir).with_context(|| format!("Could not read directory: {}", dir))?;
+
+    for result in entries {
+        let entry = result.with_context(|| format!("Could not read directory: {}", dir))?;
+        let path = entry.path();
+
+        let is_fastq_file = path.is_file()
+            && path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(is_fastq_file_name)
+                .unwrap_or(false);
+
+        if is_fastq_file {
+            srcs.push(path.to_string_lossy().into_owned());
+        }
     }
 
-    message.push_str(&format!(
-        " [{}] {}: {}",
-        error.code, error.name, error.message
-    ));
+    if srcs.is_empty() {
+        return Err(io::Error::from(io::ErrorKind::NotFound))
+            .with_context(|| format!("No FASTQ files found in directory: {}", dir));
+    }
 
-    message
+    Ok(group_sources(srcs))
 }
 
-fn exit_with_validation_error(
-    error: validators::Error,
-    pathname: &str,
-    record_counter: usize,
-) -> ! {
-    let message = build_error_message(error, pathname, record_counter);
-    eprintln!("{}", message);
-    process::exit(1);
-}
+// Opens a source, which is stdin if `-`. When following, a file is read as it is written.
+// Otherwise, bytes read from a file are counted in the metrics to report progress.
+fn open_source(
+    src: &str,
+    follow: bool,
+    follow_timeout: Option<Duration>,
+    metrics: &Metrics,
+) -> anyhow::Result<fastq::Reader<Box<dyn BufRead>>> {
+    let result = if src == STDIN {
+        fastq::open_stdin()
+    } else if follow {
+        fastq::open_follow(src, follow_timeout)
+    } else {
+        fastq::open_with_metrics(src, metrics.clone())
+    };
 
-fn log_validation_error(error: validators::Error, pathname: &str, record_counter: usize) {
-    let message = build_error_message(error, pathname, record_counter);
-    error!("{}", message);
+    result.with_context(|| format!("Could not open file: {}", src))
 }
 
-fn handle_validation_error(
-    lint_mode: LintMode,
-    error: validators::Error,
-    pathname: &str,
-    record_counter: usize,
-) {
-    match lint_mode {
-        LintMode::Panic => exit_with_validation_error(error, pathname, record_counter),
-        LintMode::Log => log_validation_error(error, pathname, record_counter),
+// Writes the code, level, kind, name, and description of all validators and rules to stdout.
+fn list_validators(output_format: output::Format, rules: &[RuleValidator]) {
+    let descriptions: Vec<_> = validators::descriptions()
+        .iter()
+        .copied()
+        .chain(rules.iter().map(RuleValidator::description))
+        .collect();
+
+    match output_format {
+        output::Format::Text => {
+            let name_width = descriptions.iter().map(|d| d.name.len()).max().unwrap_or(0);
+
+            println!(
+                "{:<4}  {:<6}  {:<6}  {:<name_width$}  DESCRIPTION",
+                "CODE",
+                "LEVEL",
+                "KIND",
+                "NAME",
+                name_width = name_width
+            );
+
+            for description in &descriptions {
+                println!(
+                    "{:<4}  {:<6}  {:<6}  {:<name_width$}  {}",
+                    description.code,
+                    description.level,
+                    description.kind,
+                    description.name,
+                    description.summary,
+                    name_width = name_width
+                );
+            }
+        }
+        output::Format::Json => {
+            for description in &descriptions {
+                println!("{}", output::build_json_description(description));
+            }
+        }
+        output::Format::Sarif => {
+            let rules: Vec<_> = descriptions
+                .iter()
+                .map(|d| (d.code.into(), d.name.into()))
+                .collect();
+
+            println!("{}", output::build_sarif(&rules, &[]));
+        }
+        output::Format::Delimited(delimiter) => {
+            println!(
+                "{}",
+                output::build_row(delimiter, &["code", "level", "kind", "name", "description"])
+            );
+
+            for description in &descriptions {
+                let level = description.level.to_string();
+                let kind = description.kind.to_string();
+
+                println!(
+                    "{}",
+                    output::build_row(
+                        delimiter,
+                        &[
+                            description.code,
+                            &level,
+                            &kind,
+                            description.name,
+                            description.summary,
+                        ],
+                    )
+                );
+            }
+        }
     }
 }
 
-fn validate_single(
-    mut reader: fastq::Reader<impl BufRead>,
-    single_read_validation_level: ValidationLevel,
-    disabled_validators: &[String],
-    lint_mode: LintMode,
-    r1_src: &str,
-) -> anyhow::Result<()> {
-    let (single_read_validators, _) =
-        validators::filter_validators(single_read_validation_level, None, disabled_validators);
+pub fn lint(matches: &ArgMatches) -> anyhow::Result<()> {
+    let rules = read_rules(matches)?;
 
-    info!("starting validation");
+    if matches.is_present("list-validators") {
+        let output_format = matches
+            .value_of_t("output-format")
+            .unwrap_or_else(|e| e.exit());
 
-    let mut record = Record::default();
-    let mut record_counter = 0;
+        list_validators(output_format, &rules);
 
-    loop {
-        let bytes_read = reader
-            .read_record(&mut record)
-            .with_context(|| format!("Could not read record from file: {}", r1_src))?;
+        return Ok(());
+    }
 
-        if bytes_read == 0 {
-            break;
-        }
+    let is_fixing = matches.is_present("fix");
 
-        record.reset();
+    let lint_mode = matches.value_of_t("lint-mode").unwrap_or_else(|e| e.exit());
 
-        for validator in &single_read_validators {
-            validator
-                .validate(&record)
-                .unwrap_or_else(|e| handle_validation_error(lint_mode, e, r1_src, record_counter));
+    // Fixing continues past errors to write all records.
+    let lint_mode = if is_fixing {
+        if matches.occurrences_of("lint-mode") > 0 && lint_mode != LintMode::Log {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .context("--fix requires --lint-mode log");
         }
 
-        record_counter += 1;
-    }
+        LintMode::Log
+    } else {
+        lint_mode
+    };
 
-    info!("read {} records", record_counter);
+    let mut single_read_validation_level = matches
+        .value_of_t("single-read-validation-level")
+        .unwrap_or_else(|e| e.exit());
 
-    Ok(())
-}
+    let mut paired_read_validation_level = matches
+        .value_of_t("paired-read-validation-level")
+        .unwrap_or_else(|e| e.exit());
 
-#[allow(clippy::too_many_arguments)]
-fn validate_pair(
-    mut reader_1: fastq::Reader<impl BufRead>,
-    mut reader_2: fastq::Reader<impl BufRead>,
-    single_read_validation_level: ValidationLevel,
-    paired_read_validation_level: ValidationLevel,
-    disabled_validators: &[String],
-    lint_mode: LintMode,
-    r1_src: &str,
-    r2_src: &str,
-) -> anyhow::Result<()> {
-    let (single_read_validators, paired_read_validators) = validators::filter_validators(
-        single_read_validation_level,
-        Some(paired_read_validation_level),
-        disabled_validators,
-    );
+    let mut disabled_validators = parse_validator_codes(matches, "disable-validator", &rules)?;
+    let enabled_validators = parse_validator_codes(matches, "enable-validator", &rules)?;
+
+    // Only the enabled validators run, regardless of level.
+    if !enabled_validators.is_empty() {
+        single_read_validation_level = ValidationLevel::High;
+        paired_read_validation_level = ValidationLevel::High;
+
+        disabled_validators = validators::descriptions()
+            .iter()
+            .map(|d| d.code)
+            .chain(rules.iter().map(|r| r.code()))
+            .map(String::from)
+            .filter(|code| !enabled_validators.contains(code))
+            .collect();
+    }
 
-    let mut duplicate_name_validator = DuplicateNameValidator::new();
+    let warn_validators = parse_validator_codes(matches, "warn-validator", &rules)?;
 
-    let code = duplicate_name_validator.code();
-    let name = duplicate_name_validator.name();
-    let use_special_validator = !disabled_validators.contains(&code.to_string());
+    let mut validator_config = validators::Config::default();
 
-    let validators = if use_special_validator {
-        format!(r#""[{}] {}""#, code, name)
-    } else {
-        String::new()
+    for s in matches.values_of("validator-option").unwrap_or_default() {
+        validator_config
+            .set(s)
+            .with_context(|| format!("invalid validator option = {}", s))?;
+    }
+
+    let mut optional_validators = OptionalValidators {
+        rules,
+        name_consistency: matches
+            .is_present("uniform-run")
+            .then(NameConsistencyValidator::default),
+        resync: matches.is_present("resync"),
+        ..Default::default()
     };
 
-    info!("enabled special validators: [{}]", validators);
+    if matches.is_present("expected-read-length") {
+        let len = matches
+            .value_of_t("expected-read-length")
+            .unwrap_or_else(|e| e.exit());
 
-    info!("starting validation (pass 1)");
+        optional_validators.read_length = Some(ReadLengthValidator::new(Some(len)));
+    } else if matches.is_present("uniform-read-length") {
+        optional_validators.read_length = Some(ReadLengthValidator::new(None));
+    }
 
-    let mut b = Record::default();
-    let mut d = Record::default();
-    let mut record_counter = 0;
+    // Options with their own flags override the equivalent validator options.
 
-    loop {
-        let r1_len = reader_1
-            .read_record(&mut b)
-            .with_context(|| format!("Could not read record from file: {}", r1_src))?;
+    if matches.is_present("max-n-fraction") {
+        let max_n_fraction: f64 = matches
+            .value_of_t("max-n-fraction")
+            .unwrap_or_else(|e| e.exit());
 
-        let r2_len = reader_2
-            .read_record(&mut d)
-            .with_context(|| format!("Could not read record from file: {}", r2_src))?;
-
-        if r1_len == 0 && r2_len > 0 {
-            return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
-                .with_context(|| format!("{} unexpectedly ended before {}", r1_src, r2_src));
-        } else if r2_len == 0 && r1_len > 0 {
-            return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
-                .with_context(|| format!("{} unexpectedly ended before {}", r2_src, r1_src));
-        } else if r1_len == 0 && r2_len == 0 {
-            break;
+        if !(0.0..=1.0).contains(&max_n_fraction) {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("invalid max N fraction = {}", max_n_fraction));
         }
 
-        b.reset();
-        d.reset();
+        validator_config.max_n_fraction = Some(max_n_fraction);
+    }
 
-        if use_special_validator {
-            duplicate_name_validator.insert(&b);
-        }
+    if matches.is_present("min-sequence-length") {
+        let n = matches
+            .value_of_t("min-sequence-length")
+            .unwrap_or_else(|e| e.exit());
 
-        for validator in &single_read_validators {
-            validator
-                .validate(&b)
-                .unwrap_or_else(|e| handle_validation_error(lint_mode, e, r1_src, record_counter));
+        validator_config.min_sequence_length = Some(n);
+    }
 
-            validator
-                .validate(&d)
-                .unwrap_or_else(|e| handle_validation_error(lint_mode, e, r2_src, record_counter));
-        }
+    if matches.is_present("max-sequence-length") {
+        let n = matches
+            .value_of_t("max-sequence-length")
+            .unwrap_or_else(|e| e.exit());
+
+        validator_config.max_sequence_length = Some(n);
+    }
+
+    if matches.is_present("name-format") {
+        let format = matches
+            .value_of_t("name-format")
+            .unwrap_or_else(|e| e.exit());
+
+        optional_validators.name_format = Some(format);
+    }
+
+    if matches.is_present("expected-quality-encoding") {
+        let encoding = matches
+            .value_of_t("expected-quality-encoding")
+            .unwrap_or_else(|e| e.exit());
+
+        optional_validators.quality_encoding = Some(QualityEncodingValidator::new(encoding));
+    }
 
-        for validator in &paired_read_validators {
-            validator
-                .validate(&b, &d)
-                .unwrap_or_else(|e| handle_validation_error(lint_mode, e, r1_src, record_counter));
+    if matches.is_present("max-adapter-rate") {
+        let max_adapter_rate: f64 = matches
+            .value_of_t("max-adapter-rate")
+            .unwrap_or_else(|e| e.exit());
+
+        if !(0.0..=1.0).contains(&max_adapter_rate) {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("invalid max adapter rate = {}", max_adapter_rate));
         }
 
-        record_counter += 1;
+        validator_config.max_adapter_rate = Some(max_adapter_rate);
     }
 
-    info!("read {} * 2 records", record_counter);
-    info!("starting validation (pass 2)");
+    if matches.is_present("max-duplicate-rate") {
+        let max_duplicate_rate: f64 = matches
+            .value_of_t("max-duplicate-rate")
+            .unwrap_or_else(|e| e.exit());
 
-    if !use_special_validator {
-        return Ok(());
+        if !(0.0..=1.0).contains(&max_duplicate_rate) {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("invalid max duplicate rate = {}", max_duplicate_rate));
+        }
+
+        validator_config.max_duplicate_rate = Some(max_duplicate_rate);
     }
 
-    let mut reader =
-        crate::fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    if matches.is_present("platform") {
+        let platform = matches.value_of_t("platform").unwrap_or_else(|e| e.exit());
 
-    let mut record = Record::default();
-    let mut record_counter = 0;
+        validator_config.platform = Some(platform);
+    }
 
-    loop {
-        let bytes_read = reader
-            .read_record(&mut record)
-            .with_context(|| format!("Could not read record from file: {}", r1_src))?;
+    optional_validators.max_n_fraction = validator_config.max_n_fraction;
 
-        if bytes_read == 0 {
-            break;
+    let min_len = validator_config.min_sequence_length;
+    let max_len = validator_config.max_sequence_length;
+
+    if let (Some(min_len), Some(max_len)) = (min_len, max_len) {
+        if min_len > max_len {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput)).with_context(|| {
+                format!(
+                    "invalid sequence length bounds: min ({}) > max ({})",
+                    min_len, max_len
+                )
+            });
         }
+    }
 
-        record.reset();
+    if min_len.is_some() || max_len.is_some() {
+        optional_validators.sequence_length = Some(SequenceLengthValidator::new(min_len, max_len));
+    }
+
+    validator_config.adapters.extend(
+        matches
+            .values_of("adapter")
+            .unwrap_or_default()
+            .map(|s| Adapter::new(s, s.to_ascii_uppercase())),
+    );
 
-        duplicate_name_validator
-            .validate(&record)
-            .unwrap_or_else(|e| handle_validation_error(lint_mode, e, r1_src, record_counter));
+    optional_validators.max_duplicate_rate = validator_config.max_duplicate_rate;
 
-        record_counter += 1;
+    if matches.is_present("max-memory") {
+        let max_memory: usize = matches
+            .value_of_t("max-memory")
+            .unwrap_or_else(|e| e.exit());
+
+        if max_memory == 0 {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("invalid max memory = {}", max_memory));
+        }
+
+        let max_memory = max_memory
+            .checked_mul(1024 * 1024)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid max memory = {}", max_memory))?;
+
+        optional_validators.max_duplicate_name_memory = Some(max_memory);
     }
 
-    info!("read {} records", record_counter);
+    let plus_line_style = matches
+        .value_of_t("plus-line-style")
+        .unwrap_or_else(|e| e.exit());
 
-    Ok(())
-}
+    let max_errors = if matches.is_present("max-errors") {
+        let n = matches
+            .value_of_t("max-errors")
+            .unwrap_or_else(|e| e.exit());
 
-pub fn lint(matches: &ArgMatches) -> anyhow::Result<()> {
-    let lint_mode = matches.value_of_t("lint-mode").unwrap_or_else(|e| e.exit());
+        Some(n)
+    } else {
+        None
+    };
 
-    let r1_src = matches.value_of("r1-src").unwrap();
-    let r2_src = matches.value_of("r2-src");
+    let sample_rate = if matches.is_present("sample-rate") {
+        let n = matches
+            .value_of_t("sample-rate")
+            .unwrap_or_else(|e| e.exit());
 
-    let single_read_validation_level = matches
-        .value_of_t("single-read-validation-level")
+        if n == 0 {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("invalid sample rate = {}", n));
+        }
+
+        n
+    } else {
+        1
+    };
+
+    let max_records = if matches.is_present("max-records") {
+        let n = matches
+            .value_of_t("max-records")
+            .unwrap_or_else(|e| e.exit());
+
+        Some(n)
+    } else {
+        None
+    };
+
+    let sampling = Sampling::new(sample_rate, max_records);
+
+    let output_format = matches
+        .value_of_t("output-format")
         .unwrap_or_else(|e| e.exit());
 
-    let paired_read_validation_level = matches
-        .value_of_t("paired-read-validation-level")
+    let report_format = matches
+        .value_of_t("report-format")
         .unwrap_or_else(|e| e.exit());
 
-    let disabled_validators: Vec<String> = matches
-        .values_of("disable-validator")
-        .unwrap_or_default()
+    let report_max_failures = matches
+        .value_of_t("report-max-failures")
+        .unwrap_or_else(|e| e.exit());
+
+    let threads: usize = matches.value_of_t("threads").unwrap_or_else(|e| e.exit());
+
+    if threads == 0 {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid threads = {}", threads));
+    }
+
+    let follow = matches.is_present("follow");
+
+    let progress_style = if matches.is_present("progress-bar") && io::stderr().is_terminal() {
+        Some(progress::Style::Bar)
+    } else {
+        let secs = matches
+            .value_of_t("progress-interval")
+            .unwrap_or_else(|e| e.exit());
+
+        match secs {
+            0 => None,
+            n => Some(progress::Style::Log(Duration::from_secs(n))),
+        }
+    };
+
+    let follow_timeout = if matches.is_present("follow-timeout") {
+        let secs = matches
+            .value_of_t("follow-timeout")
+            .unwrap_or_else(|e| e.exit());
+
+        Some(Duration::from_secs(secs))
+    } else {
+        None
+    };
+
+    let inputs = if matches.is_present("pair") || matches.is_present("dir") {
+        let mut inputs = Vec::new();
+
+        for s in matches.values_of("pair").unwrap_or_default() {
+            inputs.push(parse_pair(s)?);
+        }
+
+        if let Some(dir) = matches.value_of("dir") {
+            inputs.extend(read_dir_inputs(dir)?);
+        }
+
+        inputs
+    } else {
+        let r1_src = matches.value_of("r1-src").unwrap().into();
+
+        let input = match matches.value_of("r2-src") {
+            Some(r2_src) => Input::Pair(r1_src, r2_src.into()),
+            None if matches.is_present("interleaved") => Input::Interleaved(r1_src),
+            None => Input::Single(r1_src),
+        };
+
+        vec![input]
+    };
+
+    // Index reads are only given with a single pair.
+    let index_srcs: Vec<String> = ["i1", "i2"]
+        .iter()
+        .filter_map(|name| matches.value_of(name))
         .map(String::from)
         .collect();
 
+    let expected_index_len = if matches.is_present("expected-index-length") {
+        let n = matches
+            .value_of_t("expected-index-length")
+            .unwrap_or_else(|e| e.exit());
+
+        Some(n)
+    } else {
+        None
+    };
+
+    let sources: Vec<String> = inputs
+        .iter()
+        .flat_map(|input| input.sources())
+        .chain(index_srcs.iter().cloned())
+        .collect();
+
+    let is_stdin_in_pair = index_srcs.iter().any(|src| src == STDIN)
+        || inputs.iter().any(|input| match input {
+            Input::Pair(r1_src, r2_src) => r1_src == STDIN || r2_src == STDIN,
+            _ => false,
+        });
+
+    if is_stdin_in_pair {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput)).with_context(|| {
+            "stdin (`-`) can only be used as a source for single end or interleaved reads"
+        });
+    }
+
+    // Duplicate names (S007) of paired reads are only found in a second pass, after the pairs are
+    // written, so they cannot be fixed.
+    let has_duplicate_name_pass = single_read_validation_level >= ValidationLevel::High
+        && !disabled_validators.iter().any(|c| c == "S007")
+        && inputs.iter().any(|input| match input {
+            Input::Pair(r1_src, _) | Input::Interleaved(r1_src) => r1_src != STDIN,
+            Input::Single(_) => false,
+        });
+
+    if is_fixing && has_duplicate_name_pass {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput)).context(
+            "--fix cannot drop duplicate names (S007) of paired reads; disable the validator with `--disable-validator S007`",
+        );
+    }
+
     info!("fq-lint start");
 
-    let r1 =
-        crate::fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    let metrics = Metrics::new();
 
-    if let Some(r2_src) = r2_src {
-        info!("validating paired end reads");
+    let report = matches.value_of("report").map(|dst| ReportDestination {
+        report: Report::new(sources.join(" "), report_max_failures),
+        format: report_format,
+        dst: dst.into(),
+    });
+
+    let mut fixer = if is_fixing {
+        Some(create_fixer(matches, &inputs, plus_line_style, &metrics)?)
+    } else {
+        None
+    };
 
-        let r2 = crate::fastq::open(r2_src)
-            .with_context(|| format!("Could not open file: {}", r2_src))?;
+    let output = match matches.value_of("output") {
+        Some(dst) => {
+            let file =
+                File::create(dst).with_context(|| format!("Could not create file: {}", dst))?;
 
-        validate_pair(
-            r1,
-            r2,
-            single_read_validation_level,
-            paired_read_validation_level,
-            &disabled_validators,
-            lint_mode,
-            r1_src,
-            r2_src,
-        )?;
+            Some((BufWriter::new(file), dst.into()))
+        }
+        None => None,
+    };
+
+    let mut handler = ErrorHandler {
+        lint_mode,
+        max_errors,
+        warn_validators,
+        output_format,
+        output,
+        summary: Summary::new(&sources),
+        metrics: metrics.clone(),
+        report,
+        summary_dst: matches.value_of("summary-path").map(String::from),
+        stats: matches.value_of("stats").map(|dst| StatsDestination {
+            stats: sources
+                .iter()
+                .map(|src| (src.clone(), stats::Summary::default()))
+                .collect(),
+            dst: dst.into(),
+        }),
+        validators: Vec::new(),
+        diagnostics: Vec::new(),
+        line_offset: 0,
+        record_offsets: Vec::new(),
+    };
+
+    handler.summary.set_sampling(sampling);
+    handler.start();
+
+    // The total size is unknown for stdin and files that are still being written.
+    let total_bytes = if follow || sources.iter().any(|src| src == STDIN) {
+        None
     } else {
-        info!("validating single end read");
+        let mut n = 0;
 
-        validate_single(
-            r1,
-            single_read_validation_level,
-            &disabled_validators,
-            lint_mode,
-            r1_src,
-        )?;
+        for src in &sources {
+            n += fs::metadata(src)
+                .with_context(|| format!("Could not open file: {}", src))?
+                .len();
+        }
+
+        Some(n)
+    };
+
+    let progress = progress_style.map(|style| Progress::start(metrics.clone(), total_bytes, style));
+
+    for input in &inputs {
+        match input {
+            Input::Pair(r1_src, r2_src) => {
+                if index_srcs.is_empty() {
+                    info!("validating paired end reads: {}, {}", r1_src, r2_src);
+                } else {
+                    info!(
+                        "validating paired end reads: {}, {} with index reads: {}",
+                        r1_src,
+                        r2_src,
+                        index_srcs.join(", ")
+                    );
+                }
+
+                let validator = IdenticalMatesValidator;
+
+                if validator.level() <= paired_read_validation_level
+                    && !disabled_validators.contains(&validator.code().to_string())
+                {
+                    check_identical_sources(&mut handler, r1_src, r2_src)?;
+                }
+
+                let r1 = open_source(r1_src, follow, follow_timeout, &metrics)?;
+                let r2 = open_source(r2_src, follow, follow_timeout, &metrics)?;
+
+                let mut index_reads = if index_srcs.is_empty() {
+                    None
+                } else {
+                    let mut readers = Vec::with_capacity(index_srcs.len());
+
+                    for src in &index_srcs {
+                        let reader = open_source(src, follow, follow_timeout, &metrics)?;
+                        handler.add_record_offsets(src)?;
+                        readers.push((src.clone(), reader));
+                    }
+
+                    Some(index::IndexReads::new(
+                        readers,
+                        single_read_validation_level,
+                        paired_read_validation_level,
+                        &disabled_validators,
+                        &validator_config,
+                        plus_line_style,
+                        expected_index_len,
+                        &mut handler,
+                    ))
+                };
+
+                validate_pair(
+                    r1,
+                    Some(r2),
+                    single_read_validation_level,
+                    paired_read_validation_level,
+                    &disabled_validators,
+                    &validator_config,
+                    &optional_validators,
+                    plus_line_style,
+                    threads,
+                    sampling,
+                    fixer.as_mut(),
+                    index_reads.as_mut(),
+                    &mut handler,
+                    r1_src,
+                    r2_src,
+                )?;
+            }
+            Input::Interleaved(r1_src) => {
+                info!("validating interleaved paired end reads: {}", r1_src);
+
+                let r1 = open_source(r1_src, follow, follow_timeout, &metrics)?;
+
+                validate_pair(
+                    r1,
+                    None::<fastq::Reader<Box<dyn BufRead>>>,
+                    single_read_validation_level,
+                    paired_read_validation_level,
+                    &disabled_validators,
+                    &validator_config,
+                    &optional_validators,
+                    plus_line_style,
+                    threads,
+                    sampling,
+                    fixer.as_mut(),
+                    None,
+                    &mut handler,
+                    r1_src,
+                    r1_src,
+                )?;
+            }
+            Input::Single(r1_src) => {
+                info!("validating single end read: {}", r1_src);
+
+                let r1 = open_source(r1_src, follow, follow_timeout, &metrics)?;
+
+                validate_single(
+                    r1,
+                    single_read_validation_level,
+                    &disabled_validators,
+                    &validator_config,
+                    &optional_validators,
+                    plus_line_style,
+                    threads,
+                    sampling,
+                    fixer.as_mut(),
+                    &mut handler,
+                    r1_src,
+                )?;
+            }
+        }
+    }
+
+    drop(progress);
+
+    if let Some(fixer) = fixer {
+        fixer.log();
     }
 
+    handler.finish()?;
+
+    metrics.log();
+
     info!("fq-lint end");
 
-    Ok(())
+    match handler.summary.error_count() {
+        0 => Ok(()),
+        n => Err(anyhow!("Validation failed: {} errors", n)),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::validators::{self, LineType};
+    use std::env;
 
     use super::*;
 
+    #[test]
+    fn test_is_identical_source() -> io::Result<()> {
+        let dir = env::temp_dir();
+
+        let r1_src = dir.join("fq-lint-test_is_identical_source-r1.fastq");
+        fs::write(&r1_src, "@fqlib/1\nACGT\n+\nFQLB\n")?;
+
+        let r2_src = dir.join("fq-lint-test_is_identical_source-r2.fastq");
+        fs::write(&r2_src, "@fqlib/2\nTGCA\n+\nBLQF\n")?;
+
+        let r3_src = dir.join("fq-lint-test_is_identical_source-r3.fastq");
+        fs::write(&r3_src, "@fqlib/1\nACGT\n+\nFQLB\n")?;
+
+        assert!(is_identical_source(&r1_src, &r1_src)?);
+        assert!(!is_identical_source(&r1_src, &r2_src)?);
+        assert!(is_identical_source(&r1_src, &r3_src)?);
+
+        // Sources that only differ after a large common prefix.
+        let mut data = b"@fqlib/1\nACGT\n+\nFQLB\n".repeat(1 << 16);
+
+        let r4_src = dir.join("fq-lint-test_is_identical_source-r4.fastq");
+        fs::write(&r4_src, &data)?;
+
+        let last_base = data.len() - 2;
+        data[last_base] = b'C';
+
+        let r5_src = dir.join("fq-lint-test_is_identical_source-r5.fastq");
+        fs::write(&r5_src, &data)?;
+
+        assert!(!is_identical_source(&r4_src, &r5_src)?);
+
+        fs::remove_file(r1_src)?;
+        fs::remove_file(r2_src)?;
+        fs::remove_file(r3_src)?;
+        fs::remove_file(r4_src)?;
+        fs::remove_file(r5_src)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_pair() {
+        assert_eq!(
+            parse_pair("r1.fastq,r2.fastq").unwrap(),
+            Input::Pair(String::from("r1.fastq"), String::from("r2.fastq"))
+        );
+
+        assert!(parse_pair("r1.fastq").is_err());
+        assert!(parse_pair("r1.fastq,").is_err());
+        assert!(parse_pair(",r2.fastq").is_err());
+    }
+
+    #[test]
+    fn test_group_sources() {
+        let srcs = vec![
+            String::from("run/b_R2_001.fastq.gz"),
+            String::from("run/c.fastq"),
+            String::from("run/a_R1_001.fastq.gz"),
+            String::from("run/b_R1_001.fastq.gz"),
+            String::from("run_R1/d_R1.fq"),
+        ];
+
+        assert_eq!(
+            group_sources(srcs),
+            [
+                Input::Single(String::from("run/a_R1_001.fastq.gz")),
+                Input::Pair(
+                    String::from("run/b_R1_001.fastq.gz"),
+                    String::from("run/b_R2_001.fastq.gz")
+                ),
+                Input::Single(String::from("run/c.fastq")),
+                Input::Single(String::from("run_R1/d_R1.fq")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_fastq_file_name() {
+        assert!(is_fastq_file_name("r1.fastq"));
+        assert!(is_fastq_file_name("r1.fq"));
+        assert!(is_fastq_file_name("r1.fastq.gz"));
+        assert!(is_fastq_file_name("r1.fq.gz"));
+        assert!(!is_fastq_file_name("r1.fastq.md5"));
+        assert!(!is_fastq_file_name("SampleSheet.csv"));
+    }
+
+    #[test]
+    fn test_validate_batch() {
+        let items: Vec<usize> = (0..100).collect();
+
+        let expected: Vec<Vec<usize>> = items.iter().map(|&n| vec![n, n * 2]).collect();
+
+        for threads in [1, 3, 8, 200] {
+            let outputs = validate_batch(&items, threads, |&n, output: &mut Vec<usize>| {
+                output.push(n);
+                output.push(n * 2);
+            });
+
+            assert_eq!(outputs, expected);
+        }
+
+        let outputs = validate_batch(&[] as &[usize], 4, |_, _: &mut Vec<usize>| {});
+        assert!(outputs.is_empty());
+    }
+
     #[test]
     fn test_build_error_message() {
         let error = validators::Error::new(
@@ -293,11 +2559,27 @@ mod tests {
         );
 
         assert_eq!(
-            build_error_message(error, "in.fastq", 2),
+            Diagnostic::from_record_error(error, "in.fastq", 2).to_string(),
             "in.fastq:10:76: [S002] AlphabetValidator: Invalid character: m",
         );
     }
 
+    #[test]
+    fn test_build_file_error_message() {
+        let error = validators::Error::new(
+            "S008",
+            "ReadLengthValidator",
+            "Read lengths are not uniform",
+            LineType::Sequence,
+            None,
+        );
+
+        assert_eq!(
+            Diagnostic::from_file_error(error, "in.fastq").to_string(),
+            "in.fastq: [S008] ReadLengthValidator: Read lengths are not uniform",
+        );
+    }
+
     #[test]
     fn test_build_error_message_with_no_col_no() {
         let error = validators::Error::new(
@@ -309,7 +2591,7 @@ mod tests {
         );
 
         assert_eq!(
-            build_error_message(error, "in.fastq", 2),
+            Diagnostic::from_record_error(error, "in.fastq", 2).to_string(),
             "in.fastq:10: [S002] AlphabetValidator: Invalid character: m",
         );
     }
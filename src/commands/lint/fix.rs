@@ -0,0 +1,321 @@
+use std::{collections::BTreeMap, io, str::FromStr};
+
+use anyhow::Context;
+use tracing::info;
+
+use crate::{
+    fastq::{self, Record},
+    fix::{self as record_fix, QualityLengthPolicy},
+    validators::PlusLineStyle,
+};
+
+/// What to do with a record that fails a validator.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Action {
+    /// Removes the record or, for paired reads, the pair.
+    Drop,
+    /// Repairs the defect in place.
+    Repair,
+    /// Writes the record unchanged.
+    Keep,
+}
+
+impl FromStr for Action {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "drop" => Ok(Self::Drop),
+            "repair" => Ok(Self::Repair),
+            "keep" => Ok(Self::Keep),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "invalid fix action: expected drop, repair, or keep, got '{}'",
+                    s
+                ),
+            )),
+        }
+    }
+}
+
+/// Parses a fix policy, i.e., a validator code and its action, e.g., `S005=drop`.
+pub fn parse_policy(s: &str) -> anyhow::Result<(String, Action)> {
+    match s.split_once('=') {
+        Some((code, action)) if !code.is_empty() => {
+            let action = action
+                .parse()
+                .with_context(|| format!("invalid fix policy = {}", s))?;
+
+            Ok((code.into(), action))
+        }
+        _ => Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid fix policy = {}: expected code=action", s)),
+    }
+}
+
+/// Counts of records changed by a fix action.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Counts {
+    pub repaired: u64,
+    pub dropped: u64,
+}
+
+/// Drops or repairs records that fail validation and writes the others.
+///
+/// Without a policy, a record that fails a validator is repaired if its defect can be repaired
+/// and dropped otherwise. Only records that are read in full are written, so a truncated last
+/// record (S015) is always dropped.
+pub struct Fixer {
+    record_fixer: record_fix::Fixer,
+    policies: Vec<(String, Action)>,
+    plus_line_style: PlusLineStyle,
+    counts: BTreeMap<String, Counts>,
+    writer_1: fastq::Writer<Box<dyn io::Write>>,
+    writer_2: Option<fastq::Writer<Box<dyn io::Write>>>,
+    // The number of records written.
+    records_written: u64,
+}
+
+impl Fixer {
+    /// Creates a fixer that writes to the given writers.
+    ///
+    /// Without `writer_2`, both mates of paired reads are written to `writer_1`, i.e., the output
+    /// is interleaved.
+    pub fn new(
+        policies: Vec<(String, Action)>,
+        plus_line_style: PlusLineStyle,
+        writer_1: fastq::Writer<Box<dyn io::Write>>,
+        writer_2: Option<fastq::Writer<Box<dyn io::Write>>>,
+    ) -> anyhow::Result<Self> {
+        // Mismatched sequence and quality lengths (S005) are repaired by padding or truncating
+        // the quality string.
+        let record_fixer = record_fix::Fixer::new(Some(QualityLengthPolicy::Pad));
+
+        for (code, action) in &policies {
+            if *action == Action::Repair && !record_fixer.can_repair(code, plus_line_style) {
+                return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                    .with_context(|| format!("invalid fix policy = {}: cannot repair", code));
+            }
+        }
+
+        Ok(Self {
+            record_fixer,
+            policies,
+            plus_line_style,
+            counts: BTreeMap::new(),
+            writer_1,
+            writer_2,
+            records_written: 0,
+        })
+    }
+
+    fn action(&self, code: &str) -> Action {
+        self.policies
+            .iter()
+            .rev()
+            .find(|(c, _)| c == code)
+            .map(|(_, action)| *action)
+            .unwrap_or_else(|| {
+                if self.record_fixer.can_repair(code, self.plus_line_style) {
+                    Action::Repair
+                } else {
+                    Action::Drop
+                }
+            })
+    }
+
+    /// Fixes and writes a record given the codes of its validation errors.
+    pub fn write_record(&mut self, record: &mut Record, codes: &[String]) -> io::Result<()> {
+        if self.is_dropped(&[codes]) {
+            return Ok(());
+        }
+
+        self.repair(record, codes);
+
+        self.writer_1.write_record(record)?;
+        self.records_written += 1;
+
+        Ok(())
+    }
+
+    /// Fixes and writes a pair given the codes of the validation errors of each mate and of the
+    /// pair.
+    ///
+    /// Both mates are dropped if either is dropped.
+    pub fn write_pair(
+        &mut self,
+        (b, b_codes): (&mut Record, &[String]),
+        (d, d_codes): (&mut Record, &[String]),
+        pair_codes: &[String],
+    ) -> io::Result<()> {
+        if self.is_dropped(&[b_codes, d_codes, pair_codes]) {
+            return Ok(());
+        }
+
+        self.repair(b, b_codes);
+        self.repair(d, d_codes);
+
+        self.writer_1.write_record(b)?;
+
+        match self.writer_2.as_mut() {
+            Some(writer) => writer.write_record(d)?,
+            None => self.writer_1.write_record(d)?,
+        }
+
+        self.records_written += 2;
+
+        Ok(())
+    }
+
+    /// Counts a record that was dropped because it could not be read, e.g., a truncated record.
+    pub fn add_dropped(&mut self, code: &str) {
+        self.counts.entry(code.into()).or_default().dropped += 1;
+    }
+
+    /// Logs the number of records written and, for each code, the number of records repaired
+    /// and dropped.
+    pub fn log(&self) {
+        info!("wrote {} records", self.records_written);
+
+        for (code, counts) in &self.counts {
+            info!(
+                "[{}] repaired {} records, dropped {} records",
+                code, counts.repaired, counts.dropped
+            );
+        }
+    }
+
+    // Returns whether a record (or pair) is dropped, counting each code that drops it once.
+    fn is_dropped(&mut self, codes: &[&[String]]) -> bool {
+        let mut drop_codes: Vec<&String> = codes
+            .iter()
+            .flat_map(|codes| codes.iter())
+            .filter(|code| self.action(code) == Action::Drop)
+            .collect();
+
+        drop_codes.sort();
+        drop_codes.dedup();
+
+        for code in &drop_codes {
+            self.counts.entry(code.to_string()).or_default().dropped += 1;
+        }
+
+        !drop_codes.is_empty()
+    }
+
+    fn repair(&mut self, record: &mut Record, codes: &[String]) {
+        let mut repair_codes: Vec<&String> = codes
+            .iter()
+            .filter(|code| self.action(code) == Action::Repair)
+            .collect();
+
+        repair_codes.sort();
+        repair_codes.dedup();
+
+        for code in repair_codes {
+            self.record_fixer.repair(record, code, self.plus_line_style);
+            self.counts.entry(code.into()).or_default().repaired += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_fixer(policies: Vec<(String, Action)>) -> Fixer {
+        let writer: Box<dyn io::Write> = Box::new(io::sink());
+
+        Fixer::new(
+            policies,
+            PlusLineStyle::Any,
+            fastq::Writer::new(writer),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_policy() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_policy("S005=drop")?,
+            (String::from("S005"), Action::Drop)
+        );
+        assert_eq!(
+            parse_policy("S001=repair")?,
+            (String::from("S001"), Action::Repair)
+        );
+        assert_eq!(
+            parse_policy("P001=keep")?,
+            (String::from("P001"), Action::Keep)
+        );
+
+        assert!(parse_policy("S005").is_err());
+        assert!(parse_policy("=drop").is_err());
+        assert!(parse_policy("S005=fix").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_with_invalid_policy() {
+        let writer: Box<dyn io::Write> = Box::new(io::sink());
+        let policies = vec![(String::from("S002"), Action::Repair)];
+
+        assert!(Fixer::new(
+            policies,
+            PlusLineStyle::Any,
+            fastq::Writer::new(writer),
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_action() {
+        let fixer = build_fixer(vec![(String::from("S005"), Action::Drop)]);
+
+        assert_eq!(fixer.action("S001"), Action::Repair);
+        assert_eq!(fixer.action("S002"), Action::Drop);
+        assert_eq!(fixer.action("S005"), Action::Drop);
+        assert_eq!(fixer.action("S016"), Action::Repair);
+        assert_eq!(fixer.action("P001"), Action::Drop);
+    }
+
+    #[test]
+    fn test_write_pair() -> io::Result<()> {
+        let mut fixer = build_fixer(vec![(String::from("P001"), Action::Keep)]);
+
+        let mut b = Record::new("@r0/1", "ACGT", "+r1", "II");
+        let mut d = Record::new("@r0/2", "ACGT", "+", "IIII");
+
+        fixer.write_pair(
+            (&mut b, &[String::from("S005"), String::from("S018")]),
+            (&mut d, &[]),
+            &[String::from("P001")],
+        )?;
+
+        assert_eq!(b, Record::new("@r0/1", "ACGT", "+", "II!!"));
+
+        let mut b = Record::new("@r1/1", "ACGU", "+", "IIII");
+
+        fixer.write_pair(
+            (&mut b, &[String::from("S002")]),
+            (&mut d, &[String::from("S005")]),
+            &[],
+        )?;
+
+        assert_eq!(fixer.records_written, 2);
+
+        let counts: Vec<_> = fixer
+            .counts
+            .iter()
+            .map(|(code, counts)| (code.as_str(), counts.repaired, counts.dropped))
+            .collect();
+
+        assert_eq!(counts, [("S002", 0, 1), ("S005", 1, 0), ("S018", 1, 0)]);
+
+        Ok(())
+    }
+}
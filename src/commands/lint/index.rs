@@ -0,0 +1,326 @@
+use std::io::BufRead;
+
+use crate::{
+    fastq::{self, Record},
+    quality::Detector,
+    validators::{
+        self, NamesValidator, PairedReadValidator, PlusLineStyle, ReadLengthValidator,
+        SingleReadValidator, SingleReadValidatorMut, TruncatedFileValidator, ValidationLevel,
+    },
+};
+
+use super::{finish_validators, read_record, unexpected_eof, ErrorHandler, RawRecordValidators};
+
+// An index read source and its current batch of records.
+struct IndexSource {
+    src: String,
+    reader: fastq::Reader<Box<dyn BufRead>>,
+    records: Vec<Record>,
+    raw_errors: Vec<Vec<validators::Error>>,
+    // The number of records kept in the current batch.
+    len: usize,
+    // The number of records read.
+    record_count: usize,
+    // The error of a truncated last record, if any, and its index in the source.
+    truncated_file_error: Option<(validators::Error, usize)>,
+    raw_record_validators: RawRecordValidators,
+    single_read_validators_mut: Vec<Box<dyn SingleReadValidatorMut>>,
+    read_length_validator: Option<ReadLengthValidator>,
+    detector: Detector,
+}
+
+/// Index reads, e.g., I1 and I2, that are read in step with paired reads.
+///
+/// Index records are checked by the built-in single read validators, but the validators of
+/// biological reads, e.g., sequence lengths and rules, are not used. Instead, all index reads in
+/// a source must have the same length (S008), and each name must match the name of read 1
+/// (P001).
+pub struct IndexReads {
+    sources: Vec<IndexSource>,
+    single_read_validators: Vec<Box<dyn SingleReadValidator>>,
+    names_validator: Option<NamesValidator>,
+    truncated_file_validator: Option<TruncatedFileValidator>,
+}
+
+impl IndexReads {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        readers: Vec<(String, fastq::Reader<Box<dyn BufRead>>)>,
+        single_read_validation_level: ValidationLevel,
+        paired_read_validation_level: ValidationLevel,
+        disabled_validators: &[String],
+        validator_config: &validators::Config,
+        plus_line_style: PlusLineStyle,
+        expected_len: Option<usize>,
+        handler: &mut ErrorHandler,
+    ) -> Self {
+        let (single_read_validators, _, _) = validators::filter_validators(
+            single_read_validation_level,
+            None,
+            disabled_validators,
+            validator_config,
+        );
+
+        let is_enabled = |code: &str, level: ValidationLevel, validation_level| {
+            level <= validation_level && !disabled_validators.iter().any(|c| c == code)
+        };
+
+        let names_validator = Some(NamesValidator)
+            .filter(|v| is_enabled(v.code(), v.level(), paired_read_validation_level));
+
+        let truncated_file_validator = Some(TruncatedFileValidator)
+            .filter(|v| is_enabled(v.code(), v.level(), single_read_validation_level));
+
+        let metrics = handler.metrics.clone();
+
+        let sources: Vec<_> = readers
+            .into_iter()
+            .map(|(src, mut reader)| {
+                let raw_record_validators = RawRecordValidators::new(
+                    single_read_validation_level,
+                    disabled_validators,
+                    plus_line_style,
+                );
+
+                reader.set_strip_carriage_returns(!raw_record_validators.keeps_carriage_returns());
+                reader.set_metrics(metrics.clone());
+
+                let single_read_validators_mut = validators::filter_single_read_validators_mut(
+                    single_read_validation_level,
+                    disabled_validators,
+                    validator_config,
+                );
+
+                let read_length_validator = Some(ReadLengthValidator::new(expected_len))
+                    .filter(|v| is_enabled(v.code(), v.level(), single_read_validation_level));
+
+                IndexSource {
+                    src,
+                    reader,
+                    records: Vec::new(),
+                    raw_errors: Vec::new(),
+                    len: 0,
+                    record_count: 0,
+                    truncated_file_error: None,
+                    raw_record_validators,
+                    single_read_validators_mut,
+                    read_length_validator,
+                    detector: Detector::default(),
+                }
+            })
+            .collect();
+
+        let index_reads = Self {
+            sources,
+            single_read_validators,
+            names_validator,
+            truncated_file_validator,
+        };
+
+        index_reads.add_to(handler);
+
+        index_reads
+    }
+
+    fn add_to(&self, handler: &mut ErrorHandler) {
+        for validator in &self.single_read_validators {
+            handler.add_validator(validator.code(), validator.name());
+        }
+
+        if let Some(source) = self.sources.first() {
+            source.raw_record_validators.add_to(handler);
+
+            for validator in &source.single_read_validators_mut {
+                handler.add_validator(validator.code(), validator.name());
+            }
+
+            if let Some(validator) = &source.read_length_validator {
+                handler.add_validator(validator.code(), validator.name());
+            }
+        }
+
+        if let Some(validator) = &self.names_validator {
+            handler.add_validator(validator.code(), validator.name());
+        }
+
+        if let Some(validator) = &self.truncated_file_validator {
+            handler.add_validator(validator.code(), validator.name());
+        }
+    }
+
+    /// Reads the index records of the pairs read by read 1, up to the pair at `end`.
+    ///
+    /// Only the records of the pairs at `indices` are kept for validation. This fails if an
+    /// index source ends before read 1, but a truncated last record is held until
+    /// [`Self::handle_truncated_file_errors`].
+    pub fn read_batch(
+        &mut self,
+        indices: &[usize],
+        end: usize,
+        r1_src: &str,
+        handler: &mut ErrorHandler,
+    ) -> anyhow::Result<()> {
+        let len = indices.len();
+
+        for source in &mut self.sources {
+            source.len = 0;
+        }
+
+        for source in &mut self.sources {
+            // Records that are not kept are read into the slot after the last one.
+            if source.records.len() <= len {
+                source.records.resize_with(len + 1, Record::default);
+                source.raw_errors.resize_with(len + 1, Vec::new);
+            }
+
+            let start = source.record_count;
+
+            while source.record_count < end {
+                let i = source.len;
+                let record = &mut source.records[i];
+                let record_index = source.record_count;
+
+                handler.add_record_offset(&source.src, record_index, source.reader.position());
+
+                let result = read_record(
+                    &mut source.reader,
+                    record,
+                    self.truncated_file_validator.as_ref(),
+                    &source.src,
+                )?;
+
+                match result {
+                    Ok(0) => {
+                        return Err(unexpected_eof(format!(
+                            "{} unexpectedly ended before {}",
+                            source.src, r1_src
+                        )));
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        source.truncated_file_error = Some((e, record_index));
+                        break;
+                    }
+                }
+
+                source.record_count += 1;
+
+                if indices.get(i) != Some(&record_index) {
+                    continue;
+                }
+
+                source
+                    .raw_record_validators
+                    .validate(record, &mut source.raw_errors[i]);
+
+                record.reset();
+                source.detector.add(record.quality_scores());
+                handler.add_stats(&source.src, record);
+
+                source.len += 1;
+            }
+
+            handler
+                .summary
+                .add_records(&source.src, (source.record_count - start) as u64);
+        }
+
+        Ok(())
+    }
+
+    /// Validates the index records of the pair at position `i` in the batch.
+    ///
+    /// `r1_record` is the read 1 record of the pair, and `record_index` is the index of the pair.
+    pub fn validate(
+        &mut self,
+        i: usize,
+        r1_record: &Record,
+        record_index: usize,
+        handler: &mut ErrorHandler,
+    ) {
+        for source in &mut self.sources {
+            if i >= source.len {
+                continue;
+            }
+
+            let record = &source.records[i];
+            let src = &source.src;
+
+            for e in source.raw_errors[i].drain(..) {
+                handler.handle_validation_error(e, src, record_index);
+            }
+
+            for validator in &self.single_read_validators {
+                if let Err(e) = validator.validate(record) {
+                    handler.handle_validation_error(e, src, record_index);
+                }
+            }
+
+            for validator in &mut source.single_read_validators_mut {
+                if let Err(e) = validator.validate(record) {
+                    handler.handle_validation_error(e, src, record_index);
+                }
+            }
+
+            if let Some(validator) = &mut source.read_length_validator {
+                if let Err(e) = validator.validate(record) {
+                    handler.handle_validation_error(e, src, record_index);
+                }
+            }
+
+            if let Some(validator) = &self.names_validator {
+                if let Err(e) = validator.validate(r1_record, record) {
+                    handler.handle_validation_error(e, src, record_index);
+                }
+            }
+        }
+    }
+
+    /// Reports the errors of truncated last records and returns whether there were any.
+    pub fn handle_truncated_file_errors(&mut self, handler: &mut ErrorHandler) -> bool {
+        let mut is_truncated = false;
+
+        for source in &mut self.sources {
+            if let Some((e, index)) = source.truncated_file_error.take() {
+                handler.handle_validation_error(e, &source.src, index);
+                is_truncated = true;
+            }
+        }
+
+        is_truncated
+    }
+
+    /// Checks that all index sources end with read 1.
+    pub fn check_eof(&mut self, r1_src: &str) -> anyhow::Result<()> {
+        let mut record = Record::default();
+
+        for source in &mut self.sources {
+            let result = read_record(
+                &mut source.reader,
+                &mut record,
+                self.truncated_file_validator.as_ref(),
+                &source.src,
+            )?;
+
+            if !matches!(result, Ok(0)) {
+                return Err(unexpected_eof(format!(
+                    "{} unexpectedly ended before {}",
+                    r1_src, source.src
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reports the results of whole-file checks and the detected quality encodings.
+    pub fn finish(&mut self, handler: &mut ErrorHandler) {
+        for source in &mut self.sources {
+            handler
+                .summary
+                .set_encoding(&source.src, source.detector.encoding());
+
+            finish_validators(&mut source.single_read_validators_mut, handler, &source.src);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+};
+
+use crate::bam::bgzf;
+
+/// The offsets of the records of a gzipped source in the current batch.
+///
+/// Offsets are positions in the uncompressed data. Offsets of BGZF-compressed sources are also
+/// mapped to virtual offsets, which can be seeked to directly.
+pub struct RecordOffsets {
+    src: String,
+    // The indices and uncompressed offsets of the records, in record order.
+    offsets: Vec<(usize, u64)>,
+    virtual_positions: Option<bgzf::VirtualPositions<BufReader<File>>>,
+}
+
+impl RecordOffsets {
+    /// Opens a source to map its record offsets.
+    ///
+    /// This returns `None` if the source is not gzipped.
+    pub fn open(src: &str) -> io::Result<Option<Self>> {
+        if !src.ends_with(".gz") {
+            return Ok(None);
+        }
+
+        let mut reader = File::open(src).map(BufReader::new)?;
+        let is_bgzf = bgzf::is_bgzf(reader.fill_buf()?);
+
+        Ok(Some(Self {
+            src: src.into(),
+            offsets: Vec::new(),
+            virtual_positions: is_bgzf.then(|| bgzf::VirtualPositions::new(reader)),
+        }))
+    }
+
+    pub fn src(&self) -> &str {
+        &self.src
+    }
+
+    /// Removes the offsets of the records of the last batch.
+    pub fn clear(&mut self) {
+        self.offsets.clear();
+    }
+
+    /// Adds the uncompressed offset of the record at `record_index`.
+    pub fn push(&mut self, record_index: usize, offset: u64) {
+        self.offsets.push((record_index, offset));
+    }
+
+    /// Returns the uncompressed offset and, if BGZF-compressed, the virtual offset of the record
+    /// at `record_index`.
+    pub fn get(&mut self, record_index: usize) -> (Option<u64>, Option<u64>) {
+        let offset = self
+            .offsets
+            .binary_search_by_key(&record_index, |(i, _)| *i)
+            .ok()
+            .map(|i| self.offsets[i].1);
+
+        // Read errors of the source are reported by its reader, so a block that cannot be read
+        // only leaves the virtual offset unknown.
+        let virtual_offset = offset
+            .zip(self.virtual_positions.as_mut())
+            .and_then(|(offset, virtual_positions)| virtual_positions.get(offset).ok().flatten());
+
+        (offset, virtual_offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open() -> io::Result<()> {
+        assert!(RecordOffsets::open("r1.fastq")?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_get() {
+        let mut offsets = RecordOffsets {
+            src: String::from("r1.fastq.gz"),
+            offsets: Vec::new(),
+            virtual_positions: None,
+        };
+
+        offsets.push(8, 144);
+        offsets.push(9, 162);
+
+        assert_eq!(offsets.get(9), (Some(162), None));
+        assert_eq!(offsets.get(10), (None, None));
+
+        offsets.clear();
+        assert_eq!(offsets.get(9), (None, None));
+    }
+}
@@ -0,0 +1,586 @@
+use std::{error, fmt, str::FromStr};
+
+use serde_json::{json, Map, Value};
+
+use crate::validators;
+
+const SARIF_VERSION: &str = "2.1.0";
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// The output format of validation errors.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// Human-readable `file:line:col` messages.
+    Text,
+    /// JSON Lines, i.e., one JSON object per error followed by a summary object.
+    Json,
+    /// A SARIF 2.1.0 log, where each validator is a rule.
+    Sarif,
+    /// Delimited values, i.e., a header row followed by one row per error.
+    Delimited(Delimiter),
+}
+
+/// The field delimiter of delimited output.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Delimiter {
+    /// Tab-separated values (TSV).
+    Tab,
+    /// Comma-separated values (CSV).
+    Comma,
+}
+
+// The columns of delimited (TSV or CSV) output.
+const DELIMITED_HEADER: [&str; 9] = [
+    "severity",
+    "file",
+    "line",
+    "column",
+    "code",
+    "name",
+    "message",
+    "offset",
+    "virtual_offset",
+];
+
+/// An error returned when an output format fails to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseFormatError(String);
+
+impl error::Error for ParseFormatError {}
+
+impl fmt::Display for ParseFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid output format: expected text, json, sarif, tsv, or csv, got '{}'",
+            self.0
+        )
+    }
+}
+
+impl FromStr for Format {
+    type Err = ParseFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "sarif" => Ok(Self::Sarif),
+            "tsv" => Ok(Self::Delimited(Delimiter::Tab)),
+            "csv" => Ok(Self::Delimited(Delimiter::Comma)),
+            _ => Err(ParseFormatError(s.into())),
+        }
+    }
+}
+
+/// The severity of a diagnostic.
+///
+/// Only errors fail validation. Warnings are reported but do not change the exit status.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+/// A validation error and where it occurred.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: String,
+    pub name: String,
+    pub message: String,
+    pub pathname: String,
+    pub line_no: Option<usize>,
+    pub col_no: Option<usize>,
+    pub record_index: Option<usize>,
+    /// The position of the record in the uncompressed data of a gzipped source.
+    pub offset: Option<u64>,
+    /// The virtual position of the record in a BGZF-compressed source.
+    pub virtual_offset: Option<u64>,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic for an error in the record at `record_index` (0-based).
+    pub fn from_record_error(
+        error: validators::Error,
+        pathname: &str,
+        record_index: usize,
+    ) -> Self {
+        let line_offset = error.line_type as usize;
+        let line_no = record_index * 4 + line_offset + 1;
+
+        Self {
+            severity: Severity::Error,
+            code: error.code,
+            name: error.name,
+            message: error.message,
+            pathname: pathname.into(),
+            line_no: Some(line_no),
+            col_no: error.col_no,
+            record_index: Some(record_index),
+            offset: None,
+            virtual_offset: None,
+        }
+    }
+
+    /// Creates a diagnostic for an error reported after all records are read, i.e., with no
+    /// position.
+    pub fn from_file_error(error: validators::Error, pathname: &str) -> Self {
+        Self {
+            severity: Severity::Error,
+            code: error.code,
+            name: error.name,
+            message: error.message,
+            pathname: pathname.into(),
+            line_no: None,
+            col_no: None,
+            record_index: None,
+            offset: None,
+            virtual_offset: None,
+        }
+    }
+
+    /// Returns the diagnostic as a JSON object.
+    ///
+    /// A missing position is `null`.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "type": self.severity.as_str(),
+            "code": self.code,
+            "name": self.name,
+            "message": self.message,
+            "file": self.pathname,
+            "line": self.line_no,
+            "column": self.col_no,
+            "recordIndex": self.record_index,
+            "offset": self.offset,
+            "virtualOffset": self.virtual_offset,
+        })
+    }
+}
+
+impl Diagnostic {
+    /// Returns the diagnostic as a row of delimited output.
+    ///
+    /// A missing position is an empty field.
+    pub fn to_row(&self, delimiter: Delimiter) -> String {
+        fn optional<N: ToString>(n: Option<N>) -> String {
+            n.map(|n| n.to_string()).unwrap_or_default()
+        }
+
+        let line_no = optional(self.line_no);
+        let col_no = optional(self.col_no);
+        let offset = optional(self.offset);
+        let virtual_offset = optional(self.virtual_offset);
+
+        build_row(
+            delimiter,
+            &[
+                self.severity.as_str(),
+                &self.pathname,
+                &line_no,
+                &col_no,
+                &self.code,
+                &self.name,
+                &self.message,
+                &offset,
+                &virtual_offset,
+            ],
+        )
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.pathname)?;
+
+        if let Some(line_no) = self.line_no {
+            write!(f, "{}:", line_no)?;
+
+            if let Some(col_no) = self.col_no {
+                write!(f, "{}:", col_no)?;
+            }
+        }
+
+        write!(f, " [{}] {}: {}", self.code, self.name, self.message)?;
+
+        match (self.offset, self.virtual_offset) {
+            (Some(offset), Some(virtual_offset)) => {
+                write!(f, " (offset {}, virtual offset {})", offset, virtual_offset)
+            }
+            (Some(offset), None) => write!(f, " (offset {})", offset),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Builds the header row of delimited output.
+pub fn build_delimited_header(delimiter: Delimiter) -> String {
+    build_row(delimiter, &DELIMITED_HEADER)
+}
+
+/// Builds a row of delimited output.
+///
+/// TSV fields replace tabs and line breaks with spaces. CSV fields are quoted when they contain a
+/// comma, quote, or line break.
+pub fn build_row(delimiter: Delimiter, fields: &[&str]) -> String {
+    fn escape_tsv(field: &str) -> String {
+        field.replace(['\t', '\n', '\r'], " ")
+    }
+
+    fn escape_csv(field: &str) -> String {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.into()
+        }
+    }
+
+    let (escape, delimiter): (fn(&str) -> String, &str) = match delimiter {
+        Delimiter::Tab => (escape_tsv, "\t"),
+        Delimiter::Comma => (escape_csv, ","),
+    };
+
+    fields
+        .iter()
+        .map(|field| escape(field))
+        .collect::<Vec<_>>()
+        .join(delimiter)
+}
+
+/// Builds the object of a validator in the list of validators.
+pub fn build_json_description(description: &validators::Description) -> Value {
+    json!({
+        "type": "validator",
+        "code": description.code,
+        "name": description.name,
+        "level": description.level.to_string(),
+        "kind": description.kind.to_string(),
+        "description": description.summary,
+    })
+}
+
+/// Builds the summary object written after all errors.
+pub fn build_json_summary(sources: &[&str], error_count: u64, warning_count: u64) -> Value {
+    json!({
+        "type": "summary",
+        "sources": sources,
+        "errorCount": error_count,
+        "warningCount": warning_count,
+        "valid": error_count == 0,
+    })
+}
+
+/// Builds a SARIF log of a lint run.
+///
+/// `rules` are the codes and names of the enabled validators. Each diagnostic is a result of the
+/// rule with its code, located at its line and column, if any.
+pub fn build_sarif(rules: &[(String, String)], diagnostics: &[Diagnostic]) -> Value {
+    let sarif_rules: Vec<_> = rules
+        .iter()
+        .map(|(code, name)| json!({ "id": code, "name": name }))
+        .collect();
+
+    let results: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| build_sarif_result(rules, diagnostic))
+        .collect();
+
+    json!({
+        "version": SARIF_VERSION,
+        "$schema": SARIF_SCHEMA,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "fq lint",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": sarif_rules,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+fn build_sarif_result(rules: &[(String, String)], diagnostic: &Diagnostic) -> Value {
+    let mut region = Map::new();
+
+    if let Some(line_no) = diagnostic.line_no {
+        region.insert(String::from("startLine"), Value::from(line_no));
+    }
+
+    if let Some(col_no) = diagnostic.col_no {
+        region.insert(String::from("startColumn"), Value::from(col_no));
+    }
+
+    let mut physical_location = Map::new();
+    physical_location.insert(
+        String::from("artifactLocation"),
+        json!({ "uri": diagnostic.pathname }),
+    );
+
+    if !region.is_empty() {
+        physical_location.insert(String::from("region"), Value::Object(region));
+    }
+
+    let mut result = Map::new();
+    result.insert(
+        String::from("ruleId"),
+        Value::from(diagnostic.code.as_str()),
+    );
+
+    if let Some(i) = rules.iter().position(|(code, _)| *code == diagnostic.code) {
+        result.insert(String::from("ruleIndex"), Value::from(i));
+    }
+
+    result.insert(
+        String::from("level"),
+        Value::from(diagnostic.severity.as_str()),
+    );
+    result.insert(
+        String::from("message"),
+        json!({ "text": diagnostic.message }),
+    );
+    result.insert(
+        String::from("locations"),
+        json!([{ "physicalLocation": physical_location }]),
+    );
+
+    Value::Object(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validators::LineType;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("text".parse(), Ok(Format::Text));
+        assert_eq!("json".parse(), Ok(Format::Json));
+        assert_eq!("sarif".parse(), Ok(Format::Sarif));
+        assert_eq!("tsv".parse(), Ok(Format::Delimited(Delimiter::Tab)));
+        assert_eq!("csv".parse(), Ok(Format::Delimited(Delimiter::Comma)));
+        assert_eq!(
+            "xml".parse::<Format>(),
+            Err(ParseFormatError(String::from("xml")))
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        let error = validators::Error::new(
+            "S002",
+            "AlphabetValidator",
+            "Invalid character: m",
+            LineType::Sequence,
+            Some(76),
+        );
+
+        let diagnostic = Diagnostic::from_record_error(error, "in.fastq", 2);
+
+        assert_eq!(
+            diagnostic.to_json().to_string(),
+            r#"{"type":"error","code":"S002","name":"AlphabetValidator","message":"Invalid character: m","file":"in.fastq","line":10,"column":76,"recordIndex":2,"offset":null,"virtualOffset":null}"#
+        );
+
+        let error = validators::Error::new(
+            "S008",
+            "ReadLengthValidator",
+            "Read lengths are not uniform",
+            LineType::Sequence,
+            None,
+        );
+
+        let diagnostic = Diagnostic::from_file_error(error, "in.fastq");
+
+        assert_eq!(
+            diagnostic.to_json().to_string(),
+            r#"{"type":"error","code":"S008","name":"ReadLengthValidator","message":"Read lengths are not uniform","file":"in.fastq","line":null,"column":null,"recordIndex":null,"offset":null,"virtualOffset":null}"#
+        );
+    }
+
+    #[test]
+    fn test_to_json_with_warning() {
+        let error = validators::Error::new(
+            "S018",
+            "PlusLineStyleValidator",
+            "Plus line style does not match (expected empty, got name)",
+            LineType::PlusLine,
+            Some(2),
+        );
+
+        let mut diagnostic = Diagnostic::from_record_error(error, "in.fastq", 0);
+        diagnostic.severity = Severity::Warning;
+
+        assert_eq!(
+            diagnostic.to_json().get("type").and_then(|v| v.as_str()),
+            Some("warning")
+        );
+    }
+
+    #[test]
+    fn test_to_row() {
+        let error = validators::Error::new(
+            "S002",
+            "AlphabetValidator",
+            "Invalid character: m",
+            LineType::Sequence,
+            Some(76),
+        );
+
+        let diagnostic = Diagnostic::from_record_error(error, "in.fastq", 2);
+
+        assert_eq!(
+            diagnostic.to_row(Delimiter::Tab),
+            "error\tin.fastq\t10\t76\tS002\tAlphabetValidator\tInvalid character: m\t\t"
+        );
+
+        let mut diagnostic = diagnostic;
+        diagnostic.offset = Some(36);
+        diagnostic.virtual_offset = Some(36);
+
+        assert_eq!(
+            diagnostic.to_row(Delimiter::Comma),
+            "error,in.fastq,10,76,S002,AlphabetValidator,Invalid character: m,36,36"
+        );
+
+        let error = validators::Error::new(
+            "P002",
+            "IdenticalMatesValidator",
+            "r1.fastq and r2.fastq are identical",
+            LineType::Name,
+            None,
+        );
+
+        let diagnostic = Diagnostic::from_file_error(error, "r1.fastq");
+
+        assert_eq!(
+            diagnostic.to_row(Delimiter::Comma),
+            "error,r1.fastq,,,P002,IdenticalMatesValidator,r1.fastq and r2.fastq are identical,,"
+        );
+    }
+
+    #[test]
+    fn test_fmt() {
+        let error = validators::Error::new(
+            "S002",
+            "AlphabetValidator",
+            "Invalid character: m",
+            LineType::Sequence,
+            Some(76),
+        );
+
+        let mut diagnostic = Diagnostic::from_record_error(error, "in.fastq.gz", 2);
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "in.fastq.gz:10:76: [S002] AlphabetValidator: Invalid character: m"
+        );
+
+        diagnostic.offset = Some(36);
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "in.fastq.gz:10:76: [S002] AlphabetValidator: Invalid character: m (offset 36)"
+        );
+
+        diagnostic.virtual_offset = Some(36);
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "in.fastq.gz:10:76: [S002] AlphabetValidator: Invalid character: m (offset 36, virtual offset 36)"
+        );
+    }
+
+    #[test]
+    fn test_build_delimited_header() {
+        assert_eq!(
+            build_delimited_header(Delimiter::Tab),
+            "severity\tfile\tline\tcolumn\tcode\tname\tmessage\toffset\tvirtual_offset"
+        );
+
+        assert_eq!(
+            build_delimited_header(Delimiter::Comma),
+            "severity,file,line,column,code,name,message,offset,virtual_offset"
+        );
+    }
+
+    #[test]
+    fn test_build_row() {
+        assert_eq!(build_row(Delimiter::Tab, &["a\tb", "c\nd"]), "a b\tc d");
+
+        assert_eq!(
+            build_row(Delimiter::Comma, &["a,b", "say \"hi\"", "c"]),
+            r#""a,b","say ""hi""",c"#
+        );
+    }
+
+    #[test]
+    fn test_build_json_summary() {
+        let summary = build_json_summary(&["r1.fq", "r2.fq"], 3, 1);
+
+        assert_eq!(
+            summary.to_string(),
+            r#"{"type":"summary","sources":["r1.fq","r2.fq"],"errorCount":3,"warningCount":1,"valid":false}"#
+        );
+    }
+
+    #[test]
+    fn test_build_json_description() {
+        let description = &validators::descriptions()[2];
+
+        assert_eq!(
+            build_json_description(description).to_string(),
+            r#"{"type":"validator","code":"S001","name":"PlusLineValidator","level":"low","kind":"single","description":"Plus line starts with a \"+\"."}"#
+        );
+    }
+
+    #[test]
+    fn test_build_sarif() {
+        let rules = [
+            (String::from("S001"), String::from("PlusLineValidator")),
+            (String::from("S002"), String::from("AlphabetValidator")),
+        ];
+
+        let error = validators::Error::new(
+            "S002",
+            "AlphabetValidator",
+            "Invalid character: m",
+            LineType::Sequence,
+            Some(76),
+        );
+
+        let diagnostics = [Diagnostic::from_record_error(error, "in.fastq", 2)];
+        let sarif = build_sarif(&rules, &diagnostics);
+
+        assert_eq!(sarif.get("version").and_then(|v| v.as_str()), Some("2.1.0"));
+
+        let run = &sarif.get("runs").and_then(|v| v.as_array()).unwrap()[0];
+
+        let driver = run.get("tool").and_then(|v| v.get("driver")).unwrap();
+        let rule_ids: Vec<_> = driver
+            .get("rules")
+            .and_then(|v| v.as_array())
+            .unwrap()
+            .iter()
+            .filter_map(|rule| rule.get("id").and_then(|v| v.as_str()))
+            .collect();
+        assert_eq!(rule_ids, ["S001", "S002"]);
+
+        let results = run.get("results").and_then(|v| v.as_array()).unwrap();
+        assert_eq!(
+            results[0].to_string(),
+            r#"{"ruleId":"S002","ruleIndex":1,"level":"error","message":{"text":"Invalid character: m"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"in.fastq"},"region":{"startLine":10,"startColumn":76}}}]}"#
+        );
+    }
+}
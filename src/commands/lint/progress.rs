@@ -0,0 +1,234 @@
+use std::{
+    io::{self, Write},
+    sync::{Arc, Condvar, Mutex},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use tracing::info;
+
+use crate::metrics::Metrics;
+
+// The interval between redraws of a progress bar.
+const BAR_INTERVAL: Duration = Duration::from_secs(1);
+
+// The number of cells in a progress bar.
+const BAR_WIDTH: usize = 30;
+
+/// How progress is reported.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Style {
+    /// Log progress at the given interval.
+    Log(Duration),
+    /// Redraw a progress bar on stderr.
+    Bar,
+}
+
+/// Reports the progress of a lint run from another thread until it is dropped.
+///
+/// Progress is the number of records read and, when the total size of the sources is known,
+/// the fraction of source bytes read and an estimate of the remaining time.
+pub struct Progress {
+    is_done: Arc<(Mutex<bool>, Condvar)>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Progress {
+    /// Starts reporting progress.
+    pub fn start(metrics: Metrics, total_bytes: Option<u64>, style: Style) -> Self {
+        let is_done = Arc::new((Mutex::new(false), Condvar::new()));
+
+        let interval = match style {
+            Style::Log(interval) => interval,
+            Style::Bar => BAR_INTERVAL,
+        };
+
+        let handle = {
+            let is_done = is_done.clone();
+
+            thread::spawn(move || {
+                let (lock, cvar) = &*is_done;
+                let mut done = lock.lock().unwrap();
+
+                // The run may finish before this thread first waits.
+                while !*done {
+                    done = cvar.wait_timeout(done, interval).unwrap().0;
+
+                    if *done {
+                        break;
+                    }
+
+                    match style {
+                        Style::Log(_) => log(&metrics, total_bytes),
+                        Style::Bar => draw_bar(&metrics, total_bytes),
+                    }
+                }
+
+                if style == Style::Bar {
+                    clear_bar();
+                }
+            })
+        };
+
+        Self {
+            is_done,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        let (lock, cvar) = &*self.is_done;
+        *lock.lock().unwrap() = true;
+        cvar.notify_one();
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn log(metrics: &Metrics, total_bytes: Option<u64>) {
+    let records_read = metrics.records_read();
+    let source_bytes_read = metrics.source_bytes_read();
+
+    match total_bytes {
+        Some(total_bytes) => {
+            let fraction = fraction(source_bytes_read, total_bytes);
+            let eta = estimate_remaining(fraction, metrics.elapsed())
+                .map(format_duration)
+                .unwrap_or_else(|| String::from("unknown"));
+
+            info!(
+                records_read,
+                source_bytes_read,
+                total_bytes,
+                percent = format!("{:.1}", fraction * 100.0).as_str(),
+                eta = eta.as_str(),
+                "progress"
+            );
+        }
+        None => info!(records_read, source_bytes_read, "progress"),
+    }
+}
+
+fn draw_bar(metrics: &Metrics, total_bytes: Option<u64>) {
+    let records_read = metrics.records_read();
+
+    let line = match total_bytes {
+        Some(total_bytes) => {
+            let fraction = fraction(metrics.source_bytes_read(), total_bytes);
+            let eta = estimate_remaining(fraction, metrics.elapsed())
+                .map(format_duration)
+                .unwrap_or_else(|| String::from("?"));
+
+            format!(
+                "{} {:>5.1}% {} records, ETA {}",
+                build_bar(fraction, BAR_WIDTH),
+                fraction * 100.0,
+                records_read,
+                eta
+            )
+        }
+        None => format!("{} records", records_read),
+    };
+
+    let mut stderr = io::stderr();
+    let _ = write!(stderr, "\r\x1b[2K{}", line);
+    let _ = stderr.flush();
+}
+
+fn clear_bar() {
+    let mut stderr = io::stderr();
+    let _ = write!(stderr, "\r\x1b[2K");
+    let _ = stderr.flush();
+}
+
+fn fraction(n: u64, total: u64) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        (n as f64 / total as f64).min(1.0)
+    }
+}
+
+// Estimates the time remaining from the fraction of work done in the elapsed time.
+fn estimate_remaining(fraction: f64, elapsed: Duration) -> Option<Duration> {
+    if fraction <= 0.0 {
+        return None;
+    }
+
+    let secs = elapsed.as_secs_f64() * (1.0 - fraction) / fraction;
+    Some(Duration::from_secs_f64(secs))
+}
+
+// Formats a duration as hours, minutes, and seconds, e.g., `1:02:03`.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+// Builds a bar of `width` cells filled to the given fraction, e.g., `[=====>    ]`.
+fn build_bar(fraction: f64, width: usize) -> String {
+    let filled = (fraction * width as f64) as usize;
+
+    let mut s = String::with_capacity(width + 2);
+    s.push('[');
+
+    for i in 0..width {
+        let c = if i < filled {
+            '='
+        } else if i == filled {
+            '>'
+        } else {
+            ' '
+        };
+
+        s.push(c);
+    }
+
+    s.push(']');
+
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fraction() {
+        assert_eq!(fraction(0, 8), 0.0);
+        assert_eq!(fraction(2, 8), 0.25);
+        assert_eq!(fraction(13, 8), 1.0);
+        assert_eq!(fraction(0, 0), 1.0);
+    }
+
+    #[test]
+    fn test_estimate_remaining() {
+        assert_eq!(estimate_remaining(0.0, Duration::from_secs(60)), None);
+        assert_eq!(
+            estimate_remaining(0.25, Duration::from_secs(60)),
+            Some(Duration::from_secs(180))
+        );
+        assert_eq!(
+            estimate_remaining(1.0, Duration::from_secs(60)),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(0)), "0:00:00");
+        assert_eq!(format_duration(Duration::from_secs(3723)), "1:02:03");
+        assert_eq!(format_duration(Duration::from_secs(90061)), "25:01:01");
+    }
+
+    #[test]
+    fn test_build_bar() {
+        assert_eq!(build_bar(0.0, 4), "[>   ]");
+        assert_eq!(build_bar(0.5, 4), "[==> ]");
+        assert_eq!(build_bar(1.0, 4), "[====]");
+    }
+}
@@ -0,0 +1,268 @@
+use std::{
+    error, fmt,
+    io::{self, Write},
+    str::FromStr,
+};
+
+/// The output format of a lint report.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// JUnit XML, where each validator is a test case.
+    Junit,
+}
+
+/// An error returned when a report format fails to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseFormatError(String);
+
+impl error::Error for ParseFormatError {}
+
+impl fmt::Display for ParseFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid report format: expected junit, got '{}'", self.0)
+    }
+}
+
+impl FromStr for Format {
+    type Err = ParseFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "junit" => Ok(Self::Junit),
+            _ => Err(ParseFormatError(s.into())),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct TestCase {
+    code: String,
+    name: String,
+    error_count: u64,
+    messages: Vec<String>,
+}
+
+/// Validation results of a lint run, grouped by validator.
+#[derive(Debug)]
+pub struct Report {
+    name: String,
+    max_messages: usize,
+    test_cases: Vec<TestCase>,
+}
+
+impl Report {
+    /// Creates an empty report.
+    ///
+    /// `name` identifies the inputs, e.g., the file pair. At most `max_messages` error messages
+    /// are kept for each validator, but all errors are counted.
+    pub fn new<N>(name: N, max_messages: usize) -> Self
+    where
+        N: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            max_messages,
+            test_cases: Vec::new(),
+        }
+    }
+
+    /// Adds an enabled validator.
+    ///
+    /// Validators are listed in the order they are added. Adding a validator more than once has
+    /// no effect.
+    pub fn add_validator(&mut self, code: &str, name: &str) {
+        self.test_case_mut(code, name);
+    }
+
+    /// Adds an error message for a validator.
+    pub fn add_error(&mut self, code: &str, name: &str, message: String) {
+        let max_messages = self.max_messages;
+        let test_case = self.test_case_mut(code, name);
+
+        test_case.error_count += 1;
+
+        if test_case.messages.len() < max_messages {
+            test_case.messages.push(message);
+        }
+    }
+
+    fn test_case_mut(&mut self, code: &str, name: &str) -> &mut TestCase {
+        let i = match self.test_cases.iter().position(|t| t.code == code) {
+            Some(i) => i,
+            None => {
+                self.test_cases.push(TestCase {
+                    code: code.into(),
+                    name: name.into(),
+                    error_count: 0,
+                    messages: Vec::new(),
+                });
+
+                self.test_cases.len() - 1
+            }
+        };
+
+        &mut self.test_cases[i]
+    }
+
+    /// Writes the report in the given format.
+    pub fn write<W>(&self, writer: &mut W, format: Format) -> io::Result<()>
+    where
+        W: Write,
+    {
+        match format {
+            Format::Junit => self.write_junit(writer),
+        }
+    }
+
+    fn write_junit<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let tests = self.test_cases.len();
+        let failures = self.test_cases.iter().filter(|t| t.error_count > 0).count();
+
+        let name = escape_xml(&self.name);
+
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<testsuites name="fq lint" tests="{}" failures="{}">"#,
+            tests, failures
+        )?;
+        writeln!(
+            writer,
+            r#"  <testsuite name="{}" tests="{}" failures="{}">"#,
+            name, tests, failures
+        )?;
+
+        for test_case in &self.test_cases {
+            let case_name = escape_xml(&format!("[{}] {}", test_case.code, test_case.name));
+
+            if test_case.error_count == 0 {
+                writeln!(
+                    writer,
+                    r#"    <testcase classname="{}" name="{}"/>"#,
+                    name, case_name
+                )?;
+
+                continue;
+            }
+
+            writeln!(
+                writer,
+                r#"    <testcase classname="{}" name="{}">"#,
+                name, case_name
+            )?;
+
+            let mut body = test_case.messages.join("\n");
+            let omitted_count = test_case.error_count - test_case.messages.len() as u64;
+
+            if omitted_count > 0 {
+                body.push_str(&format!("\n... and {} more", omitted_count));
+            }
+
+            writeln!(
+                writer,
+                r#"      <failure type="{}" message="{} error(s)">{}</failure>"#,
+                escape_xml(&test_case.code),
+                test_case.error_count,
+                escape_xml(&body)
+            )?;
+
+            writeln!(writer, "    </testcase>")?;
+        }
+
+        writeln!(writer, "  </testsuite>")?;
+        writeln!(writer, "</testsuites>")?;
+
+        Ok(())
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' | '\r' | '\t' => escaped.push(c),
+            c if c.is_control() => escaped.push('\u{fffd}'),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_error() {
+        let mut report = Report::new("in.fastq", 1);
+        report.add_validator("S001", "PlusLineValidator");
+        report.add_error("S002", "AlphabetValidator", String::from("a"));
+        report.add_error("S002", "AlphabetValidator", String::from("b"));
+        report.add_validator("S002", "AlphabetValidator");
+
+        assert_eq!(
+            report.test_cases,
+            [
+                TestCase {
+                    code: String::from("S001"),
+                    name: String::from("PlusLineValidator"),
+                    error_count: 0,
+                    messages: Vec::new(),
+                },
+                TestCase {
+                    code: String::from("S002"),
+                    name: String::from("AlphabetValidator"),
+                    error_count: 2,
+                    messages: vec![String::from("a")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_junit() -> io::Result<()> {
+        let mut report = Report::new("r1.fastq r2.fastq", 1);
+        report.add_validator("S001", "PlusLineValidator");
+        report.add_error(
+            "S002",
+            "AlphabetValidator",
+            String::from("r1.fastq:2:1: [S002] AlphabetValidator: Invalid character: <"),
+        );
+        report.add_error("S002", "AlphabetValidator", String::from("r2.fastq:6:1"));
+
+        let mut buf = Vec::new();
+        report.write(&mut buf, Format::Junit)?;
+
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="fq lint" tests="2" failures="1">
+  <testsuite name="r1.fastq r2.fastq" tests="2" failures="1">
+    <testcase classname="r1.fastq r2.fastq" name="[S001] PlusLineValidator"/>
+    <testcase classname="r1.fastq r2.fastq" name="[S002] AlphabetValidator">
+      <failure type="S002" message="2 error(s)">r1.fastq:2:1: [S002] AlphabetValidator: Invalid character: &lt;
+... and 1 more</failure>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+
+        assert_eq!(String::from_utf8(buf).unwrap(), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(escape_xml(r#"<a & "b">"#), "&lt;a &amp; &quot;b&quot;&gt;");
+        assert_eq!(escape_xml("a\u{1}b"), "a\u{fffd}b");
+    }
+}
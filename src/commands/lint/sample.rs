@@ -0,0 +1,81 @@
+/// Which records, or pairs, of an input are validated.
+///
+/// With a sample rate of `n`, every `n`th record is validated, starting with the first. The
+/// records in between are still read but not validated. With a maximum number of records,
+/// reading stops once that many records are validated.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Sampling {
+    rate: usize,
+    max_records: Option<usize>,
+}
+
+impl Sampling {
+    pub fn new(rate: usize, max_records: Option<usize>) -> Self {
+        assert!(rate > 0, "sample rate must be > 0");
+        Self { rate, max_records }
+    }
+
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    pub fn max_records(&self) -> Option<usize> {
+        self.max_records
+    }
+
+    /// Returns whether the record at `index` is validated.
+    pub fn is_sampled(&self, index: usize) -> bool {
+        index.is_multiple_of(self.rate)
+    }
+
+    /// Returns whether reading stops before the record at `index`, i.e., the maximum number of
+    /// records are validated before it.
+    pub fn is_done(&self, index: usize) -> bool {
+        self.max_records
+            .map(|n| self.sampled_count(index) >= n)
+            .unwrap_or(false)
+    }
+
+    // Returns the number of records validated before the record at `index`.
+    fn sampled_count(&self, index: usize) -> usize {
+        match index {
+            0 => 0,
+            _ => (index - 1) / self.rate + 1,
+        }
+    }
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Self::new(1, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_sampled() {
+        let sampling = Sampling::default();
+        assert!((0..4).all(|i| sampling.is_sampled(i)));
+
+        let sampling = Sampling::new(3, None);
+        let indices: Vec<_> = (0..8).filter(|&i| sampling.is_sampled(i)).collect();
+        assert_eq!(indices, [0, 3, 6]);
+    }
+
+    #[test]
+    fn test_is_done() {
+        let sampling = Sampling::default();
+        assert!(!sampling.is_done(usize::MAX));
+
+        let sampling = Sampling::new(1, Some(2));
+        assert!(!sampling.is_done(1));
+        assert!(sampling.is_done(2));
+
+        let sampling = Sampling::new(3, Some(2));
+        assert!(!sampling.is_done(3));
+        assert!(sampling.is_done(4));
+    }
+}
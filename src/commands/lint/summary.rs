@@ -0,0 +1,393 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use serde_json::{json, Value};
+use tracing::{error, info, warn};
+
+use super::{
+    output::{Diagnostic, Severity},
+    sample::Sampling,
+};
+use crate::quality::Encoding;
+
+#[derive(Debug, Eq, PartialEq)]
+struct FileSummary {
+    pathname: String,
+    record_count: u64,
+    error_count: u64,
+    encoding: Option<Encoding>,
+}
+
+impl FileSummary {
+    fn new(pathname: String) -> Self {
+        Self {
+            pathname,
+            record_count: 0,
+            error_count: 0,
+            encoding: None,
+        }
+    }
+}
+
+/// Error counts of a lint run by validator and by file.
+///
+/// Warnings are counted separately by validator and are not included in error counts.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Summary {
+    // Validator codes mapped to their names and error counts.
+    codes: BTreeMap<String, (String, u64)>,
+    // Validator codes mapped to their names and warning counts.
+    warning_codes: BTreeMap<String, (String, u64)>,
+    files: Vec<FileSummary>,
+    error_count: u64,
+    warning_count: u64,
+    sampling: Sampling,
+    // Whether reading an input stopped at the maximum number of records.
+    is_stopped: bool,
+    // The codes and names of validators that did not check all records.
+    incomplete_validators: Vec<(String, String)>,
+}
+
+impl Summary {
+    /// Creates a summary of the given sources.
+    ///
+    /// Sources are listed in the given order, even those without errors.
+    pub fn new(sources: &[String]) -> Self {
+        Self {
+            files: sources.iter().cloned().map(FileSummary::new).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Counts an error or warning.
+    pub fn add(&mut self, diagnostic: &Diagnostic) {
+        let codes = match diagnostic.severity {
+            Severity::Error => &mut self.codes,
+            Severity::Warning => &mut self.warning_codes,
+        };
+
+        let entry = codes
+            .entry(diagnostic.code.clone())
+            .or_insert_with(|| (diagnostic.name.clone(), 0));
+
+        entry.1 += 1;
+
+        match diagnostic.severity {
+            Severity::Error => {
+                self.file_mut(&diagnostic.pathname).error_count += 1;
+                self.error_count += 1;
+            }
+            Severity::Warning => {
+                self.file_mut(&diagnostic.pathname);
+                self.warning_count += 1;
+            }
+        }
+    }
+
+    /// Sets which records are validated.
+    pub fn set_sampling(&mut self, sampling: Sampling) {
+        self.sampling = sampling;
+    }
+
+    /// Marks that reading an input stopped at the maximum number of records.
+    pub fn set_stopped(&mut self) {
+        self.is_stopped = true;
+    }
+
+    /// Marks that a validator did not check all records, e.g., because of a memory limit.
+    pub fn add_incomplete_validator(&mut self, code: &str, name: &str) {
+        self.incomplete_validators.push((code.into(), name.into()));
+    }
+
+    /// Returns whether only some records were validated.
+    pub fn is_partial(&self) -> bool {
+        self.sampling.rate() > 1 || self.is_stopped || !self.incomplete_validators.is_empty()
+    }
+
+    /// Counts records read from a source.
+    pub fn add_records(&mut self, pathname: &str, n: u64) {
+        self.file_mut(pathname).record_count += n;
+    }
+
+    /// Sets the quality score encoding inferred from a source.
+    pub fn set_encoding(&mut self, pathname: &str, encoding: Option<Encoding>) {
+        self.file_mut(pathname).encoding = encoding;
+    }
+
+    fn file_mut(&mut self, pathname: &str) -> &mut FileSummary {
+        let i = match self.files.iter().position(|f| f.pathname == pathname) {
+            Some(i) => i,
+            None => {
+                self.files.push(FileSummary::new(pathname.into()));
+                self.files.len() - 1
+            }
+        };
+
+        &mut self.files[i]
+    }
+
+    /// Returns the number of records read from all sources.
+    pub fn record_count(&self) -> u64 {
+        self.files.iter().map(|f| f.record_count).sum()
+    }
+
+    pub fn error_count(&self) -> u64 {
+        self.error_count
+    }
+
+    pub fn warning_count(&self) -> u64 {
+        self.warning_count
+    }
+
+    /// Returns the validator codes, names, and error counts of validators with errors, ordered by
+    /// code.
+    pub fn codes(&self) -> impl Iterator<Item = (&str, &str, u64)> {
+        self.codes
+            .iter()
+            .map(|(code, (name, n))| (code.as_str(), name.as_str(), *n))
+    }
+
+    /// Returns the sources and their error counts.
+    pub fn files(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.files
+            .iter()
+            .map(|f| (f.pathname.as_str(), f.error_count))
+    }
+
+    /// Builds the summary document of a lint run.
+    ///
+    /// `validators` are the codes and names of the enabled validators, which are listed with
+    /// their error and warning counts, even if zero, ordered by code.
+    pub fn to_json(&self, validators: &[(String, String)], runtime: Duration) -> Value {
+        let mut names: BTreeMap<&str, &str> = validators
+            .iter()
+            .map(|(code, name)| (code.as_str(), name.as_str()))
+            .collect();
+
+        for (code, (name, _)) in self.codes.iter().chain(&self.warning_codes) {
+            names.entry(code).or_insert(name);
+        }
+
+        let count = |codes: &BTreeMap<String, (String, u64)>, code: &str| {
+            codes.get(code).map(|(_, n)| *n).unwrap_or(0)
+        };
+
+        let validators: Vec<_> = names
+            .into_iter()
+            .map(|(code, name)| {
+                json!({
+                    "code": code,
+                    "name": name,
+                    "errorCount": count(&self.codes, code),
+                    "warningCount": count(&self.warning_codes, code),
+                })
+            })
+            .collect();
+
+        let files: Vec<_> = self
+            .files
+            .iter()
+            .map(|file| {
+                json!({
+                    "file": file.pathname,
+                    "recordCount": file.record_count,
+                    "errorCount": file.error_count,
+                    "encoding": file.encoding.map(|encoding| encoding.to_string()),
+                })
+            })
+            .collect();
+
+        json!({
+            "tool": "fq lint",
+            "version": env!("CARGO_PKG_VERSION"),
+            "valid": self.error_count == 0,
+            "partial": self.is_partial(),
+            "sampleRate": self.sampling.rate(),
+            "maxRecords": self.sampling.max_records(),
+            "recordCount": self.record_count(),
+            "errorCount": self.error_count,
+            "warningCount": self.warning_count,
+            "runtimeSeconds": runtime.as_secs_f64(),
+            "validators": validators,
+            "files": files,
+        })
+    }
+
+    /// Logs the error counts by validator, by file, and in total.
+    ///
+    /// The inferred quality score encoding of each file is also logged, as is whether validation
+    /// was partial.
+    pub fn log(&self) {
+        for (code, name, n) in self.codes() {
+            error!("[{}] {}: {} errors", code, name, n);
+        }
+
+        for (code, (name, n)) in &self.warning_codes {
+            warn!("[{}] {}: {} warnings", code, name, n);
+        }
+
+        for file in &self.files {
+            match file.encoding {
+                Some(encoding) => info!(
+                    "{}: {} errors, inferred quality score encoding: {}",
+                    file.pathname, file.error_count, encoding
+                ),
+                None => info!(
+                    "{}: {} errors, inferred quality score encoding: unknown",
+                    file.pathname, file.error_count
+                ),
+            }
+        }
+
+        if self.sampling.rate() > 1 {
+            warn!(
+                "validation was partial: 1 in {} records validated",
+                self.sampling.rate()
+            );
+        }
+
+        if let Some(n) = self.sampling.max_records().filter(|_| self.is_stopped) {
+            warn!(
+                "validation was partial: stopped after validating {} records of an input",
+                n
+            );
+        }
+
+        for (code, name) in &self.incomplete_validators {
+            warn!(
+                "validation was partial: [{}] {} did not check all records",
+                code, name
+            );
+        }
+
+        match (self.error_count, self.warning_count) {
+            (0, 0) => info!("validation passed: 0 errors"),
+            (0, m) => info!("validation passed: 0 errors, {} warnings", m),
+            (n, 0) => error!("validation failed: {} errors", n),
+            (n, m) => error!("validation failed: {} errors, {} warnings", n, m),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_diagnostic(code: &str, name: &str, pathname: &str) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            code: code.into(),
+            name: name.into(),
+            message: String::new(),
+            pathname: pathname.into(),
+            line_no: None,
+            col_no: None,
+            record_index: None,
+            offset: None,
+            virtual_offset: None,
+        }
+    }
+
+    #[test]
+    fn test_add() {
+        let sources = [String::from("r1.fq"), String::from("r2.fq")];
+        let mut summary = Summary::new(&sources);
+
+        summary.add(&build_diagnostic("S006", "QualityStringValidator", "r1.fq"));
+        summary.add(&build_diagnostic("S002", "AlphabetValidator", "r1.fq"));
+        summary.add(&build_diagnostic("S002", "AlphabetValidator", "r1.fq"));
+
+        assert_eq!(summary.error_count(), 3);
+
+        assert_eq!(
+            summary.codes().collect::<Vec<_>>(),
+            [
+                ("S002", "AlphabetValidator", 2),
+                ("S006", "QualityStringValidator", 1)
+            ]
+        );
+
+        assert_eq!(
+            summary.files().collect::<Vec<_>>(),
+            [("r1.fq", 3), ("r2.fq", 0)]
+        );
+    }
+
+    #[test]
+    fn test_add_with_warning() {
+        let sources = [String::from("r1.fq")];
+        let mut summary = Summary::new(&sources);
+
+        let mut diagnostic = build_diagnostic("S018", "PlusLineStyleValidator", "r1.fq");
+        diagnostic.severity = Severity::Warning;
+        summary.add(&diagnostic);
+
+        summary.add(&build_diagnostic("S002", "AlphabetValidator", "r1.fq"));
+
+        assert_eq!(summary.error_count(), 1);
+        assert_eq!(summary.warning_count(), 1);
+        assert_eq!(
+            summary.codes().collect::<Vec<_>>(),
+            [("S002", "AlphabetValidator", 1)]
+        );
+        assert_eq!(summary.files().collect::<Vec<_>>(), [("r1.fq", 1)]);
+    }
+
+    #[test]
+    fn test_set_encoding() {
+        let sources = [String::from("r1.fq")];
+        let mut summary = Summary::new(&sources);
+
+        summary.set_encoding("r1.fq", Some(Encoding::Phred33));
+        summary.set_encoding("r2.fq", None);
+
+        assert_eq!(summary.files[0].encoding, Some(Encoding::Phred33));
+        assert_eq!(
+            summary.files().collect::<Vec<_>>(),
+            [("r1.fq", 0), ("r2.fq", 0)]
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        let sources = [String::from("r1.fq")];
+        let mut summary = Summary::new(&sources);
+
+        summary.add_records("r1.fq", 3);
+        summary.set_encoding("r1.fq", Some(Encoding::Phred33));
+        summary.add(&build_diagnostic("S002", "AlphabetValidator", "r1.fq"));
+
+        let validators = [
+            (String::from("S004"), String::from("NamesValidator")),
+            (String::from("S002"), String::from("AlphabetValidator")),
+        ];
+
+        let actual = summary.to_json(&validators, Duration::from_millis(1500));
+
+        let expected = format!(
+            r#"{{"tool":"fq lint","version":"{}","valid":false,"partial":false,"sampleRate":1,"maxRecords":null,"recordCount":3,"errorCount":1,"warningCount":0,"runtimeSeconds":1.5,"validators":[{{"code":"S002","name":"AlphabetValidator","errorCount":1,"warningCount":0}},{{"code":"S004","name":"NamesValidator","errorCount":0,"warningCount":0}}],"files":[{{"file":"r1.fq","recordCount":3,"errorCount":1,"encoding":"phred33"}}]}}"#,
+            env!("CARGO_PKG_VERSION")
+        );
+
+        assert_eq!(actual.to_string(), expected);
+    }
+
+    #[test]
+    fn test_is_partial() {
+        let mut summary = Summary::new(&[]);
+        assert!(!summary.is_partial());
+
+        summary.set_sampling(Sampling::new(1, Some(10)));
+        assert!(!summary.is_partial());
+
+        summary.set_stopped();
+        assert!(summary.is_partial());
+
+        let mut summary = Summary::new(&[]);
+        summary.set_sampling(Sampling::new(10, None));
+        assert!(summary.is_partial());
+
+        let mut summary = Summary::new(&[]);
+        summary.add_incomplete_validator("S007", "DuplicateNameValidator");
+        assert!(summary.is_partial());
+    }
+}
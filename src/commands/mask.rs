@@ -0,0 +1,250 @@
+use std::io::{self, BufRead, Write};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fastq::{self, Record},
+    mask::{mask_low_quality, mask_range, PositionRange},
+    metrics::Metrics,
+};
+
+#[derive(Debug, Default)]
+struct MaskOptions {
+    min_quality: Option<u8>,
+    ranges: Vec<PositionRange>,
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Counts {
+    records: u64,
+    masked: u64,
+    bases_masked: u64,
+}
+
+pub fn mask(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r1_dst = matches.value_of("r1-dst").unwrap();
+
+    let r2_src = matches.value_of("r2-src");
+    let r2_dst = matches.value_of("r2-dst");
+
+    let min_quality = if matches.is_present("min-quality") {
+        Some(
+            matches
+                .value_of_t("min-quality")
+                .unwrap_or_else(|e| e.exit()),
+        )
+    } else {
+        None
+    };
+
+    let ranges = if matches.is_present("range") {
+        matches.values_of_t("range").unwrap_or_else(|e| e.exit())
+    } else {
+        Vec::new()
+    };
+
+    let options = MaskOptions {
+        min_quality,
+        ranges,
+    };
+
+    info!("fq-mask start");
+
+    let metrics = Metrics::new();
+
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    let mut w1 =
+        fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+    r1.set_metrics(metrics.clone());
+    w1.set_metrics(metrics.clone());
+
+    let _stage = metrics.stage("masking");
+
+    let counts = match (r2_src, r2_dst) {
+        (Some(r2_src), Some(r2_dst)) => {
+            info!("masking paired end reads");
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            let mut w2 = fastq::create(r2_dst)
+                .with_context(|| format!("Could not create file: {}", r2_dst))?;
+            r2.set_metrics(metrics.clone());
+            w2.set_metrics(metrics.clone());
+
+            mask_paired((&mut r1, &mut w1), (&mut r2, &mut w2), &options)?
+        }
+        (Some(r2_src), None) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-dst for {}", r2_src));
+        }
+        (None, Some(r2_dst)) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-src for {}", r2_dst));
+        }
+        (None, None) => {
+            info!("masking single end reads");
+            mask_single(&mut r1, &mut w1, &options)?
+        }
+    };
+
+    info!(
+        "masked {} bases in {}/{} records",
+        counts.bases_masked, counts.masked, counts.records
+    );
+
+    metrics.log();
+
+    info!("fq-mask end");
+
+    Ok(())
+}
+
+fn mask_record(options: &MaskOptions, record: &mut Record, counts: &mut Counts) {
+    let mut bases_masked = 0;
+
+    if let Some(min_quality) = options.min_quality {
+        bases_masked += mask_low_quality(record, min_quality);
+    }
+
+    for &range in &options.ranges {
+        bases_masked += mask_range(record, range);
+    }
+
+    if bases_masked > 0 {
+        counts.masked += 1;
+        counts.bases_masked += bases_masked as u64;
+    }
+
+    counts.records += 1;
+}
+
+fn mask_single<R, W>(
+    reader: &mut fastq::Reader<R>,
+    writer: &mut fastq::Writer<W>,
+    options: &MaskOptions,
+) -> anyhow::Result<Counts>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut record = Record::default();
+    let mut counts = Counts::default();
+
+    while reader.read_record(&mut record)? != 0 {
+        mask_record(options, &mut record, &mut counts);
+        writer.write_record(&record)?;
+    }
+
+    Ok(counts)
+}
+
+fn mask_paired<R, S, W, X>(
+    (r1, w1): (&mut fastq::Reader<R>, &mut fastq::Writer<W>),
+    (r2, w2): (&mut fastq::Reader<S>, &mut fastq::Writer<X>),
+    options: &MaskOptions,
+) -> anyhow::Result<Counts>
+where
+    R: BufRead,
+    S: BufRead,
+    W: Write,
+    X: Write,
+{
+    let mut s1 = Record::default();
+    let mut s2 = Record::default();
+
+    let mut counts = Counts::default();
+
+    loop {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, _) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r1-src unexpectedly ended before r2-src");
+            }
+            (_, 0) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r2-src unexpectedly ended before r1-src");
+            }
+            (_, _) => {
+                mask_record(options, &mut s1, &mut counts);
+                mask_record(options, &mut s2, &mut counts);
+
+                w1.write_record(&s1)?;
+                w2.write_record(&s2)?;
+            }
+        }
+    }
+
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_record() {
+        let options = MaskOptions {
+            min_quality: Some(20),
+            ranges: vec![
+                PositionRange::new(1, 1).unwrap(),
+                PositionRange::new(3, 4).unwrap(),
+            ],
+        };
+
+        let mut counts = Counts::default();
+
+        let mut record = Record::new("@r0", "ACGTACGT", "+", "#IIII5+I");
+        mask_record(&options, &mut record, &mut counts);
+        assert_eq!(record.sequence(), b"NCNNACNT");
+        assert_eq!(record.quality_scores(), b"#IIII5+I");
+
+        let options = MaskOptions::default();
+        let mut record = Record::new("@r1", "ACGT", "+", "####");
+        mask_record(&options, &mut record, &mut counts);
+        assert_eq!(record.sequence(), b"ACGT");
+
+        assert_eq!(
+            counts,
+            Counts {
+                records: 2,
+                masked: 1,
+                bases_masked: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn test_mask_paired() -> anyhow::Result<()> {
+        let options = MaskOptions {
+            min_quality: Some(20),
+            ..Default::default()
+        };
+
+        let r1_data = b"@r0/1\nAACCGG\n+\nII#III\n";
+        let r2_data = b"@r0/2\nTTGGCC\n+\nIIIII#\n";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut w2 = fastq::Writer::new(Vec::new());
+
+        let counts = mask_paired((&mut r1, &mut w1), (&mut r2, &mut w2), &options)?;
+
+        assert_eq!(counts.records, 2);
+        assert_eq!(counts.bases_masked, 2);
+        assert_eq!(w1.get_ref(), b"@r0/1\nAANCGG\n+\nII#III\n");
+        assert_eq!(w2.get_ref(), b"@r0/2\nTTGGCN\n+\nIIIII#\n");
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&b""[..]);
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut w2 = fastq::Writer::new(Vec::new());
+        assert!(mask_paired((&mut r1, &mut w1), (&mut r2, &mut w2), &options).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use std::io::{self, BufRead, Write};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fastq::{self, Record},
+    matching::OverlapMatcher,
+    merge::PairMerger,
+    metrics::Metrics,
+};
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Counts {
+    pairs: u64,
+    merged: u64,
+}
+
+pub fn merge_pairs(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r2_src = matches.value_of("r2-src").unwrap();
+
+    let merged_dst = matches.value_of("merged-dst").unwrap();
+    let r1_dst = matches.value_of("r1-dst").unwrap();
+    let r2_dst = matches.value_of("r2-dst").unwrap();
+
+    let min_overlap = matches
+        .value_of_t("min-overlap")
+        .unwrap_or_else(|e| e.exit());
+
+    let max_error_rate: f64 = matches
+        .value_of_t("max-error-rate")
+        .unwrap_or_else(|e| e.exit());
+
+    if !(0.0..=1.0).contains(&max_error_rate) {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid max error rate = {}", max_error_rate));
+    }
+
+    let merger = PairMerger::new(OverlapMatcher::new(max_error_rate, min_overlap));
+
+    info!("fq-merge-pairs start");
+
+    let metrics = Metrics::new();
+
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    let mut r2 = fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+    r1.set_metrics(metrics.clone());
+    r2.set_metrics(metrics.clone());
+
+    let mut merged_writer = fastq::create(merged_dst)
+        .with_context(|| format!("Could not create file: {}", merged_dst))?;
+    let mut w1 =
+        fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+    let mut w2 =
+        fastq::create(r2_dst).with_context(|| format!("Could not create file: {}", r2_dst))?;
+    merged_writer.set_metrics(metrics.clone());
+    w1.set_metrics(metrics.clone());
+    w2.set_metrics(metrics.clone());
+
+    let _stage = metrics.stage("merging pairs");
+
+    let counts = merge_paired(
+        &merger,
+        (&mut r1, &mut r2),
+        &mut merged_writer,
+        (&mut w1, &mut w2),
+    )?;
+
+    let percentage = if counts.pairs == 0 {
+        0.0
+    } else {
+        (counts.merged as f64) / (counts.pairs as f64) * 100.0
+    };
+
+    info!(
+        "merged {}/{} ({:.1}%) pairs",
+        counts.merged, counts.pairs, percentage
+    );
+
+    metrics.log();
+
+    info!("fq-merge-pairs end");
+
+    Ok(())
+}
+
+// Merged pairs are written to `merged_writer`, and unmerged pairs, to `w1` and `w2`.
+fn merge_paired<R, S, W, X, Y>(
+    merger: &PairMerger,
+    (r1, r2): (&mut fastq::Reader<R>, &mut fastq::Reader<S>),
+    merged_writer: &mut fastq::Writer<W>,
+    (w1, w2): (&mut fastq::Writer<X>, &mut fastq::Writer<Y>),
+) -> anyhow::Result<Counts>
+where
+    R: BufRead,
+    S: BufRead,
+    W: Write,
+    X: Write,
+    Y: Write,
+{
+    let mut s1 = Record::default();
+    let mut s2 = Record::default();
+    let mut merged = Record::default();
+
+    let mut counts = Counts::default();
+
+    loop {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, _) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r1-src unexpectedly ended before r2-src");
+            }
+            (_, 0) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r2-src unexpectedly ended before r1-src");
+            }
+            (_, _) => {
+                if merger.merge(&s1, &s2, &mut merged).is_some() {
+                    merged_writer.write_record(&merged)?;
+                    counts.merged += 1;
+                } else {
+                    w1.write_record(&s1)?;
+                    w2.write_record(&s2)?;
+                }
+
+                counts.pairs += 1;
+            }
+        }
+    }
+
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_paired() -> anyhow::Result<()> {
+        let r1_data = b"@r0/1\nACGTACGTTT\n+\nIIIIIIIIII\n@r1/1\nACGTACGTTT\n+\nIIIIIIIIII\n";
+        let r2_data = b"@r0/2\nGGGCAAACGT\n+\nIIIIIIIIII\n@r1/2\nCCCCCCCCCC\n+\nIIIIIIIIII\n";
+
+        let merger = PairMerger::new(OverlapMatcher::new(0.1, 4));
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut merged_writer = fastq::Writer::new(Vec::new());
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut w2 = fastq::Writer::new(Vec::new());
+
+        let counts = merge_paired(
+            &merger,
+            (&mut r1, &mut r2),
+            &mut merged_writer,
+            (&mut w1, &mut w2),
+        )?;
+
+        assert_eq!(
+            counts,
+            Counts {
+                pairs: 2,
+                merged: 1
+            }
+        );
+
+        assert_eq!(
+            merged_writer.get_ref(),
+            b"@r0\nACGTACGTTTGCCC\n+\nIIIIJJJJJJIIII\n"
+        );
+        assert_eq!(w1.get_ref(), b"@r1/1\nACGTACGTTT\n+\nIIIIIIIIII\n");
+        assert_eq!(w2.get_ref(), b"@r1/2\nCCCCCCCCCC\n+\nIIIIIIIIII\n");
+
+        Ok(())
+    }
+}
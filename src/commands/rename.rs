@@ -0,0 +1,197 @@
+use std::io::{self, BufRead, Write};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fastq::{self, name_id, Record},
+    metrics::Metrics,
+    rename::Template,
+};
+
+pub fn rename(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r1_dst = matches.value_of("r1-dst").unwrap();
+
+    let r2_src = matches.value_of("r2-src");
+    let r2_dst = matches.value_of("r2-dst");
+
+    let template: Template = matches.value_of_t("template").unwrap_or_else(|e| e.exit());
+
+    info!("fq-rename start");
+
+    let metrics = Metrics::new();
+
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    let mut w1 =
+        fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+    r1.set_metrics(metrics.clone());
+    w1.set_metrics(metrics.clone());
+
+    let _stage = metrics.stage("renaming");
+
+    let n = match (r2_src, r2_dst) {
+        (Some(r2_src), Some(r2_dst)) => {
+            info!("renaming paired end reads");
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            let mut w2 = fastq::create(r2_dst)
+                .with_context(|| format!("Could not create file: {}", r2_dst))?;
+            r2.set_metrics(metrics.clone());
+            w2.set_metrics(metrics.clone());
+
+            rename_paired((&mut r1, &mut w1), (&mut r2, &mut w2), &template)?
+        }
+        (Some(r2_src), None) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-dst for {}", r2_src));
+        }
+        (None, Some(r2_dst)) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-src for {}", r2_dst));
+        }
+        (None, None) => {
+            info!("renaming single end reads");
+            rename_single(&mut r1, &mut w1, &template)?
+        }
+    };
+
+    info!("renamed {} records (or pairs)", n);
+
+    metrics.log();
+
+    info!("fq-rename end");
+
+    Ok(())
+}
+
+// Indices are 1-based.
+fn rename_single<R, W>(
+    reader: &mut fastq::Reader<R>,
+    writer: &mut fastq::Writer<W>,
+    template: &Template,
+) -> anyhow::Result<u64>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut records = [Record::default()];
+    let mut n = 0;
+
+    while reader.read_record(&mut records[0])? != 0 {
+        n += 1;
+        template.rename(n, &mut records);
+        writer.write_record(&records[0])?;
+    }
+
+    Ok(n)
+}
+
+fn rename_paired<R, S, W, X>(
+    (r1, w1): (&mut fastq::Reader<R>, &mut fastq::Writer<W>),
+    (r2, w2): (&mut fastq::Reader<S>, &mut fastq::Writer<X>),
+    template: &Template,
+) -> anyhow::Result<u64>
+where
+    R: BufRead,
+    S: BufRead,
+    W: Write,
+    X: Write,
+{
+    let mut records = [Record::default(), Record::default()];
+    let mut n = 0;
+
+    loop {
+        let [s1, s2] = &mut records;
+
+        match (r1.read_record(s1)?, r2.read_record(s2)?) {
+            (0, 0) => break,
+            (0, _) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r1-src unexpectedly ended before r2-src");
+            }
+            (_, 0) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r2-src unexpectedly ended before r1-src");
+            }
+            (_, _) => {
+                if name_id(s1.name()) != name_id(s2.name()) {
+                    return Err(io::Error::from(io::ErrorKind::InvalidData)).with_context(|| {
+                        format!(
+                            "Mate names do not match: {}, {}",
+                            String::from_utf8_lossy(s1.name()),
+                            String::from_utf8_lossy(s2.name())
+                        )
+                    });
+                }
+
+                n += 1;
+                template.rename(n, &mut records);
+
+                w1.write_record(&records[0])?;
+                w2.write_record(&records[1])?;
+            }
+        }
+    }
+
+    Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_single() -> anyhow::Result<()> {
+        let data = b"@r0\nAC\n+\nFQ\n@r1 1:N:0\nGT\n+\nLI\n";
+        let template = "read_{index}".parse()?;
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+        let n = rename_single(&mut reader, &mut writer, &template)?;
+
+        assert_eq!(n, 2);
+        assert_eq!(
+            writer.get_ref(),
+            b"@read_1\nAC\n+\nFQ\n@read_2 1:N:0\nGT\n+\nLI\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_paired() -> anyhow::Result<()> {
+        let r1_data = b"@r0/1\nAC\n+\nFQ\n@r1/1\nGT\n+\nLI\n";
+        let r2_data = b"@r0/2\nTT\n+\nFQ\n@r1/2\nCC\n+\nLI\n";
+        let template = "s1_{name}_{index}".parse()?;
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut w2 = fastq::Writer::new(Vec::new());
+
+        let n = rename_paired((&mut r1, &mut w1), (&mut r2, &mut w2), &template)?;
+
+        assert_eq!(n, 2);
+        assert_eq!(
+            w1.get_ref(),
+            b"@s1_r0_1/1\nAC\n+\nFQ\n@s1_r1_2/1\nGT\n+\nLI\n"
+        );
+        assert_eq!(
+            w2.get_ref(),
+            b"@s1_r0_1/2\nTT\n+\nFQ\n@s1_r1_2/2\nCC\n+\nLI\n"
+        );
+
+        let r2_data = b"@r0/2\nTT\n+\nFQ\n@r2/2\nCC\n+\nLI\n";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut w2 = fastq::Writer::new(Vec::new());
+        assert!(rename_paired((&mut r1, &mut w1), (&mut r2, &mut w2), &template).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,445 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    env,
+    fs::{self, File},
+    hash::{Hash, Hasher},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    mem,
+    path::PathBuf,
+    process,
+    sync::atomic::{self, AtomicUsize},
+};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fastq::{self, name_id, Record},
+    metrics::Metrics,
+};
+
+// The number of partitions per mate when pending records are spilled to disk.
+const PARTITION_COUNT: usize = 64;
+
+// Distinguishes the partition files of repairers in the same process.
+static REPAIRER_ID: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Counts {
+    pairs: u64,
+    singletons: u64,
+}
+
+struct Writers<W> {
+    r1: fastq::Writer<W>,
+    r2: fastq::Writer<W>,
+    singletons: fastq::Writer<W>,
+}
+
+pub fn repair(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r2_src = matches.value_of("r2-src").unwrap();
+
+    let r1_dst = matches.value_of("r1-dst").unwrap();
+    let r2_dst = matches.value_of("r2-dst").unwrap();
+    let singletons_dst = matches.value_of("singletons");
+
+    let max_records_in_memory = matches
+        .value_of_t("max-records-in-memory")
+        .unwrap_or_else(|e| e.exit());
+
+    let tmp_dir = matches
+        .value_of("tmp-dir")
+        .map(PathBuf::from)
+        .unwrap_or_else(env::temp_dir);
+
+    info!("fq-repair start");
+
+    let metrics = Metrics::new();
+
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    let mut r2 = fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+    r1.set_metrics(metrics.clone());
+    r2.set_metrics(metrics.clone());
+
+    let mut w1 =
+        fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+    let mut w2 =
+        fastq::create(r2_dst).with_context(|| format!("Could not create file: {}", r2_dst))?;
+    w1.set_metrics(metrics.clone());
+    w2.set_metrics(metrics.clone());
+
+    let mut singletons = match singletons_dst {
+        Some(dst) => {
+            fastq::create(dst).with_context(|| format!("Could not create file: {}", dst))?
+        }
+        None => fastq::Writer::new(Box::new(io::sink()) as Box<dyn Write>),
+    };
+    singletons.set_metrics(metrics.clone());
+
+    let mut writers = Writers {
+        r1: w1,
+        r2: w2,
+        singletons,
+    };
+
+    let _stage = metrics.stage("repairing pairs");
+
+    let mut repairer = Repairer::new(max_records_in_memory, tmp_dir);
+
+    let counts = repair_pairs(&mut r1, &mut r2, &mut repairer, &mut writers)
+        .with_context(|| "Could not repair pairs")?;
+
+    info!(
+        "wrote {} pairs and {} singletons",
+        counts.pairs, counts.singletons
+    );
+
+    metrics.log();
+
+    info!("fq-repair end");
+
+    Ok(())
+}
+
+// Reads both sources in lockstep, so mates that are close to each other in their sources are
+// matched without holding many records.
+fn repair_pairs<R, W>(
+    r1: &mut fastq::Reader<R>,
+    r2: &mut fastq::Reader<R>,
+    repairer: &mut Repairer,
+    writers: &mut Writers<W>,
+) -> io::Result<Counts>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut readers = [r1, r2];
+    let mut is_eof = [false, false];
+    let mut record = Record::default();
+
+    while !is_eof.iter().all(|&b| b) {
+        for (mate, reader) in readers.iter_mut().enumerate() {
+            if is_eof[mate] {
+                continue;
+            }
+
+            if reader.read_record(&mut record)? == 0 {
+                is_eof[mate] = true;
+            } else {
+                repairer.push(mate, mem::take(&mut record), writers)?;
+            }
+        }
+    }
+
+    repairer.finish(writers)
+}
+
+/// A matcher of mates by name, regardless of their order in the sources.
+///
+/// Records wait in memory until their mate is read. When more than `max_pending` records are
+/// waiting, all waiting and remaining records are spilled to partition files by a hash of their
+/// name, and each pair of partitions is then matched separately.
+struct Repairer {
+    max_pending: usize,
+    tmp_dir: PathBuf,
+    id: usize,
+    seq: u64,
+    counts: Counts,
+    pending: [HashMap<Vec<u8>, (u64, Record)>; 2],
+    partitions: Vec<[PathBuf; 2]>,
+    partition_writers: Vec<[fastq::Writer<BufWriter<File>>; 2]>,
+}
+
+impl Repairer {
+    fn new(max_pending: usize, tmp_dir: PathBuf) -> Self {
+        Self {
+            max_pending,
+            tmp_dir,
+            id: REPAIRER_ID.fetch_add(1, atomic::Ordering::Relaxed),
+            seq: 0,
+            counts: Counts::default(),
+            pending: [HashMap::new(), HashMap::new()],
+            partitions: Vec::new(),
+            partition_writers: Vec::new(),
+        }
+    }
+
+    // Adds a record of read 1 (`mate` = 0) or read 2 (`mate` = 1).
+    fn push<W>(&mut self, mate: usize, record: Record, writers: &mut Writers<W>) -> io::Result<()>
+    where
+        W: Write,
+    {
+        if !self.partition_writers.is_empty() {
+            let i = partition(name_id(record.name()));
+            return self.partition_writers[i][mate].write_record(&record);
+        }
+
+        let id = name_id(record.name()).to_vec();
+
+        if let Some((_, other)) = self.pending[1 - mate].remove(&id) {
+            let (s1, s2) = if mate == 0 {
+                (&record, &other)
+            } else {
+                (&other, &record)
+            };
+
+            return self.write_pair(writers, s1, s2);
+        }
+
+        self.seq += 1;
+
+        if let Some((_, duplicate)) = self.pending[mate].insert(id, (self.seq, record)) {
+            self.write_singleton(writers, &duplicate)?;
+        }
+
+        if self.pending.iter().map(|p| p.len()).sum::<usize>() > self.max_pending {
+            self.spill()?;
+        }
+
+        Ok(())
+    }
+
+    fn write_pair<W>(
+        &mut self,
+        writers: &mut Writers<W>,
+        s1: &Record,
+        s2: &Record,
+    ) -> io::Result<()>
+    where
+        W: Write,
+    {
+        writers.r1.write_record(s1)?;
+        writers.r2.write_record(s2)?;
+        self.counts.pairs += 1;
+        Ok(())
+    }
+
+    fn write_singleton<W>(&mut self, writers: &mut Writers<W>, record: &Record) -> io::Result<()>
+    where
+        W: Write,
+    {
+        writers.singletons.write_record(record)?;
+        self.counts.singletons += 1;
+        Ok(())
+    }
+
+    // Moves all pending records to partition files. Later records are written directly to them.
+    fn spill(&mut self) -> io::Result<()> {
+        info!(
+            "spilling {} pending records to {} partitions",
+            self.pending.iter().map(|p| p.len()).sum::<usize>(),
+            PARTITION_COUNT
+        );
+
+        for i in 0..PARTITION_COUNT {
+            let paths = [1, 2].map(|mate| {
+                self.tmp_dir.join(format!(
+                    "fq-repair-{}-{}-{}_R{}.fastq",
+                    process::id(),
+                    self.id,
+                    i,
+                    mate
+                ))
+            });
+
+            self.partitions.push(paths.clone());
+
+            let [r1_dst, r2_dst] = paths;
+            let writers = [create_partition(r1_dst)?, create_partition(r2_dst)?];
+            self.partition_writers.push(writers);
+        }
+
+        for (mate, pending) in self.pending.iter_mut().enumerate() {
+            for (id, (_, record)) in pending.drain() {
+                self.partition_writers[partition(&id)][mate].write_record(&record)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Writes the remaining pairs and singletons.
+    fn finish<W>(&mut self, writers: &mut Writers<W>) -> io::Result<Counts>
+    where
+        W: Write,
+    {
+        if self.partitions.is_empty() {
+            self.write_pending_singletons(writers)?;
+            return Ok(mem::take(&mut self.counts));
+        }
+
+        // Flushes the partition files.
+        self.partition_writers.clear();
+
+        for [r1_src, r2_src] in self.partitions.clone() {
+            let mut reader = fastq::Reader::new(BufReader::new(File::open(r1_src)?));
+            let mut record = Record::default();
+
+            while reader.read_record(&mut record)? != 0 {
+                let id = name_id(record.name()).to_vec();
+                self.seq += 1;
+
+                if let Some((_, duplicate)) =
+                    self.pending[0].insert(id, (self.seq, mem::take(&mut record)))
+                {
+                    self.write_singleton(writers, &duplicate)?;
+                }
+            }
+
+            let mut reader = fastq::Reader::new(BufReader::new(File::open(r2_src)?));
+
+            while reader.read_record(&mut record)? != 0 {
+                match self.pending[0].remove(name_id(record.name())) {
+                    Some((_, s1)) => self.write_pair(writers, &s1, &record)?,
+                    None => self.write_singleton(writers, &record)?,
+                }
+            }
+
+            self.write_pending_singletons(writers)?;
+        }
+
+        Ok(mem::take(&mut self.counts))
+    }
+
+    // Writes all pending records as singletons, in input order.
+    fn write_pending_singletons<W>(&mut self, writers: &mut Writers<W>) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let mut records: Vec<_> = self
+            .pending
+            .iter_mut()
+            .flat_map(|pending| pending.drain().map(|(_, entry)| entry))
+            .collect();
+
+        records.sort_unstable_by_key(|(seq, _)| *seq);
+
+        for (_, record) in records {
+            self.write_singleton(writers, &record)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Repairer {
+    fn drop(&mut self) {
+        for src in self.partitions.iter().flatten() {
+            let _ = fs::remove_file(src);
+        }
+    }
+}
+
+fn partition(id: &[u8]) -> usize {
+    let mut hasher = DefaultHasher::new();
+    id.hash(&mut hasher);
+    (hasher.finish() % PARTITION_COUNT as u64) as usize
+}
+
+fn create_partition(dst: PathBuf) -> io::Result<fastq::Writer<BufWriter<File>>> {
+    File::create(dst).map(|file| fastq::Writer::new(BufWriter::new(file)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_writers() -> Writers<Vec<u8>> {
+        Writers {
+            r1: fastq::Writer::new(Vec::new()),
+            r2: fastq::Writer::new(Vec::new()),
+            singletons: fastq::Writer::new(Vec::new()),
+        }
+    }
+
+    static R1_DATA: &[u8] =
+        b"@r0/1\nAA\n+\nII\n@r1/1\nCC\n+\nII\n@r2/1\nGG\n+\nII\n@r4/1\nTT\n+\nII\n";
+    static R2_DATA: &[u8] = b"@r2/2\nGG\n+\nII\n@r3/2\nAC\n+\nII\n@r0/2\nAA\n+\nII\n";
+
+    #[test]
+    fn test_repair_pairs() -> io::Result<()> {
+        let mut r1 = fastq::Reader::new(R1_DATA);
+        let mut r2 = fastq::Reader::new(R2_DATA);
+        let mut repairer = Repairer::new(100, env::temp_dir());
+        let mut writers = build_writers();
+
+        let counts = repair_pairs(&mut r1, &mut r2, &mut repairer, &mut writers)?;
+
+        assert_eq!(
+            counts,
+            Counts {
+                pairs: 2,
+                singletons: 3
+            }
+        );
+
+        assert_eq!(
+            writers.r1.get_ref(),
+            b"@r2/1\nGG\n+\nII\n@r0/1\nAA\n+\nII\n"
+        );
+        assert_eq!(
+            writers.r2.get_ref(),
+            b"@r2/2\nGG\n+\nII\n@r0/2\nAA\n+\nII\n"
+        );
+        assert_eq!(
+            writers.singletons.get_ref(),
+            b"@r1/1\nCC\n+\nII\n@r3/2\nAC\n+\nII\n@r4/1\nTT\n+\nII\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_repair_pairs_with_spill() -> io::Result<()> {
+        let mut r1 = fastq::Reader::new(R1_DATA);
+        let mut r2 = fastq::Reader::new(R2_DATA);
+        let mut repairer = Repairer::new(1, env::temp_dir());
+        let mut writers = build_writers();
+
+        let counts = repair_pairs(&mut r1, &mut r2, &mut repairer, &mut writers)?;
+
+        assert_eq!(
+            counts,
+            Counts {
+                pairs: 2,
+                singletons: 3
+            }
+        );
+
+        let partitions = repairer.partitions.clone();
+        assert!(!partitions.is_empty());
+        drop(repairer);
+        assert!(partitions.iter().flatten().all(|src| !src.exists()));
+
+        let mut r1_names: Vec<_> = fastq_names(writers.r1.get_ref());
+        let mut r2_names: Vec<_> = fastq_names(writers.r2.get_ref());
+        r1_names.sort();
+        r2_names.sort();
+
+        assert_eq!(r1_names, [&b"@r0/1"[..], b"@r2/1"]);
+        assert_eq!(r2_names, [&b"@r0/2"[..], b"@r2/2"]);
+
+        // Mates are written in the same order.
+        assert_eq!(
+            fastq_names(writers.r1.get_ref())
+                .iter()
+                .map(|name| name_id(name))
+                .collect::<Vec<_>>(),
+            fastq_names(writers.r2.get_ref())
+                .iter()
+                .map(|name| name_id(name))
+                .collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    fn fastq_names(data: &[u8]) -> Vec<&[u8]> {
+        data.split(|&b| b == b'\n')
+            .step_by(4)
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+}
@@ -0,0 +1,173 @@
+use std::io::{self, BufRead, Write};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fastq::{self, Record},
+    metrics::Metrics,
+};
+
+pub fn revcomp(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r1_dst = matches.value_of("r1-dst").unwrap();
+
+    let r2_src = matches.value_of("r2-src");
+    let r2_dst = matches.value_of("r2-dst");
+
+    info!("fq-revcomp start");
+
+    let metrics = Metrics::new();
+
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    let mut w1 =
+        fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+    r1.set_metrics(metrics.clone());
+    w1.set_metrics(metrics.clone());
+
+    let _stage = metrics.stage("reverse complementing");
+
+    let n = match (r2_src, r2_dst) {
+        (Some(r2_src), Some(r2_dst)) => {
+            info!("reverse complementing paired end reads");
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            let mut w2 = fastq::create(r2_dst)
+                .with_context(|| format!("Could not create file: {}", r2_dst))?;
+            r2.set_metrics(metrics.clone());
+            w2.set_metrics(metrics.clone());
+
+            revcomp_paired((&mut r1, &mut w1), (&mut r2, &mut w2))?
+        }
+        (Some(r2_src), None) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-dst for {}", r2_src));
+        }
+        (None, Some(r2_dst)) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-src for {}", r2_dst));
+        }
+        (None, None) => {
+            info!("reverse complementing single end reads");
+            revcomp_single(&mut r1, &mut w1)?
+        }
+    };
+
+    info!("reverse complemented {} records", n);
+
+    metrics.log();
+
+    info!("fq-revcomp end");
+
+    Ok(())
+}
+
+fn revcomp_single<R, W>(
+    reader: &mut fastq::Reader<R>,
+    writer: &mut fastq::Writer<W>,
+) -> anyhow::Result<u64>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut record = Record::default();
+    let mut n = 0;
+
+    while reader.read_record(&mut record)? != 0 {
+        record.reverse_complement();
+        writer.write_record(&record)?;
+        n += 1;
+    }
+
+    Ok(n)
+}
+
+fn revcomp_paired<R, S, W, X>(
+    (r1, w1): (&mut fastq::Reader<R>, &mut fastq::Writer<W>),
+    (r2, w2): (&mut fastq::Reader<S>, &mut fastq::Writer<X>),
+) -> anyhow::Result<u64>
+where
+    R: BufRead,
+    S: BufRead,
+    W: Write,
+    X: Write,
+{
+    let mut s1 = Record::default();
+    let mut s2 = Record::default();
+
+    let mut n = 0;
+
+    loop {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, _) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r1-src unexpectedly ended before r2-src");
+            }
+            (_, 0) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r2-src unexpectedly ended before r1-src");
+            }
+            (_, _) => {
+                s1.reverse_complement();
+                s2.reverse_complement();
+
+                w1.write_record(&s1)?;
+                w2.write_record(&s2)?;
+
+                n += 2;
+            }
+        }
+    }
+
+    Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revcomp_single() -> anyhow::Result<()> {
+        let data = b"@r0\nAACG\n+\nABCD\n@r1\nRYKN\n+\nEFGH\n";
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+        let n = revcomp_single(&mut reader, &mut writer)?;
+
+        assert_eq!(n, 2);
+        assert_eq!(
+            writer.get_ref(),
+            b"@r0\nCGTT\n+\nDCBA\n@r1\nNMRY\n+\nHGFE\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_revcomp_paired() -> anyhow::Result<()> {
+        let r1_data = b"@r0/1\nAACC\n+\nABCD\n";
+        let r2_data = b"@r0/2\nGGTT\n+\nEFGH\n";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut w2 = fastq::Writer::new(Vec::new());
+
+        let n = revcomp_paired((&mut r1, &mut w1), (&mut r2, &mut w2))?;
+
+        assert_eq!(n, 2);
+        assert_eq!(w1.get_ref(), b"@r0/1\nGGTT\n+\nDCBA\n");
+        assert_eq!(w2.get_ref(), b"@r0/2\nAACC\n+\nHGFE\n");
+
+        let mut r1 = fastq::Reader::new(&b""[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut w2 = fastq::Writer::new(Vec::new());
+        assert!(revcomp_paired((&mut r1, &mut w1), (&mut r2, &mut w2)).is_err());
+
+        Ok(())
+    }
+}
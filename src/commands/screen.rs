@@ -0,0 +1,434 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fasta,
+    fastq::{self, Record},
+    metrics::Metrics,
+    screen::{self, KmerSet, Screener},
+};
+
+const FLAG_PREFIX: &[u8] = b" screen=";
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Counts {
+    records: u64,
+    contaminated: u64,
+    // Records that hit each k-mer set.
+    hits: Vec<u64>,
+}
+
+pub fn screen(matches: &ArgMatches) -> anyhow::Result<()> {
+    if let Some(m) = matches.subcommand_matches("build") {
+        build(m)
+    } else {
+        screen_reads(matches)
+    }
+}
+
+fn build(matches: &ArgMatches) -> anyhow::Result<()> {
+    let srcs: Vec<&str> = matches.values_of("src").unwrap().collect();
+    let dst = matches.value_of("dst").unwrap();
+
+    let kmer_size = matches.value_of_t("kmer-size").unwrap_or_else(|e| e.exit());
+
+    let name = match matches.value_of("name") {
+        Some(name) => name.into(),
+        None => Path::new(dst)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+
+    info!("fq-screen-build start");
+
+    let mut kmer_set = KmerSet::new(name, kmer_size)?;
+    let mut record = fasta::Record::default();
+
+    for src in srcs {
+        let mut reader =
+            fasta::open(src).with_context(|| format!("Could not open file: {}", src))?;
+
+        while reader.read_record(&mut record)? != 0 {
+            kmer_set.add_sequence(record.sequence());
+        }
+    }
+
+    info!(
+        "built k-mer set '{}' with {} {}-mers",
+        kmer_set.name(),
+        kmer_set.len(),
+        kmer_set.kmer_size()
+    );
+
+    screen::write_file(dst, &kmer_set).with_context(|| format!("Could not write file: {}", dst))?;
+
+    info!("fq-screen-build end");
+
+    Ok(())
+}
+
+fn screen_reads(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r1_dst = matches.value_of("r1-dst").unwrap();
+
+    let r2_src = matches.value_of("r2-src");
+    let r2_dst = matches.value_of("r2-dst");
+
+    let min_hits = matches.value_of_t("min-hits").unwrap_or_else(|e| e.exit());
+    let flag = matches.is_present("flag");
+    let report_dst = matches.value_of("report");
+
+    if min_hits == 0 {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| "min hits must be > 0");
+    }
+
+    info!("fq-screen start");
+
+    let kmer_sets = matches
+        .values_of("kmer-set")
+        .unwrap()
+        .map(|src| screen::read_file(src).with_context(|| format!("Could not read file: {}", src)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    for kmer_set in &kmer_sets {
+        info!(
+            "loaded k-mer set '{}' with {} {}-mers",
+            kmer_set.name(),
+            kmer_set.len(),
+            kmer_set.kmer_size()
+        );
+    }
+
+    let screener = Screener::new(kmer_sets, min_hits);
+
+    let metrics = Metrics::new();
+
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    let mut w1 =
+        fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+    r1.set_metrics(metrics.clone());
+    w1.set_metrics(metrics.clone());
+
+    let _stage = metrics.stage("screening");
+
+    let counts = match (r2_src, r2_dst) {
+        (Some(r2_src), Some(r2_dst)) => {
+            info!("screening paired end reads");
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            let mut w2 = fastq::create(r2_dst)
+                .with_context(|| format!("Could not create file: {}", r2_dst))?;
+            r2.set_metrics(metrics.clone());
+            w2.set_metrics(metrics.clone());
+
+            screen_paired(&screener, flag, (&mut r1, &mut w1), (&mut r2, &mut w2))?
+        }
+        (Some(r2_src), None) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-dst for {}", r2_src));
+        }
+        (None, Some(r2_dst)) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-src for {}", r2_dst));
+        }
+        (None, None) => {
+            info!("screening single end reads");
+            screen_single(&screener, flag, &mut r1, &mut w1)?
+        }
+    };
+
+    for (kmer_set, &hits) in screener.kmer_sets().iter().zip(&counts.hits) {
+        info!(
+            "{}: {}/{} ({:.2}%) records hit",
+            kmer_set.name(),
+            hits,
+            counts.records,
+            hit_rate(hits, counts.records) * 100.0
+        );
+    }
+
+    if flag {
+        info!("flagged {} records", counts.contaminated);
+    } else {
+        info!("removed {} records", counts.contaminated);
+    }
+
+    if let Some(dst) = report_dst {
+        let mut writer = File::create(dst)
+            .map(BufWriter::new)
+            .with_context(|| format!("Could not create file: {}", dst))?;
+
+        write_report(&mut writer, &screener, &counts)
+            .with_context(|| format!("Could not write file: {}", dst))?;
+    }
+
+    metrics.log();
+
+    info!("fq-screen end");
+
+    Ok(())
+}
+
+fn hit_rate(hits: u64, records: u64) -> f64 {
+    if records == 0 {
+        0.0
+    } else {
+        (hits as f64) / (records as f64)
+    }
+}
+
+// Appends the names of the hit references to the record name, e.g., ` screen=phix,host`.
+fn flag_record(screener: &Screener, record: &mut Record, matched: &[usize]) {
+    let name = record.name_mut();
+    name.extend_from_slice(FLAG_PREFIX);
+
+    for (i, &j) in matched.iter().enumerate() {
+        if i > 0 {
+            name.push(b',');
+        }
+
+        name.extend_from_slice(screener.kmer_sets()[j].name().as_bytes());
+    }
+}
+
+fn count_matched(counts: &mut Counts, matched: &[usize]) {
+    for &i in matched {
+        counts.hits[i] += 1;
+    }
+
+    if !matched.is_empty() {
+        counts.contaminated += 1;
+    }
+
+    counts.records += 1;
+}
+
+// Contaminated reads are removed or, if `flag` is set, kept and flagged.
+fn screen_single<R, W>(
+    screener: &Screener,
+    flag: bool,
+    reader: &mut fastq::Reader<R>,
+    writer: &mut fastq::Writer<W>,
+) -> anyhow::Result<Counts>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut record = Record::default();
+    let mut matched = Vec::new();
+
+    let mut counts = Counts {
+        hits: vec![0; screener.kmer_sets().len()],
+        ..Default::default()
+    };
+
+    while reader.read_record(&mut record)? != 0 {
+        matched.clear();
+        matched.extend(screener.screen(record.sequence()));
+
+        count_matched(&mut counts, &matched);
+
+        if matched.is_empty() {
+            writer.write_record(&record)?;
+        } else if flag {
+            flag_record(screener, &mut record, &matched);
+            writer.write_record(&record)?;
+        }
+    }
+
+    Ok(counts)
+}
+
+// A pair hits a reference if either mate hits it.
+fn screen_paired<R, S, W, X>(
+    screener: &Screener,
+    flag: bool,
+    (r1, w1): (&mut fastq::Reader<R>, &mut fastq::Writer<W>),
+    (r2, w2): (&mut fastq::Reader<S>, &mut fastq::Writer<X>),
+) -> anyhow::Result<Counts>
+where
+    R: BufRead,
+    S: BufRead,
+    W: Write,
+    X: Write,
+{
+    let mut s1 = Record::default();
+    let mut s2 = Record::default();
+    let mut matched = Vec::new();
+
+    let mut counts = Counts {
+        hits: vec![0; screener.kmer_sets().len()],
+        ..Default::default()
+    };
+
+    loop {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, _) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r1-src unexpectedly ended before r2-src");
+            }
+            (_, 0) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r2-src unexpectedly ended before r1-src");
+            }
+            (_, _) => {
+                matched.clear();
+                matched.extend(screener.screen(s1.sequence()));
+
+                for i in screener.screen(s2.sequence()) {
+                    if !matched.contains(&i) {
+                        matched.push(i);
+                    }
+                }
+
+                matched.sort_unstable();
+
+                count_matched(&mut counts, &matched);
+
+                if matched.is_empty() {
+                    w1.write_record(&s1)?;
+                    w2.write_record(&s2)?;
+                } else if flag {
+                    flag_record(screener, &mut s1, &matched);
+                    flag_record(screener, &mut s2, &matched);
+                    w1.write_record(&s1)?;
+                    w2.write_record(&s2)?;
+                }
+            }
+        }
+    }
+
+    Ok(counts)
+}
+
+fn write_report<W>(writer: &mut W, screener: &Screener, counts: &Counts) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(
+        writer,
+        "reference\tkmer_size\tkmers\trecords\thits\thit_rate"
+    )?;
+
+    for (kmer_set, &hits) in screener.kmer_sets().iter().zip(&counts.hits) {
+        writeln!(
+            writer,
+            "{}\t{}\t{}\t{}\t{}\t{:.6}",
+            kmer_set.name(),
+            kmer_set.kmer_size(),
+            kmer_set.len(),
+            counts.records,
+            hits,
+            hit_rate(hits, counts.records)
+        )?;
+    }
+
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_screener() -> io::Result<Screener> {
+        let mut phix = KmerSet::new("phix", 5)?;
+        phix.add_sequence(b"GAGTTTTATCGCTTCCATGA");
+
+        let mut adapters = KmerSet::new("adapters", 5)?;
+        adapters.add_sequence(b"AGATCGGAAGAGC");
+
+        Ok(Screener::new(vec![phix, adapters], 2))
+    }
+
+    #[test]
+    fn test_screen_single() -> anyhow::Result<()> {
+        let screener = build_screener()?;
+
+        let data = b"@r0\nTTTTATCGCT\n+\nIIIIIIIIII\n@r1\nACACACACAC\n+\nIIIIIIIIII\n@r2\nGCTCTTCCGATCT\n+\nIIIIIIIIIIIII\n";
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+        let counts = screen_single(&screener, false, &mut reader, &mut writer)?;
+
+        assert_eq!(
+            counts,
+            Counts {
+                records: 3,
+                contaminated: 2,
+                hits: vec![1, 1],
+            }
+        );
+        assert_eq!(writer.get_ref(), b"@r1\nACACACACAC\n+\nIIIIIIIIII\n");
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+        screen_single(&screener, true, &mut reader, &mut writer)?;
+
+        assert!(writer.get_ref().starts_with(b"@r0 screen=phix\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_screen_paired() -> anyhow::Result<()> {
+        let screener = build_screener()?;
+
+        let r1_data = b"@r0/1\nTTTTATCGCT\n+\nIIIIIIIIII\n@r1/1\nACACACACAC\n+\nIIIIIIIIII\n";
+        let r2_data = b"@r0/2\nAGATCGGAAG\n+\nIIIIIIIIII\n@r1/2\nACACACACAC\n+\nIIIIIIIIII\n";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut w2 = fastq::Writer::new(Vec::new());
+
+        let counts = screen_paired(&screener, true, (&mut r1, &mut w1), (&mut r2, &mut w2))?;
+
+        assert_eq!(
+            counts,
+            Counts {
+                records: 2,
+                contaminated: 1,
+                hits: vec![1, 1],
+            }
+        );
+
+        assert!(w1.get_ref().starts_with(b"@r0/1 screen=phix,adapters\n"));
+        assert!(w2.get_ref().starts_with(b"@r0/2 screen=phix,adapters\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_report() -> io::Result<()> {
+        let screener = build_screener()?;
+
+        let counts = Counts {
+            records: 4,
+            contaminated: 1,
+            hits: vec![1, 0],
+        };
+
+        let mut buf = Vec::new();
+        write_report(&mut buf, &screener, &counts)?;
+
+        let expected = "reference\tkmer_size\tkmers\trecords\thits\thit_rate
+phix\t5\t16\t4\t1\t0.250000
+adapters\t5\t9\t4\t0\t0.000000
+";
+
+        assert_eq!(String::from_utf8(buf).unwrap(), expected);
+
+        Ok(())
+    }
+}
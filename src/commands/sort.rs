@@ -0,0 +1,444 @@
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    env,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter},
+    path::PathBuf,
+    process,
+    sync::atomic::{self, AtomicUsize},
+};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fastq::{self, Record},
+    metrics::Metrics,
+    sort::SortKey,
+};
+
+// Distinguishes the chunk files of sorters in the same process.
+static SORTER_ID: AtomicUsize = AtomicUsize::new(0);
+
+pub fn sort(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r1_dst = matches.value_of("r1-dst").unwrap();
+
+    let r2_src = matches.value_of("r2-src");
+    let r2_dst = matches.value_of("r2-dst");
+
+    let key = matches.value_of_t("by").unwrap_or_else(|e| e.exit());
+
+    let max_records_in_memory = matches
+        .value_of_t("max-records-in-memory")
+        .unwrap_or_else(|e| e.exit());
+
+    if max_records_in_memory == 0 {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| "--max-records-in-memory must be > 0");
+    }
+
+    let tmp_dir = matches
+        .value_of("tmp-dir")
+        .map(PathBuf::from)
+        .unwrap_or_else(env::temp_dir);
+
+    info!("fq-sort start");
+
+    let metrics = Metrics::new();
+
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    r1.set_metrics(metrics.clone());
+
+    let mut w1 =
+        fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+    w1.set_metrics(metrics.clone());
+
+    match (r2_src, r2_dst) {
+        (Some(r2_src), Some(r2_dst)) => {
+            info!("sorting paired end reads");
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            r2.set_metrics(metrics.clone());
+
+            let mut w2 = fastq::create(r2_dst)
+                .with_context(|| format!("Could not create file: {}", r2_dst))?;
+            w2.set_metrics(metrics.clone());
+
+            let mut sorter = Sorter::new(key, 2, max_records_in_memory, tmp_dir);
+
+            let stage = metrics.stage("reading records");
+            read_paired(&mut r1, &mut r2, &mut sorter)?;
+            drop(stage);
+
+            let _stage = metrics.stage("merging records");
+
+            sorter
+                .finish(|template| {
+                    w1.write_record(&template[0])?;
+                    w2.write_record(&template[1])
+                })
+                .with_context(|| "Could not sort records")?;
+        }
+        (Some(r2_src), None) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-dst for {}", r2_src));
+        }
+        (None, Some(r2_dst)) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-src for {}", r2_dst));
+        }
+        (None, None) => {
+            info!("sorting single end reads");
+
+            let mut sorter = Sorter::new(key, 1, max_records_in_memory, tmp_dir);
+
+            let stage = metrics.stage("reading records");
+            read_single(&mut r1, &mut sorter)
+                .with_context(|| format!("Could not read file: {}", r1_src))?;
+            drop(stage);
+
+            let _stage = metrics.stage("merging records");
+
+            sorter
+                .finish(|template| w1.write_record(&template[0]))
+                .with_context(|| "Could not sort records")?;
+        }
+    }
+
+    metrics.log();
+
+    info!("fq-sort end");
+
+    Ok(())
+}
+
+// A template is the list of records from the same molecule, i.e., one record for single end reads
+// or both mates for paired end reads.
+type Template = Vec<Record>;
+
+fn read_single<R>(reader: &mut fastq::Reader<R>, sorter: &mut Sorter) -> io::Result<()>
+where
+    R: BufRead,
+{
+    let mut record = Record::default();
+
+    while reader.read_record(&mut record)? != 0 {
+        sorter.push(vec![record.clone()])?;
+    }
+
+    Ok(())
+}
+
+fn read_paired<R, S>(
+    r1: &mut fastq::Reader<R>,
+    r2: &mut fastq::Reader<S>,
+    sorter: &mut Sorter,
+) -> anyhow::Result<()>
+where
+    R: BufRead,
+    S: BufRead,
+{
+    let mut s1 = Record::default();
+    let mut s2 = Record::default();
+
+    loop {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, _) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r1-src unexpectedly ended before r2-src");
+            }
+            (_, 0) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r2-src unexpectedly ended before r1-src");
+            }
+            (_, _) => sorter.push(vec![s1.clone(), s2.clone()])?,
+        }
+    }
+
+    Ok(())
+}
+
+/// An external merge sorter of templates.
+///
+/// Up to `max_templates` templates are held in memory. When full, they are sorted and spilled to
+/// a chunk file in the temporary directory. The chunks are then merged when finishing. Sorting is
+/// stable, i.e., templates with equal keys keep their input order.
+struct Sorter {
+    key: SortKey,
+    mate_count: usize,
+    max_templates: usize,
+    tmp_dir: PathBuf,
+    id: usize,
+    templates: Vec<Template>,
+    chunks: Vec<PathBuf>,
+}
+
+impl Sorter {
+    fn new(key: SortKey, mate_count: usize, max_templates: usize, tmp_dir: PathBuf) -> Self {
+        Self {
+            key,
+            mate_count,
+            max_templates,
+            tmp_dir,
+            id: SORTER_ID.fetch_add(1, atomic::Ordering::Relaxed),
+            templates: Vec::new(),
+            chunks: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, template: Template) -> io::Result<()> {
+        self.templates.push(template);
+
+        if self.templates.len() >= self.max_templates {
+            self.spill()?;
+        }
+
+        Ok(())
+    }
+
+    fn sort_templates(&mut self) {
+        let key = self.key;
+        self.templates.sort_by(|a, b| key.compare(a, b));
+    }
+
+    // Sorts the templates in memory and writes them, interleaved, to a new chunk file.
+    fn spill(&mut self) -> io::Result<()> {
+        self.sort_templates();
+
+        let dst = self.tmp_dir.join(format!(
+            "fq-sort-{}-{}-{}.fastq",
+            process::id(),
+            self.id,
+            self.chunks.len()
+        ));
+
+        let file = File::create(&dst)?;
+        self.chunks.push(dst);
+
+        let mut writer = fastq::Writer::new(BufWriter::new(file));
+
+        for template in self.templates.drain(..) {
+            for record in &template {
+                writer.write_record(record)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Calls `write_template` for each template in sorted order.
+    fn finish<F>(mut self, mut write_template: F) -> io::Result<()>
+    where
+        F: FnMut(&[Record]) -> io::Result<()>,
+    {
+        if self.chunks.is_empty() {
+            self.sort_templates();
+
+            for template in &self.templates {
+                write_template(template)?;
+            }
+
+            return Ok(());
+        }
+
+        if !self.templates.is_empty() {
+            self.spill()?;
+        }
+
+        info!("merging {} chunks", self.chunks.len());
+
+        let mut readers = Vec::with_capacity(self.chunks.len());
+
+        for src in &self.chunks {
+            let file = File::open(src)?;
+            readers.push(fastq::Reader::new(BufReader::new(file)));
+        }
+
+        let mut heap = BinaryHeap::with_capacity(readers.len());
+
+        for (chunk, reader) in readers.iter_mut().enumerate() {
+            if let Some(template) = read_template(reader, self.mate_count)? {
+                heap.push(Entry::new(self.key, template, chunk));
+            }
+        }
+
+        while let Some(mut entry) = heap.pop() {
+            write_template(&entry.template)?;
+
+            if read_template_into(&mut readers[entry.chunk], &mut entry.template)? {
+                heap.push(entry);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Sorter {
+    fn drop(&mut self) {
+        for src in &self.chunks {
+            let _ = fs::remove_file(src);
+        }
+    }
+}
+
+fn read_template<R>(
+    reader: &mut fastq::Reader<R>,
+    mate_count: usize,
+) -> io::Result<Option<Template>>
+where
+    R: BufRead,
+{
+    let mut template = vec![Record::default(); mate_count];
+
+    if read_template_into(reader, &mut template)? {
+        Ok(Some(template))
+    } else {
+        Ok(None)
+    }
+}
+
+// Reads the next template from a chunk into an existing template, returning false at EOF.
+fn read_template_into<R>(reader: &mut fastq::Reader<R>, template: &mut [Record]) -> io::Result<bool>
+where
+    R: BufRead,
+{
+    for (i, record) in template.iter_mut().enumerate() {
+        if reader.read_record(record)? == 0 {
+            if i == 0 {
+                return Ok(false);
+            }
+
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "incomplete template in sort chunk",
+            ));
+        }
+    }
+
+    Ok(true)
+}
+
+// A heap entry of the next template of a chunk.
+//
+// The order is reversed, so that `BinaryHeap`, a max-heap, pops the smallest template first. Ties
+// pop from the earliest chunk to keep the sort stable.
+struct Entry {
+    key: SortKey,
+    template: Template,
+    chunk: usize,
+}
+
+impl Entry {
+    fn new(key: SortKey, template: Template, chunk: usize) -> Self {
+        Self {
+            key,
+            template,
+            chunk,
+        }
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key
+            .compare(&other.template, &self.template)
+            .then_with(|| other.chunk.cmp(&self.chunk))
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sort_names(key: SortKey, max_templates: usize, data: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+        let mut reader = fastq::Reader::new(data);
+        let mut sorter = Sorter::new(key, 1, max_templates, env::temp_dir());
+        read_single(&mut reader, &mut sorter)?;
+
+        let chunks = sorter.chunks.clone();
+        let mut names = Vec::new();
+
+        sorter.finish(|template| {
+            names.push(template[0].name().to_vec());
+            Ok(())
+        })?;
+
+        assert!(chunks.iter().all(|src| !src.exists()));
+
+        Ok(names)
+    }
+
+    #[test]
+    fn test_sort() -> io::Result<()> {
+        let data = b"@r10\nAAAA\n+\nIIII
+@r2\nTTTT\n+\nIIII
+@r1\nGGGG\n+\nIIII
+@r3\nAAAA\n+\nIIII
+@r20\nCCCC\n+\nIIII
+";
+
+        let expected = [&b"@r1"[..], b"@r10", b"@r2", b"@r20", b"@r3"];
+        assert_eq!(sort_names(SortKey::Name, 100, data)?, expected);
+        assert_eq!(sort_names(SortKey::Name, 2, data)?, expected);
+
+        let expected = [&b"@r1"[..], b"@r2", b"@r3", b"@r10", b"@r20"];
+        assert_eq!(sort_names(SortKey::NaturalName, 2, data)?, expected);
+
+        // Ties keep their input order across chunks.
+        let expected = [&b"@r10"[..], b"@r3", b"@r20", b"@r1", b"@r2"];
+        assert_eq!(sort_names(SortKey::Sequence, 1, data)?, expected);
+        assert_eq!(sort_names(SortKey::Sequence, 2, data)?, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_paired() -> anyhow::Result<()> {
+        let r1_data = b"@r1/1\nAC\n+\nFQ\n@r0/1\nGT\n+\nLI\n@r2/1\nAA\n+\nBF\n";
+        let r2_data = b"@r1/2\nTT\n+\nFQ\n@r0/2\nCC\n+\nLI\n@r2/2\nGG\n+\nBF\n";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut sorter = Sorter::new(SortKey::Name, 2, 2, env::temp_dir());
+        read_paired(&mut r1, &mut r2, &mut sorter)?;
+
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut w2 = fastq::Writer::new(Vec::new());
+
+        sorter.finish(|template| {
+            w1.write_record(&template[0])?;
+            w2.write_record(&template[1])
+        })?;
+
+        assert_eq!(
+            w1.get_ref(),
+            b"@r0/1\nGT\n+\nLI\n@r1/1\nAC\n+\nFQ\n@r2/1\nAA\n+\nBF\n"
+        );
+        assert_eq!(
+            w2.get_ref(),
+            b"@r0/2\nCC\n+\nLI\n@r1/2\nTT\n+\nFQ\n@r2/2\nGG\n+\nBF\n"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+use std::io::{self, BufRead, Write};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fastq::{self, Record},
+    metrics::Metrics,
+};
+
+pub fn split(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r2_src = matches.value_of("r2-src");
+
+    let prefix = matches.value_of("prefix").unwrap();
+    let suffix = matches.value_of("suffix").unwrap();
+
+    let records_per_file = matches
+        .value_of_t("records-per-file")
+        .unwrap_or_else(|e| e.exit());
+
+    if records_per_file == 0 {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| "--records-per-file must be > 0");
+    }
+
+    info!("fq-split start");
+
+    let metrics = Metrics::new();
+
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    r1.set_metrics(metrics.clone());
+
+    let _stage = metrics.stage("splitting records");
+
+    let (chunk_count, record_count) = if let Some(r2_src) = r2_src {
+        info!("splitting paired end reads");
+
+        let mut r2 =
+            fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+        r2.set_metrics(metrics.clone());
+
+        split_paired(&mut r1, &mut r2, records_per_file, |i| {
+            let w1 = create(&metrics, &chunk_path(prefix, i, Some(1), suffix))?;
+            let w2 = create(&metrics, &chunk_path(prefix, i, Some(2), suffix))?;
+            Ok((w1, w2))
+        })?
+    } else {
+        info!("splitting single end reads");
+
+        split_single(&mut r1, records_per_file, |i| {
+            create(&metrics, &chunk_path(prefix, i, None, suffix))
+        })?
+    };
+
+    info!("split {} records into {} chunks", record_count, chunk_count);
+
+    metrics.log();
+
+    info!("fq-split end");
+
+    Ok(())
+}
+
+fn create(metrics: &Metrics, dst: &str) -> anyhow::Result<fastq::Writer<Box<dyn Write>>> {
+    let mut writer =
+        fastq::create(dst).with_context(|| format!("Could not create file: {}", dst))?;
+    writer.set_metrics(metrics.clone());
+    Ok(writer)
+}
+
+// Builds the destination of a chunk, e.g., `chunk_0001_R1.fastq.gz`.
+fn chunk_path(prefix: &str, i: usize, mate: Option<u8>, suffix: &str) -> String {
+    match mate {
+        Some(mate) => format!("{}{:04}_R{}{}", prefix, i, mate, suffix),
+        None => format!("{}{:04}{}", prefix, i, suffix),
+    }
+}
+
+// Writes chunks of `records_per_file` records, creating a writer for each chunk when its first
+// record is read. This returns the number of chunks and records written.
+fn split_single<R, W, F>(
+    reader: &mut fastq::Reader<R>,
+    records_per_file: u64,
+    mut create_writer: F,
+) -> anyhow::Result<(usize, u64)>
+where
+    R: BufRead,
+    W: Write,
+    F: FnMut(usize) -> anyhow::Result<fastq::Writer<W>>,
+{
+    let mut record = Record::default();
+    let mut writer = None;
+
+    let mut chunk_count = 0;
+    let mut n = 0;
+
+    while reader.read_record(&mut record)? != 0 {
+        if n % records_per_file == 0 {
+            writer = Some(create_writer(chunk_count)?);
+            chunk_count += 1;
+        }
+
+        if let Some(writer) = writer.as_mut() {
+            writer.write_record(&record)?;
+        }
+
+        n += 1;
+    }
+
+    Ok((chunk_count, n))
+}
+
+fn split_paired<R, S, W, F>(
+    r1: &mut fastq::Reader<R>,
+    r2: &mut fastq::Reader<S>,
+    records_per_file: u64,
+    mut create_writers: F,
+) -> anyhow::Result<(usize, u64)>
+where
+    R: BufRead,
+    S: BufRead,
+    W: Write,
+    F: FnMut(usize) -> anyhow::Result<(fastq::Writer<W>, fastq::Writer<W>)>,
+{
+    let mut s1 = Record::default();
+    let mut s2 = Record::default();
+    let mut writers = None;
+
+    let mut chunk_count = 0;
+    let mut n = 0;
+
+    loop {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, _) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r1-src unexpectedly ended before r2-src");
+            }
+            (_, 0) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r2-src unexpectedly ended before r1-src");
+            }
+            (_, _) => {
+                if n % records_per_file == 0 {
+                    writers = Some(create_writers(chunk_count)?);
+                    chunk_count += 1;
+                }
+
+                if let Some((w1, w2)) = writers.as_mut() {
+                    w1.write_record(&s1)?;
+                    w2.write_record(&s2)?;
+                }
+
+                n += 1;
+            }
+        }
+    }
+
+    Ok((chunk_count, n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_path() {
+        assert_eq!(chunk_path("chunk_", 1, None, ".fastq"), "chunk_0001.fastq");
+        assert_eq!(
+            chunk_path("chunk_", 12, Some(2), ".fastq.gz"),
+            "chunk_0012_R2.fastq.gz"
+        );
+    }
+
+    #[test]
+    fn test_split_single() -> anyhow::Result<()> {
+        let data = b"@r0\nAC\n+\nFQ\n@r1\nGT\n+\nLI\n@r2\nAA\n+\nBF\n";
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut bufs: Vec<Vec<u8>> = vec![Vec::new(); 3];
+        let mut dsts = bufs.iter_mut();
+
+        let counts = split_single(&mut reader, 2, |_| {
+            Ok(fastq::Writer::new(dsts.next().unwrap()))
+        })?;
+
+        assert_eq!(counts, (2, 3));
+        assert_eq!(bufs[0], b"@r0\nAC\n+\nFQ\n@r1\nGT\n+\nLI\n");
+        assert_eq!(bufs[1], b"@r2\nAA\n+\nBF\n");
+        assert!(bufs[2].is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_paired() -> anyhow::Result<()> {
+        let r1_data = b"@r0/1\nAC\n+\nFQ\n@r1/1\nGT\n+\nLI\n";
+        let r2_data = b"@r0/2\nTT\n+\nFQ\n@r1/2\nCC\n+\nLI\n";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut r1_bufs: Vec<Vec<u8>> = vec![Vec::new(); 2];
+        let mut r2_bufs: Vec<Vec<u8>> = vec![Vec::new(); 2];
+        let mut r1_dsts = r1_bufs.iter_mut();
+        let mut r2_dsts = r2_bufs.iter_mut();
+
+        let counts = split_paired(&mut r1, &mut r2, 1, |_| {
+            Ok((
+                fastq::Writer::new(r1_dsts.next().unwrap()),
+                fastq::Writer::new(r2_dsts.next().unwrap()),
+            ))
+        })?;
+
+        assert_eq!(counts, (2, 2));
+        assert_eq!(r1_bufs[1], b"@r1/1\nGT\n+\nLI\n");
+        assert_eq!(r2_bufs[1], b"@r1/2\nCC\n+\nLI\n");
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..14]);
+        let mut bufs: Vec<Vec<u8>> = vec![Vec::new(); 4];
+        let mut dsts = bufs.iter_mut();
+
+        assert!(split_paired(&mut r1, &mut r2, 1, |_| {
+            Ok((
+                fastq::Writer::new(dsts.next().unwrap()),
+                fastq::Writer::new(dsts.next().unwrap()),
+            ))
+        })
+        .is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+use std::io::{self, BufRead, BufWriter, Write};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fastq::{self, Record},
+    metrics::Metrics,
+    stats::{default_adapters, Adapter, AdapterContent, LengthStats},
+};
+
+const NX_VALUES: [u8; 2] = [50, 90];
+
+pub fn stats(matches: &ArgMatches) -> anyhow::Result<()> {
+    let src = matches.value_of("src").unwrap();
+
+    let length_thresholds: Vec<usize> = matches
+        .values_of_t("length-threshold")
+        .unwrap_or_else(|e| e.exit());
+
+    let longest_count = matches
+        .value_of_t("longest-count")
+        .unwrap_or_else(|e| e.exit());
+
+    let adapters = match matches.values_of("adapter") {
+        Some(sequences) => sequences
+            .map(|s| Adapter::new(s, s.to_ascii_uppercase()))
+            .collect(),
+        None => default_adapters(),
+    };
+
+    info!("fq-stats start");
+
+    let metrics = Metrics::new();
+
+    let mut reader = fastq::open(src).with_context(|| format!("Could not open file: {}", src))?;
+    reader.set_metrics(metrics.clone());
+
+    let stage = metrics.stage("reading records");
+    let (stats, adapter_content) = read_stats(&mut reader, longest_count, adapters)
+        .with_context(|| format!("Could not read file: {}", src))?;
+    drop(stage);
+
+    info!("read {} records", stats.record_count());
+
+    let stdout = io::stdout();
+    let mut writer = BufWriter::new(stdout.lock());
+
+    write_length_stats(&mut writer, &stats, &length_thresholds)
+        .and_then(|_| write_adapter_content(&mut writer, &adapter_content))
+        .context("Could not write stats to stdout")?;
+
+    metrics.log();
+
+    info!("fq-stats end");
+
+    Ok(())
+}
+
+fn read_stats<R>(
+    reader: &mut fastq::Reader<R>,
+    longest_count: usize,
+    adapters: Vec<Adapter>,
+) -> io::Result<(LengthStats, AdapterContent)>
+where
+    R: BufRead,
+{
+    let mut record = Record::default();
+    let mut stats = LengthStats::new(longest_count);
+    let mut adapter_content = AdapterContent::new(adapters);
+
+    while reader.read_record(&mut record)? != 0 {
+        stats.add(&record);
+        adapter_content.add(&record);
+    }
+
+    Ok((stats, adapter_content))
+}
+
+fn write_length_stats<W>(
+    writer: &mut W,
+    stats: &LengthStats,
+    length_thresholds: &[usize],
+) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(writer, "record_count\t{}", stats.record_count())?;
+    writeln!(writer, "base_count\t{}", stats.base_count())?;
+    writeln!(writer, "max_length\t{}", stats.max_length().unwrap_or(0))?;
+
+    for &x in &NX_VALUES {
+        writeln!(writer, "n{}\t{}", x, stats.nx(x).unwrap_or(0))?;
+    }
+
+    for &min_len in length_thresholds {
+        writeln!(
+            writer,
+            "records_ge_{}\t{}",
+            min_len,
+            stats.records_at_least(min_len)
+        )?;
+
+        writeln!(
+            writer,
+            "bases_ge_{}\t{}",
+            min_len,
+            stats.bases_at_least(min_len)
+        )?;
+    }
+
+    for (name, len) in stats.longest() {
+        write!(writer, "longest_read\t")?;
+        writer.write_all(name)?;
+        writeln!(writer, "\t{}", len)?;
+    }
+
+    Ok(())
+}
+
+// Each adapter content curve is written as a comma-separated list of fractions, one per cycle.
+fn write_adapter_content<W>(writer: &mut W, adapter_content: &AdapterContent) -> io::Result<()>
+where
+    W: Write,
+{
+    for (i, adapter) in adapter_content.adapters().iter().enumerate() {
+        let curve: Vec<_> = adapter_content
+            .curve(i)
+            .iter()
+            .map(|fraction| format!("{:.4}", fraction))
+            .collect();
+
+        writeln!(
+            writer,
+            "adapter_content_{}\t{}",
+            adapter.name(),
+            curve.join(",")
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_length_stats() -> io::Result<()> {
+        let data = b"@r0\nACGTACGT\n+\nFQLIBFQL
+@r1\nACGT\n+\nFQLI
+@r2\nAC\n+\nFQ
+";
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let (stats, _) = read_stats(&mut reader, 2, Vec::new())?;
+
+        let mut buf = Vec::new();
+        write_length_stats(&mut buf, &stats, &[4])?;
+
+        let expected = "\
+record_count\t3
+base_count\t14
+max_length\t8
+n50\t8
+n90\t2
+records_ge_4\t2
+bases_ge_4\t12
+longest_read\tr0\t8
+longest_read\tr1\t4
+";
+
+        assert_eq!(String::from_utf8(buf).unwrap(), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_adapter_content() -> io::Result<()> {
+        let data = b"@r0\nACGGCC\n+\nFQLIBF
+@r1\nGGCCAA\n+\nFQLIBF
+@r2\nACGTAC\n+\nFQLIBF
+";
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let adapters = vec![Adapter::new("a", "GGCC")];
+        let (_, adapter_content) = read_stats(&mut reader, 0, adapters)?;
+
+        let mut buf = Vec::new();
+        write_adapter_content(&mut buf, &adapter_content)?;
+
+        let expected = "adapter_content_a\t0.3333,0.3333,0.6667,0.6667,0.6667,0.6667\n";
+        assert_eq!(String::from_utf8(buf).unwrap(), expected);
+
+        Ok(())
+    }
+}
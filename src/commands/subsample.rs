@@ -15,7 +15,10 @@ use rand::{
 };
 use tracing::{info, warn};
 
-use crate::fastq::{self, Record};
+use crate::{
+    fastq::{self, Record},
+    metrics::Metrics,
+};
 
 pub fn subsample(matches: &ArgMatches) -> anyhow::Result<()> {
     let r1_src = matches.value_of("r1-src").unwrap();
@@ -26,6 +29,8 @@ pub fn subsample(matches: &ArgMatches) -> anyhow::Result<()> {
 
     info!("fq-subsample start");
 
+    let metrics = Metrics::new();
+
     let rng = if matches.is_present("seed") {
         let seed = matches.value_of_t("seed").unwrap_or_else(|e| e.exit());
         info!("initializing rng from seed = {}", seed);
@@ -40,17 +45,47 @@ pub fn subsample(matches: &ArgMatches) -> anyhow::Result<()> {
             .value_of_t("probability")
             .unwrap_or_else(|e| e.exit());
 
-        subsample_approximate((r1_src, r1_dst), (r2_src, r2_dst), rng, probability)?;
+        subsample_approximate(
+            (r1_src, r1_dst),
+            (r2_src, r2_dst),
+            rng,
+            probability,
+            &metrics,
+        )?;
+    } else if matches.is_present("target-coverage") {
+        let target_coverage = matches
+            .value_of_t("target-coverage")
+            .unwrap_or_else(|e| e.exit());
+        let genome_size = matches
+            .value_of_t("genome-size")
+            .unwrap_or_else(|e| e.exit());
+
+        subsample_coverage(
+            (r1_src, r1_dst),
+            (r2_src, r2_dst),
+            rng,
+            target_coverage,
+            genome_size,
+            &metrics,
+        )?;
     } else if matches.is_present("record-count") {
         let record_count = matches
             .value_of_t("record-count")
             .unwrap_or_else(|e| e.exit());
 
-        subsample_exact((r1_src, r1_dst), (r2_src, r2_dst), rng, record_count)?;
+        subsample_exact(
+            (r1_src, r1_dst),
+            (r2_src, r2_dst),
+            rng,
+            record_count,
+            &metrics,
+        )?;
     } else {
         unreachable!();
     }
 
+    metrics.log();
+
     info!("fq-subsample end");
 
     Ok(())
@@ -61,6 +96,7 @@ fn subsample_approximate<Rng>(
     (r2_src, r2_dst): (Option<&str>, Option<&str>),
     mut rng: Rng,
     probability: f64,
+    metrics: &Metrics,
 ) -> anyhow::Result<()>
 where
     Rng: rand::Rng,
@@ -73,9 +109,13 @@ where
     let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
     let mut w1 =
         fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+    r1.set_metrics(metrics.clone());
+    w1.set_metrics(metrics.clone());
 
     info!("probability (p) = {}", probability);
 
+    let _stage = metrics.stage("sampling");
+
     let (n, total) = match (r2_src, r2_dst) {
         (Some(r2_src), Some(r2_dst)) => {
             info!("sampling paired end reads");
@@ -84,6 +124,8 @@ where
                 fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
             let mut w2 = fastq::create(r2_dst)
                 .with_context(|| format!("Could not create file: {}", r2_dst))?;
+            r2.set_metrics(metrics.clone());
+            w2.set_metrics(metrics.clone());
 
             subsample_paired(
                 (&mut r1, &mut w1),
@@ -198,18 +240,138 @@ fn subsample_exact<Rng>(
     (r1_src, r1_dst): (&str, &str),
     (r2_src, r2_dst): (Option<&str>, Option<&str>),
     rng: Rng,
-    mut record_count: u64,
+    record_count: u64,
+    metrics: &Metrics,
 ) -> anyhow::Result<()>
 where
     Rng: rand::Rng,
 {
     info!("counting records");
 
+    let stage = metrics.stage("counting records");
     let line_count = count_lines(r1_src)?;
+    drop(stage);
     let r1_src_record_count = line_count / 4;
 
     info!("r1-src record count = {}", r1_src_record_count);
 
+    sample_exact(
+        (r1_src, r1_dst),
+        (r2_src, r2_dst),
+        rng,
+        r1_src_record_count,
+        record_count,
+        metrics,
+    )
+}
+
+fn subsample_coverage<Rng>(
+    (r1_src, r1_dst): (&str, &str),
+    (r2_src, r2_dst): (Option<&str>, Option<&str>),
+    rng: Rng,
+    target_coverage: f64,
+    genome_size: f64,
+    metrics: &Metrics,
+) -> anyhow::Result<()>
+where
+    Rng: rand::Rng,
+{
+    if !target_coverage.is_finite() || target_coverage <= 0.0 {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid target coverage = {}", target_coverage));
+    }
+
+    if !genome_size.is_finite() || genome_size < 1.0 {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| format!("invalid genome size = {}", genome_size));
+    }
+
+    info!("counting records and bases");
+
+    let stage = metrics.stage("counting records");
+
+    let (r1_src_record_count, mut base_count) = count_records_and_bases(r1_src)?;
+
+    if let Some(r2_src) = r2_src {
+        let (r2_src_record_count, r2_base_count) = count_records_and_bases(r2_src)?;
+
+        if r2_src_record_count != r1_src_record_count {
+            return Err(io::Error::from(io::ErrorKind::InvalidData)).with_context(|| {
+                format!(
+                    "r1-src and r2-src have different numbers of records: {} != {}",
+                    r1_src_record_count, r2_src_record_count
+                )
+            });
+        }
+
+        base_count += r2_base_count;
+    }
+
+    drop(stage);
+
+    info!(
+        "r1-src record count = {}, base count = {}",
+        r1_src_record_count, base_count
+    );
+
+    let target_base_count = target_coverage * genome_size;
+    let coverage = base_count as f64 / genome_size;
+
+    info!(
+        "target coverage = {}x ({} bases), source coverage = {:.1}x",
+        target_coverage, target_base_count, coverage
+    );
+
+    if coverage < target_coverage {
+        warn!(
+            "source coverage ({:.1}x) < target coverage ({}x). Keeping all records.",
+            coverage, target_coverage
+        );
+    }
+
+    let n = u64::try_from(r1_src_record_count)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let record_count = coverage_record_count(target_base_count, n, base_count);
+
+    sample_exact(
+        (r1_src, r1_dst),
+        (r2_src, r2_dst),
+        rng,
+        r1_src_record_count,
+        record_count,
+        metrics,
+    )
+}
+
+// Returns the number of records (or pairs) that, at the mean record length, add up to the target
+// number of bases.
+fn coverage_record_count(target_base_count: f64, record_count: u64, base_count: u64) -> u64 {
+    if record_count == 0 || base_count == 0 {
+        return record_count;
+    }
+
+    let mean_record_len = base_count as f64 / record_count as f64;
+    let n = (target_base_count / mean_record_len).ceil();
+
+    if n >= record_count as f64 {
+        record_count
+    } else {
+        n as u64
+    }
+}
+
+fn sample_exact<Rng>(
+    (r1_src, r1_dst): (&str, &str),
+    (r2_src, r2_dst): (Option<&str>, Option<&str>),
+    rng: Rng,
+    r1_src_record_count: usize,
+    mut record_count: u64,
+    metrics: &Metrics,
+) -> anyhow::Result<()>
+where
+    Rng: rand::Rng,
+{
     let n = u64::try_from(r1_src_record_count)
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
@@ -224,11 +386,17 @@ where
 
     info!("building filter");
 
+    let stage = metrics.stage("building filter");
     let bitmap = build_filter(rng, r1_src_record_count, record_count);
+    drop(stage);
+
+    let _stage = metrics.stage("sampling");
 
     let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
     let mut w1 =
         fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+    r1.set_metrics(metrics.clone());
+    w1.set_metrics(metrics.clone());
 
     match (r2_src, r2_dst) {
         (Some(r2_src), Some(r2_dst)) => {
@@ -238,6 +406,8 @@ where
                 fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
             let mut w2 = fastq::create(r2_dst)
                 .with_context(|| format!("Could not create file: {}", r2_dst))?;
+            r2.set_metrics(metrics.clone());
+            w2.set_metrics(metrics.clone());
 
             subsample_exact_paired((&mut r1, &mut w1), (&mut r2, &mut w2), &bitmap)?;
         }
@@ -292,6 +462,21 @@ where
     Ok(n)
 }
 
+fn count_records_and_bases(src: &str) -> anyhow::Result<(usize, u64)> {
+    let mut reader = fastq::open(src).with_context(|| format!("Could not open file: {}", src))?;
+    let mut record = Record::default();
+
+    let mut record_count = 0;
+    let mut base_count = 0;
+
+    while reader.read_record(&mut record)? != 0 {
+        record_count += 1;
+        base_count += record.sequence().len() as u64;
+    }
+
+    Ok((record_count, base_count))
+}
+
 fn open<P>(src: P) -> io::Result<Box<dyn BufRead>>
 where
     P: AsRef<Path>,
@@ -456,6 +641,15 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_coverage_record_count() {
+        assert_eq!(coverage_record_count(1000.0, 100, 10000), 10);
+        assert_eq!(coverage_record_count(1001.0, 100, 10000), 11);
+        assert_eq!(coverage_record_count(20000.0, 100, 10000), 100);
+        assert_eq!(coverage_record_count(1000.0, 0, 0), 0);
+        assert_eq!(coverage_record_count(1000.0, 100, 0), 100);
+    }
+
     #[test]
     fn test_subsample_exact_single() -> anyhow::Result<()> {
         let data = b"@r1\nACGT\n+\nFQLB
@@ -0,0 +1,373 @@
+use std::io::{self, BufRead, Write};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fastq::{self, Record},
+    matching::OverlapMatcher,
+    metrics::Metrics,
+    trim::{trim_end, trim_start, AdapterTrimmer, PolyXTrimmer, QualityTrimmer},
+};
+
+#[derive(Debug, Default)]
+struct TrimOptions {
+    trim_start: usize,
+    trim_end: usize,
+    poly_x_trimmer: Option<PolyXTrimmer>,
+    quality_trimmer: Option<QualityTrimmer>,
+    adapter_trimmer: Option<AdapterTrimmer>,
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Counts {
+    records: u64,
+    trimmed: u64,
+    bases_trimmed: u64,
+    poly_x_trimmed: u64,
+    poly_x_bases_trimmed: u64,
+}
+
+pub fn trim(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r1_dst = matches.value_of("r1-dst").unwrap();
+
+    let r2_src = matches.value_of("r2-src");
+    let r2_dst = matches.value_of("r2-dst");
+
+    let mut poly_x_bases: Vec<u8> = Vec::new();
+
+    if matches.is_present("poly-g") {
+        poly_x_bases.push(b'G');
+    }
+
+    if let Some(bases) = matches.values_of("poly-x") {
+        for base in bases {
+            match base.as_bytes() {
+                [b] if b.is_ascii_alphabetic() => poly_x_bases.push(*b),
+                _ => {
+                    return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                        .with_context(|| format!("invalid poly-X base: {}", base));
+                }
+            }
+        }
+    }
+
+    let poly_x_trimmer = if poly_x_bases.is_empty() {
+        None
+    } else {
+        let min_len = matches
+            .value_of_t("poly-x-min-length")
+            .unwrap_or_else(|e| e.exit());
+
+        Some(PolyXTrimmer::new(poly_x_bases, min_len))
+    };
+
+    let quality_trimmer = if matches.is_present("quality-cutoff") {
+        let cutoff = matches
+            .value_of_t("quality-cutoff")
+            .unwrap_or_else(|e| e.exit());
+
+        let window_size = matches
+            .value_of_t("window-size")
+            .unwrap_or_else(|e| e.exit());
+
+        Some(QualityTrimmer::new(cutoff, window_size))
+    } else {
+        None
+    };
+
+    let adapter_trimmer = if let Some(sequences) = matches.values_of("adapter") {
+        let max_error_rate = matches
+            .value_of_t("adapter-error-rate")
+            .unwrap_or_else(|e| e.exit());
+
+        let min_overlap = matches
+            .value_of_t("adapter-min-overlap")
+            .unwrap_or_else(|e| e.exit());
+
+        let adapters = sequences
+            .map(|s| s.to_ascii_uppercase().into_bytes())
+            .collect();
+
+        let matcher = OverlapMatcher::new(max_error_rate, min_overlap);
+
+        Some(AdapterTrimmer::new(adapters, matcher))
+    } else {
+        None
+    };
+
+    let options = TrimOptions {
+        trim_start: matches
+            .value_of_t("trim-start")
+            .unwrap_or_else(|e| e.exit()),
+        trim_end: matches.value_of_t("trim-end").unwrap_or_else(|e| e.exit()),
+        poly_x_trimmer,
+        quality_trimmer,
+        adapter_trimmer,
+    };
+
+    info!("fq-trim start");
+
+    let metrics = Metrics::new();
+
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    let mut w1 =
+        fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+    r1.set_metrics(metrics.clone());
+    w1.set_metrics(metrics.clone());
+
+    let _stage = metrics.stage("trimming");
+
+    let counts = match (r2_src, r2_dst) {
+        (Some(r2_src), Some(r2_dst)) => {
+            info!("trimming paired end reads");
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            let mut w2 = fastq::create(r2_dst)
+                .with_context(|| format!("Could not create file: {}", r2_dst))?;
+            r2.set_metrics(metrics.clone());
+            w2.set_metrics(metrics.clone());
+
+            trim_paired((&mut r1, &mut w1), (&mut r2, &mut w2), &options)?
+        }
+        (Some(r2_src), None) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-dst for {}", r2_src));
+        }
+        (None, Some(r2_dst)) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-src for {}", r2_dst));
+        }
+        (None, None) => {
+            info!("trimming single end reads");
+            trim_single(&mut r1, &mut w1, &options)?
+        }
+    };
+
+    info!(
+        "trimmed {} bases from {}/{} records",
+        counts.bases_trimmed, counts.trimmed, counts.records
+    );
+
+    if options.poly_x_trimmer.is_some() {
+        info!(
+            "trimmed {} poly-X tail bases from {}/{} records",
+            counts.poly_x_bases_trimmed, counts.poly_x_trimmed, counts.records
+        );
+    }
+
+    metrics.log();
+
+    info!("fq-trim end");
+
+    Ok(())
+}
+
+// Reads are clipped to a fixed length, then poly-X tails are removed, then reads are quality
+// trimmed, then adapter trimmed. Removing poly-X tails first exposes adapters that precede them.
+fn trim_record(options: &TrimOptions, record: &mut Record, counts: &mut Counts) {
+    let len = record.sequence().len();
+
+    trim_start(record, options.trim_start);
+    trim_end(record, options.trim_end);
+
+    if let Some(poly_x_trimmer) = &options.poly_x_trimmer {
+        let n = poly_x_trimmer.trim(record);
+
+        if n > 0 {
+            counts.poly_x_trimmed += 1;
+            counts.poly_x_bases_trimmed += n as u64;
+        }
+    }
+
+    if let Some(quality_trimmer) = &options.quality_trimmer {
+        quality_trimmer.trim(record);
+    }
+
+    if let Some(adapter_trimmer) = &options.adapter_trimmer {
+        adapter_trimmer.trim(record);
+    }
+
+    let bases_trimmed = len - record.sequence().len();
+
+    if bases_trimmed > 0 {
+        counts.trimmed += 1;
+        counts.bases_trimmed += bases_trimmed as u64;
+    }
+
+    counts.records += 1;
+}
+
+fn trim_single<R, W>(
+    reader: &mut fastq::Reader<R>,
+    writer: &mut fastq::Writer<W>,
+    options: &TrimOptions,
+) -> anyhow::Result<Counts>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut record = Record::default();
+    let mut counts = Counts::default();
+
+    while reader.read_record(&mut record)? != 0 {
+        trim_record(options, &mut record, &mut counts);
+        writer.write_record(&record)?;
+    }
+
+    Ok(counts)
+}
+
+fn trim_paired<R, S, W, X>(
+    (r1, w1): (&mut fastq::Reader<R>, &mut fastq::Writer<W>),
+    (r2, w2): (&mut fastq::Reader<S>, &mut fastq::Writer<X>),
+    options: &TrimOptions,
+) -> anyhow::Result<Counts>
+where
+    R: BufRead,
+    S: BufRead,
+    W: Write,
+    X: Write,
+{
+    let mut s1 = Record::default();
+    let mut s2 = Record::default();
+
+    let mut counts = Counts::default();
+
+    loop {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, len) if len > 0 => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r1-src unexpectedly ended before r2-src");
+            }
+            (len, 0) if len > 0 => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r2-src unexpectedly ended before r1-src");
+            }
+            (_, _) => {
+                trim_record(options, &mut s1, &mut counts);
+                trim_record(options, &mut s2, &mut counts);
+
+                w1.write_record(&s1)?;
+                w2.write_record(&s2)?;
+            }
+        }
+    }
+
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trim_record() {
+        let options = TrimOptions {
+            trim_start: 1,
+            trim_end: 1,
+            quality_trimmer: Some(QualityTrimmer::new(20, 2)),
+            ..Default::default()
+        };
+
+        let mut counts = Counts::default();
+
+        let mut record = Record::new("@r0", "ACGTACGTAC", "+", "#IIII5+##I");
+        trim_record(&options, &mut record, &mut counts);
+        assert_eq!(record.sequence(), b"CGTAC");
+        assert_eq!(record.quality_scores(), b"IIII5");
+
+        let options = TrimOptions::default();
+        let mut record = Record::new("@r1", "ACGT", "+", "####");
+        trim_record(&options, &mut record, &mut counts);
+        assert_eq!(record.sequence(), b"ACGT");
+
+        assert_eq!(
+            counts,
+            Counts {
+                records: 2,
+                trimmed: 1,
+                bases_trimmed: 5,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_trim_record_with_adapters() {
+        let adapters = vec![b"AGATCGGAAG".to_vec()];
+
+        let options = TrimOptions {
+            trim_start: 2,
+            adapter_trimmer: Some(AdapterTrimmer::new(adapters, OverlapMatcher::new(0.1, 3))),
+            ..Default::default()
+        };
+
+        let mut counts = Counts::default();
+
+        let mut record = Record::new("@r0", "AGACGTAGATCGG", "+", "IIIIIIIIIIIII");
+        trim_record(&options, &mut record, &mut counts);
+        assert_eq!(record.sequence(), b"ACGT");
+        assert_eq!(record.quality_scores(), b"IIII");
+    }
+
+    #[test]
+    fn test_trim_record_with_poly_x_tails() {
+        let adapters = vec![b"AGATCGGAAG".to_vec()];
+
+        let options = TrimOptions {
+            poly_x_trimmer: Some(PolyXTrimmer::new(vec![b'G'], 4)),
+            adapter_trimmer: Some(AdapterTrimmer::new(adapters, OverlapMatcher::new(0.1, 3))),
+            ..Default::default()
+        };
+
+        let mut counts = Counts::default();
+
+        let mut record = Record::new("@r0", "ACGTAGATCGGGGGGG", "+", "IIIIIIIIIIIIIIII");
+        trim_record(&options, &mut record, &mut counts);
+        assert_eq!(record.sequence(), b"ACGT");
+
+        let mut record = Record::new("@r1", "ACGTGGG", "+", "IIIIIII");
+        trim_record(&options, &mut record, &mut counts);
+        assert_eq!(record.sequence(), b"ACGTGGG");
+
+        assert_eq!(
+            counts,
+            Counts {
+                records: 2,
+                trimmed: 1,
+                bases_trimmed: 12,
+                poly_x_trimmed: 1,
+                poly_x_bases_trimmed: 7,
+            }
+        );
+    }
+
+    #[test]
+    fn test_trim_paired() -> anyhow::Result<()> {
+        let options = TrimOptions {
+            trim_start: 2,
+            ..Default::default()
+        };
+
+        let r1_data = b"@r0/1\nAACCGG\n+\nABCDEF\n";
+        let r2_data = b"@r0/2\nTTGGCC\n+\nABCDEF\n";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut w2 = fastq::Writer::new(Vec::new());
+
+        let counts = trim_paired((&mut r1, &mut w1), (&mut r2, &mut w2), &options)?;
+
+        assert_eq!(counts.records, 2);
+        assert_eq!(w1.get_ref(), b"@r0/1\nCCGG\n+\nCDEF\n");
+        assert_eq!(w2.get_ref(), b"@r0/2\nGGCC\n+\nCDEF\n");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fasta,
+    fastq::{self, Record},
+    primers::{self, Primer, Trimmer},
+};
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Counts {
+    records: u64,
+    start_trimmed: u64,
+    end_trimmed: u64,
+}
+
+pub fn trim_primers(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r1_dst = matches.value_of("r1-dst").unwrap();
+
+    let r2_src = matches.value_of("r2-src");
+    let r2_dst = matches.value_of("r2-dst");
+
+    let max_mismatches = matches
+        .value_of_t("max-mismatches")
+        .unwrap_or_else(|e| e.exit());
+
+    info!("fq-trim-primers start");
+
+    let primers = read_primers(matches)?;
+    info!("read {} primers", primers.len());
+
+    let trimmer = Trimmer::new(&primers, max_mismatches);
+
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    let mut w1 =
+        fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+
+    let counts = match (r2_src, r2_dst) {
+        (Some(r2_src), Some(r2_dst)) => {
+            info!("trimming paired end reads");
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            let mut w2 = fastq::create(r2_dst)
+                .with_context(|| format!("Could not create file: {}", r2_dst))?;
+
+            trim_paired((&mut r1, &mut w1), (&mut r2, &mut w2), &trimmer)?
+        }
+        (Some(r2_src), None) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-dst for {}", r2_src));
+        }
+        (None, Some(r2_dst)) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-src for {}", r2_dst));
+        }
+        (None, None) => {
+            info!("trimming single end reads");
+            trim_single(&mut r1, &mut w1, &trimmer)?
+        }
+    };
+
+    info!(
+        "trimmed primers from {} starts and {} ends of {} records",
+        counts.start_trimmed, counts.end_trimmed, counts.records
+    );
+
+    info!("fq-trim-primers end");
+
+    Ok(())
+}
+
+fn read_primers(matches: &ArgMatches) -> anyhow::Result<Vec<Primer>> {
+    if let Some(scheme_src) = matches.value_of("scheme") {
+        let reference_src = matches.value_of("reference").unwrap();
+
+        info!("reading reference");
+
+        let reader = fasta::open(reference_src)
+            .with_context(|| format!("Could not open file: {}", reference_src))?;
+
+        let reference = primers::read_reference(reader)
+            .with_context(|| format!("Could not read file: {}", reference_src))?;
+
+        info!("reading primer scheme");
+
+        let file = File::open(scheme_src)
+            .with_context(|| format!("Could not open file: {}", scheme_src))?;
+
+        primers::read_bed_scheme(BufReader::new(file), &reference)
+            .with_context(|| format!("Could not read file: {}", scheme_src))
+    } else if let Some(primers_src) = matches.value_of("primers") {
+        info!("reading primers");
+
+        let reader = fasta::open(primers_src)
+            .with_context(|| format!("Could not open file: {}", primers_src))?;
+
+        primers::read_fasta_primers(reader)
+            .with_context(|| format!("Could not read file: {}", primers_src))
+    } else {
+        unreachable!();
+    }
+}
+
+fn trim_record(trimmer: &Trimmer, record: &mut Record, counts: &mut Counts) {
+    let (start, end) = trimmer.trim(record);
+
+    if start > 0 {
+        counts.start_trimmed += 1;
+    }
+
+    if end > 0 {
+        counts.end_trimmed += 1;
+    }
+
+    counts.records += 1;
+}
+
+fn trim_single<R, W>(
+    reader: &mut fastq::Reader<R>,
+    writer: &mut fastq::Writer<W>,
+    trimmer: &Trimmer,
+) -> anyhow::Result<Counts>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut record = Record::default();
+    let mut counts = Counts::default();
+
+    while reader.read_record(&mut record)? != 0 {
+        trim_record(trimmer, &mut record, &mut counts);
+        writer.write_record(&record)?;
+    }
+
+    Ok(counts)
+}
+
+fn trim_paired<R, S, W, X>(
+    (r1, w1): (&mut fastq::Reader<R>, &mut fastq::Writer<W>),
+    (r2, w2): (&mut fastq::Reader<S>, &mut fastq::Writer<X>),
+    trimmer: &Trimmer,
+) -> anyhow::Result<Counts>
+where
+    R: BufRead,
+    S: BufRead,
+    W: Write,
+    X: Write,
+{
+    let mut s1 = Record::default();
+    let mut s2 = Record::default();
+
+    let mut counts = Counts::default();
+
+    loop {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, len) if len > 0 => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r1-src unexpectedly ended before r2-src");
+            }
+            (len, 0) if len > 0 => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r2-src unexpectedly ended before r1-src");
+            }
+            (_, _) => {
+                trim_record(trimmer, &mut s1, &mut counts);
+                trim_record(trimmer, &mut s2, &mut counts);
+
+                w1.write_record(&s1)?;
+                w2.write_record(&s2)?;
+            }
+        }
+    }
+
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_trimmer() -> Trimmer {
+        let primers = [
+            Primer::new("p1_LEFT", "AACCGG"),
+            Primer::new("p1_RIGHT", "TTGGCC"),
+        ];
+
+        Trimmer::new(&primers, 0)
+    }
+
+    #[test]
+    fn test_trim_single() -> anyhow::Result<()> {
+        let data = b"@r1\nAACCGGACGT\n+\nABCDEFGHIJ
+@r2\nACGTACGTAC\n+\nABCDEFGHIJ
+";
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+
+        let counts = trim_single(&mut reader, &mut writer, &build_trimmer())?;
+
+        assert_eq!(
+            counts,
+            Counts {
+                records: 2,
+                start_trimmed: 1,
+                end_trimmed: 0,
+            }
+        );
+
+        let expected = b"@r1\nACGT\n+\nGHIJ\n@r2\nACGTACGTAC\n+\nABCDEFGHIJ\n";
+        assert_eq!(writer.get_ref(), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_trim_paired() -> anyhow::Result<()> {
+        let r1_data = b"@r1\nAACCGGACGTGGCCAA\n+\nABCDEFGHIJKLMNOP\n";
+        let r2_data = b"@r1\nTTGGCCACGTCCGGTT\n+\nABCDEFGHIJKLMNOP\n";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut w2 = fastq::Writer::new(Vec::new());
+
+        let counts = trim_paired((&mut r1, &mut w1), (&mut r2, &mut w2), &build_trimmer())?;
+
+        assert_eq!(
+            counts,
+            Counts {
+                records: 2,
+                start_trimmed: 2,
+                end_trimmed: 2,
+            }
+        );
+
+        assert_eq!(w1.get_ref(), b"@r1\nACGT\n+\nGHIJ\n");
+        assert_eq!(w2.get_ref(), b"@r1\nACGT\n+\nGHIJ\n");
+
+        Ok(())
+    }
+}
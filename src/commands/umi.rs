@@ -0,0 +1,210 @@
+use std::io::{self, BufRead, Write};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    dedup::move_umi_to_name,
+    fastq::{self, Record},
+    metrics::Metrics,
+};
+
+pub fn umi(matches: &ArgMatches) -> anyhow::Result<()> {
+    if let Some(m) = matches.subcommand_matches("extract") {
+        extract(m)
+    } else {
+        unreachable!();
+    }
+}
+
+fn extract(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r1_dst = matches.value_of("r1-dst").unwrap();
+
+    let r2_src = matches.value_of("r2-src");
+    let r2_dst = matches.value_of("r2-dst");
+
+    let len: usize = matches.value_of_t("length").unwrap_or_else(|e| e.exit());
+    let read: usize = matches.value_of_t("read").unwrap_or_else(|e| e.exit());
+
+    if len == 0 {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput))
+            .with_context(|| "UMI length must be > 0");
+    }
+
+    info!("fq-umi-extract start");
+
+    let metrics = Metrics::new();
+
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+    let mut w1 =
+        fastq::create(r1_dst).with_context(|| format!("Could not create file: {}", r1_dst))?;
+    r1.set_metrics(metrics.clone());
+    w1.set_metrics(metrics.clone());
+
+    let _stage = metrics.stage("extracting UMIs");
+
+    let n = match (r2_src, r2_dst) {
+        (Some(r2_src), Some(r2_dst)) => {
+            info!("extracting UMIs from read {} of paired end reads", read);
+
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            let mut w2 = fastq::create(r2_dst)
+                .with_context(|| format!("Could not create file: {}", r2_dst))?;
+            r2.set_metrics(metrics.clone());
+            w2.set_metrics(metrics.clone());
+
+            extract_paired((&mut r1, &mut w1), (&mut r2, &mut w2), read - 1, len)?
+        }
+        (Some(r2_src), None) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-dst for {}", r2_src));
+        }
+        (None, Some(r2_dst)) => {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                .with_context(|| format!("Missing r2-src for {}", r2_dst));
+        }
+        (None, None) => {
+            if read != 1 {
+                return Err(io::Error::from(io::ErrorKind::InvalidInput))
+                    .with_context(|| format!("Missing r2-src for --read {}", read));
+            }
+
+            info!("extracting UMIs from single end reads");
+            extract_single(&mut r1, &mut w1, len)?
+        }
+    };
+
+    info!("extracted {} UMIs", n);
+
+    metrics.log();
+
+    info!("fq-umi-extract end");
+
+    Ok(())
+}
+
+fn extract_single<R, W>(
+    reader: &mut fastq::Reader<R>,
+    writer: &mut fastq::Writer<W>,
+    len: usize,
+) -> anyhow::Result<u64>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut records = [Record::default()];
+    let mut n = 0;
+
+    while reader.read_record(&mut records[0])? != 0 {
+        move_umi(&mut records, 0, len)?;
+        writer.write_record(&records[0])?;
+        n += 1;
+    }
+
+    Ok(n)
+}
+
+fn extract_paired<R, S, W, X>(
+    (r1, w1): (&mut fastq::Reader<R>, &mut fastq::Writer<W>),
+    (r2, w2): (&mut fastq::Reader<S>, &mut fastq::Writer<X>),
+    read: usize,
+    len: usize,
+) -> anyhow::Result<u64>
+where
+    R: BufRead,
+    S: BufRead,
+    W: Write,
+    X: Write,
+{
+    let mut records = [Record::default(), Record::default()];
+    let mut n = 0;
+
+    loop {
+        let [s1, s2] = &mut records;
+
+        match (r1.read_record(s1)?, r2.read_record(s2)?) {
+            (0, 0) => break,
+            (0, _) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r1-src unexpectedly ended before r2-src");
+            }
+            (_, 0) => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    .with_context(|| "r2-src unexpectedly ended before r1-src");
+            }
+            (_, _) => {
+                move_umi(&mut records, read, len)?;
+                w1.write_record(&records[0])?;
+                w2.write_record(&records[1])?;
+                n += 1;
+            }
+        }
+    }
+
+    Ok(n)
+}
+
+fn move_umi(records: &mut [Record], read: usize, len: usize) -> anyhow::Result<()> {
+    if move_umi_to_name(records, read, len).is_none() {
+        let record = &records[read];
+
+        return Err(io::Error::from(io::ErrorKind::InvalidData)).with_context(|| {
+            format!(
+                "{}: read length ({}) < UMI length ({})",
+                String::from_utf8_lossy(record.name()),
+                record.sequence().len(),
+                len
+            )
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_single() -> anyhow::Result<()> {
+        let data = b"@r0 1:N:0\nACGTTT\n+\nFQLIBI\n@r1\nGGCCAA\n+\nIIIIII\n";
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+        let n = extract_single(&mut reader, &mut writer, 4)?;
+
+        assert_eq!(n, 2);
+        assert_eq!(
+            writer.get_ref(),
+            b"@r0:ACGT 1:N:0\nTT\n+\nBI\n@r1:GGCC\nAA\n+\nII\n"
+        );
+
+        let mut reader = fastq::Reader::new(&data[..]);
+        let mut writer = fastq::Writer::new(Vec::new());
+        assert!(extract_single(&mut reader, &mut writer, 7).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_paired() -> anyhow::Result<()> {
+        let r1_data = b"@r0/1\nACGT\n+\nIIII\n";
+        let r2_data = b"@r0/2\nTTGCA\n+\nFQLIB\n";
+
+        let mut r1 = fastq::Reader::new(&r1_data[..]);
+        let mut w1 = fastq::Writer::new(Vec::new());
+        let mut r2 = fastq::Reader::new(&r2_data[..]);
+        let mut w2 = fastq::Writer::new(Vec::new());
+
+        let n = extract_paired((&mut r1, &mut w1), (&mut r2, &mut w2), 1, 2)?;
+
+        assert_eq!(n, 1);
+        assert_eq!(w1.get_ref(), b"@r0:TT/1\nACGT\n+\nIIII\n");
+        assert_eq!(w2.get_ref(), b"@r0:TT/2\nGCA\n+\nLIB\n");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+use std::io::{self, BufRead, BufWriter, IsTerminal, Write};
+
+use anyhow::Context;
+use clap::ArgMatches;
+use tracing::info;
+
+use crate::{
+    fastq::{self, Record},
+    mask::PositionRange,
+    quality::Encoding,
+};
+
+const QUALITY_OFFSET: u8 = Encoding::Phred33.offset();
+
+const RESET: &[u8] = b"\x1b[0m";
+const BOLD: &[u8] = b"\x1b[1m";
+const RED: &[u8] = b"\x1b[31m";
+const GREEN: &[u8] = b"\x1b[32m";
+const YELLOW: &[u8] = b"\x1b[33m";
+const BLUE: &[u8] = b"\x1b[34m";
+const CYAN: &[u8] = b"\x1b[36m";
+const GRAY: &[u8] = b"\x1b[90m";
+
+#[derive(Debug, Default)]
+struct ViewOptions {
+    limit: Option<u64>,
+    range: Option<PositionRange>,
+    color: bool,
+}
+
+pub fn view(matches: &ArgMatches) -> anyhow::Result<()> {
+    let r1_src = matches.value_of("r1-src").unwrap();
+    let r2_src = matches.value_of("r2-src");
+
+    let limit = if matches.is_present("limit") {
+        Some(matches.value_of_t("limit").unwrap_or_else(|e| e.exit()))
+    } else {
+        None
+    };
+
+    let range = if matches.is_present("range") {
+        Some(matches.value_of_t("range").unwrap_or_else(|e| e.exit()))
+    } else {
+        None
+    };
+
+    let color = match matches.value_of("color") {
+        Some("always") => true,
+        Some("never") => false,
+        _ => io::stdout().is_terminal(),
+    };
+
+    let options = ViewOptions {
+        limit,
+        range,
+        color,
+    };
+
+    info!("fq-view start");
+
+    let mut r1 = fastq::open(r1_src).with_context(|| format!("Could not open file: {}", r1_src))?;
+
+    let stdout = io::stdout();
+    let mut writer = BufWriter::new(stdout.lock());
+
+    let result = match r2_src {
+        Some(r2_src) => {
+            let mut r2 =
+                fastq::open(r2_src).with_context(|| format!("Could not open file: {}", r2_src))?;
+            view_paired(&mut r1, &mut r2, &mut writer, &options)
+        }
+        None => view_single(&mut r1, &mut writer, &options),
+    };
+
+    let result = result.and_then(|n| writer.flush().map(|_| n));
+
+    match result {
+        Ok(n) => info!("viewed {} records (or pairs)", n),
+        // The output was closed early, e.g., by a pager.
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(e) => return Err(e).context("Could not view records"),
+    }
+
+    info!("fq-view end");
+
+    Ok(())
+}
+
+fn view_single<R, W>(
+    reader: &mut fastq::Reader<R>,
+    writer: &mut W,
+    options: &ViewOptions,
+) -> io::Result<u64>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut record = Record::default();
+    let mut n = 0;
+
+    while options.limit.map(|limit| n < limit).unwrap_or(true) {
+        if reader.read_record(&mut record)? == 0 {
+            break;
+        }
+
+        write_record(writer, &record, options)?;
+        n += 1;
+    }
+
+    Ok(n)
+}
+
+// Pairs are interleaved.
+fn view_paired<R, S, W>(
+    r1: &mut fastq::Reader<R>,
+    r2: &mut fastq::Reader<S>,
+    writer: &mut W,
+    options: &ViewOptions,
+) -> io::Result<u64>
+where
+    R: BufRead,
+    S: BufRead,
+    W: Write,
+{
+    let mut s1 = Record::default();
+    let mut s2 = Record::default();
+
+    let mut n = 0;
+
+    while options.limit.map(|limit| n < limit).unwrap_or(true) {
+        match (r1.read_record(&mut s1)?, r2.read_record(&mut s2)?) {
+            (0, 0) => break,
+            (0, _) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "r1-src unexpectedly ended before r2-src",
+                ));
+            }
+            (_, 0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "r2-src unexpectedly ended before r1-src",
+                ));
+            }
+            (_, _) => {
+                write_record(writer, &s1, options)?;
+                write_record(writer, &s2, options)?;
+                n += 1;
+            }
+        }
+    }
+
+    Ok(n)
+}
+
+fn write_record<W>(writer: &mut W, record: &Record, options: &ViewOptions) -> io::Result<()>
+where
+    W: Write,
+{
+    let (sequence, quality_scores) = match options.range {
+        Some(range) => (
+            slice(record.sequence(), range),
+            slice(record.quality_scores(), range),
+        ),
+        None => (record.sequence(), record.quality_scores()),
+    };
+
+    if options.color {
+        writer.write_all(BOLD)?;
+        writer.write_all(record.name())?;
+        writer.write_all(RESET)?;
+        writer.write_all(b"\n")?;
+
+        write_colored(writer, sequence, base_color)?;
+        writer.write_all(record.plus_line())?;
+        writer.write_all(b"\n")?;
+        write_colored(writer, quality_scores, quality_score_color)?;
+    } else {
+        writer.write_all(record.name())?;
+        writer.write_all(b"\n")?;
+        writer.write_all(sequence)?;
+        writer.write_all(b"\n")?;
+        writer.write_all(record.plus_line())?;
+        writer.write_all(b"\n")?;
+        writer.write_all(quality_scores)?;
+        writer.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
+
+// Returns the part of a sequence or quality scores in the 1-based inclusive range.
+fn slice(buf: &[u8], range: PositionRange) -> &[u8] {
+    let start = (range.start() - 1).min(buf.len());
+    let end = range.end().min(buf.len());
+    &buf[start..end]
+}
+
+// Writes a line, only emitting an escape sequence when the color changes.
+fn write_colored<W, F>(writer: &mut W, buf: &[u8], color: F) -> io::Result<()>
+where
+    W: Write,
+    F: Fn(u8) -> &'static [u8],
+{
+    let mut current = None;
+
+    for &b in buf {
+        let c = color(b);
+
+        if current != Some(c) {
+            writer.write_all(c)?;
+            current = Some(c);
+        }
+
+        writer.write_all(&[b])?;
+    }
+
+    if current.is_some() {
+        writer.write_all(RESET)?;
+    }
+
+    writer.write_all(b"\n")
+}
+
+fn base_color(base: u8) -> &'static [u8] {
+    match base.to_ascii_uppercase() {
+        b'A' => GREEN,
+        b'C' => BLUE,
+        b'G' => YELLOW,
+        b'T' | b'U' => RED,
+        _ => GRAY,
+    }
+}
+
+fn quality_score_color(score: u8) -> &'static [u8] {
+    match score.saturating_sub(QUALITY_OFFSET) {
+        0..=9 => RED,
+        10..=19 => YELLOW,
+        20..=29 => CYAN,
+        _ => GREEN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static DATA: &[u8] = b"@r0\nACGTN\n+\n!+5?I\n@r1\nTTGG\n+\nIIII\n";
+
+    #[test]
+    fn test_view_single() -> io::Result<()> {
+        let options = ViewOptions {
+            limit: Some(1),
+            range: PositionRange::new(2, 3),
+            color: false,
+        };
+
+        let mut reader = fastq::Reader::new(DATA);
+        let mut writer = Vec::new();
+        let n = view_single(&mut reader, &mut writer, &options)?;
+
+        assert_eq!(n, 1);
+        assert_eq!(writer, b"@r0\nCG\n+\n+5\n");
+
+        let options = ViewOptions {
+            color: true,
+            ..Default::default()
+        };
+
+        let mut reader = fastq::Reader::new(&DATA[..18]);
+        let mut writer = Vec::new();
+        view_single(&mut reader, &mut writer, &options)?;
+
+        let expected = [
+            &b"\x1b[1m@r0\x1b[0m\n"[..],
+            b"\x1b[32mA\x1b[34mC\x1b[33mG\x1b[31mT\x1b[90mN\x1b[0m\n",
+            b"+\n",
+            b"\x1b[31m!\x1b[33m+\x1b[36m5\x1b[32m?I\x1b[0m\n",
+        ]
+        .concat();
+
+        assert_eq!(writer, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_view_paired() -> io::Result<()> {
+        let options = ViewOptions::default();
+
+        let mut r1 = fastq::Reader::new(DATA);
+        let mut r2 = fastq::Reader::new(DATA);
+        let mut writer = Vec::new();
+        let n = view_paired(&mut r1, &mut r2, &mut writer, &options)?;
+
+        assert_eq!(n, 2);
+        assert_eq!(
+            writer,
+            b"@r0\nACGTN\n+\n!+5?I\n@r0\nACGTN\n+\n!+5?I\n@r1\nTTGG\n+\nIIII\n@r1\nTTGG\n+\nIIII\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_slice() {
+        let range = PositionRange::new(3, 8).unwrap();
+        assert_eq!(slice(b"ACGTACGT", range), b"GTACGT");
+        assert_eq!(slice(b"ACGT", range), b"GT");
+        assert_eq!(slice(b"AC", range), b"");
+    }
+}
@@ -0,0 +1,92 @@
+//! Sequence complexity.
+
+// The length of the k-mers whose frequencies are measured.
+const K: usize = 3;
+const KMER_COUNT: usize = 1 << (2 * K);
+
+/// Returns the normalized Shannon entropy of the trinucleotides in a sequence.
+///
+/// The score is in [0, 1], where 0 is a homopolymer and 1 is a sequence with no repeated
+/// trinucleotides. The entropy is normalized by its maximum for the number of trinucleotides in
+/// the sequence, so scores are comparable across read lengths. Trinucleotides with bases other
+/// than `A`, `C`, `G`, or `T` are skipped, and sequences with fewer than two trinucleotides have
+/// a score of 0.
+///
+/// # Examples
+///
+/// ```
+/// use fq::complexity::entropy;
+///
+/// assert_eq!(entropy(b"AAAAAAAAAA"), 0.0);
+/// assert_eq!(entropy(b"ACGTTGCAAT"), 1.0);
+/// assert!(entropy(b"ACACACACACACACACACAC") < 0.3);
+/// ```
+pub fn entropy(sequence: &[u8]) -> f64 {
+    let mut counts = [0u32; KMER_COUNT];
+    let mut total = 0;
+
+    for kmer in sequence.windows(K) {
+        if let Some(i) = encode(kmer) {
+            counts[i] += 1;
+            total += 1;
+        }
+    }
+
+    if total < 2 {
+        return 0.0;
+    }
+
+    let n = f64::from(total);
+
+    let h: f64 = counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = f64::from(count) / n;
+            -p * p.log2()
+        })
+        .sum();
+
+    let max = n.min(KMER_COUNT as f64).log2();
+
+    (h / max).clamp(0.0, 1.0)
+}
+
+fn encode(kmer: &[u8]) -> Option<usize> {
+    kmer.iter().try_fold(0, |i, &b| {
+        let code = match b.to_ascii_uppercase() {
+            b'A' => 0,
+            b'C' => 1,
+            b'G' => 2,
+            b'T' => 3,
+            _ => return None,
+        };
+
+        Some((i << 2) | code)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entropy() {
+        assert_eq!(entropy(b""), 0.0);
+        assert_eq!(entropy(b"ACG"), 0.0);
+        assert_eq!(entropy(b"ACGT"), 1.0);
+        assert_eq!(entropy(b"NNNNNNNN"), 0.0);
+        assert_eq!(entropy(b"acgttgcaat"), 1.0);
+
+        let score = entropy(b"ACGTACGTAC");
+        assert!((score - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode(b"AAA"), Some(0));
+        assert_eq!(encode(b"ACG"), Some(0b000110));
+        assert_eq!(encode(b"TTT"), Some(63));
+        assert_eq!(encode(b"ANA"), None);
+    }
+}
@@ -0,0 +1,144 @@
+//! Duplicate read removal.
+
+mod filter;
+mod umi;
+
+pub use self::filter::{build_key, DuplicateFilter};
+pub use self::umi::{
+    cluster_umis, edit_distance, move_umi_to_name, ParseUmiSourceError, UmiSource,
+};
+
+use std::{error, fmt, str::FromStr};
+
+use crate::fastq::Record;
+
+/// How to choose the record kept for a group of duplicates.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Keep {
+    /// The record with the highest sum of quality scores.
+    #[default]
+    BestQuality,
+    /// A per-base majority vote of all duplicates.
+    Consensus,
+}
+
+/// An error returned when a keep method fails to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseKeepError(String);
+
+impl error::Error for ParseKeepError {}
+
+impl fmt::Display for ParseKeepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid keep method: expected best-quality or consensus, got '{}'",
+            self.0
+        )
+    }
+}
+
+impl FromStr for Keep {
+    type Err = ParseKeepError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "best-quality" => Ok(Self::BestQuality),
+            "consensus" => Ok(Self::Consensus),
+            _ => Err(ParseKeepError(s.into())),
+        }
+    }
+}
+
+impl Keep {
+    /// Builds the representative of a group of duplicate records.
+    ///
+    /// `records` is a nonempty list of duplicates. The best quality record is used as the base
+    /// of a consensus, i.e., the consensus keeps its name and length.
+    pub fn build(&self, records: &[&Record]) -> Record {
+        let best = records
+            .iter()
+            .enumerate()
+            .max_by_key(|(i, r)| (quality_sum(r), std::cmp::Reverse(*i)))
+            .map(|(_, r)| *r)
+            .expect("records cannot be empty");
+
+        match self {
+            Self::BestQuality => best.clone(),
+            Self::Consensus => consensus(best, records),
+        }
+    }
+}
+
+fn quality_sum(record: &Record) -> u64 {
+    record.quality_scores().iter().map(|&q| u64::from(q)).sum()
+}
+
+fn consensus(base: &Record, records: &[&Record]) -> Record {
+    let mut record = base.clone();
+
+    for (i, &base_call) in base.sequence().iter().enumerate() {
+        let mut counts = [0; 256];
+
+        for r in records {
+            if let Some(&b) = r.sequence().get(i) {
+                counts[usize::from(b)] += 1;
+            }
+        }
+
+        // Ties keep the base record's call.
+        let mut call = base_call;
+
+        for (b, &count) in (0..=u8::MAX).zip(counts.iter()) {
+            if count > counts[usize::from(call)] {
+                call = b;
+            }
+        }
+
+        let score = records
+            .iter()
+            .filter(|r| r.sequence().get(i) == Some(&call))
+            .filter_map(|r| r.quality_scores().get(i).copied())
+            .max();
+
+        record.sequence_mut()[i] = call;
+
+        if let (Some(score), Some(dst)) = (score, record.quality_scores_mut().get_mut(i)) {
+            *dst = score;
+        }
+    }
+
+    record
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("best-quality".parse(), Ok(Keep::BestQuality));
+        assert_eq!("consensus".parse(), Ok(Keep::Consensus));
+        assert_eq!(
+            "first".parse::<Keep>(),
+            Err(ParseKeepError(String::from("first")))
+        );
+    }
+
+    #[test]
+    fn test_build() {
+        let r0 = Record::new("@r0", "ACGT", "+", "IIII");
+        let r1 = Record::new("@r1", "ACTT", "+", "IIJ!");
+        let r2 = Record::new("@r2", "ACTA", "+", "!!5!");
+        let records = [&r0, &r1, &r2];
+
+        assert_eq!(Keep::BestQuality.build(&records), r0);
+
+        assert_eq!(
+            Keep::Consensus.build(&records),
+            Record::new("@r0", "ACTT", "+", "IIJI")
+        );
+
+        assert_eq!(Keep::BestQuality.build(&[&r2, &r2]), r2);
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashSet;
+
+use bbloom::ScalableBloomFilter;
+
+use crate::fastq::Record;
+
+const FALSE_POSITIVE_PROBABILITY: f64 = 0.0001;
+const INITIAL_CAPACITY: usize = 10_000_000;
+
+/// A set of seen read keys used to drop duplicate reads in a single pass.
+///
+/// The exact filter holds every key in memory. The approximate filter uses a Bloom filter, a
+/// probabilistic data structure, which bounds memory but may treat a small fraction of unique
+/// reads as duplicates.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{dedup::{build_key, DuplicateFilter}, fastq::Record};
+///
+/// let mut filter = DuplicateFilter::exact();
+/// let mut key = Vec::new();
+///
+/// let r = Record::new("@fqlib:1", "ACGT", "+", "IIII");
+/// let s = Record::new("@fqlib:2", "ACGT", "+", "IIII");
+///
+/// build_key(&[&r], false, &mut key);
+/// assert!(filter.insert(&key));
+///
+/// build_key(&[&s], false, &mut key);
+/// assert!(!filter.insert(&key));
+/// ```
+pub enum DuplicateFilter {
+    Exact(HashSet<Vec<u8>>),
+    Approximate(ScalableBloomFilter),
+}
+
+impl DuplicateFilter {
+    /// Creates a filter that holds all keys in memory.
+    pub fn exact() -> Self {
+        Self::Exact(HashSet::new())
+    }
+
+    /// Creates a filter backed by a scalable Bloom filter.
+    pub fn approximate() -> Self {
+        Self::Approximate(ScalableBloomFilter::new(
+            FALSE_POSITIVE_PROBABILITY,
+            INITIAL_CAPACITY,
+        ))
+    }
+
+    /// Adds a key to the set.
+    ///
+    /// This returns whether the key was not previously seen, i.e., `false` for a duplicate.
+    pub fn insert(&mut self, key: &[u8]) -> bool {
+        match self {
+            Self::Exact(keys) => {
+                if keys.contains(key) {
+                    false
+                } else {
+                    keys.insert(key.to_vec());
+                    true
+                }
+            }
+            Self::Approximate(filter) => !filter.contains_or_insert(key),
+        }
+    }
+}
+
+/// Builds the duplicate key of a template, i.e., one record for single end reads or both mates
+/// for paired end reads.
+///
+/// The key is the concatenation of the record sequences and, when `with_names` is set, the record
+/// names. `dst` is cleared before the key is written.
+pub fn build_key(records: &[&Record], with_names: bool, dst: &mut Vec<u8>) {
+    dst.clear();
+
+    for record in records {
+        if with_names {
+            dst.extend_from_slice(record.name());
+            dst.push(b'\n');
+        }
+
+        dst.extend_from_slice(record.sequence());
+        dst.push(b'\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert() {
+        let mut filter = DuplicateFilter::exact();
+        assert!(filter.insert(b"ACGT"));
+        assert!(filter.insert(b"ACGA"));
+        assert!(!filter.insert(b"ACGT"));
+
+        let mut filter = DuplicateFilter::approximate();
+        assert!(filter.insert(b"ACGT"));
+        assert!(!filter.insert(b"ACGT"));
+    }
+
+    #[test]
+    fn test_build_key() {
+        let r = Record::new("@r0", "ACGT", "+", "IIII");
+        let s = Record::new("@r0", "TTGC", "+", "IIII");
+
+        let mut key = Vec::new();
+
+        build_key(&[&r], false, &mut key);
+        assert_eq!(key, b"ACGT\n");
+
+        build_key(&[&r, &s], false, &mut key);
+        assert_eq!(key, b"ACGT\nTTGC\n");
+
+        build_key(&[&r], true, &mut key);
+        assert_eq!(key, b"@r0\nACGT\n");
+    }
+}
@@ -0,0 +1,283 @@
+use std::{error, fmt, str::FromStr};
+
+use crate::fastq::{name_id, Record};
+
+const NAME_UMI_DELIMITER: u8 = b':';
+
+/// Where to read a unique molecular identifier (UMI) from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UmiSource {
+    /// The last `:`-delimited field of the read name, e.g., `@fqlib:1:ACGTACGT`.
+    Name,
+    /// The first _n_ bases of the sequence.
+    Sequence(usize),
+}
+
+impl UmiSource {
+    /// Extracts the UMI from a record.
+    ///
+    /// This returns the UMI and the offset at which the template sequence starts, i.e., after an
+    /// inline UMI. It is `None` if the name has no UMI field or the sequence is too short.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{dedup::UmiSource, fastq::Record};
+    ///
+    /// let record = Record::new("@fqlib:1:ACGT/1", "TTGCA", "+", "FQLIB");
+    /// assert_eq!(UmiSource::Name.extract(&record), Some((&b"ACGT"[..], 0)));
+    /// assert_eq!(UmiSource::Sequence(2).extract(&record), Some((&b"TT"[..], 2)));
+    /// ```
+    pub fn extract<'a>(&self, record: &'a Record) -> Option<(&'a [u8], usize)> {
+        match *self {
+            Self::Name => {
+                let name = record.name();
+                let end = name
+                    .iter()
+                    .position(|&b| b == b' ' || b == b'/')
+                    .unwrap_or(name.len());
+                let id = &name[..end];
+
+                let start = id.iter().rposition(|&b| b == NAME_UMI_DELIMITER)? + 1;
+                let umi = &id[start..];
+
+                if umi.is_empty() {
+                    None
+                } else {
+                    Some((umi, 0))
+                }
+            }
+            Self::Sequence(len) => record.sequence().get(..len).map(|umi| (umi, len)),
+        }
+    }
+}
+
+/// An error returned when a UMI source fails to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseUmiSourceError(String);
+
+impl error::Error for ParseUmiSourceError {}
+
+impl fmt::Display for ParseUmiSourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid UMI source: expected `name` or `sequence:<len>`, got '{}'",
+            self.0
+        )
+    }
+}
+
+impl FromStr for UmiSource {
+    type Err = ParseUmiSourceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "name" {
+            return Ok(Self::Name);
+        }
+
+        s.strip_prefix("sequence:")
+            .and_then(|len| len.parse().ok())
+            .filter(|&len| len > 0)
+            .map(Self::Sequence)
+            .ok_or_else(|| ParseUmiSourceError(s.into()))
+    }
+}
+
+/// Moves an inline UMI from the start of a read to the names of a template of records.
+///
+/// The first `len` bases and quality scores of `records[read]` are clipped, and the UMI is
+/// appended to the ID of each record as a `:`-delimited field, i.e., before the pair suffix
+/// (`/1`, `/2`) and comment. This is the field read by [`UmiSource::Name`].
+///
+/// This returns the UMI or `None`, leaving the records unchanged, if the read is shorter than
+/// `len`.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{dedup::move_umi_to_name, fastq::Record};
+///
+/// let mut records = [
+///     Record::new("@r0/1", "ACGTTTGCA", "+", "FQLIBFQLI"),
+///     Record::new("@r0/2", "TTTTT", "+", "IIIII"),
+/// ];
+///
+/// assert_eq!(move_umi_to_name(&mut records, 0, 4), Some(b"ACGT".to_vec()));
+///
+/// assert_eq!(records[0].name(), b"@r0:ACGT/1");
+/// assert_eq!(records[0].sequence(), b"TTGCA");
+/// assert_eq!(records[0].quality_scores(), b"BFQLI");
+/// assert_eq!(records[1].name(), b"@r0:ACGT/2");
+/// ```
+pub fn move_umi_to_name(records: &mut [Record], read: usize, len: usize) -> Option<Vec<u8>> {
+    let record = records.get_mut(read)?;
+
+    if record.sequence().len() < len || record.quality_scores().len() < len {
+        return None;
+    }
+
+    let umi: Vec<u8> = record.sequence_mut().drain(..len).collect();
+    record.quality_scores_mut().drain(..len);
+
+    for record in records {
+        let name = record.name();
+        let end = usize::from(name.starts_with(b"@")) + name_id(name).len();
+
+        let mut field = Vec::with_capacity(umi.len() + 1);
+        field.push(NAME_UMI_DELIMITER);
+        field.extend_from_slice(&umi);
+
+        record.name_mut().splice(end..end, field);
+    }
+
+    Some(umi)
+}
+
+/// Returns the Levenshtein distance between two UMIs.
+pub fn edit_distance(a: &[u8], b: &[u8]) -> usize {
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, &x) in a.iter().enumerate() {
+        curr[0] = i + 1;
+
+        for (j, &y) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(x != y);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
+
+/// Clusters UMIs that are within an edit distance of each other.
+///
+/// `umis` is a list of distinct UMIs and their read counts. Starting from the most frequent,
+/// each UMI not yet in a cluster starts a new cluster and absorbs all remaining UMIs within
+/// `max_distance` edits. Returned clusters list indices into `umis`, with the most frequent UMI
+/// first.
+///
+/// # Examples
+///
+/// ```
+/// use fq::dedup::cluster_umis;
+///
+/// let umis = [(&b"ACGT"[..], 1), (&b"ACGA"[..], 8), (&b"TTTT"[..], 2)];
+/// assert_eq!(cluster_umis(&umis, 1), [vec![1, 0], vec![2]]);
+/// ```
+pub fn cluster_umis(umis: &[(&[u8], usize)], max_distance: usize) -> Vec<Vec<usize>> {
+    let mut order: Vec<usize> = (0..umis.len()).collect();
+    order.sort_by(|&a, &b| {
+        umis[b]
+            .1
+            .cmp(&umis[a].1)
+            .then_with(|| umis[a].0.cmp(umis[b].0))
+    });
+
+    let mut is_clustered = vec![false; umis.len()];
+    let mut clusters = Vec::new();
+
+    for &i in &order {
+        if is_clustered[i] {
+            continue;
+        }
+
+        is_clustered[i] = true;
+        let mut cluster = vec![i];
+
+        for &j in &order {
+            if !is_clustered[j] && edit_distance(umis[i].0, umis[j].0) <= max_distance {
+                is_clustered[j] = true;
+                cluster.push(j);
+            }
+        }
+
+        clusters.push(cluster);
+    }
+
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract() {
+        let record = Record::new("@fqlib:1:ACGT 1:N:0", "TTGCA", "+", "FQLIB");
+        assert_eq!(UmiSource::Name.extract(&record), Some((&b"ACGT"[..], 0)));
+        assert_eq!(
+            UmiSource::Sequence(5).extract(&record),
+            Some((&b"TTGCA"[..], 5))
+        );
+        assert_eq!(UmiSource::Sequence(6).extract(&record), None);
+
+        let record = Record::new("@fqlib", "TTGCA", "+", "FQLIB");
+        assert_eq!(UmiSource::Name.extract(&record), None);
+
+        let record = Record::new("@fqlib:", "TTGCA", "+", "FQLIB");
+        assert_eq!(UmiSource::Name.extract(&record), None);
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("name".parse(), Ok(UmiSource::Name));
+        assert_eq!("sequence:8".parse(), Ok(UmiSource::Sequence(8)));
+
+        assert_eq!(
+            "sequence:0".parse::<UmiSource>(),
+            Err(ParseUmiSourceError(String::from("sequence:0")))
+        );
+        assert!("sequence".parse::<UmiSource>().is_err());
+        assert!("".parse::<UmiSource>().is_err());
+    }
+
+    #[test]
+    fn test_move_umi_to_name() {
+        let mut records = [Record::new("@r0 1:N:0", "ACGTT", "+", "FQLIB")];
+        assert_eq!(move_umi_to_name(&mut records, 0, 2), Some(b"AC".to_vec()));
+        assert_eq!(records[0].name(), b"@r0:AC 1:N:0");
+        assert_eq!(records[0].sequence(), b"GTT");
+        assert_eq!(records[0].quality_scores(), b"LIB");
+        assert_eq!(UmiSource::Name.extract(&records[0]), Some((&b"AC"[..], 0)));
+
+        let mut records = [
+            Record::new("@r0/1", "AC", "+", "II"),
+            Record::new("@r0/2", "GTTA", "+", "IIII"),
+        ];
+        assert_eq!(move_umi_to_name(&mut records, 1, 3), Some(b"GTT".to_vec()));
+        assert_eq!(records[0].name(), b"@r0:GTT/1");
+        assert_eq!(records[0].sequence(), b"AC");
+        assert_eq!(records[1].name(), b"@r0:GTT/2");
+        assert_eq!(records[1].sequence(), b"A");
+
+        assert_eq!(move_umi_to_name(&mut records, 0, 3), None);
+        assert_eq!(records[0].sequence(), b"AC");
+        assert_eq!(move_umi_to_name(&mut records, 2, 1), None);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance(b"ACGT", b"ACGT"), 0);
+        assert_eq!(edit_distance(b"ACGT", b"ACCT"), 1);
+        assert_eq!(edit_distance(b"ACGT", b"CGT"), 1);
+        assert_eq!(edit_distance(b"ACGT", b"TGCA"), 4);
+        assert_eq!(edit_distance(b"", b"ACG"), 3);
+    }
+
+    #[test]
+    fn test_cluster_umis() {
+        let umis = [
+            (&b"AAAA"[..], 2),
+            (&b"AAAT"[..], 10),
+            (&b"AATT"[..], 1),
+            (&b"GGGG"[..], 4),
+        ];
+
+        assert_eq!(cluster_umis(&umis, 0), [vec![1], vec![3], vec![0], vec![2]]);
+        assert_eq!(cluster_umis(&umis, 1), [vec![1, 0, 2], vec![3]]);
+    }
+}
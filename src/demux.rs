@@ -0,0 +1,11 @@
+//! Barcode demultiplexing.
+
+mod demultiplexer;
+mod matcher;
+mod samples;
+
+pub use self::{
+    demultiplexer::Demultiplexer,
+    matcher::{CollisionError, Matcher, NMode, ParseNModeError},
+    samples::{read_samples, Sample, MAX_BARCODE_COUNT},
+};
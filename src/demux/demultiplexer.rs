@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use super::{CollisionError, Matcher, NMode, Sample};
+
+/// A sample assigner from one or more observed barcodes.
+///
+/// Each barcode position, e.g., i7 and i5, has its own [`Matcher`] of the distinct expected
+/// barcodes at that position, so the mismatch allowance applies to each barcode separately, as in
+/// bcl2fastq. A read is assigned to a sample only when every barcode matches and the combination
+/// belongs to a sample.
+///
+/// # Examples
+///
+/// ```
+/// use fq::demux::{Demultiplexer, NMode, Sample};
+///
+/// let samples = [
+///     Sample::new("s1", vec![b"AAAA".to_vec(), b"CCCC".to_vec()]),
+///     Sample::new("s2", vec![b"AAAA".to_vec(), b"GGGG".to_vec()]),
+/// ];
+///
+/// let demultiplexer = Demultiplexer::new(&samples, 1, NMode::Mismatch)?;
+///
+/// assert_eq!(demultiplexer.find(&[b"AAAT", b"GGGG"]), Some(1));
+/// assert_eq!(demultiplexer.find(&[b"AAAA", b"TTTT"]), None);
+/// # Ok::<(), fq::demux::CollisionError>(())
+/// ```
+#[derive(Debug)]
+pub struct Demultiplexer {
+    matchers: Vec<Matcher>,
+    max_lens: Vec<usize>,
+    samples: HashMap<Vec<usize>, usize>,
+}
+
+impl Demultiplexer {
+    /// Creates a demultiplexer from a list of samples with the same number of barcodes.
+    ///
+    /// This returns an error if barcodes at the same position collide or two samples have the
+    /// same barcodes.
+    pub fn new(
+        samples: &[Sample],
+        max_mismatches: usize,
+        n_mode: NMode,
+    ) -> Result<Self, CollisionError> {
+        let barcode_count = samples.first().map(|s| s.barcodes().len()).unwrap_or(0);
+
+        let mut matchers = Vec::with_capacity(barcode_count);
+        let mut max_lens = Vec::with_capacity(barcode_count);
+
+        for i in 0..barcode_count {
+            let mut barcodes: Vec<Vec<u8>> = Vec::new();
+
+            for sample in samples {
+                let barcode = &sample.barcodes()[i];
+
+                if !barcodes.contains(barcode) {
+                    barcodes.push(barcode.clone());
+                }
+            }
+
+            max_lens.push(barcodes.iter().map(|b| b.len()).max().unwrap_or(0));
+            matchers.push(Matcher::new(barcodes, max_mismatches, n_mode)?);
+        }
+
+        let mut sample_ids = HashMap::with_capacity(samples.len());
+
+        for (i, sample) in samples.iter().enumerate() {
+            let key: Vec<usize> = sample
+                .barcodes()
+                .iter()
+                .zip(&matchers)
+                .map(|(barcode, matcher)| matcher.find(barcode).expect("missing barcode"))
+                .collect();
+
+            if let Some(j) = sample_ids.insert(key, i) {
+                let barcodes = samples[j].barcodes().join(&b'+');
+
+                return Err(CollisionError {
+                    a: barcodes.clone(),
+                    b: barcodes,
+                    max_mismatches,
+                });
+            }
+        }
+
+        Ok(Self {
+            matchers,
+            max_lens,
+            samples: sample_ids,
+        })
+    }
+
+    /// Returns the number of barcodes of each sample.
+    pub fn barcode_count(&self) -> usize {
+        self.matchers.len()
+    }
+
+    /// Returns the index of the sample with barcodes matching all observed barcodes.
+    ///
+    /// Only the leading bases of each observed barcode are compared, e.g., the start of an index
+    /// read or read 1 for inline barcodes.
+    pub fn find<B>(&self, observed: &[B]) -> Option<usize>
+    where
+        B: AsRef<[u8]>,
+    {
+        if observed.len() != self.matchers.len() {
+            return None;
+        }
+
+        let key = observed
+            .iter()
+            .zip(&self.matchers)
+            .zip(&self.max_lens)
+            .map(|((barcode, matcher), &max_len)| {
+                let barcode = barcode.as_ref();
+                let end = max_len.min(barcode.len());
+                matcher.find(&barcode[..end])
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        self.samples.get(&key).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let samples = [
+            Sample::new("s1", vec![b"AAAA".to_vec()]),
+            Sample::new("s2", vec![b"AAAT".to_vec()]),
+        ];
+
+        assert!(Demultiplexer::new(&samples, 0, NMode::Mismatch).is_ok());
+        assert!(Demultiplexer::new(&samples, 1, NMode::Mismatch).is_err());
+
+        let samples = [
+            Sample::new("s1", vec![b"AAAA".to_vec(), b"CCCC".to_vec()]),
+            Sample::new("s2", vec![b"AAAA".to_vec(), b"CCCC".to_vec()]),
+        ];
+
+        assert_eq!(
+            Demultiplexer::new(&samples, 0, NMode::Mismatch).unwrap_err(),
+            CollisionError {
+                a: b"AAAA+CCCC".to_vec(),
+                b: b"AAAA+CCCC".to_vec(),
+                max_mismatches: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_find() -> Result<(), CollisionError> {
+        let samples = [
+            Sample::new("s1", vec![b"AAAAAA".to_vec()]),
+            Sample::new("s2", vec![b"CCCCCC".to_vec()]),
+        ];
+
+        let demultiplexer = Demultiplexer::new(&samples, 1, NMode::Mismatch)?;
+        assert_eq!(demultiplexer.barcode_count(), 1);
+
+        assert_eq!(demultiplexer.find(&[b"CCCCCCGTACGT"]), Some(1));
+        assert_eq!(demultiplexer.find(&[b"CCCCCA"]), Some(1));
+        assert_eq!(demultiplexer.find(&[b"CCCCAA"]), None);
+        assert_eq!(demultiplexer.find(&[b"CCCCC"]), None);
+        assert_eq!(demultiplexer.find(&[b"CCCCCC", b"CCCCCC"]), None);
+
+        Ok(())
+    }
+}
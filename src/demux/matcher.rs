@@ -0,0 +1,282 @@
+use std::{collections::HashMap, error, fmt, str::FromStr};
+
+/// How an `N` in an observed barcode is compared to an expected base.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NMode {
+    /// An `N` counts as a mismatch. This matches the behavior of bcl2fastq.
+    #[default]
+    Mismatch,
+    /// An `N` matches any base and does not count against the mismatch budget.
+    Wildcard,
+}
+
+/// An error returned when an `N` mode fails to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseNModeError(String);
+
+impl error::Error for ParseNModeError {}
+
+impl fmt::Display for ParseNModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid N mode: expected mismatch or wildcard, got '{}'",
+            self.0
+        )
+    }
+}
+
+impl FromStr for NMode {
+    type Err = ParseNModeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mismatch" => Ok(Self::Mismatch),
+            "wildcard" => Ok(Self::Wildcard),
+            _ => Err(ParseNModeError(s.into())),
+        }
+    }
+}
+
+/// An error returned when two barcodes are too similar to be told apart.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CollisionError {
+    pub a: Vec<u8>,
+    pub b: Vec<u8>,
+    pub max_mismatches: usize,
+}
+
+impl error::Error for CollisionError {}
+
+impl fmt::Display for CollisionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "barcodes {} and {} collide with {} mismatch(es) allowed",
+            String::from_utf8_lossy(&self.a),
+            String::from_utf8_lossy(&self.b),
+            self.max_mismatches,
+        )
+    }
+}
+
+/// A barcode matcher with a mismatch allowance.
+///
+/// An observed barcode is assigned to an expected barcode only when exactly one expected barcode
+/// is within the mismatch budget. Barcodes that are close enough to each other that an observed
+/// barcode could match both are rejected when building the matcher, as in bcl2fastq.
+///
+/// # Examples
+///
+/// ```
+/// use fq::demux::{Matcher, NMode};
+///
+/// let barcodes = vec![b"ACGTACGT".to_vec(), b"TTTTGGGG".to_vec()];
+/// let matcher = Matcher::new(barcodes, 1, NMode::Mismatch)?;
+///
+/// assert_eq!(matcher.find(b"ACGTACGT"), Some(0));
+/// assert_eq!(matcher.find(b"ACGTACGA"), Some(0));
+/// assert_eq!(matcher.find(b"TTTTGGCC"), None);
+/// # Ok::<(), fq::demux::CollisionError>(())
+/// ```
+#[derive(Debug)]
+pub struct Matcher {
+    barcodes: Vec<Vec<u8>>,
+    exact: HashMap<Vec<u8>, usize>,
+    max_mismatches: usize,
+    n_mode: NMode,
+}
+
+impl Matcher {
+    /// Creates a matcher from a list of expected barcodes.
+    ///
+    /// This returns an error if any two barcodes are within `2 * max_mismatches` of each other,
+    /// i.e., an observed barcode could be ambiguously assigned.
+    pub fn new(
+        barcodes: Vec<Vec<u8>>,
+        max_mismatches: usize,
+        n_mode: NMode,
+    ) -> Result<Self, CollisionError> {
+        for (i, a) in barcodes.iter().enumerate() {
+            for b in &barcodes[i + 1..] {
+                if hamming_distance(a, b) <= 2 * max_mismatches {
+                    return Err(CollisionError {
+                        a: a.clone(),
+                        b: b.clone(),
+                        max_mismatches,
+                    });
+                }
+            }
+        }
+
+        let exact = barcodes
+            .iter()
+            .enumerate()
+            .map(|(i, barcode)| (barcode.clone(), i))
+            .collect();
+
+        Ok(Self {
+            barcodes,
+            exact,
+            max_mismatches,
+            n_mode,
+        })
+    }
+
+    pub fn barcodes(&self) -> &[Vec<u8>] {
+        &self.barcodes
+    }
+
+    pub fn max_mismatches(&self) -> usize {
+        self.max_mismatches
+    }
+
+    pub fn n_mode(&self) -> NMode {
+        self.n_mode
+    }
+
+    /// Returns the index of the single expected barcode within the mismatch budget.
+    ///
+    /// If no barcodes or more than one barcode is within the budget, this returns `None`.
+    pub fn find(&self, observed: &[u8]) -> Option<usize> {
+        if let Some(&i) = self.exact.get(observed) {
+            return Some(i);
+        }
+
+        let mut candidate = None;
+
+        for (i, barcode) in self.barcodes.iter().enumerate() {
+            if self.is_match(barcode, observed) {
+                if candidate.is_some() {
+                    return None;
+                }
+
+                candidate = Some(i);
+            }
+        }
+
+        candidate
+    }
+
+    fn is_match(&self, expected: &[u8], observed: &[u8]) -> bool {
+        if observed.len() < expected.len() {
+            return false;
+        }
+
+        let mut mismatches = 0;
+
+        for (&a, &b) in expected.iter().zip(observed) {
+            let is_mismatch = match (self.n_mode, b) {
+                (NMode::Wildcard, b'N' | b'n') => false,
+                _ => !a.eq_ignore_ascii_case(&b),
+            };
+
+            if is_mismatch {
+                mismatches += 1;
+
+                if mismatches > self.max_mismatches {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+fn hamming_distance(a: &[u8], b: &[u8]) -> usize {
+    let len_diff = if a.len() > b.len() {
+        a.len() - b.len()
+    } else {
+        b.len() - a.len()
+    };
+
+    a.iter()
+        .zip(b)
+        .filter(|(x, y)| !x.eq_ignore_ascii_case(y))
+        .count()
+        + len_diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_n_mode_from_str() {
+        assert_eq!("mismatch".parse(), Ok(NMode::Mismatch));
+        assert_eq!("wildcard".parse(), Ok(NMode::Wildcard));
+        assert_eq!(
+            "ignore".parse::<NMode>(),
+            Err(ParseNModeError(String::from("ignore")))
+        );
+    }
+
+    #[test]
+    fn test_new() {
+        let barcodes = vec![b"ACGT".to_vec(), b"TGCA".to_vec()];
+        assert!(Matcher::new(barcodes.clone(), 1, NMode::Mismatch).is_ok());
+
+        let barcodes = vec![b"ACGT".to_vec(), b"ACGA".to_vec()];
+        assert_eq!(
+            Matcher::new(barcodes.clone(), 1, NMode::Mismatch).unwrap_err(),
+            CollisionError {
+                a: b"ACGT".to_vec(),
+                b: b"ACGA".to_vec(),
+                max_mismatches: 1,
+            }
+        );
+        assert!(Matcher::new(barcodes, 0, NMode::Mismatch).is_ok());
+    }
+
+    #[test]
+    fn test_find() -> Result<(), CollisionError> {
+        let barcodes = vec![b"AAAAAA".to_vec(), b"CCCCCC".to_vec(), b"GGGGGG".to_vec()];
+
+        let matcher = Matcher::new(barcodes.clone(), 0, NMode::Mismatch)?;
+        assert_eq!(matcher.find(b"CCCCCC"), Some(1));
+        assert_eq!(matcher.find(b"CCCCCA"), None);
+
+        let matcher = Matcher::new(barcodes, 1, NMode::Mismatch)?;
+        assert_eq!(matcher.find(b"CCCCCA"), Some(1));
+        assert_eq!(matcher.find(b"ccccca"), Some(1));
+        assert_eq!(matcher.find(b"CCCCAA"), None);
+        assert_eq!(matcher.find(b"CCCCCN"), Some(1));
+        assert_eq!(matcher.find(b"CCCCNN"), None);
+        assert_eq!(matcher.find(b"CCCCC"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_with_ambiguous_match() -> Result<(), CollisionError> {
+        // These collide at 1 mismatch but can be built with 0, so force an ambiguous lookup by
+        // allowing a wildcard.
+        let barcodes = vec![b"AAAA".to_vec(), b"AAAC".to_vec()];
+        let matcher = Matcher::new(barcodes, 0, NMode::Wildcard)?;
+        assert_eq!(matcher.find(b"AAAN"), None);
+        assert_eq!(matcher.find(b"AAAC"), Some(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_with_wildcard() -> Result<(), CollisionError> {
+        let barcodes = vec![b"AAAAAA".to_vec(), b"CCCCCC".to_vec()];
+        let matcher = Matcher::new(barcodes, 1, NMode::Wildcard)?;
+
+        assert_eq!(matcher.find(b"CCCCNN"), Some(1));
+        assert_eq!(matcher.find(b"CCCNNA"), Some(1));
+        assert_eq!(matcher.find(b"NNNNNN"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hamming_distance() {
+        assert_eq!(hamming_distance(b"ACGT", b"ACGT"), 0);
+        assert_eq!(hamming_distance(b"ACGT", b"acgt"), 0);
+        assert_eq!(hamming_distance(b"ACGT", b"ACGA"), 1);
+        assert_eq!(hamming_distance(b"ACGT", b"ACG"), 1);
+    }
+}
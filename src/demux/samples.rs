@@ -0,0 +1,143 @@
+use std::{
+    collections::HashSet,
+    io::{self, BufRead},
+};
+
+const COMMENT_PREFIX: char = '#';
+const FIELD_DELIMITER: char = '\t';
+
+/// The maximum number of barcodes of a sample, i.e., dual indexes.
+pub const MAX_BARCODE_COUNT: usize = 2;
+
+/// A sample and its expected barcodes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Sample {
+    name: String,
+    barcodes: Vec<Vec<u8>>,
+}
+
+impl Sample {
+    pub fn new<S>(name: S, barcodes: Vec<Vec<u8>>) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            barcodes,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the barcodes of the sample, e.g., the i7 and i5 indexes.
+    pub fn barcodes(&self) -> &[Vec<u8>] {
+        &self.barcodes
+    }
+}
+
+/// Reads a list of samples from a tab-delimited sample sheet.
+///
+/// Each line is a sample name followed by one or two barcodes. Empty lines and lines starting
+/// with `#` are skipped. All samples must have the same number of barcodes, and names must be
+/// unique.
+///
+/// # Examples
+///
+/// ```
+/// use fq::demux::{read_samples, Sample};
+///
+/// let data = b"# name\tbarcode\nsample_1\tACGTACGT\nsample_2\tTTTTGGGG\n";
+/// let samples = read_samples(&data[..])?;
+///
+/// assert_eq!(samples, [
+///     Sample::new("sample_1", vec![b"ACGTACGT".to_vec()]),
+///     Sample::new("sample_2", vec![b"TTTTGGGG".to_vec()]),
+/// ]);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn read_samples<R>(reader: R) -> io::Result<Vec<Sample>>
+where
+    R: BufRead,
+{
+    let mut samples: Vec<Sample> = Vec::new();
+    let mut names = HashSet::new();
+
+    for (i, result) in reader.lines().enumerate() {
+        let line = result?;
+
+        if line.is_empty() || line.starts_with(COMMENT_PREFIX) {
+            continue;
+        }
+
+        let invalid_line = |message: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid sample sheet line {}: {}", i + 1, message),
+            )
+        };
+
+        let mut fields = line.split(FIELD_DELIMITER);
+
+        let name = fields
+            .next()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| invalid_line("missing sample name"))?;
+
+        let barcodes: Vec<Vec<u8>> = fields.map(|s| s.as_bytes().to_vec()).collect();
+
+        if barcodes.is_empty() || barcodes.len() > MAX_BARCODE_COUNT {
+            return Err(invalid_line("expected 1 or 2 barcodes"));
+        }
+
+        if barcodes.iter().any(|barcode| barcode.is_empty()) {
+            return Err(invalid_line("empty barcode"));
+        }
+
+        if let Some(first) = samples.first() {
+            if first.barcodes().len() != barcodes.len() {
+                return Err(invalid_line("samples have different numbers of barcodes"));
+            }
+        }
+
+        if !names.insert(name.to_string()) {
+            return Err(invalid_line("duplicate sample name"));
+        }
+
+        samples.push(Sample::new(name, barcodes));
+    }
+
+    Ok(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_samples() -> io::Result<()> {
+        let data = b"s1\tACGT\tTTTT\r\n\ns2\tGGGG\tTTTT\n";
+        let samples = read_samples(&data[..])?;
+
+        assert_eq!(
+            samples,
+            [
+                Sample::new("s1", vec![b"ACGT".to_vec(), b"TTTT".to_vec()]),
+                Sample::new("s2", vec![b"GGGG".to_vec(), b"TTTT".to_vec()]),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_samples_with_invalid_lines() {
+        assert!(read_samples(&b"s1\n"[..]).is_err());
+        assert!(read_samples(&b"\tACGT\n"[..]).is_err());
+        assert!(read_samples(&b"s1\tACGT\t\n"[..]).is_err());
+        assert!(read_samples(&b"s1\tA\tC\tG\n"[..]).is_err());
+        assert!(read_samples(&b"s1\tACGT\ns2\tGGGG\tTTTT\n"[..]).is_err());
+        assert!(read_samples(&b"s1\tACGT\ns1\tGGGG\n"[..]).is_err());
+    }
+}
@@ -17,6 +17,7 @@ use rand::{
 /// let s = String::from_utf8(bytes).unwrap();
 /// println!("{}", s); // e.g., "TCCTCGAG"
 /// ```
+#[derive(Clone)]
 pub struct Character {
     alphabet: &'static [u8],
     range: Uniform<usize>,
@@ -0,0 +1,66 @@
+mod qual_reader;
+mod reader;
+mod record;
+mod writer;
+
+pub use self::{qual_reader::QualReader, reader::Reader, record::Record, writer::Writer};
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use flate2::{bufread::MultiGzDecoder, write::GzEncoder, Compression};
+
+pub fn create<P>(dst: P) -> io::Result<Writer<Box<dyn Write>>>
+where
+    P: AsRef<Path>,
+{
+    let path = dst.as_ref();
+    let extension = path.extension();
+    let file = File::create(path)?;
+    let writer = BufWriter::new(file);
+
+    match extension.and_then(|ext| ext.to_str()) {
+        Some("gz") => {
+            let level = Compression::default();
+            let encoder = GzEncoder::new(writer, level);
+            Ok(Writer::new(Box::new(encoder)))
+        }
+        _ => Ok(Writer::new(Box::new(writer))),
+    }
+}
+
+pub fn open<P>(src: P) -> io::Result<Reader<Box<dyn BufRead>>>
+where
+    P: AsRef<Path>,
+{
+    open_inner(src).map(Reader::new)
+}
+
+/// Opens a QUAL file, the companion of a FASTA file for quality scores.
+pub fn open_qual<P>(src: P) -> io::Result<QualReader<Box<dyn BufRead>>>
+where
+    P: AsRef<Path>,
+{
+    open_inner(src).map(QualReader::new)
+}
+
+fn open_inner<P>(src: P) -> io::Result<Box<dyn BufRead>>
+where
+    P: AsRef<Path>,
+{
+    let path = src.as_ref();
+    let extension = path.extension();
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+
+    match extension.and_then(|ext| ext.to_str()) {
+        Some("gz") => {
+            let decoder = MultiGzDecoder::new(reader);
+            Ok(Box::new(BufReader::new(decoder)))
+        }
+        _ => Ok(Box::new(reader)),
+    }
+}
@@ -0,0 +1,167 @@
+use std::io::{self, BufRead};
+
+use super::{
+    reader::{read_line, DEFINITION_PREFIX},
+    Record,
+};
+use crate::quality::Encoding;
+
+const PHRED_OFFSET: u8 = Encoding::Phred33.offset();
+const MAX_SCORE: u8 = b'~' - PHRED_OFFSET;
+
+/// A reader of QUAL files, the companion format of FASTA for quality scores.
+///
+/// A QUAL record has a definition line, like FASTA, followed by whitespace-separated numeric
+/// quality scores, which may span multiple lines.
+pub struct QualReader<R>
+where
+    R: BufRead,
+{
+    inner: R,
+    buf: Vec<u8>,
+}
+
+impl<R> QualReader<R>
+where
+    R: BufRead,
+{
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Reads a single record.
+    ///
+    /// The quality scores are stored as the record sequence, encoded as Phred+33 characters.
+    ///
+    /// This returns the number of bytes read, or 0 at EOF.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::fasta::{QualReader, Record};
+    ///
+    /// let data = b">sq0\n40 40 30\n20 0\n";
+    /// let mut reader = QualReader::new(&data[..]);
+    ///
+    /// let mut record = Record::default();
+    /// reader.read_record(&mut record)?;
+    ///
+    /// assert_eq!(record, Record::new("sq0", "II?5!"));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn read_record(&mut self, record: &mut Record) -> io::Result<usize> {
+        record.clear();
+
+        let mut len = match read_line(&mut self.inner, record.name_mut()) {
+            Ok(0) => return Ok(0),
+            Ok(n) => n,
+            Err(e) => return Err(e),
+        };
+
+        if record.name().first() != Some(&DEFINITION_PREFIX) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid QUAL definition line: missing '>' prefix",
+            ));
+        }
+
+        record.name_mut().remove(0);
+
+        loop {
+            match self.inner.fill_buf()?.first() {
+                None | Some(&DEFINITION_PREFIX) => break,
+                Some(_) => {
+                    self.buf.clear();
+                    len += read_line(&mut self.inner, &mut self.buf)?;
+                    parse_scores(&self.buf, record.sequence_mut())?;
+                }
+            }
+        }
+
+        Ok(len)
+    }
+}
+
+// Parses a line of numeric quality scores and appends them as Phred+33 characters.
+fn parse_scores(line: &[u8], dst: &mut Vec<u8>) -> io::Result<()> {
+    let invalid_score = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "invalid QUAL score: expected 0..={}, got '{}'",
+                MAX_SCORE,
+                String::from_utf8_lossy(line)
+            ),
+        )
+    };
+
+    for token in line.split(|b| b.is_ascii_whitespace()) {
+        if token.is_empty() {
+            continue;
+        }
+
+        let score: u8 = std::str::from_utf8(token)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&n| n <= MAX_SCORE)
+            .ok_or_else(invalid_score)?;
+
+        dst.push(score + PHRED_OFFSET);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_record() -> io::Result<()> {
+        let data = b"\
+>sq0 LN:4
+40 30
+  20\t10\r
+>sq1
+
+>sq2
+93
+";
+
+        let mut reader = QualReader::new(&data[..]);
+        let mut record = Record::default();
+
+        reader.read_record(&mut record)?;
+        assert_eq!(record, Record::new("sq0 LN:4", "I?5+"));
+
+        reader.read_record(&mut record)?;
+        assert_eq!(record, Record::new("sq1", ""));
+
+        reader.read_record(&mut record)?;
+        assert_eq!(record, Record::new("sq2", "~"));
+
+        assert_eq!(reader.read_record(&mut record)?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_record_with_invalid_scores() {
+        let mut record = Record::default();
+
+        let data = b">sq0\n40 94\n";
+        let mut reader = QualReader::new(&data[..]);
+        assert!(reader.read_record(&mut record).is_err());
+
+        let data = b">sq0\n40 -1\n";
+        let mut reader = QualReader::new(&data[..]);
+        assert!(reader.read_record(&mut record).is_err());
+
+        let data = b"sq0\n40\n";
+        let mut reader = QualReader::new(&data[..]);
+        assert!(reader.read_record(&mut record).is_err());
+    }
+}
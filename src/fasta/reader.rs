@@ -0,0 +1,107 @@
+use std::io::{self, BufRead};
+
+use super::Record;
+
+pub(super) const DEFINITION_PREFIX: u8 = b'>';
+const LINE_FEED: u8 = b'\n';
+const CARRIAGE_RETURN: u8 = b'\r';
+
+pub struct Reader<R>
+where
+    R: BufRead,
+{
+    inner: R,
+}
+
+impl<R> Reader<R>
+where
+    R: BufRead,
+{
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    /// Reads a single record, joining sequence lines.
+    ///
+    /// This returns the number of bytes read, or 0 at EOF.
+    pub fn read_record(&mut self, record: &mut Record) -> io::Result<usize> {
+        record.clear();
+
+        let mut len = match read_line(&mut self.inner, record.name_mut()) {
+            Ok(0) => return Ok(0),
+            Ok(n) => n,
+            Err(e) => return Err(e),
+        };
+
+        if record.name().first() != Some(&DEFINITION_PREFIX) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid FASTA definition line: missing '>' prefix",
+            ));
+        }
+
+        record.name_mut().remove(0);
+
+        loop {
+            match self.inner.fill_buf()?.first() {
+                None | Some(&DEFINITION_PREFIX) => break,
+                Some(_) => len += read_line(&mut self.inner, record.sequence_mut())?,
+            }
+        }
+
+        Ok(len)
+    }
+}
+
+// Appends a line to the buffer, without the line ending.
+pub(super) fn read_line<R: BufRead>(reader: &mut R, buf: &mut Vec<u8>) -> io::Result<usize> {
+    let start = buf.len();
+    let n = reader.read_until(LINE_FEED, buf)?;
+
+    if buf[start..].ends_with(&[LINE_FEED]) {
+        buf.pop();
+
+        if buf[start..].ends_with(&[CARRIAGE_RETURN]) {
+            buf.pop();
+        }
+    }
+
+    Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_record() -> io::Result<()> {
+        let data = b"\
+>sq0 LN:8
+ACGT
+ACGT
+>sq1
+TTGG\r
+";
+
+        let mut reader = Reader::new(&data[..]);
+        let mut record = Record::default();
+
+        reader.read_record(&mut record)?;
+        assert_eq!(record, Record::new("sq0 LN:8", "ACGTACGT"));
+
+        reader.read_record(&mut record)?;
+        assert_eq!(record, Record::new("sq1", "TTGG"));
+
+        assert_eq!(reader.read_record(&mut record)?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_record_with_invalid_definition() {
+        let data = b"sq0\nACGT\n";
+        let mut reader = Reader::new(&data[..]);
+        let mut record = Record::default();
+        assert!(reader.read_record(&mut record).is_err());
+    }
+}
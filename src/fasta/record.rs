@@ -0,0 +1,77 @@
+/// A FASTA record.
+///
+/// The name is the definition line without the leading `>`.
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+pub struct Record {
+    name: Vec<u8>,
+    sequence: Vec<u8>,
+}
+
+impl Record {
+    pub fn new<S, T>(name: S, sequence: T) -> Self
+    where
+        S: Into<Vec<u8>>,
+        T: Into<Vec<u8>>,
+    {
+        Self {
+            name: name.into(),
+            sequence: sequence.into(),
+        }
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    pub fn name_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.name
+    }
+
+    /// Returns the name up to the first whitespace, i.e., without the description.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::fasta::Record;
+    ///
+    /// let record = Record::new("sq0 LN:8", "ACGTACGT");
+    /// assert_eq!(record.id(), b"sq0");
+    /// ```
+    pub fn id(&self) -> &[u8] {
+        let end = self
+            .name
+            .iter()
+            .position(|b| b.is_ascii_whitespace())
+            .unwrap_or(self.name.len());
+
+        &self.name[..end]
+    }
+
+    pub fn sequence(&self) -> &[u8] {
+        &self.sequence
+    }
+
+    pub fn sequence_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.sequence
+    }
+
+    pub fn clear(&mut self) {
+        self.name.clear();
+        self.sequence.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clear() {
+        let mut record = Record::new("sq0", "ACGT");
+
+        record.clear();
+
+        assert!(record.name().is_empty());
+        assert!(record.sequence().is_empty());
+    }
+}
@@ -0,0 +1,139 @@
+use std::io::{self, Write};
+
+use super::Record;
+use crate::metrics::Metrics;
+
+const DEFINITION_PREFIX: u8 = b'>';
+const LINE_FEED: u8 = b'\n';
+
+pub struct Writer<W> {
+    inner: W,
+    line_width: usize,
+    metrics: Option<Metrics>,
+}
+
+impl<W> Writer<W>
+where
+    W: Write,
+{
+    /// Creates a FASTA writer that writes each sequence on a single line.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            line_width: 0,
+            metrics: None,
+        }
+    }
+
+    /// Sets the maximum number of bases per sequence line.
+    ///
+    /// A line width of 0 disables wrapping.
+    pub fn set_line_width(&mut self, line_width: usize) {
+        self.line_width = line_width;
+    }
+
+    /// Sets the metrics to update on each record written.
+    pub fn set_metrics(&mut self, metrics: Metrics) {
+        self.metrics = Some(metrics);
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Writes a single record, wrapping the sequence at the line width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::fasta::{Record, Writer};
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.set_line_width(4);
+    ///
+    /// let record = Record::new("sq0", "ACGTACGTAC");
+    /// writer.write_record(&record)?;
+    ///
+    /// assert_eq!(writer.get_ref(), b">sq0\nACGT\nACGT\nAC\n");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_record(&mut self, record: &Record) -> io::Result<()> {
+        self.inner.write_all(&[DEFINITION_PREFIX])?;
+        self.inner.write_all(record.name())?;
+        self.inner.write_all(&[LINE_FEED])?;
+
+        let sequence = record.sequence();
+
+        let line_count = if sequence.is_empty() {
+            0
+        } else if self.line_width == 0 {
+            self.inner.write_all(sequence)?;
+            self.inner.write_all(&[LINE_FEED])?;
+            1
+        } else {
+            let mut n = 0;
+
+            for line in sequence.chunks(self.line_width) {
+                self.inner.write_all(line)?;
+                self.inner.write_all(&[LINE_FEED])?;
+                n += 1;
+            }
+
+            n
+        };
+
+        if let Some(metrics) = &self.metrics {
+            let len = record.name().len() + sequence.len() + line_count + 2;
+
+            metrics.add_records_written(1);
+            metrics.add_bytes_written(len as u64);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_record() -> io::Result<()> {
+        let mut writer = Writer::new(Vec::new());
+
+        writer.write_record(&Record::new("sq0 LN:8", "ACGTACGT"))?;
+        writer.write_record(&Record::new("sq1", ""))?;
+
+        assert_eq!(writer.get_ref(), b">sq0 LN:8\nACGTACGT\n>sq1\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_record_with_line_width() -> io::Result<()> {
+        let mut writer = Writer::new(Vec::new());
+        writer.set_line_width(4);
+
+        writer.write_record(&Record::new("sq0", "ACGTACGT"))?;
+
+        assert_eq!(writer.get_ref(), b">sq0\nACGT\nACGT\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_record_with_metrics() -> io::Result<()> {
+        let metrics = Metrics::new();
+
+        let mut writer = Writer::new(Vec::new());
+        writer.set_line_width(4);
+        writer.set_metrics(metrics.clone());
+
+        writer.write_record(&Record::new("sq0", "ACGTACGTAC"))?;
+
+        assert_eq!(metrics.records_written(), 1);
+        assert_eq!(metrics.bytes_written(), writer.get_ref().len() as u64);
+
+        Ok(())
+    }
+}
@@ -1,34 +1,76 @@
+mod follow_reader;
+mod gz_reader;
+pub(crate) mod name;
+mod parallel_gz_writer;
 mod reader;
 mod record;
+mod validating_writer;
 mod writer;
 
-pub use self::{reader::Reader, record::Record, writer::Writer};
+pub use self::{
+    follow_reader::FollowReader,
+    name::name_id,
+    reader::Reader,
+    record::Record,
+    validating_writer::{ValidatingWriter, WriteError},
+    writer::Writer,
+};
 
 use std::{
     fs::File,
-    io::{self, BufRead, BufReader, BufWriter, Write},
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
     path::Path,
+    time::Duration,
 };
 
-use flate2::{bufread::MultiGzDecoder, write::GzEncoder, Compression};
+use flate2::{write::GzEncoder, Compression};
+
+use self::{gz_reader::GzReader, parallel_gz_writer::ParallelGzWriter};
+use crate::metrics::{CountingReader, Metrics};
 
 pub fn create<P>(dst: P) -> io::Result<Writer<Box<dyn Write>>>
+where
+    P: AsRef<Path>,
+{
+    create_with_compression(dst, Compression::default(), 1)
+}
+
+/// Creates a FASTQ file that is gzipped with the given compression level if the path ends in
+/// `.gz`.
+///
+/// With more than one compression thread, blocks of the output are compressed in parallel into
+/// independent gzip members, which readers of multi-member gzip files, e.g., [`open`], read as
+/// one stream.
+pub fn create_with_compression<P>(
+    dst: P,
+    level: Compression,
+    threads: usize,
+) -> io::Result<Writer<Box<dyn Write>>>
 where
     P: AsRef<Path>,
 {
     let path = dst.as_ref();
-    let extension = path.extension();
     let file = File::create(path)?;
     let writer = BufWriter::new(file);
 
-    match extension.and_then(|ext| ext.to_str()) {
-        Some("gz") => {
-            let level = Compression::default();
-            let encoder = GzEncoder::new(writer, level);
-            Ok(Writer::new(Box::new(encoder)))
-        }
-        _ => Ok(Writer::new(Box::new(writer))),
+    if !is_gzipped(path) {
+        return Ok(Writer::new(Box::new(writer)));
     }
+
+    if threads > 1 {
+        let encoder = ParallelGzWriter::new(writer, level, threads);
+        Ok(Writer::new(Box::new(encoder)))
+    } else {
+        let encoder = GzEncoder::new(writer, level);
+        Ok(Writer::new(Box::new(encoder)))
+    }
+}
+
+/// Creates a FASTQ writer to stdout that counts records and bytes written in the given metrics.
+pub fn create_stdout_with_metrics(metrics: Metrics) -> Writer<BufWriter<io::Stdout>> {
+    let mut writer = Writer::new(BufWriter::new(io::stdout()));
+    writer.set_metrics(metrics);
+    writer
 }
 
 pub fn open<P>(src: P) -> io::Result<Reader<Box<dyn BufRead>>>
@@ -36,15 +78,74 @@ where
     P: AsRef<Path>,
 {
     let path = src.as_ref();
-    let extension = path.extension();
     let file = File::open(path)?;
-    let reader = BufReader::new(file);
-
-    match extension.and_then(|ext| ext.to_str()) {
-        Some("gz") => {
-            let decoder = MultiGzDecoder::new(reader);
-            Ok(Reader::new(Box::new(BufReader::new(decoder))))
-        }
-        _ => Ok(Reader::new(Box::new(reader))),
+    Ok(build_reader(file, is_gzipped(path)))
+}
+
+/// Opens a FASTQ file and counts the bytes read from it as source bytes read in the given
+/// metrics.
+///
+/// The returned reader also counts records and bytes read in the metrics, i.e., this is
+/// [`open`] followed by [`Reader::set_metrics`].
+pub fn open_with_metrics<P>(src: P, metrics: Metrics) -> io::Result<Reader<Box<dyn BufRead>>>
+where
+    P: AsRef<Path>,
+{
+    let path = src.as_ref();
+    let file = File::open(path)?;
+
+    let mut reader = build_reader(CountingReader::new(file, metrics.clone()), is_gzipped(path));
+
+    reader.set_metrics(metrics);
+
+    Ok(reader)
+}
+
+fn is_gzipped(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some("gz")
+}
+
+fn build_reader<R>(inner: R, is_gzipped: bool) -> Reader<Box<dyn BufRead>>
+where
+    R: Read + 'static,
+{
+    let reader = BufReader::new(inner);
+
+    if is_gzipped {
+        let decoder = GzReader::new(reader);
+        Reader::new(Box::new(BufReader::new(decoder)))
+    } else {
+        Reader::new(Box::new(reader))
+    }
+}
+
+/// Opens a FASTQ file that may still be written, waiting for more data at EOF.
+///
+/// See [`FollowReader`]. Gzipped files are decompressed as data arrives.
+pub fn open_follow<P>(src: P, timeout: Option<Duration>) -> io::Result<Reader<Box<dyn BufRead>>>
+where
+    P: AsRef<Path>,
+{
+    let path = src.as_ref();
+    let file = File::open(path)?;
+    Ok(build_reader(
+        FollowReader::new(file, timeout),
+        is_gzipped(path),
+    ))
+}
+
+/// Opens stdin as a FASTQ source.
+///
+/// Gzipped input is detected by its magic number.
+pub fn open_stdin() -> io::Result<Reader<Box<dyn BufRead>>> {
+    const GZIP_MAGIC_NUMBER: [u8; 2] = [0x1f, 0x8b];
+
+    let mut reader = BufReader::new(io::stdin());
+
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC_NUMBER) {
+        let decoder = GzReader::new(reader);
+        Ok(Reader::new(Box::new(BufReader::new(decoder))))
+    } else {
+        Ok(Reader::new(Box::new(reader)))
     }
 }
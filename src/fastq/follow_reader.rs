@@ -0,0 +1,107 @@
+use std::{
+    io::{self, Read},
+    thread,
+    time::{Duration, Instant},
+};
+
+// The time to wait before reading again after reaching the end of the source.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A reader that waits for more data at EOF, like `tail -f`.
+///
+/// This is useful to read a file that is still being written, e.g., during a transfer. By
+/// default, it waits indefinitely. With a timeout, EOF is returned once no new data has been read
+/// for that long.
+pub struct FollowReader<R> {
+    inner: R,
+    poll_interval: Duration,
+    timeout: Option<Duration>,
+    last_read: Instant,
+}
+
+impl<R> FollowReader<R>
+where
+    R: Read,
+{
+    pub fn new(inner: R, timeout: Option<Duration>) -> Self {
+        Self {
+            inner,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            timeout,
+            last_read: Instant::now(),
+        }
+    }
+
+    /// Sets the time to wait before reading again after reaching the end of the source.
+    pub fn set_poll_interval(&mut self, poll_interval: Duration) {
+        self.poll_interval = poll_interval;
+    }
+}
+
+impl<R> Read for FollowReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let n = self.inner.read(buf)?;
+
+            if n > 0 {
+                self.last_read = Instant::now();
+                return Ok(n);
+            }
+
+            if let Some(timeout) = self.timeout {
+                if self.last_read.elapsed() >= timeout {
+                    return Ok(0);
+                }
+            }
+
+            thread::sleep(self.poll_interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A source that is empty on its first read, as if data has not yet arrived.
+    struct DelayedSource<'a> {
+        data: &'a [u8],
+        is_ready: bool,
+    }
+
+    impl<'a> Read for DelayedSource<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.is_ready {
+                self.data.read(buf)
+            } else {
+                self.is_ready = true;
+                Ok(0)
+            }
+        }
+    }
+
+    #[test]
+    fn test_read() -> io::Result<()> {
+        let source = DelayedSource {
+            data: b"@r0\nACGT\n+\nIIII\n",
+            is_ready: false,
+        };
+
+        let mut reader = FollowReader::new(source, Some(Duration::from_millis(10)));
+        reader.set_poll_interval(Duration::from_millis(1));
+
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+
+        assert_eq!(buf, b"@r0\nACGT\n+\nIIII\n");
+
+        Ok(())
+    }
+}
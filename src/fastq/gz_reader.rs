@@ -0,0 +1,112 @@
+use std::io::{self, BufRead, Read};
+
+use flate2::bufread::MultiGzDecoder;
+
+/// A multi-member gzip decoder that reports a stream that ends early as an unexpected EOF.
+///
+/// A [`MultiGzDecoder`] fails with a corrupt stream error, e.g., "corrupt deflate stream", when
+/// its input ends mid-stream. If the input is exhausted when decoding fails, the error is
+/// reported as [`io::ErrorKind::UnexpectedEof`] instead, i.e., the input is truncated (S015).
+/// Decoding errors before the end of the input are returned unchanged.
+pub struct GzReader<R> {
+    inner: MultiGzDecoder<R>,
+}
+
+impl<R> GzReader<R>
+where
+    R: BufRead,
+{
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner: MultiGzDecoder::new(inner),
+        }
+    }
+
+    fn is_eof(&mut self) -> bool {
+        matches!(self.inner.get_mut().fill_buf(), Ok(buf) if buf.is_empty())
+    }
+}
+
+impl<R> Read for GzReader<R>
+where
+    R: BufRead,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.inner.read(buf) {
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData
+                ) && self.is_eof() =>
+            {
+                Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("unexpected end of gzip stream: {}", e),
+                ))
+            }
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+
+    fn compress(data: &[u8]) -> io::Result<Vec<u8>> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data)?;
+        encoder.finish()
+    }
+
+    #[test]
+    fn test_read() -> io::Result<()> {
+        let data = b"@r0\nACGT\n+\nFQLB\n".repeat(64);
+        let compressed = compress(&data)?;
+
+        let mut buf = Vec::new();
+        GzReader::new(&compressed[..]).read_to_end(&mut buf)?;
+        assert_eq!(buf, data);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_with_truncated_stream() -> io::Result<()> {
+        let data: Vec<u8> = (0..4096).map(|i| (i * 7 % 251) as u8).collect();
+        let compressed = compress(&data)?;
+
+        for len in [compressed.len() / 2, compressed.len() - 4] {
+            let mut buf = Vec::new();
+            let e = GzReader::new(&compressed[..len])
+                .read_to_end(&mut buf)
+                .unwrap_err();
+
+            assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_with_corrupt_stream() -> io::Result<()> {
+        let data: Vec<u8> = (0..4096).map(|i| (i * 7 % 251) as u8).collect();
+        let mut compressed = compress(&data)?;
+
+        // Invalid block type (0b11) of the first deflate block, after the 10-byte header.
+        compressed[10] |= 0b110;
+
+        let mut buf = Vec::new();
+        let e = GzReader::new(&compressed[..])
+            .read_to_end(&mut buf)
+            .unwrap_err();
+
+        assert_ne!(e.kind(), io::ErrorKind::UnexpectedEof);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use std::ops::Range;
+
+/// Returns the ID of a record name, i.e., the name excluding the `@` prefix, pair suffix, and
+/// comment.
+///
+/// # Examples
+///
+/// ```
+/// use fq::fastq::name_id;
+///
+/// assert_eq!(name_id(b"@r0/1"), b"r0");
+/// assert_eq!(name_id(b"@r0 1:N:0:ACGT"), b"r0");
+/// assert_eq!(name_id(b"@r0"), b"r0");
+/// ```
+pub fn name_id(name: &[u8]) -> &[u8] {
+    &name[id_range(name)]
+}
+
+/// Returns the range of the ID in a record name.
+pub(crate) fn id_range(name: &[u8]) -> Range<usize> {
+    let start = usize::from(name.starts_with(b"@"));
+
+    let end = name
+        .iter()
+        .position(|b| b.is_ascii_whitespace())
+        .unwrap_or(name.len());
+
+    let end = if name[start..end].ends_with(b"/1") || name[start..end].ends_with(b"/2") {
+        end - 2
+    } else {
+        end
+    };
+
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_id() {
+        assert_eq!(name_id(b"@r0/1"), b"r0");
+        assert_eq!(name_id(b"@r0/2 1:N:0"), b"r0");
+        assert_eq!(name_id(b"@r0/3"), b"r0/3");
+        assert_eq!(name_id(b"@r0\t1:N:0"), b"r0");
+        assert_eq!(name_id(b"r0"), b"r0");
+        assert_eq!(name_id(b"@"), b"");
+        assert_eq!(name_id(b""), b"");
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    io::{self, Write},
+    panic, thread,
+};
+
+use flate2::{write::GzEncoder, Compression};
+
+// The number of uncompressed bytes in each gzip member.
+const BLOCK_LEN: usize = 1 << 20;
+
+/// A gzip writer that compresses blocks in parallel.
+///
+/// Data is buffered and split into blocks, which are compressed into independent gzip members,
+/// one block per thread at a time, and written in order. Concatenated members are a valid gzip
+/// stream, e.g., for a [`flate2::bufread::MultiGzDecoder`].
+///
+/// The writer must be finished to write the remaining blocks. This is also attempted on drop,
+/// ignoring errors.
+pub struct ParallelGzWriter<W>
+where
+    W: Write,
+{
+    inner: W,
+    buf: Vec<u8>,
+    level: Compression,
+    threads: usize,
+    is_finished: bool,
+}
+
+impl<W> ParallelGzWriter<W>
+where
+    W: Write,
+{
+    pub fn new(inner: W, level: Compression, threads: usize) -> Self {
+        let threads = threads.max(1);
+
+        Self {
+            inner,
+            buf: Vec::with_capacity(BLOCK_LEN * threads),
+            level,
+            threads,
+            is_finished: false,
+        }
+    }
+
+    /// Compresses and writes the buffered data.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.is_finished {
+            return Ok(());
+        }
+
+        self.flush_blocks()?;
+        self.inner.flush()?;
+
+        self.is_finished = true;
+
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        BLOCK_LEN * self.threads
+    }
+
+    fn flush_blocks(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+
+        let level = self.level;
+        let buf = &self.buf;
+
+        let members: Vec<io::Result<Vec<u8>>> = thread::scope(|scope| {
+            let handles: Vec<_> = buf
+                .chunks(BLOCK_LEN)
+                .map(|block| scope.spawn(move || compress_member(block, level)))
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+                .collect()
+        });
+
+        self.buf.clear();
+
+        for member in members {
+            self.inner.write_all(&member?)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W> Write for ParallelGzWriter<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.buf.len() >= self.capacity() {
+            self.flush_blocks()?;
+        }
+
+        let n = buf.len().min(self.capacity() - self.buf.len());
+        self.buf.extend_from_slice(&buf[..n]);
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flush_blocks()?;
+        self.inner.flush()
+    }
+}
+
+impl<W> Drop for ParallelGzWriter<W>
+where
+    W: Write,
+{
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+fn compress_member(data: &[u8], level: Compression) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), level);
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::MultiGzDecoder;
+
+    use super::*;
+
+    #[test]
+    fn test_write() -> io::Result<()> {
+        let data: Vec<u8> = (0..BLOCK_LEN * 5 + 8).map(|i| (i % 251) as u8).collect();
+
+        let mut dst = Vec::new();
+
+        let mut writer = ParallelGzWriter::new(&mut dst, Compression::fast(), 2);
+        writer.write_all(&data)?;
+        writer.finish()?;
+        drop(writer);
+
+        let mut buf = Vec::new();
+        MultiGzDecoder::new(&dst[..]).read_to_end(&mut buf)?;
+        assert_eq!(buf, data);
+
+        Ok(())
+    }
+}
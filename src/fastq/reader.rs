@@ -1,15 +1,38 @@
-use std::io::{self, BufRead};
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead},
+};
 
 use super::Record;
+use crate::metrics::Metrics;
 
 const LINE_FEED: u8 = b'\n';
 const CARRIAGE_RETURN: u8 = b'\r';
 
+// A line to read before the next line of the inner reader.
+struct PendingLine {
+    // The line with its line ending.
+    buf: Vec<u8>,
+    // The length of the line in the input, which may differ from the buffer, e.g., when a
+    // carriage return was removed.
+    len: usize,
+    // Whether the bytes of the line were already counted in the metrics.
+    is_counted: bool,
+}
+
 pub struct Reader<R>
 where
     R: BufRead,
 {
     inner: R,
+    metrics: Option<Metrics>,
+    strip_carriage_returns: bool,
+    // Lines to read before the next line of the inner reader, e.g., when resyncing.
+    pending_lines: VecDeque<PendingLine>,
+    // The position of the next line in the input.
+    position: u64,
+    // The lengths of the sequence, plus, and quality lines of the last record in the input.
+    line_lens: [usize; 3],
 }
 
 impl<R> Reader<R>
@@ -17,34 +40,257 @@ where
     R: BufRead,
 {
     pub fn new(inner: R) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            metrics: None,
+            strip_carriage_returns: true,
+            pending_lines: VecDeque::new(),
+            position: 0,
+            line_lens: [0; 3],
+        }
+    }
+
+    /// Sets whether to remove the carriage return of a CRLF line ending (default: `true`).
+    ///
+    /// Keeping carriage returns is useful to validate line endings.
+    pub fn set_strip_carriage_returns(&mut self, strip_carriage_returns: bool) {
+        self.strip_carriage_returns = strip_carriage_returns;
+    }
+
+    /// Sets the metrics to update on each record read.
+    pub fn set_metrics(&mut self, metrics: Metrics) {
+        self.metrics = Some(metrics);
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the position of the next record in the input, i.e., the number of bytes before it.
+    ///
+    /// For a compressed input, this is the position in the uncompressed data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// use fq::fastq::{Reader, Record};
+    ///
+    /// let data = b"@r0\nACGT\n+\nIIII\n@r1\nAC\n+\nII\n";
+    /// let mut reader = Reader::new(&data[..]);
+    /// let mut record = Record::default();
+    ///
+    /// assert_eq!(reader.position(), 0);
+    /// reader.read_record(&mut record)?;
+    /// assert_eq!(reader.position(), 16);
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns whether the input has no more data, i.e., no record remains.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// use fq::fastq::{Reader, Record};
+    ///
+    /// let data = b"@r0\nACGT\n+\nIIII\n";
+    /// let mut reader = Reader::new(&data[..]);
+    /// let mut record = Record::default();
+    ///
+    /// assert!(!reader.is_eof()?);
+    /// reader.read_record(&mut record)?;
+    /// assert!(reader.is_eof()?);
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    pub fn is_eof(&mut self) -> io::Result<bool> {
+        if !self.pending_lines.is_empty() {
+            return Ok(false);
+        }
+
+        self.inner.fill_buf().map(|buf| buf.is_empty())
+    }
+
+    /// Unwraps and returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
     }
 
     pub fn read_record(&mut self, record: &mut Record) -> io::Result<usize> {
+        self.read_record_inner(record, false)
+    }
+
+    /// Reads a record, failing if the input ends before all four record lines.
+    ///
+    /// Unlike [`Self::read_record`], which returns a record with the missing lines empty, this
+    /// returns an error of kind [`io::ErrorKind::UnexpectedEof`] for a truncated record.
+    pub fn read_complete_record(&mut self, record: &mut Record) -> io::Result<usize> {
+        self.read_record_inner(record, true)
+    }
+
+    /// Skips to the next record after a malformed record.
+    ///
+    /// The sequence, plus, and quality lines of `record`, which may include the name line of the
+    /// next record, are read again. Lines are skipped until a line that starts with an "@" and is
+    /// followed two lines later by a line that starts with a "+", or the end of the input.
+    ///
+    /// This returns the number of skipped lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// use fq::fastq::{Reader, Record};
+    ///
+    /// let data = b"@r0\nACGT\n+\n@r1\nTGCA\n+\nIIII\n";
+    /// let mut reader = Reader::new(&data[..]);
+    /// let mut record = Record::default();
+    ///
+    /// // r0 is missing its quality line.
+    /// reader.read_record(&mut record)?;
+    /// assert_eq!(record.quality_scores(), b"@r1");
+    ///
+    /// assert_eq!(reader.resync(&record)?, 2);
+    ///
+    /// reader.read_record(&mut record)?;
+    /// assert_eq!(record.name(), b"@r1");
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    pub fn resync(&mut self, record: &Record) -> io::Result<usize> {
+        let lines = [
+            record.sequence(),
+            record.plus_line(),
+            record.quality_scores(),
+        ];
+
+        for (line, &len) in lines.iter().zip(&self.line_lens).rev() {
+            let mut buf = line.to_vec();
+            buf.push(LINE_FEED);
+
+            self.pending_lines.push_front(PendingLine {
+                buf,
+                len,
+                is_counted: true,
+            });
+
+            self.position -= len as u64;
+        }
+
+        let mut skipped_lines = 0;
+
+        loop {
+            while self.pending_lines.len() < 3 {
+                let mut buf = Vec::new();
+
+                let len = self.inner.read_until(LINE_FEED, &mut buf)?;
+
+                if len == 0 {
+                    break;
+                }
+
+                self.pending_lines.push_back(PendingLine {
+                    buf,
+                    len,
+                    is_counted: false,
+                });
+            }
+
+            let is_name_line = |i: usize, prefix: &[u8]| {
+                self.pending_lines
+                    .get(i)
+                    .map(|line| line.buf.starts_with(prefix))
+            };
+
+            match is_name_line(0, b"@") {
+                // A name line near the end of the input starts a record, even if truncated.
+                Some(true) if is_name_line(2, b"+").unwrap_or(true) => break,
+                Some(_) => {}
+                None => break,
+            }
+
+            if let Some(line) = self.pending_lines.pop_front() {
+                if let (Some(metrics), false) = (&self.metrics, line.is_counted) {
+                    metrics.add_bytes_read(line.len as u64);
+                }
+
+                self.position += line.len as u64;
+            }
+
+            skipped_lines += 1;
+        }
+
+        Ok(skipped_lines)
+    }
+
+    fn read_record_inner(&mut self, record: &mut Record, is_strict: bool) -> io::Result<usize> {
         record.clear();
 
-        let mut len = match read_line(&mut self.inner, record.name_mut()) {
-            Ok(0) => return Ok(0),
-            Ok(n) => n,
-            Err(e) => return Err(e),
-        };
+        let (name_len, mut counted_len) = self.read_line(record.name_mut())?;
+
+        if name_len == 0 {
+            return Ok(0);
+        }
+
+        let (sequence_len, sequence_counted_len) = self.read_line(record.sequence_mut())?;
+        let (plus_line_len, plus_line_counted_len) = self.read_line(record.plus_line_mut())?;
+        let (quality_len, quality_counted_len) = self.read_line(record.quality_scores_mut())?;
+
+        let line_lens = [sequence_len, plus_line_len, quality_len];
+        counted_len += sequence_counted_len + plus_line_counted_len + quality_counted_len;
+        self.line_lens = line_lens;
 
-        len += read_line(&mut self.inner, record.sequence_mut())?;
-        len += read_line(&mut self.inner, record.plus_line_mut())?;
-        len += read_line(&mut self.inner, record.quality_scores_mut())?;
+        if is_strict && line_lens.contains(&0) {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "incomplete record",
+            ));
+        }
+
+        let len = name_len + line_lens.iter().sum::<usize>();
+        self.position += len as u64;
+
+        if let Some(metrics) = &self.metrics {
+            metrics.add_records_read(1);
+            metrics.add_bytes_read((len - counted_len) as u64);
+        }
 
         Ok(len)
     }
+
+    // Reads a line, from the pending lines first, and returns the number of bytes read and the
+    // number of those that were already counted.
+    fn read_line(&mut self, buf: &mut Vec<u8>) -> io::Result<(usize, usize)> {
+        let strip_cr = self.strip_carriage_returns;
+
+        match self.pending_lines.pop_front() {
+            Some(line) => {
+                read_line(&mut &line.buf[..], buf, strip_cr)?;
+                Ok((line.len, if line.is_counted { line.len } else { 0 }))
+            }
+            None => read_line(&mut self.inner, buf, strip_cr).map(|n| (n, 0)),
+        }
+    }
 }
 
-fn read_line<R: BufRead>(reader: &mut R, buf: &mut Vec<u8>) -> io::Result<usize> {
+fn read_line<R: BufRead>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    strip_carriage_return: bool,
+) -> io::Result<usize> {
     match reader.read_until(LINE_FEED, buf) {
         Ok(0) => Ok(0),
         Ok(n) => {
             if buf.ends_with(&[LINE_FEED]) {
                 buf.pop();
 
-                if buf.ends_with(&[CARRIAGE_RETURN]) {
+                if strip_carriage_return && buf.ends_with(&[CARRIAGE_RETURN]) {
                     buf.pop();
                 }
             }
@@ -82,6 +328,50 @@ FQLB
         Ok(())
     }
 
+    #[test]
+    fn test_read_complete_record() -> io::Result<()> {
+        let data = b"@fqlib:1/1\nACGT\n+\nFQLB";
+        let mut reader = Reader::new(&data[..]);
+        let mut record = Record::default();
+
+        reader.read_complete_record(&mut record)?;
+        assert_eq!(record.quality_scores(), b"FQLB");
+        assert_eq!(reader.read_complete_record(&mut record)?, 0);
+
+        let data = b"@fqlib:1/1\nACGT\n+\n";
+
+        let mut reader = Reader::new(&data[..]);
+        assert_eq!(reader.read_record(&mut record)?, 18);
+        assert!(record.quality_scores().is_empty());
+
+        let mut reader = Reader::new(&data[..]);
+        assert!(matches!(
+            reader.read_complete_record(&mut record),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_position_after_resync() -> io::Result<()> {
+        let data = b"@r0\r\nACGT\r\n+\r\n@r1\r\nTGCA\r\n+\r\nIIII\r\n";
+        let mut reader = Reader::new(&data[..]);
+        let mut record = Record::default();
+
+        reader.read_record(&mut record)?;
+        assert_eq!(reader.position(), 19);
+
+        reader.resync(&record)?;
+        assert_eq!(reader.position(), 14);
+
+        reader.read_record(&mut record)?;
+        assert_eq!(record.name(), b"@r1");
+        assert_eq!(reader.position(), data.len() as u64);
+
+        Ok(())
+    }
+
     #[test]
     fn test_read_line() -> io::Result<()> {
         let mut buf = Vec::new();
@@ -89,21 +379,27 @@ FQLB
         let data = b"@fqlib\n";
         let mut reader = &data[..];
         buf.clear();
-        read_line(&mut reader, &mut buf)?;
+        read_line(&mut reader, &mut buf, true)?;
         assert_eq!(buf, b"@fqlib");
 
         let data = b"@fqlib\r\n";
         let mut reader = &data[..];
         buf.clear();
-        read_line(&mut reader, &mut buf)?;
+        read_line(&mut reader, &mut buf, true)?;
         assert_eq!(buf, b"@fqlib");
 
         let data = b"@fqlib";
         let mut reader = &data[..];
         buf.clear();
-        read_line(&mut reader, &mut buf)?;
+        read_line(&mut reader, &mut buf, true)?;
         assert_eq!(buf, b"@fqlib");
 
+        let data = b"@fqlib\r\n";
+        let mut reader = &data[..];
+        buf.clear();
+        read_line(&mut reader, &mut buf, false)?;
+        assert_eq!(buf, b"@fqlib\r");
+
         Ok(())
     }
 }
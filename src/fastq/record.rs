@@ -1,3 +1,5 @@
+use crate::sequence;
+
 #[derive(Clone, Default, Debug, Eq, PartialEq)]
 pub struct Record {
     name: Vec<u8>,
@@ -61,6 +63,25 @@ impl Record {
         self.quality_scores.clear();
     }
 
+    /// Reverse complements the sequence and reverses the quality scores.
+    ///
+    /// IUPAC ambiguity codes are complemented, and case is preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::fastq::Record;
+    ///
+    /// let mut record = Record::new("@r0", "AACGRN", "+", "ABCDEF");
+    /// record.reverse_complement();
+    /// assert_eq!(record.sequence(), b"NYCGTT");
+    /// assert_eq!(record.quality_scores(), b"FEDCBA");
+    /// ```
+    pub fn reverse_complement(&mut self) {
+        sequence::reverse_complement(&mut self.sequence);
+        self.quality_scores.reverse();
+    }
+
     /// Prepares a record after initialization.
     ///
     /// This should be called after clearing and directly writing to the line
@@ -111,4 +132,20 @@ mod tests {
         assert!(record.plus_line().is_empty());
         assert!(record.quality_scores().is_empty());
     }
+
+    #[test]
+    fn test_reverse_complement() {
+        let mut record = Record::new("@fqlib:1/1", "ACGTu", "+", "FQLB!");
+
+        record.reverse_complement();
+
+        assert_eq!(record.name(), b"@fqlib:1/1");
+        assert_eq!(record.sequence(), b"aACGT");
+        assert_eq!(record.quality_scores(), b"!BLQF");
+
+        record.reverse_complement();
+
+        assert_eq!(record.sequence(), b"ACGTt");
+        assert_eq!(record.quality_scores(), b"FQLB!");
+    }
 }
@@ -0,0 +1,181 @@
+use std::{
+    error, fmt,
+    io::{self, Write},
+};
+
+use super::{Record, Writer};
+use crate::validators::{self, SingleReadValidator, SingleReadValidatorMut, ValidationLevel};
+
+/// An error returned when a record fails to be written by a [`ValidatingWriter`].
+#[derive(Debug)]
+pub enum WriteError {
+    /// The record failed validation and was not written.
+    Validation(validators::Error),
+    /// An I/O error occurred when writing.
+    Io(io::Error),
+}
+
+impl error::Error for WriteError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Validation(e) => Some(e),
+            Self::Io(e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Validation(e) => write!(f, "invalid record: {}", e),
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl From<validators::Error> for WriteError {
+    fn from(e: validators::Error) -> Self {
+        Self::Validation(e)
+    }
+}
+
+impl From<io::Error> for WriteError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// A FASTQ writer that validates each record before writing it.
+///
+/// This runs the same single read validators as `fq lint`, so a program writing through a
+/// `ValidatingWriter` produces output that passes them. A record that fails validation is not
+/// written.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{
+///     fastq::{Record, ValidatingWriter, WriteError, Writer},
+///     ValidationLevel,
+/// };
+///
+/// let mut writer = ValidatingWriter::new(Writer::new(Vec::new()), ValidationLevel::High, &[]);
+///
+/// writer.write_record(&Record::new("@fqlib:1", "ACGT", "+", "FQLB"))?;
+///
+/// let result = writer.write_record(&Record::new("@fqlib:2", "ACGT", "+", "FQL"));
+/// assert!(matches!(result, Err(WriteError::Validation(e)) if e.code == "S005"));
+///
+/// writer.finish()?;
+/// assert_eq!(writer.get_ref().get_ref(), b"@fqlib:1\nACGT\n+\nFQLB\n");
+/// # Ok::<(), WriteError>(())
+/// ```
+pub struct ValidatingWriter<W> {
+    inner: Writer<W>,
+    single_read_validators: Vec<Box<dyn SingleReadValidator>>,
+    single_read_validators_mut: Vec<Box<dyn SingleReadValidatorMut>>,
+}
+
+impl<W> ValidatingWriter<W>
+where
+    W: Write,
+{
+    /// Creates a validating writer using the single read validators up to the given level that
+    /// are not disabled.
+    pub fn new(
+        inner: Writer<W>,
+        validation_level: ValidationLevel,
+        disabled_validators: &[String],
+    ) -> Self {
+        let single_read_validators =
+            validators::filter_single_read_validators(validation_level, disabled_validators);
+
+        let single_read_validators_mut = validators::filter_single_read_validators_mut(
+            validation_level,
+            disabled_validators,
+            &validators::Config::default(),
+        );
+
+        Self::with_validators(inner, single_read_validators, single_read_validators_mut)
+    }
+
+    /// Creates a validating writer using the given validators.
+    pub fn with_validators(
+        inner: Writer<W>,
+        single_read_validators: Vec<Box<dyn SingleReadValidator>>,
+        single_read_validators_mut: Vec<Box<dyn SingleReadValidatorMut>>,
+    ) -> Self {
+        Self {
+            inner,
+            single_read_validators,
+            single_read_validators_mut,
+        }
+    }
+
+    pub fn get_ref(&self) -> &Writer<W> {
+        &self.inner
+    }
+
+    /// Validates a record and, if it is valid, writes it.
+    pub fn write_record(&mut self, record: &Record) -> Result<(), WriteError> {
+        for validator in &self.single_read_validators {
+            validator.validate(record)?;
+        }
+
+        for validator in &mut self.single_read_validators_mut {
+            validator.validate(record)?;
+        }
+
+        self.inner.write_record(record)?;
+
+        Ok(())
+    }
+
+    /// Runs the whole-file checks of the stateful validators.
+    ///
+    /// This should be called once after the last record is written.
+    pub fn finish(&mut self) -> Result<(), WriteError> {
+        for validator in &mut self.single_read_validators_mut {
+            validator.finish()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_record() -> Result<(), WriteError> {
+        let mut writer = ValidatingWriter::new(Writer::new(Vec::new()), ValidationLevel::High, &[]);
+
+        writer.write_record(&Record::new("@fqlib:1", "ACGT", "+", "FQLB"))?;
+
+        let result = writer.write_record(&Record::new("fqlib:2", "ACGT", "+", "FQLB"));
+        assert!(matches!(result, Err(WriteError::Validation(e)) if e.code == "S003"));
+
+        let result = writer.write_record(&Record::new("@fqlib:3", "ACGT", "-", "FQLB"));
+        assert!(matches!(result, Err(WriteError::Validation(e)) if e.code == "S001"));
+
+        assert_eq!(writer.get_ref().get_ref(), b"@fqlib:1\nACGT\n+\nFQLB\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_record_with_disabled_validators() -> Result<(), WriteError> {
+        let disabled_validators = [String::from("S001")];
+        let mut writer = ValidatingWriter::new(
+            Writer::new(Vec::new()),
+            ValidationLevel::High,
+            &disabled_validators,
+        );
+
+        writer.write_record(&Record::new("@fqlib:1", "ACGT", "-", "FQLB"))?;
+        assert_eq!(writer.get_ref().get_ref(), b"@fqlib:1\nACGT\n-\nFQLB\n");
+
+        Ok(())
+    }
+}
@@ -1,9 +1,11 @@
 use std::io::{self, Write};
 
 use super::Record;
+use crate::metrics::Metrics;
 
 pub struct Writer<W> {
     inner: W,
+    metrics: Option<Metrics>,
 }
 
 impl<W> Writer<W>
@@ -11,7 +13,15 @@ where
     W: Write,
 {
     pub fn new(inner: W) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            metrics: None,
+        }
+    }
+
+    /// Sets the metrics to update on each record written.
+    pub fn set_metrics(&mut self, metrics: Metrics) {
+        self.metrics = Some(metrics);
     }
 
     pub fn get_ref(&self) -> &W {
@@ -28,6 +38,17 @@ where
         self.inner.write_all(record.quality_scores())?;
         self.inner.write_all(b"\n")?;
 
+        if let Some(metrics) = &self.metrics {
+            let len = record.name().len()
+                + record.sequence().len()
+                + record.plus_line().len()
+                + record.quality_scores().len()
+                + 4;
+
+            metrics.add_records_written(1);
+            metrics.add_bytes_written(len as u64);
+        }
+
         Ok(())
     }
 }
@@ -47,4 +68,21 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_write_record_with_metrics() -> io::Result<()> {
+        let metrics = Metrics::new();
+
+        let mut writer = Writer::new(Vec::new());
+        writer.set_metrics(metrics.clone());
+
+        let record = Record::new("@fqlib:1/1", "ACGT", "+", "FQLB");
+        writer.write_record(&record)?;
+        writer.write_record(&record)?;
+
+        assert_eq!(metrics.records_written(), 2);
+        assert_eq!(metrics.bytes_written(), 46);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,342 @@
+//! FASTQ record repair.
+//!
+//! Defects are detected by the same validators used by lint, so each fix maps to a lint code.
+
+use std::{io, str::FromStr};
+
+use crate::{
+    fastq::Record,
+    validators::{
+        self, CompleteValidator, ConsistentSeqQualValidator, PlusLineStyle, PlusLineStyleValidator,
+        PlusLineValidator, SingleReadValidator, SingleReadValidatorMut, WhitespaceValidator,
+    },
+};
+
+const PLUS_LINE: &[u8] = b"+";
+
+// Q0 in Phred+33.
+const PAD_QUALITY_SCORE: u8 = b'!';
+
+/// How to fix a record with a different number of bases and quality scores.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QualityLengthPolicy {
+    /// Pads short quality strings with Q0 (`!`) and truncates long ones to the sequence length.
+    Pad,
+    /// Truncates the longer of the sequence and quality string to the length of the other.
+    Truncate,
+}
+
+impl FromStr for QualityLengthPolicy {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pad" => Ok(Self::Pad),
+            "truncate" => Ok(Self::Truncate),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "invalid quality length policy: expected pad or truncate, got '{}'",
+                    s
+                ),
+            )),
+        }
+    }
+}
+
+/// Counts of fixed defects.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Fixes {
+    /// [S001] Plus lines normalized to `+`.
+    pub plus_lines: u64,
+    /// [S004] Incomplete trailing records removed.
+    pub partial_records: u64,
+    /// [S005] Quality strings padded or truncated to the sequence length.
+    pub quality_lengths: u64,
+}
+
+impl Fixes {
+    /// Returns the lint code and count of each kind of fix.
+    pub fn codes(&self) -> [(&'static str, u64); 3] {
+        [
+            (PlusLineValidator.code(), self.plus_lines),
+            (CompleteValidator.code(), self.partial_records),
+            (ConsistentSeqQualValidator.code(), self.quality_lengths),
+        ]
+    }
+}
+
+/// A FASTQ record fixer.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Fixer {
+    quality_length_policy: Option<QualityLengthPolicy>,
+}
+
+impl Fixer {
+    /// Creates a fixer.
+    ///
+    /// Records with mismatched sequence and quality lengths are only fixed when a policy is
+    /// given.
+    pub fn new(quality_length_policy: Option<QualityLengthPolicy>) -> Self {
+        Self {
+            quality_length_policy,
+        }
+    }
+
+    /// Fixes a record in place, counting each fix in `fixes`.
+    ///
+    /// Plus lines are always normalized to `+`, which drops repeated names. This fails with the
+    /// validation error of a defect that cannot be fixed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{
+    ///     fastq::Record,
+    ///     fix::{Fixer, Fixes, QualityLengthPolicy},
+    /// };
+    ///
+    /// let fixer = Fixer::new(Some(QualityLengthPolicy::Pad));
+    /// let mut fixes = Fixes::default();
+    ///
+    /// let mut record = Record::new("@r0", "ACGT", "+r0", "II");
+    /// fixer.fix(&mut record, &mut fixes)?;
+    ///
+    /// assert_eq!(record, Record::new("@r0", "ACGT", "+", "II!!"));
+    /// assert_eq!(fixes.plus_lines, 1);
+    /// assert_eq!(fixes.quality_lengths, 1);
+    /// # Ok::<(), fq::validators::Error>(())
+    /// ```
+    pub fn fix(&self, record: &mut Record, fixes: &mut Fixes) -> Result<(), validators::Error> {
+        if record.plus_line() != PLUS_LINE {
+            set_plus_line(record, PlusLineStyle::Empty);
+            fixes.plus_lines += 1;
+        }
+
+        CompleteValidator.validate(record)?;
+
+        if let Err(e) = ConsistentSeqQualValidator.validate(record) {
+            if !self.fix_quality_length(record) {
+                return Err(e);
+            }
+
+            fixes.quality_lengths += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the defect found by the validator with the given code can be repaired.
+    ///
+    /// Mismatched sequence and quality lengths (S005) can only be repaired with a quality length
+    /// policy, and plus lines (S018) cannot be repaired to the `consistent` style.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fix::Fixer, validators::PlusLineStyle};
+    ///
+    /// let fixer = Fixer::default();
+    /// assert!(fixer.can_repair("S016", PlusLineStyle::Any));
+    /// assert!(!fixer.can_repair("S005", PlusLineStyle::Any));
+    /// assert!(!fixer.can_repair("S018", PlusLineStyle::Consistent));
+    /// ```
+    pub fn can_repair(&self, code: &str, plus_line_style: PlusLineStyle) -> bool {
+        if code == PlusLineStyleValidator::new(plus_line_style).code() {
+            plus_line_style != PlusLineStyle::Consistent
+        } else if code == ConsistentSeqQualValidator.code() {
+            self.quality_length_policy.is_some()
+        } else {
+            code == PlusLineValidator.code() || code == WhitespaceValidator.code()
+        }
+    }
+
+    /// Repairs the defect found by the validator with the given code in place.
+    ///
+    ///   * [S001] The plus line is set to `+`.
+    ///   * [S005] The quality string is fixed by the quality length policy.
+    ///   * [S016] Trailing whitespace is removed from each line.
+    ///   * [S018] The plus line is set to `+` or, with the `name` style, `+` and the name.
+    ///
+    /// This returns whether the defect is repaired. A record with a defect that cannot be
+    /// repaired is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, fix::Fixer, validators::PlusLineStyle};
+    ///
+    /// let fixer = Fixer::default();
+    ///
+    /// let mut record = Record::new("@r0 ", "ACGT", "+", "IIII");
+    /// assert!(fixer.repair(&mut record, "S016", PlusLineStyle::Name));
+    /// assert!(fixer.repair(&mut record, "S018", PlusLineStyle::Name));
+    /// assert_eq!(record, Record::new("@r0", "ACGT", "+r0", "IIII"));
+    /// ```
+    pub fn repair(&self, record: &mut Record, code: &str, plus_line_style: PlusLineStyle) -> bool {
+        if !self.can_repair(code, plus_line_style) {
+            return false;
+        }
+
+        if code == PlusLineValidator.code() {
+            set_plus_line(record, PlusLineStyle::Empty);
+        } else if code == ConsistentSeqQualValidator.code() {
+            return self.fix_quality_length(record);
+        } else if code == WhitespaceValidator.code() {
+            trim_end(record.name_mut());
+            trim_end(record.sequence_mut());
+            trim_end(record.plus_line_mut());
+            trim_end(record.quality_scores_mut());
+        } else {
+            set_plus_line(record, plus_line_style);
+        }
+
+        true
+    }
+
+    /// Returns whether the last record of a source is partial, i.e., cut short by an unexpected
+    /// EOF.
+    ///
+    /// A last record is partial if it is incomplete (S004) or has fewer quality scores than bases
+    /// (S005).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, fix::Fixer};
+    ///
+    /// let fixer = Fixer::default();
+    /// assert!(!fixer.is_partial(&Record::new("@r0", "ACGT", "+", "IIII")));
+    /// assert!(fixer.is_partial(&Record::new("@r0", "ACGT", "+", "II")));
+    /// assert!(fixer.is_partial(&Record::new("@r0", "ACGT", "", "")));
+    /// ```
+    pub fn is_partial(&self, record: &Record) -> bool {
+        CompleteValidator.validate(record).is_err()
+            || record.quality_scores().len() < record.sequence().len()
+    }
+
+    // Fixes the length of the quality string by the quality length policy, returning false if
+    // there is no policy.
+    fn fix_quality_length(&self, record: &mut Record) -> bool {
+        match self.quality_length_policy {
+            Some(QualityLengthPolicy::Pad) => {
+                let len = record.sequence().len();
+                record.quality_scores_mut().resize(len, PAD_QUALITY_SCORE);
+            }
+            Some(QualityLengthPolicy::Truncate) => {
+                let len = record.sequence().len().min(record.quality_scores().len());
+                record.sequence_mut().truncate(len);
+                record.quality_scores_mut().truncate(len);
+            }
+            None => return false,
+        }
+
+        true
+    }
+}
+
+fn set_plus_line(record: &mut Record, plus_line_style: PlusLineStyle) {
+    let mut plus_line = PLUS_LINE.to_vec();
+
+    if plus_line_style == PlusLineStyle::Name {
+        plus_line.extend_from_slice(record.name().get(1..).unwrap_or_default());
+    }
+
+    *record.plus_line_mut() = plus_line;
+}
+
+fn trim_end(buf: &mut Vec<u8>) {
+    let len = buf
+        .iter()
+        .rposition(|&b| !b.is_ascii_whitespace())
+        .map(|i| i + 1)
+        .unwrap_or(0);
+
+    buf.truncate(len);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix() -> Result<(), validators::Error> {
+        let fixer = Fixer::default();
+        let mut fixes = Fixes::default();
+
+        let mut record = Record::new("@r0", "ACGT", "+", "IIII");
+        fixer.fix(&mut record, &mut fixes)?;
+        assert_eq!(record, Record::new("@r0", "ACGT", "+", "IIII"));
+        assert_eq!(fixes, Fixes::default());
+
+        let mut record = Record::new("@r0", "ACGT", "", "IIII");
+        fixer.fix(&mut record, &mut fixes)?;
+        assert_eq!(record, Record::new("@r0", "ACGT", "+", "IIII"));
+        assert_eq!(fixes.plus_lines, 1);
+
+        let mut record = Record::new("@r0", "ACGT", "+", "II");
+        let e = fixer.fix(&mut record, &mut fixes).unwrap_err();
+        assert_eq!(e.code, "S005");
+
+        let mut record = Record::new("", "ACGT", "+", "IIII");
+        let e = fixer.fix(&mut record, &mut fixes).unwrap_err();
+        assert_eq!(e.code, "S004");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fix_with_truncate_policy() -> Result<(), validators::Error> {
+        let fixer = Fixer::new(Some(QualityLengthPolicy::Truncate));
+        let mut fixes = Fixes::default();
+
+        let mut record = Record::new("@r0", "ACGT", "+", "II");
+        fixer.fix(&mut record, &mut fixes)?;
+        assert_eq!(record, Record::new("@r0", "AC", "+", "II"));
+
+        let mut record = Record::new("@r0", "AC", "+", "IIII");
+        fixer.fix(&mut record, &mut fixes)?;
+        assert_eq!(record, Record::new("@r0", "AC", "+", "II"));
+
+        assert_eq!(fixes.quality_lengths, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_repair() {
+        let fixer = Fixer::new(Some(QualityLengthPolicy::Pad));
+
+        let mut record = Record::new("@r0 ", "ACGT\t", "+r1", "IIIII");
+
+        assert!(fixer.repair(&mut record, "S016", PlusLineStyle::Any));
+        assert_eq!(record, Record::new("@r0", "ACGT", "+r1", "IIIII"));
+
+        assert!(fixer.repair(&mut record, "S005", PlusLineStyle::Any));
+        assert_eq!(record, Record::new("@r0", "ACGT", "+r1", "IIII"));
+
+        assert!(fixer.repair(&mut record, "S018", PlusLineStyle::Name));
+        assert_eq!(record, Record::new("@r0", "ACGT", "+r0", "IIII"));
+
+        assert!(fixer.repair(&mut record, "S001", PlusLineStyle::Name));
+        assert_eq!(record, Record::new("@r0", "ACGT", "+", "IIII"));
+
+        assert!(!fixer.repair(&mut record, "S018", PlusLineStyle::Consistent));
+        assert!(!fixer.repair(&mut record, "S002", PlusLineStyle::Any));
+        assert!(!Fixer::default().repair(&mut record, "S005", PlusLineStyle::Any));
+        assert_eq!(record, Record::new("@r0", "ACGT", "+", "IIII"));
+    }
+
+    #[test]
+    fn test_quality_length_policy_from_str() {
+        assert_eq!(
+            "pad".parse::<QualityLengthPolicy>().ok(),
+            Some(QualityLengthPolicy::Pad)
+        );
+        assert_eq!(
+            "truncate".parse::<QualityLengthPolicy>().ok(),
+            Some(QualityLengthPolicy::Truncate)
+        );
+        assert!("drop".parse::<QualityLengthPolicy>().is_err());
+    }
+}
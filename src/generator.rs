@@ -1,36 +1,102 @@
+mod base_composition;
 mod builder;
+mod error_model;
+mod model;
+mod name_format;
+mod name_template;
+mod orientation;
+mod quality_model;
+mod read_length_distribution;
+mod reference;
+mod umi_location;
 
-pub use self::builder::Builder;
+pub use self::{
+    base_composition::{BaseComposition, ParseBaseCompositionError},
+    builder::Builder,
+    error_model::ErrorModel,
+    model::Model,
+    name_format::{NameFormat, ParseNameFormatError},
+    name_template::{NameTemplate, ParseNameTemplateError},
+    orientation::Orientation,
+    quality_model::{
+        DegradingQualityScores, NovaseqQualityScores, ParseQualityProfileError, QualityModel,
+        QualityProfile,
+    },
+    read_length_distribution::{ParseReadLengthDistributionError, ReadLengthDistribution},
+    reference::Reference,
+    umi_location::{ParseUmiLocationError, UmiLocation},
+};
 
-use std::io::Write;
+use std::{collections::VecDeque, io::Write, sync::Arc};
 
 use rand::{
     distributions::{Distribution, Uniform},
     rngs::SmallRng,
     Rng, SeedableRng,
 };
+use rand_distr::Normal;
 
+use self::{model::Sampler, name_format::Coordinates, name_template::Segment};
 use super::{
-    distributions::{Character, QualityScores},
-    fastq::Record,
+    demux::Sample, distributions::Character, fastq::Record, quality::Encoding,
+    sequence::reverse_complement,
 };
 
 static UPPER_ALPHA_CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
 static NUCLEOBASE_CHARSET: &[u8] = b"AGTC";
 
 const READ_LEN: usize = 101;
+const FRAGMENT_LEN: usize = 300;
 const FLOW_CELL_ID_LEN: usize = 7;
+const UMI_LEN: usize = 8;
+const BARCODE_LEN: usize = 8;
+const DEFAULT_SAMPLE_NAME: &str = "sample";
+const QUALITY_OFFSET: u8 = Encoding::Phred33.offset();
+
+// The number of preceding pairs a duplicate can be a copy of.
+const DUPLICATE_WINDOW: u64 = 4096;
+
+/// The maximum duplicate rate.
+///
+/// Regenerating a duplicate follows its chain of sources to the original, which has an expected
+/// length of 1 / (1 - rate) pairs, so the rate is kept away from 1.
+pub const MAX_DUPLICATE_RATE: f64 = 0.99;
 
 const LANES: u32 = 8;
 const TILES: u32 = 60;
 const MAX_X: u32 = 10000;
 const MAX_Y: u32 = 10000;
 
+// The fragment of a pair, its sample and UMI, and the lengths of its reads.
+#[derive(Clone)]
+struct Fragment {
+    bases: Vec<u8>,
+    sample_index: usize,
+    umi: Vec<u8>,
+    r_length: usize,
+    s_length: usize,
+}
+
 /// A FASTQ record generator.
+///
+/// A clone shares the reference and quality model of the original, e.g., to generate parts of a
+/// seeded dataset on different threads.
+#[derive(Clone)]
 pub struct Generator<R> {
     instrument: String,
-    run_number: i32,
+    run_number: u32,
     flow_cell_id: String,
+    name_format: NameFormat,
+    name_template: Option<NameTemplate>,
+    umi_length: usize,
+    umi_location: Option<UmiLocation>,
+    // The UMI of the current pair, if UMIs are generated.
+    umi: Vec<u8>,
+    samples: Vec<Sample>,
+    // The sample of the current pair.
+    sample_index: usize,
+    // The index of the next record in the dataset.
+    record_index: u64,
 
     rng: R,
     lane_range: Uniform<u32>,
@@ -38,9 +104,24 @@ pub struct Generator<R> {
     x_pos_range: Uniform<u32>,
     y_pos_range: Uniform<u32>,
     sequence_distribution: Character,
-    quality_distribution: QualityScores,
+    base_composition: Option<BaseComposition>,
+    quality_model: Arc<dyn QualityModel>,
+    error_model: Option<ErrorModel>,
+    index_error_model: Option<ErrorModel>,
 
     read_length: usize,
+    read_length_distribution: Option<ReadLengthDistribution>,
+    fragment_length: usize,
+    fragment_length_std_dev: f64,
+    orientation: Orientation,
+    fragment: Vec<u8>,
+    // The adapters of read 1 and read 2.
+    adapters: Option<(Vec<u8>, Vec<u8>)>,
+    duplicate_rate: f64,
+    // The fragments of the preceding pairs, oldest first, if duplicates are generated.
+    recent_fragments: VecDeque<Fragment>,
+    reference: Option<Arc<Reference>>,
+    model: Option<Sampler>,
 }
 
 impl Generator<SmallRng> {
@@ -64,6 +145,112 @@ impl Generator<SmallRng> {
         let rng = SmallRng::seed_from_u64(seed);
         Self::from_rng(rng, READ_LEN)
     }
+
+    /// Reseeds the generator for the record at the given index of a dataset seeded by `seed`.
+    ///
+    /// The next record then only depends on `seed` and `record_index`, not on any records
+    /// generated before it. This allows a dataset to be split into independently generated
+    /// shards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, Generator};
+    ///
+    /// let mut generator = Generator::seed_from_u64(0);
+    /// let mut a = Record::default();
+    /// generator.seek(0, 8);
+    /// generator.next_record(&mut a);
+    ///
+    /// let mut generator = Generator::seed_from_u64(0);
+    /// let mut b = Record::default();
+    /// generator.next_record(&mut b);
+    /// generator.seek(0, 8);
+    /// generator.next_record(&mut b);
+    ///
+    /// assert_eq!(a, b);
+    /// ```
+    pub fn seek(&mut self, seed: u64, record_index: u64) {
+        self.rng = SmallRng::seed_from_u64(record_seed(seed, record_index));
+        self.record_index = record_index;
+    }
+
+    /// Generates the pair at the given index of a dataset seeded by `seed`.
+    ///
+    /// Like [`Self::seek`], the pair only depends on `seed` and `record_index`. A duplicate is
+    /// generated from the same fragment as the pair it is a copy of, which is regenerated from
+    /// its own index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, generator::Builder};
+    ///
+    /// let mut generator = Builder::default().set_duplicate_rate(0.5).build();
+    ///
+    /// let (mut a, mut b) = (Record::default(), Record::default());
+    /// generator.next_pair_at(0, 8, &mut a, &mut b);
+    ///
+    /// let (mut c, mut d) = (Record::default(), Record::default());
+    /// generator.next_pair_at(0, 3, &mut c, &mut d);
+    /// generator.next_pair_at(0, 8, &mut c, &mut d);
+    ///
+    /// assert_eq!((a, b), (c, d));
+    /// ```
+    pub fn next_pair_at(&mut self, seed: u64, record_index: u64, r: &mut Record, s: &mut Record) {
+        clear_record(r);
+        clear_record(s);
+
+        self.seek(seed, record_index);
+
+        let lengths = match self.duplicate_source(record_index) {
+            Some(source_index) => {
+                let rng = self.rng.clone();
+                let lengths = self.regenerate_fragment(seed, source_index);
+                self.rng = rng;
+                self.record_index = record_index;
+                Some(lengths)
+            }
+            None => {
+                self.next_sample();
+                self.next_umi();
+                None
+            }
+        };
+
+        self.next_name(r);
+        s.name_mut().extend_from_slice(r.name());
+
+        let (r_length, s_length) = match lengths {
+            Some(lengths) => lengths,
+            None => self.next_original_fragment(),
+        };
+
+        self.finish_pair(r, s, r_length, s_length);
+    }
+
+    // Regenerates the fragment, sample, and UMI of the pair at `record_index`, following duplicates
+    // to the original, and returns the read lengths.
+    fn regenerate_fragment(&mut self, seed: u64, mut record_index: u64) -> (usize, usize) {
+        let mut record = Record::default();
+
+        loop {
+            self.seek(seed, record_index);
+
+            match self.duplicate_source(record_index) {
+                Some(source_index) => record_index = source_index,
+                None => {
+                    self.next_sample();
+                    self.next_umi();
+
+                    // The name is regenerated to draw the same random numbers.
+                    self.next_name(&mut record);
+
+                    return self.next_original_fragment();
+                }
+            }
+        }
+    }
 }
 
 impl Default for Generator<SmallRng> {
@@ -97,12 +284,20 @@ where
         let y_pos_range = Uniform::new(1, MAX_Y + 1);
 
         let sequence_distribution = Character::new(NUCLEOBASE_CHARSET);
-        let quality_distribution = QualityScores::default();
+        let quality_model = QualityProfile::default().build();
 
         Self {
             instrument,
             run_number,
             flow_cell_id,
+            name_format: NameFormat::default(),
+            name_template: None,
+            umi_length: UMI_LEN,
+            umi_location: None,
+            umi: Vec::new(),
+            samples: Vec::new(),
+            sample_index: 0,
+            record_index: 0,
 
             rng,
             lane_range,
@@ -110,12 +305,35 @@ where
             x_pos_range,
             y_pos_range,
             sequence_distribution,
-            quality_distribution,
+            base_composition: None,
+            quality_model: Arc::from(quality_model),
+            error_model: None,
+            index_error_model: None,
 
             read_length,
+            read_length_distribution: None,
+            fragment_length: FRAGMENT_LEN,
+            fragment_length_std_dev: 0.0,
+            orientation: Orientation::default(),
+            fragment: Vec::new(),
+            adapters: None,
+            duplicate_rate: 0.0,
+            recent_fragments: VecDeque::new(),
+            reference: None,
+            model: None,
         }
     }
 
+    /// Returns the format of the names of generated records.
+    pub fn name_format(&self) -> NameFormat {
+        self.name_format
+    }
+
+    /// Returns the sample of the last generated record (or pair), if samples are set.
+    pub fn sample(&self) -> Option<&Sample> {
+        self.samples.get(self.sample_index)
+    }
+
     /// Returns a freshly generated record.
     ///
     /// # Examples
@@ -130,9 +348,15 @@ where
     pub fn next_record(&mut self, record: &mut Record) {
         clear_record(record);
 
+        self.next_sample();
+        self.next_umi();
         self.next_name(record);
-        self.next_sequence(record);
+
+        let read_length = self.next_read_length();
+        self.next_sequence(record, read_length);
+        self.add_sequence_umi(record, read_length);
         self.next_quality(record);
+        self.add_errors(record);
     }
 
     /// Returns a freshly generated record, setting the name to the given input.
@@ -151,14 +375,296 @@ where
         clear_record(record);
 
         record.name_mut().extend_from_slice(name);
-        self.next_sequence(record);
+
+        self.next_sample();
+        self.next_umi();
+
+        let read_length = self.next_read_length();
+        self.next_sequence(record, read_length);
+        self.add_sequence_umi(record, read_length);
         self.next_quality(record);
+        self.add_errors(record);
+    }
+
+    /// Generates a pair of records drawn from opposite ends of the same fragment.
+    ///
+    /// Both records share the same name. Read 1 is taken from the start of the fragment and read 2
+    /// from the end, each oriented using the generator's [`Orientation`]. With a [`Reference`],
+    /// the fragment is sampled from a random position and strand of a reference sequence, and
+    /// reads are truncated to the fragment if it is shorter.
+    ///
+    /// With adapters, a fragment can be shorter than the reads. The reads then read through the
+    /// fragment into the adapter, i.e., the adapter follows the fragment at the 3' end, and any
+    /// remaining bases are random.
+    ///
+    /// With a duplicate rate, a pair is a duplicate of one of the preceding pairs at that rate,
+    /// i.e., it has the same fragment, sample, UMI, and read lengths but its own name and quality
+    /// scores.
+    ///
+    /// With a [`UmiLocation`], both mates of a pair have the same random UMI, either as the first
+    /// bases of read 1 or in the name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, Generator};
+    ///
+    /// let mut generator = Generator::new();
+    /// let mut r = Record::default();
+    /// let mut s = Record::default();
+    /// generator.next_pair(&mut r, &mut s);
+    /// assert_eq!(r.name(), s.name());
+    /// ```
+    pub fn next_pair(&mut self, r: &mut Record, s: &mut Record) {
+        clear_record(r);
+        clear_record(s);
+
+        let fragment_index = self.recent_fragment_index(self.record_index);
+
+        match fragment_index {
+            Some(i) => {
+                self.sample_index = self.recent_fragments[i].sample_index;
+                self.umi.clear();
+                self.umi.extend_from_slice(&self.recent_fragments[i].umi);
+            }
+            None => {
+                self.next_sample();
+                self.next_umi();
+            }
+        }
+
+        self.next_name(r);
+        s.name_mut().extend_from_slice(r.name());
+
+        let (r_length, s_length) = match fragment_index {
+            Some(i) => {
+                let fragment = &self.recent_fragments[i];
+                self.fragment.clear();
+                self.fragment.extend_from_slice(&fragment.bases);
+                (fragment.r_length, fragment.s_length)
+            }
+            None => self.next_original_fragment(),
+        };
+
+        if self.duplicate_rate > 0.0 {
+            if self.recent_fragments.len() as u64 >= DUPLICATE_WINDOW {
+                self.recent_fragments.pop_front();
+            }
+
+            self.recent_fragments.push_back(Fragment {
+                bases: self.fragment.clone(),
+                sample_index: self.sample_index,
+                umi: self.umi.clone(),
+                r_length,
+                s_length,
+            });
+        }
+
+        self.finish_pair(r, s, r_length, s_length);
+    }
+
+    // Returns the position in the recent fragments of the fragment of a preceding pair if the pair
+    // at `record_index` is a duplicate.
+    fn recent_fragment_index(&mut self, record_index: u64) -> Option<usize> {
+        let source_index = self.duplicate_source(record_index)?;
+
+        // The window is incomplete if pairs were skipped, e.g., by seeking.
+        let distance = (record_index - source_index) as usize;
+        self.recent_fragments.len().checked_sub(distance)
+    }
+
+    // Returns the index of the pair that the pair at `record_index` is a duplicate of, if any.
+    fn duplicate_source(&mut self, record_index: u64) -> Option<u64> {
+        if self.duplicate_rate <= 0.0 || !self.rng.gen_bool(self.duplicate_rate) {
+            return None;
+        }
+
+        let window = record_index.min(DUPLICATE_WINDOW);
+
+        if window == 0 {
+            return None;
+        }
+
+        Some(record_index - 1 - self.rng.gen_range(0..window))
+    }
+
+    // Generates a new fragment and returns the read lengths.
+    fn next_original_fragment(&mut self) -> (usize, usize) {
+        let r_length = self.next_read_length();
+        let s_length = self.next_read_length();
+        let mut fragment_length = self.next_fragment_length();
+
+        // Without adapters, reads cannot read through the fragment.
+        if self.adapters.is_none() {
+            fragment_length = fragment_length.max(r_length).max(s_length);
+        }
+
+        let mut fragment = std::mem::take(&mut self.fragment);
+        fragment.clear();
+        self.fill_bases(&mut fragment, fragment_length);
+        self.fragment = fragment;
+
+        (r_length, s_length)
+    }
+
+    // Sets the sequences, quality scores, and errors of a pair from the current fragment.
+    fn finish_pair(&mut self, r: &mut Record, s: &mut Record, r_length: usize, s_length: usize) {
+        // A fragment from a reference is at most as long as the longest reference sequence.
+        let fragment_length = self.fragment.len();
+
+        // A UMI in read 1 takes the place of the first bases of the fragment.
+        let umi_length = match self.umi_location {
+            Some(UmiLocation::Sequence) => self.umi.len().min(r_length),
+            _ => 0,
+        };
+
+        let head = &self.fragment[..(r_length - umi_length).min(fragment_length)];
+        let tail = &self.fragment[fragment_length - s_length.min(fragment_length)..];
+
+        r.sequence_mut().extend_from_slice(head);
+        s.sequence_mut().extend_from_slice(tail);
+
+        match self.orientation {
+            Orientation::Fr => reverse_complement(s.sequence_mut()),
+            Orientation::Rf => reverse_complement(r.sequence_mut()),
+            Orientation::Ff => {}
+        }
+
+        r.sequence_mut()
+            .splice(0..0, self.umi[..umi_length].iter().copied());
+
+        if let Some((r_adapter, s_adapter)) = &self.adapters {
+            // Adapters are not part of the fragment and so are never reverse complemented.
+            for (record, adapter, len) in [
+                (&mut *r, r_adapter, r_length),
+                (&mut *s, s_adapter, s_length),
+            ] {
+                let sequence = record.sequence_mut();
+
+                if sequence.len() < len {
+                    let n = (len - sequence.len()).min(adapter.len());
+                    sequence.extend_from_slice(&adapter[..n]);
+
+                    let n = len - sequence.len();
+                    let iter = (&mut self.rng)
+                        .sample_iter(&self.sequence_distribution)
+                        .take(n);
+                    sequence.extend(iter);
+                }
+            }
+        }
+
+        self.next_quality(r);
+        self.next_quality(s);
+
+        self.add_errors(r);
+        self.add_errors(s);
     }
 
     // Generates a name following Illumina's naming format, sans interleave.
     //
     // @see <https://help.basespace.illumina.com/articles/descriptive/fastq-files/>
     fn next_name(&mut self, record: &mut Record) {
+        if self.name_template.is_some() {
+            self.next_template_name(record);
+        } else {
+            match self.name_format {
+                NameFormat::Random => self.next_random_name(record),
+                NameFormat::Illumina => self.next_illumina_name(record),
+            }
+        }
+
+        if self.umi_location == Some(UmiLocation::Name) {
+            let dst = record.name_mut();
+            dst.push(b':');
+            dst.extend_from_slice(&self.umi);
+        }
+
+        self.record_index += 1;
+    }
+
+    /// Generates the index reads of the last generated pair, i.e., the barcodes of its sample.
+    ///
+    /// Each record is named `name` and has the barcode at the same position of the sample, e.g.,
+    /// the i7 and i5 indexes, with substitutions at the index error rate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{demux::Sample, fastq::Record, generator::Builder};
+    ///
+    /// let mut generator = Builder::default()
+    ///     .set_samples(vec![Sample::new("s1", vec![b"ACGT".to_vec(), b"TTTT".to_vec()])])
+    ///     .build();
+    ///
+    /// let (mut r, mut s) = (Record::default(), Record::default());
+    /// generator.next_pair(&mut r, &mut s);
+    ///
+    /// let mut indexes = [Record::default(), Record::default()];
+    /// generator.next_index_reads(r.name(), &mut indexes);
+    /// assert_eq!(indexes[0].sequence(), b"ACGT");
+    /// assert_eq!(indexes[1].sequence(), b"TTTT");
+    /// ```
+    pub fn next_index_reads(&mut self, name: &[u8], records: &mut [Record]) {
+        let sample = match self.samples.get(self.sample_index) {
+            Some(sample) => sample,
+            None => return,
+        };
+
+        let len = sample.barcodes().len().min(records.len());
+        let records = &mut records[..len];
+
+        for (record, barcode) in records.iter_mut().zip(sample.barcodes()) {
+            clear_record(record);
+            record.name_mut().extend_from_slice(name);
+            record.sequence_mut().extend_from_slice(barcode);
+        }
+
+        for record in records.iter_mut() {
+            self.next_quality(record);
+
+            if let Some(error_model) = &self.index_error_model {
+                error_model.apply(&mut self.rng, record);
+            }
+        }
+    }
+
+    // Draws the sample of the next record if there is more than one.
+    fn next_sample(&mut self) {
+        if self.samples.len() > 1 {
+            self.sample_index = self.rng.gen_range(0..self.samples.len());
+        }
+    }
+
+    // Draws the UMI of the next record, if UMIs are generated.
+    fn next_umi(&mut self) {
+        self.umi.clear();
+
+        let has_umi = self
+            .name_template
+            .as_ref()
+            .map(|template| template.has_umi())
+            .unwrap_or(false);
+
+        if self.umi_location.is_some() || has_umi {
+            let iter = (&mut self.rng)
+                .sample_iter(&self.sequence_distribution)
+                .take(self.umi_length);
+
+            self.umi.extend(iter);
+        }
+    }
+
+    // Replaces the first bases of a single record with the UMI if it is located in the sequence.
+    fn add_sequence_umi(&self, record: &mut Record, read_length: usize) {
+        if self.umi_location == Some(UmiLocation::Sequence) {
+            let sequence = record.sequence_mut();
+            sequence.splice(0..0, self.umi.iter().copied());
+            sequence.truncate(read_length);
+        }
+    }
+
+    fn next_random_name(&mut self, record: &mut Record) {
         let lane = self.lane_range.sample(&mut self.rng);
         let tile = self.tile_range.sample(&mut self.rng);
         let x_pos = self.x_pos_range.sample(&mut self.rng);
@@ -178,28 +684,133 @@ where
         .unwrap();
     }
 
-    fn next_sequence(&mut self, record: &mut Record) {
-        let iter = (&mut self.rng)
-            .sample_iter(&self.sequence_distribution)
-            .take(self.read_length);
+    fn next_illumina_name(&mut self, record: &mut Record) {
+        let coordinates = Coordinates::at(&mut self.rng, self.record_index);
 
-        let sequence = record.sequence_mut();
+        write!(
+            record.name_mut(),
+            "@{}:{}:{}:{}:{}:{}:{}",
+            self.instrument,
+            self.run_number,
+            self.flow_cell_id,
+            coordinates.lane,
+            coordinates.tile,
+            coordinates.x,
+            coordinates.y,
+        )
+        .unwrap();
+    }
+
+    fn next_template_name(&mut self, record: &mut Record) {
+        let template = match &self.name_template {
+            Some(template) => template,
+            None => return,
+        };
+
+        let dst = record.name_mut();
+        dst.push(b'@');
 
-        for c in iter {
-            sequence.push(c);
+        for segment in template.segments() {
+            match segment {
+                Segment::Literal(s) => dst.extend_from_slice(s),
+                Segment::Index => write!(dst, "{}", self.record_index + 1).unwrap(),
+                Segment::Instrument => dst.extend_from_slice(self.instrument.as_bytes()),
+                Segment::Run => write!(dst, "{}", self.run_number).unwrap(),
+                Segment::FlowCell => dst.extend_from_slice(self.flow_cell_id.as_bytes()),
+                Segment::Umi => dst.extend_from_slice(&self.umi),
+                Segment::Barcode => {
+                    if let Some(sample) = self.samples.get(self.sample_index) {
+                        for (i, barcode) in sample.barcodes().iter().enumerate() {
+                            if i > 0 {
+                                dst.push(b'+');
+                            }
+
+                            dst.extend_from_slice(barcode);
+                        }
+                    }
+                }
+            }
         }
     }
 
-    fn next_quality(&mut self, record: &mut Record) {
-        let iter = (&mut self.rng)
-            .sample_iter(&self.quality_distribution)
-            .take(self.read_length)
-            .map(|phred| phred + 33);
+    fn next_read_length(&mut self) -> usize {
+        match &self.model {
+            Some(model) => model.sample_read_length(&mut self.rng),
+            None => match &self.read_length_distribution {
+                Some(distribution) => distribution.sample(&mut self.rng),
+                None => self.read_length,
+            },
+        }
+    }
+
+    fn next_fragment_length(&mut self) -> usize {
+        if self.fragment_length_std_dev > 0.0 {
+            // Std. dev. is always finite.
+            let distribution =
+                Normal::new(self.fragment_length as f64, self.fragment_length_std_dev).unwrap();
+            distribution.sample(&mut self.rng).round().max(0.0) as usize
+        } else {
+            self.fragment_length
+        }
+    }
+
+    fn fill_bases(&mut self, dst: &mut Vec<u8>, len: usize) {
+        if let Some(reference) = &self.reference {
+            reference.sample_fragment(&mut self.rng, len, dst);
+            return;
+        }
+
+        match &self.model {
+            Some(model) => {
+                for _ in 0..len {
+                    dst.push(model.sample_base(&mut self.rng));
+                }
+            }
+            None => match &self.base_composition {
+                Some(base_composition) => {
+                    let iter = (&mut self.rng).sample_iter(base_composition).take(len);
+                    dst.extend(iter);
+                }
+                None => {
+                    let iter = (&mut self.rng)
+                        .sample_iter(&self.sequence_distribution)
+                        .take(len);
 
+                    dst.extend(iter);
+                }
+            },
+        }
+    }
+
+    fn next_sequence(&mut self, record: &mut Record, read_length: usize) {
+        self.fill_bases(record.sequence_mut(), read_length);
+    }
+
+    // Quality scores are generated for each base of the record's sequence.
+    fn next_quality(&mut self, record: &mut Record) {
+        let read_length = record.sequence().len();
         let quality = record.quality_scores_mut();
 
-        for c in iter {
-            quality.push(c);
+        match &self.model {
+            Some(model) => {
+                for cycle in 0..read_length {
+                    let phred = model.sample_quality_score(&mut self.rng, cycle);
+                    quality.push(phred + QUALITY_OFFSET);
+                }
+            }
+            None => {
+                for cycle in 0..read_length {
+                    let phred = self.quality_model.sample(&mut self.rng, cycle, read_length);
+                    quality.push(phred + QUALITY_OFFSET);
+                }
+            }
+        }
+    }
+
+    // Sequencing errors are added after quality scores, which they depend on.
+    fn add_errors(&mut self, record: &mut Record) {
+        if let Some(error_model) = &self.error_model {
+            error_model.apply(&mut self.rng, record);
         }
     }
 }
@@ -210,6 +821,18 @@ fn clear_record(record: &mut Record) {
     record.quality_scores_mut().clear();
 }
 
+// Derives a seed for a single record using the SplitMix64 finalizer, so that seeds of neighboring
+// records are uncorrelated.
+fn record_seed(seed: u64, record_index: u64) -> u64 {
+    let mut z = seed
+        ^ record_index
+            .wrapping_add(1)
+            .wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
 fn gen_flow_cell_id<R>(rng: &mut R, len: usize) -> String
 where
     R: Rng,
@@ -251,4 +874,172 @@ mod tests {
         assert_eq!(record.sequence().len(), READ_LENGTH);
         assert_eq!(record.quality_scores().len(), READ_LENGTH);
     }
+
+    #[test]
+    fn test_next_pair() {
+        const READ_LENGTH: usize = 4;
+
+        let mut r = Record::default();
+        let mut s = Record::default();
+
+        let rng = SmallRng::seed_from_u64(0);
+        let mut generator = Generator::from_rng(rng, READ_LENGTH);
+        generator.fragment_length = READ_LENGTH;
+
+        generator.next_pair(&mut r, &mut s);
+        assert_eq!(r.name(), s.name());
+        assert_eq!(s.sequence().len(), READ_LENGTH);
+        assert_eq!(s.quality_scores().len(), READ_LENGTH);
+
+        let mut expected = r.sequence().to_vec();
+        reverse_complement(&mut expected);
+        assert_eq!(s.sequence(), expected);
+
+        generator.orientation = Orientation::Ff;
+        generator.next_pair(&mut r, &mut s);
+        assert_eq!(r.sequence(), s.sequence());
+
+        generator.orientation = Orientation::Rf;
+        generator.fragment_length = 16;
+        generator.next_pair(&mut r, &mut s);
+        assert_eq!(r.sequence().len(), READ_LENGTH);
+        assert_eq!(s.sequence().len(), READ_LENGTH);
+
+        let mut expected = generator.fragment[..READ_LENGTH].to_vec();
+        reverse_complement(&mut expected);
+        assert_eq!(r.sequence(), expected);
+        assert_eq!(s.sequence(), &generator.fragment[12..]);
+    }
+
+    #[test]
+    fn test_next_pair_with_adapters() {
+        const READ_LENGTH: usize = 12;
+
+        let mut r = Record::default();
+        let mut s = Record::default();
+
+        let mut generator = Generator::builder()
+            .set_read_length(READ_LENGTH)
+            .set_fragment_length(4)
+            .set_adapters(b"AGATCGG".to_vec(), b"CCCC".to_vec())
+            .build();
+
+        generator.next_pair(&mut r, &mut s);
+
+        assert_eq!(&r.sequence()[..4], generator.fragment);
+        assert_eq!(&r.sequence()[4..11], b"AGATCGG");
+        assert_eq!(r.sequence().len(), READ_LENGTH);
+        assert_eq!(r.quality_scores().len(), READ_LENGTH);
+
+        let mut expected = generator.fragment.clone();
+        reverse_complement(&mut expected);
+        assert_eq!(&s.sequence()[..4], expected);
+        assert_eq!(&s.sequence()[4..8], b"CCCC");
+        assert_eq!(s.sequence().len(), READ_LENGTH);
+
+        generator.fragment_length = 16;
+        generator.next_pair(&mut r, &mut s);
+        assert_eq!(r.sequence(), &generator.fragment[..READ_LENGTH]);
+    }
+
+    #[test]
+    fn test_next_pair_at_with_umis() {
+        use std::collections::HashMap;
+
+        let mut r = Record::default();
+        let mut s = Record::default();
+
+        let mut generator = Generator::builder()
+            .set_read_length(16)
+            .set_umi_location(UmiLocation::Name)
+            .set_duplicate_rate(0.5)
+            .build();
+
+        let mut umis = HashMap::new();
+
+        for i in 0..64 {
+            generator.next_pair_at(0, i, &mut r, &mut s);
+
+            let umi = r.name().rsplit(|&b| b == b':').next().unwrap().to_vec();
+            let previous_umi = umis.insert(s.sequence().to_vec(), umi.clone());
+
+            // Duplicates have the UMI of their original.
+            if let Some(previous_umi) = previous_umi {
+                assert_eq!(previous_umi, umi);
+            }
+        }
+
+        assert!(umis.len() < 64);
+    }
+
+    #[test]
+    fn test_next_pair_with_reference() {
+        const READ_LENGTH: usize = 4;
+
+        let mut r = Record::default();
+        let mut s = Record::default();
+
+        let reference = Reference::new([b"ACGTTGCAAC".to_vec()]).unwrap();
+
+        let mut generator = Generator::builder()
+            .set_read_length(READ_LENGTH)
+            .set_fragment_length(8)
+            .set_reference(reference)
+            .build();
+
+        for _ in 0..16 {
+            generator.next_pair(&mut r, &mut s);
+
+            // Reads cover the whole fragment.
+            let mut fragment = r.sequence().to_vec();
+            let mut tail = s.sequence().to_vec();
+            reverse_complement(&mut tail);
+            fragment.extend_from_slice(&tail);
+
+            assert_eq!(fragment, generator.fragment);
+
+            let mut reverse_fragment = fragment.clone();
+            reverse_complement(&mut reverse_fragment);
+
+            assert!(
+                b"ACGTTGCAAC".windows(8).any(|w| w == &fragment[..])
+                    || b"ACGTTGCAAC".windows(8).any(|w| w == &reverse_fragment[..])
+            );
+        }
+
+        generator.fragment_length = 32;
+        generator.read_length = 16;
+        generator.next_pair(&mut r, &mut s);
+        assert_eq!(generator.fragment.len(), 10);
+        assert_eq!(r.sequence().len(), 10);
+        assert_eq!(s.sequence().len(), 10);
+    }
+
+    #[test]
+    fn test_record_seed() {
+        assert_eq!(record_seed(0, 0), record_seed(0, 0));
+        assert_ne!(record_seed(0, 0), record_seed(0, 1));
+        assert_ne!(record_seed(0, 0), record_seed(1, 0));
+    }
+
+    #[test]
+    fn test_next_record_with_model() {
+        let mut model = Model::default();
+        model.add(&Record::new("@r0", "GGCC", "+", "IIII"));
+        model.add(&Record::new("@r1", "CCGGCC", "+", "IIIIII"));
+
+        let rng = SmallRng::seed_from_u64(0);
+        let mut generator = Builder::from_rng(rng).set_model(&model).unwrap().build();
+
+        let mut record = Record::default();
+
+        for _ in 0..16 {
+            generator.next_record(&mut record);
+
+            let len = record.sequence().len();
+            assert!(len == 4 || len == 6);
+            assert!(record.sequence().iter().all(|&b| b == b'G' || b == b'C'));
+            assert_eq!(record.quality_scores(), &b"IIIIII"[..len]);
+        }
+    }
 }
@@ -0,0 +1,178 @@
+use std::{error, fmt, str::FromStr};
+
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+
+static NUCLEOBASES: &[u8] = b"ACGT";
+
+/// The relative frequencies of the bases A, C, G, and T of generated sequences.
+///
+/// # Examples
+///
+/// ```
+/// use fq::generator::BaseComposition;
+/// use rand::{distributions::Distribution, rngs::SmallRng, SeedableRng};
+///
+/// let mut rng = SmallRng::seed_from_u64(0);
+/// let composition = BaseComposition::from_gc_content(1.0).unwrap();
+///
+/// let sequence: Vec<u8> = composition.sample_iter(&mut rng).take(8).collect();
+/// assert!(sequence.iter().all(|&b| b == b'C' || b == b'G'));
+/// ```
+#[derive(Clone, Debug)]
+pub struct BaseComposition {
+    weights: [f64; 4],
+    distribution: WeightedIndex<f64>,
+}
+
+impl BaseComposition {
+    /// Creates a base composition from the weights of A, C, G, and T.
+    ///
+    /// Weights are relative, i.e., they do not need to sum to 1. This returns `None` if a weight
+    /// is negative or not finite or if all weights are 0.
+    pub fn new(weights: [f64; 4]) -> Option<Self> {
+        if weights.iter().any(|w| !w.is_finite()) {
+            return None;
+        }
+
+        let distribution = WeightedIndex::new(weights).ok()?;
+
+        Some(Self {
+            weights,
+            distribution,
+        })
+    }
+
+    /// Creates a base composition with the given fraction of G and C bases.
+    ///
+    /// A and T, and C and G, are equally frequent. This returns `None` if the GC content is not in
+    /// [0, 1].
+    pub fn from_gc_content(gc_content: f64) -> Option<Self> {
+        if !(0.0..=1.0).contains(&gc_content) {
+            return None;
+        }
+
+        let at = (1.0 - gc_content) / 2.0;
+        let gc = gc_content / 2.0;
+
+        Self::new([at, gc, gc, at])
+    }
+
+    /// Returns the fraction of G and C bases.
+    pub fn gc_content(&self) -> f64 {
+        let [a, c, g, t] = self.weights;
+        (c + g) / (a + c + g + t)
+    }
+}
+
+// `WeightedIndex` only implements `PartialEq` since rand 0.8.5, but it is built from the weights.
+impl PartialEq for BaseComposition {
+    fn eq(&self, other: &Self) -> bool {
+        self.weights == other.weights
+    }
+}
+
+impl Distribution<u8> for BaseComposition {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> u8 {
+        NUCLEOBASES[self.distribution.sample(rng)]
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseBaseCompositionError(String);
+
+impl error::Error for ParseBaseCompositionError {}
+
+impl fmt::Display for ParseBaseCompositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid base weights: expected four comma-separated weights of A, C, G, and T, got '{}'",
+            self.0
+        )
+    }
+}
+
+/// Parses the comma-separated weights of A, C, G, and T, e.g., `0.3,0.2,0.2,0.3`.
+impl FromStr for BaseComposition {
+    type Err = ParseBaseCompositionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid_weights = || ParseBaseCompositionError(s.into());
+
+        let weights: Vec<f64> = s
+            .split(',')
+            .map(|t| t.trim().parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| invalid_weights())?;
+
+        let weights = weights.try_into().map_err(|_| invalid_weights())?;
+
+        Self::new(weights).ok_or_else(invalid_weights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert!(BaseComposition::new([1.0, 1.0, 1.0, 1.0]).is_some());
+        assert!(BaseComposition::new([1.0, 0.0, 0.0, 0.0]).is_some());
+
+        assert!(BaseComposition::new([0.0, 0.0, 0.0, 0.0]).is_none());
+        assert!(BaseComposition::new([-1.0, 1.0, 1.0, 1.0]).is_none());
+        assert!(BaseComposition::new([f64::INFINITY, 1.0, 1.0, 1.0]).is_none());
+        assert!(BaseComposition::new([f64::NAN, 1.0, 1.0, 1.0]).is_none());
+    }
+
+    #[test]
+    fn test_from_gc_content() {
+        let composition = BaseComposition::from_gc_content(0.42).unwrap();
+        assert!((composition.gc_content() - 0.42).abs() < 1e-12);
+
+        assert!(BaseComposition::from_gc_content(-0.1).is_none());
+        assert!(BaseComposition::from_gc_content(1.1).is_none());
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let composition = BaseComposition::from_gc_content(0.42).unwrap();
+
+        let sequence: Vec<u8> = composition.sample_iter(&mut rng).take(10000).collect();
+        let gc_count = sequence.iter().filter(|&&b| b == b'C' || b == b'G').count();
+
+        assert!((gc_count as f64 / 10000.0 - 0.42).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            "3,2,2,3".parse(),
+            Ok(BaseComposition::new([3.0, 2.0, 2.0, 3.0]).unwrap())
+        );
+        assert_eq!(
+            "0.3, 0.2, 0.2, 0.3".parse(),
+            Ok(BaseComposition::new([0.3, 0.2, 0.2, 0.3]).unwrap())
+        );
+
+        assert_eq!(
+            "0.3,0.2,0.2".parse::<BaseComposition>(),
+            Err(ParseBaseCompositionError(String::from("0.3,0.2,0.2")))
+        );
+        assert_eq!(
+            "a,c,g,t".parse::<BaseComposition>(),
+            Err(ParseBaseCompositionError(String::from("a,c,g,t")))
+        );
+        assert_eq!(
+            "0,0,0,0".parse::<BaseComposition>(),
+            Err(ParseBaseCompositionError(String::from("0,0,0,0")))
+        );
+    }
+}
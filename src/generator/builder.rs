@@ -1,10 +1,37 @@
+use std::sync::Arc;
+
 use rand::{rngs::SmallRng, Rng, SeedableRng};
 
-use super::{Generator, READ_LEN};
+use super::{
+    model::Sampler, BaseComposition, ErrorModel, Generator, Model, NameFormat, NameTemplate,
+    Orientation, QualityModel, ReadLengthDistribution, Reference, UmiLocation, BARCODE_LEN,
+    DEFAULT_SAMPLE_NAME, FRAGMENT_LEN, MAX_DUPLICATE_RATE, NUCLEOBASE_CHARSET, READ_LEN,
+};
+use crate::demux::Sample;
 
 pub struct Builder<R> {
     rng: R,
+    instrument: Option<String>,
+    run_number: Option<u32>,
+    flow_cell_id: Option<String>,
+    name_format: NameFormat,
+    name_template: Option<NameTemplate>,
+    umi_length: Option<usize>,
+    umi_location: Option<UmiLocation>,
+    samples: Vec<Sample>,
     read_length: usize,
+    read_length_distribution: Option<ReadLengthDistribution>,
+    fragment_length: usize,
+    fragment_length_std_dev: f64,
+    orientation: Orientation,
+    adapters: Option<(Vec<u8>, Vec<u8>)>,
+    duplicate_rate: f64,
+    base_composition: Option<BaseComposition>,
+    quality_model: Option<Box<dyn QualityModel>>,
+    error_model: Option<ErrorModel>,
+    index_error_rate: f64,
+    reference: Option<Reference>,
+    model: Option<Sampler>,
 }
 
 impl<R> Builder<R>
@@ -14,26 +41,286 @@ where
     pub fn from_rng(rng: R) -> Self {
         Self {
             rng,
+            instrument: None,
+            run_number: None,
+            flow_cell_id: None,
+            name_format: NameFormat::default(),
+            name_template: None,
+            umi_length: None,
+            umi_location: None,
+            samples: Vec::new(),
             read_length: READ_LEN,
+            read_length_distribution: None,
+            fragment_length: FRAGMENT_LEN,
+            fragment_length_std_dev: 0.0,
+            orientation: Orientation::default(),
+            adapters: None,
+            duplicate_rate: 0.0,
+            base_composition: None,
+            quality_model: None,
+            error_model: None,
+            index_error_rate: 0.0,
+            reference: None,
+            model: None,
         }
     }
 
+    /// Sets the instrument of read names. By default, this is random, e.g., `fqlib5`.
+    pub fn set_instrument<S>(mut self, instrument: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.instrument = Some(instrument.into());
+        self
+    }
+
+    /// Sets the run number of read names. By default, this is random.
+    pub fn set_run_number(mut self, run_number: u32) -> Self {
+        self.run_number = Some(run_number);
+        self
+    }
+
+    /// Sets the flow cell ID of read names. By default, this is random, e.g., `ZMXYPLK`.
+    pub fn set_flow_cell_id<S>(mut self, flow_cell_id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.flow_cell_id = Some(flow_cell_id.into());
+        self
+    }
+
+    pub fn set_name_format(mut self, name_format: NameFormat) -> Self {
+        self.name_format = name_format;
+        self
+    }
+
+    /// Sets a template of read names, which overrides the name format.
+    ///
+    /// The read number suffix of paired reads is still set by the name format.
+    pub fn set_name_template(mut self, name_template: NameTemplate) -> Self {
+        self.name_template = Some(name_template);
+        self
+    }
+
+    /// Sets the length of the UMIs of pairs, i.e., of the `{umi}` placeholder of a name template
+    /// and of UMIs placed by [`Self::set_umi_location`]. By default, this is 8.
+    pub fn set_umi_length(mut self, umi_length: usize) -> Self {
+        self.umi_length = Some(umi_length);
+        self
+    }
+
+    /// Adds a random UMI to each pair at the given location.
+    pub fn set_umi_location(mut self, umi_location: UmiLocation) -> Self {
+        self.umi_location = Some(umi_location);
+        self
+    }
+
+    /// Sets the samples of the `{barcode}` placeholder of a name template and of index reads.
+    ///
+    /// Each pair belongs to a random sample. `{barcode}` is replaced by its barcodes, joined by
+    /// `+`. By default, all records have the same random barcode.
+    pub fn set_samples(mut self, samples: Vec<Sample>) -> Self {
+        self.samples = samples;
+        self
+    }
+
     pub fn set_read_length(mut self, read_length: usize) -> Self {
         self.read_length = read_length;
         self
     }
 
+    /// Sets a distribution of read lengths, e.g., to simulate trimmed reads.
+    ///
+    /// The distribution overrides the read length. A model set with [`Self::set_model`] takes
+    /// precedence.
+    pub fn set_read_length_distribution(
+        mut self,
+        read_length_distribution: ReadLengthDistribution,
+    ) -> Self {
+        self.read_length_distribution = Some(read_length_distribution);
+        self
+    }
+
+    pub fn set_fragment_length(mut self, fragment_length: usize) -> Self {
+        self.fragment_length = fragment_length;
+        self
+    }
+
+    /// Sets the standard deviation of the normally distributed fragment (insert) length.
+    ///
+    /// By default, this is 0, i.e., all fragments have the same length.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the standard deviation is negative or not finite.
+    pub fn set_fragment_length_std_dev(mut self, fragment_length_std_dev: f64) -> Self {
+        assert!(
+            fragment_length_std_dev.is_finite() && fragment_length_std_dev >= 0.0,
+            "fragment length std. dev. must be finite and >= 0"
+        );
+
+        self.fragment_length_std_dev = fragment_length_std_dev;
+        self
+    }
+
+    pub fn set_orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Sets the adapters of read 1 and read 2.
+    ///
+    /// With adapters, fragments can be shorter than reads, in which case reads read through the
+    /// fragment into the adapter at the 3' end.
+    pub fn set_adapters(mut self, r1_adapter: Vec<u8>, r2_adapter: Vec<u8>) -> Self {
+        self.adapters = Some((r1_adapter, r2_adapter));
+        self
+    }
+
+    /// Sets the fraction of pairs that are duplicates of one of the preceding pairs.
+    ///
+    /// By default, this is 0, i.e., all pairs are drawn from new fragments.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the rate is not in [0, [`MAX_DUPLICATE_RATE`]].
+    pub fn set_duplicate_rate(mut self, duplicate_rate: f64) -> Self {
+        assert!(
+            (0.0..=MAX_DUPLICATE_RATE).contains(&duplicate_rate),
+            "duplicate rate must be in [0, 0.99]"
+        );
+
+        self.duplicate_rate = duplicate_rate;
+        self
+    }
+
+    /// Sets the relative frequencies of the bases of generated sequences.
+    ///
+    /// By default, bases are uniformly distributed. A model set with [`Self::set_model`] takes
+    /// precedence.
+    pub fn set_base_composition(mut self, base_composition: BaseComposition) -> Self {
+        self.base_composition = Some(base_composition);
+        self
+    }
+
+    /// Sets the model of the quality scores of each cycle, e.g., from a [`super::QualityProfile`].
+    ///
+    /// A model set with [`Self::set_model`] takes precedence.
+    pub fn set_quality_model(mut self, quality_model: Box<dyn QualityModel>) -> Self {
+        self.quality_model = Some(quality_model);
+        self
+    }
+
+    /// Sets a model of sequencing errors to add to generated reads.
+    ///
+    /// Errors are added after quality scores are generated, so they also apply to reads
+    /// simulated from a model set with [`Self::set_model`].
+    pub fn set_error_model(mut self, error_model: ErrorModel) -> Self {
+        self.error_model = Some(error_model);
+        self
+    }
+
+    /// Sets the expected number of substitutions per base of index reads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rate is not in [0, 1].
+    pub fn set_index_error_rate(mut self, index_error_rate: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&index_error_rate),
+            "index error rate must be in [0, 1]"
+        );
+
+        self.index_error_rate = index_error_rate;
+        self
+    }
+
+    /// Sets a reference to sample fragments from, so that generated reads align to it.
+    ///
+    /// The reference takes precedence over the base composition and the bases of a model set
+    /// with [`Self::set_model`].
+    pub fn set_reference(mut self, reference: Reference) -> Self {
+        self.reference = Some(reference);
+        self
+    }
+
+    /// Sets a model to simulate read lengths, bases, and quality scores from.
+    ///
+    /// The model overrides the read length. This returns `None` if the model is empty.
+    pub fn set_model(mut self, model: &Model) -> Option<Self> {
+        self.model = Some(Sampler::new(model)?);
+        Some(self)
+    }
+
     pub fn build(self) -> Generator<R> {
-        Generator::from_rng(self.rng, self.read_length)
+        let mut generator = Generator::from_rng(self.rng, self.read_length);
+
+        if let Some(instrument) = self.instrument {
+            generator.instrument = instrument;
+        }
+
+        if let Some(run_number) = self.run_number {
+            generator.run_number = run_number;
+        }
+
+        if let Some(flow_cell_id) = self.flow_cell_id {
+            generator.flow_cell_id = flow_cell_id;
+        }
+
+        generator.name_format = self.name_format;
+
+        if let Some(umi_length) = self.umi_length {
+            generator.umi_length = umi_length;
+        }
+
+        generator.umi_location = self.umi_location;
+
+        // The default barcode is only drawn when needed, so that it does not change the records
+        // of a seeded generator otherwise.
+        if self.samples.is_empty()
+            && self
+                .name_template
+                .as_ref()
+                .map(|template| template.has_barcode())
+                .unwrap_or(false)
+        {
+            let barcode = (0..BARCODE_LEN)
+                .map(|_| NUCLEOBASE_CHARSET[generator.rng.gen_range(0..NUCLEOBASE_CHARSET.len())])
+                .collect();
+
+            generator.samples = vec![Sample::new(DEFAULT_SAMPLE_NAME, vec![barcode])];
+        } else {
+            generator.samples = self.samples;
+        }
+
+        generator.name_template = self.name_template;
+        generator.read_length_distribution = self.read_length_distribution;
+        generator.fragment_length = self.fragment_length;
+        generator.fragment_length_std_dev = self.fragment_length_std_dev;
+        generator.orientation = self.orientation;
+        generator.adapters = self.adapters;
+        generator.duplicate_rate = self.duplicate_rate;
+        generator.base_composition = self.base_composition;
+
+        if let Some(quality_model) = self.quality_model {
+            generator.quality_model = Arc::from(quality_model);
+        }
+
+        generator.error_model = self.error_model;
+
+        if self.index_error_rate > 0.0 {
+            generator.index_error_model = Some(ErrorModel::new(self.index_error_rate, 0.0, 0.0));
+        }
+
+        generator.reference = self.reference.map(Arc::new);
+        generator.model = self.model;
+        generator
     }
 }
 
 impl Default for Builder<SmallRng> {
     fn default() -> Self {
-        Self {
-            rng: SmallRng::from_entropy(),
-            read_length: READ_LEN,
-        }
+        Self::from_rng(SmallRng::from_entropy())
     }
 }
 
@@ -43,7 +330,212 @@ mod tests {
 
     #[test]
     fn test_build() {
-        let generator = Builder::default().set_read_length(4).build();
+        let generator = Builder::default()
+            .set_read_length(4)
+            .set_fragment_length(8)
+            .set_orientation(Orientation::Rf)
+            .build();
+
         assert_eq!(generator.read_length, 4);
+        assert_eq!(generator.fragment_length, 8);
+        assert_eq!(generator.orientation, Orientation::Rf);
+    }
+
+    #[test]
+    fn test_build_with_quality_model() {
+        use crate::{fastq::Record, generator::QualityProfile};
+
+        let mut generator = Builder::default()
+            .set_quality_model(QualityProfile::IlluminaNovaseq.build())
+            .build();
+
+        let mut record = Record::default();
+        generator.next_record(&mut record);
+
+        assert!(record
+            .quality_scores()
+            .iter()
+            .all(|score| b"#-8F".contains(score)));
+    }
+
+    #[test]
+    fn test_build_with_name_fields() {
+        use crate::fastq::Record;
+
+        let mut generator = Builder::default()
+            .set_instrument("EAS139")
+            .set_run_number(136)
+            .set_flow_cell_id("FC706VJ")
+            .build();
+
+        let mut record = Record::default();
+        generator.next_record(&mut record);
+
+        assert!(record.name().starts_with(b"@EAS139:136:FC706VJ:"));
+    }
+
+    #[test]
+    fn test_build_with_name_template() {
+        use crate::fastq::Record;
+
+        let mut generator = Builder::default()
+            .set_name_template("{umi}_{barcode}".parse().unwrap())
+            .set_umi_length(4)
+            .set_samples(vec![
+                Sample::new("s1", vec![b"ACGTACGT".to_vec()]),
+                Sample::new("s2", vec![b"TTTTAAAA".to_vec()]),
+            ])
+            .build();
+
+        let mut record = Record::default();
+
+        for _ in 0..8 {
+            generator.next_record(&mut record);
+
+            let name = record.name();
+            assert_eq!(name.len(), 14);
+            assert!(name[1..5].iter().all(|b| b"ACGT".contains(b)));
+            assert!(name.ends_with(b"_ACGTACGT") || name.ends_with(b"_TTTTAAAA"));
+        }
+
+        let mut generator = Builder::default()
+            .set_name_template("{barcode}".parse().unwrap())
+            .build();
+
+        generator.next_record(&mut record);
+        let barcode = record.name().to_vec();
+        assert_eq!(barcode.len(), BARCODE_LEN + 1);
+
+        generator.next_record(&mut record);
+        assert_eq!(record.name(), barcode);
+    }
+
+    #[test]
+    fn test_build_with_umi_location() {
+        use crate::fastq::Record;
+
+        let mut generator = Builder::default()
+            .set_name_template("sim_{umi}".parse().unwrap())
+            .set_umi_length(4)
+            .set_umi_location(UmiLocation::Sequence)
+            .set_read_length(8)
+            .build();
+
+        let mut r = Record::default();
+        let mut s = Record::default();
+
+        for _ in 0..8 {
+            generator.next_pair(&mut r, &mut s);
+            assert_eq!(r.sequence().len(), 8);
+            assert_eq!(&r.sequence()[..4], &r.name()[5..]);
+        }
+
+        let mut generator = Builder::default()
+            .set_umi_location(UmiLocation::Name)
+            .set_duplicate_rate(0.5)
+            .build();
+
+        for _ in 0..8 {
+            generator.next_pair(&mut r, &mut s);
+
+            let umi = r.name().rsplit(|&b| b == b':').next().unwrap();
+            assert_eq!(umi.len(), 8);
+            assert!(umi.iter().all(|b| b"ACGT".contains(b)));
+            assert_eq!(r.name(), s.name());
+        }
+    }
+
+    #[test]
+    fn test_build_with_read_length_distribution() {
+        use crate::fastq::Record;
+
+        let mut generator = Builder::default()
+            .set_read_length_distribution(ReadLengthDistribution::Uniform { min: 4, max: 8 })
+            .build();
+
+        let mut record = Record::default();
+
+        for _ in 0..16 {
+            generator.next_record(&mut record);
+            assert!((4..=8).contains(&record.sequence().len()));
+            assert_eq!(record.quality_scores().len(), record.sequence().len());
+        }
+    }
+
+    #[test]
+    fn test_build_with_duplicate_rate() {
+        use std::collections::HashSet;
+
+        use crate::fastq::Record;
+
+        let mut generator = Builder::default()
+            .set_read_length(16)
+            .set_duplicate_rate(0.5)
+            .build();
+
+        let mut r = Record::default();
+        let mut s = Record::default();
+        let mut pairs = HashSet::new();
+
+        for _ in 0..1000 {
+            generator.next_pair(&mut r, &mut s);
+            pairs.insert((r.sequence().to_vec(), s.sequence().to_vec()));
+        }
+
+        let duplicate_count = 1000 - pairs.len();
+        assert!((400..600).contains(&duplicate_count));
+    }
+
+    #[test]
+    fn test_build_with_base_composition() {
+        use crate::fastq::Record;
+
+        let mut generator = Builder::default()
+            .set_base_composition(BaseComposition::from_gc_content(0.0).unwrap())
+            .build();
+
+        let mut record = Record::default();
+        generator.next_record(&mut record);
+
+        assert!(record.sequence().iter().all(|&b| b == b'A' || b == b'T'));
+    }
+
+    #[test]
+    fn test_build_with_error_model() {
+        use crate::fastq::Record;
+
+        let mut generator = Builder::default()
+            .set_read_length(8)
+            .set_error_model(ErrorModel::new(0.0, 1.0, 0.0))
+            .build();
+
+        let mut record = Record::default();
+        generator.next_record(&mut record);
+
+        assert!(record.sequence().len() > 8);
+        assert_eq!(record.quality_scores().len(), record.sequence().len());
+    }
+
+    #[test]
+    fn test_build_with_index_error_rate() {
+        use crate::fastq::Record;
+
+        let barcode = b"ACGTACGTACGTACGT";
+
+        let mut generator = Builder::default()
+            .set_samples(vec![Sample::new("s1", vec![barcode.to_vec()])])
+            .set_index_error_rate(0.5)
+            .build();
+
+        let mut r = Record::default();
+        let mut s = Record::default();
+        let mut indexes = [Record::default()];
+
+        generator.next_pair(&mut r, &mut s);
+        generator.next_index_reads(r.name(), &mut indexes);
+
+        assert_eq!(indexes[0].name(), r.name());
+        assert_eq!(indexes[0].sequence().len(), barcode.len());
+        assert_ne!(indexes[0].sequence(), barcode);
     }
 }
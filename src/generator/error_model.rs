@@ -0,0 +1,241 @@
+use rand::{Rng, RngCore};
+
+use crate::{fastq::Record, quality::Encoding};
+
+static NUCLEOBASES: &[u8] = b"ACGT";
+
+const QUALITY_OFFSET: u8 = Encoding::Phred33.offset();
+
+/// A model of sequencing errors, i.e., substitutions, insertions, and deletions.
+///
+/// Each rate is the expected number of errors of its kind per base. Errors are correlated with
+/// quality scores: within a read, errors are distributed in proportion to the error probabilities
+/// of the quality scores of the bases, so bases with low scores have more errors.
+///
+/// An inserted base has the quality score of the base it precedes, and a deleted base is removed
+/// with its quality score, so indels change the length of a read.
+///
+/// The error probability of a base is at most 1. When the weight of a base is too large for the
+/// total rate, it is clamped, so errors of each kind keep their relative rates.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{fastq::Record, generator::ErrorModel};
+/// use rand::{rngs::SmallRng, SeedableRng};
+///
+/// let mut rng = SmallRng::seed_from_u64(0);
+/// let model = ErrorModel::new(1.0, 0.0, 0.0);
+///
+/// let mut record = Record::new("@r0", "AAAA", "+", "IIII");
+/// model.apply(&mut rng, &mut record);
+///
+/// assert!(record.sequence().iter().all(|&b| b != b'A'));
+/// assert_eq!(record.quality_scores(), b"IIII");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ErrorModel {
+    substitution_rate: f64,
+    insertion_rate: f64,
+    deletion_rate: f64,
+}
+
+impl ErrorModel {
+    pub fn new(substitution_rate: f64, insertion_rate: f64, deletion_rate: f64) -> Self {
+        Self {
+            substitution_rate,
+            insertion_rate,
+            deletion_rate,
+        }
+    }
+
+    /// Adds errors to the sequence of a record and, for indels, its quality scores.
+    ///
+    /// Quality scores are Phred+33.
+    pub fn apply(&self, rng: &mut dyn RngCore, record: &mut Record) {
+        let len = record.sequence().len();
+
+        if len == 0 {
+            return;
+        }
+
+        let error_probabilities: Vec<f64> = record
+            .quality_scores()
+            .iter()
+            .map(|&score| error_probability(score))
+            .collect();
+
+        // Weights have a mean of 1, so the expected number of errors of a read is independent of
+        // its quality scores.
+        let sum: f64 = error_probabilities.iter().sum();
+        let scale = if sum > 0.0 { len as f64 / sum } else { 1.0 };
+
+        let total_rate = self.deletion_rate + self.insertion_rate + self.substitution_rate;
+        let max_weight = if total_rate > 0.0 {
+            1.0 / total_rate
+        } else {
+            f64::INFINITY
+        };
+
+        let mut sequence = Vec::with_capacity(len);
+        let mut quality_scores = Vec::with_capacity(len);
+
+        for (i, (&base, &score)) in record
+            .sequence()
+            .iter()
+            .zip(record.quality_scores())
+            .enumerate()
+        {
+            let weight = error_probabilities
+                .get(i)
+                .map(|p| p * scale)
+                .unwrap_or(1.0)
+                .min(max_weight);
+
+            let deletion_threshold = self.deletion_rate * weight;
+            let insertion_threshold = deletion_threshold + self.insertion_rate * weight;
+            let substitution_threshold = insertion_threshold + self.substitution_rate * weight;
+
+            let n: f64 = rng.gen();
+
+            if n < deletion_threshold {
+                continue;
+            } else if n < insertion_threshold {
+                sequence.push(NUCLEOBASES[rng.gen_range(0..NUCLEOBASES.len())]);
+                quality_scores.push(score);
+                sequence.push(base);
+            } else if n < substitution_threshold {
+                sequence.push(substitute(rng, base));
+            } else {
+                sequence.push(base);
+            }
+
+            quality_scores.push(score);
+        }
+
+        *record.sequence_mut() = sequence;
+        *record.quality_scores_mut() = quality_scores;
+    }
+}
+
+// Returns the probability that a base with the given Phred+33 quality score is wrong.
+fn error_probability(score: u8) -> f64 {
+    let q = score.saturating_sub(QUALITY_OFFSET);
+    10f64.powf(-f64::from(q) / 10.0)
+}
+
+// Returns a random nucleobase that is different from `base`.
+fn substitute(rng: &mut dyn RngCore, base: u8) -> u8 {
+    let others: Vec<u8> = NUCLEOBASES
+        .iter()
+        .copied()
+        .filter(|&b| b != base.to_ascii_uppercase())
+        .collect();
+
+    others[rng.gen_range(0..others.len())]
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn test_apply_with_no_errors() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let model = ErrorModel::default();
+
+        let mut record = Record::new("@r0", "ACGT", "+", "!5?I");
+        model.apply(&mut rng, &mut record);
+
+        assert_eq!(record.sequence(), b"ACGT");
+        assert_eq!(record.quality_scores(), b"!5?I");
+    }
+
+    #[test]
+    fn test_apply_with_indels() {
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        let model = ErrorModel::new(0.0, 1.0, 0.0);
+        let mut record = Record::new("@r0", "ACGT", "+", "IIII");
+        model.apply(&mut rng, &mut record);
+        assert_eq!(record.sequence().len(), 8);
+        assert_eq!(record.quality_scores(), b"IIIIIIII");
+
+        let model = ErrorModel::new(0.0, 0.0, 1.0);
+        let mut record = Record::new("@r0", "ACGT", "+", "IIII");
+        model.apply(&mut rng, &mut record);
+        assert!(record.sequence().is_empty());
+        assert!(record.quality_scores().is_empty());
+    }
+
+    #[test]
+    fn test_apply_correlates_errors_with_quality_scores() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let model = ErrorModel::new(0.1, 0.0, 0.0);
+
+        let mut low_quality_error_count = 0;
+        let mut high_quality_error_count = 0;
+
+        for _ in 0..1000 {
+            let mut record = Record::new("@r0", "AAAAAAAA", "+", "++++IIII");
+            model.apply(&mut rng, &mut record);
+
+            for (i, &base) in record.sequence().iter().enumerate() {
+                if base != b'A' {
+                    if i < 4 {
+                        low_quality_error_count += 1;
+                    } else {
+                        high_quality_error_count += 1;
+                    }
+                }
+            }
+        }
+
+        assert!(low_quality_error_count > 10 * high_quality_error_count);
+    }
+
+    #[test]
+    fn test_apply_clamps_weights() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let model = ErrorModel::new(0.25, 0.0, 0.25);
+
+        let mut substitution_count = 0;
+        let mut deletion_count = 0;
+
+        // The weight of the first base is ~8, so without clamping, it is always deleted.
+        for _ in 0..64 {
+            let mut record = Record::new("@r0", "CAAAAAAA", "+", "!IIIIIII");
+            model.apply(&mut rng, &mut record);
+
+            match record.sequence() {
+                [b'C', ..] => {}
+                [_, ..] if record.sequence().len() == 8 => substitution_count += 1,
+                _ => deletion_count += 1,
+            }
+        }
+
+        assert!(substitution_count > 0);
+        assert!(deletion_count > 0);
+        assert_eq!(substitution_count + deletion_count, 64);
+    }
+
+    #[test]
+    fn test_error_probability() {
+        assert_eq!(error_probability(b'!'), 1.0);
+        assert_eq!(error_probability(b'+'), 0.1);
+        assert!((error_probability(b'5') - 0.01).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_substitute() {
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        for &base in b"ACGTNa" {
+            let b = substitute(&mut rng, base);
+            assert!(NUCLEOBASES.contains(&b));
+            assert_ne!(b, base.to_ascii_uppercase());
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Read, Write},
+};
+
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+
+use serde_json::{json, Value};
+
+use crate::{fastq::Record, quality::Encoding};
+
+const FORMAT_VERSION: u64 = 1;
+const QUALITY_OFFSET: u8 = Encoding::Phred33.offset();
+// The largest score that is printable in Phred+33, i.e., `~`.
+const MAX_QUALITY_SCORE: u8 = b'~' - QUALITY_OFFSET;
+
+/// A profile of read lengths, per-cycle quality scores, GC content, and N rate.
+///
+/// A model is learned from existing records and can be saved as JSON, e.g., to share a synthetic
+/// stand-in for a restricted dataset. A [`super::Generator`] with a model simulates records that
+/// match the profile.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{fastq::Record, generator::Model};
+///
+/// let mut model = Model::default();
+/// model.add(&Record::new("@r0", "ACGN", "+", "FQLB"));
+///
+/// assert_eq!(model.record_count(), 1);
+/// assert_eq!(model.gc_content(), 2.0 / 3.0);
+/// assert_eq!(model.n_rate(), 0.25);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Model {
+    read_lengths: BTreeMap<usize, u64>,
+    cycle_quality_scores: Vec<BTreeMap<u8, u64>>,
+    base_count: u64,
+    gc_count: u64,
+    n_count: u64,
+}
+
+impl Model {
+    /// Adds the profile of a record to the model.
+    pub fn add(&mut self, record: &Record) {
+        let sequence = record.sequence();
+
+        *self.read_lengths.entry(sequence.len()).or_insert(0) += 1;
+
+        for &base in sequence {
+            match base.to_ascii_uppercase() {
+                b'G' | b'C' | b'S' => self.gc_count += 1,
+                b'N' => self.n_count += 1,
+                _ => {}
+            }
+        }
+
+        self.base_count += sequence.len() as u64;
+
+        let quality_scores = record.quality_scores();
+
+        if self.cycle_quality_scores.len() < quality_scores.len() {
+            self.cycle_quality_scores
+                .resize(quality_scores.len(), BTreeMap::new());
+        }
+
+        for (cycle, &score) in self.cycle_quality_scores.iter_mut().zip(quality_scores) {
+            let phred = score.saturating_sub(QUALITY_OFFSET);
+            *cycle.entry(phred).or_insert(0) += 1;
+        }
+    }
+
+    /// Returns the number of records added to the model.
+    pub fn record_count(&self) -> u64 {
+        self.read_lengths.values().sum()
+    }
+
+    /// Returns the fraction of G and C bases, excluding Ns.
+    pub fn gc_content(&self) -> f64 {
+        let called_count = self.base_count - self.n_count;
+
+        if called_count == 0 {
+            0.0
+        } else {
+            self.gc_count as f64 / called_count as f64
+        }
+    }
+
+    /// Returns the fraction of N bases.
+    pub fn n_rate(&self) -> f64 {
+        if self.base_count == 0 {
+            0.0
+        } else {
+            self.n_count as f64 / self.base_count as f64
+        }
+    }
+
+    /// Reads a model from JSON.
+    pub fn read<R>(reader: R) -> io::Result<Self>
+    where
+        R: Read,
+    {
+        let value: Value = serde_json::from_reader(reader)?;
+
+        Self::from_json(&value)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid generator model"))
+    }
+
+    /// Writes the model as JSON.
+    pub fn write<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        writeln!(writer, "{}", self.to_json())
+    }
+
+    fn to_json(&self) -> Value {
+        let read_lengths = histogram_to_json(self.read_lengths.iter().map(|(&k, &v)| (k, v)));
+
+        let cycle_quality_scores = self
+            .cycle_quality_scores
+            .iter()
+            .map(|cycle| histogram_to_json(cycle.iter().map(|(&k, &v)| (usize::from(k), v))))
+            .collect();
+
+        json!({
+            "version": FORMAT_VERSION,
+            "readLengths": read_lengths,
+            "cycleQualityScores": Value::Array(cycle_quality_scores),
+            "baseCount": self.base_count,
+            "gcCount": self.gc_count,
+            "nCount": self.n_count,
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        if value.get("version")?.as_u64()? != FORMAT_VERSION {
+            return None;
+        }
+
+        let read_lengths = histogram_from_json(value.get("readLengths")?)?
+            .into_iter()
+            .collect();
+
+        let cycle_quality_scores = value
+            .get("cycleQualityScores")?
+            .as_array()?
+            .iter()
+            .map(|cycle| {
+                histogram_from_json(cycle)?
+                    .into_iter()
+                    .map(|(k, v)| u8::try_from(k).ok().map(|k| (k, v)))
+                    .collect()
+            })
+            .collect::<Option<_>>()?;
+
+        let model = Self {
+            read_lengths,
+            cycle_quality_scores,
+            base_count: value.get("baseCount")?.as_u64()?,
+            gc_count: value.get("gcCount")?.as_u64()?,
+            n_count: value.get("nCount")?.as_u64()?,
+        };
+
+        if model.gc_count + model.n_count > model.base_count {
+            return None;
+        }
+
+        Some(model)
+    }
+}
+
+// Histograms are written as arrays of `[value, count]` pairs.
+fn histogram_to_json<I>(iter: I) -> Value
+where
+    I: Iterator<Item = (usize, u64)>,
+{
+    iter.map(|(k, v)| json!([k, v])).collect()
+}
+
+fn histogram_from_json(value: &Value) -> Option<Vec<(usize, u64)>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|pair| match pair.as_array()?.as_slice() {
+            [k, v] => Some((k.as_u64()? as usize, v.as_u64()?)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Samples read lengths, bases, and quality scores from a [`Model`].
+#[derive(Clone)]
+pub(super) struct Sampler {
+    read_lengths: Vec<usize>,
+    read_length_distribution: WeightedIndex<f64>,
+    cycle_quality_scores: Vec<(Vec<u8>, WeightedIndex<f64>)>,
+    gc_content: f64,
+    n_rate: f64,
+}
+
+impl Sampler {
+    /// Creates a sampler from a model.
+    ///
+    /// This returns `None` if the model has no records or no quality scores.
+    pub(super) fn new(model: &Model) -> Option<Self> {
+        let (read_lengths, weights) = split_histogram(model.read_lengths.iter())?;
+        let read_length_distribution = WeightedIndex::new(weights).ok()?;
+
+        let cycle_quality_scores = model
+            .cycle_quality_scores
+            .iter()
+            .map(|cycle| {
+                let (scores, weights) = split_histogram(cycle.iter())?;
+                let distribution = WeightedIndex::new(weights).ok()?;
+                Some((scores, distribution))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        if cycle_quality_scores.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            read_lengths,
+            read_length_distribution,
+            cycle_quality_scores,
+            gc_content: model.gc_content(),
+            n_rate: model.n_rate(),
+        })
+    }
+
+    pub(super) fn sample_read_length<R>(&self, rng: &mut R) -> usize
+    where
+        R: Rng,
+    {
+        let i = self.read_length_distribution.sample(rng);
+        self.read_lengths[i]
+    }
+
+    pub(super) fn sample_base<R>(&self, rng: &mut R) -> u8
+    where
+        R: Rng,
+    {
+        if rng.gen_bool(self.n_rate) {
+            return b'N';
+        }
+
+        match (rng.gen_bool(self.gc_content), rng.gen_bool(0.5)) {
+            (true, true) => b'G',
+            (true, false) => b'C',
+            (false, true) => b'A',
+            (false, false) => b'T',
+        }
+    }
+
+    /// Samples a Phred quality score for the given cycle.
+    ///
+    /// Cycles past the end of the model reuse the last modeled cycle. Scores are clamped to the
+    /// largest score that is printable in Phred+33 (93).
+    pub(super) fn sample_quality_score<R>(&self, rng: &mut R, cycle: usize) -> u8
+    where
+        R: Rng,
+    {
+        let i = cycle.min(self.cycle_quality_scores.len() - 1);
+        let (scores, distribution) = &self.cycle_quality_scores[i];
+        scores[distribution.sample(rng)].min(MAX_QUALITY_SCORE)
+    }
+}
+
+fn split_histogram<'a, K, I>(iter: I) -> Option<(Vec<K>, Vec<f64>)>
+where
+    K: Copy + 'a,
+    I: Iterator<Item = (&'a K, &'a u64)>,
+{
+    let (values, weights): (Vec<_>, Vec<_>) = iter.map(|(&k, &v)| (k, v as f64)).unzip();
+
+    if values.is_empty() {
+        None
+    } else {
+        Some((values, weights))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use super::*;
+
+    fn build_model() -> Model {
+        let mut model = Model::default();
+        model.add(&Record::new("@r0", "ACGT", "+", "+5?I"));
+        model.add(&Record::new("@r1", "GCNNGC", "+", "+5????"));
+        model
+    }
+
+    #[test]
+    fn test_add() {
+        let model = build_model();
+
+        assert_eq!(model.record_count(), 2);
+        assert_eq!(model.base_count, 10);
+        assert_eq!(model.gc_count, 6);
+        assert_eq!(model.n_count, 2);
+        assert_eq!(model.gc_content(), 0.75);
+        assert_eq!(model.n_rate(), 0.2);
+
+        assert_eq!(model.cycle_quality_scores.len(), 6);
+        assert_eq!(model.cycle_quality_scores[0].get(&10), Some(&2));
+        assert_eq!(model.cycle_quality_scores[3].get(&40), Some(&1));
+        assert_eq!(model.cycle_quality_scores[3].get(&30), Some(&1));
+    }
+
+    #[test]
+    fn test_read_and_write() -> io::Result<()> {
+        let model = build_model();
+
+        let mut buf = Vec::new();
+        model.write(&mut buf)?;
+
+        let actual = Model::read(&buf[..])?;
+        assert_eq!(actual, model);
+
+        assert!(Model::read(&b"{}"[..]).is_err());
+        assert!(Model::read(&b"[1,"[..]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sampler() {
+        let model = build_model();
+        let sampler = Sampler::new(&model).unwrap();
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        for _ in 0..16 {
+            let len = sampler.sample_read_length(&mut rng);
+            assert!(len == 4 || len == 6);
+
+            assert_eq!(sampler.sample_quality_score(&mut rng, 0), 10);
+            assert_eq!(sampler.sample_quality_score(&mut rng, 64), 30);
+        }
+
+        assert!(Sampler::new(&Model::default()).is_none());
+    }
+
+    #[test]
+    fn test_sampler_with_out_of_range_quality_score() {
+        let mut model = build_model();
+        model.cycle_quality_scores = vec![[(255, 1)].into_iter().collect()];
+
+        let sampler = Sampler::new(&model).unwrap();
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        assert_eq!(sampler.sample_quality_score(&mut rng, 0), 93);
+    }
+}
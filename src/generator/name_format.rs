@@ -0,0 +1,145 @@
+use std::{error, fmt, str::FromStr};
+
+use rand::Rng;
+
+const LANES: u64 = 8;
+const SURFACES: u64 = 2;
+const SWATHS: u64 = 4;
+const TILES_PER_SWATH: u64 = 78;
+const TILES_PER_LANE: u64 = SURFACES * SWATHS * TILES_PER_SWATH;
+const READS_PER_TILE: u64 = 4096;
+
+const MIN_X: u32 = 1000;
+const MAX_X: u32 = 32000;
+const MIN_Y: u64 = 1000;
+// The distance in y between consecutive reads of a tile.
+const Y_STEP: u64 = 8;
+
+/// The format of the names of generated records.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NameFormat {
+    /// Illumina-like names with random lane, tile, and x and y coordinates, and the read number
+    /// as a `/1` or `/2` suffix, e.g., `@fqlib5:440:ZMXYPLK:7:15:9764:6446/1`.
+    #[default]
+    Random,
+    /// Illumina Casava 1.8+ names with incrementing coordinates and the read number in the
+    /// comment, e.g., `@fqlib5:440:ZMXYPLK:1:1101:17562:1003 1:N:0:1`.
+    ///
+    /// Reads are ordered by lane, tile, and y coordinate, as they are in the output of bcl2fastq.
+    /// The coordinates only depend on the position of the record in the dataset.
+    Illumina,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseNameFormatError(String);
+
+impl error::Error for ParseNameFormatError {}
+
+impl fmt::Display for ParseNameFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid name format: expected random or illumina, got '{}'",
+            self.0
+        )
+    }
+}
+
+impl FromStr for NameFormat {
+    type Err = ParseNameFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Self::Random),
+            "illumina" => Ok(Self::Illumina),
+            _ => Err(ParseNameFormatError(s.into())),
+        }
+    }
+}
+
+/// The location of a cluster on a flow cell.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct Coordinates {
+    pub lane: u64,
+    pub tile: u64,
+    pub x: u32,
+    pub y: u64,
+}
+
+impl Coordinates {
+    /// Returns plausible coordinates of the record at `record_index`.
+    ///
+    /// Tiles are numbered `<surface><swath><tile>`, e.g., 2104 is tile 4 of swath 1 on the bottom
+    /// surface, as they are for patterned flow cells. Within a tile, y increases with the record
+    /// index, and x is random.
+    pub(super) fn at<R>(rng: &mut R, record_index: u64) -> Self
+    where
+        R: Rng,
+    {
+        let tile_index = record_index / READS_PER_TILE;
+        let i = record_index % READS_PER_TILE;
+
+        let lane = (tile_index / TILES_PER_LANE) % LANES + 1;
+
+        let t = tile_index % TILES_PER_LANE;
+        let surface = t / (SWATHS * TILES_PER_SWATH) + 1;
+        let swath = (t / TILES_PER_SWATH) % SWATHS + 1;
+        let tile = surface * 1000 + swath * 100 + t % TILES_PER_SWATH + 1;
+
+        let x = rng.gen_range(MIN_X..=MAX_X);
+        let y = MIN_Y + i * Y_STEP + rng.gen_range(0..Y_STEP);
+
+        Self { lane, tile, x, y }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("random".parse(), Ok(NameFormat::Random));
+        assert_eq!("illumina".parse(), Ok(NameFormat::Illumina));
+
+        assert_eq!(
+            "casava".parse::<NameFormat>(),
+            Err(ParseNameFormatError(String::from("casava")))
+        );
+    }
+
+    #[test]
+    fn test_coordinates_at() {
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        let a = Coordinates::at(&mut rng, 0);
+        assert_eq!((a.lane, a.tile), (1, 1101));
+        assert!((MIN_Y..MIN_Y + Y_STEP).contains(&a.y));
+        assert!((MIN_X..=MAX_X).contains(&a.x));
+
+        let b = Coordinates::at(&mut rng, 1);
+        assert_eq!((b.lane, b.tile), (1, 1101));
+        assert!(b.y > a.y);
+
+        let c = Coordinates::at(&mut rng, READS_PER_TILE);
+        assert_eq!((c.lane, c.tile), (1, 1102));
+        assert!(c.y < b.y);
+
+        let d = Coordinates::at(&mut rng, READS_PER_TILE * TILES_PER_SWATH);
+        assert_eq!((d.lane, d.tile), (1, 1201));
+
+        let e = Coordinates::at(&mut rng, READS_PER_TILE * SWATHS * TILES_PER_SWATH);
+        assert_eq!((e.lane, e.tile), (1, 2101));
+
+        let f = Coordinates::at(&mut rng, READS_PER_TILE * TILES_PER_LANE - 1);
+        assert_eq!((f.lane, f.tile), (1, 2478));
+
+        let g = Coordinates::at(&mut rng, READS_PER_TILE * TILES_PER_LANE);
+        assert_eq!((g.lane, g.tile), (2, 1101));
+
+        let h = Coordinates::at(&mut rng, READS_PER_TILE * TILES_PER_LANE * LANES);
+        assert_eq!((h.lane, h.tile), (1, 1101));
+    }
+}
@@ -0,0 +1,218 @@
+use std::{error, fmt, str::FromStr};
+
+const INDEX_PLACEHOLDER: &str = "index";
+const INSTRUMENT_PLACEHOLDER: &str = "instrument";
+const RUN_PLACEHOLDER: &str = "run";
+const FLOW_CELL_PLACEHOLDER: &str = "flowcell";
+const UMI_PLACEHOLDER: &str = "umi";
+const BARCODE_PLACEHOLDER: &str = "barcode";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) enum Segment {
+    Literal(Vec<u8>),
+    Index,
+    Instrument,
+    Run,
+    FlowCell,
+    Umi,
+    Barcode,
+}
+
+/// A template of the names of generated records.
+///
+/// A template is literal text with placeholders: `{index}` is replaced by the 1-based index of
+/// the record (or pair) in the dataset; `{instrument}`, `{run}`, and `{flowcell}` by the
+/// instrument, run number, and flow cell ID of the generator; `{umi}` by the UMI of the pair;
+/// and `{barcode}` by one of the sample barcodes of the generator.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{fastq::Record, generator::{Builder, NameTemplate}};
+///
+/// let template: NameTemplate = "sim_{run}_{index}".parse()?;
+///
+/// let mut generator = Builder::default()
+///     .set_run_number(8)
+///     .set_name_template(template)
+///     .build();
+///
+/// let mut record = Record::default();
+/// generator.next_record(&mut record);
+/// assert_eq!(record.name(), b"@sim_8_1");
+/// # Ok::<(), fq::generator::ParseNameTemplateError>(())
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NameTemplate {
+    segments: Vec<Segment>,
+}
+
+impl NameTemplate {
+    pub(super) fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Returns whether the template has a `{barcode}` placeholder.
+    pub fn has_barcode(&self) -> bool {
+        self.segments.contains(&Segment::Barcode)
+    }
+
+    /// Returns whether the template has a `{umi}` placeholder.
+    pub fn has_umi(&self) -> bool {
+        self.segments.contains(&Segment::Umi)
+    }
+}
+
+/// An error returned when a name template fails to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseNameTemplateError {
+    /// The template is empty.
+    Empty,
+    /// The template has whitespace, which would start the name comment.
+    InvalidCharacter(char),
+    /// A placeholder is not closed.
+    UnclosedPlaceholder,
+    /// A placeholder is not one of `{index}`, `{instrument}`, `{run}`, `{flowcell}`, `{umi}`, or
+    /// `{barcode}`.
+    InvalidPlaceholder(String),
+}
+
+impl error::Error for ParseNameTemplateError {}
+
+impl fmt::Display for ParseNameTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("empty template"),
+            Self::InvalidCharacter(c) => write!(f, "invalid template character: {:?}", c),
+            Self::UnclosedPlaceholder => f.write_str("unclosed template placeholder"),
+            Self::InvalidPlaceholder(s) => write!(
+                f,
+                "invalid template placeholder: expected {{index}}, {{instrument}}, {{run}}, {{flowcell}}, {{umi}}, or {{barcode}}, got '{{{}}}'",
+                s
+            ),
+        }
+    }
+}
+
+impl FromStr for NameTemplate {
+    type Err = ParseNameTemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseNameTemplateError::Empty);
+        }
+
+        if let Some(c) = s.chars().find(|c| c.is_whitespace()) {
+            return Err(ParseNameTemplateError::InvalidCharacter(c));
+        }
+
+        let mut segments = Vec::new();
+        let mut rest = s;
+
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(Segment::Literal(rest[..start].into()));
+            }
+
+            let end = rest[start..]
+                .find('}')
+                .map(|i| start + i)
+                .ok_or(ParseNameTemplateError::UnclosedPlaceholder)?;
+
+            let segment = match &rest[start + 1..end] {
+                INDEX_PLACEHOLDER => Segment::Index,
+                INSTRUMENT_PLACEHOLDER => Segment::Instrument,
+                RUN_PLACEHOLDER => Segment::Run,
+                FLOW_CELL_PLACEHOLDER => Segment::FlowCell,
+                UMI_PLACEHOLDER => Segment::Umi,
+                BARCODE_PLACEHOLDER => Segment::Barcode,
+                placeholder => {
+                    return Err(ParseNameTemplateError::InvalidPlaceholder(
+                        placeholder.into(),
+                    ))
+                }
+            };
+
+            segments.push(segment);
+            rest = &rest[end + 1..];
+        }
+
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.into()));
+        }
+
+        Ok(Self { segments })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            "sim_{run}_{index}".parse(),
+            Ok(NameTemplate {
+                segments: vec![
+                    Segment::Literal(b"sim_".to_vec()),
+                    Segment::Run,
+                    Segment::Literal(b"_".to_vec()),
+                    Segment::Index,
+                ]
+            })
+        );
+
+        assert_eq!(
+            "{instrument}:{flowcell}:{umi}+{barcode}".parse(),
+            Ok(NameTemplate {
+                segments: vec![
+                    Segment::Instrument,
+                    Segment::Literal(b":".to_vec()),
+                    Segment::FlowCell,
+                    Segment::Literal(b":".to_vec()),
+                    Segment::Umi,
+                    Segment::Literal(b"+".to_vec()),
+                    Segment::Barcode,
+                ]
+            })
+        );
+
+        assert_eq!(
+            "".parse::<NameTemplate>(),
+            Err(ParseNameTemplateError::Empty)
+        );
+        assert_eq!(
+            "sim {index}".parse::<NameTemplate>(),
+            Err(ParseNameTemplateError::InvalidCharacter(' '))
+        );
+        assert_eq!(
+            "sim_{index".parse::<NameTemplate>(),
+            Err(ParseNameTemplateError::UnclosedPlaceholder)
+        );
+        assert_eq!(
+            "sim_{lane}".parse::<NameTemplate>(),
+            Err(ParseNameTemplateError::InvalidPlaceholder(String::from(
+                "lane"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_has_barcode() {
+        let template: NameTemplate = "sim_{index}".parse().unwrap();
+        assert!(!template.has_barcode());
+
+        let template: NameTemplate = "sim_{index}_{barcode}".parse().unwrap();
+        assert!(template.has_barcode());
+    }
+
+    #[test]
+    fn test_has_umi() {
+        let template: NameTemplate = "sim_{index}".parse().unwrap();
+        assert!(!template.has_umi());
+
+        let template: NameTemplate = "sim_{index}_{umi}".parse().unwrap();
+        assert!(template.has_umi());
+    }
+}
@@ -0,0 +1,67 @@
+use std::{error, fmt, str::FromStr};
+
+/// The relative orientation of read 1 and read 2 in a generated pair.
+///
+/// Orientations are given as the strand of read 1 followed by the strand of read 2, where each
+/// read is drawn from opposite ends of the same fragment.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Orientation {
+    /// Read 1 is on the forward strand; read 2, the reverse strand (pointing inward).
+    #[default]
+    Fr,
+    /// Read 1 is on the reverse strand; read 2, the forward strand (pointing outward).
+    Rf,
+    /// Both reads are on the forward strand.
+    Ff,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError(String);
+
+impl error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid input: '{}'", self.0)
+    }
+}
+
+impl FromStr for Orientation {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fr" => Ok(Self::Fr),
+            "rf" => Ok(Self::Rf),
+            "ff" => Ok(Self::Ff),
+            _ => Err(ParseError(s.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        assert_eq!(Orientation::default(), Orientation::Fr);
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("fr".parse(), Ok(Orientation::Fr));
+        assert_eq!("rf".parse(), Ok(Orientation::Rf));
+        assert_eq!("ff".parse(), Ok(Orientation::Ff));
+
+        assert_eq!("".parse::<Orientation>(), Err(ParseError(String::new())));
+        assert_eq!(
+            "FR".parse::<Orientation>(),
+            Err(ParseError(String::from("FR")))
+        );
+        assert_eq!(
+            "rr".parse::<Orientation>(),
+            Err(ParseError(String::from("rr")))
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use std::{error, fmt, str::FromStr};
+
+use rand::{distributions::Distribution, RngCore};
+use rand_distr::Normal;
+
+use crate::distributions::QualityScores;
+
+const MIN_SCORE: f64 = 2.0;
+const MAX_SCORE: f64 = 41.0;
+
+/// A model of the quality scores of generated reads.
+///
+/// Unlike a [`Distribution`], a quality model can depend on the cycle, i.e., the position of the
+/// base in the read.
+pub trait QualityModel: Send + Sync {
+    /// Samples the Phred quality score of the base at `cycle` of a read with `read_length` bases.
+    fn sample(&self, rng: &mut dyn RngCore, cycle: usize, read_length: usize) -> u8;
+}
+
+/// Quality scores that do not depend on the cycle.
+impl QualityModel for QualityScores {
+    fn sample(&self, rng: &mut dyn RngCore, _: usize, _: usize) -> u8 {
+        Distribution::sample(self, rng)
+    }
+}
+
+/// Quality scores that are high at the 5' end and degrade toward the 3' end, with increasing
+/// variance, as is typical of sequencing by synthesis.
+///
+/// # Examples
+///
+/// ```
+/// use fq::generator::{DegradingQualityScores, QualityModel};
+/// use rand::{rngs::SmallRng, SeedableRng};
+///
+/// let mut rng = SmallRng::seed_from_u64(0);
+/// let model = DegradingQualityScores::default();
+///
+/// let first: u32 = (0..100).map(|_| u32::from(model.sample(&mut rng, 0, 150))).sum();
+/// let last: u32 = (0..100).map(|_| u32::from(model.sample(&mut rng, 149, 150))).sum();
+/// assert!(first > last);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DegradingQualityScores {
+    start_mean: f64,
+    end_mean: f64,
+    start_std_dev: f64,
+    end_std_dev: f64,
+}
+
+impl DegradingQualityScores {
+    // Returns the mean and standard deviation at the relative position `x` in [0, 1] of a read.
+    //
+    // Quality falls off quadratically, so most of the loss is in the 3' tail.
+    fn parameters(&self, x: f64) -> (f64, f64) {
+        let mean = self.start_mean - (self.start_mean - self.end_mean) * x * x;
+        let std_dev = self.start_std_dev + (self.end_std_dev - self.start_std_dev) * x;
+        (mean, std_dev)
+    }
+}
+
+impl Default for DegradingQualityScores {
+    fn default() -> Self {
+        Self {
+            start_mean: 38.0,
+            end_mean: 26.0,
+            start_std_dev: 1.5,
+            end_std_dev: 6.0,
+        }
+    }
+}
+
+impl QualityModel for DegradingQualityScores {
+    fn sample(&self, rng: &mut dyn RngCore, cycle: usize, read_length: usize) -> u8 {
+        let x = relative_position(cycle, read_length);
+        let (mean, std_dev) = self.parameters(x);
+        sample_normal(rng, mean, std_dev)
+    }
+}
+
+/// Binned quality scores of Illumina NovaSeq (RTA3) runs.
+///
+/// Scores are one of 2, 12, 23, or 37, mostly 37, with more low bins toward the 3' end.
+///
+/// # Examples
+///
+/// ```
+/// use fq::generator::{NovaseqQualityScores, QualityModel};
+/// use rand::{rngs::SmallRng, SeedableRng};
+///
+/// let mut rng = SmallRng::seed_from_u64(0);
+/// let model = NovaseqQualityScores::default();
+///
+/// for cycle in 0..150 {
+///     assert!([2, 12, 23, 37].contains(&model.sample(&mut rng, cycle, 150)));
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NovaseqQualityScores {
+    // The unbinned scores.
+    scores: DegradingQualityScores,
+}
+
+impl NovaseqQualityScores {
+    fn bin(score: u8) -> u8 {
+        match score {
+            0..=2 => 2,
+            3..=14 => 12,
+            15..=30 => 23,
+            _ => 37,
+        }
+    }
+}
+
+impl QualityModel for NovaseqQualityScores {
+    fn sample(&self, rng: &mut dyn RngCore, cycle: usize, read_length: usize) -> u8 {
+        Self::bin(self.scores.sample(rng, cycle, read_length))
+    }
+}
+
+// Returns the position of `cycle` in a read, from 0 at the first base to 1 at the last base.
+fn relative_position(cycle: usize, read_length: usize) -> f64 {
+    if read_length > 1 {
+        cycle as f64 / (read_length - 1) as f64
+    } else {
+        0.0
+    }
+}
+
+fn sample_normal(rng: &mut dyn RngCore, mean: f64, std_dev: f64) -> u8 {
+    // Std. dev. is never < 0.0.
+    let distribution = Normal::new(mean, std_dev).unwrap();
+    let n = distribution.sample(rng);
+    n.clamp(MIN_SCORE, MAX_SCORE).round() as u8
+}
+
+/// A named quality model.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum QualityProfile {
+    /// Scores around Q20 at every cycle ([`QualityScores`]).
+    #[default]
+    Flat,
+    /// High scores that degrade toward the 3' end ([`DegradingQualityScores`]).
+    Degrading,
+    /// Binned scores of Illumina NovaSeq runs ([`NovaseqQualityScores`]).
+    IlluminaNovaseq,
+}
+
+impl QualityProfile {
+    /// Builds the quality model of this profile.
+    pub fn build(&self) -> Box<dyn QualityModel> {
+        match self {
+            Self::Flat => Box::new(QualityScores::default()),
+            Self::Degrading => Box::new(DegradingQualityScores::default()),
+            Self::IlluminaNovaseq => Box::new(NovaseqQualityScores::default()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseQualityProfileError(String);
+
+impl error::Error for ParseQualityProfileError {}
+
+impl fmt::Display for ParseQualityProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid quality profile: '{}'", self.0)
+    }
+}
+
+impl FromStr for QualityProfile {
+    type Err = ParseQualityProfileError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flat" => Ok(Self::Flat),
+            "degrading" => Ok(Self::Degrading),
+            "illumina-novaseq" => Ok(Self::IlluminaNovaseq),
+            _ => Err(ParseQualityProfileError(s.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use super::*;
+
+    fn mean_score(model: &dyn QualityModel, cycle: usize, read_length: usize) -> f64 {
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        let sum: u32 = (0..1000)
+            .map(|_| u32::from(model.sample(&mut rng, cycle, read_length)))
+            .sum();
+
+        f64::from(sum) / 1000.0
+    }
+
+    #[test]
+    fn test_degrading_quality_scores() {
+        let model = DegradingQualityScores::default();
+
+        assert!((mean_score(&model, 0, 100) - 38.0).abs() < 0.5);
+        assert!((mean_score(&model, 99, 100) - 26.0).abs() < 1.0);
+        assert!(mean_score(&model, 50, 100) > mean_score(&model, 90, 100));
+
+        assert!((mean_score(&model, 0, 1) - 38.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_novaseq_quality_scores() {
+        assert_eq!(NovaseqQualityScores::bin(0), 2);
+        assert_eq!(NovaseqQualityScores::bin(14), 12);
+        assert_eq!(NovaseqQualityScores::bin(15), 23);
+        assert_eq!(NovaseqQualityScores::bin(31), 37);
+        assert_eq!(NovaseqQualityScores::bin(41), 37);
+
+        let model = NovaseqQualityScores::default();
+        assert!(mean_score(&model, 0, 100) > 36.0);
+        assert!(mean_score(&model, 0, 100) > mean_score(&model, 99, 100));
+    }
+
+    #[test]
+    fn test_quality_profile_from_str() {
+        assert_eq!("flat".parse(), Ok(QualityProfile::Flat));
+        assert_eq!("degrading".parse(), Ok(QualityProfile::Degrading));
+        assert_eq!(
+            "illumina-novaseq".parse(),
+            Ok(QualityProfile::IlluminaNovaseq)
+        );
+
+        assert_eq!(
+            "novaseq".parse::<QualityProfile>(),
+            Err(ParseQualityProfileError(String::from("novaseq")))
+        );
+    }
+}
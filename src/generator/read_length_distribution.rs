@@ -0,0 +1,168 @@
+use std::{error, fmt, str::FromStr};
+
+use rand::{distributions::Distribution, Rng};
+use rand_distr::Normal;
+
+/// A distribution of the lengths of generated reads.
+///
+/// # Examples
+///
+/// ```
+/// use fq::generator::ReadLengthDistribution;
+/// use rand::{distributions::Distribution, rngs::SmallRng, SeedableRng};
+///
+/// let mut rng = SmallRng::seed_from_u64(0);
+/// let distribution: ReadLengthDistribution = "uniform:50,150".parse()?;
+///
+/// let len = distribution.sample(&mut rng);
+/// assert!((50..=150).contains(&len));
+/// # Ok::<_, fq::generator::ParseReadLengthDistributionError>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReadLengthDistribution {
+    /// All reads have the same length.
+    Fixed(usize),
+    /// Normally distributed lengths, rounded and at least 1.
+    Normal { mean: f64, std_dev: f64 },
+    /// Uniformly distributed lengths in [min, max].
+    Uniform { min: usize, max: usize },
+}
+
+impl Distribution<usize> for ReadLengthDistribution {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        match *self {
+            Self::Fixed(len) => len,
+            Self::Normal { mean, std_dev } => {
+                // The parameters are validated when parsed.
+                let distribution = Normal::new(mean, std_dev).unwrap();
+                distribution.sample(rng).round().max(1.0) as usize
+            }
+            Self::Uniform { min, max } => rng.gen_range(min..=max),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseReadLengthDistributionError(String);
+
+impl error::Error for ParseReadLengthDistributionError {}
+
+impl fmt::Display for ParseReadLengthDistributionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid read length distribution: expected `fixed:<len>`, `normal:<mean>,<std-dev>`, or `uniform:<min>,<max>`, got '{}'",
+            self.0
+        )
+    }
+}
+
+/// Parses a distribution, e.g., `fixed:150`, `normal:150,10`, or `uniform:50,150`.
+impl FromStr for ReadLengthDistribution {
+    type Err = ParseReadLengthDistributionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid_distribution = || ParseReadLengthDistributionError(s.into());
+
+        let (kind, params) = s.split_once(':').ok_or_else(invalid_distribution)?;
+
+        match kind {
+            "fixed" => params
+                .parse()
+                .map(Self::Fixed)
+                .map_err(|_| invalid_distribution()),
+            "normal" => {
+                let (mean, std_dev) = params.split_once(',').ok_or_else(invalid_distribution)?;
+                let mean: f64 = mean.parse().map_err(|_| invalid_distribution())?;
+                let std_dev: f64 = std_dev.parse().map_err(|_| invalid_distribution())?;
+
+                if !mean.is_finite() || mean < 0.0 || !std_dev.is_finite() || std_dev < 0.0 {
+                    return Err(invalid_distribution());
+                }
+
+                Ok(Self::Normal { mean, std_dev })
+            }
+            "uniform" => {
+                let (min, max) = params.split_once(',').ok_or_else(invalid_distribution)?;
+                let min = min.parse().map_err(|_| invalid_distribution())?;
+                let max = max.parse().map_err(|_| invalid_distribution())?;
+
+                if min > max {
+                    return Err(invalid_distribution());
+                }
+
+                Ok(Self::Uniform { min, max })
+            }
+            _ => Err(invalid_distribution()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn test_sample() {
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        let distribution = ReadLengthDistribution::Fixed(8);
+        assert_eq!(distribution.sample(&mut rng), 8);
+
+        let distribution = ReadLengthDistribution::Uniform { min: 4, max: 6 };
+        let lengths: Vec<_> = distribution.sample_iter(&mut rng).take(100).collect();
+        assert!(lengths.iter().all(|len| (4..=6).contains(len)));
+        assert!(lengths.contains(&4) && lengths.contains(&6));
+
+        let distribution = ReadLengthDistribution::Normal {
+            mean: 150.0,
+            std_dev: 10.0,
+        };
+        let sum: usize = distribution.sample_iter(&mut rng).take(1000).sum();
+        assert!((sum as f64 / 1000.0 - 150.0).abs() < 2.0);
+
+        let distribution = ReadLengthDistribution::Normal {
+            mean: 0.0,
+            std_dev: 1.0,
+        };
+        assert!(distribution
+            .sample_iter(&mut rng)
+            .take(100)
+            .all(|len| len >= 1));
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("fixed:150".parse(), Ok(ReadLengthDistribution::Fixed(150)));
+        assert_eq!(
+            "normal:150,10".parse(),
+            Ok(ReadLengthDistribution::Normal {
+                mean: 150.0,
+                std_dev: 10.0
+            })
+        );
+        assert_eq!(
+            "uniform:50,150".parse(),
+            Ok(ReadLengthDistribution::Uniform { min: 50, max: 150 })
+        );
+
+        for s in [
+            "",
+            "150",
+            "fixed:",
+            "normal:150",
+            "normal:150,-1",
+            "normal:inf,10",
+            "uniform:150,50",
+            "uniform:-1,50",
+            "poisson:150",
+        ] {
+            assert_eq!(
+                s.parse::<ReadLengthDistribution>(),
+                Err(ParseReadLengthDistributionError(s.into()))
+            );
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::io::{self, BufRead};
+
+use rand::Rng;
+
+use crate::{fasta, sequence::reverse_complement};
+
+/// Reference sequences to sample fragments from.
+///
+/// Sequences are concatenated into one buffer, and fragments never span two sequences.
+///
+/// # Examples
+///
+/// ```
+/// use fq::generator::Reference;
+///
+/// let reference = Reference::new([b"ACGT".to_vec(), b"nnACGTAC".to_vec()]).unwrap();
+/// assert_eq!(reference.len(), 12);
+/// assert_eq!(reference.max_sequence_length(), 8);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Reference {
+    bases: Vec<u8>,
+    // The end position of each sequence in `bases`.
+    ends: Vec<usize>,
+    max_sequence_length: usize,
+}
+
+impl Reference {
+    /// Creates a reference from a list of sequences.
+    ///
+    /// Bases are uppercased, e.g., to unmask soft-masked repeats. Empty sequences are skipped. This
+    /// returns `None` if there are no bases.
+    pub fn new<I>(sequences: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = Vec<u8>>,
+    {
+        let mut bases = Vec::new();
+        let mut ends = Vec::new();
+        let mut max_sequence_length = 0;
+
+        for sequence in sequences {
+            if sequence.is_empty() {
+                continue;
+            }
+
+            max_sequence_length = max_sequence_length.max(sequence.len());
+            bases.extend(sequence.iter().map(|b| b.to_ascii_uppercase()));
+            ends.push(bases.len());
+        }
+
+        if bases.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            bases,
+            ends,
+            max_sequence_length,
+        })
+    }
+
+    /// Reads all sequences of a FASTA reader.
+    pub fn read<R>(reader: &mut fasta::Reader<R>) -> io::Result<Self>
+    where
+        R: BufRead,
+    {
+        let mut record = fasta::Record::default();
+        let mut sequences = Vec::new();
+
+        while reader.read_record(&mut record)? > 0 {
+            sequences.push(std::mem::take(record.sequence_mut()));
+        }
+
+        Self::new(sequences)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "reference has no sequences"))
+    }
+
+    /// Returns the total number of bases of all sequences.
+    pub fn len(&self) -> usize {
+        self.bases.len()
+    }
+
+    /// Returns whether the reference has no bases. This is never true.
+    pub fn is_empty(&self) -> bool {
+        self.bases.is_empty()
+    }
+
+    /// Returns the length of the longest sequence.
+    pub fn max_sequence_length(&self) -> usize {
+        self.max_sequence_length
+    }
+
+    /// Appends a fragment of `len` bases from a random position and strand of a sequence to `dst`.
+    ///
+    /// Each position where a fragment fits is equally likely. Fragments longer than the longest
+    /// sequence are truncated to its length.
+    pub(super) fn sample_fragment<R>(&self, rng: &mut R, len: usize, dst: &mut Vec<u8>)
+    where
+        R: Rng,
+    {
+        let len = len.min(self.max_sequence_length);
+
+        // Starts that run past the end of a sequence are rejected, which keeps the remaining
+        // starts uniformly distributed.
+        let start = loop {
+            let start = rng.gen_range(0..self.bases.len());
+            let i = self.ends.partition_point(|&end| end <= start);
+
+            if start + len <= self.ends[i] {
+                break start;
+            }
+        };
+
+        let offset = dst.len();
+        dst.extend_from_slice(&self.bases[start..start + len]);
+
+        if rng.gen_bool(0.5) {
+            reverse_complement(&mut dst[offset..]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert_eq!(Reference::new([Vec::new()]), None);
+        assert_eq!(Reference::new(Vec::new()), None);
+
+        let reference = Reference::new([b"acgt".to_vec(), Vec::new(), b"NNACGT".to_vec()]).unwrap();
+        assert_eq!(reference.bases, b"ACGTNNACGT");
+        assert_eq!(reference.ends, [4, 10]);
+        assert_eq!(reference.max_sequence_length, 6);
+    }
+
+    #[test]
+    fn test_read() -> io::Result<()> {
+        let data = b">sq0\nACGT\nAC\n>sq1\nGG\n";
+        let mut reader = fasta::Reader::new(&data[..]);
+        let reference = Reference::read(&mut reader)?;
+        assert_eq!(reference.bases, b"ACGTACGG");
+        assert_eq!(reference.ends, [6, 8]);
+
+        let data = b">sq0\n";
+        let mut reader = fasta::Reader::new(&data[..]);
+        assert!(matches!(
+            Reference::read(&mut reader),
+            Err(e) if e.kind() == io::ErrorKind::InvalidData
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sample_fragment() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let reference = Reference::new([b"AAAAAAAA".to_vec(), b"CCCC".to_vec()]).unwrap();
+
+        for _ in 0..100 {
+            let mut fragment = Vec::new();
+            reference.sample_fragment(&mut rng, 6, &mut fragment);
+            assert!(fragment == b"AAAAAA" || fragment == b"TTTTTT");
+        }
+
+        let mut fragment = Vec::new();
+        reference.sample_fragment(&mut rng, 16, &mut fragment);
+        assert_eq!(fragment.len(), 8);
+
+        let mut saw_c = false;
+
+        for _ in 0..100 {
+            let mut fragment = b"N".to_vec();
+            reference.sample_fragment(&mut rng, 3, &mut fragment);
+            assert_eq!(fragment[0], b'N');
+            assert!(fragment[1..].iter().all(|&b| fragment[1] == b));
+            saw_c |= fragment[1] == b'C' || fragment[1] == b'G';
+        }
+
+        assert!(saw_c);
+    }
+}
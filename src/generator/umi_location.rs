@@ -0,0 +1,58 @@
+use std::{error, fmt, str::FromStr};
+
+/// Where the UMI of a generated pair is placed.
+///
+/// Both mates of a pair and all duplicates of a fragment have the same UMI.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UmiLocation {
+    /// The UMI is the first bases of read 1, e.g., `ACGTACGT...`. Read 1 keeps its read length, so
+    /// the UMI takes the place of the first bases of the fragment.
+    Sequence,
+    /// The UMI is appended to the name as the last `:`-delimited field, e.g.,
+    /// `@fqlib5:440:ZMXYPLK:7:15:9764:6446:ACGTACGT`, as written by bcl2fastq and
+    /// `fq umi extract`.
+    Name,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseUmiLocationError(String);
+
+impl error::Error for ParseUmiLocationError {}
+
+impl fmt::Display for ParseUmiLocationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid UMI location: expected sequence or name, got '{}'",
+            self.0
+        )
+    }
+}
+
+impl FromStr for UmiLocation {
+    type Err = ParseUmiLocationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sequence" => Ok(Self::Sequence),
+            "name" => Ok(Self::Name),
+            _ => Err(ParseUmiLocationError(s.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("sequence".parse(), Ok(UmiLocation::Sequence));
+        assert_eq!("name".parse(), Ok(UmiLocation::Name));
+
+        assert_eq!(
+            "comment".parse::<UmiLocation>(),
+            Err(ParseUmiLocationError(String::from("comment")))
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Record searching.
+
+use std::{error, fmt};
+
+use regex::bytes::Regex;
+
+use crate::fastq::Record;
+
+const NAME_PREFIX: u8 = b'@';
+
+/// A record matcher by name and/or sequence.
+///
+/// A record matches when it matches all given patterns.
+#[derive(Clone, Debug, Default)]
+pub struct RecordMatcher {
+    name_regex: Option<Regex>,
+    sequence_regex: Option<Regex>,
+}
+
+impl RecordMatcher {
+    /// Creates a record matcher.
+    ///
+    /// The name regex is matched against the record name without the `@` prefix.
+    pub fn new(name_regex: Option<Regex>, sequence_regex: Option<Regex>) -> Self {
+        Self {
+            name_regex,
+            sequence_regex,
+        }
+    }
+
+    /// Returns whether the record matches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, grep::{motif_regex, RecordMatcher}};
+    /// use regex::bytes::Regex;
+    ///
+    /// let name_regex = Regex::new("^r[0-9]$").unwrap();
+    /// let matcher = RecordMatcher::new(Some(name_regex), Some(motif_regex("CRT")?));
+    ///
+    /// assert!(matcher.is_match(&Record::new("@r0", "ACGTA", "+", "IIIII")));
+    /// assert!(!matcher.is_match(&Record::new("@r0", "ACCTA", "+", "IIIII")));
+    /// assert!(!matcher.is_match(&Record::new("@r10", "ACGTA", "+", "IIIII")));
+    /// # Ok::<(), fq::grep::ParseMotifError>(())
+    /// ```
+    pub fn is_match(&self, record: &Record) -> bool {
+        if let Some(regex) = &self.name_regex {
+            let name = record.name();
+            let name = name.strip_prefix(&[NAME_PREFIX]).unwrap_or(name);
+
+            if !regex.is_match(name) {
+                return false;
+            }
+        }
+
+        if let Some(regex) = &self.sequence_regex {
+            if !regex.is_match(record.sequence()) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// An error returned when a motif fails to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseMotifError(String);
+
+impl error::Error for ParseMotifError {}
+
+impl fmt::Display for ParseMotifError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid motif: expected IUPAC nucleotide codes, got '{}'",
+            self.0
+        )
+    }
+}
+
+/// Builds a case-insensitive regex that finds a sequence motif.
+///
+/// IUPAC ambiguity codes in the motif are expanded to the bases they represent, e.g., `R` matches
+/// `A` or `G`. Read bases are matched literally, so an `N` in a read only matches an `N` in the
+/// motif.
+///
+/// # Examples
+///
+/// ```
+/// use fq::grep::motif_regex;
+///
+/// let regex = motif_regex("GATYN")?;
+/// assert!(regex.is_match(b"AAGATCAA"));
+/// assert!(regex.is_match(b"aagattaa"));
+/// assert!(!regex.is_match(b"AAGATGAA"));
+/// # Ok::<(), fq::grep::ParseMotifError>(())
+/// ```
+pub fn motif_regex(motif: &str) -> Result<Regex, ParseMotifError> {
+    if motif.is_empty() {
+        return Err(ParseMotifError(motif.into()));
+    }
+
+    let mut pattern = String::from("(?i)");
+
+    for b in motif.bytes() {
+        let class = expand(b).ok_or_else(|| ParseMotifError(motif.into()))?;
+        pattern.push_str(class);
+    }
+
+    Ok(Regex::new(&pattern).expect("invalid motif regex"))
+}
+
+// Returns the regex character class of the bases represented by an IUPAC code.
+fn expand(b: u8) -> Option<&'static str> {
+    match b.to_ascii_uppercase() {
+        b'A' => Some("A"),
+        b'C' => Some("C"),
+        b'G' => Some("G"),
+        b'T' | b'U' => Some("T"),
+        b'R' => Some("[AG]"),
+        b'Y' => Some("[CT]"),
+        b'S' => Some("[CG]"),
+        b'W' => Some("[AT]"),
+        b'K' => Some("[GT]"),
+        b'M' => Some("[AC]"),
+        b'B' => Some("[CGT]"),
+        b'D' => Some("[AGT]"),
+        b'H' => Some("[ACT]"),
+        b'V' => Some("[ACG]"),
+        b'N' => Some("[ACGTN]"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_match() -> Result<(), Box<dyn error::Error>> {
+        let record = Record::new("@r0 1:N:0", "ACGT", "+", "IIII");
+
+        assert!(RecordMatcher::default().is_match(&record));
+
+        let matcher = RecordMatcher::new(Some(Regex::new("^r0 ")?), None);
+        assert!(matcher.is_match(&record));
+
+        let matcher = RecordMatcher::new(None, Some(motif_regex("GT")?));
+        assert!(matcher.is_match(&record));
+
+        let matcher = RecordMatcher::new(Some(Regex::new("^r1")?), Some(motif_regex("GT")?));
+        assert!(!matcher.is_match(&record));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_motif_regex() -> Result<(), ParseMotifError> {
+        let regex = motif_regex("N")?;
+        assert!(regex.is_match(b"N"));
+        assert!(!regex.is_match(b"."));
+
+        let regex = motif_regex("ACGU")?;
+        assert!(regex.is_match(b"ACGT"));
+
+        let regex = motif_regex("BDHV")?;
+        assert!(regex.is_match(b"CAAA"));
+        assert!(!regex.is_match(b"AAAA"));
+
+        assert_eq!(motif_regex("").err(), Some(ParseMotifError(String::new())));
+        assert_eq!(
+            motif_regex("AC.T").err(),
+            Some(ParseMotifError(String::from("AC.T")))
+        );
+        assert!(motif_regex("A*").is_err());
+
+        Ok(())
+    }
+}
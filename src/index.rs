@@ -0,0 +1,301 @@
+//! FASTQ record index.
+//!
+//! An index maps each record, by number and name, to the offset of its first byte in a FASTQ
+//! file. Offsets of raw files are byte offsets, and offsets of BGZF-compressed files are virtual
+//! offsets. Plain gzip files cannot be indexed.
+//!
+//! The index is a sidecar file with the same path as the FASTQ file and an `.fqi` extension
+//! appended.
+
+mod indexed_reader;
+mod reader;
+mod writer;
+
+pub use self::{indexed_reader::IndexedReader, reader::Reader, writer::Writer};
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    bam::bgzf,
+    fastq::{self, name_id},
+};
+
+const EXTENSION: &str = "fqi";
+
+/// The compression format of an indexed file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// Uncompressed. Offsets are byte offsets.
+    Raw,
+    /// BGZF-compressed. Offsets are virtual offsets.
+    Bgzf,
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Raw => f.write_str("raw"),
+            Self::Bgzf => f.write_str("bgzf"),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raw" => Ok(Self::Raw),
+            "bgzf" => Ok(Self::Bgzf),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid index format: expected raw or bgzf, got '{}'", s),
+            )),
+        }
+    }
+}
+
+/// An index entry, i.e., the name ID and offset of a record.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry {
+    name: Vec<u8>,
+    offset: u64,
+}
+
+impl Entry {
+    pub fn new<N>(name: N, offset: u64) -> Self
+    where
+        N: Into<Vec<u8>>,
+    {
+        Self {
+            name: name.into(),
+            offset,
+        }
+    }
+
+    /// Returns the record name ID, i.e., the name without the `@` prefix, pair suffix, and
+    /// comment.
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+/// A FASTQ record index.
+///
+/// Entries are in record order, i.e., entry `i` is record `i` (0-based).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Index {
+    format: Format,
+    entries: Vec<Entry>,
+}
+
+impl Index {
+    pub fn new(format: Format, entries: Vec<Entry>) -> Self {
+        Self { format, entries }
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Returns the number of the first record with the given name ID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::index::{Entry, Format, Index};
+    ///
+    /// let index = Index::new(Format::Raw, vec![Entry::new("r0", 0), Entry::new("r1", 18)]);
+    /// assert_eq!(index.find(b"r1"), Some(1));
+    /// assert_eq!(index.find(b"r2"), None);
+    /// ```
+    pub fn find(&self, name: &[u8]) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.name() == name)
+    }
+}
+
+/// Returns the sidecar index path of a FASTQ file.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+///
+/// use fq::index::index_path;
+///
+/// assert_eq!(index_path("r1.fastq.gz"), Path::new("r1.fastq.gz.fqi"));
+/// ```
+pub fn index_path<P>(src: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    let mut path = src.as_ref().as_os_str().to_owned();
+    path.push(".");
+    path.push(EXTENSION);
+    PathBuf::from(path)
+}
+
+/// Detects the format of a FASTQ source from its first bytes.
+///
+/// This fails for gzip sources that are not BGZF-compressed.
+pub fn detect_format<R>(reader: &mut R) -> io::Result<Format>
+where
+    R: BufRead,
+{
+    let buf = reader.fill_buf()?;
+
+    if bgzf::is_bgzf(buf) {
+        Ok(Format::Bgzf)
+    } else if buf.starts_with(&[0x1f, 0x8b]) {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "gzip input is not BGZF-compressed. Recompress it with `bgzip`.",
+        ))
+    } else {
+        Ok(Format::Raw)
+    }
+}
+
+/// Builds an index by reading all records of a FASTQ source.
+///
+/// # Examples
+///
+/// ```
+/// use fq::index::{self, Entry, Format};
+///
+/// let data = b"@r0/1\nACGT\n+\nIIII\n@r1/1 1:N:0\nAC\n+\nII\n";
+/// let index = index::build(&data[..])?;
+///
+/// assert_eq!(index.format(), Format::Raw);
+/// assert_eq!(index.entries(), [Entry::new("r0", 0), Entry::new("r1", 18)]);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn build<R>(mut reader: R) -> io::Result<Index>
+where
+    R: BufRead,
+{
+    match detect_format(&mut reader)? {
+        Format::Raw => {
+            let mut reader = fastq::Reader::new(reader);
+            let mut entries = Vec::new();
+            let mut record = fastq::Record::default();
+            let mut offset = 0;
+
+            loop {
+                let n = reader.read_record(&mut record)?;
+
+                if n == 0 {
+                    break;
+                }
+
+                entries.push(Entry::new(name_id(record.name()), offset));
+                offset += n as u64;
+            }
+
+            Ok(Index::new(Format::Raw, entries))
+        }
+        Format::Bgzf => {
+            let mut reader = fastq::Reader::new(bgzf::Reader::new(reader));
+            let mut entries = Vec::new();
+            let mut record = fastq::Record::default();
+
+            loop {
+                let offset = reader.get_ref().virtual_position();
+
+                if reader.read_record(&mut record)? == 0 {
+                    break;
+                }
+
+                entries.push(Entry::new(name_id(record.name()), offset));
+            }
+
+            Ok(Index::new(Format::Bgzf, entries))
+        }
+    }
+}
+
+/// Builds the index of a FASTQ file and writes it to its sidecar path.
+pub fn build_file<P>(src: P) -> io::Result<Index>
+where
+    P: AsRef<Path>,
+{
+    let src = src.as_ref();
+
+    let reader = File::open(src).map(BufReader::new)?;
+    let index = build(reader)?;
+
+    let mut writer = File::create(index_path(src)).map(|f| Writer::new(BufWriter::new(f)))?;
+    writer.write_index(&index)?;
+
+    Ok(index)
+}
+
+/// Reads the sidecar index of a FASTQ file.
+pub fn read_file<P>(src: P) -> io::Result<Index>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(index_path(src))?;
+    Reader::new(BufReader::new(file)).read_index()
+}
+
+/// Opens a FASTQ file and its sidecar index for random access.
+pub fn open<P>(src: P) -> io::Result<IndexedReader<BufReader<File>>>
+where
+    P: AsRef<Path>,
+{
+    let src = src.as_ref();
+    let index = read_file(src)?;
+    let file = File::open(src)?;
+    Ok(IndexedReader::new(BufReader::new(file), index))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn test_build_with_bgzf_source() -> io::Result<()> {
+        let data = b"@r0/1\nACGT\n+\nIIII\n@r1/1\nAC\n+\nII\n";
+
+        let mut writer = bgzf::Writer::new(Vec::new());
+        writer.write_all(data)?;
+        writer.finish()?;
+
+        let index = build(&writer.get_ref()[..])?;
+
+        assert_eq!(index.format(), Format::Bgzf);
+        assert_eq!(index.entries(), [Entry::new("r0", 0), Entry::new("r1", 18)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_with_gzip_source() {
+        let data = [0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff];
+        assert!(build(&data[..]).is_err());
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("raw".parse::<Format>().ok(), Some(Format::Raw));
+        assert_eq!("bgzf".parse::<Format>().ok(), Some(Format::Bgzf));
+        assert!("gz".parse::<Format>().is_err());
+    }
+}
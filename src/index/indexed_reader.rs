@@ -0,0 +1,165 @@
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+
+use super::{Format, Index};
+use crate::{bam::bgzf, fastq};
+
+enum Source<R> {
+    Raw(R),
+    Bgzf(bgzf::Reader<R>),
+}
+
+impl<R> Source<R>
+where
+    R: BufRead + Seek,
+{
+    fn seek(&mut self, offset: u64) -> io::Result<()> {
+        match self {
+            Self::Raw(inner) => inner.seek(SeekFrom::Start(offset)).map(|_| ()),
+            Self::Bgzf(inner) => inner.seek(offset),
+        }
+    }
+}
+
+impl<R> Read for Source<R>
+where
+    R: BufRead,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Raw(inner) => inner.read(buf),
+            Self::Bgzf(inner) => inner.read(buf),
+        }
+    }
+}
+
+impl<R> BufRead for Source<R>
+where
+    R: BufRead,
+{
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match self {
+            Self::Raw(inner) => inner.fill_buf(),
+            Self::Bgzf(inner) => inner.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match self {
+            Self::Raw(inner) => inner.consume(amt),
+            Self::Bgzf(inner) => inner.consume(amt),
+        }
+    }
+}
+
+/// A FASTQ reader with random access by record number or name.
+pub struct IndexedReader<R>
+where
+    R: BufRead,
+{
+    inner: fastq::Reader<Source<R>>,
+    index: Index,
+}
+
+impl<R> IndexedReader<R>
+where
+    R: BufRead + Seek,
+{
+    pub fn new(inner: R, index: Index) -> Self {
+        let source = match index.format() {
+            Format::Raw => Source::Raw(inner),
+            Format::Bgzf => Source::Bgzf(bgzf::Reader::new(inner)),
+        };
+
+        Self {
+            inner: fastq::Reader::new(source),
+            index,
+        }
+    }
+
+    pub fn index(&self) -> &Index {
+        &self.index
+    }
+
+    /// Seeks to the start of record `i` (0-based).
+    ///
+    /// Records are then read in order from that record.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    ///
+    /// use fq::{fastq, index::{self, IndexedReader}};
+    ///
+    /// let data = b"@r0\nACGT\n+\nIIII\n@r1\nAC\n+\nII\n";
+    /// let index = index::build(&data[..])?;
+    ///
+    /// let mut reader = IndexedReader::new(Cursor::new(&data[..]), index);
+    /// reader.seek(1)?;
+    ///
+    /// let mut record = fastq::Record::default();
+    /// reader.read_record(&mut record)?;
+    /// assert_eq!(record.name(), b"@r1");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn seek(&mut self, i: usize) -> io::Result<()> {
+        let entry = self.index.entries().get(i).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "record {} is out of range: the index has {} records",
+                    i,
+                    self.index.entries().len()
+                ),
+            )
+        })?;
+
+        self.inner.get_mut().seek(entry.offset())
+    }
+
+    /// Reads the record at the current position.
+    ///
+    /// This returns the number of bytes read, or 0 at EOF.
+    pub fn read_record(&mut self, record: &mut fastq::Record) -> io::Result<usize> {
+        self.inner.read_record(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use super::*;
+    use crate::index;
+
+    #[test]
+    fn test_seek_with_bgzf_source() -> io::Result<()> {
+        let mut writer = bgzf::Writer::new(Vec::new());
+
+        for i in 0..10000 {
+            writeln!(writer, "@r{}\nACGTACGT\n+\nIIIIIIII", i)?;
+        }
+
+        writer.finish()?;
+
+        let data = writer.get_ref();
+        let index = index::build(&data[..])?;
+        assert_eq!(index.entries().len(), 10000);
+
+        let mut reader = IndexedReader::new(Cursor::new(&data[..]), index);
+        let mut record = fastq::Record::default();
+
+        for i in [9999, 0, 4321] {
+            reader.seek(i)?;
+            reader.read_record(&mut record)?;
+            assert_eq!(record.name(), format!("@r{}", i).as_bytes());
+        }
+
+        reader.read_record(&mut record)?;
+        assert_eq!(record.name(), b"@r4322");
+
+        assert!(reader.seek(10000).is_err());
+
+        Ok(())
+    }
+}
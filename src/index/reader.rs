@@ -0,0 +1,115 @@
+use std::io::{self, BufRead};
+
+use super::{
+    writer::{MAGIC, VERSION},
+    Entry, Index,
+};
+
+const LINE_FEED: u8 = b'\n';
+
+/// An index reader.
+pub struct Reader<R> {
+    inner: R,
+}
+
+impl<R> Reader<R>
+where
+    R: BufRead,
+{
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    /// Reads an index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::index::{Entry, Format, Reader};
+    ///
+    /// let data = b"#fqi\t1\tbgzf\nr0\t0\nr1\t18\n";
+    /// let index = Reader::new(&data[..]).read_index()?;
+    ///
+    /// assert_eq!(index.format(), Format::Bgzf);
+    /// assert_eq!(index.entries(), [Entry::new("r0", 0), Entry::new("r1", 18)]);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn read_index(&mut self) -> io::Result<Index> {
+        let mut header = String::new();
+        self.inner.read_line(&mut header)?;
+
+        let mut fields = header.trim_end().split('\t');
+
+        if fields.next() != Some(MAGIC) || fields.next() != Some(VERSION) {
+            return Err(invalid_data("invalid index header"));
+        }
+
+        let format = fields
+            .next()
+            .ok_or_else(|| invalid_data("invalid index header: missing format"))?
+            .parse()?;
+
+        let mut entries = Vec::new();
+        let mut buf = Vec::new();
+
+        loop {
+            buf.clear();
+
+            if self.inner.read_until(LINE_FEED, &mut buf)? == 0 {
+                break;
+            }
+
+            let line = buf.strip_suffix(&[LINE_FEED]).unwrap_or(&buf);
+            entries.push(parse_entry(line)?);
+        }
+
+        Ok(Index::new(format, entries))
+    }
+}
+
+fn parse_entry(line: &[u8]) -> io::Result<Entry> {
+    let i = line
+        .iter()
+        .rposition(|&b| b == b'\t')
+        .ok_or_else(|| invalid_data("invalid index entry: missing offset"))?;
+
+    let (name, raw_offset) = (&line[..i], &line[i + 1..]);
+
+    let offset = std::str::from_utf8(raw_offset)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| invalid_data("invalid index entry: invalid offset"))?;
+
+    Ok(Entry::new(name, offset))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_index_with_invalid_data() {
+        let data = b"#fqi\t2\traw\n";
+        assert!(Reader::new(&data[..]).read_index().is_err());
+
+        let data = b"#fqi\t1\tgz\n";
+        assert!(Reader::new(&data[..]).read_index().is_err());
+
+        let data = b"#fqi\t1\traw\nr0\n";
+        assert!(Reader::new(&data[..]).read_index().is_err());
+
+        let data = b"#fqi\t1\traw\nr0\t-1\n";
+        assert!(Reader::new(&data[..]).read_index().is_err());
+    }
+
+    #[test]
+    fn test_parse_entry() -> io::Result<()> {
+        assert_eq!(parse_entry(b"r0\t8")?, Entry::new("r0", 8));
+        assert_eq!(parse_entry(b"r\t0\t8")?, Entry::new("r\t0", 8));
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use std::io::{self, Write};
+
+use super::Index;
+
+pub(super) const MAGIC: &str = "#fqi";
+pub(super) const VERSION: &str = "1";
+
+/// An index writer.
+///
+/// The index is written as text. The first line is a header with the format version and
+/// compression format, and each following line is the name ID and offset of a record, separated
+/// by a tab.
+pub struct Writer<W> {
+    inner: W,
+}
+
+impl<W> Writer<W>
+where
+    W: Write,
+{
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Writes an index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::index::{Entry, Format, Index, Writer};
+    ///
+    /// let index = Index::new(Format::Raw, vec![Entry::new("r0", 0), Entry::new("r1", 18)]);
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.write_index(&index)?;
+    ///
+    /// assert_eq!(writer.get_ref(), b"#fqi\t1\traw\nr0\t0\nr1\t18\n");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_index(&mut self, index: &Index) -> io::Result<()> {
+        writeln!(self.inner, "{}\t{}\t{}", MAGIC, VERSION, index.format())?;
+
+        for entry in index.entries() {
+            self.inner.write_all(entry.name())?;
+            writeln!(self.inner, "\t{}", entry.offset())?;
+        }
+
+        self.inner.flush()
+    }
+}
@@ -1,10 +1,31 @@
 #![deny(bare_trait_objects)]
 
+pub mod bam;
 pub mod commands;
+pub mod complexity;
+pub mod dedup;
+pub mod demux;
 pub mod distributions;
+pub mod fasta;
 pub mod fastq;
+pub mod fix;
 pub mod generator;
+pub mod grep;
+pub mod index;
+pub mod mask;
+pub mod matching;
+pub mod merge;
+pub mod metrics;
 pub mod pair_writer;
+pub mod primers;
+pub mod quality;
+pub mod rename;
+pub mod screen;
+pub mod sequence;
+pub mod sort;
+pub mod stats;
+pub mod tab;
+pub mod trim;
 pub mod validators;
 
 pub use crate::{generator::Generator, pair_writer::PairWriter, validators::ValidationLevel};
@@ -1,95 +1,1546 @@
+use std::io;
+
 use clap::{App, AppSettings, Arg};
-use fq::commands::{filter, generate, lint, subsample};
+use fq::commands::{
+    cat, clip, compare, convert, count, dedup, demux, describe, extract, filter, fix, generate,
+    grep, head, index, lint, mask, merge_pairs, rename, repair, revcomp, screen, sort, split,
+    stats, subsample, trim, trim_primers, umi, view,
+};
 
 use git_testament::{git_testament, render_testament};
-use tracing::warn;
+use tracing::{level_filters::LevelFilter, warn};
 
 git_testament!(TESTAMENT);
 
 fn main() -> anyhow::Result<()> {
     let version = render_testament!(TESTAMENT);
 
-    let filter_cmd = App::new("filter")
-        .about("Filters a FASTQ from an allowlist of names")
+    let cat_cmd = App::new("cat")
+        .about("Concatenates FASTQ files, validating each record")
+        .arg(
+            Arg::new("dst")
+                .short('o')
+                .long("dst")
+                .value_name("path")
+                .help("Destination. Output will be gzipped if ends in `.gz`. Defaults to stdout."),
+        )
+        .arg(
+            Arg::new("check-run")
+                .long("check-run")
+                .help("Check that all records have the same instrument, run number, and flowcell ID in their Illumina read names"),
+        )
+        .arg(
+            Arg::new("single-read-validation-level")
+                .long("single-read-validation-level")
+                .help("Only use single read validators up to a given level")
+                .value_name("str")
+                .possible_values(["low", "medium", "high"])
+                .default_value("high"),
+        )
+        .arg(
+            Arg::new("disable-validator")
+                .long("disable-validator")
+                .help("Disable validators by code or name, e.g., S007 or DuplicateNameValidator. Use multiple times to disable more than one.")
+                .value_name("str")
+                .multiple_occurrences(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::new("srcs")
+                .help("Sources. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .multiple_values(true)
+                .required(true),
+        );
+
+    let clip_cmd = App::new("clip")
+        .about("Removes a fixed number of bases from the start and/or end of reads")
+        .arg(
+            Arg::new("front")
+                .long("front")
+                .value_name("usize")
+                .help("Number of bases to remove from the 5' end of each read")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("tail")
+                .long("tail")
+                .value_name("usize")
+                .help("Number of bases to remove from the 3' end of each read")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("r2-front")
+                .long("r2-front")
+                .value_name("usize")
+                .help(
+                "Number of bases to remove from the 5' end of each read 2. Defaults to `front`.",
+            ),
+        )
+        .arg(
+            Arg::new("r2-tail")
+                .long("r2-tail")
+                .value_name("usize")
+                .help(
+                    "Number of bases to remove from the 3' end of each read 2. Defaults to `tail`.",
+                ),
+        )
+        .arg(
+            Arg::new("r1-dst")
+                .help("Read 1 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r1-dst")
+                .value_name("path")
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-dst")
+                .help("Read 2 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r2-dst")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2),
+        );
+
+    let compare_cmd = App::new("compare")
+        .about("Compares the records of two FASTQ files")
+        .arg(
+            Arg::new("mode")
+                .long("mode")
+                .help("Compare records field by field in order (`ordered`) or by content regardless of order, name comments, and pair suffixes (`unordered`)")
+                .value_name("str")
+                .possible_values(["ordered", "unordered"])
+                .default_value("ordered"),
+        )
+        .arg(
+            Arg::new("a-src")
+                .help("First source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("b-src")
+                .help("Second source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2)
+                .required(true),
+        );
+
+    let convert_cmd = App::new("convert")
+        .about("Converts between FASTQ, FASTA, BAM/SAM, and tabular formats and between quality score encodings")
+        .arg(
+            Arg::new("from")
+                .long("from")
+                .help("Input format")
+                .value_name("str")
+                .possible_values(["fastq", "fasta", "bam", "sam", "tab"])
+                .default_value("fastq"),
+        )
+        .arg(
+            Arg::new("to")
+                .long("to")
+                .help("Output format. Defaults to FASTA for FASTQ input and FASTQ for other inputs.")
+                .value_name("str")
+                .possible_values(["bam", "fasta", "fastq", "tab"]),
+        )
+        .arg(
+            Arg::new("read-group")
+                .long("read-group")
+                .value_name("str")
+                .help(r"Read group header line of BAM output, e.g., `@RG\tID:rg0\tSM:sample0`. Records are tagged with its ID."),
+        )
+        .arg(
+            Arg::new("qual")
+                .long("qual")
+                .value_name("path")
+                .help("QUAL source of numeric quality scores for FASTA input. Accepts both raw and gzipped inputs."),
+        )
+        .arg(
+            Arg::new("quality-from")
+                .long("quality-from")
+                .help("Quality score encoding of FASTQ input. `auto` detects it from the first records.")
+                .value_name("str")
+                .possible_values(["auto", "phred33", "phred64", "solexa64"]),
+        )
+        .arg(
+            Arg::new("quality-to")
+                .long("quality-to")
+                .help("Quality score encoding of FASTQ output. Defaults to phred33.")
+                .value_name("str")
+                .possible_values(["phred33", "phred64"]),
+        )
+        .arg(
+            Arg::new("line-width")
+                .long("line-width")
+                .help("Maximum number of bases per sequence line of FASTA output. Use 0 to disable wrapping.")
+                .value_name("usize")
+                .default_value("60"),
+        )
+        .arg(
+            Arg::new("dst")
+                .long("dst")
+                .value_name("path")
+                .help("Destination. Output will be gzipped if ends in `.gz`. Defaults to stdout."),
+        )
+        .arg(
+            Arg::new("r2-dst")
+                .long("r2-dst")
+                .value_name("path")
+                .help("Read 2 destination of paired end BAM, SAM, or tabular input. Output will be gzipped if ends in `.gz`. Defaults to interleaving mates in `dst`."),
+        )
+        .arg(
+            Arg::new("singletons")
+                .long("singletons")
+                .value_name("path")
+                .help("Destination for mates without a pair in BAM or SAM input. Output will be gzipped if ends in `.gz`. Defaults to discarding them."),
+        )
+        .arg(
+            Arg::new("src")
+                .help("Source. Accepts both raw and gzipped inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source of paired end FASTQ input for BAM or tabular output. Accepts both raw and gzipped inputs.")
+                .index(2),
+        );
+
+    let count_cmd = App::new("count")
+        .about("Counts records")
+        .arg(
+            Arg::new("bases")
+                .long("bases")
+                .help("Also count the number of bases"),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2),
+        );
+
+    let dedup_cmd = App::new("dedup")
+        .about("Removes duplicate reads")
+        .alias("dedupe")
+        .arg(
+            Arg::new("umi-from")
+                .long("umi-from")
+                .help("Group reads by UMI, read from the last `:`-delimited field of the name (`name`) or the first N bases of read 1 (`sequence:N`)")
+                .value_name("str"),
+        )
+        .arg(
+            Arg::new("with-names")
+                .long("with-names")
+                .help("Only remove reads with identical names and sequences")
+                .conflicts_with("umi-from"),
+        )
+        .arg(
+            Arg::new("approximate")
+                .long("approximate")
+                .help("Use a probabilistic filter to bound memory. A small fraction of unique reads may be removed.")
+                .conflicts_with("umi-from"),
+        )
+        .arg(
+            Arg::new("umi-distance")
+                .long("umi-distance")
+                .help("Maximum edit distance between UMIs of the same molecule")
+                .value_name("usize")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("prefix-length")
+                .long("prefix-length")
+                .help("Number of bases of each read, after an inline UMI, used to group duplicates")
+                .value_name("usize")
+                .default_value("16"),
+        )
+        .arg(
+            Arg::new("keep")
+                .long("keep")
+                .help("Record to keep for each group of UMI duplicates")
+                .value_name("str")
+                .possible_values(["best-quality", "consensus"])
+                .default_value("best-quality"),
+        )
+        .arg(
+            Arg::new("r1-dst")
+                .help("Read 1 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r1-dst")
+                .value_name("path")
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-dst")
+                .help("Read 2 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r2-dst")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2),
+        );
+
+    let extract_cmd = App::new("extract")
+        .about("Outputs records by number or name using an index")
+        .arg(
+            Arg::new("index")
+                .long("index")
+                .value_name("start-end")
+                .help("Inclusive range of 1-based record numbers to output, e.g., `1000-2000`. Use multiple times to output more than one.")
+                .multiple_occurrences(true)
+                .number_of_values(1)
+                .required_unless_present("name"),
+        )
+        .arg(
+            Arg::new("name")
+                .long("name")
+                .value_name("str")
+                .help("Name of a record to output, without the `@` prefix, pair suffix, or comment. Use multiple times to output more than one.")
+                .multiple_occurrences(true)
+                .number_of_values(1)
+                .required_unless_present("index"),
+        )
+        .arg(
+            Arg::new("dst")
+                .long("dst")
+                .value_name("path")
+                .help("Destination. Output will be gzipped if ends in `.gz`. Defaults to stdout."),
+        )
+        .arg(
+            Arg::new("src")
+                .help("Source. Accepts raw and BGZF-compressed FASTQ inputs with an index (`<src>.fqi`).")
+                .index(1)
+                .required(true),
+        );
+
+    let filter_cmd = App::new("filter")
+        .about("Filters a FASTQ from an allowlist of names and/or by sequence complexity")
+        .arg(
+            Arg::new("names")
+                .long("names")
+                .value_name("path")
+                .help("Allowlist of record names")
+                .required_unless_present("min-complexity"),
+        )
+        .arg(
+            Arg::new("min-complexity")
+                .long("min-complexity")
+                .value_name("f64")
+                .help(
+                    "Remove reads with a normalized trinucleotide entropy below this score [0, 1]",
+                )
+                .required_unless_present("names"),
+        )
+        .arg(Arg::new("src").help("Source FASTQ").index(1).required(true));
+
+    let fix_cmd = App::new("fix")
+        .about("Fixes common FASTQ defects reported by lint")
+        .arg(
+            Arg::new("quality-length")
+                .long("quality-length")
+                .value_name("str")
+                .help("Fix records with different sequence and quality lengths (S005) by padding or truncating quality strings (`pad`) or by truncating the longer of the two (`truncate`)")
+                .possible_values(["pad", "truncate"]),
+        )
+        .arg(
+            Arg::new("dst")
+                .long("dst")
+                .value_name("path")
+                .help("Destination. Output will be gzipped if ends in `.gz`.")
+                .required(true),
+        )
+        .arg(
+            Arg::new("src")
+                .help("Source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        );
+
+    let generate_cmd = App::new("generate")
+        .about("Generates a random FASTQ file pair or single end FASTQ file")
+        .arg(
+            Arg::new("seed")
+                .short('s')
+                .long("seed")
+                .value_name("u64")
+                .help("Seed to use for the random number generator"),
+        )
+        .arg(
+            Arg::new("threads")
+                .long("threads")
+                .value_name("usize")
+                .help("Number of threads used to generate records. Records are generated from a seed (random, if not given), so the output is the same for any number of threads.")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("shard")
+                .long("shard")
+                .value_name("i/N")
+                .help("Only generate part i of N of the seeded dataset, e.g., `2/4`")
+                .requires("seed"),
+        )
+        .arg(
+            Arg::new("name-format")
+                .long("name-format")
+                .help("Format of read names: `random`, random coordinates and a `/1` or `/2` read number suffix; or `illumina`, Casava 1.8+ names with incrementing coordinates and the read number in the comment")
+                .value_name("str")
+                .possible_values(["random", "illumina"])
+                .default_value("random"),
+        )
+        .arg(
+            Arg::new("name-template")
+                .long("name-template")
+                .help("Template of read names, e.g., `sim_{run}_{index}`. Placeholders are `{index}`, the 1-based record (or pair) index; `{instrument}`; `{run}`; `{flowcell}`; `{umi}`, the UMI of the pair; and `{barcode}`, a sample barcode. The read number is still set by `name-format`.")
+                .value_name("str"),
+        )
+        .arg(
+            Arg::new("umi-length")
+                .long("umi-length")
+                .help("Length of the UMIs of `umi-location` and of `{umi}` in name templates. Without `umi-location`, UMIs are added to the sequence unless the name template has `{umi}`. (default: 8)")
+                .value_name("usize"),
+        )
+        .arg(
+            Arg::new("umi-location")
+                .long("umi-location")
+                .help("Adds a random UMI to each pair: `sequence`, as the first bases of read 1; or `name`, as the last `:`-delimited field of the read name, the convention of `umi extract` and `dedup --umi-from name`. Duplicates have the UMI of their original.")
+                .value_name("str")
+                .possible_values(["sequence", "name"]),
+        )
+        .arg(
+            Arg::new("barcodes")
+                .long("barcodes")
+                .help("Sample sheet (TSV) of sample names and one or two barcodes, the format of `demux --barcodes`. Each pair belongs to a random sample, whose barcodes replace `{barcode}` in name templates and are the index reads. Requires `i1-dst`, BAM output, or `{barcode}` in `name-template`. (default: one random barcode)")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("index-error-rate")
+                .long("index-error-rate")
+                .help("Expected number of substitutions per base of index reads [0, 1]")
+                .value_name("f64")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("instrument")
+                .long("instrument")
+                .help("Instrument of read names (default: random)")
+                .value_name("str"),
+        )
+        .arg(
+            Arg::new("run-number")
+                .long("run-number")
+                .help("Run number of read names (default: random)")
+                .value_name("u32"),
+        )
+        .arg(
+            Arg::new("flow-cell-id")
+                .long("flow-cell-id")
+                .help("Flow cell ID of read names (default: random)")
+                .value_name("str"),
+        )
+        .arg(
+            Arg::new("record-count")
+                .short('n')
+                .long("record-count")
+                .help("Number of records to generate")
+                .value_name("u64")
+                .default_value("10000"),
+        )
+        .arg(
+            Arg::new("read-length")
+                .long("read-length")
+                .help("Number of bases in the sequence")
+                .value_name("usize")
+                .default_value("101"),
+        )
+        .arg(
+            Arg::new("read-length-distribution")
+                .long("read-length-distribution")
+                .help("Distribution of read lengths: `fixed:<len>`, `normal:<mean>,<std-dev>`, or `uniform:<min>,<max>`, e.g., `normal:150,10`. Overrides `read-length`.")
+                .value_name("str"),
+        )
+        .arg(
+            Arg::new("insert-size")
+                .long("insert-size")
+                .alias("insert-size-mean")
+                .help("Mean length of the fragment each pair is drawn from")
+                .value_name("usize")
+                .default_value("300"),
+        )
+        .arg(
+            Arg::new("insert-size-std-dev")
+                .long("insert-size-std-dev")
+                .alias("insert-size-sd")
+                .help("Standard deviation of the normally distributed fragment length")
+                .value_name("f64")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("adapter")
+                .long("adapter")
+                .help("Adapter sequence of read 1. Reads of fragments shorter than the read length read through into the adapter at the 3' end.")
+                .value_name("str"),
+        )
+        .arg(
+            Arg::new("adapter-2")
+                .long("adapter-2")
+                .help("Adapter sequence of read 2 (default: the adapter of read 1)")
+                .value_name("str")
+                .requires("adapter"),
+        )
+        .arg(
+            Arg::new("duplicate-rate")
+                .long("duplicate-rate")
+                .help("Fraction of pairs that are duplicates of a preceding pair, i.e., have the same fragment [0, 0.99]")
+                .value_name("f64")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("reference")
+                .long("reference")
+                .help("Sample fragments from the sequences of a reference FASTA, so that reads align to it. Accepts both raw and gzipped FASTA inputs.")
+                .value_name("path")
+                .conflicts_with_all(&["gc-content", "base-weights"]),
+        )
+        .arg(
+            Arg::new("orientation")
+                .long("orientation")
+                .help("Relative orientation of read 1 and read 2")
+                .value_name("str")
+                .possible_values(["fr", "rf", "ff"])
+                .default_value("fr"),
+        )
+        .arg(
+            Arg::new("gc-content")
+                .long("gc-content")
+                .help("Fraction of G and C bases in generated sequences [0, 1]")
+                .value_name("f64")
+                .conflicts_with_all(&["base-weights", "model-from", "model"]),
+        )
+        .arg(
+            Arg::new("base-weights")
+                .long("base-weights")
+                .help("Relative weights of A, C, G, and T in generated sequences, e.g., `0.3,0.2,0.2,0.3`")
+                .value_name("A,C,G,T")
+                .conflicts_with_all(&["gc-content", "model-from", "model"]),
+        )
+        .arg(
+            Arg::new("quality-model")
+                .long("quality-model")
+                .help("Profile of the quality scores of each cycle: `flat`, scores around Q20 at every cycle; `degrading`, high scores that degrade toward the 3' end; or `illumina-novaseq`, binned NovaSeq scores (Q2, Q12, Q23, Q37) that degrade toward the 3' end")
+                .value_name("str")
+                .possible_values(["flat", "degrading", "illumina-novaseq"])
+                .default_value("flat")
+                .conflicts_with_all(&["model-from", "model"]),
+        )
+        .arg(
+            Arg::new("error-rate")
+                .long("error-rate")
+                .help("Expected number of substitutions per base [0, 1]. Errors are more likely at bases with low quality scores.")
+                .value_name("f64")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("insertion-rate")
+                .long("insertion-rate")
+                .help("Expected number of inserted bases per base [0, 1]. Insertions lengthen reads.")
+                .value_name("f64")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("deletion-rate")
+                .long("deletion-rate")
+                .help("Expected number of deleted bases per base [0, 1]. Deletions shorten reads.")
+                .value_name("f64")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("model-from")
+                .long("model-from")
+                .alias("from-existing")
+                .help("Learn read lengths, quality scores, GC content, and N rate from an existing FASTQ. Overrides `read-length`.")
+                .value_name("path")
+                .conflicts_with("model"),
+        )
+        .arg(
+            Arg::new("model")
+                .long("model")
+                .help("Generator model (JSON) to simulate records from. Overrides `read-length`.")
+                .value_name("path")
+                .conflicts_with("model-from"),
+        )
+        .arg(
+            Arg::new("model-dst")
+                .long("model-dst")
+                .help("Write the learned model (JSON) for reuse with `--model`")
+                .value_name("path")
+                .requires("model-from"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help("Output format: `fastq`; or `bam`, unaligned BAM, either single end (`r1-dst`) or paired (`--interleaved`)")
+                .value_name("str")
+                .possible_values(["fastq", "bam"])
+                .default_value("fastq"),
+        )
+        .arg(
+            Arg::new("read-group")
+                .long("read-group")
+                .value_name("str")
+                .help(r"Read group header line of BAM output, e.g., `@RG\tID:rg0\tSM:sample0`. Records are tagged with its ID. (default: `@RG\tID:A\tSM:sample`, or, with `barcodes`, one read group per sample)")
+                .conflicts_with("barcodes"),
+        )
+        .arg(
+            Arg::new("compression-level")
+                .long("compression-level")
+                .help("Gzip compression level of gzipped outputs, from 0 (none) to 9 (smallest)")
+                .value_name("u32")
+                .default_value("6"),
+        )
+        .arg(
+            Arg::new("compression-threads")
+                .long("compression-threads")
+                .help("Number of threads used to compress each gzipped output. With more than one thread, blocks are compressed in parallel into independent gzip members.")
+                .value_name("usize")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("interleaved")
+                .long("interleaved")
+                .help("Write both mates of each pair, read 1 first, to the given destination instead. Output will be gzipped if ends in `.gz`. Use `-` for stdout.")
+                .value_name("path")
+                .conflicts_with_all(&["r1-dst", "r2-dst"]),
+        )
+        .arg(
+            Arg::new("i1-dst")
+                .long("i1-dst")
+                .help("Index read 1 destination, i.e., the first barcode of the sample of each pair. Output will be gzipped if ends in `.gz`.")
+                .value_name("path")
+                .requires("barcodes"),
+        )
+        .arg(
+            Arg::new("i2-dst")
+                .long("i2-dst")
+                .help("Index read 2 destination, i.e., the second barcode of the sample of each pair. Output will be gzipped if ends in `.gz`.")
+                .value_name("path")
+                .requires("i1-dst"),
+        )
+        .arg(
+            Arg::new("r1-dst")
+                .help("Read 1 destination. Output will be gzipped if ends in `.gz`. Use `-` for stdout.")
+                .index(1)
+                .required_unless_present("interleaved"),
+        )
+        .arg(
+            Arg::new("r2-dst")
+                .help("Read 2 destination. Output will be gzipped if ends in `.gz`. Without it, only read 1 is generated, i.e., single end reads.")
+                .index(2),
+        );
+
+    let grep_cmd = App::new("grep")
+        .about("Outputs records matching a name regex and/or sequence motif")
+        .arg(
+            Arg::new("name-regex")
+                .short('e')
+                .long("name-regex")
+                .value_name("regex")
+                .help("Regular expression matched against the record name, excluding the `@` prefix")
+                .required_unless_present("motif"),
+        )
+        .arg(
+            Arg::new("motif")
+                .short('m')
+                .long("motif")
+                .value_name("str")
+                .help("Sequence motif to search for. Accepts IUPAC nucleotide codes, e.g., `GATYN`.")
+                .required_unless_present("name-regex"),
+        )
+        .arg(
+            Arg::new("invert")
+                .short('v')
+                .long("invert")
+                .help("Output records (or pairs) that do not match"),
+        )
+        .arg(
+            Arg::new("count")
+                .short('c')
+                .long("count")
+                .help("Only print the number of matching records (or pairs) to stdout"),
+        )
+        .arg(
+            Arg::new("r1-dst")
+                .long("r1-dst")
+                .value_name("path")
+                .help("Read 1 destination. Output will be gzipped if ends in `.gz`. Defaults to stdout, where paired reads are interleaved."),
+        )
+        .arg(
+            Arg::new("r2-dst")
+                .long("r2-dst")
+                .value_name("path")
+                .help("Read 2 destination. Output will be gzipped if ends in `.gz`.")
+                .requires("r1-dst"),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2),
+        );
+
+    let head_cmd = App::new("head")
+        .about("Outputs the first records")
+        .arg(
+            Arg::new("record-count")
+                .short('n')
+                .long("record-count")
+                .value_name("u64")
+                .help("The number of records (or pairs) to output")
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("r1-dst")
+                .long("r1-dst")
+                .value_name("path")
+                .help("Read 1 destination. Output will be gzipped if ends in `.gz`. Defaults to stdout, where paired reads are interleaved."),
+        )
+        .arg(
+            Arg::new("r2-dst")
+                .long("r2-dst")
+                .value_name("path")
+                .help("Read 2 destination. Output will be gzipped if ends in `.gz`.")
+                .requires("r1-dst"),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2),
+        );
+
+    let index_cmd = App::new("index")
+        .about("Indexes records for random access")
+        .arg(
+            Arg::new("src")
+                .help("Source. Accepts raw and BGZF-compressed FASTQ inputs. The index is written to `<src>.fqi`.")
+                .index(1)
+                .required(true),
+        );
+
+    let lint_cmd = App::new("lint")
+        .about("Validates a FASTQ file pair")
+        .arg(
+            Arg::new("list-validators")
+                .long("list-validators")
+                .help("Print the code, level, kind, name, and description of each validator and exit. Use with `--output-format` to print a table (text), JSON objects (json), or delimited rows (tsv or csv)."),
+        )
+        .arg(
+            Arg::new("lint-mode")
+                .long("lint-mode")
+                .help("Panic on first error or log all errors")
+                .value_name("str")
+                .possible_values(["panic", "log"])
+                .default_value("panic"),
+        )
+        .arg(
+            Arg::new("max-errors")
+                .long("max-errors")
+                .help("Stop validation with a non-zero exit status after this number of errors. Only applies to log mode.")
+                .value_name("u64"),
+        )
+        .arg(
+            Arg::new("output-format")
+                .long("output-format")
+                .help("Format of validation errors. `json` writes one JSON object per error and a summary object to stdout; `sarif`, a SARIF log; and `tsv` and `csv`, a header row and one row per error.")
+                .value_name("str")
+                .possible_values(["text", "json", "sarif", "tsv", "csv"])
+                .default_value("text"),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .help("Write validation errors in the output format to the given path instead of stdout. Text errors are written instead of logged.")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("single-read-validation-level")
+                .long("single-read-validation-level")
+                .help("Only use single read validators up to a given level")
+                .value_name("str")
+                .possible_values(["low", "medium", "high"])
+                .default_value("high"),
+        )
+        .arg(
+            Arg::new("paired-read-validation-level")
+                .long("paired-read-validation-level")
+                .help("Only use paired read validators up to a given level")
+                .value_name("str")
+                .possible_values(["low", "medium", "high"])
+                .default_value("high"),
+        )
+        .arg(
+            Arg::new("disable-validator")
+                .long("disable-validator")
+                .help("Disable validators by code or name, e.g., S007 or DuplicateNameValidator. Use multiple times to disable more than one.")
+                .value_name("str")
+                .multiple_occurrences(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::new("validator-option")
+                .long("validator-option")
+                .help("Set a validator option as <code>.<key>=<value>, e.g., S002.alphabet=ACGTNU. Use multiple times to set more than one.")
+                .value_name("str")
+                .multiple_occurrences(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::new("rules")
+                .long("rules")
+                .help("Read user-defined validators from a TOML file. See the README for the rule format.")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("enable-validator")
+                .long("enable-validator")
+                .help("Only run the given validators by code or name, regardless of validation level. Use multiple times to enable more than one.")
+                .value_name("str")
+                .multiple_occurrences(true)
+                .number_of_values(1)
+                .conflicts_with("disable-validator"),
+        )
+        .arg(
+            Arg::new("warn-validator")
+                .long("warn-validator")
+                .help("Report errors of validators as warnings by code or name, which do not fail validation. Use multiple times to downgrade more than one.")
+                .value_name("str")
+                .multiple_occurrences(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::new("uniform-read-length")
+                .long("uniform-read-length")
+                .help("Check that all reads have the same length as the first record (S008)"),
+        )
+        .arg(
+            Arg::new("uniform-run")
+                .long("uniform-run")
+                .help("Check that all read names have the instrument, run, flowcell, and lane of the first record (S020), e.g., to catch files of different runs that were concatenated"),
+        )
+        .arg(
+            Arg::new("max-duplicate-rate")
+                .long("max-duplicate-rate")
+                .value_name("f64")
+                .help("Check that the fraction of reads (or pairs) with an exact duplicate sequence is at most this value, from 0.0 to 1.0 (S017)"),
+        )
+        .arg(
+            Arg::new("max-memory")
+                .long("max-memory")
+                .value_name("usize")
+                .help("Limit the memory used to find duplicate names (S007) to about this number of MiB. By default, memory grows with the number of reads. If possible duplicates exceed the limit, validation is reported as partial.")
+        )
+        .arg(
+            Arg::new("max-adapter-rate")
+                .long("max-adapter-rate")
+                .value_name("f64")
+                .help("Check that the fraction of reads in each source containing an adapter sequence is at most this value, from 0.0 to 1.0 (S011)"),
+        )
+        .arg(
+            Arg::new("adapter")
+                .long("adapter")
+                .value_name("str")
+                .help("Adapter sequence to check for with --max-adapter-rate, in addition to common Illumina and Nextera adapters and poly-A/G. Use multiple times to check more than one.")
+                .multiple_occurrences(true)
+                .number_of_values(1)
+                .requires("max-adapter-rate"),
+        )
+        .arg(
+            Arg::new("min-sequence-length")
+                .long("min-sequence-length")
+                .value_name("usize")
+                .help("Check that each sequence is at least this length, e.g., 1 to reject empty records (S013)"),
+        )
+        .arg(
+            Arg::new("max-sequence-length")
+                .long("max-sequence-length")
+                .value_name("usize")
+                .help("Check that each sequence is at most this length (S013)"),
+        )
+        .arg(
+            Arg::new("platform")
+                .long("platform")
+                .value_name("str")
+                .possible_values(["illumina-1.8", "pacbio", "ont"])
+                .help("Check that quality scores are in the range of the given platform: Q0 to Q41 (illumina-1.8), Q93 (pacbio), or Q90 (ont) (S006)"),
+        )
+        .arg(
+            Arg::new("plus-line-style")
+                .long("plus-line-style")
+                .value_name("str")
+                .possible_values(["any", "consistent", "empty", "name"])
+                .default_value("any")
+                .help("Require plus lines to be empty or repeat the name (any), have the same style as the first record (consistent), or have a given style (empty or name) (S018)"),
+        )
+        .arg(
+            Arg::new("name-format")
+                .long("name-format")
+                .value_name("str")
+                .possible_values(["casava", "sra"])
+                .help("Check that each read name is in the given format: Illumina Casava 1.8+ or SRA (S012)"),
+        )
+        .arg(
+            Arg::new("max-n-fraction")
+                .long("max-n-fraction")
+                .value_name("f64")
+                .help("Check that the fraction of N bases in each read is at most this value, from 0.0 to 1.0 (S010)"),
+        )
+        .arg(
+            Arg::new("expected-quality-encoding")
+                .long("expected-quality-encoding")
+                .value_name("str")
+                .possible_values(["phred33", "phred64", "solexa64"])
+                .help("Check that the quality score encoding inferred from each source is the given encoding (S009)"),
+        )
+        .arg(
+            Arg::new("expected-read-length")
+                .long("expected-read-length")
+                .value_name("usize")
+                .help("Check that all reads have the given length (S008)"),
+        )
+        .arg(
+            Arg::new("report")
+                .long("report")
+                .help("Write a validation report to the given path")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("report-format")
+                .long("report-format")
+                .help("Format of the validation report")
+                .value_name("str")
+                .possible_values(["junit"])
+                .default_value("junit"),
+        )
+        .arg(
+            Arg::new("stats")
+                .long("stats")
+                .help("Write read statistics of each source, i.e., the read length distribution, mean quality score of each cycle, and GC content, collected from the validated records, as JSON to the given path")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("summary-path")
+                .long("summary-path")
+                .help(
+                    "Write a JSON summary of the run, with error counts by validator, to the \
+                     given path, independent of the output format",
+                )
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("report-max-failures")
+                .long("report-max-failures")
+                .help("Maximum number of errors listed for each validator in the validation report")
+                .value_name("usize")
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("follow")
+                .long("follow")
+                .short('f')
+                .help("Wait for more data at the end of the sources, like `tail -f`, to validate files as they are written"),
+        )
+        .arg(
+            Arg::new("follow-timeout")
+                .long("follow-timeout")
+                .value_name("u64")
+                .help("Stop following after no new data is read for this number of seconds. By default, sources are followed until interrupted.")
+                .requires("follow"),
+        )
+        .arg(
+            Arg::new("resync")
+                .long("resync")
+                .help("Report malformed records (S019), e.g., with a missing line, and resume validation at the next record instead of misreading the rest of the file. Only single end inputs can be resynced.")
+                .conflicts_with_all(&["r2-src", "interleaved", "pair", "dir"]),
+        )
+        .arg(
+            Arg::new("sample-rate")
+                .long("sample-rate")
+                .value_name("usize")
+                .help("Only validate every this number of records (or pairs), starting with the first, e.g., for quick checks of large inputs. Records in between are read but not validated. Validation is reported as partial.")
+                .conflicts_with("fix"),
+        )
+        .arg(
+            Arg::new("max-records")
+                .long("max-records")
+                .value_name("usize")
+                .help("Stop reading an input after validating this number of records (or pairs). Validation is reported as partial.")
+                .conflicts_with("fix"),
+        )
+        .arg(
+            Arg::new("progress-interval")
+                .long("progress-interval")
+                .value_name("u64")
+                .help("Log progress, i.e., records read and, for files, the percent read and estimated time remaining, every this number of seconds. Use 0 to disable.")
+                .default_value("60"),
+        )
+        .arg(
+            Arg::new("fix")
+                .long("fix")
+                .help("Drop or repair records that fail validation and write the others to --r1-dst and, for paired reads, --r2-dst. Errors are logged instead of stopping validation, i.e., --lint-mode log. Duplicate names (S007) of paired reads cannot be fixed and must be disabled.")
+                .requires("r1-dst")
+                .conflicts_with_all(&["pair", "dir", "max-errors"]),
+        )
+        .arg(
+            Arg::new("r1-dst")
+                .long("r1-dst")
+                .value_name("path")
+                .help("Read 1 destination of fixed records. Output will be gzipped if ends in `.gz`.")
+                .requires("fix"),
+        )
+        .arg(
+            Arg::new("r2-dst")
+                .long("r2-dst")
+                .value_name("path")
+                .help("Read 2 destination of fixed records. Output will be gzipped if ends in `.gz`. Defaults to interleaving mates in --r1-dst.")
+                .requires("fix"),
+        )
+        .arg(
+            Arg::new("fix-policy")
+                .long("fix-policy")
+                .value_name("str")
+                .help("Set the action for records that fail a validator as <code>=<action>, where the action is drop, repair, or keep. Defaults to repair for S001, S005, S016, and S018 and drop otherwise. Use multiple times to set more than one.")
+                .multiple_occurrences(true)
+                .number_of_values(1)
+                .requires("fix"),
+        )
+        .arg(
+            Arg::new("threads")
+                .long("threads")
+                .value_name("usize")
+                .help("Number of threads used to validate records. Records are validated in batches, and errors are reported in the same order as with one thread.")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("progress-bar")
+                .long("progress-bar")
+                .help("Show a progress bar on stderr instead of logging progress. Only used if stderr is a terminal."),
+        )
+        .arg(
+            Arg::new("interleaved")
+                .long("interleaved")
+                .help("Read 1 source is interleaved, i.e., the mates of each pair are consecutive records. Paired read validators are used.")
+                .conflicts_with("r2-src"),
+        )
+        .arg(
+            Arg::new("i1")
+                .long("i1")
+                .value_name("path")
+                .help("Index read 1 source, validated in step with read 1 and read 2. Index read names must match read 1 names (P001), and all index reads must have the same length (S008).")
+                .requires("r2-src")
+                .conflicts_with_all(&["interleaved", "pair", "dir", "fix"]),
+        )
+        .arg(
+            Arg::new("i2")
+                .long("i2")
+                .value_name("path")
+                .help("Index read 2 source, for dual index runs")
+                .requires("i1"),
+        )
+        .arg(
+            Arg::new("expected-index-length")
+                .long("expected-index-length")
+                .value_name("usize")
+                .help("Check that all index reads have the given length (S008)")
+                .requires("i1"),
+        )
+        .arg(
+            Arg::new("pair")
+                .long("pair")
+                .value_name("r1,r2")
+                .help("Read 1 and read 2 sources of a pair, separated by a comma. Use multiple times to validate more than one pair. Errors of all inputs are summarized together.")
+                .multiple_occurrences(true)
+                .number_of_values(1)
+                .conflicts_with_all(&["r1-src", "interleaved", "follow"]),
+        )
+        .arg(
+            Arg::new("dir")
+                .long("dir")
+                .value_name("path")
+                .help("Validate all FASTQ files (`*.fastq`, `*.fq`, optionally gzipped) in a directory. Files with `_R1` and `_R2` in their names are validated as pairs, e.g., `sample_R1_001.fastq.gz` and `sample_R2_001.fastq.gz`.")
+                .conflicts_with_all(&["r1-src", "interleaved", "follow"]),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs. Use `-` to read from stdin.")
+                .index(1)
+                .required_unless_present_any(["pair", "dir", "list-validators"]),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2),
+        );
+
+    let demux_cmd = App::new("demux")
+        .about("Demultiplexes reads into samples by barcode")
+        .arg(
+            Arg::new("barcodes")
+                .long("barcodes")
+                .value_name("path")
+                .help("Tab-delimited sample sheet of sample names and one or two barcodes")
+                .required(true),
+        )
+        .arg(Arg::new("i1").long("i1").value_name("path").help(
+            "Index read 1 source. Defaults to reading inline barcodes from the start of read 1.",
+        ))
+        .arg(
+            Arg::new("i2")
+                .long("i2")
+                .value_name("path")
+                .help("Index read 2 source, for dual index barcodes")
+                .requires("i1"),
+        )
+        .arg(
+            Arg::new("max-mismatches")
+                .long("max-mismatches")
+                .value_name("usize")
+                .help("Maximum number of mismatches allowed in each barcode")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("n-mode")
+                .long("n-mode")
+                .value_name("str")
+                .help("Count an `N` in an observed barcode as a mismatch or match any base")
+                .possible_values(["mismatch", "wildcard"])
+                .default_value("mismatch"),
+        )
+        .arg(
+            Arg::new("output-dir")
+                .long("output-dir")
+                .value_name("path")
+                .help("Directory to write per-sample files to")
+                .default_value("."),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2),
+        );
+
+    let describe_cmd = App::new("describe")
+        .about("Reports summary statistics")
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help("Output format")
+                .value_name("str")
+                .possible_values(["json", "tsv"])
+                .default_value("tsv"),
+        )
+        .arg(
+            Arg::new("src")
+                .help("Source FASTQ. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        );
+
+    let mask_cmd = App::new("mask")
+        .about("Replaces low quality or selected bases with N")
+        .arg(
+            Arg::new("min-quality")
+                .long("min-quality")
+                .value_name("u8")
+                .help("Mask bases with a quality score below this threshold")
+                .required_unless_present("range"),
+        )
+        .arg(
+            Arg::new("range")
+                .long("range")
+                .value_name("start-end")
+                .help("Inclusive range of 1-based positions to mask in each read, e.g., `1-10`. Use multiple times to mask more than one.")
+                .multiple_occurrences(true)
+                .number_of_values(1)
+                .required_unless_present("min-quality"),
+        )
+        .arg(
+            Arg::new("r1-dst")
+                .help("Read 1 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r1-dst")
+                .value_name("path")
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-dst")
+                .help("Read 2 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r2-dst")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2),
+        );
+
+    let merge_pairs_cmd = App::new("merge-pairs")
+        .about("Merges overlapping paired end reads into single reads")
+        .arg(
+            Arg::new("min-overlap")
+                .long("min-overlap")
+                .value_name("usize")
+                .help("Minimum number of overlapping bases required to merge a pair")
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("max-error-rate")
+                .long("max-error-rate")
+                .value_name("f64")
+                .help("Maximum fraction of mismatched bases in the overlap")
+                .default_value("0.1"),
+        )
+        .arg(
+            Arg::new("merged-dst")
+                .help("Merged reads destination. Output will be gzipped if ends in `.gz`.")
+                .long("merged-dst")
+                .value_name("path")
+                .required(true),
+        )
+        .arg(
+            Arg::new("r1-dst")
+                .help("Unmerged read 1 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r1-dst")
+                .value_name("path")
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-dst")
+                .help("Unmerged read 2 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r2-dst")
+                .value_name("path")
+                .required(true),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2)
+                .required(true),
+        );
+
+    let rename_cmd = App::new("rename")
+        .about("Renames records using a template")
+        .arg(
+            Arg::new("template")
+                .long("template")
+                .value_name("str")
+                .help("Name template. `{index}` is replaced by the 1-based record (or pair) index and `{name}` by the original name. Pair suffixes (`/1`, `/2`) and comments are kept.")
+                .required(true),
+        )
+        .arg(
+            Arg::new("r1-dst")
+                .help("Read 1 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r1-dst")
+                .value_name("path")
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-dst")
+                .help("Read 2 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r2-dst")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2),
+        );
+
+    let repair_cmd = App::new("repair")
+        .about("Re-pairs mates that are out of sync")
+        .arg(
+            Arg::new("singletons")
+                .long("singletons")
+                .help("Destination for reads without a mate. Output will be gzipped if ends in `.gz`. Defaults to discarding them.")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("max-records-in-memory")
+                .long("max-records-in-memory")
+                .help("The number of unmatched records to hold in memory before spilling to temporary files")
+                .value_name("usize")
+                .default_value("1000000"),
+        )
+        .arg(
+            Arg::new("tmp-dir")
+                .long("tmp-dir")
+                .help("Directory for temporary files. Defaults to the system temporary directory.")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("r1-dst")
+                .help("Read 1 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r1-dst")
+                .value_name("path")
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-dst")
+                .help("Read 2 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r2-dst")
+                .value_name("path")
+                .required(true),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2)
+                .required(true),
+        );
+
+    let revcomp_cmd = App::new("revcomp")
+        .about("Reverse complements reads")
+        .alias("reverse-complement")
+        .arg(
+            Arg::new("r1-dst")
+                .help("Read 1 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r1-dst")
+                .value_name("path")
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-dst")
+                .help("Read 2 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r2-dst")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2),
+        );
+
+    let screen_cmd = App::new("screen")
+        .about("Screens reads for contamination using reference k-mer sets")
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .setting(AppSettings::ArgsNegateSubcommands)
         .arg(
-            Arg::new("names")
-                .long("names")
+            Arg::new("kmer-set")
+                .long("kmer-set")
                 .value_name("path")
-                .help("Allowlist of record names")
+                .help("K-mer set to screen against, built with `fq screen build`. Use multiple times to screen against more than one reference.")
+                .multiple_occurrences(true)
+                .number_of_values(1)
                 .required(true),
         )
-        .arg(Arg::new("src").help("Source FASTQ").index(1).required(true));
-
-    let generate_cmd = App::new("generate")
-        .about("Generates a random FASTQ file pair")
         .arg(
-            Arg::new("seed")
-                .short('s')
-                .long("seed")
-                .value_name("u64")
-                .help("Seed to use for the random number generator"),
+            Arg::new("min-hits")
+                .long("min-hits")
+                .value_name("usize")
+                .help("Minimum number of k-mers of a read that must be in a k-mer set to hit it")
+                .default_value("1"),
         )
         .arg(
-            Arg::new("record-count")
-                .short('n')
-                .long("record-count")
-                .help("Number of records to generate")
-                .value_name("u64")
-                .default_value("10000"),
+            Arg::new("flag")
+                .long("flag")
+                .help("Keep contaminated reads and append the references they hit to their names, e.g., ` screen=phix`"),
         )
         .arg(
-            Arg::new("read-length")
-                .long("read-length")
-                .help("Number of bases in the sequence")
-                .value_name("usize")
-                .default_value("101"),
+            Arg::new("report")
+                .long("report")
+                .value_name("path")
+                .help("Write a report of hit rates per reference (TSV)"),
         )
         .arg(
             Arg::new("r1-dst")
                 .help("Read 1 destination. Output will be gzipped if ends in `.gz`.")
-                .index(1)
+                .long("r1-dst")
+                .value_name("path")
                 .required(true),
         )
         .arg(
             Arg::new("r2-dst")
                 .help("Read 2 destination. Output will be gzipped if ends in `.gz`.")
-                .index(2)
+                .long("r2-dst")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
                 .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2),
+        )
+        .subcommand(
+            App::new("build")
+                .about("Builds a k-mer set from reference sequences")
+                .arg(
+                    Arg::new("kmer-size")
+                        .short('k')
+                        .long("kmer-size")
+                        .value_name("usize")
+                        .help("K-mer size [1, 32]")
+                        .default_value("21"),
+                )
+                .arg(
+                    Arg::new("name")
+                        .long("name")
+                        .value_name("str")
+                        .help("Reference name used in reports. Defaults to the destination file stem."),
+                )
+                .arg(
+                    Arg::new("dst")
+                        .long("dst")
+                        .value_name("path")
+                        .help("K-mer set destination")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("src")
+                        .help("Reference sequences (FASTA). Accepts both raw and gzipped inputs.")
+                        .multiple_values(true)
+                        .required(true),
+                ),
         );
 
-    let lint_cmd = App::new("lint")
-        .about("Validates a FASTQ file pair")
+    let sort_cmd = App::new("sort")
+        .about("Sorts records by name or sequence")
         .arg(
-            Arg::new("lint-mode")
-                .long("lint-mode")
-                .help("Panic on first error or log all errors")
+            Arg::new("by")
+                .long("by")
+                .help("Sort by name, comparing bytes (`name`) or numbers by value (`natural`), or by sequence (`sequence`)")
                 .value_name("str")
-                .possible_values(&["panic", "log"])
-                .default_value("panic"),
+                .possible_values(["name", "natural", "sequence"])
+                .default_value("name"),
         )
         .arg(
-            Arg::new("single-read-validation-level")
-                .long("single-read-validation-level")
-                .help("Only use single read validators up to a given level")
-                .value_name("str")
-                .possible_values(&["low", "medium", "high"])
-                .default_value("high"),
+            Arg::new("max-records-in-memory")
+                .long("max-records-in-memory")
+                .help("The number of records (or pairs) to sort in memory before spilling to a temporary file")
+                .value_name("usize")
+                .default_value("1000000"),
         )
         .arg(
-            Arg::new("paired-read-validation-level")
-                .long("paired-read-validation-level")
-                .help("Only use paired read validators up to a given level")
+            Arg::new("tmp-dir")
+                .long("tmp-dir")
+                .help("Directory for temporary files. Defaults to the system temporary directory.")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("r1-dst")
+                .help("Read 1 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r1-dst")
+                .value_name("path")
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-dst")
+                .help("Read 2 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r2-dst")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2),
+        );
+
+    let split_cmd = App::new("split")
+        .about("Splits a FASTQ file or file pair into chunks")
+        .arg(
+            Arg::new("records-per-file")
+                .short('n')
+                .long("records-per-file")
+                .value_name("u64")
+                .help("The number of records (or pairs) per chunk")
+                .required(true),
+        )
+        .arg(
+            Arg::new("prefix")
+                .long("prefix")
                 .value_name("str")
-                .possible_values(&["low", "medium", "high"])
-                .default_value("high"),
+                .help("Destination prefix of each chunk, e.g., `chunks/sample_`")
+                .default_value("chunk_"),
         )
         .arg(
-            Arg::new("disable-validator")
-                .long("disable-validator")
-                .help("Disable validators by code. Use multiple times to disable more than one.")
+            Arg::new("suffix")
+                .long("suffix")
                 .value_name("str")
-                .multiple_occurrences(true)
-                .number_of_values(1),
+                .help("Destination suffix of each chunk. Output will be gzipped if ends in `.gz`.")
+                .default_value(".fastq.gz"),
         )
         .arg(
             Arg::new("r1-src")
@@ -103,6 +1554,39 @@ fn main() -> anyhow::Result<()> {
                 .index(2),
         );
 
+    let stats_cmd = App::new("stats")
+        .about("Reports read length statistics")
+        .arg(
+            Arg::new("length-threshold")
+                .long("length-threshold")
+                .help("Report the records and bases of reads at least this long. Use multiple times to report more than one.")
+                .value_name("usize")
+                .multiple_occurrences(true)
+                .number_of_values(1)
+                .default_values(&["10000", "50000"]),
+        )
+        .arg(
+            Arg::new("longest-count")
+                .long("longest-count")
+                .help("Number of longest reads to list")
+                .value_name("usize")
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("adapter")
+                .long("adapter")
+                .help("Adapter sequence to report the content of by cycle. Use multiple times to check more than one. Defaults to common Illumina and Nextera adapters and poly-A/G.")
+                .value_name("str")
+                .multiple_occurrences(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::new("src")
+                .help("Source FASTQ. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        );
+
     let subsample_cmd = App::new("subsample")
         .about("Outputs a subset of records")
         .arg(
@@ -110,18 +1594,34 @@ fn main() -> anyhow::Result<()> {
                 .short('p')
                 .long("probability")
                 .value_name("f64")
-                .help("The probability a record is kept, as a percentage [0, 1]. Cannot be used with `record-count`.")
-                .required(true)
-                .conflicts_with("record-count"),
+                .help("The probability a record is kept, as a percentage [0, 1]. Cannot be used with `record-count` or `target-coverage`.")
+                .required_unless_present_any(["record-count", "target-coverage"])
+                .conflicts_with_all(&["record-count", "target-coverage"]),
         )
         .arg(
             Arg::new("record-count")
                 .short('n')
                 .long("record-count")
                 .value_name("u64")
-                .help("The exact number of records to keep. Cannot be used with `probability`.")
-                .required(true)
-                .conflicts_with("probability"),
+                .help("The exact number of records to keep. Cannot be used with `probability` or `target-coverage`.")
+                .required_unless_present_any(["probability", "target-coverage"])
+                .conflicts_with_all(&["probability", "target-coverage"]),
+        )
+        .arg(
+            Arg::new("target-coverage")
+                .long("target-coverage")
+                .value_name("f64")
+                .help("The depth of coverage to keep, e.g., `30` for 30x. Requires `genome-size`. Cannot be used with `probability` or `record-count`.")
+                .required_unless_present_any(["probability", "record-count"])
+                .conflicts_with_all(&["probability", "record-count"])
+                .requires("genome-size"),
+        )
+        .arg(
+            Arg::new("genome-size")
+                .long("genome-size")
+                .value_name("f64")
+                .help("The number of bases in the genome, e.g., `3.1e9`")
+                .requires("target-coverage"),
         )
         .arg(
             Arg::new("seed")
@@ -155,31 +1655,372 @@ fn main() -> anyhow::Result<()> {
                 .index(2),
         );
 
+    let trim_cmd = App::new("trim")
+        .about("Removes bases from read ends")
+        .arg(
+            Arg::new("trim-start")
+                .long("trim-start")
+                .value_name("usize")
+                .help("Number of bases to remove from the start of each read")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("trim-end")
+                .long("trim-end")
+                .value_name("usize")
+                .help("Number of bases to remove from the end of each read")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("poly-g")
+                .long("poly-g")
+                .help("Remove polyG tails, e.g., from two-color instruments. Same as `--poly-x G`."),
+        )
+        .arg(
+            Arg::new("poly-x")
+                .long("poly-x")
+                .value_name("base")
+                .help("Remove tails of a repeated base from the 3' end of each read. Use multiple times to remove more than one base.")
+                .multiple_occurrences(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::new("poly-x-min-length")
+                .long("poly-x-min-length")
+                .value_name("usize")
+                .help("Minimum length of a poly-X tail to remove")
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("quality-cutoff")
+                .long("quality-cutoff")
+                .value_name("u8")
+                .help("Trim the end of each read from the first window with a mean quality score below this cutoff"),
+        )
+        .arg(
+            Arg::new("window-size")
+                .long("window-size")
+                .value_name("usize")
+                .help("Size of the sliding window used for quality trimming")
+                .default_value("4"),
+        )
+        .arg(
+            Arg::new("adapter")
+                .long("adapter")
+                .value_name("str")
+                .help("Adapter sequence to remove from the 3' end of each read, along with everything after it. Use multiple times to remove more than one.")
+                .multiple_occurrences(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::new("adapter-error-rate")
+                .long("adapter-error-rate")
+                .value_name("f64")
+                .help("Maximum fraction of mismatches allowed in an adapter match")
+                .default_value("0.1"),
+        )
+        .arg(
+            Arg::new("adapter-min-overlap")
+                .long("adapter-min-overlap")
+                .value_name("usize")
+                .help("Minimum number of adapter bases that must overlap the end of a read")
+                .default_value("3"),
+        )
+        .arg(
+            Arg::new("r1-dst")
+                .help("Read 1 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r1-dst")
+                .value_name("path")
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-dst")
+                .help("Read 2 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r2-dst")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2),
+        );
+
+    let trim_primers_cmd = App::new("trim-primers")
+        .about("Removes amplicon primer sequences from read ends")
+        .arg(
+            Arg::new("scheme")
+                .long("scheme")
+                .value_name("path")
+                .help("Primer scheme (BED). Primer sequences are read from `--reference`.")
+                .requires("reference")
+                .required_unless_present("primers")
+                .conflicts_with("primers"),
+        )
+        .arg(
+            Arg::new("reference")
+                .long("reference")
+                .value_name("path")
+                .help("Reference sequences (FASTA) of the primer scheme")
+                .requires("scheme"),
+        )
+        .arg(
+            Arg::new("primers")
+                .long("primers")
+                .value_name("path")
+                .help("Primer sequences (FASTA). Cannot be used with `scheme`.")
+                .required_unless_present("scheme")
+                .conflicts_with("scheme"),
+        )
+        .arg(
+            Arg::new("max-mismatches")
+                .long("max-mismatches")
+                .value_name("usize")
+                .help("Maximum number of mismatches allowed when matching a primer")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("r1-dst")
+                .help("Read 1 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r1-dst")
+                .value_name("path")
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-dst")
+                .help("Read 2 destination. Output will be gzipped if ends in `.gz`.")
+                .long("r2-dst")
+                .value_name("path"),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(2),
+        );
+
+    let umi_cmd = App::new("umi")
+        .about("Processes unique molecular identifiers (UMIs)")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            App::new("extract")
+                .about("Moves an inline UMI from the start of a read to the read names")
+                .arg(
+                    Arg::new("length")
+                        .long("length")
+                        .value_name("usize")
+                        .help("Number of bases of the UMI")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("read")
+                        .long("read")
+                        .value_name("u8")
+                        .help("Read to clip the UMI from")
+                        .possible_values(["1", "2"])
+                        .default_value("1"),
+                )
+                .arg(
+                    Arg::new("r1-dst")
+                        .help("Read 1 destination. Output will be gzipped if ends in `.gz`.")
+                        .long("r1-dst")
+                        .value_name("path")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("r2-dst")
+                        .help("Read 2 destination. Output will be gzipped if ends in `.gz`.")
+                        .long("r2-dst")
+                        .value_name("path"),
+                )
+                .arg(
+                    Arg::new("r1-src")
+                        .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                        .index(1)
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("r2-src")
+                        .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs.")
+                        .index(2),
+                ),
+        );
+
+    let view_cmd = App::new("view")
+        .about("Prints records for viewing, with colored bases and quality scores")
+        .arg(
+            Arg::new("limit")
+                .short('n')
+                .long("limit")
+                .value_name("u64")
+                .help("Maximum number of records (or pairs) to print"),
+        )
+        .arg(
+            Arg::new("range")
+                .long("range")
+                .value_name("start-end")
+                .help("Only print bases and quality scores in an inclusive range of 1-based positions, e.g., `1-50`"),
+        )
+        .arg(
+            Arg::new("color")
+                .long("color")
+                .value_name("str")
+                .help("When to color the output. `auto` colors it if stdout is a terminal.")
+                .possible_values(["auto", "always", "never"])
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::new("r1-src")
+                .help("Read 1 source. Accepts both raw and gzipped FASTQ inputs.")
+                .index(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("r2-src")
+                .help("Read 2 source. Accepts both raw and gzipped FASTQ inputs. Pairs are interleaved.")
+                .index(2),
+        );
+
     let matches = App::new("fq")
         .version(version.as_str())
         .setting(AppSettings::PropagateVersion)
         .setting(AppSettings::SubcommandRequiredElseHelp)
         .arg(Arg::new("verbose").short('v').long("verbose").hide(true))
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .global(true)
+                .conflicts_with("log-level")
+                .help("Only log errors. This is the same as `--log-level error`."),
+        )
+        .arg(
+            Arg::new("log-level")
+                .long("log-level")
+                .global(true)
+                .help("Maximum level of log messages written to stderr")
+                .value_name("str")
+                .possible_values(["off", "error", "warn", "info", "debug", "trace"])
+                .default_value("info"),
+        )
+        .subcommand(cat_cmd)
+        .subcommand(clip_cmd)
+        .subcommand(compare_cmd)
+        .subcommand(convert_cmd)
+        .subcommand(count_cmd)
+        .subcommand(dedup_cmd)
+        .subcommand(demux_cmd)
+        .subcommand(describe_cmd)
+        .subcommand(extract_cmd)
         .subcommand(filter_cmd)
+        .subcommand(fix_cmd)
         .subcommand(generate_cmd)
+        .subcommand(grep_cmd)
+        .subcommand(head_cmd)
+        .subcommand(index_cmd)
         .subcommand(lint_cmd)
+        .subcommand(mask_cmd)
+        .subcommand(merge_pairs_cmd)
+        .subcommand(rename_cmd)
+        .subcommand(repair_cmd)
+        .subcommand(revcomp_cmd)
+        .subcommand(screen_cmd)
+        .subcommand(sort_cmd)
+        .subcommand(split_cmd)
+        .subcommand(stats_cmd)
         .subcommand(subsample_cmd)
+        .subcommand(trim_cmd)
+        .subcommand(trim_primers_cmd)
+        .subcommand(umi_cmd)
+        .subcommand(view_cmd)
         .get_matches();
 
-    tracing_subscriber::fmt::init();
+    let log_level = if matches.is_present("quiet") {
+        LevelFilter::ERROR
+    } else {
+        // Possible values are valid level filters.
+        matches.value_of_t_or_exit("log-level")
+    };
+
+    tracing_subscriber::fmt()
+        .with_max_level(log_level)
+        .with_writer(io::stderr)
+        .init();
 
     if matches.is_present("verbose") {
         warn!("`--verbose` is deprecated and will be removed in a future version. Logging is now always enabled.");
     }
 
-    if let Some(m) = matches.subcommand_matches("filter") {
+    if let Some(m) = matches.subcommand_matches("cat") {
+        cat(m)
+    } else if let Some(m) = matches.subcommand_matches("clip") {
+        clip(m)
+    } else if let Some(m) = matches.subcommand_matches("compare") {
+        compare(m)
+    } else if let Some(m) = matches.subcommand_matches("convert") {
+        convert(m)
+    } else if let Some(m) = matches.subcommand_matches("count") {
+        count(m)
+    } else if let Some(m) = matches.subcommand_matches("dedup") {
+        dedup(m)
+    } else if let Some(m) = matches.subcommand_matches("demux") {
+        demux(m)
+    } else if let Some(m) = matches.subcommand_matches("describe") {
+        describe(m)
+    } else if let Some(m) = matches.subcommand_matches("extract") {
+        extract(m)
+    } else if let Some(m) = matches.subcommand_matches("filter") {
         filter(m)
+    } else if let Some(m) = matches.subcommand_matches("fix") {
+        fix(m)
     } else if let Some(m) = matches.subcommand_matches("generate") {
         generate(m)
+    } else if let Some(m) = matches.subcommand_matches("grep") {
+        grep(m)
+    } else if let Some(m) = matches.subcommand_matches("head") {
+        head(m)
+    } else if let Some(m) = matches.subcommand_matches("index") {
+        index(m)
     } else if let Some(m) = matches.subcommand_matches("lint") {
         lint(m)
+    } else if let Some(m) = matches.subcommand_matches("mask") {
+        mask(m)
+    } else if let Some(m) = matches.subcommand_matches("merge-pairs") {
+        merge_pairs(m)
+    } else if let Some(m) = matches.subcommand_matches("rename") {
+        rename(m)
+    } else if let Some(m) = matches.subcommand_matches("repair") {
+        repair(m)
+    } else if let Some(m) = matches.subcommand_matches("revcomp") {
+        revcomp(m)
+    } else if let Some(m) = matches.subcommand_matches("screen") {
+        screen(m)
+    } else if let Some(m) = matches.subcommand_matches("sort") {
+        sort(m)
+    } else if let Some(m) = matches.subcommand_matches("split") {
+        split(m)
+    } else if let Some(m) = matches.subcommand_matches("stats") {
+        stats(m)
     } else if let Some(m) = matches.subcommand_matches("subsample") {
         subsample(m)
+    } else if let Some(m) = matches.subcommand_matches("trim") {
+        trim(m)
+    } else if let Some(m) = matches.subcommand_matches("trim-primers") {
+        trim_primers(m)
+    } else if let Some(m) = matches.subcommand_matches("umi") {
+        umi(m)
+    } else if let Some(m) = matches.subcommand_matches("view") {
+        view(m)
     } else {
         unreachable!();
     }
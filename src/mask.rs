@@ -0,0 +1,188 @@
+//! Base masking.
+
+use std::{error, fmt, str::FromStr};
+
+use crate::{fastq::Record, quality::Encoding};
+
+/// The base that masked positions are rewritten to.
+pub const MASK_BASE: u8 = b'N';
+
+const QUALITY_OFFSET: u8 = Encoding::Phred33.offset();
+const RANGE_DELIMITER: char = '-';
+
+/// An inclusive range of 1-based read positions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PositionRange {
+    start: usize,
+    end: usize,
+}
+
+impl PositionRange {
+    /// Creates a position range.
+    ///
+    /// This returns `None` if `start` is 0 or greater than `end`.
+    pub fn new(start: usize, end: usize) -> Option<Self> {
+        if start == 0 || start > end {
+            None
+        } else {
+            Some(Self { start, end })
+        }
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+}
+
+/// An error returned when a position range fails to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParsePositionRangeError(String);
+
+impl error::Error for ParsePositionRangeError {}
+
+impl fmt::Display for ParsePositionRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid position range: expected <start>-<end> with 1 <= start <= end, got '{}'",
+            self.0
+        )
+    }
+}
+
+impl FromStr for PositionRange {
+    type Err = ParsePositionRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once(RANGE_DELIMITER)
+            .ok_or_else(|| ParsePositionRangeError(s.into()))?;
+
+        let start = start
+            .parse()
+            .map_err(|_| ParsePositionRangeError(s.into()))?;
+        let end = end.parse().map_err(|_| ParsePositionRangeError(s.into()))?;
+
+        Self::new(start, end).ok_or_else(|| ParsePositionRangeError(s.into()))
+    }
+}
+
+/// Masks bases with a Phred quality score below `min_quality`.
+///
+/// Quality scores are left unchanged. This returns the number of bases masked.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{fastq::Record, mask::mask_low_quality};
+///
+/// let mut record = Record::new("@r0", "ACGTACGT", "+", "II#I5+II");
+/// assert_eq!(mask_low_quality(&mut record, 20), 2);
+/// assert_eq!(record.sequence(), b"ACNTANGT");
+/// assert_eq!(record.quality_scores(), b"II#I5+II");
+/// ```
+pub fn mask_low_quality(record: &mut Record, min_quality: u8) -> usize {
+    let min_score = min_quality.saturating_add(QUALITY_OFFSET);
+
+    let len = record.sequence().len().min(record.quality_scores().len());
+    let mut n = 0;
+
+    for i in 0..len {
+        if record.quality_scores()[i] < min_score && record.sequence()[i] != MASK_BASE {
+            record.sequence_mut()[i] = MASK_BASE;
+            n += 1;
+        }
+    }
+
+    n
+}
+
+/// Masks bases in a range of positions.
+///
+/// The parts of the range past the end of the read are ignored. This returns the number of bases
+/// masked.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{fastq::Record, mask::{mask_range, PositionRange}};
+///
+/// let mut record = Record::new("@r0", "ACGTACGT", "+", "IIIIIIII");
+/// let range = PositionRange::new(7, 10).unwrap();
+/// assert_eq!(mask_range(&mut record, range), 2);
+/// assert_eq!(record.sequence(), b"ACGTACNN");
+/// ```
+pub fn mask_range(record: &mut Record, range: PositionRange) -> usize {
+    let sequence = record.sequence_mut();
+
+    let start = (range.start() - 1).min(sequence.len());
+    let end = range.end().min(sequence.len());
+
+    let mut n = 0;
+
+    for base in &mut sequence[start..end] {
+        if *base != MASK_BASE {
+            *base = MASK_BASE;
+            n += 1;
+        }
+    }
+
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("1-10".parse(), Ok(PositionRange { start: 1, end: 10 }));
+        assert_eq!("5-5".parse(), Ok(PositionRange { start: 5, end: 5 }));
+
+        assert!("0-10".parse::<PositionRange>().is_err());
+        assert!("10-5".parse::<PositionRange>().is_err());
+        assert!("10".parse::<PositionRange>().is_err());
+        assert!("1-".parse::<PositionRange>().is_err());
+        assert_eq!(
+            "a-b".parse::<PositionRange>(),
+            Err(ParsePositionRangeError(String::from("a-b")))
+        );
+    }
+
+    #[test]
+    fn test_mask_low_quality() {
+        let mut record = Record::new("@r0", "ANGT", "+", "####");
+        assert_eq!(mask_low_quality(&mut record, 20), 3);
+        assert_eq!(record.sequence(), b"NNNN");
+
+        let mut record = Record::new("@r1", "ACGT", "+", "5555");
+        assert_eq!(mask_low_quality(&mut record, 20), 0);
+        assert_eq!(mask_low_quality(&mut record, 21), 4);
+    }
+
+    #[test]
+    fn test_mask_range() {
+        let mut record = Record::new("@r0", "ACGT", "+", "IIII");
+        assert_eq!(
+            mask_range(&mut record, PositionRange::new(2, 3).unwrap()),
+            2
+        );
+        assert_eq!(record.sequence(), b"ANNT");
+
+        assert_eq!(
+            mask_range(&mut record, PositionRange::new(1, 2).unwrap()),
+            1
+        );
+        assert_eq!(record.sequence(), b"NNNT");
+
+        assert_eq!(
+            mask_range(&mut record, PositionRange::new(8, 10).unwrap()),
+            0
+        );
+        assert_eq!(record.sequence(), b"NNNT");
+    }
+}
@@ -0,0 +1,140 @@
+//! Approximate sequence matching.
+
+use crate::sequence::is_compatible;
+
+/// A match of a pattern in a sequence.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Match {
+    start: usize,
+    len: usize,
+    mismatches: usize,
+}
+
+impl Match {
+    /// Returns the start position of the match in the sequence.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the number of aligned bases.
+    ///
+    /// This is shorter than the pattern when only a prefix of the pattern overlaps the end of the
+    /// sequence.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether no bases are aligned.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of mismatches in the aligned bases.
+    pub fn mismatches(&self) -> usize {
+        self.mismatches
+    }
+}
+
+/// An ungapped overlap aligner for finding a pattern toward the 3' end of a sequence.
+///
+/// The pattern may start anywhere in the sequence and run past its end, e.g., an adapter that is
+/// only partially read. An alignment matches if it overlaps at least `min_overlap` bases and has
+/// at most `floor(len * max_error_rate)` mismatches. IUPAC codes are compared by compatibility.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OverlapMatcher {
+    max_error_rate: f64,
+    min_overlap: usize,
+}
+
+impl OverlapMatcher {
+    /// Creates an overlap matcher.
+    ///
+    /// A `min_overlap` of 0 is treated as 1.
+    pub fn new(max_error_rate: f64, min_overlap: usize) -> Self {
+        Self {
+            max_error_rate,
+            min_overlap: min_overlap.max(1),
+        }
+    }
+
+    /// Finds the leftmost match of the pattern in the sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::matching::OverlapMatcher;
+    ///
+    /// let matcher = OverlapMatcher::new(0.1, 3);
+    ///
+    /// let m = matcher.find(b"AGATCGGAAG", b"ACGTACGTAGATCG").unwrap();
+    /// assert_eq!((m.start(), m.len(), m.mismatches()), (8, 6, 0));
+    ///
+    /// assert!(matcher.find(b"AGATCGGAAG", b"ACGTACGTACGTAG").is_none());
+    /// ```
+    pub fn find(&self, pattern: &[u8], sequence: &[u8]) -> Option<Match> {
+        if pattern.is_empty() {
+            return None;
+        }
+
+        let last_start = sequence.len().checked_sub(self.min_overlap)?;
+
+        (0..=last_start).find_map(|start| {
+            let len = pattern.len().min(sequence.len() - start);
+
+            if len < self.min_overlap {
+                return None;
+            }
+
+            let max_mismatches = (len as f64 * self.max_error_rate) as usize;
+            let mut mismatches = 0;
+
+            for (&a, &b) in pattern.iter().zip(&sequence[start..start + len]) {
+                if !is_compatible(a, b) {
+                    mismatches += 1;
+
+                    if mismatches > max_mismatches {
+                        return None;
+                    }
+                }
+            }
+
+            Some(Match {
+                start,
+                len,
+                mismatches,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let matcher = OverlapMatcher::new(0.1, 3);
+        let pattern = b"AGATCGGAAG";
+
+        // full match
+        let m = matcher.find(pattern, b"ACGTAGATCGGAAGACGT").unwrap();
+        assert_eq!((m.start(), m.len(), m.mismatches()), (4, 10, 0));
+
+        // full match with one mismatch
+        let m = matcher.find(pattern, b"ACGTAGATCGCAAGACGT").unwrap();
+        assert_eq!((m.start(), m.len(), m.mismatches()), (4, 10, 1));
+
+        // partial overlap at the 3' end
+        let m = matcher.find(pattern, b"ACGTACGTAGA").unwrap();
+        assert_eq!((m.start(), m.len()), (8, 3));
+
+        // overlap shorter than the minimum
+        assert!(matcher.find(pattern, b"ACGTACGTCAG").is_none());
+
+        // too many mismatches for a short overlap
+        assert!(matcher.find(pattern, b"ACGTACGTAGTTC").is_none());
+
+        assert!(matcher.find(pattern, b"AG").is_none());
+        assert!(matcher.find(b"", b"ACGT").is_none());
+    }
+}
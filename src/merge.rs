@@ -0,0 +1,166 @@
+//! Paired read overlap merging.
+
+use crate::{
+    fastq::{name_id, Record},
+    matching::OverlapMatcher,
+    quality::Encoding,
+};
+
+const NAME_PREFIX: u8 = b'@';
+const QUALITY_OFFSET: u8 = Encoding::Phred33.offset();
+
+// Quality scores of agreeing bases are summed but capped, and those of disagreeing bases are the
+// difference of the two, floored.
+const MAX_QUALITY: u8 = 41;
+const MIN_QUALITY: u8 = 2;
+
+/// A paired read merger.
+///
+/// Read 2 is reverse complemented and aligned to the 3' end of read 1 without gaps. When the mates
+/// overlap, they are merged into a single read that spans the fragment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PairMerger {
+    matcher: OverlapMatcher,
+}
+
+impl PairMerger {
+    /// Creates a pair merger.
+    pub fn new(matcher: OverlapMatcher) -> Self {
+        Self { matcher }
+    }
+
+    /// Merges a pair of mates into `dst`.
+    ///
+    /// The merged read is read 1 up to the overlap, a consensus of the overlap, and the rest of
+    /// the reverse complement of read 2. In the overlap, agreeing bases get the sum of their
+    /// quality scores (capped at Q41). Disagreeing bases are resolved to the base with the higher
+    /// quality score, with the difference of the scores (floored at Q2) as its quality score. The
+    /// name is the read 1 name without its pair suffix and comment.
+    ///
+    /// This returns the length of the overlap or `None`, leaving `dst` unchanged, if the mates do
+    /// not overlap or either mate has a different number of bases and quality scores.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, matching::OverlapMatcher, merge::PairMerger};
+    ///
+    /// let merger = PairMerger::new(OverlapMatcher::new(0.1, 4));
+    ///
+    /// let r1 = Record::new("@r0/1", "ACGTACGTTT", "+", "IIIIIIIIII");
+    /// let r2 = Record::new("@r0/2", "GGGCAAACGT", "+", "IIIIIIIIII");
+    ///
+    /// let mut merged = Record::default();
+    /// assert_eq!(merger.merge(&r1, &r2, &mut merged), Some(6));
+    ///
+    /// assert_eq!(merged.name(), b"@r0");
+    /// assert_eq!(merged.sequence(), b"ACGTACGTTTGCCC");
+    /// ```
+    pub fn merge(&self, r1: &Record, r2: &Record, dst: &mut Record) -> Option<usize> {
+        if r1.sequence().len() != r1.quality_scores().len()
+            || r2.sequence().len() != r2.quality_scores().len()
+        {
+            return None;
+        }
+
+        let mut r2 = r2.clone();
+        r2.reverse_complement();
+
+        let m = self.matcher.find(r2.sequence(), r1.sequence())?;
+
+        let start = m.start();
+        let end = start + m.len();
+
+        dst.clear();
+
+        dst.name_mut().push(NAME_PREFIX);
+        dst.name_mut().extend_from_slice(name_id(r1.name()));
+
+        dst.sequence_mut()
+            .extend_from_slice(&r1.sequence()[..start]);
+        dst.quality_scores_mut()
+            .extend_from_slice(&r1.quality_scores()[..start]);
+
+        let overlap = r1.sequence()[start..end]
+            .iter()
+            .zip(&r1.quality_scores()[start..end])
+            .zip(r2.sequence().iter().zip(r2.quality_scores()));
+
+        for ((&a, &p), (&b, &q)) in overlap {
+            let (base, score) = consensus((a, p), (b, q));
+            dst.sequence_mut().push(base);
+            dst.quality_scores_mut().push(score);
+        }
+
+        if end < r1.sequence().len() {
+            dst.sequence_mut().extend_from_slice(&r1.sequence()[end..]);
+            dst.quality_scores_mut()
+                .extend_from_slice(&r1.quality_scores()[end..]);
+        } else {
+            dst.sequence_mut()
+                .extend_from_slice(&r2.sequence()[m.len()..]);
+            dst.quality_scores_mut()
+                .extend_from_slice(&r2.quality_scores()[m.len()..]);
+        }
+
+        dst.plus_line_mut().push(b'+');
+
+        Some(m.len())
+    }
+}
+
+fn consensus((a, p): (u8, u8), (b, q): (u8, u8)) -> (u8, u8) {
+    let p = p.saturating_sub(QUALITY_OFFSET);
+    let q = q.saturating_sub(QUALITY_OFFSET);
+
+    let (base, score) = if a.eq_ignore_ascii_case(&b) {
+        (a, p.saturating_add(q).min(MAX_QUALITY))
+    } else if p >= q {
+        (a, (p - q).max(MIN_QUALITY))
+    } else {
+        (b, (q - p).max(MIN_QUALITY))
+    };
+
+    (base, score + QUALITY_OFFSET)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let merger = PairMerger::new(OverlapMatcher::new(0.25, 4));
+
+        // The reverse complement of r2 is AAGTGCCC.
+        let r1 = Record::new("@r0/1 1:N:0", "ACGTAAGT", "+", "IIII##II");
+        let r2 = Record::new("@r0/2 2:N:0", "GGGCACTT", "+", "5555III5");
+
+        let mut merged = Record::default();
+        assert_eq!(merger.merge(&r1, &r2, &mut merged), Some(4));
+
+        assert_eq!(merged.name(), b"@r0");
+        assert_eq!(merged.sequence(), b"ACGTAAGTGCCC");
+        assert_eq!(merged.quality_scores(), b"IIII7JJJ5555");
+
+        // The reverse complement of r2 is AAATGCCC, which has a mismatch in the overlap.
+        let r1 = Record::new("@r0/1", "ACGTAAGT", "+", "IIII##5I");
+        let r2 = Record::new("@r0/2", "GGGCATTT", "+", "5555IIII");
+
+        assert_eq!(merger.merge(&r1, &r2, &mut merged), Some(4));
+        assert_eq!(merged.sequence(), b"ACGTAAATGCCC");
+        assert_eq!(merged.quality_scores(), b"IIIIJJ5J5555");
+
+        let r2 = Record::new("@r0/2", "TTTTTTTT", "+", "IIIIIIII");
+        assert_eq!(merger.merge(&r1, &r2, &mut merged), None);
+    }
+
+    #[test]
+    fn test_consensus() {
+        assert_eq!(consensus((b'A', b'5'), (b'A', b'5')), (b'A', b'I'));
+        assert_eq!(consensus((b'A', b'I'), (b'A', b'I')), (b'A', b'J'));
+        assert_eq!(consensus((b'A', b'I'), (b'C', b'5')), (b'A', b'5'));
+        assert_eq!(consensus((b'A', b'5'), (b'C', b'I')), (b'C', b'5'));
+        assert_eq!(consensus((b'A', b'I'), (b'C', b'I')), (b'A', b'#'));
+    }
+}
@@ -0,0 +1,293 @@
+//! Runtime throughput metrics.
+
+use std::{
+    io::{self, Read},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use tracing::info;
+
+/// Throughput counters shared across readers, writers, and commands.
+///
+/// `Metrics` is a cheap, cloneable handle. All clones share the same counters, so a caller can
+/// keep a handle to poll progress, e.g., from another thread, while work is in flight.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{fastq, metrics::Metrics};
+///
+/// let metrics = Metrics::new();
+///
+/// let mut reader = fastq::Reader::new(&b"@fqlib\nACGT\n+\nFQLB\n"[..]);
+/// reader.set_metrics(metrics.clone());
+///
+/// let mut record = fastq::Record::default();
+/// reader.read_record(&mut record)?;
+///
+/// assert_eq!(metrics.records_read(), 1);
+/// assert_eq!(metrics.bytes_read(), 19);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    start: Instant,
+    records_read: AtomicU64,
+    records_written: AtomicU64,
+    bytes_read: AtomicU64,
+    bytes_written: AtomicU64,
+    source_bytes_read: AtomicU64,
+    errors: AtomicU64,
+    stages: Mutex<Vec<(&'static str, Duration)>>,
+}
+
+impl Default for Inner {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            records_read: AtomicU64::new(0),
+            records_written: AtomicU64::new(0),
+            bytes_read: AtomicU64::new(0),
+            bytes_written: AtomicU64::new(0),
+            source_bytes_read: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            stages: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl Metrics {
+    /// Creates a set of metrics with all counters at zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn records_read(&self) -> u64 {
+        self.inner.records_read.load(Ordering::Relaxed)
+    }
+
+    pub fn add_records_read(&self, n: u64) {
+        self.inner.records_read.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn records_written(&self) -> u64 {
+        self.inner.records_written.load(Ordering::Relaxed)
+    }
+
+    pub fn add_records_written(&self, n: u64) {
+        self.inner.records_written.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn bytes_read(&self) -> u64 {
+        self.inner.bytes_read.load(Ordering::Relaxed)
+    }
+
+    pub fn add_bytes_read(&self, n: u64) {
+        self.inner.bytes_read.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.inner.bytes_written.load(Ordering::Relaxed)
+    }
+
+    pub fn add_bytes_written(&self, n: u64) {
+        self.inner.bytes_written.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Returns the number of bytes read from sources before decompression.
+    ///
+    /// Unlike [`bytes_read`], this is comparable to the size of a compressed file, e.g., to
+    /// estimate progress. Only sources read through a [`CountingReader`] are counted.
+    ///
+    /// [`bytes_read`]: #method.bytes_read
+    pub fn source_bytes_read(&self) -> u64 {
+        self.inner.source_bytes_read.load(Ordering::Relaxed)
+    }
+
+    pub fn add_source_bytes_read(&self, n: u64) {
+        self.inner.source_bytes_read.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn errors(&self) -> u64 {
+        self.inner.errors.load(Ordering::Relaxed)
+    }
+
+    pub fn add_errors(&self, n: u64) {
+        self.inner.errors.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Returns the time elapsed since the metrics were created.
+    pub fn elapsed(&self) -> Duration {
+        self.inner.start.elapsed()
+    }
+
+    /// Starts timing a named stage.
+    ///
+    /// The stage ends when the returned guard is dropped. Its duration is then logged and added to
+    /// the list of completed [`stages`].
+    ///
+    /// [`stages`]: #method.stages
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::metrics::Metrics;
+    ///
+    /// let metrics = Metrics::new();
+    ///
+    /// {
+    ///     let _stage = metrics.stage("validation");
+    /// }
+    ///
+    /// let stages = metrics.stages();
+    /// assert_eq!(stages.len(), 1);
+    /// assert_eq!(stages[0].0, "validation");
+    /// ```
+    pub fn stage(&self, name: &'static str) -> Stage {
+        Stage {
+            metrics: self.clone(),
+            name,
+            start: Instant::now(),
+        }
+    }
+
+    /// Returns the names and durations of completed stages, in order of completion.
+    pub fn stages(&self) -> Vec<(&'static str, Duration)> {
+        self.inner.stages.lock().unwrap().clone()
+    }
+
+    /// Logs all counters as structured fields.
+    pub fn log(&self) {
+        let elapsed = self.elapsed();
+        let secs = elapsed.as_secs_f64();
+
+        let records_per_sec = if secs > 0.0 {
+            (self.records_read() as f64 / secs) as u64
+        } else {
+            0
+        };
+
+        info!(
+            records_read = self.records_read(),
+            records_written = self.records_written(),
+            bytes_read = self.bytes_read(),
+            bytes_written = self.bytes_written(),
+            errors = self.errors(),
+            elapsed_ms = elapsed.as_millis() as u64,
+            records_per_sec = records_per_sec,
+            "metrics"
+        );
+    }
+}
+
+/// A reader that counts the bytes read from its inner reader as source bytes read.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Read;
+/// use fq::metrics::{CountingReader, Metrics};
+///
+/// let metrics = Metrics::new();
+/// let mut reader = CountingReader::new(&b"fqlib"[..], metrics.clone());
+///
+/// let mut buf = Vec::new();
+/// reader.read_to_end(&mut buf)?;
+///
+/// assert_eq!(metrics.source_bytes_read(), 5);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct CountingReader<R> {
+    inner: R,
+    metrics: Metrics,
+}
+
+impl<R> CountingReader<R> {
+    pub fn new(inner: R, metrics: Metrics) -> Self {
+        Self { inner, metrics }
+    }
+}
+
+impl<R> Read for CountingReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.metrics.add_source_bytes_read(n as u64);
+        Ok(n)
+    }
+}
+
+/// A timer for a named stage of work.
+///
+/// This is created by [`Metrics::stage`].
+pub struct Stage {
+    metrics: Metrics,
+    name: &'static str,
+    start: Instant,
+}
+
+impl Drop for Stage {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+
+        info!(
+            stage = self.name,
+            elapsed_ms = elapsed.as_millis() as u64,
+            "stage complete"
+        );
+
+        self.metrics
+            .inner
+            .stages
+            .lock()
+            .unwrap()
+            .push((self.name, elapsed));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters() {
+        let metrics = Metrics::new();
+        let handle = metrics.clone();
+
+        metrics.add_records_read(2);
+        metrics.add_records_written(1);
+        metrics.add_bytes_read(38);
+        metrics.add_bytes_written(19);
+        metrics.add_source_bytes_read(8);
+        metrics.add_errors(3);
+
+        assert_eq!(handle.records_read(), 2);
+        assert_eq!(handle.records_written(), 1);
+        assert_eq!(handle.bytes_read(), 38);
+        assert_eq!(handle.bytes_written(), 19);
+        assert_eq!(handle.source_bytes_read(), 8);
+        assert_eq!(handle.errors(), 3);
+    }
+
+    #[test]
+    fn test_stages() {
+        let metrics = Metrics::new();
+
+        drop(metrics.stage("pass 1"));
+        drop(metrics.stage("pass 2"));
+
+        let names: Vec<_> = metrics.stages().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["pass 1", "pass 2"]);
+    }
+}
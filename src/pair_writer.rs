@@ -1,17 +1,72 @@
-use std::io::{self, Write};
+use std::{
+    io::{self, Write},
+    ops::Range,
+    panic, thread,
+};
 
-use rand::Rng;
+use rand::{rngs::SmallRng, Rng};
 
 use super::{
-    fastq::{self, Record},
+    bam::{self, flags},
+    fastq::{self, name_id, Record},
+    generator::NameFormat,
     Generator,
 };
 
 static PLUS_LINE: &[u8] = b"+";
 
+// The number of pairs each thread generates at a time.
+const BATCH_LEN: u64 = 4096;
+
+// Where read 1 of each pair is written.
+enum Read1Destination<W: Write> {
+    Fastq(fastq::Writer<W>),
+    // An unaligned BAM. Records are tagged with the read group ID, if given, or otherwise the
+    // name of the sample of their pair.
+    Bam {
+        writer: bam::Writer<W>,
+        read_group_id: Option<Vec<u8>>,
+        record: bam::Record,
+    },
+}
+
+impl<W> Read1Destination<W>
+where
+    W: Write,
+{
+    #[cfg(test)]
+    fn get_ref(&self) -> &W {
+        match self {
+            Self::Fastq(writer) => writer.get_ref(),
+            Self::Bam { writer, .. } => writer.get_ref(),
+        }
+    }
+}
+
+// Where read 2 of each pair is written.
+enum Read2Destination<X: Write> {
+    Writer(fastq::Writer<X>),
+    // After read 1, to the read 1 writer.
+    Interleaved,
+    // Nowhere, i.e., single end.
+    None,
+}
+
+/// A writer of generated pairs.
+///
+/// A single end writer only writes read 1 of each pair, so its records are the same as the read 1
+/// records of a pair writer with the same generator. An interleaved writer writes both mates of
+/// each pair, read 1 first, to one writer.
+///
+/// A BAM writer writes unaligned records, i.e., uBAM, either single end or, with both mates of each
+/// pair, interleaved. The writer must be finished to write the end-of-file marker of the BAM.
+///
+/// With index writers, the index reads of each pair, i.e., the barcodes of its sample, are
+/// written in sync with the pair.
 pub struct PairWriter<W: Write, X: Write> {
-    writer_1: fastq::Writer<W>,
-    writer_2: fastq::Writer<X>,
+    writer_1: Read1Destination<W>,
+    writer_2: Read2Destination<X>,
+    index_writers: Vec<fastq::Writer<W>>,
 }
 
 impl<W, X> PairWriter<W, X>
@@ -20,30 +75,556 @@ where
     X: Write,
 {
     pub fn new(writer_1: fastq::Writer<W>, writer_2: fastq::Writer<X>) -> Self {
-        Self { writer_1, writer_2 }
+        Self {
+            writer_1: Read1Destination::Fastq(writer_1),
+            writer_2: Read2Destination::Writer(writer_2),
+            index_writers: Vec::new(),
+        }
+    }
+
+    /// Creates a writer that only writes read 1.
+    pub fn single_end(writer_1: fastq::Writer<W>) -> Self {
+        Self {
+            writer_1: Read1Destination::Fastq(writer_1),
+            writer_2: Read2Destination::None,
+            index_writers: Vec::new(),
+        }
+    }
+
+    /// Creates a writer that writes both mates of each pair to one writer.
+    pub fn interleaved(writer: fastq::Writer<W>) -> Self {
+        Self {
+            writer_1: Read1Destination::Fastq(writer),
+            writer_2: Read2Destination::Interleaved,
+            index_writers: Vec::new(),
+        }
+    }
+
+    /// Creates a writer that writes both mates of each pair to an unaligned BAM.
+    ///
+    /// Records are tagged with the given read group ID or, if none is given, the name of the
+    /// sample of their pair, if any. The BAM header must already be written.
+    pub fn bam(writer: bam::Writer<W>, read_group_id: Option<Vec<u8>>) -> Self {
+        Self {
+            writer_1: Read1Destination::Bam {
+                writer,
+                read_group_id,
+                record: bam::Record::default(),
+            },
+            writer_2: Read2Destination::Interleaved,
+            index_writers: Vec::new(),
+        }
+    }
+
+    /// Creates a writer that only writes read 1 to an unaligned BAM.
+    ///
+    /// See [`Self::bam`].
+    pub fn bam_single_end(writer: bam::Writer<W>, read_group_id: Option<Vec<u8>>) -> Self {
+        Self {
+            writer_2: Read2Destination::None,
+            ..Self::bam(writer, read_group_id)
+        }
+    }
+
+    /// Sets the writers of the index reads of each pair, e.g., I1 and I2.
+    pub fn set_index_writers(&mut self, index_writers: Vec<fastq::Writer<W>>) {
+        self.index_writers = index_writers;
     }
 
     pub fn write<R>(&mut self, mut generator: Generator<R>, record_count: u64) -> io::Result<()>
     where
         R: Rng,
     {
+        let mut pair = Pair::new(self.index_writers.len());
+        let name_format = generator.name_format();
+
+        for _ in 0..record_count {
+            pair.generate(&mut generator);
+            self.write_pair(name_format, &mut pair)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the records in the given range of a dataset seeded by `seed`.
+    ///
+    /// Each record is generated independently (see [`Generator::next_pair_at`]), so writing
+    /// consecutive ranges produces the same records as writing their union.
+    pub fn write_range(
+        &mut self,
+        mut generator: Generator<SmallRng>,
+        seed: u64,
+        record_indices: Range<u64>,
+    ) -> io::Result<()> {
+        let mut pair = Pair::new(self.index_writers.len());
+        let name_format = generator.name_format();
+
+        for i in record_indices {
+            pair.generate_at(&mut generator, seed, i);
+            self.write_pair(name_format, &mut pair)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the records in the given range of a dataset seeded by `seed`, generated by the given
+    /// number of threads.
+    ///
+    /// Records are generated in batches, which are split into contiguous parts that are generated
+    /// in parallel, each by a clone of the generator, and written in order. Since each record is
+    /// generated independently, the output is the same as [`Self::write_range`] regardless of the
+    /// number of threads.
+    pub fn write_range_parallel(
+        &mut self,
+        generator: Generator<SmallRng>,
+        seed: u64,
+        record_indices: Range<u64>,
+        threads: usize,
+    ) -> io::Result<()> {
+        if threads <= 1 {
+            return self.write_range(generator, seed, record_indices);
+        }
+
+        let index_count = self.index_writers.len();
+        let name_format = generator.name_format();
+
+        let mut generators = vec![generator; threads];
+        let batch_len = BATCH_LEN * threads as u64;
+
+        let mut start = record_indices.start;
+
+        while start < record_indices.end {
+            let end = (start + batch_len).min(record_indices.end);
+            let part_len = (end - start).div_ceil(threads as u64);
+
+            let parts: Vec<Vec<Pair>> = thread::scope(|scope| {
+                let handles: Vec<_> = generators
+                    .iter_mut()
+                    .enumerate()
+                    .map(|(i, generator)| {
+                        let part_start = (start + part_len * i as u64).min(end);
+                        let part_end = (part_start + part_len).min(end);
+
+                        scope.spawn(move || {
+                            (part_start..part_end)
+                                .map(|j| {
+                                    let mut pair = Pair::new(index_count);
+                                    pair.generate_at(generator, seed, j);
+                                    pair
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect();
+
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+                    .collect()
+            });
+
+            for mut pair in parts.into_iter().flatten() {
+                self.write_pair(name_format, &mut pair)?;
+            }
+
+            start = end;
+        }
+
+        Ok(())
+    }
+
+    // Writes a generated pair and its index reads with read number suffixes. Index reads have
+    // the suffixes of read 1 and read 2, respectively.
+    fn write_pair(&mut self, name_format: NameFormat, pair: &mut Pair) -> io::Result<()> {
+        let (r_suffix, s_suffix) = read_number_suffixes(name_format);
+
+        for ((writer, record), suffix) in self
+            .index_writers
+            .iter_mut()
+            .zip(pair.indexes.iter_mut())
+            .zip([r_suffix, s_suffix])
+        {
+            record.name_mut().extend_from_slice(suffix);
+            writer.write_record(record)?;
+        }
+
+        let writer_1 = match &mut self.writer_1 {
+            Read1Destination::Fastq(writer) => writer,
+            Read1Destination::Bam {
+                writer,
+                read_group_id,
+                record,
+            } => {
+                let read_group_id = read_group_id.as_deref().or(pair.sample_name.as_deref());
+
+                return match self.writer_2 {
+                    Read2Destination::None => {
+                        copy_bam_record(&pair.r, flags::UNMAPPED, read_group_id, record);
+                        writer.write_record(record)
+                    }
+                    _ => {
+                        const PAIRED_FLAGS: u16 =
+                            flags::SEGMENTED | flags::UNMAPPED | flags::MATE_UNMAPPED;
+
+                        let r_flags = PAIRED_FLAGS | flags::FIRST_SEGMENT;
+                        copy_bam_record(&pair.r, r_flags, read_group_id, record);
+                        writer.write_record(record)?;
+
+                        let s_flags = PAIRED_FLAGS | flags::LAST_SEGMENT;
+                        copy_bam_record(&pair.s, s_flags, read_group_id, record);
+                        writer.write_record(record)
+                    }
+                };
+            }
+        };
+
+        pair.r.name_mut().extend_from_slice(r_suffix);
+        pair.s.name_mut().extend_from_slice(s_suffix);
+
+        writer_1.write_record(&pair.r)?;
+
+        match &mut self.writer_2 {
+            Read2Destination::Writer(writer) => writer.write_record(&pair.s),
+            Read2Destination::Interleaved => writer_1.write_record(&pair.s),
+            Read2Destination::None => Ok(()),
+        }
+    }
+
+    /// Finishes the output, i.e., writes the end-of-file marker of a BAM.
+    pub fn finish(&mut self) -> io::Result<()> {
+        match &mut self.writer_1 {
+            Read1Destination::Fastq(_) => Ok(()),
+            Read1Destination::Bam { writer, .. } => writer.finish(),
+        }
+    }
+}
+
+// Copies a generated record to an unaligned BAM record. The name is the record ID, i.e., without
+// the `@` prefix.
+fn copy_bam_record(src: &Record, flags: u16, read_group_id: Option<&[u8]>, dst: &mut bam::Record) {
+    dst.clear();
+
+    dst.name_mut().extend_from_slice(name_id(src.name()));
+    *dst.flags_mut() = flags;
+    dst.sequence_mut().extend_from_slice(src.sequence());
+    dst.quality_scores_mut()
+        .extend_from_slice(src.quality_scores());
+    *dst.read_group_mut() = read_group_id.map(|id| id.to_vec());
+}
+
+// A generated pair, its index reads, and the name of its sample, if any.
+struct Pair {
+    r: Record,
+    s: Record,
+    indexes: Vec<Record>,
+    sample_name: Option<Vec<u8>>,
+}
+
+impl Pair {
+    fn new(index_count: usize) -> Self {
+        let mut record = Record::default();
+        record.plus_line_mut().extend_from_slice(PLUS_LINE);
+
+        Self {
+            r: record.clone(),
+            s: record.clone(),
+            indexes: vec![record; index_count],
+            sample_name: None,
+        }
+    }
+
+    fn generate<R>(&mut self, generator: &mut Generator<R>)
+    where
+        R: Rng,
+    {
+        generator.next_pair(&mut self.r, &mut self.s);
+        self.generate_index_reads(generator);
+        self.set_sample_name(generator);
+    }
+
+    fn generate_at(&mut self, generator: &mut Generator<SmallRng>, seed: u64, record_index: u64) {
+        generator.next_pair_at(seed, record_index, &mut self.r, &mut self.s);
+        self.generate_index_reads(generator);
+        self.set_sample_name(generator);
+    }
+
+    fn generate_index_reads<R>(&mut self, generator: &mut Generator<R>)
+    where
+        R: Rng,
+    {
+        if !self.indexes.is_empty() {
+            generator.next_index_reads(self.r.name(), &mut self.indexes);
+        }
+    }
+
+    fn set_sample_name<R>(&mut self, generator: &Generator<R>)
+    where
+        R: Rng,
+    {
+        self.sample_name = generator
+            .sample()
+            .map(|sample| sample.name().as_bytes().to_vec());
+    }
+}
+
+fn read_number_suffixes(name_format: NameFormat) -> (&'static [u8], &'static [u8]) {
+    match name_format {
+        NameFormat::Random => (b"/1", b"/2"),
+        NameFormat::Illumina => (b" 1:N:0:1", b" 2:N:0:1"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::generator::Builder;
+
+    fn write_range(record_indices: Range<u64>) -> io::Result<(Vec<u8>, Vec<u8>)> {
+        write_range_with_duplicate_rate(record_indices, 0.0)
+    }
+
+    fn write_range_with_duplicate_rate(
+        record_indices: Range<u64>,
+        duplicate_rate: f64,
+    ) -> io::Result<(Vec<u8>, Vec<u8>)> {
+        let mut writer = PairWriter::new(
+            fastq::Writer::new(Vec::new()),
+            fastq::Writer::new(Vec::new()),
+        );
+
+        let rng = SmallRng::seed_from_u64(13);
+        let generator = Builder::from_rng(rng)
+            .set_read_length(4)
+            .set_duplicate_rate(duplicate_rate)
+            .build();
+        writer.write_range(generator, 13, record_indices)?;
+
+        let r2 = match &writer.writer_2 {
+            Read2Destination::Writer(writer) => writer.get_ref().clone(),
+            _ => unreachable!(),
+        };
+
+        Ok((writer.writer_1.get_ref().clone(), r2))
+    }
+
+    fn write_range_to(mut writer: PairWriter<Vec<u8>, Vec<u8>>) -> io::Result<Vec<u8>> {
+        let rng = SmallRng::seed_from_u64(13);
+        let generator = Builder::from_rng(rng).set_read_length(4).build();
+        writer.write_range(generator, 13, 0..5)?;
+        Ok(writer.writer_1.get_ref().clone())
+    }
+
+    #[test]
+    fn test_write_range() -> io::Result<()> {
+        let (r1, r2) = write_range(0..5)?;
+
+        let (mut a1, mut a2) = write_range(0..2)?;
+        let (b1, b2) = write_range(2..5)?;
+        a1.extend(b1);
+        a2.extend(b2);
+
+        assert_eq!(a1, r1);
+        assert_eq!(a2, r2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_range_with_duplicate_rate() -> io::Result<()> {
+        let (r1, r2) = write_range_with_duplicate_rate(0..64, 0.5)?;
+
+        let (mut a1, mut a2) = write_range_with_duplicate_rate(0..21, 0.5)?;
+        let (b1, b2) = write_range_with_duplicate_rate(21..64, 0.5)?;
+        a1.extend(b1);
+        a2.extend(b2);
+
+        assert_eq!(a1, r1);
+        assert_eq!(a2, r2);
+
+        // Read 1 sequences are the second line of each record.
+        let sequences: Vec<_> = r1.split(|&b| b == b'\n').skip(1).step_by(4).collect();
+        let unique_sequences: std::collections::HashSet<_> = sequences.iter().collect();
+        assert!(unique_sequences.len() < sequences.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_range_parallel() -> io::Result<()> {
+        let record_count = BATCH_LEN * 2 + 5;
+        let (r1, r2) = write_range_with_duplicate_rate(0..record_count, 0.5)?;
+
+        let mut writer = PairWriter::new(
+            fastq::Writer::new(Vec::new()),
+            fastq::Writer::new(Vec::new()),
+        );
+
+        let rng = SmallRng::seed_from_u64(13);
+        let generator = Builder::from_rng(rng)
+            .set_read_length(4)
+            .set_duplicate_rate(0.5)
+            .build();
+        writer.write_range_parallel(generator, 13, 0..record_count, 3)?;
+
+        assert_eq!(writer.writer_1.get_ref(), &r1);
+
+        match &writer.writer_2 {
+            Read2Destination::Writer(writer) => assert_eq!(writer.get_ref(), &r2),
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_single_end() -> io::Result<()> {
+        let (r1, _) = write_range(0..5)?;
+
+        let writer = PairWriter::single_end(fastq::Writer::new(Vec::new()));
+        assert_eq!(write_range_to(writer)?, r1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_interleaved() -> io::Result<()> {
+        let (r1, r2) = write_range(0..5)?;
+
+        let r1_lines: Vec<_> = r1.split_inclusive(|&b| b == b'\n').collect();
+        let r2_lines: Vec<_> = r2.split_inclusive(|&b| b == b'\n').collect();
+
+        let expected: Vec<u8> = r1_lines
+            .chunks(4)
+            .zip(r2_lines.chunks(4))
+            .flat_map(|(r, s)| r.iter().chain(s))
+            .flat_map(|line| line.iter().copied())
+            .collect();
+
+        let writer = PairWriter::interleaved(fastq::Writer::new(Vec::new()));
+        assert_eq!(write_range_to(writer)?, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_with_illumina_names() -> io::Result<()> {
+        use crate::validators::single::{NameFormat, NameFormatValidator, SingleReadValidator};
+
+        let mut writer: PairWriter<Vec<u8>, Vec<u8>> =
+            PairWriter::interleaved(fastq::Writer::new(Vec::new()));
+
+        let generator = Builder::default()
+            .set_instrument("EAS139")
+            .set_run_number(136)
+            .set_flow_cell_id("FC706VJ")
+            .set_name_format(crate::generator::NameFormat::Illumina)
+            .set_read_length(4)
+            .build();
+
+        writer.write(generator, 2)?;
+
+        let mut reader = fastq::Reader::new(&writer.writer_1.get_ref()[..]);
+        let mut record = Record::default();
+        let validator = NameFormatValidator::new(NameFormat::Casava);
+
+        for suffix in [b" 1:N:0:1", b" 2:N:0:1", b" 1:N:0:1", b" 2:N:0:1"] {
+            reader.read_record(&mut record)?;
+            assert!(record.name().starts_with(b"@EAS139:136:FC706VJ:1:1101:"));
+            assert!(record.name().ends_with(suffix));
+            assert!(validator.validate(&record).is_ok());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_with_index_writers() -> io::Result<()> {
+        use crate::demux::Sample;
+
+        let mut writer = PairWriter::new(
+            fastq::Writer::new(Vec::new()),
+            fastq::Writer::new(Vec::new()),
+        );
+
+        writer.set_index_writers(vec![
+            fastq::Writer::new(Vec::new()),
+            fastq::Writer::new(Vec::new()),
+        ]);
+
+        let generator = Builder::default()
+            .set_read_length(4)
+            .set_samples(vec![Sample::new(
+                "s1",
+                vec![b"ACGT".to_vec(), b"TTTT".to_vec()],
+            )])
+            .build();
+
+        writer.write(generator, 2)?;
+
+        let mut r1_reader = fastq::Reader::new(&writer.writer_1.get_ref()[..]);
+        let mut i1_reader = fastq::Reader::new(&writer.index_writers[0].get_ref()[..]);
+        let mut i2_reader = fastq::Reader::new(&writer.index_writers[1].get_ref()[..]);
+
         let mut r = Record::default();
-        let mut s = Record::default();
+        let mut i1 = Record::default();
+        let mut i2 = Record::default();
 
-        r.plus_line_mut().extend_from_slice(PLUS_LINE);
-        s.plus_line_mut().extend_from_slice(PLUS_LINE);
+        for _ in 0..2 {
+            r1_reader.read_record(&mut r)?;
+            i1_reader.read_record(&mut i1)?;
+            i2_reader.read_record(&mut i2)?;
 
-        for _ in 0..record_count {
-            generator.next_record(&mut r);
-            generator.next_record_with_name(r.name(), &mut s);
+            let name = &r.name()[..r.name().len() - 2];
+
+            assert_eq!(i1.name(), r.name());
+            assert_eq!(&i2.name()[..name.len()], name);
+            assert!(i2.name().ends_with(b"/2"));
+            assert_eq!(i1.sequence(), b"ACGT");
+            assert_eq!(i2.sequence(), b"TTTT");
+            assert_eq!(i1.quality_scores().len(), 4);
+        }
 
-            r.name_mut().extend_from_slice(b"/1");
-            s.name_mut().extend_from_slice(b"/2");
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_bam() -> io::Result<()> {
+        use flate2::read::MultiGzDecoder;
+
+        use crate::demux::Sample;
+
+        let mut bam_writer = bam::Writer::new(Vec::new());
+        bam_writer.write_header("@HD\tVN:1.6\n@RG\tID:s1\n")?;
+
+        let mut writer: PairWriter<Vec<u8>, Vec<u8>> = PairWriter::bam(bam_writer, None);
 
-            self.writer_1.write_record(&r)?;
-            self.writer_2.write_record(&s)?;
+        let generator = Builder::default()
+            .set_read_length(4)
+            .set_samples(vec![Sample::new("s1", vec![b"ACGT".to_vec()])])
+            .build();
+
+        writer.write(generator, 2)?;
+        writer.finish()?;
+
+        let data = writer.writer_1.get_ref();
+        let mut reader = bam::Reader::new(MultiGzDecoder::new(&data[..]));
+        reader.read_header()?;
+
+        let mut record = bam::Record::default();
+
+        for is_read_1 in [true, false, true, false] {
+            reader.read_record(&mut record)?;
+            assert!(!record.name().starts_with(b"@"));
+            assert_eq!(record.is_read_1(), is_read_1);
+            assert_eq!(record.is_read_2(), !is_read_1);
+            assert_eq!(record.sequence().len(), 4);
+            assert_eq!(record.read_group(), Some(&b"s1"[..]));
         }
 
+        assert_eq!(reader.read_record(&mut record)?, 0);
+
         Ok(())
     }
 }
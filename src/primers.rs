@@ -0,0 +1,360 @@
+//! Amplicon primer schemes and primer trimming.
+
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    io::{self, BufRead},
+};
+
+use crate::{
+    fasta,
+    fastq::Record,
+    sequence::{is_compatible, reverse_complement},
+    trim::{trim_end, trim_start},
+};
+
+/// A primer oligo, written 5' to 3'.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Primer {
+    name: String,
+    sequence: Vec<u8>,
+}
+
+impl Primer {
+    pub fn new<S, T>(name: S, sequence: T) -> Self
+    where
+        S: Into<String>,
+        T: Into<Vec<u8>>,
+    {
+        Self {
+            name: name.into(),
+            sequence: sequence.into(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn sequence(&self) -> &[u8] {
+        &self.sequence
+    }
+}
+
+/// Reads primers from a FASTA, one primer per record.
+pub fn read_fasta_primers<R>(mut reader: fasta::Reader<R>) -> io::Result<Vec<Primer>>
+where
+    R: BufRead,
+{
+    let mut primers = Vec::new();
+    let mut record = fasta::Record::default();
+
+    while reader.read_record(&mut record)? != 0 {
+        let name = String::from_utf8_lossy(record.id()).into_owned();
+        primers.push(Primer::new(name, record.sequence().to_ascii_uppercase()));
+    }
+
+    Ok(primers)
+}
+
+/// Reads all sequences in a FASTA into a map of reference sequences by ID.
+pub fn read_reference<R>(mut reader: fasta::Reader<R>) -> io::Result<HashMap<Vec<u8>, Vec<u8>>>
+where
+    R: BufRead,
+{
+    let mut reference = HashMap::new();
+    let mut record = fasta::Record::default();
+
+    while reader.read_record(&mut record)? != 0 {
+        reference.insert(record.id().to_vec(), record.sequence().to_ascii_uppercase());
+    }
+
+    Ok(reference)
+}
+
+/// Reads primers from a BED scheme, taking their sequences from a reference.
+///
+/// Each line is expected to have at least the chromosome, start, end, and name columns. The
+/// primer strand is read from the sixth column, if present; otherwise, primers with names
+/// containing "RIGHT" are taken to be on the reverse strand, as in ARTIC schemes. Primers on the
+/// reverse strand are reverse complemented.
+pub fn read_bed_scheme<R>(
+    reader: R,
+    reference: &HashMap<Vec<u8>, Vec<u8>>,
+) -> io::Result<Vec<Primer>>
+where
+    R: BufRead,
+{
+    let mut primers = Vec::new();
+
+    for result in reader.lines() {
+        let line = result?;
+
+        if line.is_empty()
+            || line.starts_with('#')
+            || line.starts_with("track")
+            || line.starts_with("browser")
+        {
+            continue;
+        }
+
+        primers.push(parse_bed_line(&line, reference)?);
+    }
+
+    Ok(primers)
+}
+
+fn parse_bed_line(line: &str, reference: &HashMap<Vec<u8>, Vec<u8>>) -> io::Result<Primer> {
+    let invalid_data = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let fields: Vec<&str> = line.split('\t').collect();
+
+    if fields.len() < 4 {
+        return Err(invalid_data(format!(
+            "invalid BED line: expected at least 4 fields, got {}",
+            fields.len()
+        )));
+    }
+
+    let chrom = fields[0];
+
+    let start: usize = fields[1]
+        .parse()
+        .map_err(|_| invalid_data(format!("invalid BED start: {}", fields[1])))?;
+
+    let end: usize = fields[2]
+        .parse()
+        .map_err(|_| invalid_data(format!("invalid BED end: {}", fields[2])))?;
+
+    let name = fields[3];
+
+    let is_reverse = match fields.get(5) {
+        Some(&"-") => true,
+        Some(&"+") => false,
+        _ => name.contains("RIGHT"),
+    };
+
+    let reference_sequence = reference
+        .get(chrom.as_bytes())
+        .ok_or_else(|| invalid_data(format!("missing reference sequence: {}", chrom)))?;
+
+    if start >= end || end > reference_sequence.len() {
+        return Err(invalid_data(format!(
+            "invalid BED interval for {}: {}:{}-{}",
+            name, chrom, start, end
+        )));
+    }
+
+    let mut sequence = reference_sequence[start..end].to_vec();
+
+    if is_reverse {
+        reverse_complement(&mut sequence);
+    }
+
+    Ok(Primer::new(name, sequence))
+}
+
+/// A trimmer that removes primer sequences from the ends of reads.
+///
+/// Reads are expected to start with a primer oligo. If a read spans its entire amplicon, it also
+/// ends with the reverse complement of the opposite primer.
+pub struct Trimmer {
+    primers: Vec<Vec<u8>>,
+    reverse_complement_primers: Vec<Vec<u8>>,
+    max_mismatches: usize,
+}
+
+impl Trimmer {
+    pub fn new(primers: &[Primer], max_mismatches: usize) -> Self {
+        let mut primers: Vec<Vec<u8>> = primers.iter().map(|p| p.sequence().to_vec()).collect();
+
+        // Prefer the longest match.
+        primers.sort_by_key(|p| Reverse(p.len()));
+
+        let reverse_complement_primers = primers
+            .iter()
+            .map(|p| {
+                let mut sequence = p.clone();
+                reverse_complement(&mut sequence);
+                sequence
+            })
+            .collect();
+
+        Self {
+            primers,
+            reverse_complement_primers,
+            max_mismatches,
+        }
+    }
+
+    /// Trims primers from the start and end of a record.
+    ///
+    /// This returns the number of bases trimmed from the start and from the end, respectively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, primers::{Primer, Trimmer}};
+    ///
+    /// let primers = [Primer::new("p1_LEFT", "ACCA"), Primer::new("p1_RIGHT", "GGTT")];
+    /// let trimmer = Trimmer::new(&primers, 0);
+    ///
+    /// let mut record = Record::new("@fqlib", "ACCAGTAACC", "+", "ABCDEFGHIJ");
+    /// assert_eq!(trimmer.trim(&mut record), (4, 4));
+    /// assert_eq!(record.sequence(), b"GT");
+    /// assert_eq!(record.quality_scores(), b"EF");
+    /// ```
+    pub fn trim(&self, record: &mut Record) -> (usize, usize) {
+        let start = self
+            .primers
+            .iter()
+            .find(|p| self.is_match(p, record.sequence()))
+            .map(|p| p.len())
+            .unwrap_or(0);
+
+        trim_start(record, start);
+
+        let end = self
+            .reverse_complement_primers
+            .iter()
+            .find(|p| {
+                let sequence = record.sequence();
+                p.len() <= sequence.len() && self.is_match(p, &sequence[sequence.len() - p.len()..])
+            })
+            .map(|p| p.len())
+            .unwrap_or(0);
+
+        trim_end(record, end);
+
+        (start, end)
+    }
+
+    // Returns whether the primer matches the start of the sequence within the mismatch budget.
+    fn is_match(&self, primer: &[u8], sequence: &[u8]) -> bool {
+        if primer.is_empty() || primer.len() > sequence.len() {
+            return false;
+        }
+
+        let mut mismatches = 0;
+
+        for (&a, &b) in primer.iter().zip(sequence) {
+            if !is_compatible(a, b) {
+                mismatches += 1;
+
+                if mismatches > self.max_mismatches {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_reference() -> HashMap<Vec<u8>, Vec<u8>> {
+        [(b"sq0".to_vec(), b"AACCGGTTACGTACGTTTGGCCAA".to_vec())]
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn test_read_fasta_primers() -> io::Result<()> {
+        let data = b">p1_LEFT\nacca\n>p1_RIGHT pool=1\nGGTT\n";
+        let reader = fasta::Reader::new(&data[..]);
+
+        let primers = read_fasta_primers(reader)?;
+
+        assert_eq!(
+            primers,
+            [
+                Primer::new("p1_LEFT", "ACCA"),
+                Primer::new("p1_RIGHT", "GGTT")
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_bed_scheme() -> io::Result<()> {
+        let reference = build_reference();
+
+        let data = "\
+# scheme
+sq0\t0\t6\tp1_LEFT\t1\t+
+sq0\t18\t24\tp1_RIGHT\t1\t-
+sq0\t8\t12\tp2_RIGHT\t2
+";
+
+        let primers = read_bed_scheme(data.as_bytes(), &reference)?;
+
+        assert_eq!(
+            primers,
+            [
+                Primer::new("p1_LEFT", "AACCGG"),
+                Primer::new("p1_RIGHT", "TTGGCC"),
+                Primer::new("p2_RIGHT", "ACGT"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_bed_scheme_with_invalid_lines() {
+        let reference = build_reference();
+
+        let data = "sq0\t0\t6\n";
+        assert!(read_bed_scheme(data.as_bytes(), &reference).is_err());
+
+        let data = "sq1\t0\t6\tp1_LEFT\n";
+        assert!(read_bed_scheme(data.as_bytes(), &reference).is_err());
+
+        let data = "sq0\t0\t32\tp1_LEFT\n";
+        assert!(read_bed_scheme(data.as_bytes(), &reference).is_err());
+
+        let data = "sq0\tstart\t6\tp1_LEFT\n";
+        assert!(read_bed_scheme(data.as_bytes(), &reference).is_err());
+    }
+
+    #[test]
+    fn test_trim() {
+        let primers = [
+            Primer::new("p1_LEFT", "AACCGG"),
+            Primer::new("p1_RIGHT", "TTGGCC"),
+        ];
+
+        let trimmer = Trimmer::new(&primers, 0);
+
+        let mut record = Record::new("@fqlib", "AACCGGTTACGT", "+", "ABCDEFGHIJKL");
+        assert_eq!(trimmer.trim(&mut record), (6, 0));
+        assert_eq!(record.sequence(), b"TTACGT");
+        assert_eq!(record.quality_scores(), b"GHIJKL");
+
+        let mut record = Record::new("@fqlib", "TTGGCCACGTCCGGTT", "+", "ABCDEFGHIJKLMNOP");
+        assert_eq!(trimmer.trim(&mut record), (6, 6));
+        assert_eq!(record.sequence(), b"ACGT");
+        assert_eq!(record.quality_scores(), b"GHIJ");
+
+        let mut record = Record::new("@fqlib", "ATCCGGTTACGT", "+", "ABCDEFGHIJKL");
+        assert_eq!(trimmer.trim(&mut record), (0, 0));
+
+        let trimmer = Trimmer::new(&primers, 1);
+        assert_eq!(trimmer.trim(&mut record), (6, 0));
+        assert_eq!(record.sequence(), b"TTACGT");
+    }
+
+    #[test]
+    fn test_trim_with_degenerate_primer() {
+        let primers = [Primer::new("p1_LEFT", "AAYCGG")];
+        let trimmer = Trimmer::new(&primers, 0);
+
+        let mut record = Record::new("@fqlib", "AATCGGTTACGT", "+", "ABCDEFGHIJKL");
+        assert_eq!(trimmer.trim(&mut record), (6, 0));
+    }
+}
@@ -0,0 +1,288 @@
+//! Quality score encodings.
+
+use std::{error, fmt, str::FromStr};
+
+const MAX_SCORE: u8 = b'~';
+
+// Illumina 1.8+ (Phred+33) scores range from Q0 (`!`) to Q41 (`J`), Phred+64 scores start at
+// Q0 (`@`), and Solexa+64 scores start at Q-5 (`;`).
+const PHRED33_MAX_SCORE: u8 = b'J';
+const PHRED64_MIN_SCORE: u8 = b'@';
+const SOLEXA64_MIN_SCORE: u8 = b';';
+
+/// A quality score encoding, i.e., the ASCII offset of a Phred quality score.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Encoding {
+    /// Phred+33, e.g., Sanger and Illumina 1.8+.
+    Phred33,
+    /// Phred+64, e.g., Illumina 1.3 and 1.5.
+    Phred64,
+    /// Solexa+64, e.g., Solexa and Illumina 1.0. Solexa scores are log-odds rather than Phred
+    /// scores and start at -5.
+    Solexa64,
+}
+
+impl Encoding {
+    /// Returns the ASCII offset of the encoding.
+    pub const fn offset(&self) -> u8 {
+        match self {
+            Self::Phred33 => 33,
+            Self::Phred64 | Self::Solexa64 => 64,
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Phred33 => f.write_str("phred33"),
+            Self::Phred64 => f.write_str("phred64"),
+            Self::Solexa64 => f.write_str("solexa64"),
+        }
+    }
+}
+
+/// An error returned when a quality score encoding fails to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseEncodingError(String);
+
+impl error::Error for ParseEncodingError {}
+
+impl fmt::Display for ParseEncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid quality encoding: expected phred33, phred64, or solexa64, got '{}'",
+            self.0
+        )
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = ParseEncodingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "phred33" => Ok(Self::Phred33),
+            "phred64" => Ok(Self::Phred64),
+            "solexa64" => Ok(Self::Solexa64),
+            _ => Err(ParseEncodingError(s.into())),
+        }
+    }
+}
+
+/// A quality score encoding detector.
+///
+/// This tracks the range of observed quality score characters.
+#[derive(Clone, Debug, Default)]
+pub struct Detector {
+    range: Option<(u8, u8)>,
+}
+
+impl Detector {
+    /// Adds the quality scores of a record.
+    pub fn add(&mut self, quality_scores: &[u8]) {
+        for &score in quality_scores {
+            self.range = match self.range {
+                Some((min, max)) => Some((min.min(score), max.max(score))),
+                None => Some((score, score)),
+            };
+        }
+    }
+
+    /// Returns the detected encoding.
+    ///
+    /// Scores below `;` are only valid in Phred+33, and scores above `J` are only expected in
+    /// Phred+64 and Solexa+64. Scores from `;` to `?` are negative in Solexa+64, so they are
+    /// Phred+33 unless higher scores are also observed, in which case the range fits both Phred+33
+    /// with high scores and Solexa+64. Solexa+64 is therefore never detected. This returns `None`
+    /// if no scores were added or if the observed range fits more than one encoding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::quality::{Detector, Encoding};
+    ///
+    /// let mut detector = Detector::default();
+    /// detector.add(b"BBBBhhhh");
+    /// assert_eq!(detector.encoding(), Some(Encoding::Phred64));
+    ///
+    /// detector.add(b"#");
+    /// assert_eq!(detector.encoding(), Some(Encoding::Phred33));
+    /// ```
+    pub fn encoding(&self) -> Option<Encoding> {
+        let (min, max) = self.range?;
+
+        if min < SOLEXA64_MIN_SCORE {
+            Some(Encoding::Phred33)
+        } else if min < PHRED64_MIN_SCORE {
+            if max > PHRED33_MAX_SCORE {
+                None
+            } else {
+                Some(Encoding::Phred33)
+            }
+        } else if max > PHRED33_MAX_SCORE {
+            Some(Encoding::Phred64)
+        } else {
+            None
+        }
+    }
+}
+
+/// An error returned when a quality score cannot be reencoded.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RecodeError {
+    /// The score is below the offset of the source encoding.
+    InvalidScore(u8, Encoding),
+    /// The score cannot be represented in the destination encoding.
+    OutOfRange(u8, Encoding),
+    /// The encoding is not supported as a destination.
+    UnsupportedEncoding(Encoding),
+}
+
+impl error::Error for RecodeError {}
+
+impl fmt::Display for RecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidScore(score, encoding) => write!(
+                f,
+                "invalid {} quality score: '{}'",
+                encoding,
+                char::from(*score)
+            ),
+            Self::OutOfRange(score, encoding) => write!(
+                f,
+                "quality score '{}' is out of range for {}",
+                char::from(*score),
+                encoding
+            ),
+            Self::UnsupportedEncoding(encoding) => {
+                write!(f, "unsupported destination encoding: {}", encoding)
+            }
+        }
+    }
+}
+
+/// Reencodes quality scores in place.
+///
+/// Solexa+64 scores are converted to the nearest Phred score. Solexa+64 is not supported as a
+/// destination encoding.
+///
+/// # Examples
+///
+/// ```
+/// use fq::quality::{recode, Encoding};
+///
+/// let mut quality_scores = b"BFhh".to_vec();
+/// recode(&mut quality_scores, Encoding::Phred64, Encoding::Phred33)?;
+/// assert_eq!(quality_scores, b"#'II");
+/// # Ok::<(), fq::quality::RecodeError>(())
+/// ```
+pub fn recode(quality_scores: &mut [u8], src: Encoding, dst: Encoding) -> Result<(), RecodeError> {
+    if src == dst {
+        return Ok(());
+    } else if dst == Encoding::Solexa64 {
+        return Err(RecodeError::UnsupportedEncoding(dst));
+    }
+
+    let max_score = MAX_SCORE - dst.offset();
+
+    for score in quality_scores.iter_mut() {
+        let q = if src == Encoding::Solexa64 {
+            if *score < SOLEXA64_MIN_SCORE {
+                return Err(RecodeError::InvalidScore(*score, src));
+            }
+
+            solexa_to_phred(i32::from(*score) - i32::from(src.offset()))
+        } else {
+            score
+                .checked_sub(src.offset())
+                .ok_or(RecodeError::InvalidScore(*score, src))?
+        };
+
+        if q > max_score {
+            return Err(RecodeError::OutOfRange(*score, dst));
+        }
+
+        *score = q + dst.offset();
+    }
+
+    Ok(())
+}
+
+// Converts a Solexa score, Q = -10 log10(p / (1 - p)), to the nearest Phred score,
+// Q = -10 log10(p).
+fn solexa_to_phred(q: i32) -> u8 {
+    let q = 10.0 * (10f64.powf(f64::from(q) / 10.0) + 1.0).log10();
+    q.round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("phred33".parse(), Ok(Encoding::Phred33));
+        assert_eq!("phred64".parse(), Ok(Encoding::Phred64));
+        assert_eq!("solexa64".parse(), Ok(Encoding::Solexa64));
+        assert_eq!(
+            "solexa".parse::<Encoding>(),
+            Err(ParseEncodingError(String::from("solexa")))
+        );
+    }
+
+    #[test]
+    fn test_encoding() {
+        let mut detector = Detector::default();
+        assert_eq!(detector.encoding(), None);
+
+        detector.add(b"FFFFJJJJ");
+        assert_eq!(detector.encoding(), None);
+
+        detector.add(b"FFFF!!!!");
+        assert_eq!(detector.encoding(), Some(Encoding::Phred33));
+
+        let mut detector = Detector::default();
+        detector.add(b"@@@@hhhh");
+        assert_eq!(detector.encoding(), Some(Encoding::Phred64));
+
+        let mut detector = Detector::default();
+        detector.add(b";;??");
+        assert_eq!(detector.encoding(), Some(Encoding::Phred33));
+        detector.add(b"KKKK");
+        assert_eq!(detector.encoding(), None);
+    }
+
+    #[test]
+    fn test_recode() {
+        let mut quality_scores = b"!+5?I".to_vec();
+        assert!(recode(&mut quality_scores, Encoding::Phred33, Encoding::Phred64).is_ok());
+        assert_eq!(quality_scores, b"@JT^h");
+        assert!(recode(&mut quality_scores, Encoding::Phred64, Encoding::Phred33).is_ok());
+        assert_eq!(quality_scores, b"!+5?I");
+
+        let mut quality_scores = b"I#".to_vec();
+        assert_eq!(
+            recode(&mut quality_scores, Encoding::Phred64, Encoding::Phred33),
+            Err(RecodeError::InvalidScore(b'#', Encoding::Phred64))
+        );
+
+        let mut quality_scores = b"~".to_vec();
+        assert_eq!(
+            recode(&mut quality_scores, Encoding::Phred33, Encoding::Phred64),
+            Err(RecodeError::OutOfRange(b'~', Encoding::Phred64))
+        );
+
+        let mut quality_scores = b";@Jh".to_vec();
+        assert!(recode(&mut quality_scores, Encoding::Solexa64, Encoding::Phred33).is_ok());
+        assert_eq!(quality_scores, b"\"$+I");
+
+        let mut quality_scores = b"I".to_vec();
+        assert_eq!(
+            recode(&mut quality_scores, Encoding::Phred33, Encoding::Solexa64),
+            Err(RecodeError::UnsupportedEncoding(Encoding::Solexa64))
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Record renaming.
+
+use std::{error, fmt, str::FromStr};
+
+use crate::fastq::{
+    name::{id_range, name_id},
+    Record,
+};
+
+const INDEX_PLACEHOLDER: &str = "index";
+const NAME_PLACEHOLDER: &str = "name";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Segment {
+    Literal(Vec<u8>),
+    Index,
+    Name,
+}
+
+/// A record name template.
+///
+/// A template is literal text with placeholders: `{index}` is replaced by the index of the record
+/// (or pair), and `{name}` is replaced by the original read name, excluding the pair suffix and
+/// comment.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+/// An error returned when a name template fails to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseTemplateError {
+    /// The template is empty.
+    Empty,
+    /// The template has whitespace, which would start the name comment.
+    InvalidCharacter(char),
+    /// A placeholder is not closed.
+    UnclosedPlaceholder,
+    /// A placeholder is not `{index}` or `{name}`.
+    InvalidPlaceholder(String),
+}
+
+impl error::Error for ParseTemplateError {}
+
+impl fmt::Display for ParseTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("empty template"),
+            Self::InvalidCharacter(c) => write!(f, "invalid template character: {:?}", c),
+            Self::UnclosedPlaceholder => f.write_str("unclosed template placeholder"),
+            Self::InvalidPlaceholder(s) => write!(
+                f,
+                "invalid template placeholder: expected {{index}} or {{name}}, got '{{{}}}'",
+                s
+            ),
+        }
+    }
+}
+
+impl FromStr for Template {
+    type Err = ParseTemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseTemplateError::Empty);
+        }
+
+        if let Some(c) = s.chars().find(|c| c.is_whitespace()) {
+            return Err(ParseTemplateError::InvalidCharacter(c));
+        }
+
+        let mut segments = Vec::new();
+        let mut rest = s;
+
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(Segment::Literal(rest[..start].into()));
+            }
+
+            let end = rest[start..]
+                .find('}')
+                .map(|i| start + i)
+                .ok_or(ParseTemplateError::UnclosedPlaceholder)?;
+
+            let segment = match &rest[start + 1..end] {
+                INDEX_PLACEHOLDER => Segment::Index,
+                NAME_PLACEHOLDER => Segment::Name,
+                placeholder => {
+                    return Err(ParseTemplateError::InvalidPlaceholder(placeholder.into()))
+                }
+            };
+
+            segments.push(segment);
+            rest = &rest[end + 1..];
+        }
+
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.into()));
+        }
+
+        Ok(Self { segments })
+    }
+}
+
+impl Template {
+    /// Renames a template of records, i.e., one record for single end reads or both mates for
+    /// paired end reads.
+    ///
+    /// The new name is built from the name of the first record and applied to all records. Pair
+    /// suffixes (`/1`, `/2`) and comments are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, rename::Template};
+    ///
+    /// let template: Template = "sample_{index}".parse()?;
+    ///
+    /// let mut records = [
+    ///     Record::new("@r0/1", "ACGT", "+", "IIII"),
+    ///     Record::new("@r0/2", "TTTT", "+", "IIII"),
+    /// ];
+    ///
+    /// template.rename(8, &mut records);
+    ///
+    /// assert_eq!(records[0].name(), b"@sample_8/1");
+    /// assert_eq!(records[1].name(), b"@sample_8/2");
+    /// # Ok::<(), fq::rename::ParseTemplateError>(())
+    /// ```
+    pub fn rename(&self, index: u64, records: &mut [Record]) {
+        let id = match records.first() {
+            Some(record) => self.render(index, name_id(record.name())),
+            None => return,
+        };
+
+        for record in records {
+            let range = id_range(record.name());
+            record.name_mut().splice(range, id.iter().copied());
+        }
+    }
+
+    fn render(&self, index: u64, name: &[u8]) -> Vec<u8> {
+        let mut dst = Vec::new();
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(s) => dst.extend_from_slice(s),
+                Segment::Index => dst.extend_from_slice(index.to_string().as_bytes()),
+                Segment::Name => dst.extend_from_slice(name),
+            }
+        }
+
+        dst
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            "read_{index}".parse(),
+            Ok(Template {
+                segments: vec![Segment::Literal(b"read_".to_vec()), Segment::Index],
+            })
+        );
+
+        assert_eq!(
+            "{name}:{index}".parse(),
+            Ok(Template {
+                segments: vec![
+                    Segment::Name,
+                    Segment::Literal(b":".to_vec()),
+                    Segment::Index
+                ],
+            })
+        );
+
+        assert_eq!("".parse::<Template>(), Err(ParseTemplateError::Empty));
+        assert_eq!(
+            "read {index}".parse::<Template>(),
+            Err(ParseTemplateError::InvalidCharacter(' '))
+        );
+        assert_eq!(
+            "read_{index".parse::<Template>(),
+            Err(ParseTemplateError::UnclosedPlaceholder)
+        );
+        assert_eq!(
+            "read_{id}".parse::<Template>(),
+            Err(ParseTemplateError::InvalidPlaceholder(String::from("id")))
+        );
+    }
+
+    #[test]
+    fn test_rename() -> Result<(), ParseTemplateError> {
+        let template: Template = "s1_{name}".parse()?;
+
+        let mut records = [Record::new("@r0 1:N:0:ACGT", "ACGT", "+", "IIII")];
+        template.rename(0, &mut records);
+        assert_eq!(records[0].name(), b"@s1_r0 1:N:0:ACGT");
+
+        let mut records = [
+            Record::new("@r0/1", "ACGT", "+", "IIII"),
+            Record::new("@r0/2", "ACGT", "+", "IIII"),
+        ];
+        template.rename(0, &mut records);
+        assert_eq!(records[0].name(), b"@s1_r0/1");
+        assert_eq!(records[1].name(), b"@s1_r0/2");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+//! Contamination screening using reference k-mer sets.
+//!
+//! A k-mer set holds the canonical k-mers of a reference, e.g., PhiX, adapters, or a host genome.
+//! A canonical k-mer is the lesser of a k-mer and its reverse complement, so reads from either
+//! strand match. K-mers with bases other than A, C, G, or T are skipped.
+//!
+//! K-mer sets are stored as text. The first line is a header with the format version, k-mer size,
+//! and reference name, separated by tabs, and each following line is a k-mer.
+
+mod reader;
+mod writer;
+
+pub use self::{reader::Reader, writer::Writer};
+
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, BufReader, BufWriter},
+    path::Path,
+};
+
+/// The maximum k-mer size. K-mers are packed into 64 bits with 2 bits per base.
+pub const MAX_KMER_SIZE: usize = 32;
+
+/// A set of canonical k-mers from a reference.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KmerSet {
+    name: String,
+    kmer_size: usize,
+    kmers: HashSet<u64>,
+}
+
+impl KmerSet {
+    /// Creates an empty k-mer set.
+    ///
+    /// This fails if the k-mer size is not in [1, 32].
+    pub fn new<N>(name: N, kmer_size: usize) -> io::Result<Self>
+    where
+        N: Into<String>,
+    {
+        if !(1..=MAX_KMER_SIZE).contains(&kmer_size) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "invalid k-mer size: expected 1..={}, got {}",
+                    MAX_KMER_SIZE, kmer_size
+                ),
+            ));
+        }
+
+        Ok(Self {
+            name: name.into(),
+            kmer_size,
+            kmers: HashSet::new(),
+        })
+    }
+
+    /// Returns the reference name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kmer_size(&self) -> usize {
+        self.kmer_size
+    }
+
+    /// Returns the number of distinct canonical k-mers.
+    pub fn len(&self) -> usize {
+        self.kmers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kmers.is_empty()
+    }
+
+    /// Returns an iterator over the packed canonical k-mers in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        self.kmers.iter().copied()
+    }
+
+    /// Adds all canonical k-mers of a sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::screen::KmerSet;
+    ///
+    /// let mut set = KmerSet::new("ref", 3)?;
+    /// set.add_sequence(b"ACGTNAAC");
+    ///
+    /// // ACG and CGT are reverse complements, GTN and TNA are ambiguous, and AAC is added.
+    /// assert_eq!(set.len(), 2);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn add_sequence(&mut self, sequence: &[u8]) {
+        self.kmers.extend(Kmers::new(sequence, self.kmer_size));
+    }
+
+    pub(crate) fn insert(&mut self, kmer: u64) {
+        self.kmers.insert(kmer);
+    }
+
+    /// Returns the number of k-mers of a sequence that are in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::screen::KmerSet;
+    ///
+    /// let mut set = KmerSet::new("ref", 4)?;
+    /// set.add_sequence(b"AACCGGTT");
+    ///
+    /// assert_eq!(set.count_hits(b"CCGGTTAA"), 3);
+    /// assert_eq!(set.count_hits(b"AACCGGTT"), 5);
+    /// assert_eq!(set.count_hits(b"ACGTACGT"), 0);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn count_hits(&self, sequence: &[u8]) -> usize {
+        Kmers::new(sequence, self.kmer_size)
+            .filter(|kmer| self.kmers.contains(kmer))
+            .count()
+    }
+}
+
+/// A screen of reads against one or more k-mer sets.
+#[derive(Clone, Debug)]
+pub struct Screener {
+    kmer_sets: Vec<KmerSet>,
+    min_hits: usize,
+}
+
+impl Screener {
+    /// Creates a screener.
+    ///
+    /// A read hits a k-mer set when at least `min_hits` of its k-mers are in the set.
+    pub fn new(kmer_sets: Vec<KmerSet>, min_hits: usize) -> Self {
+        Self {
+            kmer_sets,
+            min_hits,
+        }
+    }
+
+    pub fn kmer_sets(&self) -> &[KmerSet] {
+        &self.kmer_sets
+    }
+
+    /// Returns an iterator over the indices of the k-mer sets that a sequence hits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::screen::{KmerSet, Screener};
+    ///
+    /// let mut phix = KmerSet::new("phix", 4)?;
+    /// phix.add_sequence(b"GAGTTTTATCGCTTCCATGA");
+    ///
+    /// let mut adapters = KmerSet::new("adapters", 4)?;
+    /// adapters.add_sequence(b"AGATCGGAAGAGC");
+    ///
+    /// let screener = Screener::new(vec![phix, adapters], 3);
+    ///
+    /// assert_eq!(screener.screen(b"TTATCGCTTC").collect::<Vec<_>>(), [0]);
+    /// assert_eq!(screener.screen(b"CCCCCCCCCC").count(), 0);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn screen<'a>(&'a self, sequence: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        self.kmer_sets
+            .iter()
+            .enumerate()
+            .filter(move |(_, kmer_set)| kmer_set.count_hits(sequence) >= self.min_hits)
+            .map(|(i, _)| i)
+    }
+}
+
+// An iterator over the packed canonical k-mers of a sequence, skipping ambiguous k-mers.
+struct Kmers<'a> {
+    sequence: &'a [u8],
+    kmer_size: usize,
+    mask: u64,
+    i: usize,
+    // The number of consecutive unambiguous bases ending at the current position.
+    len: usize,
+    forward: u64,
+    reverse: u64,
+}
+
+impl<'a> Kmers<'a> {
+    fn new(sequence: &'a [u8], kmer_size: usize) -> Self {
+        let mask = if kmer_size == MAX_KMER_SIZE {
+            u64::MAX
+        } else {
+            (1 << (2 * kmer_size)) - 1
+        };
+
+        Self {
+            sequence,
+            kmer_size,
+            mask,
+            i: 0,
+            len: 0,
+            forward: 0,
+            reverse: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for Kmers<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(&b) = self.sequence.get(self.i) {
+            self.i += 1;
+
+            match encode(b) {
+                Some(code) => {
+                    self.forward = ((self.forward << 2) | code) & self.mask;
+                    self.reverse = (self.reverse >> 2) | ((3 - code) << (2 * (self.kmer_size - 1)));
+                    self.len += 1;
+
+                    if self.len >= self.kmer_size {
+                        return Some(self.forward.min(self.reverse));
+                    }
+                }
+                None => self.len = 0,
+            }
+        }
+
+        None
+    }
+}
+
+fn encode(b: u8) -> Option<u64> {
+    match b {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' => Some(3),
+        _ => None,
+    }
+}
+
+/// Unpacks a k-mer into its bases.
+pub fn decode_kmer(kmer: u64, kmer_size: usize) -> Vec<u8> {
+    const BASES: [u8; 4] = [b'A', b'C', b'G', b'T'];
+
+    (0..kmer_size)
+        .rev()
+        .map(|i| BASES[((kmer >> (2 * i)) & 3) as usize])
+        .collect()
+}
+
+/// Packs the bases of a k-mer into its canonical form.
+///
+/// This returns `None` if the k-mer has a base other than A, C, G, or T or is too long.
+pub fn encode_kmer(bases: &[u8]) -> Option<u64> {
+    if bases.is_empty() || bases.len() > MAX_KMER_SIZE {
+        return None;
+    }
+
+    Kmers::new(bases, bases.len()).next()
+}
+
+/// Reads a k-mer set file.
+pub fn read_file<P>(src: P) -> io::Result<KmerSet>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(src)?;
+    Reader::new(BufReader::new(file)).read_kmer_set()
+}
+
+/// Writes a k-mer set file.
+pub fn write_file<P>(dst: P, kmer_set: &KmerSet) -> io::Result<()>
+where
+    P: AsRef<Path>,
+{
+    let file = File::create(dst)?;
+    Writer::new(BufWriter::new(file)).write_kmer_set(kmer_set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kmers() {
+        let kmers: Vec<_> = Kmers::new(b"ACGTT", 3).collect();
+        // ACG/CGT, CGT/ACG, GTT/AAC
+        assert_eq!(kmers, [0b000110, 0b000110, 0b000001]);
+
+        assert_eq!(Kmers::new(b"ACNGT", 3).count(), 0);
+        assert_eq!(Kmers::new(b"AC", 3).count(), 0);
+
+        let sequence = b"ACGTACGTACGTACGTACGTACGTACGTACGTA";
+        assert_eq!(Kmers::new(sequence, MAX_KMER_SIZE).count(), 2);
+    }
+
+    #[test]
+    fn test_encode_and_decode_kmer() {
+        assert_eq!(encode_kmer(b"AAC"), Some(0b000001));
+        assert_eq!(encode_kmer(b"GTT"), Some(0b000001));
+        assert_eq!(encode_kmer(b"ANC"), None);
+        assert_eq!(encode_kmer(b""), None);
+
+        assert_eq!(decode_kmer(0b000001, 3), b"AAC");
+        assert_eq!(decode_kmer(u64::MAX, MAX_KMER_SIZE), [b'T'; MAX_KMER_SIZE]);
+    }
+
+    #[test]
+    fn test_kmer_set_new_with_invalid_kmer_size() {
+        assert!(KmerSet::new("ref", 0).is_err());
+        assert!(KmerSet::new("ref", 33).is_err());
+    }
+}
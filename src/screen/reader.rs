@@ -0,0 +1,107 @@
+use std::io::{self, BufRead};
+
+use super::{
+    encode_kmer,
+    writer::{MAGIC, VERSION},
+    KmerSet,
+};
+
+const LINE_FEED: u8 = b'\n';
+
+/// A k-mer set reader.
+pub struct Reader<R> {
+    inner: R,
+}
+
+impl<R> Reader<R>
+where
+    R: BufRead,
+{
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    /// Reads a k-mer set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::screen::Reader;
+    ///
+    /// let data = b"#fqk\t1\t3\tref\nAAC\nACG\n";
+    /// let kmer_set = Reader::new(&data[..]).read_kmer_set()?;
+    ///
+    /// assert_eq!(kmer_set.name(), "ref");
+    /// assert_eq!(kmer_set.kmer_size(), 3);
+    /// assert_eq!(kmer_set.len(), 2);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn read_kmer_set(&mut self) -> io::Result<KmerSet> {
+        let mut header = String::new();
+        self.inner.read_line(&mut header)?;
+
+        let mut fields = header.trim_end().splitn(4, '\t');
+
+        if fields.next() != Some(MAGIC) || fields.next() != Some(VERSION) {
+            return Err(invalid_data("invalid k-mer set header"));
+        }
+
+        let kmer_size = fields
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| invalid_data("invalid k-mer set header: invalid k-mer size"))?;
+
+        let name = fields
+            .next()
+            .ok_or_else(|| invalid_data("invalid k-mer set header: missing name"))?;
+
+        let mut kmer_set = KmerSet::new(name, kmer_size)?;
+        let mut buf = Vec::new();
+
+        loop {
+            buf.clear();
+
+            if self.inner.read_until(LINE_FEED, &mut buf)? == 0 {
+                break;
+            }
+
+            let line = buf.strip_suffix(&[LINE_FEED]).unwrap_or(&buf);
+
+            let kmer = Some(line)
+                .filter(|kmer| kmer.len() == kmer_size)
+                .and_then(encode_kmer)
+                .ok_or_else(|| invalid_data("invalid k-mer"))?;
+
+            kmer_set.insert(kmer);
+        }
+
+        Ok(kmer_set)
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_kmer_set_with_invalid_data() {
+        let data = b"#fqk\t2\t3\tref\n";
+        assert!(Reader::new(&data[..]).read_kmer_set().is_err());
+
+        let data = b"#fqk\t1\t33\tref\n";
+        assert!(Reader::new(&data[..]).read_kmer_set().is_err());
+
+        let data = b"#fqk\t1\t3\n";
+        assert!(Reader::new(&data[..]).read_kmer_set().is_err());
+
+        let data = b"#fqk\t1\t3\tref\nAC\n";
+        assert!(Reader::new(&data[..]).read_kmer_set().is_err());
+
+        let data = b"#fqk\t1\t3\tref\nANC\n";
+        assert!(Reader::new(&data[..]).read_kmer_set().is_err());
+    }
+}
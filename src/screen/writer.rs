@@ -0,0 +1,64 @@
+use std::io::{self, Write};
+
+use super::{decode_kmer, KmerSet};
+
+pub(super) const MAGIC: &str = "#fqk";
+pub(super) const VERSION: &str = "1";
+
+/// A k-mer set writer.
+pub struct Writer<W> {
+    inner: W,
+}
+
+impl<W> Writer<W>
+where
+    W: Write,
+{
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Writes a k-mer set.
+    ///
+    /// K-mers are written in sorted order of their packed form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::screen::{KmerSet, Writer};
+    ///
+    /// let mut kmer_set = KmerSet::new("ref", 3)?;
+    /// kmer_set.add_sequence(b"ACGTT");
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.write_kmer_set(&kmer_set)?;
+    ///
+    /// assert_eq!(writer.get_ref(), b"#fqk\t1\t3\tref\nAAC\nACG\n");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_kmer_set(&mut self, kmer_set: &KmerSet) -> io::Result<()> {
+        writeln!(
+            self.inner,
+            "{}\t{}\t{}\t{}",
+            MAGIC,
+            VERSION,
+            kmer_set.kmer_size(),
+            kmer_set.name()
+        )?;
+
+        let mut kmers: Vec<_> = kmer_set.iter().collect();
+        kmers.sort_unstable();
+
+        for kmer in kmers {
+            self.inner
+                .write_all(&decode_kmer(kmer, kmer_set.kmer_size()))?;
+            self.inner.write_all(b"\n")?;
+        }
+
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,145 @@
+//! Nucleotide sequence utilities.
+
+/// Returns the complement of an IUPAC nucleotide code.
+///
+/// Case is preserved. Characters that are not nucleotide codes are returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use fq::sequence::complement;
+///
+/// assert_eq!(complement(b'A'), b'T');
+/// assert_eq!(complement(b'r'), b'y');
+/// assert_eq!(complement(b'N'), b'N');
+/// ```
+pub fn complement(b: u8) -> u8 {
+    match b {
+        b'A' => b'T',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'T' => b'A',
+        b'U' => b'A',
+        b'R' => b'Y',
+        b'Y' => b'R',
+        b'K' => b'M',
+        b'M' => b'K',
+        b'B' => b'V',
+        b'V' => b'B',
+        b'D' => b'H',
+        b'H' => b'D',
+        b'a' => b't',
+        b'c' => b'g',
+        b'g' => b'c',
+        b't' => b'a',
+        b'u' => b'a',
+        b'r' => b'y',
+        b'y' => b'r',
+        b'k' => b'm',
+        b'm' => b'k',
+        b'b' => b'v',
+        b'v' => b'b',
+        b'd' => b'h',
+        b'h' => b'd',
+        // S, W, and N are their own complements.
+        _ => b,
+    }
+}
+
+/// Reverse complements a sequence in place.
+///
+/// # Examples
+///
+/// ```
+/// use fq::sequence::reverse_complement;
+///
+/// let mut sequence = b"AACGTN".to_vec();
+/// reverse_complement(&mut sequence);
+/// assert_eq!(sequence, b"NACGTT");
+/// ```
+pub fn reverse_complement(sequence: &mut [u8]) {
+    sequence.reverse();
+
+    for b in sequence.iter_mut() {
+        *b = complement(*b);
+    }
+}
+
+/// Returns whether two IUPAC nucleotide codes can represent the same base.
+///
+/// # Examples
+///
+/// ```
+/// use fq::sequence::is_compatible;
+///
+/// assert!(is_compatible(b'A', b'a'));
+/// assert!(is_compatible(b'R', b'G'));
+/// assert!(is_compatible(b'N', b'T'));
+/// assert!(!is_compatible(b'Y', b'A'));
+/// ```
+pub fn is_compatible(a: u8, b: u8) -> bool {
+    iupac_mask(a) & iupac_mask(b) != 0
+}
+
+// Returns the set of bases represented by an IUPAC code, as a bitmask of A (1), C (2), G (4), and
+// T (8).
+fn iupac_mask(b: u8) -> u8 {
+    const A: u8 = 1;
+    const C: u8 = 2;
+    const G: u8 = 4;
+    const T: u8 = 8;
+
+    match b.to_ascii_uppercase() {
+        b'A' => A,
+        b'C' => C,
+        b'G' => G,
+        b'T' | b'U' => T,
+        b'R' => A | G,
+        b'Y' => C | T,
+        b'S' => C | G,
+        b'W' => A | T,
+        b'K' => G | T,
+        b'M' => A | C,
+        b'B' => C | G | T,
+        b'D' => A | G | T,
+        b'H' => A | C | T,
+        b'V' => A | C | G,
+        b'N' => A | C | G | T,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complement() {
+        for (b, expected) in b"ACGTRYKMBVDHSWN".iter().zip(b"TGCAYRMKVBHDSWN") {
+            assert_eq!(complement(*b), *expected);
+            assert_eq!(
+                complement(b.to_ascii_lowercase()),
+                expected.to_ascii_lowercase()
+            );
+        }
+
+        assert_eq!(complement(b'.'), b'.');
+    }
+
+    #[test]
+    fn test_reverse_complement() {
+        let mut sequence = b"ACGTRYN".to_vec();
+        reverse_complement(&mut sequence);
+        assert_eq!(sequence, b"NRYACGT");
+    }
+
+    #[test]
+    fn test_is_compatible() {
+        assert!(is_compatible(b'A', b'A'));
+        assert!(is_compatible(b'g', b'S'));
+        assert!(is_compatible(b'N', b'N'));
+        assert!(!is_compatible(b'A', b'C'));
+        assert!(!is_compatible(b'W', b'S'));
+        assert!(!is_compatible(b'.', b'A'));
+    }
+}
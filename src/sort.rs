@@ -0,0 +1,190 @@
+//! Record sorting.
+
+use std::{cmp::Ordering, error, fmt, str::FromStr};
+
+use crate::fastq::Record;
+
+/// The key records are sorted by.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SortKey {
+    /// The record name, compared byte by byte.
+    #[default]
+    Name,
+    /// The record name, comparing runs of digits by their numeric value, e.g., `r2` < `r10`.
+    NaturalName,
+    /// The record sequence.
+    Sequence,
+}
+
+/// An error returned when a sort key fails to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseSortKeyError(String);
+
+impl error::Error for ParseSortKeyError {}
+
+impl fmt::Display for ParseSortKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid sort key: expected name, natural, or sequence, got '{}'",
+            self.0
+        )
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = ParseSortKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(Self::Name),
+            "natural" => Ok(Self::NaturalName),
+            "sequence" => Ok(Self::Sequence),
+            _ => Err(ParseSortKeyError(s.into())),
+        }
+    }
+}
+
+impl SortKey {
+    /// Compares two templates, i.e., one record for single end reads or both mates for paired end
+    /// reads.
+    ///
+    /// Names are compared using the first record of each template. Sequences are compared mate
+    /// by mate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use fq::{fastq::Record, sort::SortKey};
+    ///
+    /// let a = [Record::new("@r2", "TTTT", "+", "IIII")];
+    /// let b = [Record::new("@r10", "AAAA", "+", "IIII")];
+    ///
+    /// assert_eq!(SortKey::Name.compare(&a, &b), Ordering::Greater);
+    /// assert_eq!(SortKey::NaturalName.compare(&a, &b), Ordering::Less);
+    /// assert_eq!(SortKey::Sequence.compare(&a, &b), Ordering::Greater);
+    /// ```
+    pub fn compare(&self, a: &[Record], b: &[Record]) -> Ordering {
+        match self {
+            Self::Name => a[0].name().cmp(b[0].name()),
+            Self::NaturalName => natural_cmp(a[0].name(), b[0].name()),
+            Self::Sequence => a
+                .iter()
+                .map(|r| r.sequence())
+                .cmp(b.iter().map(|r| r.sequence())),
+        }
+    }
+}
+
+/// Compares two strings, treating runs of ASCII digits as numbers.
+///
+/// Numbers that are equal in value but differ in leading zeros are ordered by their length, so
+/// this is a total order.
+///
+/// # Examples
+///
+/// ```
+/// use std::cmp::Ordering;
+/// use fq::sort::natural_cmp;
+///
+/// assert_eq!(natural_cmp(b"r:2:9", b"r:2:10"), Ordering::Less);
+/// assert_eq!(natural_cmp(b"r:10", b"r:9"), Ordering::Greater);
+/// ```
+pub fn natural_cmp(a: &[u8], b: &[u8]) -> Ordering {
+    let mut i = 0;
+    let mut j = 0;
+
+    while i < a.len() && j < b.len() {
+        if a[i].is_ascii_digit() && b[j].is_ascii_digit() {
+            let a_end = digits_end(a, i);
+            let b_end = digits_end(b, j);
+
+            let ordering =
+                numeric_cmp(&a[i..a_end], &b[j..b_end]).then_with(|| (a_end - i).cmp(&(b_end - j)));
+
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+
+            i = a_end;
+            j = b_end;
+        } else {
+            match a[i].cmp(&b[j]) {
+                Ordering::Equal => {
+                    i += 1;
+                    j += 1;
+                }
+                ordering => return ordering,
+            }
+        }
+    }
+
+    (a.len() - i).cmp(&(b.len() - j))
+}
+
+fn digits_end(s: &[u8], start: usize) -> usize {
+    s[start..]
+        .iter()
+        .position(|b| !b.is_ascii_digit())
+        .map(|n| start + n)
+        .unwrap_or(s.len())
+}
+
+// Compares two runs of digits by value, without parsing them, so they can be arbitrarily long.
+fn numeric_cmp(a: &[u8], b: &[u8]) -> Ordering {
+    let a = trim_leading_zeros(a);
+    let b = trim_leading_zeros(b);
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+fn trim_leading_zeros(s: &[u8]) -> &[u8] {
+    let start = s.iter().position(|&b| b != b'0').unwrap_or(s.len());
+    &s[start..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("name".parse(), Ok(SortKey::Name));
+        assert_eq!("natural".parse(), Ok(SortKey::NaturalName));
+        assert_eq!("sequence".parse(), Ok(SortKey::Sequence));
+        assert_eq!(
+            "quality".parse::<SortKey>(),
+            Err(ParseSortKeyError(String::from("quality")))
+        );
+    }
+
+    #[test]
+    fn test_compare_with_pairs() {
+        let a = [
+            Record::new("@r0/1", "ACGT", "+", "IIII"),
+            Record::new("@r0/2", "TTTT", "+", "IIII"),
+        ];
+
+        let b = [
+            Record::new("@r1/1", "ACGT", "+", "IIII"),
+            Record::new("@r1/2", "GGGG", "+", "IIII"),
+        ];
+
+        assert_eq!(SortKey::Name.compare(&a, &b), Ordering::Less);
+        assert_eq!(SortKey::Sequence.compare(&a, &b), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp(b"r2", b"r10"), Ordering::Less);
+        assert_eq!(natural_cmp(b"r10", b"r10"), Ordering::Equal);
+        assert_eq!(natural_cmp(b"r10a", b"r10b"), Ordering::Less);
+        assert_eq!(natural_cmp(b"r010", b"r10"), Ordering::Greater);
+        assert_eq!(natural_cmp(b"r1", b"r1:1"), Ordering::Less);
+        assert_eq!(natural_cmp(b"a", b"1"), Ordering::Greater);
+        assert_eq!(
+            natural_cmp(b"r99999999999999999999999", b"r100000000000000000000000"),
+            Ordering::Less
+        );
+    }
+}
@@ -0,0 +1,11 @@
+//! Read statistics.
+
+mod adapters;
+mod lengths;
+mod summary;
+
+pub use self::{
+    adapters::{default_adapters, Adapter, AdapterContent},
+    lengths::LengthStats,
+    summary::Summary,
+};
@@ -0,0 +1,170 @@
+use crate::fastq::Record;
+
+// The number of leading adapter bases searched for, following FastQC.
+const ADAPTER_KMER_LEN: usize = 12;
+
+/// A named adapter sequence.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Adapter {
+    name: String,
+    sequence: Vec<u8>,
+}
+
+impl Adapter {
+    pub fn new<N, S>(name: N, sequence: S) -> Self
+    where
+        N: Into<String>,
+        S: Into<Vec<u8>>,
+    {
+        Self {
+            name: name.into(),
+            sequence: sequence.into(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn sequence(&self) -> &[u8] {
+        &self.sequence
+    }
+
+    // Returns the start of the first occurrence of the adapter in the given sequence.
+    pub(crate) fn find(&self, sequence: &[u8]) -> Option<usize> {
+        let len = self.sequence.len().min(ADAPTER_KMER_LEN);
+
+        if len == 0 {
+            return None;
+        }
+
+        let kmer = &self.sequence[..len];
+
+        sequence
+            .windows(len)
+            .position(|window| window.eq_ignore_ascii_case(kmer))
+    }
+}
+
+/// Returns the commonly used adapters checked by default.
+pub fn default_adapters() -> Vec<Adapter> {
+    vec![
+        Adapter::new("illumina_universal", "AGATCGGAAGAG"),
+        Adapter::new("illumina_small_rna_3p", "TGGAATTCTCGG"),
+        Adapter::new("illumina_small_rna_5p", "GATCGTCGGACT"),
+        Adapter::new("nextera_transposase", "CTGTCTCTTATA"),
+        Adapter::new("poly_a", "AAAAAAAAAAAA"),
+        Adapter::new("poly_g", "GGGGGGGGGGGG"),
+    ]
+}
+
+/// Adapter content by position.
+///
+/// For each adapter and cycle, this is the fraction of reads in which the adapter starts at or
+/// before that cycle, i.e., the FastQC "Adapter Content" curve. Adapters are matched exactly by
+/// their first 12 bases.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{fastq::Record, stats::{AdapterContent, Adapter}};
+///
+/// let mut content = AdapterContent::new(vec![Adapter::new("a", "GGCC")]);
+/// content.add(&Record::new("@r0", "AGGCC", "+", "FQLIB"));
+/// content.add(&Record::new("@r1", "AAAAA", "+", "FQLIB"));
+///
+/// assert_eq!(content.curve(0), [0.0, 0.5, 0.5, 0.5, 0.5]);
+/// ```
+#[derive(Debug)]
+pub struct AdapterContent {
+    adapters: Vec<Adapter>,
+    start_counts: Vec<Vec<u64>>,
+    record_count: u64,
+    max_length: usize,
+}
+
+impl AdapterContent {
+    pub fn new(adapters: Vec<Adapter>) -> Self {
+        let start_counts = vec![Vec::new(); adapters.len()];
+
+        Self {
+            adapters,
+            start_counts,
+            record_count: 0,
+            max_length: 0,
+        }
+    }
+
+    pub fn adapters(&self) -> &[Adapter] {
+        &self.adapters
+    }
+
+    /// Adds a record, counting where each adapter first starts.
+    pub fn add(&mut self, record: &Record) {
+        let sequence = record.sequence();
+
+        self.record_count += 1;
+        self.max_length = self.max_length.max(sequence.len());
+
+        for (adapter, counts) in self.adapters.iter().zip(self.start_counts.iter_mut()) {
+            if let Some(i) = adapter.find(sequence) {
+                if counts.len() <= i {
+                    counts.resize(i + 1, 0);
+                }
+
+                counts[i] += 1;
+            }
+        }
+    }
+
+    /// Returns the cumulative fraction of reads containing the `i`th adapter for each cycle.
+    ///
+    /// The curve has one value per cycle of the longest read.
+    pub fn curve(&self, i: usize) -> Vec<f64> {
+        let counts = &self.start_counts[i];
+        let mut curve = Vec::with_capacity(self.max_length);
+        let mut sum = 0;
+
+        for cycle in 0..self.max_length {
+            sum += counts.get(cycle).copied().unwrap_or(0);
+            curve.push(sum as f64 / self.record_count as f64);
+        }
+
+        curve
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let adapter = Adapter::new("illumina_universal", "AGATCGGAAGAGCACACGTCT");
+
+        assert_eq!(adapter.find(b"ACGTAGATCGGAAGAGCA"), Some(4));
+        assert_eq!(adapter.find(b"ACGTagatcggaagag"), Some(4));
+        assert_eq!(adapter.find(b"ACGTAGATCGGAAGA"), None);
+        assert_eq!(Adapter::new("empty", "").find(b"ACGT"), None);
+    }
+
+    #[test]
+    fn test_curve() {
+        let adapters = vec![Adapter::new("a", "GGCC"), Adapter::new("b", "TTTT")];
+        let mut content = AdapterContent::new(adapters);
+
+        content.add(&Record::new("@r0", "AGGCCA", "+", "FQLIBF"));
+        content.add(&Record::new("@r1", "GGCCAA", "+", "FQLIBF"));
+        content.add(&Record::new("@r2", "AAAAAAAA", "+", "FQLIBFQL"));
+        content.add(&Record::new("@r3", "AAGGCCAA", "+", "FQLIBFQL"));
+
+        assert_eq!(
+            content.curve(0),
+            [0.25, 0.5, 0.75, 0.75, 0.75, 0.75, 0.75, 0.75]
+        );
+        assert_eq!(content.curve(1), [0.0; 8]);
+
+        let content = AdapterContent::new(default_adapters());
+        assert!(content.curve(0).is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap},
+};
+
+use crate::fastq::Record;
+
+/// Read length statistics, e.g., for long read run triage.
+///
+/// Lengths are kept as a histogram, so Nx values are exact without storing every read. Names are
+/// only kept for the longest reads.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{fastq::Record, stats::LengthStats};
+///
+/// let mut stats = LengthStats::new(1);
+///
+/// stats.add(&Record::new("@r0", "ACGTACGT", "+", "FQLIBFQL"));
+/// stats.add(&Record::new("@r1", "ACGT", "+", "FQLI"));
+/// stats.add(&Record::new("@r2", "AC", "+", "FQ"));
+///
+/// assert_eq!(stats.record_count(), 3);
+/// assert_eq!(stats.base_count(), 14);
+/// assert_eq!(stats.nx(50), Some(8));
+/// assert_eq!(stats.longest(), vec![(&b"r0"[..], 8)]);
+/// ```
+#[derive(Debug, Default)]
+pub struct LengthStats {
+    histogram: BTreeMap<usize, u64>,
+    record_count: u64,
+    base_count: u64,
+    longest_count: usize,
+    longest: BinaryHeap<Reverse<(usize, Vec<u8>)>>,
+}
+
+impl LengthStats {
+    /// Creates length statistics that keep up to `longest_count` of the longest reads.
+    pub fn new(longest_count: usize) -> Self {
+        Self {
+            longest_count,
+            ..Default::default()
+        }
+    }
+
+    /// Adds the length of a record.
+    pub fn add(&mut self, record: &Record) {
+        let len = record.sequence().len();
+
+        *self.histogram.entry(len).or_insert(0) += 1;
+        self.record_count += 1;
+        self.base_count += len as u64;
+
+        if self.longest_count == 0 {
+            return;
+        }
+
+        let is_longer = self
+            .longest
+            .peek()
+            .map(|Reverse((shortest_len, _))| len > *shortest_len)
+            .unwrap_or(true);
+
+        if self.longest.len() < self.longest_count || is_longer {
+            let name = record.name();
+            let name = name.strip_prefix(b"@").unwrap_or(name);
+
+            self.longest.push(Reverse((len, name.to_vec())));
+
+            if self.longest.len() > self.longest_count {
+                self.longest.pop();
+            }
+        }
+    }
+
+    /// Returns the number of records added.
+    pub fn record_count(&self) -> u64 {
+        self.record_count
+    }
+
+    /// Returns the total number of bases added.
+    pub fn base_count(&self) -> u64 {
+        self.base_count
+    }
+
+    /// Returns the length of the longest read.
+    pub fn max_length(&self) -> Option<usize> {
+        self.histogram.keys().next_back().copied()
+    }
+
+    /// Returns the Nx value, e.g., N50 for `x = 50`.
+    ///
+    /// This is the length such that reads of at least this length contain at least `x` percent of
+    /// all bases. It is `None` when no bases were added.
+    pub fn nx(&self, x: u8) -> Option<usize> {
+        if self.base_count == 0 {
+            return None;
+        }
+
+        let target = self.base_count * u64::from(x);
+        let mut sum = 0;
+
+        for (&len, &count) in self.histogram.iter().rev() {
+            sum += len as u64 * count;
+
+            if sum * 100 >= target {
+                return Some(len);
+            }
+        }
+
+        self.histogram.keys().next().copied()
+    }
+
+    /// Returns the number of records with a length of at least `min_len`.
+    pub fn records_at_least(&self, min_len: usize) -> u64 {
+        self.histogram
+            .range(min_len..)
+            .map(|(_, &count)| count)
+            .sum()
+    }
+
+    /// Returns the number of bases in records with a length of at least `min_len`.
+    pub fn bases_at_least(&self, min_len: usize) -> u64 {
+        self.histogram
+            .range(min_len..)
+            .map(|(&len, &count)| len as u64 * count)
+            .sum()
+    }
+
+    /// Returns the names and lengths of the longest reads, longest first.
+    pub fn longest(&self) -> Vec<(&[u8], usize)> {
+        let mut reads: Vec<_> = self
+            .longest
+            .iter()
+            .map(|Reverse((len, name))| (name.as_slice(), *len))
+            .collect();
+
+        reads.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+        reads
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_stats(lengths: &[usize]) -> LengthStats {
+        let mut stats = LengthStats::new(2);
+
+        for (i, &len) in lengths.iter().enumerate() {
+            let name = format!("@r{}", i);
+            let sequence = vec![b'A'; len];
+            let quality_scores = vec![b'F'; len];
+            stats.add(&Record::new(name, sequence, "+", quality_scores));
+        }
+
+        stats
+    }
+
+    #[test]
+    fn test_nx() {
+        let stats = build_stats(&[2, 3, 4, 5, 6, 10]);
+
+        assert_eq!(stats.base_count(), 30);
+        assert_eq!(stats.nx(50), Some(6));
+        assert_eq!(stats.nx(90), Some(3));
+        assert_eq!(stats.nx(100), Some(2));
+
+        let stats = LengthStats::new(0);
+        assert!(stats.nx(50).is_none());
+    }
+
+    #[test]
+    fn test_records_and_bases_at_least() {
+        let stats = build_stats(&[2, 3, 4, 5, 6, 10]);
+
+        assert_eq!(stats.records_at_least(5), 3);
+        assert_eq!(stats.bases_at_least(5), 21);
+        assert_eq!(stats.records_at_least(11), 0);
+        assert_eq!(stats.bases_at_least(11), 0);
+    }
+
+    #[test]
+    fn test_longest() {
+        let stats = build_stats(&[2, 10, 4, 6, 5]);
+
+        assert_eq!(stats.max_length(), Some(10));
+        assert_eq!(stats.longest(), vec![(&b"r1"[..], 10), (&b"r3"[..], 6)]);
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+
+use crate::{fastq::Record, quality::Encoding};
+
+const QUALITY_OFFSET: u8 = Encoding::Phred33.offset();
+
+/// Summary statistics of a set of records, e.g., for dashboards.
+///
+/// Quality scores are summed per cycle and read lengths are kept as a histogram, so mean qualities
+/// and the length distribution are exact without storing any records.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{fastq::Record, stats::Summary};
+///
+/// let mut summary = Summary::default();
+///
+/// summary.add(&Record::new("@r0", "ACGN", "+", "+5?I"));
+/// summary.add(&Record::new("@r1", "GC", "+", "5?"));
+///
+/// assert_eq!(summary.record_count(), 2);
+/// assert_eq!(summary.min_length(), Some(2));
+/// assert_eq!(summary.max_length(), Some(4));
+/// assert_eq!(summary.mean_length(), Some(3.0));
+/// assert_eq!(summary.length_distribution().collect::<Vec<_>>(), [(2, 1), (4, 1)]);
+/// assert_eq!(summary.cycle_mean_quality_scores(), vec![15.0, 25.0, 30.0, 40.0]);
+/// assert_eq!(summary.gc_content(), Some(0.8));
+/// assert_eq!(summary.n_content(), Some(1.0 / 6.0));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Summary {
+    record_count: u64,
+    base_count: u64,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    // Read lengths mapped to their record counts.
+    length_counts: BTreeMap<usize, u64>,
+    gc_count: u64,
+    n_count: u64,
+    cycle_quality_score_sums: Vec<u64>,
+    cycle_counts: Vec<u64>,
+}
+
+impl Summary {
+    /// Adds a record to the summary.
+    pub fn add(&mut self, record: &Record) {
+        let sequence = record.sequence();
+        let len = sequence.len();
+
+        self.record_count += 1;
+        self.base_count += len as u64;
+        self.min_length = Some(self.min_length.map_or(len, |n| n.min(len)));
+        self.max_length = Some(self.max_length.map_or(len, |n| n.max(len)));
+        *self.length_counts.entry(len).or_insert(0) += 1;
+
+        for &base in sequence {
+            match base.to_ascii_uppercase() {
+                b'G' | b'C' | b'S' => self.gc_count += 1,
+                b'N' => self.n_count += 1,
+                _ => {}
+            }
+        }
+
+        let quality_scores = record.quality_scores();
+
+        if self.cycle_counts.len() < quality_scores.len() {
+            self.cycle_quality_score_sums
+                .resize(quality_scores.len(), 0);
+            self.cycle_counts.resize(quality_scores.len(), 0);
+        }
+
+        for (i, &score) in quality_scores.iter().enumerate() {
+            self.cycle_quality_score_sums[i] += u64::from(score.saturating_sub(QUALITY_OFFSET));
+            self.cycle_counts[i] += 1;
+        }
+    }
+
+    /// Returns the number of records added.
+    pub fn record_count(&self) -> u64 {
+        self.record_count
+    }
+
+    /// Returns the total number of bases added.
+    pub fn base_count(&self) -> u64 {
+        self.base_count
+    }
+
+    /// Returns the length of the shortest read.
+    pub fn min_length(&self) -> Option<usize> {
+        self.min_length
+    }
+
+    /// Returns the length of the longest read.
+    pub fn max_length(&self) -> Option<usize> {
+        self.max_length
+    }
+
+    /// Returns the mean read length.
+    pub fn mean_length(&self) -> Option<f64> {
+        if self.record_count == 0 {
+            None
+        } else {
+            Some(self.base_count as f64 / self.record_count as f64)
+        }
+    }
+
+    /// Returns the read lengths and their record counts, ordered by length.
+    pub fn length_distribution(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.length_counts.iter().map(|(&len, &n)| (len, n))
+    }
+
+    /// Returns the mean Phred quality score of each cycle.
+    ///
+    /// Each cycle is averaged over the reads that reach it.
+    pub fn cycle_mean_quality_scores(&self) -> Vec<f64> {
+        self.cycle_quality_score_sums
+            .iter()
+            .zip(&self.cycle_counts)
+            .map(|(&sum, &count)| sum as f64 / count as f64)
+            .collect()
+    }
+
+    /// Returns the fraction of G and C bases, excluding Ns.
+    pub fn gc_content(&self) -> Option<f64> {
+        let called_count = self.base_count - self.n_count;
+
+        if called_count == 0 {
+            None
+        } else {
+            Some(self.gc_count as f64 / called_count as f64)
+        }
+    }
+
+    /// Returns the fraction of N bases.
+    pub fn n_content(&self) -> Option<f64> {
+        if self.base_count == 0 {
+            None
+        } else {
+            Some(self.n_count as f64 / self.base_count as f64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add() {
+        let mut summary = Summary::default();
+
+        assert_eq!(summary.min_length(), None);
+        assert_eq!(summary.mean_length(), None);
+        assert_eq!(summary.gc_content(), None);
+        assert_eq!(summary.n_content(), None);
+        assert!(summary.cycle_mean_quality_scores().is_empty());
+        assert_eq!(summary.length_distribution().next(), None);
+
+        summary.add(&Record::new("@r0", "NNNN", "+", "!!!!"));
+        summary.add(&Record::new("@r1", "", "+", ""));
+
+        assert_eq!(summary.record_count(), 2);
+        assert_eq!(summary.base_count(), 4);
+        assert_eq!(summary.min_length(), Some(0));
+        assert_eq!(summary.max_length(), Some(4));
+        assert_eq!(summary.mean_length(), Some(2.0));
+        assert_eq!(
+            summary.length_distribution().collect::<Vec<_>>(),
+            [(0, 1), (4, 1)]
+        );
+        assert_eq!(summary.gc_content(), None);
+        assert_eq!(summary.n_content(), Some(1.0));
+        assert_eq!(summary.cycle_mean_quality_scores(), vec![0.0; 4]);
+    }
+}
@@ -0,0 +1,152 @@
+use std::io::{self, BufRead};
+
+use crate::{fastq::Record, metrics::Metrics};
+
+const NAME_PREFIX: u8 = b'@';
+const PLUS_LINE: &[u8] = b"+";
+const TAB: u8 = b'\t';
+const LINE_FEED: u8 = b'\n';
+const CARRIAGE_RETURN: u8 = b'\r';
+
+/// A tabular FASTQ reader.
+pub struct Reader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    metrics: Option<Metrics>,
+}
+
+impl<R> Reader<R>
+where
+    R: BufRead,
+{
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buf: Vec::new(),
+            metrics: None,
+        }
+    }
+
+    /// Sets the metrics to update on each line read.
+    pub fn set_metrics(&mut self, metrics: Metrics) {
+        self.metrics = Some(metrics);
+    }
+
+    /// Reads a line as FASTQ records.
+    ///
+    /// A line with three fields is read into `r1`, and a line with six fields, into `r1` and
+    /// `r2`. This returns the number of records read, i.e., 1 or 2, or 0 at EOF.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, tab::Reader};
+    ///
+    /// let data = b"r0/1\tACGT\tIIII\tr0/2\tTTGG\tIIII\nr1\tAC\tII\n";
+    /// let mut reader = Reader::new(&data[..]);
+    ///
+    /// let mut r1 = Record::default();
+    /// let mut r2 = Record::default();
+    ///
+    /// assert_eq!(reader.read_records(&mut r1, &mut r2)?, 2);
+    /// assert_eq!(r1, Record::new("@r0/1", "ACGT", "+", "IIII"));
+    /// assert_eq!(r2, Record::new("@r0/2", "TTGG", "+", "IIII"));
+    ///
+    /// assert_eq!(reader.read_records(&mut r1, &mut r2)?, 1);
+    /// assert_eq!(r1, Record::new("@r1", "AC", "+", "II"));
+    ///
+    /// assert_eq!(reader.read_records(&mut r1, &mut r2)?, 0);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn read_records(&mut self, r1: &mut Record, r2: &mut Record) -> io::Result<usize> {
+        self.buf.clear();
+
+        let len = self.inner.read_until(LINE_FEED, &mut self.buf)?;
+
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let mut line = &self.buf[..];
+
+        if line.ends_with(&[LINE_FEED]) {
+            line = &line[..line.len() - 1];
+
+            if line.ends_with(&[CARRIAGE_RETURN]) {
+                line = &line[..line.len() - 1];
+            }
+        }
+
+        let fields: Vec<&[u8]> = line.split(|&b| b == TAB).collect();
+
+        let n = match fields[..] {
+            [name, sequence, quality_scores] => {
+                copy_fields(r1, name, sequence, quality_scores);
+                1
+            }
+            [name_1, sequence_1, quality_scores_1, name_2, sequence_2, quality_scores_2] => {
+                copy_fields(r1, name_1, sequence_1, quality_scores_1);
+                copy_fields(r2, name_2, sequence_2, quality_scores_2);
+                2
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "invalid tabular record: expected 3 or 6 fields, got {}",
+                        fields.len()
+                    ),
+                ))
+            }
+        };
+
+        if let Some(metrics) = &self.metrics {
+            metrics.add_records_read(n as u64);
+            metrics.add_bytes_read(len as u64);
+        }
+
+        Ok(n)
+    }
+}
+
+fn copy_fields(record: &mut Record, name: &[u8], sequence: &[u8], quality_scores: &[u8]) {
+    record.clear();
+
+    record.name_mut().push(NAME_PREFIX);
+    record.name_mut().extend_from_slice(name);
+    record.sequence_mut().extend_from_slice(sequence);
+    record.plus_line_mut().extend_from_slice(PLUS_LINE);
+    record
+        .quality_scores_mut()
+        .extend_from_slice(quality_scores);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_records_with_crlf() -> io::Result<()> {
+        let data = b"r0\tACGT\tIIII\r\n";
+        let mut reader = Reader::new(&data[..]);
+
+        let mut r1 = Record::default();
+        let mut r2 = Record::default();
+
+        assert_eq!(reader.read_records(&mut r1, &mut r2)?, 1);
+        assert_eq!(r1, Record::new("@r0", "ACGT", "+", "IIII"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_records_with_invalid_field_count() {
+        let data = b"r0\tACGT\n";
+        let mut reader = Reader::new(&data[..]);
+
+        let mut r1 = Record::default();
+        let mut r2 = Record::default();
+
+        assert!(reader.read_records(&mut r1, &mut r2).is_err());
+    }
+}
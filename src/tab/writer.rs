@@ -0,0 +1,100 @@
+use std::io::{self, Write};
+
+use crate::{fastq::Record, metrics::Metrics};
+
+const NAME_PREFIX: u8 = b'@';
+const TAB: u8 = b'\t';
+const LINE_FEED: u8 = b'\n';
+
+/// A tabular FASTQ writer.
+pub struct Writer<W> {
+    inner: W,
+    metrics: Option<Metrics>,
+}
+
+impl<W> Writer<W>
+where
+    W: Write,
+{
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            metrics: None,
+        }
+    }
+
+    /// Sets the metrics to update on each record written.
+    pub fn set_metrics(&mut self, metrics: Metrics) {
+        self.metrics = Some(metrics);
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Writes a single end record as a line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, tab::Writer};
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.write_record(&Record::new("@r0", "ACGT", "+", "IIII"))?;
+    ///
+    /// assert_eq!(writer.get_ref(), b"r0\tACGT\tIIII\n");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_record(&mut self, record: &Record) -> io::Result<()> {
+        let len = self.write_fields(record)?;
+        self.inner.write_all(&[LINE_FEED])?;
+        self.update_metrics(1, len + 1);
+        Ok(())
+    }
+
+    /// Writes a pair of mates as a line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, tab::Writer};
+    ///
+    /// let mut writer = Writer::new(Vec::new());
+    ///
+    /// writer.write_pair(
+    ///     &Record::new("@r0/1", "ACGT", "+", "IIII"),
+    ///     &Record::new("@r0/2", "TTGG", "+", "IIII"),
+    /// )?;
+    ///
+    /// assert_eq!(writer.get_ref(), b"r0/1\tACGT\tIIII\tr0/2\tTTGG\tIIII\n");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn write_pair(&mut self, r1: &Record, r2: &Record) -> io::Result<()> {
+        let mut len = self.write_fields(r1)?;
+        self.inner.write_all(&[TAB])?;
+        len += self.write_fields(r2)?;
+        self.inner.write_all(&[LINE_FEED])?;
+        self.update_metrics(2, len + 2);
+        Ok(())
+    }
+
+    fn write_fields(&mut self, record: &Record) -> io::Result<usize> {
+        let name = record.name();
+        let name = name.strip_prefix(&[NAME_PREFIX]).unwrap_or(name);
+
+        self.inner.write_all(name)?;
+        self.inner.write_all(&[TAB])?;
+        self.inner.write_all(record.sequence())?;
+        self.inner.write_all(&[TAB])?;
+        self.inner.write_all(record.quality_scores())?;
+
+        Ok(name.len() + record.sequence().len() + record.quality_scores().len() + 2)
+    }
+
+    fn update_metrics(&self, records: u64, bytes: usize) {
+        if let Some(metrics) = &self.metrics {
+            metrics.add_records_written(records);
+            metrics.add_bytes_written(bytes as u64);
+        }
+    }
+}
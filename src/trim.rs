@@ -0,0 +1,298 @@
+//! Fixed-length, quality, adapter, and poly-X tail trimming.
+
+use crate::{fastq::Record, matching::OverlapMatcher, quality::Encoding};
+
+const QUALITY_OFFSET: u8 = Encoding::Phred33.offset();
+
+/// Removes up to `n` bases from the start of a record.
+///
+/// The sequence and quality scores are trimmed together.
+pub fn trim_start(record: &mut Record, n: usize) {
+    let sequence = record.sequence_mut();
+    sequence.drain(..n.min(sequence.len()));
+
+    let quality_scores = record.quality_scores_mut();
+    quality_scores.drain(..n.min(quality_scores.len()));
+}
+
+/// Removes up to `n` bases from the end of a record.
+///
+/// The sequence and quality scores are trimmed together.
+pub fn trim_end(record: &mut Record, n: usize) {
+    let sequence = record.sequence_mut();
+    sequence.truncate(sequence.len().saturating_sub(n));
+
+    let quality_scores = record.quality_scores_mut();
+    quality_scores.truncate(quality_scores.len().saturating_sub(n));
+}
+
+/// A sliding window quality trimmer.
+///
+/// Windows are scanned from the start of the read. The read is cut at the start of the first
+/// window with a mean Phred quality score below the cutoff, keeping any leading bases of that
+/// window that are at least the cutoff.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct QualityTrimmer {
+    cutoff: u8,
+    window_size: usize,
+}
+
+impl QualityTrimmer {
+    /// Creates a quality trimmer.
+    ///
+    /// A window size of 0 is treated as 1.
+    pub fn new(cutoff: u8, window_size: usize) -> Self {
+        Self {
+            cutoff,
+            window_size: window_size.max(1),
+        }
+    }
+
+    /// Trims low quality bases from the end of a record.
+    ///
+    /// This returns the number of bases trimmed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, trim::QualityTrimmer};
+    ///
+    /// let trimmer = QualityTrimmer::new(20, 2);
+    ///
+    /// let mut record = Record::new("@r0", "ACGTACGT", "+", "IIII5+##");
+    /// assert_eq!(trimmer.trim(&mut record), 3);
+    /// assert_eq!(record.sequence(), b"ACGTA");
+    /// assert_eq!(record.quality_scores(), b"IIII5");
+    /// ```
+    pub fn trim(&self, record: &mut Record) -> usize {
+        let len = record.quality_scores().len();
+        let end = self.find_end(record.quality_scores());
+        trim_end(record, len - end);
+        len - end
+    }
+
+    // Returns the number of bases to keep.
+    fn find_end(&self, quality_scores: &[u8]) -> usize {
+        let window_size = self.window_size.min(quality_scores.len());
+
+        if window_size == 0 {
+            return 0;
+        }
+
+        let min_sum = u64::from(self.cutoff) * window_size as u64;
+        let phred = |score: u8| u64::from(score.saturating_sub(QUALITY_OFFSET));
+
+        let mut sum: u64 = quality_scores[..window_size]
+            .iter()
+            .map(|&s| phred(s))
+            .sum();
+
+        for start in 0..=quality_scores.len() - window_size {
+            if start > 0 {
+                sum -= phred(quality_scores[start - 1]);
+                sum += phred(quality_scores[start + window_size - 1]);
+            }
+
+            if sum < min_sum {
+                let kept = quality_scores[start..start + window_size]
+                    .iter()
+                    .take_while(|&&s| phred(s) >= u64::from(self.cutoff))
+                    .count();
+
+                return start + kept;
+            }
+        }
+
+        quality_scores.len()
+    }
+}
+
+/// A trimmer that removes adapter sequences from the 3' end of reads.
+///
+/// Everything from the leftmost adapter match onward is removed. Adapters may be partially read,
+/// i.e., only a prefix of the adapter overlaps the end of the read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdapterTrimmer {
+    adapters: Vec<Vec<u8>>,
+    matcher: OverlapMatcher,
+}
+
+impl AdapterTrimmer {
+    /// Creates an adapter trimmer.
+    pub fn new(adapters: Vec<Vec<u8>>, matcher: OverlapMatcher) -> Self {
+        Self { adapters, matcher }
+    }
+
+    /// Trims adapters from the end of a record.
+    ///
+    /// This returns the number of bases trimmed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, matching::OverlapMatcher, trim::AdapterTrimmer};
+    ///
+    /// let trimmer = AdapterTrimmer::new(vec![b"AGATCGGAAG".to_vec()], OverlapMatcher::new(0.1, 3));
+    ///
+    /// let mut record = Record::new("@r0", "ACGTACGTAGATCG", "+", "IIIIIIIIIIIIII");
+    /// assert_eq!(trimmer.trim(&mut record), 6);
+    /// assert_eq!(record.sequence(), b"ACGTACGT");
+    /// ```
+    pub fn trim(&self, record: &mut Record) -> usize {
+        let sequence = record.sequence();
+        let len = sequence.len();
+
+        let end = self
+            .adapters
+            .iter()
+            .filter_map(|adapter| self.matcher.find(adapter, sequence))
+            .map(|m| m.start())
+            .min()
+            .unwrap_or(len);
+
+        trim_end(record, len - end);
+
+        len - end
+    }
+}
+
+/// A trimmer that removes homopolymer tails, e.g., polyG, from the 3' end of reads.
+///
+/// Two-color instruments (NextSeq, NovaSeq) call G when there is no signal, so reads that run
+/// past the end of a short fragment often end in a run of Gs.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PolyXTrimmer {
+    bases: Vec<u8>,
+    min_len: usize,
+}
+
+impl PolyXTrimmer {
+    /// Creates a poly-X trimmer.
+    ///
+    /// A tail is only trimmed when it is a run of one of the given bases that is at least
+    /// `min_len` bases long. Bases are compared case-insensitively.
+    pub fn new(bases: Vec<u8>, min_len: usize) -> Self {
+        let bases = bases.iter().map(|b| b.to_ascii_uppercase()).collect();
+        Self { bases, min_len }
+    }
+
+    /// Trims a homopolymer tail from the end of a record.
+    ///
+    /// This returns the number of bases trimmed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, trim::PolyXTrimmer};
+    ///
+    /// let trimmer = PolyXTrimmer::new(vec![b'G'], 4);
+    ///
+    /// let mut record = Record::new("@r0", "ACGTGGGGG", "+", "IIIIIIIII");
+    /// assert_eq!(trimmer.trim(&mut record), 5);
+    /// assert_eq!(record.sequence(), b"ACGT");
+    /// ```
+    pub fn trim(&self, record: &mut Record) -> usize {
+        let sequence = record.sequence();
+
+        let base = match sequence.last() {
+            Some(b) => b.to_ascii_uppercase(),
+            None => return 0,
+        };
+
+        if !self.bases.contains(&base) {
+            return 0;
+        }
+
+        let len = sequence
+            .iter()
+            .rev()
+            .take_while(|b| b.to_ascii_uppercase() == base)
+            .count();
+
+        if len < self.min_len {
+            return 0;
+        }
+
+        trim_end(record, len);
+
+        len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trim_start_and_end() {
+        let mut record = Record::new("@r0", "ACGTACGT", "+", "ABCDEFGH");
+
+        trim_start(&mut record, 2);
+        assert_eq!(record.sequence(), b"GTACGT");
+        assert_eq!(record.quality_scores(), b"CDEFGH");
+
+        trim_end(&mut record, 3);
+        assert_eq!(record.sequence(), b"GTA");
+        assert_eq!(record.quality_scores(), b"CDE");
+
+        trim_start(&mut record, 8);
+        assert!(record.sequence().is_empty());
+        assert!(record.quality_scores().is_empty());
+    }
+
+    #[test]
+    fn test_quality_trimmer_find_end() {
+        let trimmer = QualityTrimmer::new(20, 4);
+
+        assert_eq!(trimmer.find_end(b"IIIIIIII"), 8);
+        assert_eq!(trimmer.find_end(b"IIII####"), 4);
+        assert_eq!(trimmer.find_end(b"########"), 0);
+        assert_eq!(trimmer.find_end(b"5#"), 1);
+        assert_eq!(trimmer.find_end(b""), 0);
+
+        // A single low quality base is tolerated when the window mean is above the cutoff.
+        assert_eq!(trimmer.find_end(b"III#IIII"), 8);
+
+        let trimmer = QualityTrimmer::new(20, 0);
+        assert_eq!(trimmer.find_end(b"II#I"), 2);
+    }
+
+    #[test]
+    fn test_adapter_trimmer() {
+        let adapters = vec![b"AGATCGGAAG".to_vec(), b"CTGTCTCTTA".to_vec()];
+        let trimmer = AdapterTrimmer::new(adapters, OverlapMatcher::new(0.1, 3));
+
+        let mut record = Record::new("@r0", "ACGTCTGTCTCTTAAGATCG", "+", "IIIIIIIIIIIIIIIIIIII");
+        assert_eq!(trimmer.trim(&mut record), 16);
+        assert_eq!(record.sequence(), b"ACGT");
+        assert_eq!(record.quality_scores(), b"IIII");
+
+        let mut record = Record::new("@r1", "ACGTACGT", "+", "IIIIIIII");
+        assert_eq!(trimmer.trim(&mut record), 0);
+        assert_eq!(record.sequence(), b"ACGTACGT");
+    }
+
+    #[test]
+    fn test_poly_x_trimmer() {
+        let trimmer = PolyXTrimmer::new(b"ga".to_vec(), 3);
+
+        let mut record = Record::new("@r0", "ACGTaaaa", "+", "IIIIIIII");
+        assert_eq!(trimmer.trim(&mut record), 4);
+        assert_eq!(record.sequence(), b"ACGT");
+        assert_eq!(record.quality_scores(), b"IIII");
+
+        let mut record = Record::new("@r1", "ACGTGG", "+", "IIIIII");
+        assert_eq!(trimmer.trim(&mut record), 0);
+        assert_eq!(record.sequence(), b"ACGTGG");
+
+        let mut record = Record::new("@r2", "ACGTTTTT", "+", "IIIIIIII");
+        assert_eq!(trimmer.trim(&mut record), 0);
+
+        let mut record = Record::new("@r3", "GGGG", "+", "IIII");
+        assert_eq!(trimmer.trim(&mut record), 4);
+        assert!(record.sequence().is_empty());
+
+        let mut record = Record::default();
+        assert_eq!(trimmer.trim(&mut record), 0);
+    }
+}
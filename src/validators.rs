@@ -1,12 +1,23 @@
+mod config;
+mod description;
 pub mod paired;
+mod rules;
 pub mod single;
 pub mod validation_level;
 
 pub use self::{
-    paired::{NamesValidator, PairedReadValidator},
+    config::{Config, ParseOptionError},
+    description::{descriptions, find_description, Description, Kind},
+    paired::{IdenticalMatesValidator, NamesValidator, PairedReadValidator},
+    rules::{parse_rules, ParseRulesError, RuleValidator},
     single::{
-        AlphabetValidator, CompleteValidator, ConsistentSeqQualValidator, NameValidator,
-        PlusLineValidator, QualityStringValidator, SingleReadValidator, SingleReadValidatorMut,
+        AdapterContentValidator, AlphabetValidator, CompleteValidator, ConsistentSeqQualValidator,
+        DuplicateSequenceValidator, EmptyFileValidator, MalformedRecordValidator,
+        NContentValidator, NameConsistencyValidator, NameFormat, NameFormatValidator,
+        NameValidator, Platform, PlusLineStyle, PlusLineStyleValidator, PlusLineValidator,
+        QualityEncodingValidator, QualityStringValidator, ReadLengthValidator,
+        SequenceLengthValidator, SingleReadValidator, SingleReadValidatorMut,
+        TruncatedFileValidator, WhitespaceValidator,
     },
     validation_level::ValidationLevel,
 };
@@ -17,6 +28,7 @@ use tracing::info;
 
 pub type SingleAndPairedValidators = (
     Vec<Box<dyn SingleReadValidator>>,
+    Vec<Box<dyn SingleReadValidatorMut>>,
     Vec<Box<dyn PairedReadValidator>>,
 );
 
@@ -85,19 +97,36 @@ impl FromStr for LintMode {
     }
 }
 
+/// Returns the single read, stateful single read, and paired read validators up to the given
+/// levels that are not disabled, using the given validator options.
 pub fn filter_validators(
     single_read_validation_level: ValidationLevel,
     paired_read_validation_level: Option<ValidationLevel>,
     disabled_validators: &[String],
+    config: &Config,
 ) -> SingleAndPairedValidators {
     info!("disabled validators: {:?}", disabled_validators);
 
-    let single_read_validators =
-        filter_single_read_validators(single_read_validation_level, disabled_validators);
+    let single_read_validators = filter_configured_single_read_validators(
+        single_read_validation_level,
+        disabled_validators,
+        config,
+    );
+
+    let single_read_validators_mut = filter_single_read_validators_mut(
+        single_read_validation_level,
+        disabled_validators,
+        config,
+    );
 
     let validators: Vec<String> = single_read_validators
         .iter()
         .map(|v| format!("[{}] {}", v.code(), v.name()))
+        .chain(
+            single_read_validators_mut
+                .iter()
+                .map(|v| format!("[{}] {}", v.code(), v.name())),
+        )
         .collect();
 
     info!("enabled single read validators: {:?}", validators);
@@ -113,20 +142,37 @@ pub fn filter_validators(
 
     info!("enabled paired read validators: {:?}", validators);
 
-    (single_read_validators, paired_read_validators)
+    (
+        single_read_validators,
+        single_read_validators_mut,
+        paired_read_validators,
+    )
+}
+
+/// Returns the single read validators up to the given level that are not disabled.
+pub fn filter_single_read_validators(
+    validation_level: ValidationLevel,
+    disabled_validators: &[String],
+) -> Vec<Box<dyn SingleReadValidator>> {
+    filter_configured_single_read_validators(
+        validation_level,
+        disabled_validators,
+        &Config::default(),
+    )
 }
 
-fn filter_single_read_validators(
+fn filter_configured_single_read_validators(
     validation_level: ValidationLevel,
     disabled_validators: &[String],
+    config: &Config,
 ) -> Vec<Box<dyn SingleReadValidator>> {
     let single_read_validators: Vec<Box<dyn SingleReadValidator>> = vec![
         Box::new(NameValidator),
         Box::new(CompleteValidator),
-        Box::new(AlphabetValidator::default()),
+        Box::new(config.alphabet_validator()),
         Box::new(PlusLineValidator),
         Box::new(ConsistentSeqQualValidator),
-        Box::new(QualityStringValidator),
+        Box::new(config.quality_string_validator()),
     ];
 
     single_read_validators
@@ -136,11 +182,34 @@ fn filter_single_read_validators(
         .collect()
 }
 
+/// Returns the stateful single read validators up to the given level that are not disabled,
+/// using the given validator options.
+///
+/// Stateful validators keep state for a single file, so a new set is needed for each input.
+pub fn filter_single_read_validators_mut(
+    validation_level: ValidationLevel,
+    disabled_validators: &[String],
+    config: &Config,
+) -> Vec<Box<dyn SingleReadValidatorMut>> {
+    let single_read_validators: [Option<Box<dyn SingleReadValidatorMut>>; 2] = [
+        Some(Box::new(EmptyFileValidator::default())),
+        config.adapter_content_validator().map(|v| Box::new(v) as _),
+    ];
+
+    single_read_validators
+        .into_iter()
+        .flatten()
+        .filter(|v| v.level() <= validation_level)
+        .filter(|v| !disabled_validators.contains(&v.code().to_string()))
+        .collect()
+}
+
 fn filter_paired_read_validators(
     validation_level: ValidationLevel,
     disabled_validators: &[String],
 ) -> Vec<Box<dyn PairedReadValidator>> {
-    let paired_read_validators: Vec<Box<dyn PairedReadValidator>> = vec![Box::new(NamesValidator)];
+    let paired_read_validators: Vec<Box<dyn PairedReadValidator>> =
+        vec![Box::new(NamesValidator), Box::new(IdenticalMatesValidator)];
 
     paired_read_validators
         .into_iter()
@@ -155,17 +224,48 @@ mod tests {
 
     #[test]
     fn test_filter_validators() {
-        let (single_read_validators, paired_read_validators) =
-            filter_validators(ValidationLevel::High, None, &[]);
+        let (single_read_validators, single_read_validators_mut, paired_read_validators) =
+            filter_validators(ValidationLevel::High, None, &[], &Config::default());
 
         assert_eq!(single_read_validators.len(), 6);
+        assert_eq!(single_read_validators_mut.len(), 1);
         assert_eq!(paired_read_validators.len(), 0);
 
-        let (single_read_validators, paired_read_validators) =
-            filter_validators(ValidationLevel::High, Some(ValidationLevel::High), &[]);
+        let (single_read_validators, single_read_validators_mut, paired_read_validators) =
+            filter_validators(
+                ValidationLevel::High,
+                Some(ValidationLevel::High),
+                &[],
+                &Config::default(),
+            );
 
         assert_eq!(single_read_validators.len(), 6);
-        assert_eq!(paired_read_validators.len(), 1);
+        assert_eq!(single_read_validators_mut.len(), 1);
+        assert_eq!(paired_read_validators.len(), 2);
+    }
+
+    #[test]
+    fn test_filter_validators_with_config() {
+        use crate::fastq::Record;
+
+        let record = Record::new("@r", "ACGU", "+", "IIII");
+
+        let (single_read_validators, _, _) =
+            filter_validators(ValidationLevel::High, None, &[], &Config::default());
+
+        assert!(single_read_validators
+            .iter()
+            .any(|v| v.validate(&record).is_err()));
+
+        let mut config = Config::default();
+        config.set("S002.alphabet=ACGTNU").unwrap();
+
+        let (single_read_validators, _, _) =
+            filter_validators(ValidationLevel::High, None, &[], &config);
+
+        assert!(single_read_validators
+            .iter()
+            .all(|v| v.validate(&record).is_ok()));
     }
 
     #[test]
@@ -193,6 +293,29 @@ mod tests {
         assert!(validators.iter().find(|v| v.code() == "S001").is_none());
     }
 
+    #[test]
+    fn test_filter_single_read_validators_mut() {
+        let validators =
+            filter_single_read_validators_mut(ValidationLevel::High, &[], &Config::default());
+
+        assert_eq!(validators.len(), 1);
+        assert_eq!(validators[0].code(), "S014");
+
+        let mut config = Config::default();
+        config.set("S011.max_rate=0.1").unwrap();
+
+        let validators = filter_single_read_validators_mut(ValidationLevel::High, &[], &config);
+
+        assert_eq!(validators.len(), 2);
+        assert_eq!(validators[1].code(), "S011");
+
+        let disabled_validators = vec![String::from("S011")];
+        let validators =
+            filter_single_read_validators_mut(ValidationLevel::High, &disabled_validators, &config);
+
+        assert_eq!(validators.len(), 1);
+    }
+
     #[test]
     fn test_filter_paired_read_validators() {
         let disabled_validators = Vec::new();
@@ -201,10 +324,16 @@ mod tests {
 
         assert_eq!(validators.len(), 0);
 
-        let validators = filter_paired_read_validators(ValidationLevel::High, &disabled_validators);
+        let validators =
+            filter_paired_read_validators(ValidationLevel::Medium, &disabled_validators);
 
         assert_eq!(validators.len(), 1);
         assert_eq!(validators[0].name(), "NamesValidator");
+
+        let validators = filter_paired_read_validators(ValidationLevel::High, &disabled_validators);
+
+        assert_eq!(validators.len(), 2);
+        assert_eq!(validators[1].name(), "IdenticalMatesValidator");
     }
 
     #[test]
@@ -213,7 +342,7 @@ mod tests {
 
         let validators = filter_paired_read_validators(ValidationLevel::High, &disabled_validators);
 
-        assert_eq!(validators.len(), 0);
+        assert_eq!(validators.len(), 1);
         assert!(validators.iter().find(|v| v.code() == "P001").is_none());
     }
 }
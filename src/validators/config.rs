@@ -0,0 +1,229 @@
+use std::{error, fmt};
+
+use super::{AdapterContentValidator, AlphabetValidator, Platform, QualityStringValidator};
+use crate::stats::{default_adapters, Adapter};
+
+/// Options that parameterize individual validators.
+///
+/// An option is set by the code of its validator and a key, e.g., `S002.alphabet=ACGTNU`. Unset
+/// options use the default configuration of their validators.
+///
+/// # Examples
+///
+/// ```
+/// use fq::validators::{Config, Platform};
+///
+/// let mut config = Config::default();
+/// config.set("S002.alphabet=ACGTNU")?;
+/// config.set("S006.platform=pacbio")?;
+///
+/// assert_eq!(config.alphabet.as_deref(), Some(&b"AaCcGgTtNnUu"[..]));
+/// assert_eq!(config.platform, Some(Platform::PacBio));
+/// # Ok::<(), fq::validators::ParseOptionError>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    /// [S002] The characters allowed in the sequence line, case-insensitive.
+    pub alphabet: Option<Vec<u8>>,
+    /// [S006] The platform of the quality score range.
+    pub platform: Option<Platform>,
+    /// [S010] The maximum fraction of N bases in a read.
+    pub max_n_fraction: Option<f64>,
+    /// [S011] The maximum fraction of reads containing an adapter.
+    pub max_adapter_rate: Option<f64>,
+    /// [S011] Adapters checked in addition to the default adapters.
+    pub adapters: Vec<Adapter>,
+    /// [S013] The minimum sequence length.
+    pub min_sequence_length: Option<usize>,
+    /// [S013] The maximum sequence length.
+    pub max_sequence_length: Option<usize>,
+    /// [S017] The maximum fraction of reads with a duplicate sequence.
+    pub max_duplicate_rate: Option<f64>,
+}
+
+impl Config {
+    /// Sets an option from a string in the form `<code>.<key>=<value>`.
+    ///
+    /// The supported options are:
+    ///
+    ///   * `S002.alphabet`: the characters allowed in the sequence line, case-insensitive.
+    ///   * `S006.platform`: `illumina-1.8`, `pacbio`, or `ont`.
+    ///   * `S010.max_fraction`: the maximum N fraction, from 0.0 to 1.0.
+    ///   * `S011.max_rate`: the maximum adapter rate, from 0.0 to 1.0.
+    ///   * `S013.min_length` and `S013.max_length`: the sequence length bounds.
+    ///   * `S017.max_rate`: the maximum duplicate rate, from 0.0 to 1.0.
+    pub fn set(&mut self, s: &str) -> Result<(), ParseOptionError> {
+        let (name, value) = s
+            .split_once('=')
+            .ok_or_else(|| ParseOptionError::Invalid(s.into()))?;
+
+        let invalid_value = || ParseOptionError::InvalidValue(name.into(), value.into());
+
+        match name {
+            "S002.alphabet" => {
+                if value.is_empty() {
+                    return Err(invalid_value());
+                }
+
+                let alphabet = value
+                    .bytes()
+                    .flat_map(|b| [b.to_ascii_uppercase(), b.to_ascii_lowercase()])
+                    .collect();
+
+                self.alphabet = Some(alphabet);
+            }
+            "S006.platform" => {
+                self.platform = Some(value.parse().map_err(|_| invalid_value())?);
+            }
+            "S010.max_fraction" => {
+                self.max_n_fraction = Some(parse_fraction(value).ok_or_else(invalid_value)?);
+            }
+            "S011.max_rate" => {
+                self.max_adapter_rate = Some(parse_fraction(value).ok_or_else(invalid_value)?);
+            }
+            "S013.min_length" => {
+                self.min_sequence_length = Some(value.parse().map_err(|_| invalid_value())?);
+            }
+            "S013.max_length" => {
+                self.max_sequence_length = Some(value.parse().map_err(|_| invalid_value())?);
+            }
+            "S017.max_rate" => {
+                self.max_duplicate_rate = Some(parse_fraction(value).ok_or_else(invalid_value)?);
+            }
+            _ => return Err(ParseOptionError::UnknownOption(name.into())),
+        }
+
+        Ok(())
+    }
+
+    /// Returns the alphabet validator (S002) with the configured alphabet.
+    pub fn alphabet_validator(&self) -> AlphabetValidator {
+        match &self.alphabet {
+            Some(alphabet) => AlphabetValidator::new(alphabet),
+            None => AlphabetValidator::default(),
+        }
+    }
+
+    /// Returns the quality string validator (S006) with the configured platform.
+    pub fn quality_string_validator(&self) -> QualityStringValidator {
+        QualityStringValidator::new(self.platform)
+    }
+
+    /// Returns a new adapter content validator (S011) with the default and configured adapters,
+    /// if a maximum adapter rate is set.
+    pub fn adapter_content_validator(&self) -> Option<AdapterContentValidator> {
+        self.max_adapter_rate.map(|max_rate| {
+            let mut adapters = default_adapters();
+            adapters.extend(self.adapters.iter().cloned());
+            AdapterContentValidator::new(adapters, max_rate)
+        })
+    }
+}
+
+fn parse_fraction(s: &str) -> Option<f64> {
+    s.parse().ok().filter(|n| (0.0..=1.0).contains(n))
+}
+
+/// An error returned when a validator option fails to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseOptionError {
+    /// The option is not in the form `<code>.<key>=<value>`.
+    Invalid(String),
+    /// No validator has the option.
+    UnknownOption(String),
+    /// The value is invalid for the option.
+    InvalidValue(String, String),
+}
+
+impl error::Error for ParseOptionError {}
+
+impl fmt::Display for ParseOptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(s) => write!(
+                f,
+                "invalid validator option: expected <code>.<key>=<value>, got '{}'",
+                s
+            ),
+            Self::UnknownOption(name) => write!(f, "unknown validator option: {}", name),
+            Self::InvalidValue(name, value) => {
+                write!(f, "invalid value for {}: '{}'", name, value)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set() {
+        let mut config = Config::default();
+
+        config.set("S002.alphabet=ACGTNU").unwrap();
+        assert_eq!(config.alphabet.as_deref(), Some(&b"AaCcGgTtNnUu"[..]));
+
+        config.set("S006.platform=ont").unwrap();
+        assert_eq!(config.platform, Some(Platform::Ont));
+
+        config.set("S010.max_fraction=0.5").unwrap();
+        assert_eq!(config.max_n_fraction, Some(0.5));
+
+        config.set("S011.max_rate=0.05").unwrap();
+        assert_eq!(config.max_adapter_rate, Some(0.05));
+
+        config.set("S013.min_length=1").unwrap();
+        config.set("S013.max_length=1000").unwrap();
+        assert_eq!(config.min_sequence_length, Some(1));
+        assert_eq!(config.max_sequence_length, Some(1000));
+
+        config.set("S017.max_rate=0.2").unwrap();
+        assert_eq!(config.max_duplicate_rate, Some(0.2));
+    }
+
+    #[test]
+    fn test_set_with_invalid_option() {
+        let mut config = Config::default();
+
+        assert_eq!(
+            config.set("S002.alphabet"),
+            Err(ParseOptionError::Invalid(String::from("S002.alphabet")))
+        );
+
+        assert_eq!(
+            config.set("S003.alphabet=ACGT"),
+            Err(ParseOptionError::UnknownOption(String::from(
+                "S003.alphabet"
+            )))
+        );
+
+        assert_eq!(
+            config.set("S002.alphabet="),
+            Err(ParseOptionError::InvalidValue(
+                String::from("S002.alphabet"),
+                String::new()
+            ))
+        );
+
+        assert!(config.set("S006.platform=illumina").is_err());
+        assert!(config.set("S010.max_fraction=1.5").is_err());
+        assert!(config.set("S013.min_length=-1").is_err());
+
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_alphabet_validator() {
+        use crate::{fastq::Record, validators::SingleReadValidator};
+
+        let record = Record::new("@r", "ACGU", "+", "IIII");
+
+        let config = Config::default();
+        assert!(config.alphabet_validator().validate(&record).is_err());
+
+        let mut config = Config::default();
+        config.set("S002.alphabet=ACGTNU").unwrap();
+        assert!(config.alphabet_validator().validate(&record).is_ok());
+    }
+}
@@ -0,0 +1,359 @@
+use std::fmt;
+
+use super::ValidationLevel;
+
+/// The kind of reads a validator checks.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Kind {
+    /// Records from a single read.
+    Single,
+    /// Records from both reads of a pair.
+    Paired,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Single => f.pad("single"),
+            Self::Paired => f.pad("paired"),
+        }
+    }
+}
+
+/// A description of a validator, e.g., to list which validators can be disabled.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Description<'a> {
+    pub code: &'a str,
+    pub name: &'a str,
+    pub level: ValidationLevel,
+    pub kind: Kind,
+    pub summary: &'a str,
+}
+
+impl Description<'static> {
+    const fn new(
+        code: &'static str,
+        name: &'static str,
+        level: ValidationLevel,
+        kind: Kind,
+        summary: &'static str,
+    ) -> Self {
+        Self {
+            code,
+            name,
+            level,
+            kind,
+            summary,
+        }
+    }
+}
+
+/// Returns the descriptions of all validators, ordered by code.
+///
+/// This includes validators that are only used when requested, e.g., with an expected value.
+///
+/// # Examples
+///
+/// ```
+/// use fq::validators::{self, Kind, ValidationLevel};
+///
+/// let descriptions = validators::descriptions();
+///
+/// assert_eq!(descriptions[0].code, "P001");
+/// assert_eq!(descriptions[0].name, "NamesValidator");
+/// assert_eq!(descriptions[0].level, ValidationLevel::Medium);
+/// assert_eq!(descriptions[0].kind, Kind::Paired);
+/// ```
+pub fn descriptions() -> &'static [Description<'static>] {
+    use self::{Kind::*, ValidationLevel::*};
+
+    const DESCRIPTIONS: [Description<'static>; 22] = [
+        Description::new(
+            "P001",
+            "NamesValidator",
+            Medium,
+            Paired,
+            "Each paired read name is the same, excluding interleave.",
+        ),
+        Description::new(
+            "P002",
+            "IdenticalMatesValidator",
+            High,
+            Paired,
+            "Paired records are not identical copies of each other.",
+        ),
+        Description::new(
+            "S001",
+            "PlusLineValidator",
+            Low,
+            Single,
+            "Plus line starts with a \"+\".",
+        ),
+        Description::new(
+            "S002",
+            "AlphabetValidator",
+            Medium,
+            Single,
+            "All characters in sequence line are one of \"ACGTN\", case-insensitive.",
+        ),
+        Description::new(
+            "S003",
+            "NameValidator",
+            High,
+            Single,
+            "Name line starts with an \"@\".",
+        ),
+        Description::new(
+            "S004",
+            "CompleteValidator",
+            Low,
+            Single,
+            "All four record lines (name, sequence, plus line, and quality) are present.",
+        ),
+        Description::new(
+            "S005",
+            "ConsistentSeqQualValidator",
+            High,
+            Single,
+            "Sequence and quality lengths are the same.",
+        ),
+        Description::new(
+            "S006",
+            "QualityStringValidator",
+            Medium,
+            Single,
+            "All characters in quality line are between \"!\" and \"~\", or in the platform range.",
+        ),
+        Description::new(
+            "S007",
+            "DuplicateNameValidator",
+            High,
+            Single,
+            "All record names are unique.",
+        ),
+        Description::new(
+            "S008",
+            "ReadLengthValidator",
+            High,
+            Single,
+            "All reads have the same length.",
+        ),
+        Description::new(
+            "S009",
+            "QualityEncodingValidator",
+            High,
+            Single,
+            "The inferred quality score encoding is the expected encoding.",
+        ),
+        Description::new(
+            "S010",
+            "NContentValidator",
+            High,
+            Single,
+            "The fraction of \"N\" bases in the sequence line is at most the maximum.",
+        ),
+        Description::new(
+            "S011",
+            "AdapterContentValidator",
+            High,
+            Single,
+            "The fraction of reads containing an adapter sequence is at most the maximum.",
+        ),
+        Description::new(
+            "S012",
+            "NameFormatValidator",
+            High,
+            Single,
+            "Name line is in the expected read name format.",
+        ),
+        Description::new(
+            "S013",
+            "SequenceLengthValidator",
+            High,
+            Single,
+            "Sequence length is within the minimum and maximum lengths.",
+        ),
+        Description::new(
+            "S014",
+            "EmptyFileValidator",
+            Low,
+            Single,
+            "File contains at least one record.",
+        ),
+        Description::new(
+            "S015",
+            "TruncatedFileValidator",
+            Low,
+            Single,
+            "File does not end mid-record.",
+        ),
+        Description::new(
+            "S016",
+            "WhitespaceValidator",
+            Medium,
+            Single,
+            "No line has a carriage return or trailing whitespace.",
+        ),
+        Description::new(
+            "S017",
+            "DuplicateSequenceValidator",
+            High,
+            Single,
+            "The fraction of reads with an exact duplicate sequence is at most the maximum.",
+        ),
+        Description::new(
+            "S018",
+            "PlusLineStyleValidator",
+            Medium,
+            Single,
+            "Plus line is either empty or repeats the name, in the expected style.",
+        ),
+        Description::new(
+            "S019",
+            "MalformedRecordValidator",
+            Low,
+            Single,
+            "Record has the line structure of a FASTQ record.",
+        ),
+        Description::new(
+            "S020",
+            "NameConsistencyValidator",
+            High,
+            Single,
+            "All read names have the instrument, run, flowcell, and lane of the first record.",
+        ),
+    ];
+
+    &DESCRIPTIONS
+}
+
+/// Returns the description of a validator by its code or name.
+///
+/// A name may omit the `Validator` suffix, e.g., `DuplicateName` for `DuplicateNameValidator`.
+///
+/// # Examples
+///
+/// ```
+/// use fq::validators;
+///
+/// assert_eq!(validators::find_description("S007").map(|d| d.code), Some("S007"));
+/// assert_eq!(
+///     validators::find_description("DuplicateNameValidator").map(|d| d.code),
+///     Some("S007")
+/// );
+/// assert_eq!(validators::find_description("DuplicateName").map(|d| d.code), Some("S007"));
+/// assert!(validators::find_description("S999").is_none());
+/// ```
+pub fn find_description(s: &str) -> Option<&'static Description<'static>> {
+    descriptions()
+        .iter()
+        .find(|d| d.code == s || d.name == s || d.name.strip_suffix("Validator") == Some(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        quality::Encoding,
+        validators::{single::*, IdenticalMatesValidator, NamesValidator, PairedReadValidator},
+    };
+
+    #[test]
+    fn test_descriptions() {
+        fn describe(
+            code: &str,
+            name: &str,
+            level: ValidationLevel,
+        ) -> (String, String, ValidationLevel) {
+            (code.into(), name.into(), level)
+        }
+
+        let single_read_validators: Vec<Box<dyn SingleReadValidator>> = vec![
+            Box::new(PlusLineValidator),
+            Box::new(AlphabetValidator::default()),
+            Box::new(NameValidator),
+            Box::new(CompleteValidator),
+            Box::new(ConsistentSeqQualValidator),
+            Box::new(QualityStringValidator::default()),
+            Box::new(NContentValidator::new(1.0)),
+            Box::new(NameFormatValidator::new(NameFormat::Casava)),
+            Box::new(SequenceLengthValidator::new(None, None)),
+            Box::new(WhitespaceValidator),
+            Box::new(MalformedRecordValidator),
+        ];
+
+        let single_read_validators_mut: Vec<Box<dyn SingleReadValidatorMut>> = vec![
+            Box::new(DuplicateNameValidator::with_max_memory(1)),
+            Box::new(ReadLengthValidator::new(None)),
+            Box::new(QualityEncodingValidator::new(Encoding::Phred33)),
+            Box::new(AdapterContentValidator::new(Vec::new(), 1.0)),
+            Box::new(EmptyFileValidator::default()),
+            Box::new(DuplicateSequenceValidator::new(1.0)),
+            Box::new(PlusLineStyleValidator::default()),
+            Box::new(NameConsistencyValidator::default()),
+        ];
+
+        let paired_read_validators: Vec<Box<dyn PairedReadValidator>> =
+            vec![Box::new(NamesValidator), Box::new(IdenticalMatesValidator)];
+
+        let truncated_file_validator = TruncatedFileValidator;
+
+        let mut expected: Vec<_> = single_read_validators
+            .iter()
+            .map(|v| describe(v.code(), v.name(), v.level()))
+            .chain(
+                single_read_validators_mut
+                    .iter()
+                    .map(|v| describe(v.code(), v.name(), v.level())),
+            )
+            .chain(
+                paired_read_validators
+                    .iter()
+                    .map(|v| describe(v.code(), v.name(), v.level())),
+            )
+            .chain([describe(
+                truncated_file_validator.code(),
+                truncated_file_validator.name(),
+                truncated_file_validator.level(),
+            )])
+            .collect();
+
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let actual: Vec<_> = descriptions()
+            .iter()
+            .map(|d| describe(d.code, d.name, d.level))
+            .collect();
+
+        assert_eq!(actual, expected);
+
+        for description in descriptions() {
+            let kind = if description.code.starts_with('P') {
+                Kind::Paired
+            } else {
+                Kind::Single
+            };
+
+            assert_eq!(description.kind, kind);
+        }
+    }
+
+    #[test]
+    fn test_find_description() {
+        for description in descriptions() {
+            assert_eq!(find_description(description.code), Some(description));
+            assert_eq!(find_description(description.name), Some(description));
+        }
+
+        assert_eq!(find_description("Alphabet").map(|d| d.code), Some("S002"));
+        assert!(find_description("s002").is_none());
+        assert!(find_description("Validator").is_none());
+        assert!(find_description("").is_none());
+    }
+
+    #[test]
+    fn test_fmt_kind() {
+        assert_eq!(Kind::Single.to_string(), "single");
+        assert_eq!(Kind::Paired.to_string(), "paired");
+    }
+}
@@ -1,17 +1,18 @@
 //! Validators that use records from paired reads.
 
+mod identical_mates;
 mod names;
 
-pub use self::names::NamesValidator;
+pub use self::{identical_mates::IdenticalMatesValidator, names::NamesValidator};
 
 use crate::{
     fastq::Record,
     validators::{Error, ValidationLevel},
 };
 
-pub trait PairedReadValidator {
-    fn code(&self) -> &'static str;
-    fn name(&self) -> &'static str;
+pub trait PairedReadValidator: Send + Sync {
+    fn code(&self) -> &str;
+    fn name(&self) -> &str;
     fn level(&self) -> ValidationLevel;
     fn validate(&self, r: &Record, s: &Record) -> Result<(), Error>;
 }
@@ -0,0 +1,80 @@
+use crate::{
+    fastq::Record,
+    validators::{Error, LineType, PairedReadValidator, ValidationLevel},
+};
+
+/// [P002] (high) Validator to check if paired records are not identical copies of each other.
+///
+/// Mates with both the same name and the same sequence almost always mean the same file was given
+/// as both read 1 and read 2.
+pub struct IdenticalMatesValidator;
+
+impl PairedReadValidator for IdenticalMatesValidator {
+    fn code(&self) -> &'static str {
+        "P002"
+    }
+
+    fn name(&self) -> &'static str {
+        "IdenticalMatesValidator"
+    }
+
+    fn level(&self) -> ValidationLevel {
+        ValidationLevel::High
+    }
+
+    fn validate(&self, r: &Record, s: &Record) -> Result<(), Error> {
+        if !r.sequence().is_empty() && r.name() == s.name() && r.sequence() == s.sequence() {
+            Err(Error::new(
+                self.code(),
+                self.name(),
+                format!(
+                    "Mates are identical: '{}'",
+                    String::from_utf8_lossy(r.name()),
+                ),
+                LineType::Sequence,
+                Some(1),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code() {
+        let validator = IdenticalMatesValidator;
+        assert_eq!(validator.code(), "P002");
+    }
+
+    #[test]
+    fn test_name() {
+        let validator = IdenticalMatesValidator;
+        assert_eq!(validator.name(), "IdenticalMatesValidator");
+    }
+
+    #[test]
+    fn test_level() {
+        let validator = IdenticalMatesValidator;
+        assert_eq!(validator.level(), ValidationLevel::High);
+    }
+
+    #[test]
+    fn test_validate() {
+        let validator = IdenticalMatesValidator;
+
+        let r = Record::new("@fqlib", "ACGT", "+", "FQLB");
+
+        let s = Record::new("@fqlib", "TGCA", "+", "BLQF");
+        assert!(validator.validate(&r, &s).is_ok());
+
+        let s = Record::new("@fqlib:2", "ACGT", "+", "FQLB");
+        assert!(validator.validate(&r, &s).is_ok());
+
+        let s = Record::new("@fqlib", "ACGT", "+", "FQLB");
+        assert!(validator.validate(&r, &s).is_err());
+    }
+}
@@ -0,0 +1,566 @@
+use std::{error, fmt};
+
+use regex::bytes::Regex;
+
+use toml::Value;
+
+use super::{find_description, Description, Error, Kind, LineType, ValidationLevel};
+use crate::fastq::Record;
+
+const CHECK_KEYS: [&str; 4] = [
+    "name_pattern",
+    "required_comment_fields",
+    "read_lengths",
+    "banned_subsequences",
+];
+
+#[derive(Clone, Debug)]
+enum Check {
+    NamePattern(Regex),
+    RequiredCommentFields(Vec<String>),
+    ReadLengths(Vec<usize>),
+    BannedSubsequences(Vec<Vec<u8>>),
+}
+
+/// A user-defined single read validator.
+///
+/// A rule has a code, a name, a level, and exactly one check:
+///
+///   * `name_pattern`: the name line, without the `@` prefix, matches a regular expression.
+///   * `required_comment_fields`: the comment has whitespace-separated fields starting with each
+///     of the given prefixes, e.g., `length=`.
+///   * `read_lengths`: the sequence length is one of the given lengths.
+///   * `banned_subsequences`: the sequence does not contain any of the given subsequences,
+///     case-insensitive.
+///
+/// Rules are read from a TOML document using [`parse_rules`].
+#[derive(Clone, Debug)]
+pub struct RuleValidator {
+    code: String,
+    name: String,
+    level: ValidationLevel,
+    summary: String,
+    check: Check,
+}
+
+impl RuleValidator {
+    /// Returns the description of the rule, e.g., to list it with the built-in validators.
+    pub fn description(&self) -> Description<'_> {
+        Description {
+            code: &self.code,
+            name: &self.name,
+            level: self.level,
+            kind: Kind::Single,
+            summary: &self.summary,
+        }
+    }
+
+    fn build_error(&self, message: String, line_type: LineType, col_no: Option<usize>) -> Error {
+        Error::new(&self.code, &self.name, message, line_type, col_no)
+    }
+}
+
+impl super::SingleReadValidator for RuleValidator {
+    fn code(&self) -> &str {
+        &self.code
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn level(&self) -> ValidationLevel {
+        self.level
+    }
+
+    fn validate(&self, r: &Record) -> Result<(), Error> {
+        match &self.check {
+            Check::NamePattern(pattern) => {
+                let name = r.name().strip_prefix(b"@").unwrap_or(r.name());
+
+                if !pattern.is_match(name) {
+                    return Err(self.build_error(
+                        format!("Name does not match pattern '{}'", pattern),
+                        LineType::Name,
+                        None,
+                    ));
+                }
+            }
+            Check::RequiredCommentFields(prefixes) => {
+                let comment = r
+                    .name()
+                    .iter()
+                    .position(|&b| b == b' ')
+                    .map(|i| &r.name()[i + 1..])
+                    .unwrap_or_default();
+
+                let fields: Vec<_> = comment
+                    .split(|&b| b == b' ' || b == b'\t')
+                    .filter(|field| !field.is_empty())
+                    .collect();
+
+                for prefix in prefixes {
+                    if !fields
+                        .iter()
+                        .any(|field| field.starts_with(prefix.as_bytes()))
+                    {
+                        return Err(self.build_error(
+                            format!("Missing comment field '{}'", prefix),
+                            LineType::Name,
+                            None,
+                        ));
+                    }
+                }
+            }
+            Check::ReadLengths(lengths) => {
+                let len = r.sequence().len();
+
+                if !lengths.contains(&len) {
+                    let expected: Vec<_> = lengths.iter().map(|n| n.to_string()).collect();
+
+                    return Err(self.build_error(
+                        format!(
+                            "Invalid sequence length (expected one of {}, got {})",
+                            expected.join(", "),
+                            len
+                        ),
+                        LineType::Sequence,
+                        None,
+                    ));
+                }
+            }
+            Check::BannedSubsequences(subsequences) => {
+                let sequence = r.sequence();
+
+                for subsequence in subsequences {
+                    if let Some(i) = find_ignore_ascii_case(sequence, subsequence) {
+                        return Err(self.build_error(
+                            format!(
+                                "Sequence contains banned subsequence '{}'",
+                                String::from_utf8_lossy(subsequence)
+                            ),
+                            LineType::Sequence,
+                            Some(i + 1),
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn find_ignore_ascii_case(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return None;
+    }
+
+    haystack
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle))
+}
+
+/// An error returned when rules fail to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseRulesError {
+    /// The document is not valid TOML.
+    Invalid(toml::de::Error),
+    /// The document has a key other than `rules`, or `rules` is not an array of tables.
+    InvalidDocument(String),
+    /// A rule is invalid. The rule is given by its 1-based index.
+    InvalidRule(usize, String),
+}
+
+impl error::Error for ParseRulesError {}
+
+impl fmt::Display for ParseRulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(e) => write!(f, "invalid rules: {}", e),
+            Self::InvalidDocument(message) => write!(f, "invalid rules: {}", message),
+            Self::InvalidRule(i, message) => write!(f, "invalid rule {}: {}", i, message),
+        }
+    }
+}
+
+/// Parses user-defined validators from a TOML document.
+///
+/// Each rule is a table in the `rules` array with the keys
+///
+///   * `name` (required): the validator name, unique among all validators;
+///   * `code` (optional): the validator code, unique among all validators, defaulting to `U001`,
+///     `U002`, etc., by position;
+///   * `level` (optional): `low`, `medium`, or `high` (default); and
+///   * exactly one check key (see [`RuleValidator`]).
+///
+/// # Examples
+///
+/// ```
+/// use fq::{
+///     fastq::Record,
+///     validators::{self, SingleReadValidator},
+/// };
+///
+/// let rules = validators::parse_rules(
+///     r#"
+/// [[rules]]
+/// name = "IlluminaName"
+/// name_pattern = '^\S+:\d+:\S+:\d+:\d+:\d+:\d+'
+///
+/// [[rules]]
+/// code = "X001"
+/// name = "AllowedReadLength"
+/// level = "low"
+/// read_lengths = [100, 150]
+/// "#,
+/// )?;
+///
+/// assert_eq!(rules[0].code(), "U001");
+/// assert_eq!(rules[1].code(), "X001");
+///
+/// let record = Record::new("@fqlib:1:FQ1:2:2104:15343:197393", "ACGT", "+", "IIII");
+/// assert!(rules[0].validate(&record).is_ok());
+/// assert!(rules[1].validate(&record).is_err());
+/// # Ok::<(), validators::ParseRulesError>(())
+/// ```
+pub fn parse_rules(s: &str) -> Result<Vec<RuleValidator>, ParseRulesError> {
+    let document: toml::value::Table = toml::from_str(s).map_err(ParseRulesError::Invalid)?;
+
+    if let Some(key) = document.keys().find(|key| *key != "rules") {
+        return Err(ParseRulesError::InvalidDocument(format!(
+            "unknown key '{}'",
+            key
+        )));
+    }
+
+    let tables = match document.get("rules") {
+        Some(Value::Array(tables)) => tables.as_slice(),
+        Some(_) => {
+            return Err(ParseRulesError::InvalidDocument(String::from(
+                "expected rules to be an array of tables",
+            )))
+        }
+        None => &[],
+    };
+
+    let mut rules: Vec<RuleValidator> = Vec::with_capacity(tables.len());
+
+    for (i, table) in tables.iter().enumerate() {
+        let invalid_rule = |message: String| ParseRulesError::InvalidRule(i + 1, message);
+
+        let rule = parse_rule(table, i).map_err(invalid_rule)?;
+
+        if find_description(&rule.code).is_some() || rules.iter().any(|r| r.code == rule.code) {
+            return Err(invalid_rule(format!("duplicate code '{}'", rule.code)));
+        }
+
+        if find_description(&rule.name).is_some() || rules.iter().any(|r| r.name == rule.name) {
+            return Err(invalid_rule(format!("duplicate name '{}'", rule.name)));
+        }
+
+        rules.push(rule);
+    }
+
+    Ok(rules)
+}
+
+fn parse_rule(table: &Value, i: usize) -> Result<RuleValidator, String> {
+    let entries = table
+        .as_table()
+        .ok_or_else(|| String::from("expected a table"))?;
+
+    let mut checks = Vec::new();
+
+    for (key, value) in entries {
+        match key.as_str() {
+            "code" | "name" | "level" => {}
+            key if CHECK_KEYS.contains(&key) => checks.push((key, value)),
+            _ => return Err(format!("unknown key '{}'", key)),
+        }
+    }
+
+    let (key, value) = match checks[..] {
+        [check] => check,
+        _ => return Err(format!("expected exactly one of {}", CHECK_KEYS.join(", "))),
+    };
+
+    let code = match table.get("code") {
+        Some(value) => parse_name(value, "code")?,
+        None => format!("U{:03}", i + 1),
+    };
+
+    let name = match table.get("name") {
+        Some(value) => parse_name(value, "name")?,
+        None => return Err(String::from("missing name")),
+    };
+
+    let level = match table.get("level") {
+        Some(value) => value
+            .as_str()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| String::from("expected level to be low, medium, or high"))?,
+        None => ValidationLevel::High,
+    };
+
+    let (check, summary) = match key {
+        "name_pattern" => {
+            let s = value
+                .as_str()
+                .ok_or_else(|| String::from("expected name_pattern to be a string"))?;
+
+            let pattern = Regex::new(s).map_err(|e| format!("invalid name_pattern: {}", e))?;
+
+            (
+                Check::NamePattern(pattern),
+                format!("Checks if the name line matches '{}'", s),
+            )
+        }
+        "required_comment_fields" => {
+            let fields = parse_strings(value, key)?;
+            let summary = format!("Checks if the comment has the fields {}", fields.join(", "));
+            (Check::RequiredCommentFields(fields), summary)
+        }
+        "read_lengths" => {
+            let lengths = value
+                .as_array()
+                .filter(|values| !values.is_empty())
+                .and_then(|values| {
+                    values
+                        .iter()
+                        .map(|v| v.as_integer().and_then(|n| usize::try_from(n).ok()))
+                        .collect::<Option<Vec<_>>>()
+                })
+                .ok_or_else(|| {
+                    String::from("expected read_lengths to be a non-empty array of lengths")
+                })?;
+
+            let expected: Vec<_> = lengths.iter().map(|n| n.to_string()).collect();
+            let summary = format!(
+                "Checks if the sequence length is one of {}",
+                expected.join(", ")
+            );
+
+            (Check::ReadLengths(lengths), summary)
+        }
+        "banned_subsequences" => {
+            let subsequences = parse_strings(value, key)?;
+            let summary = format!(
+                "Checks if the sequence does not contain {}",
+                subsequences.join(", ")
+            );
+
+            let subsequences = subsequences.into_iter().map(String::into_bytes).collect();
+
+            (Check::BannedSubsequences(subsequences), summary)
+        }
+        _ => unreachable!(),
+    };
+
+    Ok(RuleValidator {
+        code,
+        name,
+        level,
+        summary,
+        check,
+    })
+}
+
+// Parses a code or name, which are non-empty and have no whitespace.
+fn parse_name(value: &Value, key: &str) -> Result<String, String> {
+    value
+        .as_str()
+        .filter(|s| !s.is_empty() && !s.contains(char::is_whitespace))
+        .map(String::from)
+        .ok_or_else(|| {
+            format!(
+                "expected {} to be a non-empty string without whitespace",
+                key
+            )
+        })
+}
+
+// Parses a non-empty array of non-empty strings.
+fn parse_strings(value: &Value, key: &str) -> Result<Vec<String>, String> {
+    value
+        .as_array()
+        .filter(|values| !values.is_empty())
+        .and_then(|values| {
+            values
+                .iter()
+                .map(|v| v.as_str().filter(|s| !s.is_empty()).map(String::from))
+                .collect()
+        })
+        .ok_or_else(|| format!("expected {} to be a non-empty array of strings", key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validators::SingleReadValidator;
+
+    fn parse_rule(s: &str) -> RuleValidator {
+        let mut rules = parse_rules(s).unwrap();
+        assert_eq!(rules.len(), 1);
+        rules.remove(0)
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rules = parse_rules(
+            r#"
+[[rules]]
+name = "A"
+name_pattern = "^r"
+
+[[rules]]
+code = "X001"
+name = "B"
+level = "medium"
+read_lengths = [4]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(rules.len(), 2);
+
+        assert_eq!(rules[0].code(), "U001");
+        assert_eq!(rules[0].name(), "A");
+        assert_eq!(rules[0].level(), ValidationLevel::High);
+
+        let description = rules[1].description();
+        assert_eq!(description.code, "X001");
+        assert_eq!(description.name, "B");
+        assert_eq!(description.level, ValidationLevel::Medium);
+        assert_eq!(description.kind, Kind::Single);
+        assert_eq!(
+            description.summary,
+            "Checks if the sequence length is one of 4"
+        );
+
+        assert!(parse_rules("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_rules_with_invalid_rules() {
+        assert!(matches!(
+            parse_rules("[[rules]]\nname = "),
+            Err(ParseRulesError::Invalid(_))
+        ));
+
+        assert!(matches!(
+            parse_rules("version = 1"),
+            Err(ParseRulesError::InvalidDocument(_))
+        ));
+
+        assert!(matches!(
+            parse_rules("[rules]\nname = \"A\""),
+            Err(ParseRulesError::InvalidDocument(_))
+        ));
+
+        assert_eq!(
+            parse_rules("[[rules]]\nname_pattern = \"^r\"").unwrap_err(),
+            ParseRulesError::InvalidRule(1, String::from("missing name"))
+        );
+
+        let invalid_rules = [
+            "name = \"A\"",
+            "name = \"A\"\nread_lengths = [1]\nname_pattern = \"^r\"",
+            "name = \"A\"\nread_lengths = [-1]",
+            "name = \"A\"\nread_lengths = []",
+            "name = \"A\"\nname_pattern = \"(\"",
+            "name = \"A\"\nbanned_subsequences = [\"\"]",
+            "name = \"A\"\nrequired_comment_fields = \"length=\"",
+            "name = \"A\"\nlevel = \"max\"\nread_lengths = [1]",
+            "name = \"A B\"\nread_lengths = [1]",
+            "name = \"A\"\nread_length = [1]",
+            "code = \"S001\"\nname = \"A\"\nread_lengths = [1]",
+            "name = \"PlusLine\"\nread_lengths = [1]",
+        ];
+
+        for rule in invalid_rules {
+            let s = format!("[[rules]]\n{}", rule);
+
+            assert!(
+                matches!(parse_rules(&s), Err(ParseRulesError::InvalidRule(1, _))),
+                "{}",
+                rule
+            );
+        }
+
+        let s = "[[rules]]\nname = \"A\"\nread_lengths = [1]\n[[rules]]\nname = \"A\"\nread_lengths = [2]";
+
+        assert_eq!(
+            parse_rules(s).unwrap_err(),
+            ParseRulesError::InvalidRule(2, String::from("duplicate name 'A'"))
+        );
+    }
+
+    #[test]
+    fn test_validate_with_name_pattern() {
+        let rule = parse_rule("[[rules]]\nname = \"A\"\nname_pattern = '^r\\d+$'");
+
+        assert!(rule.validate(&Record::new("@r0", "A", "+", "I")).is_ok());
+
+        let e = rule
+            .validate(&Record::new("@x0", "A", "+", "I"))
+            .unwrap_err();
+
+        assert_eq!(e.code, "U001");
+        assert_eq!(e.name, "A");
+        assert_eq!(e.message, "Name does not match pattern '^r\\d+$'");
+    }
+
+    #[test]
+    fn test_validate_with_required_comment_fields() {
+        let rule = parse_rule(
+            "[[rules]]\nname = \"A\"\nrequired_comment_fields = [\"length=\", \"BC:Z:\"]",
+        );
+
+        let record = Record::new("@r0 length=1\tBC:Z:ACGT", "A", "+", "I");
+        assert!(rule.validate(&record).is_ok());
+
+        let record = Record::new("@r0 length=1", "A", "+", "I");
+        let e = rule.validate(&record).unwrap_err();
+        assert_eq!(e.message, "Missing comment field 'BC:Z:'");
+
+        let record = Record::new("@r0", "A", "+", "I");
+        let e = rule.validate(&record).unwrap_err();
+        assert_eq!(e.message, "Missing comment field 'length='");
+    }
+
+    #[test]
+    fn test_validate_with_read_lengths() {
+        let rule = parse_rule("[[rules]]\nname = \"A\"\nread_lengths = [2, 4]");
+
+        assert!(rule.validate(&Record::new("@r0", "AC", "+", "II")).is_ok());
+        assert!(rule
+            .validate(&Record::new("@r0", "ACGT", "+", "IIII"))
+            .is_ok());
+
+        let e = rule
+            .validate(&Record::new("@r0", "ACG", "+", "III"))
+            .unwrap_err();
+
+        assert_eq!(
+            e.message,
+            "Invalid sequence length (expected one of 2, 4, got 3)"
+        );
+    }
+
+    #[test]
+    fn test_validate_with_banned_subsequences() {
+        let rule = parse_rule("[[rules]]\nname = \"A\"\nbanned_subsequences = [\"GGGG\", \"aaa\"]");
+
+        assert!(rule
+            .validate(&Record::new("@r0", "ACGGGAA", "+", "IIIIIII"))
+            .is_ok());
+
+        let e = rule
+            .validate(&Record::new("@r0", "ACaAAT", "+", "IIIIII"))
+            .unwrap_err();
+
+        assert_eq!(e.message, "Sequence contains banned subsequence 'aaa'");
+        assert_eq!(e.col_no, Some(3));
+    }
+}
@@ -1,17 +1,47 @@
 //! Validators that use records from a single read.
 
+mod adapter_content;
 mod alphabet;
 mod complete;
 mod consistent_seq_qual;
 mod duplicate_name;
+mod duplicate_sequence;
+mod empty_file;
+mod malformed_record;
+mod n_content;
 mod name;
+mod name_consistency;
+mod name_format;
 mod plus_line;
+mod plus_line_style;
+mod quality_encoding;
 mod quality_string;
+mod read_length;
+mod sequence_length;
+mod truncated_file;
+mod whitespace;
 
 pub use self::{
-    alphabet::AlphabetValidator, complete::CompleteValidator,
-    consistent_seq_qual::ConsistentSeqQualValidator, duplicate_name::DuplicateNameValidator,
-    name::NameValidator, plus_line::PlusLineValidator, quality_string::QualityStringValidator,
+    adapter_content::AdapterContentValidator,
+    alphabet::AlphabetValidator,
+    complete::CompleteValidator,
+    consistent_seq_qual::ConsistentSeqQualValidator,
+    duplicate_name::DuplicateNameValidator,
+    duplicate_sequence::DuplicateSequenceValidator,
+    empty_file::EmptyFileValidator,
+    malformed_record::MalformedRecordValidator,
+    n_content::NContentValidator,
+    name::NameValidator,
+    name_consistency::NameConsistencyValidator,
+    name_format::{NameFormat, NameFormatValidator, ParseNameFormatError},
+    plus_line::PlusLineValidator,
+    plus_line_style::{ParsePlusLineStyleError, PlusLineStyle, PlusLineStyleValidator},
+    quality_encoding::QualityEncodingValidator,
+    quality_string::{ParsePlatformError, Platform, QualityStringValidator},
+    read_length::ReadLengthValidator,
+    sequence_length::SequenceLengthValidator,
+    truncated_file::TruncatedFileValidator,
+    whitespace::WhitespaceValidator,
 };
 
 use crate::{
@@ -19,16 +49,30 @@ use crate::{
     validators::{Error, ValidationLevel},
 };
 
-pub trait SingleReadValidator {
-    fn code(&self) -> &'static str;
-    fn name(&self) -> &'static str;
+pub trait SingleReadValidator: Send + Sync {
+    fn code(&self) -> &str;
+    fn name(&self) -> &str;
     fn level(&self) -> ValidationLevel;
     fn validate(&self, r: &Record) -> Result<(), Error>;
 }
 
+/// A single read validator that keeps state across records.
+///
+/// Records are passed to [`validate`] in order. After the last record, [`finish`] is called once
+/// to report checks over the whole file, e.g., on distributions or rates.
+///
+/// [`validate`]: #tymethod.validate
+/// [`finish`]: #method.finish
 pub trait SingleReadValidatorMut {
-    fn code(&self) -> &'static str;
-    fn name(&self) -> &'static str;
+    fn code(&self) -> &str;
+    fn name(&self) -> &str;
     fn level(&self) -> ValidationLevel;
     fn validate(&mut self, r: &Record) -> Result<(), Error>;
+
+    /// Reports the result of checks over all validated records.
+    ///
+    /// The default implementation has no whole-file checks and always succeeds.
+    fn finish(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
 }
@@ -0,0 +1,187 @@
+use tracing::info;
+
+use crate::{
+    fastq::Record,
+    stats::Adapter,
+    validators::{Error, LineType, SingleReadValidatorMut, ValidationLevel},
+};
+
+/// [S011] (high) Validator to check if the fraction of reads with adapter sequences is at most a
+/// given maximum.
+///
+/// Adapters are matched exactly by their first 12 bases. Since the contamination rate is over the
+/// whole file, this is reported after the last record.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{
+///     fastq::Record,
+///     stats::Adapter,
+///     validators::single::{AdapterContentValidator, SingleReadValidatorMut},
+/// };
+///
+/// let adapters = vec![Adapter::new("a", "GGCC")];
+/// let mut validator = AdapterContentValidator::new(adapters, 0.25);
+///
+/// validator.validate(&Record::new("@r0", "AGGCC", "+", "FQLIB"))?;
+/// validator.validate(&Record::new("@r1", "AAAAA", "+", "FQLIB"))?;
+///
+/// assert_eq!(validator.rate(), 0.5);
+/// assert!(validator.finish().is_err());
+/// # Ok::<(), fq::validators::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct AdapterContentValidator {
+    adapters: Vec<Adapter>,
+    max_rate: f64,
+    // The number of reads containing each adapter.
+    adapter_counts: Vec<u64>,
+    contaminated_count: u64,
+    record_count: u64,
+}
+
+impl AdapterContentValidator {
+    pub fn new(adapters: Vec<Adapter>, max_rate: f64) -> Self {
+        let adapter_counts = vec![0; adapters.len()];
+
+        Self {
+            adapters,
+            max_rate,
+            adapter_counts,
+            contaminated_count: 0,
+            record_count: 0,
+        }
+    }
+
+    /// Returns the fraction of validated reads that contain any adapter.
+    pub fn rate(&self) -> f64 {
+        if self.record_count == 0 {
+            0.0
+        } else {
+            self.contaminated_count as f64 / self.record_count as f64
+        }
+    }
+}
+
+impl SingleReadValidatorMut for AdapterContentValidator {
+    fn code(&self) -> &'static str {
+        "S011"
+    }
+
+    fn name(&self) -> &'static str {
+        "AdapterContentValidator"
+    }
+
+    fn level(&self) -> ValidationLevel {
+        ValidationLevel::High
+    }
+
+    fn validate(&mut self, r: &Record) -> Result<(), Error> {
+        let sequence = r.sequence();
+        let mut is_contaminated = false;
+
+        for (adapter, count) in self.adapters.iter().zip(self.adapter_counts.iter_mut()) {
+            if adapter.find(sequence).is_some() {
+                *count += 1;
+                is_contaminated = true;
+            }
+        }
+
+        if is_contaminated {
+            self.contaminated_count += 1;
+        }
+
+        self.record_count += 1;
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        let rate = self.rate();
+
+        info!(
+            "adapter contamination rate: {:.4} ({}/{} records)",
+            rate, self.contaminated_count, self.record_count
+        );
+
+        if rate <= self.max_rate {
+            return Ok(());
+        }
+
+        let adapter_rates: Vec<_> = self
+            .adapters
+            .iter()
+            .zip(&self.adapter_counts)
+            .filter(|(_, &count)| count > 0)
+            .map(|(adapter, &count)| {
+                let adapter_rate = count as f64 / self.record_count as f64;
+                format!("{}: {:.4}", adapter.name(), adapter_rate)
+            })
+            .collect();
+
+        let message = format!(
+            "Adapter contamination rate exceeds maximum (expected <= {}, got {:.4}; {})",
+            self.max_rate,
+            rate,
+            adapter_rates.join(", ")
+        );
+
+        Err(Error::new(
+            self.code(),
+            self.name(),
+            message,
+            LineType::Sequence,
+            None,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_validator() -> AdapterContentValidator {
+        let adapters = vec![Adapter::new("a", "GGCC"), Adapter::new("b", "TTTT")];
+        AdapterContentValidator::new(adapters, 0.25)
+    }
+
+    #[test]
+    fn test_code() {
+        assert_eq!(build_validator().code(), "S011");
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!(build_validator().name(), "AdapterContentValidator");
+    }
+
+    #[test]
+    fn test_level() {
+        assert_eq!(build_validator().level(), ValidationLevel::High);
+    }
+
+    #[test]
+    fn test_finish() -> Result<(), Error> {
+        let mut validator = build_validator();
+        assert!(validator.finish().is_ok());
+
+        for sequence in ["ACGTA", "AGGCC", "ACGTA", "ACGTA"] {
+            validator.validate(&Record::new("", sequence, "", "FQLIB"))?;
+        }
+
+        assert_eq!(validator.rate(), 0.25);
+        assert!(validator.finish().is_ok());
+
+        validator.validate(&Record::new("", "GGCCTTTT", "", "FQLIBFQL"))?;
+        assert_eq!(validator.rate(), 0.4);
+
+        let e = validator.finish().unwrap_err();
+        assert_eq!(
+            e.message,
+            "Adapter contamination rate exceeds maximum (expected <= 0.25, got 0.4000; a: 0.4000, b: 0.2000)"
+        );
+
+        Ok(())
+    }
+}
@@ -1,6 +1,10 @@
-use std::collections::HashMap;
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+};
 
 use bbloom::ScalableBloomFilter;
+use bitvec::vec::BitVec;
 
 use crate::{
     fastq::Record,
@@ -10,6 +14,14 @@ use crate::{
 const FALSE_POSITIVE_PROBABILITY: f64 = 0.0001;
 const INITIAL_CAPACITY: usize = 10_000_000;
 
+// The number of bit positions set for each hash in a bounded filter.
+const HASH_COUNT: u64 = 7;
+
+// The approximate number of bytes used by a possible duplicate, i.e., its hash in the set of
+// possible duplicates and its name in the set of seen names, including the overhead of the hash
+// tables. Names are assumed to be about 64 bytes.
+const POSSIBLE_DUPLICATE_SIZE: usize = 128;
+
 /// [S007] (high) Validator to check if all record names are unique.
 ///
 /// The implementation of this validator uses a Bloom filter, a probabilistic data structure.
@@ -17,6 +29,15 @@ const INITIAL_CAPACITY: usize = 10_000_000;
 /// ([`insert`]), which may or may not hit duplicates; and the second, checking that list of
 /// possible duplicates ([`validate`]).
 ///
+/// Names are added to the filter as 64-bit hashes, and only the hashes of possible duplicates
+/// are kept after the first pass. In the second pass, names with these hashes are kept and
+/// compared exactly, so false positives of the filter are not reported as duplicates.
+///
+/// By default, the filter and the possible duplicates grow with the number of names. A fixed
+/// memory limit can be set with [`with_max_memory`], at the cost of more possible duplicates when
+/// the filter is small for the number of names. Possible duplicates past the limit are not kept
+/// and so are not reported (see [`is_full`]).
+///
 /// # Examples
 ///
 /// ```
@@ -42,15 +63,87 @@ const INITIAL_CAPACITY: usize = 10_000_000;
 ///
 /// [`insert`]: #method.insert
 /// [`validate`]: #method.validate
+/// [`with_max_memory`]: #method.with_max_memory
+/// [`is_full`]: #method.is_full
 pub struct DuplicateNameValidator {
-    filter: ScalableBloomFilter,
-    possible_duplicates: HashMap<Vec<u8>, u8>,
+    filter: NameFilter,
+    // The hashes of names that may be duplicates.
+    possible_duplicates: HashSet<u64>,
+    // The maximum number of possible duplicates, if bounded.
+    max_possible_duplicates: Option<usize>,
+    // Whether a possible duplicate was not kept because of the limit.
+    is_full: bool,
+    // The names with a hash in `possible_duplicates` that were seen in the second pass.
+    seen_names: HashSet<Vec<u8>>,
+}
+
+enum NameFilter {
+    Scalable(ScalableBloomFilter),
+    Bounded(BitVec),
+}
+
+impl NameFilter {
+    // Adds a hash to the filter and returns whether it may have been previously added.
+    fn contains_or_insert(&mut self, hash: u64) -> bool {
+        match self {
+            Self::Scalable(filter) => filter.contains_or_insert(&hash),
+            Self::Bounded(bits) => {
+                let len = bits.len() as u64;
+
+                // Bit positions are derived from the hash using double hashing.
+                let a = hash;
+                let b = hash.rotate_left(32) | 1;
+
+                let mut contains = true;
+
+                for i in 0..HASH_COUNT {
+                    let j = (a.wrapping_add(i.wrapping_mul(b)) % len) as usize;
+
+                    if !bits[j] {
+                        bits.set(j, true);
+                        contains = false;
+                    }
+                }
+
+                contains
+            }
+        }
+    }
 }
 
 impl DuplicateNameValidator {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Creates a validator that uses about the given number of bytes.
+    ///
+    /// Half of the memory is used by the filter and the other half by possible duplicates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_memory` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::validators::single::DuplicateNameValidator;
+    /// let validator = DuplicateNameValidator::with_max_memory(1 << 20);
+    /// ```
+    pub fn with_max_memory(max_memory: usize) -> Self {
+        assert!(max_memory > 0, "max memory must be > 0");
+
+        let filter_len = (max_memory / 2).max(1);
+        let max_possible_duplicates = (max_memory - filter_len) / POSSIBLE_DUPLICATE_SIZE;
+
+        Self {
+            filter: NameFilter::Bounded(BitVec::repeat(false, filter_len * 8)),
+            possible_duplicates: HashSet::new(),
+            max_possible_duplicates: Some(max_possible_duplicates),
+            is_full: false,
+            seen_names: HashSet::new(),
+        }
+    }
 }
 
 impl DuplicateNameValidator {
@@ -68,13 +161,42 @@ impl DuplicateNameValidator {
     /// validator.insert(&record);
     /// ```
     pub fn insert(&mut self, r: &Record) {
-        let name = r.name();
+        let hash = hash_name(r.name());
 
-        if self.filter.contains_or_insert(name) {
-            self.possible_duplicates.insert(name.to_vec(), 0);
+        if !self.filter.contains_or_insert(hash) || self.possible_duplicates.contains(&hash) {
+            return;
+        }
+
+        match self.max_possible_duplicates {
+            Some(n) if self.possible_duplicates.len() >= n => self.is_full = true,
+            _ => {
+                self.possible_duplicates.insert(hash);
+            }
         }
     }
 
+    /// Returns whether possible duplicates were not kept because of the memory limit.
+    ///
+    /// If so, some duplicates may not be reported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fq::{fastq::Record, validators::single::DuplicateNameValidator};
+    ///
+    /// let mut validator = DuplicateNameValidator::with_max_memory(1);
+    /// let record = Record::new("@fqlib:1", "", "", "");
+    ///
+    /// validator.insert(&record);
+    /// assert!(!validator.is_full());
+    ///
+    /// validator.insert(&record);
+    /// assert!(validator.is_full());
+    /// ```
+    pub fn is_full(&self) -> bool {
+        self.is_full
+    }
+
     /// Returns whether there are possible duplicates.
     ///
     /// This is only useful if [`insert`] was previously called for all names.
@@ -94,6 +216,12 @@ impl DuplicateNameValidator {
     }
 }
 
+fn hash_name(name: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    hasher.finish()
+}
+
 impl SingleReadValidatorMut for DuplicateNameValidator {
     fn code(&self) -> &'static str {
         "S007"
@@ -108,21 +236,16 @@ impl SingleReadValidatorMut for DuplicateNameValidator {
     }
 
     fn validate(&mut self, r: &Record) -> Result<(), Error> {
-        let code = self.code();
-        let name = self.name();
-
-        if let Some(count) = self.possible_duplicates.get_mut(&r.name().to_vec()) {
-            if *count >= 1 {
-                return Err(Error::new(
-                    code,
-                    name,
-                    format!("Duplicate found: '{}'", String::from_utf8_lossy(r.name())),
-                    LineType::Name,
-                    Some(1),
-                ));
-            }
-
-            *count += 1;
+        if self.possible_duplicates.contains(&hash_name(r.name()))
+            && !self.seen_names.insert(r.name().to_vec())
+        {
+            return Err(Error::new(
+                self.code(),
+                self.name(),
+                format!("Duplicate found: '{}'", String::from_utf8_lossy(r.name())),
+                LineType::Name,
+                Some(1),
+            ));
         }
 
         Ok(())
@@ -132,8 +255,14 @@ impl SingleReadValidatorMut for DuplicateNameValidator {
 impl Default for DuplicateNameValidator {
     fn default() -> Self {
         Self {
-            filter: ScalableBloomFilter::new(FALSE_POSITIVE_PROBABILITY, INITIAL_CAPACITY),
-            possible_duplicates: HashMap::new(),
+            filter: NameFilter::Scalable(ScalableBloomFilter::new(
+                FALSE_POSITIVE_PROBABILITY,
+                INITIAL_CAPACITY,
+            )),
+            possible_duplicates: HashSet::new(),
+            max_possible_duplicates: None,
+            is_full: false,
+            seen_names: HashSet::new(),
         }
     }
 }
@@ -148,6 +277,70 @@ mod tests {
         assert!(validator.is_empty());
     }
 
+    #[test]
+    fn test_validate() {
+        let r = Record::new("@fqlib:1", "", "", "");
+        let s = Record::new("@fqlib:2", "", "", "");
+
+        for mut validator in [
+            DuplicateNameValidator::new(),
+            DuplicateNameValidator::with_max_memory(1 << 10),
+        ] {
+            validator.insert(&r);
+            validator.insert(&s);
+            validator.insert(&s);
+
+            assert!(!validator.is_empty());
+
+            assert!(validator.validate(&r).is_ok());
+            assert!(validator.validate(&s).is_ok());
+            assert!(validator.validate(&s).is_err());
+        }
+    }
+
+    #[test]
+    fn test_validate_with_false_positives() {
+        // A filter of 8 bits is saturated quickly, so all names become possible duplicates.
+        let mut validator = DuplicateNameValidator::with_max_memory(1);
+
+        let records: Vec<_> = (0..32)
+            .map(|i| Record::new(format!("@fqlib:{}", i), "", "", ""))
+            .collect();
+
+        for record in &records {
+            validator.insert(record);
+        }
+
+        for record in &records {
+            assert!(validator.validate(record).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_insert_with_max_memory() {
+        // A filter of 128 bytes and at most one possible duplicate.
+        let mut validator = DuplicateNameValidator::with_max_memory(256);
+
+        let r = Record::new("@fqlib:1", "", "", "");
+        let s = Record::new("@fqlib:2", "", "", "");
+
+        validator.insert(&r);
+        validator.insert(&r);
+        validator.insert(&r);
+        assert!(!validator.is_full());
+
+        validator.insert(&s);
+        validator.insert(&s);
+        assert!(validator.is_full());
+
+        assert!(validator.validate(&r).is_ok());
+        assert!(validator.validate(&r).is_err());
+
+        // The possible duplicate past the limit is not kept.
+        assert!(validator.validate(&s).is_ok());
+        assert!(validator.validate(&s).is_ok());
+    }
+
     #[test]
     fn test_code() {
         let validator = DuplicateNameValidator::new();
@@ -0,0 +1,183 @@
+use tracing::info;
+
+use crate::{
+    dedup::{build_key, DuplicateFilter},
+    fastq::Record,
+    validators::{Error, LineType, SingleReadValidatorMut, ValidationLevel},
+};
+
+/// [S017] (high) Validator to check if the fraction of reads with an exact duplicate sequence is
+/// at most a given maximum, e.g., to catch PCR over-amplification.
+///
+/// Sequences are tracked with a Bloom filter, so memory use is bounded, but a small fraction of
+/// unique sequences may be counted as duplicates. For paired reads, use [`insert`] with both
+/// mates, which counts templates with the same pair of sequences.
+///
+/// Since the duplicate rate is over the whole file, this is reported after the last record.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{
+///     fastq::Record,
+///     validators::single::{DuplicateSequenceValidator, SingleReadValidatorMut},
+/// };
+///
+/// let mut validator = DuplicateSequenceValidator::new(0.25);
+///
+/// validator.validate(&Record::new("@r0", "ACGT", "+", "IIII"))?;
+/// validator.validate(&Record::new("@r1", "ACGT", "+", "IIII"))?;
+///
+/// assert_eq!(validator.rate(), 0.5);
+/// assert!(validator.finish().is_err());
+/// # Ok::<(), fq::validators::Error>(())
+/// ```
+///
+/// [`insert`]: #method.insert
+pub struct DuplicateSequenceValidator {
+    max_rate: f64,
+    filter: DuplicateFilter,
+    key: Vec<u8>,
+    duplicate_count: u64,
+    record_count: u64,
+}
+
+impl DuplicateSequenceValidator {
+    pub fn new(max_rate: f64) -> Self {
+        Self {
+            max_rate,
+            filter: DuplicateFilter::approximate(),
+            key: Vec::new(),
+            duplicate_count: 0,
+            record_count: 0,
+        }
+    }
+
+    /// Adds the sequences of a template, i.e., one record for single end reads or both mates for
+    /// paired end reads.
+    pub fn insert(&mut self, records: &[&Record]) {
+        build_key(records, false, &mut self.key);
+
+        if !self.filter.insert(&self.key) {
+            self.duplicate_count += 1;
+        }
+
+        self.record_count += 1;
+    }
+
+    /// Returns the fraction of inserted templates that are duplicates of a previous template.
+    pub fn rate(&self) -> f64 {
+        if self.record_count == 0 {
+            0.0
+        } else {
+            self.duplicate_count as f64 / self.record_count as f64
+        }
+    }
+}
+
+impl SingleReadValidatorMut for DuplicateSequenceValidator {
+    fn code(&self) -> &'static str {
+        "S017"
+    }
+
+    fn name(&self) -> &'static str {
+        "DuplicateSequenceValidator"
+    }
+
+    fn level(&self) -> ValidationLevel {
+        ValidationLevel::High
+    }
+
+    fn validate(&mut self, r: &Record) -> Result<(), Error> {
+        self.insert(&[r]);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        let rate = self.rate();
+
+        info!(
+            "duplicate sequence rate: {:.4} ({}/{} records)",
+            rate, self.duplicate_count, self.record_count
+        );
+
+        if rate <= self.max_rate {
+            return Ok(());
+        }
+
+        let message = format!(
+            "Duplicate sequence rate exceeds maximum (expected <= {}, got {:.4})",
+            self.max_rate, rate
+        );
+
+        Err(Error::new(
+            self.code(),
+            self.name(),
+            message,
+            LineType::Sequence,
+            None,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code() {
+        let validator = DuplicateSequenceValidator::new(0.5);
+        assert_eq!(validator.code(), "S017");
+    }
+
+    #[test]
+    fn test_name() {
+        let validator = DuplicateSequenceValidator::new(0.5);
+        assert_eq!(validator.name(), "DuplicateSequenceValidator");
+    }
+
+    #[test]
+    fn test_level() {
+        let validator = DuplicateSequenceValidator::new(0.5);
+        assert_eq!(validator.level(), ValidationLevel::High);
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut validator = DuplicateSequenceValidator::new(0.5);
+
+        let r = Record::new("@r0", "ACGT", "+", "IIII");
+        let s = Record::new("@r0", "TTGC", "+", "IIII");
+
+        validator.insert(&[&r, &s]);
+        validator.insert(&[&r, &r]);
+        validator.insert(&[&r, &s]);
+        validator.insert(&[&s, &r]);
+
+        assert_eq!(validator.rate(), 0.25);
+    }
+
+    #[test]
+    fn test_finish() -> Result<(), Error> {
+        let mut validator = DuplicateSequenceValidator::new(0.25);
+        assert!(validator.finish().is_ok());
+
+        for sequence in ["ACGT", "ACGA", "ACGT", "ACGC"] {
+            validator.validate(&Record::new("", sequence, "", "IIII"))?;
+        }
+
+        assert_eq!(validator.rate(), 0.25);
+        assert!(validator.finish().is_ok());
+
+        validator.validate(&Record::new("", "ACGA", "", "IIII"))?;
+        assert_eq!(validator.rate(), 0.4);
+
+        let e = validator.finish().unwrap_err();
+        assert_eq!(
+            e.message,
+            "Duplicate sequence rate exceeds maximum (expected <= 0.25, got 0.4000)"
+        );
+
+        Ok(())
+    }
+}
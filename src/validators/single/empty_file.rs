@@ -0,0 +1,94 @@
+use crate::{
+    fastq::Record,
+    validators::{Error, LineType, SingleReadValidatorMut, ValidationLevel},
+};
+
+/// [S014] (low) Validator to check if the input has at least one record.
+///
+/// Since this is only known after the last record, this is reported by `finish`.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{fastq::Record, validators::single::{EmptyFileValidator, SingleReadValidatorMut}};
+///
+/// let mut validator = EmptyFileValidator::default();
+/// assert!(validator.finish().is_err());
+///
+/// validator.validate(&Record::new("@r0", "ACGT", "+", "IIII"))?;
+/// assert!(validator.finish().is_ok());
+/// # Ok::<(), fq::validators::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EmptyFileValidator {
+    record_count: u64,
+}
+
+impl SingleReadValidatorMut for EmptyFileValidator {
+    fn code(&self) -> &'static str {
+        "S014"
+    }
+
+    fn name(&self) -> &'static str {
+        "EmptyFileValidator"
+    }
+
+    fn level(&self) -> ValidationLevel {
+        ValidationLevel::Low
+    }
+
+    fn validate(&mut self, _: &Record) -> Result<(), Error> {
+        self.record_count += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        if self.record_count == 0 {
+            Err(Error::new(
+                self.code(),
+                self.name(),
+                String::from("File contains no records"),
+                LineType::Name,
+                None,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code() {
+        let validator = EmptyFileValidator::default();
+        assert_eq!(validator.code(), "S014");
+    }
+
+    #[test]
+    fn test_name() {
+        let validator = EmptyFileValidator::default();
+        assert_eq!(validator.name(), "EmptyFileValidator");
+    }
+
+    #[test]
+    fn test_level() {
+        let validator = EmptyFileValidator::default();
+        assert_eq!(validator.level(), ValidationLevel::Low);
+    }
+
+    #[test]
+    fn test_finish() -> Result<(), Error> {
+        let mut validator = EmptyFileValidator::default();
+
+        let e = validator.finish().unwrap_err();
+        assert_eq!(e.message, "File contains no records");
+
+        validator.validate(&Record::new("", "", "", ""))?;
+        assert!(validator.finish().is_ok());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    fastq::Record,
+    validators::{Error, LineType, SingleReadValidator, ValidationLevel},
+};
+
+/// [S019] (low) Validator to check if a record has the line structure of a FASTQ record.
+///
+/// A record is malformed when its name line does not start with an "@", its plus line does not
+/// start with a "+", or its quality line appears to be the name line of the next record, i.e., it
+/// starts with an "@" and its length is not the sequence length. This is used to skip malformed
+/// records and resume validation at the next record (see
+/// [`crate::fastq::Reader::resync`]).
+///
+/// # Examples
+///
+/// ```
+/// use fq::{
+///     fastq::Record,
+///     validators::single::{MalformedRecordValidator, SingleReadValidator},
+/// };
+///
+/// let validator = MalformedRecordValidator;
+///
+/// assert!(validator.validate(&Record::new("@r0", "ACGT", "+", "@III")).is_ok());
+/// assert!(validator.validate(&Record::new("@r0", "ACGT", "+", "@r1")).is_err());
+/// ```
+pub struct MalformedRecordValidator;
+
+impl SingleReadValidator for MalformedRecordValidator {
+    fn code(&self) -> &'static str {
+        "S019"
+    }
+
+    fn name(&self) -> &'static str {
+        "MalformedRecordValidator"
+    }
+
+    fn level(&self) -> ValidationLevel {
+        ValidationLevel::Low
+    }
+
+    fn validate(&self, r: &Record) -> Result<(), Error> {
+        let (message, line_type) = if !r.name().starts_with(b"@") {
+            ("name line does not start with an '@'", LineType::Name)
+        } else if !r.plus_line().starts_with(b"+") {
+            ("plus line does not start with a '+'", LineType::PlusLine)
+        } else if r.quality_scores().starts_with(b"@")
+            && r.quality_scores().len() != r.sequence().len()
+        {
+            ("quality line is missing", LineType::Quality)
+        } else {
+            return Ok(());
+        };
+
+        Err(Error::new(
+            self.code(),
+            self.name(),
+            format!("Malformed record: {}", message),
+            line_type,
+            None,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code() {
+        let validator = MalformedRecordValidator;
+        assert_eq!(validator.code(), "S019");
+    }
+
+    #[test]
+    fn test_name() {
+        let validator = MalformedRecordValidator;
+        assert_eq!(validator.name(), "MalformedRecordValidator");
+    }
+
+    #[test]
+    fn test_level() {
+        let validator = MalformedRecordValidator;
+        assert_eq!(validator.level(), ValidationLevel::Low);
+    }
+
+    #[test]
+    fn test_validate() {
+        let validator = MalformedRecordValidator;
+
+        let record = Record::new("@r0", "ACGT", "+", "IIII");
+        assert!(validator.validate(&record).is_ok());
+
+        // S005 reports inconsistent lengths of quality lines that are not name lines.
+        let record = Record::new("@r0", "ACGT", "+", "III");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("ACGT", "+", "IIII", "@r1");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(
+            e.message,
+            "Malformed record: name line does not start with an '@'"
+        );
+
+        let record = Record::new("@r0", "ACGT", "IIII", "@r1");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(
+            e.message,
+            "Malformed record: plus line does not start with a '+'"
+        );
+
+        let record = Record::new("@r0", "ACGT", "+", "@r1");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(e.message, "Malformed record: quality line is missing");
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{
+    fastq::Record,
+    validators::{Error, LineType, SingleReadValidator, ValidationLevel},
+};
+
+/// [S010] (high) Validator to check if the fraction of `N` bases in the sequence line is at most
+/// a given maximum.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{fastq::Record, validators::single::{NContentValidator, SingleReadValidator}};
+///
+/// let validator = NContentValidator::new(0.5);
+///
+/// assert!(validator.validate(&Record::new("@r0", "ACNN", "+", "IIII")).is_ok());
+/// assert!(validator.validate(&Record::new("@r1", "ANNN", "+", "IIII")).is_err());
+/// ```
+pub struct NContentValidator {
+    max_fraction: f64,
+}
+
+impl NContentValidator {
+    pub fn new(max_fraction: f64) -> Self {
+        Self { max_fraction }
+    }
+}
+
+impl SingleReadValidator for NContentValidator {
+    fn code(&self) -> &'static str {
+        "S010"
+    }
+
+    fn name(&self) -> &'static str {
+        "NContentValidator"
+    }
+
+    fn level(&self) -> ValidationLevel {
+        ValidationLevel::High
+    }
+
+    fn validate(&self, r: &Record) -> Result<(), Error> {
+        let sequence = r.sequence();
+
+        if sequence.is_empty() {
+            return Ok(());
+        }
+
+        let n_count = sequence.iter().filter(|&&b| b == b'N' || b == b'n').count();
+
+        let fraction = n_count as f64 / sequence.len() as f64;
+
+        if fraction > self.max_fraction {
+            Err(Error::new(
+                self.code(),
+                self.name(),
+                format!(
+                    "N fraction exceeds maximum (expected <= {}, got {:.3})",
+                    self.max_fraction, fraction
+                ),
+                LineType::Sequence,
+                None,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code() {
+        let validator = NContentValidator::new(0.5);
+        assert_eq!(validator.code(), "S010");
+    }
+
+    #[test]
+    fn test_name() {
+        let validator = NContentValidator::new(0.5);
+        assert_eq!(validator.name(), "NContentValidator");
+    }
+
+    #[test]
+    fn test_level() {
+        let validator = NContentValidator::new(0.5);
+        assert_eq!(validator.level(), ValidationLevel::High);
+    }
+
+    #[test]
+    fn test_validate() {
+        let validator = NContentValidator::new(0.25);
+
+        let record = Record::new("", "ACGT", "", "IIII");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("", "ACGn", "", "IIII");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("", "ANGn", "", "IIII");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(
+            e.message,
+            "N fraction exceeds maximum (expected <= 0.25, got 0.500)"
+        );
+
+        let record = Record::new("", "", "", "");
+        assert!(validator.validate(&record).is_ok());
+    }
+}
@@ -0,0 +1,184 @@
+use crate::{
+    fastq::{name_id, Record},
+    validators::{Error, LineType, SingleReadValidatorMut, ValidationLevel},
+};
+
+// The number of colon-separated fields of an Illumina Casava 1.8+ read name, i.e.,
+// `<instrument>:<run>:<flowcell>:<lane>:<tile>:<x>:<y>`.
+const CASAVA_FIELD_COUNT: usize = 7;
+
+// The number of fields in the prefix, i.e., the instrument, run, flowcell, and lane.
+const PREFIX_FIELD_COUNT: usize = 4;
+
+/// [S020] (high) Validator to check if all read names have the run of the first record.
+///
+/// The run is the instrument, run, flowcell, and lane prefix of an Illumina Casava 1.8+ name,
+/// e.g., `EAS139:136:FC706VJ:2` of `@EAS139:136:FC706VJ:2:2104:15343:197393`. A different prefix
+/// usually means that files of different runs were concatenated. If the first name does not
+/// have this format, no names are checked.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{
+///     fastq::Record,
+///     validators::single::{NameConsistencyValidator, SingleReadValidatorMut},
+/// };
+///
+/// let mut validator = NameConsistencyValidator::default();
+///
+/// let record = Record::new("@EAS139:136:FC706VJ:2:2104:15343:197393", "ACGT", "+", "IIII");
+/// assert!(validator.validate(&record).is_ok());
+///
+/// let record = Record::new("@EAS139:136:FC706VJ:2:2104:15343:197394", "ACGT", "+", "IIII");
+/// assert!(validator.validate(&record).is_ok());
+///
+/// let record = Record::new("@EAS139:137:FC706VK:1:1101:1000:2000", "ACGT", "+", "IIII");
+/// assert!(validator.validate(&record).is_err());
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NameConsistencyValidator {
+    // The prefix of the first record, if it has one, once the first record is validated.
+    expected_prefix: Option<Option<Vec<u8>>>,
+}
+
+impl SingleReadValidatorMut for NameConsistencyValidator {
+    fn code(&self) -> &'static str {
+        "S020"
+    }
+
+    fn name(&self) -> &'static str {
+        "NameConsistencyValidator"
+    }
+
+    fn level(&self) -> ValidationLevel {
+        ValidationLevel::High
+    }
+
+    fn validate(&mut self, r: &Record) -> Result<(), Error> {
+        let id = name_id(r.name());
+        let prefix = parse_prefix(id);
+
+        let expected_prefix = match self
+            .expected_prefix
+            .get_or_insert_with(|| prefix.map(|p| p.to_vec()))
+        {
+            Some(expected_prefix) => expected_prefix,
+            None => return Ok(()),
+        };
+
+        if prefix == Some(&expected_prefix[..]) {
+            Ok(())
+        } else {
+            let message = format!(
+                "Name prefix does not match the first record (expected {}, got {})",
+                String::from_utf8_lossy(expected_prefix),
+                String::from_utf8_lossy(prefix.unwrap_or(id)),
+            );
+
+            Err(Error::new(
+                self.code(),
+                self.name(),
+                message,
+                LineType::Name,
+                Some(1),
+            ))
+        }
+    }
+}
+
+// Returns the instrument, run, flowcell, and lane prefix of a Casava 1.8+ name ID.
+fn parse_prefix(id: &[u8]) -> Option<&[u8]> {
+    let mut separators = id
+        .iter()
+        .enumerate()
+        .filter(|(_, &b)| b == b':')
+        .map(|(i, _)| i);
+
+    let end = separators.nth(PREFIX_FIELD_COUNT - 1)?;
+
+    if separators.count() == CASAVA_FIELD_COUNT - PREFIX_FIELD_COUNT - 1 {
+        Some(&id[..end])
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code() {
+        let validator = NameConsistencyValidator::default();
+        assert_eq!(validator.code(), "S020");
+    }
+
+    #[test]
+    fn test_name() {
+        let validator = NameConsistencyValidator::default();
+        assert_eq!(validator.name(), "NameConsistencyValidator");
+    }
+
+    #[test]
+    fn test_level() {
+        let validator = NameConsistencyValidator::default();
+        assert_eq!(validator.level(), ValidationLevel::High);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut validator = NameConsistencyValidator::default();
+
+        let record = Record::new(
+            "@EAS139:136:FC706VJ:2:2104:15343:197393 1:Y:18:ATCACG",
+            "",
+            "",
+            "",
+        );
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new(
+            "@EAS139:136:FC706VJ:2:2104:15343:197394 1:N:18:ATCACG",
+            "",
+            "",
+            "",
+        );
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("@EAS139:136:FC706VJ:3:2104:15343:197393", "", "", "");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(
+            e.message,
+            "Name prefix does not match the first record (expected EAS139:136:FC706VJ:2, got EAS139:136:FC706VJ:3)"
+        );
+
+        let record = Record::new("@SRR001666.1", "", "", "");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(
+            e.message,
+            "Name prefix does not match the first record (expected EAS139:136:FC706VJ:2, got SRR001666.1)"
+        );
+    }
+
+    #[test]
+    fn test_validate_with_other_first_name() {
+        let mut validator = NameConsistencyValidator::default();
+
+        let record = Record::new("@SRR001666.1", "", "", "");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("@EAS139:136:FC706VJ:2:2104:15343:197393", "", "", "");
+        assert!(validator.validate(&record).is_ok());
+    }
+
+    #[test]
+    fn test_parse_prefix() {
+        assert_eq!(
+            parse_prefix(b"EAS139:136:FC706VJ:2:2104:15343:197393"),
+            Some(&b"EAS139:136:FC706VJ:2"[..])
+        );
+        assert_eq!(parse_prefix(b"HWUSI-EAS100R:6:73:941:1973#0/1"), None);
+        assert_eq!(parse_prefix(b"SRR001666.1"), None);
+    }
+}
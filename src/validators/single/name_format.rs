@@ -0,0 +1,395 @@
+use std::{error, fmt, str::FromStr};
+
+use crate::{
+    fastq::Record,
+    validators::{Error, LineType, SingleReadValidator, ValidationLevel},
+};
+
+const CASAVA_NAME_FIELDS: [&str; 7] = ["instrument", "run", "flowcell", "lane", "tile", "x", "y"];
+const CASAVA_COMMENT_FIELDS: [&str; 4] = ["read", "filtered", "control", "index"];
+
+/// A read name format.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NameFormat {
+    /// Illumina Casava 1.8+, e.g., `@EAS139:136:FC706VJ:2:2104:15343:197393 1:Y:18:ATCACG`.
+    ///
+    /// The comment is optional.
+    Casava,
+    /// Sequence Read Archive, e.g., `@SRR001666.1 071112_SLXA-EAS1_s_7:5:1:817:345 length=36`.
+    ///
+    /// The name is an SRA, ENA, or DDBJ run accession and spot number, optionally followed by a
+    /// read number. The comment is not checked.
+    Sra,
+}
+
+impl fmt::Display for NameFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Casava => f.write_str("casava"),
+            Self::Sra => f.write_str("sra"),
+        }
+    }
+}
+
+/// An error returned when a name format fails to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseNameFormatError(String);
+
+impl error::Error for ParseNameFormatError {}
+
+impl fmt::Display for ParseNameFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid name format: expected casava or sra, got '{}'",
+            self.0
+        )
+    }
+}
+
+impl FromStr for NameFormat {
+    type Err = ParseNameFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "casava" => Ok(Self::Casava),
+            "sra" => Ok(Self::Sra),
+            _ => Err(ParseNameFormatError(s.into())),
+        }
+    }
+}
+
+/// [S012] (high) Validator to check if the name line is in a given read name format.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{
+///     fastq::Record,
+///     validators::single::{NameFormat, NameFormatValidator, SingleReadValidator},
+/// };
+///
+/// let validator = NameFormatValidator::new(NameFormat::Casava);
+///
+/// let record = Record::new("@fqlib:1:FQ1:2:2104:15343:197393 1:N:0:ACGT", "A", "+", "I");
+/// assert!(validator.validate(&record).is_ok());
+///
+/// let record = Record::new("@SRR001666.1", "A", "+", "I");
+/// assert!(validator.validate(&record).is_err());
+/// ```
+pub struct NameFormatValidator {
+    format: NameFormat,
+}
+
+impl NameFormatValidator {
+    pub fn new(format: NameFormat) -> Self {
+        Self { format }
+    }
+
+    fn build_error(&self, message: String, col_no: usize) -> Error {
+        Error::new(
+            self.code(),
+            self.name(),
+            format!("Invalid {} name: {}", self.format, message),
+            LineType::Name,
+            Some(col_no),
+        )
+    }
+
+    fn validate_casava(&self, name: &[u8], start: usize) -> Result<(), Error> {
+        let (id, comment) = split_comment(name);
+
+        validate_fields(id, &CASAVA_NAME_FIELDS, is_valid_casava_name_field)
+            .map_err(|(message, offset)| self.build_error(message, start + offset + 1))?;
+
+        if let Some(comment) = comment {
+            let comment_start = start + id.len() + 1;
+
+            validate_fields(
+                comment,
+                &CASAVA_COMMENT_FIELDS,
+                is_valid_casava_comment_field,
+            )
+            .map_err(|(message, offset)| self.build_error(message, comment_start + offset + 1))?;
+        }
+
+        Ok(())
+    }
+
+    fn validate_sra(&self, name: &[u8], start: usize) -> Result<(), Error> {
+        let (id, _) = split_comment(name);
+        let mut parts = id.split(|&b| b == b'.');
+
+        let accession = parts.next().unwrap_or_default();
+
+        if !is_valid_sra_accession(accession) {
+            return Err(self.build_error(
+                String::from("expected run accession, e.g., SRR001666"),
+                start + 1,
+            ));
+        }
+
+        let mut offset = accession.len() + 1;
+        let mut part_count = 0;
+
+        for part in parts {
+            // The spot number is optionally followed by a read number.
+            if part_count == 2 || !is_numeric(part) {
+                let message = format!(
+                    "invalid spot or read number '{}'",
+                    String::from_utf8_lossy(part)
+                );
+
+                return Err(self.build_error(message, start + offset + 1));
+            }
+
+            offset += part.len() + 1;
+            part_count += 1;
+        }
+
+        if part_count == 0 {
+            return Err(self.build_error(
+                String::from("expected spot number"),
+                start + accession.len() + 1,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl SingleReadValidator for NameFormatValidator {
+    fn code(&self) -> &'static str {
+        "S012"
+    }
+
+    fn name(&self) -> &'static str {
+        "NameFormatValidator"
+    }
+
+    fn level(&self) -> ValidationLevel {
+        ValidationLevel::High
+    }
+
+    fn validate(&self, r: &Record) -> Result<(), Error> {
+        // The leading "@" is checked by S003.
+        let (name, start) = match r.name().strip_prefix(b"@") {
+            Some(name) => (name, 1),
+            None => (r.name(), 0),
+        };
+
+        match self.format {
+            NameFormat::Casava => self.validate_casava(name, start),
+            NameFormat::Sra => self.validate_sra(name, start),
+        }
+    }
+}
+
+// Splits a name into its identifier and comment, which are separated by the first space.
+fn split_comment(name: &[u8]) -> (&[u8], Option<&[u8]>) {
+    match name.iter().position(|&b| b == b' ') {
+        Some(i) => (&name[..i], Some(&name[i + 1..])),
+        None => (name, None),
+    }
+}
+
+// Checks that `s` has the given colon-separated fields.
+//
+// On failure, this returns a message and the offset of the invalid field in `s`.
+fn validate_fields<F>(s: &[u8], field_names: &[&str], is_valid: F) -> Result<(), (String, usize)>
+where
+    F: Fn(usize, &[u8]) -> bool,
+{
+    let field_count = s.split(|&b| b == b':').count();
+
+    if field_count != field_names.len() {
+        let message = format!(
+            "expected {} colon-separated fields, got {}",
+            field_names.len(),
+            field_count
+        );
+
+        return Err((message, 0));
+    }
+
+    let mut offset = 0;
+
+    for (i, (field, field_name)) in s.split(|&b| b == b':').zip(field_names).enumerate() {
+        if !is_valid(i, field) {
+            let message = format!(
+                "invalid {} '{}'",
+                field_name,
+                String::from_utf8_lossy(field)
+            );
+
+            return Err((message, offset));
+        }
+
+        offset += field.len() + 1;
+    }
+
+    Ok(())
+}
+
+fn is_numeric(s: &[u8]) -> bool {
+    !s.is_empty() && s.iter().all(u8::is_ascii_digit)
+}
+
+// An SRA, ENA, or DDBJ run accession is "SRR", "ERR", or "DRR" followed by a number.
+fn is_valid_sra_accession(s: &[u8]) -> bool {
+    s.len() > 3 && matches!(s[0], b'S' | b'E' | b'D') && s[1..3] == *b"RR" && is_numeric(&s[3..])
+}
+
+// The instrument and flowcell are identifiers, and the rest are numbers.
+fn is_valid_casava_name_field(i: usize, field: &[u8]) -> bool {
+    match i {
+        0 | 2 => {
+            !field.is_empty()
+                && field
+                    .iter()
+                    .all(|&b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+        }
+        _ => is_numeric(field),
+    }
+}
+
+// The filtered flag is `Y` or `N`, and the index may be empty, e.g., when not multiplexed.
+fn is_valid_casava_comment_field(i: usize, field: &[u8]) -> bool {
+    match i {
+        1 => field == b"Y" || field == b"N",
+        3 => !field.iter().any(u8::is_ascii_whitespace),
+        _ => is_numeric(field),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fmt() {
+        assert_eq!(NameFormat::Casava.to_string(), "casava");
+        assert_eq!(NameFormat::Sra.to_string(), "sra");
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("casava".parse(), Ok(NameFormat::Casava));
+        assert_eq!("sra".parse(), Ok(NameFormat::Sra));
+        assert_eq!(
+            "illumina".parse::<NameFormat>(),
+            Err(ParseNameFormatError(String::from("illumina")))
+        );
+    }
+
+    #[test]
+    fn test_code() {
+        let validator = NameFormatValidator::new(NameFormat::Casava);
+        assert_eq!(validator.code(), "S012");
+    }
+
+    #[test]
+    fn test_name() {
+        let validator = NameFormatValidator::new(NameFormat::Casava);
+        assert_eq!(validator.name(), "NameFormatValidator");
+    }
+
+    #[test]
+    fn test_level() {
+        let validator = NameFormatValidator::new(NameFormat::Casava);
+        assert_eq!(validator.level(), ValidationLevel::High);
+    }
+
+    #[test]
+    fn test_validate_with_casava() {
+        let validator = NameFormatValidator::new(NameFormat::Casava);
+
+        let record = Record::new("@fq-lib_1:136:FQ706VJ:2:2104:15343:197393", "", "", "");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new(
+            "@fqlib:136:FQ706VJ:2:2104:15343:197393 1:Y:18:ATCACG",
+            "",
+            "",
+            "",
+        );
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("@fqlib:136:FQ706VJ:2:2104:15343:197393 2:N:0:", "", "", "");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("@fqlib:136:FQ706VJ:2:2104:15343", "", "", "");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(
+            e.message,
+            "Invalid casava name: expected 7 colon-separated fields, got 6"
+        );
+        assert_eq!(e.col_no, Some(2));
+
+        let record = Record::new("@fqlib:136:FQ706VJ:2:2104:x:197393", "", "", "");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(e.message, "Invalid casava name: invalid x 'x'");
+        assert_eq!(e.col_no, Some(27));
+
+        let record = Record::new(
+            "@fqlib:136:FQ706VJ:2:2104:15343:197393 1:F:18:ATCACG",
+            "",
+            "",
+            "",
+        );
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(e.message, "Invalid casava name: invalid filtered 'F'");
+        assert_eq!(e.col_no, Some(42));
+
+        let record = Record::new("@SRR001666.1", "", "", "");
+        assert!(validator.validate(&record).is_err());
+    }
+
+    #[test]
+    fn test_validate_with_sra() {
+        let validator = NameFormatValidator::new(NameFormat::Sra);
+
+        let record = Record::new("@SRR001666.1", "", "", "");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new(
+            "@ERR001666.1.2 071112_SLXA-EAS1_s_7:5:1:817:345",
+            "",
+            "",
+            "",
+        );
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("@SRR001666", "", "", "");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(e.message, "Invalid sra name: expected spot number");
+        assert_eq!(e.col_no, Some(11));
+
+        let record = Record::new("@SRR001666.1.2.3", "", "", "");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(
+            e.message,
+            "Invalid sra name: invalid spot or read number '3'"
+        );
+        assert_eq!(e.col_no, Some(16));
+
+        let record = Record::new("@SRR001666.a", "", "", "");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(
+            e.message,
+            "Invalid sra name: invalid spot or read number 'a'"
+        );
+        assert_eq!(e.col_no, Some(12));
+
+        let record = Record::new("@SRRX.1", "", "", "");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(
+            e.message,
+            "Invalid sra name: expected run accession, e.g., SRR001666"
+        );
+        assert_eq!(e.col_no, Some(2));
+
+        let record = Record::new("@fqlib:136:FQ706VJ:2:2104:15343:197393", "", "", "");
+        assert!(validator.validate(&record).is_err());
+    }
+}
@@ -0,0 +1,273 @@
+use std::{error, fmt, str::FromStr};
+
+use crate::{
+    fastq::Record,
+    validators::{Error, LineType, SingleReadValidatorMut, ValidationLevel},
+};
+
+/// The required style of plus lines.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PlusLineStyle {
+    /// Each plus line is either empty or repeats the name.
+    Any,
+    /// All plus lines have the style of the first record.
+    Consistent,
+    /// Each plus line is empty, i.e., only "+".
+    Empty,
+    /// Each plus line repeats the name, e.g., "+r0" for "@r0".
+    Name,
+}
+
+impl fmt::Display for PlusLineStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Any => f.write_str("any"),
+            Self::Consistent => f.write_str("consistent"),
+            Self::Empty => f.write_str("empty"),
+            Self::Name => f.write_str("name"),
+        }
+    }
+}
+
+/// An error returned when a plus line style fails to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParsePlusLineStyleError(String);
+
+impl error::Error for ParsePlusLineStyleError {}
+
+impl fmt::Display for ParsePlusLineStyleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid plus line style: expected any, consistent, empty, or name, got '{}'",
+            self.0
+        )
+    }
+}
+
+impl FromStr for PlusLineStyle {
+    type Err = ParsePlusLineStyleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "any" => Ok(Self::Any),
+            "consistent" => Ok(Self::Consistent),
+            "empty" => Ok(Self::Empty),
+            "name" => Ok(Self::Name),
+            _ => Err(ParsePlusLineStyleError(s.into())),
+        }
+    }
+}
+
+/// [S018] (medium) Validator to check if the plus line is either empty or repeats the name.
+///
+/// With a style other than [`PlusLineStyle::Any`], all plus lines are also checked to have the
+/// same style.
+///
+/// The full name is compared, so records must not be reset beforehand. A plus line that does not
+/// start with a "+" is checked by S001.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{
+///     fastq::Record,
+///     validators::single::{PlusLineStyle, PlusLineStyleValidator, SingleReadValidatorMut},
+/// };
+///
+/// let mut validator = PlusLineStyleValidator::new(PlusLineStyle::Consistent);
+///
+/// assert!(validator.validate(&Record::new("@r0", "ACGT", "+r0", "IIII")).is_ok());
+/// assert!(validator.validate(&Record::new("@r1", "ACGT", "+", "IIII")).is_err());
+/// assert!(validator.validate(&Record::new("@r2", "ACGT", "+r1", "IIII")).is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct PlusLineStyleValidator {
+    style: PlusLineStyle,
+    // The style of the first record when consistent.
+    first_style: Option<PlusLineStyle>,
+}
+
+impl PlusLineStyleValidator {
+    pub fn new(style: PlusLineStyle) -> Self {
+        Self {
+            style,
+            first_style: None,
+        }
+    }
+
+    fn build_error(&self, message: String, col_no: usize) -> Error {
+        Error::new(
+            self.code(),
+            self.name(),
+            message,
+            LineType::PlusLine,
+            Some(col_no),
+        )
+    }
+}
+
+impl Default for PlusLineStyleValidator {
+    fn default() -> Self {
+        Self::new(PlusLineStyle::Any)
+    }
+}
+
+impl SingleReadValidatorMut for PlusLineStyleValidator {
+    fn code(&self) -> &'static str {
+        "S018"
+    }
+
+    fn name(&self) -> &'static str {
+        "PlusLineStyleValidator"
+    }
+
+    fn level(&self) -> ValidationLevel {
+        ValidationLevel::Medium
+    }
+
+    fn validate(&mut self, r: &Record) -> Result<(), Error> {
+        let description = match r.plus_line().strip_prefix(b"+") {
+            Some(description) => description,
+            None => return Ok(()),
+        };
+
+        let actual_style = if description.is_empty() {
+            PlusLineStyle::Empty
+        } else {
+            let name = r.name().strip_prefix(b"@").unwrap_or(r.name());
+
+            if description != name {
+                // The position of the first byte that differs from the name.
+                let i = description
+                    .iter()
+                    .zip(name)
+                    .position(|(a, b)| a != b)
+                    .unwrap_or_else(|| description.len().min(name.len()));
+
+                let message = format!(
+                    "Plus line is neither empty nor a repeat of the name: '{}'",
+                    String::from_utf8_lossy(r.plus_line())
+                );
+
+                return Err(self.build_error(message, i + 2));
+            }
+
+            PlusLineStyle::Name
+        };
+
+        let expected_style = match self.style {
+            PlusLineStyle::Any => return Ok(()),
+            PlusLineStyle::Consistent => *self.first_style.get_or_insert(actual_style),
+            style => style,
+        };
+
+        if actual_style == expected_style {
+            Ok(())
+        } else {
+            let message = format!(
+                "Plus line style does not match (expected {}, got {})",
+                expected_style, actual_style
+            );
+
+            Err(self.build_error(message, 2))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fmt() {
+        assert_eq!(PlusLineStyle::Any.to_string(), "any");
+        assert_eq!(PlusLineStyle::Consistent.to_string(), "consistent");
+        assert_eq!(PlusLineStyle::Empty.to_string(), "empty");
+        assert_eq!(PlusLineStyle::Name.to_string(), "name");
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("any".parse(), Ok(PlusLineStyle::Any));
+        assert_eq!("consistent".parse(), Ok(PlusLineStyle::Consistent));
+        assert_eq!("empty".parse(), Ok(PlusLineStyle::Empty));
+        assert_eq!("name".parse(), Ok(PlusLineStyle::Name));
+        assert_eq!(
+            "none".parse::<PlusLineStyle>(),
+            Err(ParsePlusLineStyleError(String::from("none")))
+        );
+    }
+
+    #[test]
+    fn test_code() {
+        let validator = PlusLineStyleValidator::default();
+        assert_eq!(validator.code(), "S018");
+    }
+
+    #[test]
+    fn test_name() {
+        let validator = PlusLineStyleValidator::default();
+        assert_eq!(validator.name(), "PlusLineStyleValidator");
+    }
+
+    #[test]
+    fn test_level() {
+        let validator = PlusLineStyleValidator::default();
+        assert_eq!(validator.level(), ValidationLevel::Medium);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut validator = PlusLineStyleValidator::default();
+
+        let record = Record::new("@fqlib:1 1:N:0:1", "", "+", "");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("@fqlib:1 1:N:0:1", "", "+fqlib:1 1:N:0:1", "");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("@fqlib:1 1:N:0:1", "", "+fqlib:2", "");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(
+            e.message,
+            "Plus line is neither empty nor a repeat of the name: '+fqlib:2'"
+        );
+        assert_eq!(e.col_no, Some(8));
+
+        let record = Record::new("@fqlib:1", "", "+fqlib:1 1:N:0:1", "");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(e.col_no, Some(9));
+
+        let record = Record::new("@fqlib:1", "", "fqlib:1", "");
+        assert!(validator.validate(&record).is_ok());
+    }
+
+    #[test]
+    fn test_validate_with_style() {
+        let mut validator = PlusLineStyleValidator::new(PlusLineStyle::Empty);
+
+        let record = Record::new("@fqlib:1", "", "+", "");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("@fqlib:1", "", "+fqlib:1", "");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(
+            e.message,
+            "Plus line style does not match (expected empty, got name)"
+        );
+        assert_eq!(e.col_no, Some(2));
+
+        let mut validator = PlusLineStyleValidator::new(PlusLineStyle::Consistent);
+
+        let record = Record::new("@fqlib:1", "", "+", "");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("@fqlib:2", "", "+fqlib:2", "");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(
+            e.message,
+            "Plus line style does not match (expected empty, got name)"
+        );
+    }
+}
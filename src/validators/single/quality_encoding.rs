@@ -0,0 +1,132 @@
+use crate::{
+    fastq::Record,
+    quality::{Detector, Encoding},
+    validators::{Error, LineType, SingleReadValidatorMut, ValidationLevel},
+};
+
+/// [S009] (high) Validator to check if the quality scores are in the expected encoding.
+///
+/// The encoding is inferred from the range of quality scores over the whole file, so this is
+/// reported after the last record. An input with a range that fits more than one encoding is
+/// valid.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{
+///     fastq::Record,
+///     quality::Encoding,
+///     validators::single::{QualityEncodingValidator, SingleReadValidatorMut},
+/// };
+///
+/// let mut validator = QualityEncodingValidator::new(Encoding::Phred33);
+///
+/// validator.validate(&Record::new("@r0", "ACGT", "+", "BBhh"))?;
+/// assert_eq!(validator.encoding(), Some(Encoding::Phred64));
+/// assert!(validator.finish().is_err());
+/// # Ok::<(), fq::validators::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct QualityEncodingValidator {
+    expected_encoding: Encoding,
+    detector: Detector,
+}
+
+impl QualityEncodingValidator {
+    pub fn new(expected_encoding: Encoding) -> Self {
+        Self {
+            expected_encoding,
+            detector: Detector::default(),
+        }
+    }
+
+    /// Returns the encoding inferred from the validated records.
+    pub fn encoding(&self) -> Option<Encoding> {
+        self.detector.encoding()
+    }
+}
+
+impl SingleReadValidatorMut for QualityEncodingValidator {
+    fn code(&self) -> &'static str {
+        "S009"
+    }
+
+    fn name(&self) -> &'static str {
+        "QualityEncodingValidator"
+    }
+
+    fn level(&self) -> ValidationLevel {
+        ValidationLevel::High
+    }
+
+    fn validate(&mut self, r: &Record) -> Result<(), Error> {
+        self.detector.add(r.quality_scores());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        match self.encoding() {
+            Some(encoding) if encoding != self.expected_encoding => {
+                let message = format!(
+                    "Quality score encoding does not match (expected {}, got {})",
+                    self.expected_encoding, encoding
+                );
+
+                Err(Error::new(
+                    self.code(),
+                    self.name(),
+                    message,
+                    LineType::Quality,
+                    None,
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code() {
+        let validator = QualityEncodingValidator::new(Encoding::Phred33);
+        assert_eq!(validator.code(), "S009");
+    }
+
+    #[test]
+    fn test_name() {
+        let validator = QualityEncodingValidator::new(Encoding::Phred33);
+        assert_eq!(validator.name(), "QualityEncodingValidator");
+    }
+
+    #[test]
+    fn test_level() {
+        let validator = QualityEncodingValidator::new(Encoding::Phred33);
+        assert_eq!(validator.level(), ValidationLevel::High);
+    }
+
+    #[test]
+    fn test_finish() -> Result<(), Error> {
+        let mut validator = QualityEncodingValidator::new(Encoding::Phred33);
+        assert!(validator.finish().is_ok());
+
+        validator.validate(&Record::new("", "AGTC", "", "FFFF"))?;
+        assert!(validator.finish().is_ok());
+
+        validator.validate(&Record::new("", "AGTC", "", "!!!!"))?;
+        assert!(validator.finish().is_ok());
+
+        let mut validator = QualityEncodingValidator::new(Encoding::Phred64);
+        validator.validate(&Record::new("", "AGTC", "", "!!hh"))?;
+
+        let e = validator.finish().unwrap_err();
+        assert_eq!(
+            e.message,
+            "Quality score encoding does not match (expected phred64, got phred33)"
+        );
+
+        Ok(())
+    }
+}
@@ -1,11 +1,102 @@
+use std::{error, fmt, str::FromStr};
+
 use crate::{
     fastq::Record,
     validators::{Error, LineType, SingleReadValidator, ValidationLevel},
 };
 
+/// A sequencing platform profile, i.e., the range of its Phred+33 quality scores.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Platform {
+    /// Illumina 1.8+, Q0 to Q41 ("!" to "J").
+    Illumina18,
+    /// PacBio, Q0 to Q93 ("!" to "~"), e.g., for HiFi reads.
+    PacBio,
+    /// Oxford Nanopore Technologies, Q0 to Q90 ("!" to "{").
+    Ont,
+}
+
+impl Platform {
+    /// Returns the inclusive range of quality score characters.
+    pub fn score_range(&self) -> (u8, u8) {
+        match self {
+            Self::Illumina18 => (b'!', b'J'),
+            Self::PacBio => (b'!', b'~'),
+            Self::Ont => (b'!', b'{'),
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Illumina18 => f.write_str("illumina-1.8"),
+            Self::PacBio => f.write_str("pacbio"),
+            Self::Ont => f.write_str("ont"),
+        }
+    }
+}
+
+/// An error returned when a platform fails to parse.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParsePlatformError(String);
+
+impl error::Error for ParsePlatformError {}
+
+impl fmt::Display for ParsePlatformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid platform: expected illumina-1.8, pacbio, or ont, got '{}'",
+            self.0
+        )
+    }
+}
+
+impl FromStr for Platform {
+    type Err = ParsePlatformError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "illumina-1.8" => Ok(Self::Illumina18),
+            "pacbio" => Ok(Self::PacBio),
+            "ont" => Ok(Self::Ont),
+            _ => Err(ParsePlatformError(s.into())),
+        }
+    }
+}
+
 /// [S006] (medium) Validator to check if all the characters in the quality line are between "!" and
 /// "~" (ordinal values).
-pub struct QualityStringValidator;
+///
+/// With a platform, the characters are checked to be in the range of its quality scores instead.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{
+///     fastq::Record,
+///     validators::single::{Platform, QualityStringValidator, SingleReadValidator},
+/// };
+///
+/// let record = Record::new("@r0", "ACGT", "+", "!J]~");
+///
+/// let validator = QualityStringValidator::default();
+/// assert!(validator.validate(&record).is_ok());
+///
+/// let validator = QualityStringValidator::new(Some(Platform::Illumina18));
+/// assert!(validator.validate(&record).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct QualityStringValidator {
+    platform: Option<Platform>,
+}
+
+impl QualityStringValidator {
+    pub fn new(platform: Option<Platform>) -> Self {
+        Self { platform }
+    }
+}
 
 impl SingleReadValidator for QualityStringValidator {
     fn code(&self) -> &'static str {
@@ -21,16 +112,33 @@ impl SingleReadValidator for QualityStringValidator {
     }
 
     fn validate(&self, r: &Record) -> Result<(), Error> {
-        for (i, b) in r.quality_scores().iter().enumerate() {
+        for (i, &b) in r.quality_scores().iter().enumerate() {
             if !b.is_ascii_graphic() {
                 return Err(Error::new(
                     self.code(),
                     self.name(),
-                    format!("Invalid character '{}'", *b as char),
+                    format!("Invalid character '{}'", b as char),
                     LineType::Quality,
                     Some(i + 1),
                 ));
             }
+
+            if let Some(platform) = self.platform {
+                let (min, max) = platform.score_range();
+
+                if b < min || b > max {
+                    return Err(Error::new(
+                        self.code(),
+                        self.name(),
+                        format!(
+                            "Quality score out of range for {} (expected '{}' to '{}', got '{}')",
+                            platform, min as char, max as char, b as char
+                        ),
+                        LineType::Quality,
+                        Some(i + 1),
+                    ));
+                }
+            }
         }
 
         Ok(())
@@ -43,25 +151,25 @@ mod tests {
 
     #[test]
     fn test_code() {
-        let validator = QualityStringValidator;
+        let validator = QualityStringValidator::default();
         assert_eq!(validator.code(), "S006");
     }
 
     #[test]
     fn test_name() {
-        let validator = QualityStringValidator;
+        let validator = QualityStringValidator::default();
         assert_eq!(validator.name(), "QualityStringValidator");
     }
 
     #[test]
     fn test_level() {
-        let validator = QualityStringValidator;
+        let validator = QualityStringValidator::default();
         assert_eq!(validator.level(), ValidationLevel::Medium);
     }
 
     #[test]
     fn test_validate() {
-        let validator = QualityStringValidator;
+        let validator = QualityStringValidator::default();
 
         let quality = r##"!"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\]^_`abcdefghijklmnopqrstuvwxyz{|}~"##;
         let record = Record::new("", "", "", quality);
@@ -70,4 +178,39 @@ mod tests {
         let record = Record::new("", "", "", "ab早いcd");
         assert!(validator.validate(&record).is_err());
     }
+
+    #[test]
+    fn test_validate_with_platform() {
+        let validator = QualityStringValidator::new(Some(Platform::Illumina18));
+
+        let record = Record::new("", "", "", "!+5?J");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("", "", "", "!+5?K");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(
+            e.message,
+            "Quality score out of range for illumina-1.8 (expected '!' to 'J', got 'K')"
+        );
+        assert_eq!(e.col_no, Some(5));
+
+        let validator = QualityStringValidator::new(Some(Platform::PacBio));
+        let record = Record::new("", "", "", "!J~");
+        assert!(validator.validate(&record).is_ok());
+
+        let validator = QualityStringValidator::new(Some(Platform::Ont));
+        let record = Record::new("", "", "", "!J~");
+        assert!(validator.validate(&record).is_err());
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("illumina-1.8".parse(), Ok(Platform::Illumina18));
+        assert_eq!("pacbio".parse(), Ok(Platform::PacBio));
+        assert_eq!("ont".parse(), Ok(Platform::Ont));
+        assert_eq!(
+            "illumina".parse::<Platform>(),
+            Err(ParsePlatformError(String::from("illumina")))
+        );
+    }
 }
@@ -0,0 +1,107 @@
+use crate::{
+    fastq::Record,
+    validators::{Error, LineType, SingleReadValidatorMut, ValidationLevel},
+};
+
+/// [S008] (high) Validator to check if all reads have the same length.
+///
+/// Reads are compared to an expected length or, if not given, the length of the first record.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{fastq::Record, validators::single::{ReadLengthValidator, SingleReadValidatorMut}};
+///
+/// let mut validator = ReadLengthValidator::new(None);
+///
+/// assert!(validator.validate(&Record::new("@r0", "ACGT", "+", "IIII")).is_ok());
+/// assert!(validator.validate(&Record::new("@r1", "TGCA", "+", "IIII")).is_ok());
+/// assert!(validator.validate(&Record::new("@r2", "ACG", "+", "III")).is_err());
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReadLengthValidator {
+    expected_len: Option<usize>,
+}
+
+impl ReadLengthValidator {
+    /// Creates a read length validator.
+    ///
+    /// If `expected_len` is `None`, the length of the first record is expected.
+    pub fn new(expected_len: Option<usize>) -> Self {
+        Self { expected_len }
+    }
+}
+
+impl SingleReadValidatorMut for ReadLengthValidator {
+    fn code(&self) -> &'static str {
+        "S008"
+    }
+
+    fn name(&self) -> &'static str {
+        "ReadLengthValidator"
+    }
+
+    fn level(&self) -> ValidationLevel {
+        ValidationLevel::High
+    }
+
+    fn validate(&mut self, r: &Record) -> Result<(), Error> {
+        let actual_len = r.sequence().len();
+        let expected_len = *self.expected_len.get_or_insert(actual_len);
+
+        if actual_len == expected_len {
+            Ok(())
+        } else {
+            let message = format!(
+                "Read length does not match (expected {}, got {})",
+                expected_len, actual_len
+            );
+
+            Err(Error::new(
+                self.code(),
+                self.name(),
+                message,
+                LineType::Sequence,
+                None,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code() {
+        let validator = ReadLengthValidator::default();
+        assert_eq!(validator.code(), "S008");
+    }
+
+    #[test]
+    fn test_name() {
+        let validator = ReadLengthValidator::default();
+        assert_eq!(validator.name(), "ReadLengthValidator");
+    }
+
+    #[test]
+    fn test_level() {
+        let validator = ReadLengthValidator::default();
+        assert_eq!(validator.level(), ValidationLevel::High);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut validator = ReadLengthValidator::new(Some(3));
+
+        let record = Record::new("", "AGT", "", "ABC");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("", "AGTC", "", "ABCD");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(e.message, "Read length does not match (expected 3, got 4)");
+
+        let record = Record::new("", "", "", "");
+        assert!(validator.validate(&record).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{
+    fastq::Record,
+    validators::{Error, LineType, SingleReadValidator, ValidationLevel},
+};
+
+/// [S013] (high) Validator to check if the length of the sequence line is within given bounds.
+///
+/// Both bounds are inclusive, and a missing bound is unchecked.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{
+///     fastq::Record,
+///     validators::single::{SequenceLengthValidator, SingleReadValidator},
+/// };
+///
+/// let validator = SequenceLengthValidator::new(Some(1), Some(4));
+///
+/// assert!(validator.validate(&Record::new("@r0", "ACGT", "+", "IIII")).is_ok());
+/// assert!(validator.validate(&Record::new("@r1", "", "+", "")).is_err());
+/// assert!(validator.validate(&Record::new("@r2", "ACGTA", "+", "IIIII")).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SequenceLengthValidator {
+    min_len: Option<usize>,
+    max_len: Option<usize>,
+}
+
+impl SequenceLengthValidator {
+    pub fn new(min_len: Option<usize>, max_len: Option<usize>) -> Self {
+        Self { min_len, max_len }
+    }
+}
+
+impl SingleReadValidator for SequenceLengthValidator {
+    fn code(&self) -> &'static str {
+        "S013"
+    }
+
+    fn name(&self) -> &'static str {
+        "SequenceLengthValidator"
+    }
+
+    fn level(&self) -> ValidationLevel {
+        ValidationLevel::High
+    }
+
+    fn validate(&self, r: &Record) -> Result<(), Error> {
+        let len = r.sequence().len();
+
+        let message = match (self.min_len, self.max_len) {
+            (Some(min_len), _) if len < min_len => format!(
+                "Sequence is too short (expected >= {}, got {})",
+                min_len, len
+            ),
+            (_, Some(max_len)) if len > max_len => format!(
+                "Sequence is too long (expected <= {}, got {})",
+                max_len, len
+            ),
+            _ => return Ok(()),
+        };
+
+        Err(Error::new(
+            self.code(),
+            self.name(),
+            message,
+            LineType::Sequence,
+            None,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code() {
+        let validator = SequenceLengthValidator::default();
+        assert_eq!(validator.code(), "S013");
+    }
+
+    #[test]
+    fn test_name() {
+        let validator = SequenceLengthValidator::default();
+        assert_eq!(validator.name(), "SequenceLengthValidator");
+    }
+
+    #[test]
+    fn test_level() {
+        let validator = SequenceLengthValidator::default();
+        assert_eq!(validator.level(), ValidationLevel::High);
+    }
+
+    #[test]
+    fn test_validate() {
+        let validator = SequenceLengthValidator::new(Some(1), Some(4));
+
+        let record = Record::new("", "A", "", "I");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("", "ACGT", "", "IIII");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("", "", "", "");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(e.message, "Sequence is too short (expected >= 1, got 0)");
+
+        let record = Record::new("", "ACGTA", "", "IIIII");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(e.message, "Sequence is too long (expected <= 4, got 5)");
+
+        let validator = SequenceLengthValidator::new(None, Some(4));
+        let record = Record::new("", "", "", "");
+        assert!(validator.validate(&record).is_ok());
+    }
+}
@@ -0,0 +1,93 @@
+use std::io;
+
+use crate::validators::{Error, LineType, ValidationLevel};
+
+/// [S015] (low) Validator to check if the input does not end mid-record.
+///
+/// Unlike other validators, this checks read errors rather than records: an input is truncated
+/// when reading a record fails with [`io::ErrorKind::UnexpectedEof`], e.g., when the last record
+/// is missing lines (see [`crate::fastq::Reader::read_complete_record`]) or a gzip stream ends
+/// early.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+///
+/// use fq::validators::single::TruncatedFileValidator;
+///
+/// let validator = TruncatedFileValidator;
+///
+/// let e = io::Error::from(io::ErrorKind::UnexpectedEof);
+/// assert!(validator.validate(&e).is_err());
+///
+/// let e = io::Error::from(io::ErrorKind::InvalidData);
+/// assert!(validator.validate(&e).is_ok());
+/// ```
+pub struct TruncatedFileValidator;
+
+impl TruncatedFileValidator {
+    pub fn code(&self) -> &'static str {
+        "S015"
+    }
+
+    pub fn name(&self) -> &'static str {
+        "TruncatedFileValidator"
+    }
+
+    pub fn level(&self) -> ValidationLevel {
+        ValidationLevel::Low
+    }
+
+    /// Checks whether a read error is from a truncated input.
+    ///
+    /// Other read errors are not validation errors and return `Ok`.
+    pub fn validate(&self, e: &io::Error) -> Result<(), Error> {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            Err(Error::new(
+                self.code(),
+                self.name(),
+                format!("Truncated file: {}", e),
+                LineType::Name,
+                None,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code() {
+        let validator = TruncatedFileValidator;
+        assert_eq!(validator.code(), "S015");
+    }
+
+    #[test]
+    fn test_name() {
+        let validator = TruncatedFileValidator;
+        assert_eq!(validator.name(), "TruncatedFileValidator");
+    }
+
+    #[test]
+    fn test_level() {
+        let validator = TruncatedFileValidator;
+        assert_eq!(validator.level(), ValidationLevel::Low);
+    }
+
+    #[test]
+    fn test_validate() {
+        let validator = TruncatedFileValidator;
+
+        let e = io::Error::new(io::ErrorKind::UnexpectedEof, "incomplete record");
+        let error = validator.validate(&e).unwrap_err();
+        assert_eq!(error.message, "Truncated file: incomplete record");
+
+        let e = io::Error::from(io::ErrorKind::InvalidData);
+        assert!(validator.validate(&e).is_ok());
+    }
+}
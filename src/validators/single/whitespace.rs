@@ -0,0 +1,139 @@
+use crate::{
+    fastq::Record,
+    validators::{Error, LineType, SingleReadValidator, ValidationLevel},
+};
+
+const CARRIAGE_RETURN: u8 = b'\r';
+
+/// [S016] (medium) Validator to check if no line has a carriage return or trailing whitespace.
+///
+/// These are typical of files edited or produced on Windows. Carriage returns are only kept when
+/// the reader is set to not strip them (see
+/// [`crate::fastq::Reader::set_strip_carriage_returns`]), and trailing whitespace in the name
+/// line is only kept before the record is reset.
+///
+/// # Examples
+///
+/// ```
+/// use fq::{fastq::Record, validators::single::{SingleReadValidator, WhitespaceValidator}};
+///
+/// let validator = WhitespaceValidator;
+///
+/// assert!(validator.validate(&Record::new("@r0", "ACGT", "+", "IIII")).is_ok());
+/// assert!(validator.validate(&Record::new("@r1\r", "ACGT\r", "+\r", "IIII\r")).is_err());
+/// assert!(validator.validate(&Record::new("@r2", "ACGT ", "+", "IIII")).is_err());
+/// ```
+pub struct WhitespaceValidator;
+
+impl WhitespaceValidator {
+    fn validate_line(&self, line: &[u8], line_type: LineType) -> Result<(), Error> {
+        if let Some(i) = line.iter().position(|&b| b == CARRIAGE_RETURN) {
+            let message = if i == line.len() - 1 {
+                "Line ends with a carriage return (CRLF line ending)"
+            } else {
+                "Line contains a carriage return"
+            };
+
+            return Err(self.build_error(message, line_type, i));
+        }
+
+        let trimmed_len = line
+            .iter()
+            .rposition(|&b| b != b' ' && b != b'\t')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+
+        if trimmed_len < line.len() {
+            return Err(self.build_error("Line has trailing whitespace", line_type, trimmed_len));
+        }
+
+        Ok(())
+    }
+
+    fn build_error(&self, message: &str, line_type: LineType, i: usize) -> Error {
+        Error::new(
+            self.code(),
+            self.name(),
+            String::from(message),
+            line_type,
+            Some(i + 1),
+        )
+    }
+}
+
+impl SingleReadValidator for WhitespaceValidator {
+    fn code(&self) -> &'static str {
+        "S016"
+    }
+
+    fn name(&self) -> &'static str {
+        "WhitespaceValidator"
+    }
+
+    fn level(&self) -> ValidationLevel {
+        ValidationLevel::Medium
+    }
+
+    fn validate(&self, r: &Record) -> Result<(), Error> {
+        self.validate_line(r.name(), LineType::Name)?;
+        self.validate_line(r.sequence(), LineType::Sequence)?;
+        self.validate_line(r.plus_line(), LineType::PlusLine)?;
+        self.validate_line(r.quality_scores(), LineType::Quality)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code() {
+        let validator = WhitespaceValidator;
+        assert_eq!(validator.code(), "S016");
+    }
+
+    #[test]
+    fn test_name() {
+        let validator = WhitespaceValidator;
+        assert_eq!(validator.name(), "WhitespaceValidator");
+    }
+
+    #[test]
+    fn test_level() {
+        let validator = WhitespaceValidator;
+        assert_eq!(validator.level(), ValidationLevel::Medium);
+    }
+
+    #[test]
+    fn test_validate() {
+        let validator = WhitespaceValidator;
+
+        let record = Record::new("@fqlib 1:N:0:1", "AGCT", "+", "abcd");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("", "", "", "");
+        assert!(validator.validate(&record).is_ok());
+
+        let record = Record::new("@fqlib\r", "AGCT\r", "+\r", "abcd\r");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(
+            e.message,
+            "Line ends with a carriage return (CRLF line ending)"
+        );
+        assert!(matches!(e.line_type, LineType::Name));
+        assert_eq!(e.col_no, Some(7));
+
+        let record = Record::new("@fqlib", "AG\rCT", "+", "abcd");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(e.message, "Line contains a carriage return");
+        assert!(matches!(e.line_type, LineType::Sequence));
+        assert_eq!(e.col_no, Some(3));
+
+        let record = Record::new("@fqlib", "AGCT", "+", "abcd \t");
+        let e = validator.validate(&record).unwrap_err();
+        assert_eq!(e.message, "Line has trailing whitespace");
+        assert!(matches!(e.line_type, LineType::Quality));
+        assert_eq!(e.col_no, Some(5));
+    }
+}
@@ -18,6 +18,16 @@ impl fmt::Display for ParseError {
     }
 }
 
+impl fmt::Display for ValidationLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Low => f.pad("low"),
+            Self::Medium => f.pad("medium"),
+            Self::High => f.pad("high"),
+        }
+    }
+}
+
 impl FromStr for ValidationLevel {
     type Err = ParseError;
 
@@ -35,6 +45,13 @@ impl FromStr for ValidationLevel {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_fmt() {
+        assert_eq!(ValidationLevel::Low.to_string(), "low");
+        assert_eq!(ValidationLevel::Medium.to_string(), "medium");
+        assert_eq!(ValidationLevel::High.to_string(), "high");
+    }
+
     #[test]
     fn test_from_str() {
         assert_eq!("low".parse(), Ok(ValidationLevel::Low));
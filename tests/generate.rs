@@ -0,0 +1,29 @@
+use std::{io, process::Command};
+
+#[test]
+fn test_generate_to_stdout() -> io::Result<()> {
+    let output = Command::new(env!("CARGO_BIN_EXE_fq"))
+        .args(["generate", "--seed", "0", "--record-count", "2"])
+        .args(["--read-length", "4", "-"])
+        .output()?;
+
+    assert!(output.status.success());
+
+    // Logs go to stderr, leaving only records on stdout.
+    let stdout = String::from_utf8(output.stdout)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let lines: Vec<_> = stdout.lines().collect();
+
+    assert_eq!(lines.len(), 8);
+
+    for record in lines.chunks(4) {
+        assert!(record[0].starts_with('@'));
+        assert_eq!(record[1].len(), 4);
+        assert_eq!(record[2], "+");
+        assert_eq!(record[3].len(), 4);
+    }
+
+    assert!(!output.stderr.is_empty());
+
+    Ok(())
+}
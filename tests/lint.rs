@@ -0,0 +1,50 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+// Runs lint and returns its JSON summary.
+fn lint_summary(max_records: &str, srcs: &[&PathBuf], summary_dst: &Path) -> io::Result<String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_fq"))
+        .args(["lint", "--max-records", max_records, "--summary-path"])
+        .arg(summary_dst)
+        .args(srcs)
+        .output()?;
+
+    assert!(output.status.success());
+
+    fs::read_to_string(summary_dst)
+}
+
+#[test]
+fn test_lint_with_max_records() -> io::Result<()> {
+    let dir = env::temp_dir();
+
+    let r1_src = dir.join("fq-lint-test_lint_with_max_records-r1.fastq");
+    fs::write(&r1_src, "@r0/1\nACGT\n+\nFQLB\n@r1/1\nACGT\n+\nFQLB\n")?;
+
+    let r2_src = dir.join("fq-lint-test_lint_with_max_records-r2.fastq");
+    fs::write(&r2_src, "@r0/2\nTGCA\n+\nBLQF\n@r1/2\nTGCA\n+\nBLQF\n")?;
+
+    let summary_dst = dir.join("fq-lint-test_lint_with_max_records-summary.json");
+
+    // Validation is complete when the input has exactly the maximum number of records.
+    let summary = lint_summary("2", &[&r1_src], &summary_dst)?;
+    assert!(summary.contains(r#""partial":false"#));
+
+    let summary = lint_summary("2", &[&r1_src, &r2_src], &summary_dst)?;
+    assert!(summary.contains(r#""partial":false"#));
+
+    let summary = lint_summary("1", &[&r1_src], &summary_dst)?;
+    assert!(summary.contains(r#""partial":true"#));
+
+    let summary = lint_summary("1", &[&r1_src, &r2_src], &summary_dst)?;
+    assert!(summary.contains(r#""partial":true"#));
+
+    fs::remove_file(r1_src)?;
+    fs::remove_file(r2_src)?;
+    fs::remove_file(summary_dst)?;
+
+    Ok(())
+}